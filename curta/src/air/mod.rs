@@ -7,6 +7,7 @@ pub mod parser;
 pub mod fibonacci;
 
 use parser::AirParser;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RoundDatum {
@@ -18,6 +19,21 @@ pub struct RoundDatum {
     pub num_challenges: usize,
 }
 
+/// The challenges drawn by the verifier before the first round, right after observing the first
+/// `num_public_inputs` public inputs.
+///
+/// The challenges take the indices `challenge_range` among the challenges of the air, the other
+/// challenges being drawn after the rounds as given by the round data.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct PublicChallengeDatum {
+    /// The number of public inputs observed before drawing the challenges
+    pub num_public_inputs: usize,
+    /// The range of indices of the challenges
+    pub challenge_range: (usize, usize),
+}
+
 pub trait AirConstraint<AP: AirParser> {
     /// Evaluation of the vanishing polynomials.
     fn eval(&self, parser: &mut AP);
@@ -38,6 +54,11 @@ pub trait RAirData {
 
     fn num_public_inputs(&self) -> usize;
 
+    /// The challenges drawn before the first round, none by default
+    fn public_challenge_data(&self) -> PublicChallengeDatum {
+        PublicChallengeDatum::default()
+    }
+
     fn num_rounds(&self) -> usize {
         self.round_data().len()
     }
@@ -75,3 +96,21 @@ impl RoundDatum {
         }
     }
 }
+
+impl PublicChallengeDatum {
+    pub fn num_challenges(&self) -> usize {
+        self.challenge_range.1 - self.challenge_range.0
+    }
+
+    /// Inserts the challenges drawn before the first round at their indices among the challenges
+    /// drawn after it.
+    pub fn insert<T: Clone>(&self, public_challenges: &[T], round_challenges: &[T]) -> Vec<T> {
+        let (start, _) = self.challenge_range;
+        round_challenges[..start]
+            .iter()
+            .chain(public_challenges)
+            .chain(&round_challenges[start..])
+            .cloned()
+            .collect()
+    }
+}
@@ -71,17 +71,52 @@ pub trait AirParser: Sized {
         let c = self.sub(a, b);
         self.constraint_transition(c);
     }
+
+    /// Marks the start of the evaluation of the constraint with the given index.
+    ///
+    /// This is a no-op by default and is only used to attribute constraint failures when
+    /// debugging a trace.
+    #[inline]
+    fn begin_constraint(&mut self, _index: usize) {}
 }
 
 #[derive(Debug)]
 pub struct MulParser<'a, AP: AirParser> {
     pub parser: &'a mut AP,
     pub multiplier: AP::Var,
+    /// Multipliers for the transition, first row, and last row constraints.
+    ///
+    /// If set, first and last row constraints are multiplied by their multiplier and enforced on
+    /// every row, so that they can refer to rows other than the first and last rows of the trace.
+    pub boundary: Option<BoundaryMultipliers<AP::Var>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundaryMultipliers<V> {
+    pub transition: V,
+    pub first_row: V,
+    pub last_row: V,
 }
 
 impl<'a, AP: AirParser> MulParser<'a, AP> {
     pub fn new(parser: &'a mut AP, multiplier: AP::Var) -> Self {
-        Self { parser, multiplier }
+        Self {
+            parser,
+            multiplier,
+            boundary: None,
+        }
+    }
+
+    pub fn with_boundary(
+        parser: &'a mut AP,
+        multiplier: AP::Var,
+        boundary: BoundaryMultipliers<AP::Var>,
+    ) -> Self {
+        Self {
+            parser,
+            multiplier,
+            boundary: Some(boundary),
+        }
     }
 }
 
@@ -115,18 +150,37 @@ impl<'a, AP: AirParser> AirParser for MulParser<'a, AP> {
     }
 
     fn constraint_transition(&mut self, constraint: Self::Var) {
-        let constr = self.parser.mul(constraint, self.multiplier);
+        let multiplier = self
+            .boundary
+            .map_or(self.multiplier, |boundary| boundary.transition);
+        let constr = self.parser.mul(constraint, multiplier);
         self.parser.constraint_transition(constr);
     }
 
     fn constraint_first_row(&mut self, constraint: Self::Var) {
-        let constr = self.parser.mul(constraint, self.multiplier);
-        self.parser.constraint_first_row(constr);
+        match self.boundary {
+            Some(boundary) => {
+                let constr = self.parser.mul(constraint, boundary.first_row);
+                self.parser.constraint(constr);
+            }
+            None => {
+                let constr = self.parser.mul(constraint, self.multiplier);
+                self.parser.constraint_first_row(constr);
+            }
+        }
     }
 
     fn constraint_last_row(&mut self, constraint: Self::Var) {
-        let constr = self.parser.mul(constraint, self.multiplier);
-        self.parser.constraint_last_row(constr);
+        match self.boundary {
+            Some(boundary) => {
+                let constr = self.parser.mul(constraint, boundary.last_row);
+                self.parser.constraint(constr);
+            }
+            None => {
+                let constr = self.parser.mul(constraint, self.multiplier);
+                self.parser.constraint_last_row(constr);
+            }
+        }
     }
 
     fn constant(&mut self, value: Self::Field) -> Self::Var {
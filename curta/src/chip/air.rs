@@ -1,12 +1,12 @@
 use super::constraint::Constraint;
 use super::{AirParameters, Chip};
 use crate::air::parser::AirParser;
-use crate::air::{AirConstraint, RAir, RAirData, RoundDatum};
+use crate::air::{AirConstraint, PublicChallengeDatum, RAir, RAirData, RoundDatum};
 
 impl<L: AirParameters> RAirData for Chip<L> {
     /// The maximal constraint degree
     fn constraint_degree(&self) -> usize {
-        3
+        self.constraint_degree
     }
 
     /// Columns for each round
@@ -14,16 +14,18 @@ impl<L: AirParameters> RAirData for Chip<L> {
         let total = L::num_columns();
         let execution_trace_length = self.execution_trace_length;
         let extended_trace_length = total - execution_trace_length;
+        // The public challenges are drawn before the first round.
+        let num_challenges = self.num_challenges - self.public_challenge_data.num_challenges();
 
         if extended_trace_length == 0 {
             return vec![RoundDatum::new(
                 total,
                 (0, self.num_global_values),
-                self.num_challenges,
+                num_challenges,
             )];
         }
         vec![
-            RoundDatum::new(execution_trace_length, (0, 0), self.num_challenges),
+            RoundDatum::new(execution_trace_length, (0, 0), num_challenges),
             RoundDatum::new(extended_trace_length, (0, self.num_global_values), 0),
         ]
     }
@@ -32,6 +34,10 @@ impl<L: AirParameters> RAirData for Chip<L> {
         self.num_public_values
     }
 
+    fn public_challenge_data(&self) -> PublicChallengeDatum {
+        self.public_challenge_data
+    }
+
     fn width(&self) -> usize {
         L::NUM_ARITHMETIC_COLUMNS + L::NUM_FREE_COLUMNS + L::EXTENDED_COLUMNS
     }
@@ -42,13 +48,15 @@ where
     Constraint<L>: AirConstraint<AP>,
 {
     fn eval(&self, parser: &mut AP) {
-        for constraint in self.constraints.iter() {
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            parser.begin_constraint(i);
             constraint.eval(parser);
         }
     }
 
     fn eval_global(&self, parser: &mut AP) {
-        for constraint in self.global_constraints.iter() {
+        for (i, constraint) in self.global_constraints.iter().enumerate() {
+            parser.begin_constraint(i);
             constraint.eval(parser);
         }
     }
@@ -25,12 +25,16 @@ pub struct SelectInstruction {
 }
 
 impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `bit ? a : b`.
+    ///
+    /// The selection is constrained element by element, so it applies to registers of any type,
+    /// such as the limbs of a `FieldRegister<P>` or the coordinates of a point.
     pub fn select<T: Register>(&mut self, bit: &BitRegister, a: &T, b: &T) -> T {
         let is_trace = a.is_trace() || b.is_trace() || bit.is_trace();
         let result = if is_trace {
             self.alloc::<T>()
         } else {
-            self.alloc_public::<T>()
+            self.alloc_public_unchecked::<T>()
         };
         let instr = SelectInstruction {
             bit: *bit,
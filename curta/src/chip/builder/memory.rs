@@ -1,10 +1,15 @@
 use super::{AirBuilder, AirParameters};
+use crate::air::PublicChallengeDatum;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
 use crate::chip::instruction::set::AirInstruction;
 use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
 use crate::chip::register::cell::CellType;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::register::memory::MemorySlice;
 use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::math::prelude::*;
 
 impl<L: AirParameters> AirBuilder<L> {
     /// Allocates `size` cells/columns worth of memory and returns it as a `MemorySlice`.
@@ -44,7 +49,7 @@ impl<L: AirParameters> AirBuilder<L> {
     /// and returns it.
     pub fn alloc<T: Register>(&mut self) -> T {
         let register = match T::CELL {
-            CellType::Element => self.get_local_memory(T::size_of()),
+            CellType::Element | CellType::Byte => self.get_local_memory(T::size_of()),
             CellType::U16 => self.get_local_u16_memory(T::size_of()),
             CellType::Bit => {
                 let reg = self.get_local_memory(T::size_of());
@@ -60,7 +65,7 @@ impl<L: AirParameters> AirBuilder<L> {
     /// and returns it.
     pub(crate) fn alloc_extended<T: Register>(&mut self) -> T {
         let register = match T::CELL {
-            CellType::Element => self.get_extended_memory(T::size_of()),
+            CellType::Element | CellType::Byte => self.get_extended_memory(T::size_of()),
             CellType::U16 => unreachable!("Extended U16 not implemented"),
             CellType::Bit => {
                 let reg = self.get_extended_memory(T::size_of());
@@ -75,7 +80,7 @@ impl<L: AirParameters> AirBuilder<L> {
     pub fn alloc_array<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
         let size_of = T::size_of() * length;
         let register = match T::CELL {
-            CellType::Element => self.get_local_memory(size_of),
+            CellType::Element | CellType::Byte => self.get_local_memory(size_of),
             CellType::U16 => self.get_local_u16_memory(size_of),
             CellType::Bit => {
                 let reg = self.get_local_memory(size_of);
@@ -90,7 +95,7 @@ impl<L: AirParameters> AirBuilder<L> {
     pub fn alloc_array_extended<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
         let size_of = T::size_of() * length;
         let register = match T::CELL {
-            CellType::Element => self.get_extended_memory(size_of),
+            CellType::Element | CellType::Byte => self.get_extended_memory(size_of),
             CellType::U16 => unreachable!("Extended U16 not implemented"),
             CellType::Bit => {
                 let reg = self.get_extended_memory(size_of);
@@ -113,11 +118,32 @@ impl<L: AirParameters> AirBuilder<L> {
         ArrayRegister::<T>::from_register_unsafe(register)
     }
 
+    /// Allocates an array of challenges drawn by the verifier right after observing the public
+    /// inputs allocated so far, before the execution trace is committed.
+    ///
+    /// The challenges can be used in the execution trace, so the prover must know their values,
+    /// given by `Starky::public_challenges`, when writing it. Public inputs depending on the
+    /// challenges must be allocated after them. Only one such array can be allocated in an AIR.
+    pub fn alloc_array_public_challenge<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
+        assert!(
+            self.public_challenge_data.is_none(),
+            "Public challenges are already allocated"
+        );
+        let size_of = T::size_of() * length;
+        let num_public_inputs = self.shared_memory.public_index();
+        let register = self.get_challenge_memory(size_of);
+        self.public_challenge_data = Some(PublicChallengeDatum {
+            num_public_inputs,
+            challenge_range: (register.index(), register.index() + size_of),
+        });
+        ArrayRegister::<T>::from_register_unsafe(register)
+    }
+
     /// Allocates a new local register according to type `T` which implements the Register trait
     /// and returns it.
     pub fn alloc_global<T: Register>(&mut self) -> T {
         let register = match T::CELL {
-            CellType::Element => self.get_global_memory(T::size_of()),
+            CellType::Element | CellType::Byte => self.get_global_memory(T::size_of()),
             CellType::U16 => unreachable!("Global U16 not supported"),
             CellType::Bit => self.get_global_memory(T::size_of()),
         };
@@ -127,46 +153,102 @@ impl<L: AirParameters> AirBuilder<L> {
     pub fn alloc_array_global<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
         let size_of = T::size_of() * length;
         let register = match T::CELL {
-            CellType::Element => self.get_global_memory(size_of),
+            CellType::Element | CellType::Byte => self.get_global_memory(size_of),
             CellType::U16 => unreachable!("Global U16 not supported"),
             CellType::Bit => self.get_global_memory(size_of),
         };
         ArrayRegister::<T>::from_register_unsafe(register)
     }
 
-    /// Allocates a new local register according to type `T` which implements the Register trait
+    /// Allocates a new public register according to type `T` which implements the Register trait
     /// and returns it.
+    ///
+    /// The value of the register is validated according to its cell type: bits are constrained
+    /// to be boolean, bytes are range checked using the byte lookup table and `u16` limbs are
+    /// range checked using the arithmetic lookup table.
+    ///
+    /// Before this validation, public registers of any type were left unconstrained. Callers that
+    /// constrain the value themselves, or whose builder has no byte lookup, should allocate the
+    /// register with `alloc_public_unchecked` instead, as `build` panics on public bytes that are
+    /// not range checked.
     pub fn alloc_public<T: Register>(&mut self) -> T {
-        let register = match T::CELL {
-            CellType::Element => self.get_public_memory(T::size_of()),
-            CellType::U16 => {
-                let register = self.get_public_memory(T::size_of());
-                let elements = ArrayRegister::<ElementRegister>::from_register_unsafe(register);
-                for element in elements {
-                    self.global_arithmetic.push(element);
-                }
-                register
-            }
-            CellType::Bit => self.get_public_memory(T::size_of()),
-        };
+        let register = self.get_public_memory(T::size_of());
+        self.constrain_public_memory(T::CELL, &register);
         T::from_register(register)
     }
 
     pub fn alloc_array_public<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
         let size_of = T::size_of() * length;
-        let register = match T::CELL {
-            CellType::Element => self.get_public_memory(size_of),
+        let register = self.get_public_memory(size_of);
+        self.constrain_public_memory(T::CELL, &register);
+        ArrayRegister::<T>::from_register_unsafe(register)
+    }
+
+    /// Allocates a new public register without validating its value.
+    ///
+    /// This is meant for registers whose values are already constrained by the caller, such as
+    /// the result of an instruction or a constant.
+    pub fn alloc_public_unchecked<T: Register>(&mut self) -> T {
+        let register = self.get_public_memory(T::size_of());
+        T::from_register(register)
+    }
+
+    pub fn alloc_array_public_unchecked<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
+        let size_of = T::size_of() * length;
+        let register = self.get_public_memory(size_of);
+        ArrayRegister::<T>::from_register_unsafe(register)
+    }
+
+    /// Allocates a public element register whose value is constrained to be in `[0, max]`.
+    ///
+    /// Both the value and the difference `max - value` are decomposed into `n` bits, where `n` is
+    /// the bit length of `max`. This requires the field order to be at least `2^(n + 1)`.
+    pub fn alloc_public_bounded(&mut self, max: u64) -> ElementRegister {
+        let num_bits = (u64::BITS - max.leading_zeros()) as usize;
+        assert!(
+            num_bits < 64 && L::Field::order() >> num_bits >= 2,
+            "bound {} is too large for the field",
+            max
+        );
+        let value = self.alloc_public_unchecked::<ElementRegister>();
+
+        let value_bits = self.alloc_array_public::<BitRegister>(num_bits);
+        self.register_global_air_instruction_internal(AirInstruction::bit_decomposition(
+            value.expr(),
+            value_bits,
+        ));
+
+        let slack_bits = self.alloc_array_public::<BitRegister>(num_bits);
+        let slack =
+            ArithmeticExpression::from_constant(L::Field::from_canonical_u64(max)) - value.expr();
+        self.register_global_air_instruction_internal(AirInstruction::bit_decomposition(
+            slack, slack_bits,
+        ));
+
+        value
+    }
+
+    /// Adds the validation constraints of public memory with the given cell type.
+    fn constrain_public_memory(&mut self, cell: CellType, register: &MemorySlice) {
+        match cell {
+            CellType::Element => {}
             CellType::U16 => {
-                let register = self.get_public_memory(size_of);
-                let elements = ArrayRegister::<ElementRegister>::from_register_unsafe(register);
+                let elements = ArrayRegister::<ElementRegister>::from_register_unsafe(*register);
                 for element in elements {
                     self.global_arithmetic.push(element);
                 }
-                register
             }
-            CellType::Bit => self.get_public_memory(size_of),
-        };
-        ArrayRegister::<T>::from_register_unsafe(register)
+            CellType::Bit => {
+                let constraint = AirInstruction::bits(register);
+                self.register_global_air_instruction_internal(constraint);
+            }
+            CellType::Byte => {
+                let bytes = ArrayRegister::<ByteRegister>::from_register_unsafe(*register);
+                for byte in bytes {
+                    self.public_bytes.push(byte);
+                }
+            }
+        }
     }
 
     pub fn is_local<T: RegisterSerializable>(&self, register: &T) -> bool {
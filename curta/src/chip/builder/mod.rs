@@ -1,13 +1,16 @@
 pub mod arithmetic;
 pub mod memory;
 pub mod range_check;
+pub mod segment;
 pub mod shared_memory;
 
 use core::cmp::Ordering;
 
+use self::segment::{OpenSegment, Segment};
 use self::shared_memory::SharedMemory;
 use super::arithmetic::expression::ArithmeticExpression;
 use super::constraint::Constraint;
+use super::debug::ConstraintLabels;
 use super::instruction::clock::ClockInstruction;
 use super::instruction::set::AirInstruction;
 use super::memory::pointer::accumulate::PointerAccumulator;
@@ -21,7 +24,9 @@ use super::table::bus::global::Bus;
 use super::table::lookup::table::LookupTable;
 use super::table::lookup::values::LookupValues;
 use super::trace::data::AirTraceData;
+use super::uint::bytes::register::ByteRegister;
 use super::{AirParameters, Chip};
+use crate::air::PublicChallengeDatum;
 use crate::chip::register::RegisterSerializable;
 
 #[derive(Debug, Clone)]
@@ -33,6 +38,7 @@ pub struct AirBuilder<L: AirParameters> {
     pub(crate) internal_range_check: bool,
     pub(crate) shared_memory: SharedMemory,
     pub(crate) global_arithmetic: Vec<ElementRegister>,
+    pub(crate) public_bytes: Vec<ByteRegister>,
     pub(crate) instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
     pub(crate) global_instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
     pub(crate) constraints: Vec<Constraint<L>>,
@@ -44,6 +50,11 @@ pub struct AirBuilder<L: AirParameters> {
     pub(crate) buses: Vec<Bus<CubicRegister, L::CubicParams>>,
     pub(crate) lookup_values: Vec<LookupValues<L::Field, L::CubicParams>>,
     pub(crate) lookup_tables: Vec<LookupTable<L::Field, L::CubicParams>>,
+    pub(crate) labels: ConstraintLabels,
+    pub(crate) public_challenge_data: Option<PublicChallengeDatum>,
+    clk: Option<ElementRegister>,
+    segments: Vec<Segment>,
+    open_segment: Option<OpenSegment>,
     range_data: Option<(
         LookupTable<L::Field, L::CubicParams>,
         LookupValues<L::Field, L::CubicParams>,
@@ -65,6 +76,7 @@ impl<L: AirParameters> AirBuilder<L> {
             local_arithmetic_index: 0,
             extended_index: L::NUM_ARITHMETIC_COLUMNS + L::NUM_FREE_COLUMNS,
             global_arithmetic: Vec::new(),
+            public_bytes: Vec::new(),
             shared_memory,
             internal_range_check: true,
             instructions: Vec::new(),
@@ -78,12 +90,17 @@ impl<L: AirParameters> AirBuilder<L> {
             buses: Vec::new(),
             lookup_values: Vec::new(),
             lookup_tables: Vec::new(),
+            labels: ConstraintLabels::default(),
+            public_challenge_data: None,
+            clk: None,
+            segments: Vec::new(),
+            open_segment: None,
             range_data: None,
         }
     }
 
     pub fn constant<T: Register>(&mut self, value: &T::Value<L::Field>) -> T {
-        let register = self.alloc_public::<T>();
+        let register = self.alloc_public_unchecked::<T>();
         self.set_to_expression_public(
             &register,
             ArithmeticExpression::from_constant_vec(T::align(value).to_vec()),
@@ -95,7 +112,7 @@ impl<L: AirParameters> AirBuilder<L> {
         &mut self,
         values: &[T::Value<L::Field>],
     ) -> ArrayRegister<T> {
-        let array = self.alloc_array_public::<T>(values.len());
+        let array = self.alloc_array_public_unchecked::<T>(values.len());
 
         for (register, value) in array.iter().zip(values.iter()) {
             self.set_to_expression_public(
@@ -190,15 +207,36 @@ impl<L: AirParameters> AirBuilder<L> {
         self.global_constraints.push(constraint.into());
     }
 
+    /// The number of arithmetic, free, and extended columns allocated so far.
+    ///
+    /// Columns allocated when building the chip, such as those of the bus constraints, are not
+    /// included.
+    pub fn num_allocated_columns(&self) -> (usize, usize, usize) {
+        (
+            self.local_arithmetic_index,
+            self.local_index - L::NUM_ARITHMETIC_COLUMNS,
+            self.extended_index - L::NUM_ARITHMETIC_COLUMNS - L::NUM_FREE_COLUMNS,
+        )
+    }
+
     pub fn clock(&mut self) -> ElementRegister {
         let clk = self.alloc::<ElementRegister>();
 
         let instruction = AirInstruction::clock(ClockInstruction { clk });
         self.register_air_instruction_internal(instruction);
+        self.clk.get_or_insert(clk);
         clk
     }
 
+    /// Builds the chip and the data to generate its trace.
+    ///
+    /// Panics if a public byte register allocated with `alloc_public` is not range checked by a
+    /// registered byte lookup. Public bytes used to be left unconstrained: builders relying on
+    /// that must either register a byte lookup, as `BytesBuilder` does, or allocate the bytes
+    /// with `alloc_public_unchecked` when their values are constrained otherwise.
     pub fn build(mut self) -> (Chip<L>, AirTraceData<L>) {
+        self.constrain_segments();
+
         // Register all bus constraints.
         for i in 0..self.buses.len() {
             self.register_bus_constraint(i);
@@ -215,6 +253,14 @@ impl<L: AirParameters> AirBuilder<L> {
             self.arithmetic_range_checks();
         }
 
+        // Public bytes are range checked when the byte lookup is registered.
+        assert!(
+            self.public_bytes.is_empty(),
+            "{} public bytes are not range checked, register a byte lookup or allocate them with \
+             `alloc_public_unchecked`",
+            self.public_bytes.len()
+        );
+
         // Check the number of columns in comparison to config
         let num_free_columns = self.local_index - L::NUM_ARITHMETIC_COLUMNS;
 
@@ -268,12 +314,18 @@ impl<L: AirParameters> AirBuilder<L> {
             Ordering::Equal => {}
         }
 
+        // Constraints of segments are multiplied by the segment selectors.
+        let constraint_degree = if self.segments.is_empty() { 3 } else { 4 };
+
         let execution_trace_length = self.local_index;
         (
             Chip {
                 constraints: self.constraints,
                 global_constraints: self.global_constraints,
+                labels: self.labels,
+                constraint_degree,
                 num_challenges: self.shared_memory.challenge_index(),
+                public_challenge_data: self.public_challenge_data.unwrap_or_default(),
                 execution_trace_length,
                 num_public_values: self.shared_memory.public_index(),
                 num_global_values: self.shared_memory.global_index(),
@@ -293,6 +345,7 @@ impl<L: AirParameters> AirBuilder<L> {
                 lookup_values: self.lookup_values,
                 lookup_tables: self.lookup_tables,
                 range_data: self.range_data,
+                segments: self.segments,
             },
         )
     }
@@ -0,0 +1,244 @@
+use alloc::sync::Arc;
+use core::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::AirBuilder;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::constraint::Constraint;
+use crate::chip::instruction::segment::{SegmentFilter, SegmentSelectorInstruction};
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::cubic::EvalCubic;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::table::log_derivative::entry::LogEntry;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// A named range of trace rows.
+///
+/// The instructions and constraints registered while a segment is open only apply to the rows
+/// of the segment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub name: String,
+    pub rows: Range<usize>,
+    pub filter: SegmentFilter,
+}
+
+/// The state of the builder when the currently open segment was opened.
+#[derive(Debug, Clone)]
+pub(crate) struct OpenSegment {
+    index: usize,
+    num_instructions: usize,
+    num_constraints: usize,
+    num_bus_entries: Vec<usize>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Opens a segment of `num_rows` rows named `name` and returns its selector.
+    ///
+    /// Segments are laid out one after the other from the first row of the trace, in the order in
+    /// which they are opened. Until `end_segment` is called, the instructions, constraints, bus
+    /// entries and byte lookups registered with the builder only apply to the rows of the segment.
+    /// Rows after the last segment do not belong to any segment.
+    pub fn begin_segment(&mut self, name: &str, num_rows: usize) -> BitRegister {
+        if let Some(open) = &self.open_segment {
+            panic!(
+                "Cannot open segment {} while segment {} is open",
+                name, self.segments[open.index].name
+            );
+        }
+        assert!(num_rows > 0, "Segment {} must have at least one row", name);
+
+        let clk = match self.clk {
+            Some(clk) => clk,
+            None => self.clock(),
+        };
+        let start = self.segments.last().map_or(0, |segment| segment.rows.end);
+        let rows = start..start + num_rows;
+
+        let filter = SegmentFilter {
+            selector: self.alloc(),
+            transition: self.alloc(),
+            first_row: self.alloc(),
+        };
+        let SegmentFilter {
+            selector,
+            transition,
+            first_row,
+        } = filter;
+        self.register_air_instruction_internal(AirInstruction::SegmentSelector(
+            SegmentSelectorInstruction {
+                filter,
+                rows: rows.clone(),
+            },
+        ));
+
+        // The transition selector is set if the next row belongs to the segment as well.
+        self.assert_expression_zero_transition(
+            transition.expr() - selector.expr() * selector.next().expr(),
+        );
+        self.assert_zero_last_row(&transition);
+
+        // The segment starts at `rows.start` and ends at `rows.end - 1`.
+        self.assert_expression_zero(
+            first_row.expr() * (clk.expr() - L::Field::from_canonical_usize(rows.start)),
+        );
+        self.assert_expression_zero(
+            (selector.expr() - transition.expr())
+                * (clk.expr() - L::Field::from_canonical_usize(rows.end - 1)),
+        );
+
+        match self.segments.last().map(|segment| segment.filter.selector) {
+            None => {
+                self.assert_expression_zero_first_row(selector.expr() - L::Field::ONE);
+                self.assert_expression_zero_first_row(first_row.expr() - L::Field::ONE);
+                self.assert_zero_transition(&first_row.next());
+            }
+            Some(previous) => {
+                self.assert_zero_first_row(&selector);
+                self.assert_zero_first_row(&first_row);
+                self.assert_expression_zero_transition(
+                    first_row.next().expr() - previous.expr() * selector.next().expr(),
+                );
+                // A row of the previous segment is followed by a row of either segment.
+                self.assert_expression_zero_transition(
+                    previous.expr()
+                        * (previous.next().expr() + selector.next().expr() - L::Field::ONE),
+                );
+            }
+        }
+
+        self.open_segment = Some(OpenSegment {
+            index: self.segments.len(),
+            num_instructions: self.instructions.len(),
+            num_constraints: self.constraints.len(),
+            num_bus_entries: self
+                .bus_channels
+                .iter()
+                .map(|channel| channel.entries.len())
+                .collect(),
+        });
+        self.segments.push(Segment {
+            name: name.to_string(),
+            rows,
+            filter,
+        });
+
+        selector
+    }
+
+    /// Closes the open segment.
+    pub fn end_segment(&mut self) {
+        let open = self.open_segment.take().expect("No segment is open");
+        let filter = self.segments[open.index].filter;
+
+        for instruction in self.instructions[open.num_instructions..].iter_mut() {
+            *instruction = AirInstruction::Segment(filter, Arc::new(instruction.clone()));
+        }
+
+        // Lookup and bus constraints are shared by all rows and are left as they are.
+        for constraint in self.constraints[open.num_constraints..].iter_mut() {
+            let segment_constraint = match constraint {
+                Constraint::Instruction(instruction) => Constraint::Instruction(
+                    AirInstruction::Segment(filter, Arc::new(instruction.clone())),
+                ),
+                Constraint::Arithmetic(arithmetic) => {
+                    Constraint::Segment(filter, arithmetic.clone())
+                }
+                _ => continue,
+            };
+            *constraint = segment_constraint;
+        }
+
+        // Bus entries are only counted on the rows of the segment.
+        for channel_idx in 0..self.bus_channels.len() {
+            let start = open.num_bus_entries.get(channel_idx).copied().unwrap_or(0);
+            for entry_idx in start..self.bus_channels[channel_idx].entries.len() {
+                let entry = match self.bus_channels[channel_idx].entries[entry_idx].clone() {
+                    LogEntry::Input(value) => {
+                        LogEntry::InputMultiplicity(value, filter.selector.as_element())
+                    }
+                    LogEntry::Output(value) => {
+                        LogEntry::OutputMultiplicity(value, filter.selector.as_element())
+                    }
+                    LogEntry::InputMultiplicity(value, multiplicity) => {
+                        LogEntry::InputMultiplicity(
+                            value,
+                            self.segment_multiplicity(&multiplicity, &filter.selector),
+                        )
+                    }
+                    LogEntry::OutputMultiplicity(value, multiplicity) => {
+                        LogEntry::OutputMultiplicity(
+                            value,
+                            self.segment_multiplicity(&multiplicity, &filter.selector),
+                        )
+                    }
+                };
+                self.bus_channels[channel_idx].entries[entry_idx] = entry;
+            }
+        }
+    }
+
+    /// The segments opened so far.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// A lookup of `value`, which is only counted on the rows of the open segment, if any.
+    pub(crate) fn segment_lookup_entry<T: EvalCubic>(&self, value: T) -> LogEntry<T> {
+        match &self.open_segment {
+            Some(open) => LogEntry::input_with_multiplicity(
+                value,
+                self.segments[open.index].filter.selector.as_element(),
+            ),
+            None => LogEntry::input(value),
+        }
+    }
+
+    /// Registers the constraints relating the selectors of different segments.
+    pub(crate) fn constrain_segments(&mut self) {
+        if let Some(open) = &self.open_segment {
+            panic!("Segment {} is not closed", self.segments[open.index].name);
+        }
+        let Some(last) = self.segments.last().map(|segment| segment.filter.selector) else {
+            return;
+        };
+
+        let selectors = self
+            .segments
+            .iter()
+            .map(|segment| segment.filter.selector)
+            .collect::<Vec<_>>();
+
+        // Every row belongs to at most one segment.
+        let sum = selectors
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, selector| {
+                acc + selector.expr()
+            });
+        self.assert_expression_zero(sum.clone() * (sum - L::Field::ONE));
+
+        // A row of the last segment is followed by a row of the last segment or by a row outside
+        // of all segments.
+        let others_next = selectors[..selectors.len() - 1]
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, selector| {
+                acc + selector.next().expr()
+            });
+        self.assert_expression_zero_transition(last.expr() * others_next);
+    }
+
+    /// A multiplicity equal to `multiplicity` on the rows of the segment and zero elsewhere.
+    fn segment_multiplicity(
+        &mut self,
+        multiplicity: &ElementRegister,
+        selector: &BitRegister,
+    ) -> ElementRegister {
+        let segment_multiplicity = self.alloc::<ElementRegister>();
+        self.set_to_expression(&segment_multiplicity, multiplicity.expr() * selector.expr());
+        segment_multiplicity
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::arithmetic::ArithmeticConstraint;
+use super::instruction::segment::SegmentFilter;
 use super::instruction::set::AirInstruction;
 use super::memory::pointer::accumulate::PointerAccumulator;
 use super::register::cubic::CubicRegister;
@@ -22,6 +23,7 @@ pub enum Constraint<L: AirParameters> {
     BusChannel(BusChannel<CubicRegister, L::CubicParams>),
     Bus(Bus<CubicRegister, L::CubicParams>),
     Lookup(LookupChipConstraint<L::Field, L::CubicParams>),
+    Segment(SegmentFilter, ArithmeticConstraint<L::Field>),
 }
 
 impl<L: AirParameters> Constraint<L> {
@@ -66,6 +68,9 @@ where
             Constraint::BusChannel(bus_channel) => bus_channel.eval(parser),
             Constraint::Bus(bus) => bus.eval(parser),
             Constraint::Lookup(lookup) => lookup.eval(parser),
+            Constraint::Segment(segment, constraint) => {
+                constraint.eval(&mut segment.mul_parser(parser, None))
+            }
         }
     }
 }
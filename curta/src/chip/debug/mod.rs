@@ -0,0 +1,324 @@
+//! Debugging utilities for attributing constraint failures to the instructions that caused them.
+//!
+//! The builder records an optional label for ranges of constraints. Checking the constraints of a
+//! chip on a trace reports the first failing constraint together with its label, the registers
+//! it refers to, and their values at the failing row.
+//!
+//! The labels are plain metadata and are never touched when proving. When the `debug-constraints`
+//! feature is enabled, the constraints are checked on the full trace before it is committed to by
+//! `ByteStark::prove`, `EmulatedStark::prove`, `Stark::prove` and the Starky prover with an
+//! `ArithmeticGenerator`, and a failure is returned as the error of the proof.
+
+pub mod parser;
+pub mod registers;
+
+use core::fmt::{self, Debug, Display};
+use core::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use self::parser::ConstraintCheckParser;
+use self::registers::collect_registers;
+use super::builder::AirBuilder;
+use super::constraint::Constraint;
+use super::register::memory::MemorySlice;
+use super::trace::writer::TraceWriter;
+use super::{AirParameters, Chip};
+use crate::air::RAir;
+use crate::math::prelude::*;
+use crate::trace::window::TraceWindow;
+
+/// The maximal length of the description of a constraint in a failure report.
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+/// Labels of the constraints of a chip, given as ranges of constraint indices.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConstraintLabels {
+    local: Vec<(Range<usize>, String)>,
+    global: Vec<(Range<usize>, String)>,
+}
+
+impl ConstraintLabels {
+    /// The innermost label of the constraint with the given index.
+    pub fn get(&self, index: usize, global: bool) -> Option<&str> {
+        let labels = if global { &self.global } else { &self.local };
+        labels
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&index))
+            .map(|(_, label)| label.as_str())
+    }
+}
+
+/// A constraint that does not vanish on a trace.
+#[derive(Debug, Clone)]
+pub struct ConstraintFailure<F> {
+    /// The label of the constraint, as given to the builder.
+    pub label: Option<String>,
+    /// A description of the constraint, derived from its debug representation.
+    pub description: String,
+    /// The index of the constraint in the chip.
+    pub constraint_index: usize,
+    /// Whether the constraint is a global constraint.
+    pub global: bool,
+    /// The row at which the constraint failed, `None` for global constraints.
+    pub row: Option<usize>,
+    /// The value of the constraint.
+    pub value: F,
+    /// The registers referred to by the constraint and their values at the failing row.
+    pub registers: Vec<(MemorySlice, Vec<F>)>,
+}
+
+impl<F: Debug> Display for ConstraintFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.global {
+            "global constraint"
+        } else {
+            "constraint"
+        };
+        write!(f, "Nonzero {} {}", kind, self.constraint_index)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({})", label)?;
+        }
+        if let Some(row) = self.row {
+            write!(f, " at row {}", row)?;
+        }
+        writeln!(f, ": value {:?}", self.value)?;
+        writeln!(f, "  instruction: {}", self.description)?;
+        for (register, values) in self.registers.iter() {
+            writeln!(f, "  {:?} = {:?}", register, values)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Debug> std::error::Error for ConstraintFailure<F> {}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Registers all instructions and constraints added by `f` under the label `label`.
+    ///
+    /// Labels can be nested, in which case failures are reported with the innermost label.
+    pub fn with_label<T>(&mut self, label: &'static str, f: impl FnOnce(&mut Self) -> T) -> T {
+        let local_start = self.constraints.len();
+        let global_start = self.global_constraints.len();
+        let value = f(self);
+        let local_end = self.constraints.len();
+        let global_end = self.global_constraints.len();
+
+        if local_start < local_end {
+            self.labels
+                .local
+                .push((local_start..local_end, label.to_string()));
+        }
+        if global_start < global_end {
+            self.labels
+                .global
+                .push((global_start..global_end, label.to_string()));
+        }
+        value
+    }
+
+    /// Registers a custom instruction with the builder under the label `label`.
+    pub fn register_instruction_with_label<I>(&mut self, instruction: I, label: &'static str)
+    where
+        L::Instruction: From<I>,
+    {
+        self.with_label(label, |builder| builder.register_instruction(instruction))
+    }
+}
+
+impl<L: AirParameters> Chip<L> {
+    pub fn labels(&self) -> &ConstraintLabels {
+        &self.labels
+    }
+
+    /// Checks all the constraints of the chip on the trace of `writer`.
+    ///
+    /// The global constraints are checked first, followed by the constraints of each row in
+    /// order. The first failing constraint is returned.
+    pub fn check_constraints(
+        &self,
+        writer: &TraceWriter<L::Field>,
+    ) -> Result<(), ConstraintFailure<L::Field>>
+    where
+        Self: for<'a> RAir<ConstraintCheckParser<'a, L::Field>>,
+    {
+        let trace = writer.read_trace().unwrap();
+        let global = writer.global().unwrap();
+        let public = writer.public().unwrap();
+        let challenges = writer.challenges.read().unwrap();
+
+        let mut parser =
+            ConstraintCheckParser::new(TraceWindow::empty(), &challenges, &global, &public);
+        self.eval_global(&mut parser);
+        if let Some(&(index, value)) = parser.failure() {
+            let window = TraceWindow::empty();
+            return Err(self.failure(
+                index,
+                true,
+                None,
+                value,
+                &window,
+                &challenges,
+                &global,
+                &public,
+            ));
+        }
+
+        for window in trace.windows() {
+            let mut parser =
+                ConstraintCheckParser::new(window.clone(), &challenges, &global, &public);
+            self.eval(&mut parser);
+            if let Some(&(index, value)) = parser.failure() {
+                return Err(self.failure(
+                    index,
+                    false,
+                    Some(window.row),
+                    value,
+                    &window,
+                    &challenges,
+                    &global,
+                    &public,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn failure(
+        &self,
+        index: usize,
+        global: bool,
+        row: Option<usize>,
+        value: L::Field,
+        window: &TraceWindow<L::Field>,
+        challenges: &[L::Field],
+        global_values: &[L::Field],
+        public: &[L::Field],
+    ) -> ConstraintFailure<L::Field> {
+        let constraint = if global {
+            &self.global_constraints[index]
+        } else {
+            &self.constraints[index]
+        };
+
+        let read = |slice: &[L::Field], index: usize, length: usize| {
+            slice
+                .get(index..index + length)
+                .map(|values| values.to_vec())
+                .unwrap_or_default()
+        };
+        let registers = collect_registers::<Constraint<L>>(constraint)
+            .into_iter()
+            .map(|register| {
+                let values = match register {
+                    MemorySlice::Local(i, len) => read(window.local_slice, i, len),
+                    MemorySlice::Next(i, len) => read(window.next_slice, i, len),
+                    MemorySlice::Public(i, len) => read(public, i, len),
+                    MemorySlice::Global(i, len) => read(global_values, i, len),
+                    MemorySlice::Challenge(i, len) => read(challenges, i, len),
+                };
+                (register, values)
+            })
+            .collect();
+
+        let mut description = format!("{:?}", constraint);
+        if description.len() > MAX_DESCRIPTION_LENGTH {
+            let mut end = MAX_DESCRIPTION_LENGTH;
+            while !description.is_char_boundary(end) {
+                end -= 1;
+            }
+            description.truncate(end);
+            description.push_str("...");
+        }
+
+        ConstraintFailure {
+            label: self.labels.get(index, global).map(String::from),
+            description,
+            constraint_index: index,
+            global,
+            row,
+            value,
+            registers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::register::U64Register;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct DebugTest;
+
+    impl AirParameters for DebugTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 48;
+    }
+
+    #[test]
+    fn test_constraint_failure_attribution() {
+        type F = GoldilocksField;
+        type L = DebugTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let mut operations = builder.byte_operations();
+
+        let a = builder.alloc::<U64Register>();
+        let b = builder.alloc::<U64Register>();
+        let _a_and_b = builder.with_label("u64 and", |builder| {
+            builder.bitwise_and(&a, &b, &mut operations)
+        });
+        let a_xor_b = builder.with_label("u64 xor", |builder| {
+            builder.bitwise_xor(&a, &b, &mut operations)
+        });
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 5;
+        let bad_row = 17;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        let to_field = |a: u64| a.to_le_bytes().map(F::from_canonical_u8);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            let a_val = rng.gen::<u64>();
+            let b_val = rng.gen::<u64>();
+            writer.write(&a, &to_field(a_val), i);
+            writer.write(&b, &to_field(b_val), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        assert!(air.check_constraints(&writer).is_ok());
+
+        // Overwrite the xor result with a wrong value.
+        let xor_value = writer.read(&a_xor_b, bad_row);
+        let mut wrong_value = xor_value;
+        wrong_value[3] = F::from_canonical_u8(!(xor_value[3].as_canonical_u64() as u8));
+        writer.write(&a_xor_b, &wrong_value, bad_row);
+
+        let failure = air.check_constraints(&writer).unwrap_err();
+        assert_eq!(failure.label.as_deref(), Some("u64 xor"));
+        assert_eq!(failure.row, Some(bad_row));
+        assert!(failure.description.contains("Xor"));
+        let result_byte = a_xor_b.to_le_bytes().get(3);
+        assert!(failure
+            .registers
+            .iter()
+            .any(|(register, _)| register == result_byte.register()));
+
+        let message = failure.to_string();
+        assert!(message.contains("u64 xor"));
+        assert!(message.contains(&format!("at row {}", bad_row)));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::air::extension::cubic::CubicParser;
+use crate::air::parser::AirParser;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::trace::window::TraceWindow;
+
+/// A parser that evaluates the constraints of an air on a trace window and records the first
+/// constraint that does not vanish, instead of panicking.
+#[derive(Debug, Clone)]
+pub struct ConstraintCheckParser<'a, T> {
+    window: TraceWindow<'a, T>,
+    challenge_slice: &'a [T],
+    global_slice: &'a [T],
+    public_slice: &'a [T],
+    constraint_index: usize,
+    failure: Option<(usize, T)>,
+}
+
+impl<'a, T> ConstraintCheckParser<'a, T> {
+    pub fn new(
+        window: TraceWindow<'a, T>,
+        challenge_slice: &'a [T],
+        global_slice: &'a [T],
+        public_slice: &'a [T],
+    ) -> Self {
+        Self {
+            window,
+            challenge_slice,
+            global_slice,
+            public_slice,
+            constraint_index: 0,
+            failure: None,
+        }
+    }
+
+    /// The index of the first failing constraint and its value, if any.
+    pub fn failure(&self) -> Option<&(usize, T)> {
+        self.failure.as_ref()
+    }
+}
+
+impl<'a, F: Field> ConstraintCheckParser<'a, F> {
+    fn check(&mut self, constraint: F) {
+        if self.failure.is_none() && constraint != F::ZERO {
+            self.failure = Some((self.constraint_index, constraint));
+        }
+    }
+}
+
+impl<'a, F: Field> AirParser for ConstraintCheckParser<'a, F> {
+    type Field = F;
+
+    type Var = F;
+
+    fn local_slice(&self) -> &[Self::Var] {
+        self.window.local_slice
+    }
+
+    fn next_slice(&self) -> &[Self::Var] {
+        self.window.next_slice
+    }
+
+    fn challenge_slice(&self) -> &[Self::Var] {
+        self.challenge_slice
+    }
+
+    fn global_slice(&self) -> &[Self::Var] {
+        self.global_slice
+    }
+
+    fn public_slice(&self) -> &[Self::Var] {
+        self.public_slice
+    }
+
+    fn constraint(&mut self, constraint: Self::Var) {
+        self.check(constraint);
+    }
+
+    fn constraint_transition(&mut self, constraint: Self::Var) {
+        if !self.window.is_last_row {
+            self.check(constraint);
+        }
+    }
+
+    fn constraint_first_row(&mut self, constraint: Self::Var) {
+        if self.window.is_first_row {
+            self.check(constraint);
+        }
+    }
+
+    fn constraint_last_row(&mut self, constraint: Self::Var) {
+        if self.window.is_last_row {
+            self.check(constraint);
+        }
+    }
+
+    fn constant(&mut self, value: Self::Field) -> Self::Var {
+        value
+    }
+
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a + b
+    }
+
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a - b
+    }
+
+    fn neg(&mut self, a: Self::Var) -> Self::Var {
+        -a
+    }
+
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a * b
+    }
+
+    fn begin_constraint(&mut self, index: usize) {
+        self.constraint_index = index;
+    }
+}
+
+impl<'a, F: Field> PolynomialParser for ConstraintCheckParser<'a, F> {}
+
+impl<'a, F: Field, E: CubicParameters<F>> CubicParser<E> for ConstraintCheckParser<'a, F> {}
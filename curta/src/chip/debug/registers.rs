@@ -0,0 +1,377 @@
+//! Extraction of the registers referenced by a constraint.
+//!
+//! Constraints and instructions do not expose the registers they use, but all of them are
+//! serializable. The collector below is a serializer that walks through the serialized data and
+//! records every `MemorySlice` it encounters.
+
+use core::fmt::{self, Display};
+
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::chip::register::memory::MemorySlice;
+
+/// Returns all the memory slices referenced in `value`, sorted and without duplicates.
+pub fn collect_registers<T: Serialize + ?Sized>(value: &T) -> Vec<MemorySlice> {
+    let mut collector = RegisterCollector::default();
+    // The collector itself never fails, so any error can only come from a custom `Serialize`
+    // implementation. In that case we return whatever was collected so far.
+    let _ = value.serialize(&mut collector);
+    let mut registers = collector.registers;
+    registers.sort();
+    registers.dedup();
+    registers
+}
+
+#[derive(Debug, Default)]
+struct RegisterCollector {
+    registers: Vec<MemorySlice>,
+    /// The integer fields of the `MemorySlice` currently being serialized.
+    capture: Option<Vec<u64>>,
+}
+
+#[derive(Debug)]
+struct CollectorError(String);
+
+impl Display for CollectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CollectorError {}
+
+impl ser::Error for CollectorError {
+    fn custom<T: Display>(msg: T) -> Self {
+        CollectorError(msg.to_string())
+    }
+}
+
+impl RegisterCollector {
+    fn capture_u64(&mut self, v: u64) {
+        if let Some(fields) = self.capture.as_mut() {
+            fields.push(v);
+        }
+    }
+}
+
+/// Collects the fields of a tuple variant and records it if it is a `MemorySlice`.
+struct TupleVariantCollector<'a> {
+    collector: &'a mut RegisterCollector,
+    memory_slice_variant: Option<&'static str>,
+}
+
+impl<'a> Serializer for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = TupleVariantCollector<'a>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Self::Error> {
+        self.capture_u64(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Self::Error> {
+        self.capture_u64(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Self::Error> {
+        self.capture_u64(v as u64);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.capture_u64(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let memory_slice_variant = (name == "MemorySlice").then_some(variant);
+        if memory_slice_variant.is_some() {
+            self.capture = Some(Vec::new());
+        }
+        Ok(TupleVariantCollector {
+            collector: self,
+            memory_slice_variant,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<'a> ser::SerializeSeq for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for &'a mut RegisterCollector {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for TupleVariantCollector<'a> {
+    type Ok = ();
+    type Error = CollectorError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut *self.collector)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        let Some(variant) = self.memory_slice_variant else {
+            return Ok(());
+        };
+        let fields = self.collector.capture.take().unwrap_or_default();
+        let (index, length) = match fields[..] {
+            [index, length] => (index as usize, length as usize),
+            _ => return Ok(()),
+        };
+        let slice = match variant {
+            "Local" => MemorySlice::Local(index, length),
+            "Next" => MemorySlice::Next(index, length),
+            "Public" => MemorySlice::Public(index, length),
+            "Global" => MemorySlice::Global(index, length),
+            "Challenge" => MemorySlice::Challenge(index, length),
+            _ => return Ok(()),
+        };
+        self.collector.registers.push(slice);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::register::array::ArrayRegister;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::RegisterSerializable;
+
+    #[test]
+    fn test_collect_registers() {
+        let a = ElementRegister::from_register_unsafe(MemorySlice::Local(3, 1));
+        let b = ArrayRegister::<ElementRegister>::from_register_unsafe(MemorySlice::Public(0, 4));
+        let c = ElementRegister::from_register_unsafe(MemorySlice::Next(1, 1));
+
+        let registers = collect_registers(&(a, Some(b), vec![c, a]));
+        assert_eq!(
+            registers,
+            vec![
+                MemorySlice::Local(3, 1),
+                MemorySlice::Next(1, 1),
+                MemorySlice::Public(0, 4)
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use super::params::{Bls12381BaseField, Bls12381ScalarField};
+use super::tower::{Fp2, Fp2Register};
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::extension::fp2::Fp2AirWriter;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// A point of the G2 group in affine coordinates.
+///
+/// G2 lives on the sextic twist `E'(Fp2): y^2 = x^3 + 4 * (1 + u)`, which maps into the curve
+/// over `Fp12` by `(x, y) -> (x / w^2, y / w^3)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct G2AffinePoint {
+    pub x: Fp2,
+    pub y: Fp2,
+}
+
+impl G2AffinePoint {
+    pub fn new(x: Fp2, y: Fp2) -> Self {
+        Self { x, y }
+    }
+
+    /// The coefficient `b' = 4 * (1 + u)` of the twist.
+    pub fn b() -> Fp2 {
+        Fp2::new(BigUint::from(4u32), BigUint::from(4u32))
+    }
+
+    pub fn generator() -> Self {
+        let coordinate = |c0: &str, c1: &str| {
+            Fp2::new(
+                BigUint::from_str_radix(c0, 16).unwrap(),
+                BigUint::from_str_radix(c1, 16).unwrap(),
+            )
+        };
+        let x = coordinate(
+            "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+            "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e",
+        );
+        let y = coordinate(
+            "0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801",
+            "0606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be",
+        );
+        Self::new(x, y)
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == &(&self.x.square() * &self.x) + &Self::b()
+    }
+
+    /// The slope of the line through two different points `self` and `other`.
+    pub fn chord_slope(&self, other: &Self) -> Fp2 {
+        &(&other.y - &self.y) * &(&other.x - &self.x).inverse()
+    }
+
+    /// The slope `3 * x^2 / (2 * y)` of the tangent line at `self`.
+    pub fn tangent_slope(&self) -> Fp2 {
+        let x_sq = self.x.square();
+        let numerator = &(&x_sq + &x_sq) + &x_sq;
+        &numerator * &(&self.y + &self.y).inverse()
+    }
+
+    /// Computes the third intersection of the line of slope `slope` through `self` and `other`
+    /// with the curve, negated.
+    pub fn add_with_slope(&self, other: &Self, slope: &Fp2) -> Self {
+        let x = &(&slope.square() - &self.x) - &other.x;
+        let y = &(slope * &(&self.x - &x)) - &self.y;
+        Self::new(x, y)
+    }
+
+    /// Adds two different points `self` and `other` with `self != -other`.
+    pub fn g2_add(&self, other: &Self) -> Self {
+        self.add_with_slope(other, &self.chord_slope(other))
+    }
+
+    pub fn g2_double(&self) -> Self {
+        self.add_with_slope(self, &self.tangent_slope())
+    }
+
+    pub fn g2_neg(&self) -> Self {
+        Self::new(self.x.clone(), -&self.y)
+    }
+
+    pub fn g2_scalar_mul(&self, scalar: &BigUint) -> Self {
+        let mut result: Option<Self> = None;
+        let mut temp = self.clone();
+        let nb_bits = Bls12381ScalarField::NB_LIMBS * Bls12381ScalarField::NB_BITS_PER_LIMB;
+        for bit in biguint_to_bits_le(scalar, nb_bits) {
+            if bit {
+                result = result.map(|r| r.g2_add(&temp)).or(Some(temp.clone()));
+            }
+            temp = temp.g2_double();
+        }
+        result.unwrap()
+    }
+}
+
+/// A register for a point of G2 in affine coordinates.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct G2PointRegister {
+    pub x: Fp2Register,
+    pub y: Fp2Register,
+}
+
+impl G2PointRegister {
+    pub fn new(x: Fp2Register, y: Fp2Register) -> Self {
+        Self { x, y }
+    }
+}
+
+pub trait G2AirWriter: Fp2AirWriter {
+    fn read_g2_point(&self, data: &G2PointRegister) -> G2AffinePoint
+    where
+        Self::Field: PrimeField64,
+    {
+        G2AffinePoint::new(self.read_fp2(&data.x), self.read_fp2(&data.y))
+    }
+
+    fn write_g2_point(&mut self, data: &G2PointRegister, value: &G2AffinePoint) {
+        self.write_fp2(&data.x, &value.x);
+        self.write_fp2(&data.y, &value.y);
+    }
+}
+
+impl<W: AirWriter> G2AirWriter for W {}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_g2_point(&mut self) -> G2PointRegister {
+        G2PointRegister::new(self.alloc_fp2(), self.alloc_fp2())
+    }
+
+    pub fn alloc_public_g2_point(&mut self) -> G2PointRegister {
+        G2PointRegister::new(self.alloc_public_fp2(), self.alloc_public_fp2())
+    }
+
+    pub(crate) fn bls12_381_g2_chord_slope(
+        &mut self,
+        p: &G2PointRegister,
+        q: &G2PointRegister,
+    ) -> Fp2Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let numerator = self.fp2_sub(&q.y, &p.y);
+        let denominator = self.fp2_sub(&q.x, &p.x);
+        self.fp2_div(&numerator, &denominator)
+    }
+
+    pub(crate) fn bls12_381_g2_tangent_slope(&mut self, p: &G2PointRegister) -> Fp2Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let mut three = [0u16; MAX_NB_LIMBS];
+        three[0] = 3;
+
+        let x_sq = self.fp2_square(&p.x);
+        let numerator = self.fp2_mul_const(&x_sq, three);
+        let denominator = self.fp2_add(&p.y, &p.y);
+        self.fp2_div(&numerator, &denominator)
+    }
+
+    pub(crate) fn bls12_381_g2_add_with_slope(
+        &mut self,
+        p: &G2PointRegister,
+        q: &G2PointRegister,
+        slope: &Fp2Register,
+    ) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        // x = slope^2 - p.x - q.x
+        let slope_sq = self.fp2_square(slope);
+        let x = self.fp2_sub(&slope_sq, &p.x);
+        let x = self.fp2_sub(&x, &q.x);
+
+        // y = slope * (p.x - x) - p.y
+        let p_x_minus_x = self.fp2_sub(&p.x, &x);
+        let y = self.fp2_mul(slope, &p_x_minus_x);
+        let y = self.fp2_sub(&y, &p.y);
+
+        G2PointRegister::new(x, y)
+    }
+
+    /// Adds two different points `p` and `q` of G2, assuming `p != -q`.
+    pub fn bls12_381_g2_add(&mut self, p: &G2PointRegister, q: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let slope = self.bls12_381_g2_chord_slope(p, q);
+        self.bls12_381_g2_add_with_slope(p, q, &slope)
+    }
+
+    /// Doubles a point `p` of G2.
+    pub fn bls12_381_g2_double(&mut self, p: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let slope = self.bls12_381_g2_tangent_slope(p);
+        self.bls12_381_g2_add_with_slope(p, p, &slope)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::extension::fp2::tests::{read_fp2, write_fp2};
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::trace::writer::TraceWriter;
+    use crate::math::prelude::*;
+
+    pub(crate) fn write_g2_point<F: PrimeField64>(
+        writer: &TraceWriter<F>,
+        data: &G2PointRegister,
+        value: &G2AffinePoint,
+        row_index: usize,
+    ) {
+        write_fp2(writer, &data.x, &value.x, row_index);
+        write_fp2(writer, &data.y, &value.y, row_index);
+    }
+
+    pub(crate) fn read_g2_point<F: PrimeField64>(
+        writer: &TraceWriter<F>,
+        data: &G2PointRegister,
+        row_index: usize,
+    ) -> G2AffinePoint {
+        G2AffinePoint::new(
+            read_fp2(writer, &data.x, row_index),
+            read_fp2(writer, &data.y, row_index),
+        )
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct G2AddTest;
+
+    impl AirParameters for G2AddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3972;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 5967;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    #[test]
+    fn test_bls12_381_g2_generator() {
+        let g = G2AffinePoint::generator();
+        assert!(g.is_on_curve());
+
+        let order = Bls12381ScalarField::modulus();
+        let minus_g = g.g2_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g, g.g2_neg());
+
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+        let b = rng.gen_biguint_below(&order);
+        let lhs = g.g2_scalar_mul(&a).g2_add(&g.g2_scalar_mul(&b));
+        assert_eq!(lhs, g.g2_scalar_mul(&((&a + &b) % &order)));
+        assert!(lhs.is_on_curve());
+    }
+
+    #[test]
+    fn test_bls12_381_g2_add() {
+        type L = G2AddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_g2_point();
+        let q = builder.alloc_g2_point();
+        let r = builder.bls12_381_g2_add(&p, &q);
+
+        let p_pub = builder.alloc_public_g2_point();
+        let double_pub = builder.bls12_381_g2_double(&p_pub);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let g = G2AffinePoint::generator();
+        let order = Bls12381ScalarField::modulus();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+        let b = rng.gen_biguint_below(&order);
+        let p_int = g.g2_scalar_mul(&a);
+        let q_int = g.g2_scalar_mul(&b);
+        let r_int = p_int.g2_add(&q_int);
+        let writer = generator.new_writer();
+        write_g2_point(&writer, &p_pub, &p_int, 0);
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            write_g2_point(&writer, &p, &p_int, i);
+            write_g2_point(&writer, &q, &q_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(read_g2_point(&writer, &r, i), r_int);
+        });
+        assert_eq!(read_g2_point(&writer, &double_pub, 0), p_int.g2_double());
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,151 @@
+use super::params::{Bls12381, Bls12381BaseField, Bls12381Parameters};
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::MAX_NB_LIMBS;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Adds two different points `p` and `q` of the BLS12-381 G1 group.
+    pub fn bls12_381_add(
+        &mut self,
+        p: &AffinePointRegister<Bls12381>,
+        q: &AffinePointRegister<Bls12381>,
+    ) -> AffinePointRegister<Bls12381>
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        self.sw_add::<Bls12381Parameters>(p, q)
+    }
+
+    /// Doubles a point `p` of the BLS12-381 G1 group.
+    ///
+    /// As for secp256k1, `a = 0` and the slope of the tangent line is `3 * x^2 / (2 * y)`.
+    pub fn bls12_381_double(
+        &mut self,
+        p: &AffinePointRegister<Bls12381>,
+    ) -> AffinePointRegister<Bls12381>
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let mut three = [0u16; MAX_NB_LIMBS];
+        three[0] = 3;
+
+        let x_sq = self.fp_mul(&p.x, &p.x);
+        let slope_numerator = self.fp_mul_const(&x_sq, three);
+        let slope_denominator = self.fp_add(&p.y, &p.y);
+        let slope = self.fp_div(&slope_numerator, &slope_denominator);
+
+        self.sw_add_with_slope(p, p, &slope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Bls12381GroupTest;
+
+    impl AirParameters for Bls12381GroupTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1588;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 2391;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    #[test]
+    fn test_bls12_381_add() {
+        type L = Bls12381GroupTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Bls12381;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let r = builder.bls12_381_add(&p, &q);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let base = E::generator();
+        let order = Bls12381Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint(256) % &order;
+        let b = rng.gen_biguint(256) % &order;
+        let p_int = base.sw_scalar_mul(&a);
+        let q_int = base.sw_scalar_mul(&b);
+        let r_int = p_int.sw_add(&q_int);
+        let writer = generator.new_writer();
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &p_int, i);
+            writer.write_ec_point(&q, &q_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(writer.read_ec_point(&r, i), r_int);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[test]
+    fn test_bls12_381_double() {
+        type L = Bls12381GroupTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Bls12381;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let r = builder.bls12_381_double(&p);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let base = E::generator();
+        let order = Bls12381Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint(256) % &order;
+        let p_int = base.sw_scalar_mul(&a);
+        let r_int = p_int.sw_double();
+        let writer = generator.new_writer();
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &p_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(writer.read_ec_point(&r, i), r_int);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
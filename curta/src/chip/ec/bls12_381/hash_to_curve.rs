@@ -0,0 +1,511 @@
+use num::{BigUint, Num, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::g2::{G2AffinePoint, G2PointRegister};
+use super::pairing::{bls_x_bits, BLS_X};
+use super::params::Bls12381BaseField;
+use super::tower::{Fp2, Fp2Register};
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::AirParameters;
+
+/// The coefficient `A' = 240 * u` of the curve `E': y^2 = x^3 + A' * x + B'`, which is
+/// 3-isogenous to the twist of G2 and on which the simplified SWU map is defined.
+fn sswu_a() -> Fp2 {
+    Fp2::new(BigUint::zero(), BigUint::from(240u32))
+}
+
+/// The coefficient `B' = 1012 * (1 + u)` of the curve `E'`.
+fn sswu_b() -> Fp2 {
+    Fp2::new(BigUint::from(1012u32), BigUint::from(1012u32))
+}
+
+/// The non-square `Z = -(2 + u)` of the simplified SWU map.
+fn sswu_z() -> Fp2 {
+    let p = Bls12381BaseField::modulus();
+    Fp2::new(&p - 2u32, &p - 1u32)
+}
+
+/// The coefficients of the rational maps of the 3-isogeny from `E'` to the twist, from the
+/// constant term, as given in RFC 9380, Appendix E.3. The denominators are monic.
+struct IsogenyMap {
+    x_num: [Fp2; 4],
+    x_den: [Fp2; 3],
+    y_num: [Fp2; 4],
+    y_den: [Fp2; 4],
+}
+
+impl IsogenyMap {
+    fn new() -> Self {
+        let fp2 = |c0: &str, c1: &str| {
+            Fp2::new(
+                BigUint::from_str_radix(c0, 16).unwrap(),
+                BigUint::from_str_radix(c1, 16).unwrap(),
+            )
+        };
+        Self {
+            x_num: [
+                fp2(
+                    "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+                    "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+                ),
+                fp2(
+                    "0",
+                    "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71a",
+                ),
+                fp2(
+                    "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71e",
+                    "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38d",
+                ),
+                fp2(
+                    "171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1",
+                    "0",
+                ),
+            ],
+            x_den: [
+                fp2(
+                    "0",
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa63",
+                ),
+                fp2(
+                    "c",
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa9f",
+                ),
+                Fp2::one(),
+            ],
+            y_num: [
+                fp2(
+                    "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+                    "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+                ),
+                fp2(
+                    "0",
+                    "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be",
+                ),
+                fp2(
+                    "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71c",
+                    "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38f",
+                ),
+                fp2(
+                    "124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10",
+                    "0",
+                ),
+            ],
+            y_den: [
+                fp2(
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+                ),
+                fp2(
+                    "0",
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa9d3",
+                ),
+                fp2(
+                    "12",
+                    "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa99",
+                ),
+                Fp2::one(),
+            ],
+        }
+    }
+}
+
+/// Evaluates the polynomial with coefficients `coefficients`, from the constant term, at `x`.
+fn evaluate(coefficients: &[Fp2], x: &Fp2) -> Fp2 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Fp2::zero(), |acc, c| &(&acc * x) + c)
+}
+
+/// The constants `(1 + u)^(-(p - 1) / 3)` and `(1 + u)^(-(p - 1) / 2)` of the endomorphism
+/// `psi`.
+fn psi_coefficients() -> (Fp2, Fp2) {
+    let p = Bls12381BaseField::modulus();
+    let xi = Fp2::one().mul_by_nonresidue();
+    let c_x = xi.pow(&((&p - 1u32) / 3u32)).inverse();
+    let c_y = xi.pow(&((&p - 1u32) >> 1)).inverse();
+    (c_x, c_y)
+}
+
+/// The simplified SWU map of `u` to a point of `E'`, as in RFC 9380, Section 6.6.2.
+///
+/// The exceptional inputs, for which `Z^2 * u^4 + Z * u^2 = 0`, are not supported.
+pub fn map_to_curve_simple_swu(u: &Fp2) -> (Fp2, Fp2) {
+    let (a, b, z) = (sswu_a(), sswu_b(), sswu_z());
+    let curve = |x: &Fp2| &(&(&x.square() * x) + &(&a * x)) + &b;
+
+    let z_u_sq = &z * &u.square();
+    let tv = &z_u_sq.square() + &z_u_sq;
+    assert!(!tv.is_zero(), "exceptional input of the SWU map");
+    let minus_b_over_a = &(-&b) * &a.inverse();
+    let x_1 = &minus_b_over_a * &(&Fp2::one() + &tv.inverse());
+
+    let (x, y) = match curve(&x_1).sqrt() {
+        Some(y) => (x_1, y),
+        None => {
+            let x_2 = &z_u_sq * &x_1;
+            let y = curve(&x_2).sqrt().unwrap();
+            (x_2, y)
+        }
+    };
+    let y = if y.sgn0() == u.sgn0() { y } else { -&y };
+    (x, y)
+}
+
+/// The 3-isogeny from `E'` to the twist.
+pub fn iso_map(x: &Fp2, y: &Fp2) -> G2AffinePoint {
+    let map = IsogenyMap::new();
+    let x_num = evaluate(&map.x_num, x);
+    let x_den = evaluate(&map.x_den, x);
+    let y_num = evaluate(&map.y_num, x);
+    let y_den = evaluate(&map.y_den, x);
+    G2AffinePoint::new(&x_num * &x_den.inverse(), &(y * &y_num) * &y_den.inverse())
+}
+
+/// Maps a field element to a point of the twist, which still has to be multiplied by the
+/// cofactor to land in G2.
+pub fn map_to_g2(u: &Fp2) -> G2AffinePoint {
+    let (x, y) = map_to_curve_simple_swu(u);
+    iso_map(&x, &y)
+}
+
+/// The untwist-Frobenius-twist endomorphism `psi` of the twist.
+pub fn psi(p: &G2AffinePoint) -> G2AffinePoint {
+    let (c_x, c_y) = psi_coefficients();
+    G2AffinePoint::new(&p.x.conjugate() * &c_x, &p.y.conjugate() * &c_y)
+}
+
+fn mul_by_x(p: &G2AffinePoint) -> G2AffinePoint {
+    p.g2_scalar_mul(&BigUint::from(BLS_X)).g2_neg()
+}
+
+/// Clears the cofactor of a point of the twist, computing `[h_eff]P` as
+///
+/// [x^2 - x - 1]P + [x - 1]psi(P) + psi^2([2]P)
+///
+/// with the method of Budroni and Pintore given in RFC 9380, Appendix G.3.
+pub fn clear_cofactor(p: &G2AffinePoint) -> G2AffinePoint {
+    let t_1 = mul_by_x(p);
+    let t_2 = psi(p);
+    let t_3 = psi(&psi(&p.g2_double()));
+    let t_3 = t_3.g2_add(&t_2.g2_neg());
+    let t_2 = mul_by_x(&t_1.g2_add(&t_2));
+    let t_3 = t_3.g2_add(&t_2);
+    let t_3 = t_3.g2_add(&t_1.g2_neg());
+    t_3.g2_add(&p.g2_neg())
+}
+
+/// Whether a point of the twist is in G2, which holds exactly when `psi(P) = [x]P`.
+///
+/// Reference: https://eprint.iacr.org/2021/1130, section 4
+pub fn is_in_g2(p: &G2AffinePoint) -> bool {
+    psi(p) == mul_by_x(p)
+}
+
+/// The registers of the simplified SWU map of an input `u` to the point `(x, y)` of `E'`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SSWURegister {
+    pub u: Fp2Register,
+    /// The coordinates of the image, whose values are given by `map_to_curve_simple_swu` and
+    /// must be written with the input.
+    pub x: Fp2Register,
+    pub y: Fp2Register,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// The parity of the public register `a`, read from the bits of its least significant limb.
+    fn bls12_381_fp_parity(&mut self, a: &FieldRegister<Bls12381BaseField>) -> BitRegister {
+        assert!(
+            !a.is_trace(),
+            "the parity is only supported for public registers"
+        );
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*a.register());
+        let bits = self.alloc_array_public::<BitRegister>(Bls12381BaseField::NB_BITS_PER_LIMB);
+        self.register_global_air_instruction_internal(AirInstruction::bit_decomposition(
+            limbs.get(0).expr(),
+            bits,
+        ));
+        bits.get(0)
+    }
+
+    /// Maps the public input `u` to a point `(x, y)` of `E'` with the simplified SWU map.
+    ///
+    /// The image is witnessed and constrained by `x` being one of the two candidates `x_1` and
+    /// `x_2 = Z * u^2 * x_1` of the map, and by `(x, y)` being on `E'`. Since `g(x_2)` is `g(x_1)`
+    /// times a non-square, only one of the two candidates has a square image, so `x` is uniquely
+    /// determined. The sign of `y` is fixed by `sgn0(y) = sgn0(u)`.
+    ///
+    /// The sign is computed from the parities of the first coordinates of `u` and `y`, so the
+    /// inputs whose first coordinate is zero are not supported, which only happens with
+    /// negligible probability for `u` coming out of a hash. The parities are read from the limbs,
+    /// so the first coordinates of `u` and `y` are checked to be reduced.
+    pub fn bls12_381_map_to_curve_simple_swu(&mut self, u: &Fp2Register) -> SSWURegister
+    where
+        L::Instruction:
+            FromFieldInstruction<Bls12381BaseField> + From<FpCompareInstruction<Bls12381BaseField>>,
+    {
+        let z = self.fp2_constant(&sswu_z());
+        let a = self.fp2_constant(&sswu_a());
+        let b = self.fp2_constant(&sswu_b());
+        let minus_b_over_a = self.fp2_constant(&(&(-&sswu_b()) * &sswu_a().inverse()));
+        let one = self.fp2_constant(&Fp2::one());
+        let zero = self.fp2_constant(&Fp2::zero());
+
+        // x_1 = (-B / A) * (1 + 1 / (Z^2 * u^4 + Z * u^2)) and x_2 = Z * u^2 * x_1.
+        let u_sq = self.fp2_square(u);
+        let z_u_sq = self.fp2_mul(&z, &u_sq);
+        let tv = self.fp2_square(&z_u_sq);
+        let tv = self.fp2_add(&tv, &z_u_sq);
+        let tv_inv = self.fp2_inverse(&tv);
+        let one_plus_tv_inv = self.fp2_add(&one, &tv_inv);
+        let x_1 = self.fp2_mul(&minus_b_over_a, &one_plus_tv_inv);
+        let x_2 = self.fp2_mul(&z_u_sq, &x_1);
+
+        let x = self.alloc_public_fp2();
+        let y = self.alloc_public_fp2();
+
+        // (x - x_1) * (x - x_2) = 0.
+        let x_minus_x_1 = self.fp2_sub(&x, &x_1);
+        let x_minus_x_2 = self.fp2_sub(&x, &x_2);
+        let product = self.fp2_mul(&x_minus_x_1, &x_minus_x_2);
+        self.fp2_assert_equal(&product, &zero);
+
+        // y^2 = x^3 + A * x + B.
+        let y_sq = self.fp2_square(&y);
+        let x_sq = self.fp2_square(&x);
+        let x_cube = self.fp2_mul(&x_sq, &x);
+        let a_x = self.fp2_mul(&a, &x);
+        let g_x = self.fp2_add(&x_cube, &a_x);
+        let g_x = self.fp2_add(&g_x, &b);
+        self.fp2_assert_equal(&y_sq, &g_x);
+
+        // sgn0(y) = sgn0(u).
+        for c in [u.c0, y.c0] {
+            let is_reduced = self.fp_is_reduced(&c);
+            self.assert_expression_zero(is_reduced.not_expr());
+        }
+        let u_sign = self.bls12_381_fp_parity(&u.c0);
+        let y_sign = self.bls12_381_fp_parity(&y.c0);
+        self.assert_equal(&u_sign, &y_sign);
+
+        SSWURegister { u: *u, x, y }
+    }
+
+    /// Evaluates the polynomial with constant coefficients `coefficients`, from the constant
+    /// term, at `x`.
+    fn fp2_evaluate(&mut self, coefficients: &[Fp2], x: &Fp2Register) -> Fp2Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let (leading, rest) = coefficients.split_last().unwrap();
+        let leading = self.fp2_constant(leading);
+        rest.iter().rev().fold(leading, |acc, c| {
+            let c = self.fp2_constant(c);
+            let acc_x = self.fp2_mul(&acc, x);
+            self.fp2_add(&acc_x, &c)
+        })
+    }
+
+    /// The 3-isogeny from `E'` to the twist.
+    pub fn bls12_381_iso_map(&mut self, x: &Fp2Register, y: &Fp2Register) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let map = IsogenyMap::new();
+        let x_num = self.fp2_evaluate(&map.x_num, x);
+        let x_den = self.fp2_evaluate(&map.x_den, x);
+        let y_num = self.fp2_evaluate(&map.y_num, x);
+        let y_den = self.fp2_evaluate(&map.y_den, x);
+
+        let iso_x = self.fp2_div(&x_num, &x_den);
+        let y_ratio = self.fp2_div(&y_num, &y_den);
+        let iso_y = self.fp2_mul(y, &y_ratio);
+        G2PointRegister::new(iso_x, iso_y)
+    }
+
+    /// Maps the public input `u` to a point of the twist, as in `map_to_g2`.
+    pub fn bls12_381_map_to_g2(&mut self, u: &Fp2Register) -> (SSWURegister, G2PointRegister)
+    where
+        L::Instruction:
+            FromFieldInstruction<Bls12381BaseField> + From<FpCompareInstruction<Bls12381BaseField>>,
+    {
+        let sswu = self.bls12_381_map_to_curve_simple_swu(u);
+        let point = self.bls12_381_iso_map(&sswu.x, &sswu.y);
+        (sswu, point)
+    }
+
+    pub fn bls12_381_g2_psi(&mut self, p: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let (c_x, c_y) = psi_coefficients();
+        let c_x = self.fp2_constant(&c_x);
+        let c_y = self.fp2_constant(&c_y);
+
+        let x = self.fp2_conjugate(&p.x);
+        let x = self.fp2_mul(&x, &c_x);
+        let y = self.fp2_conjugate(&p.y);
+        let y = self.fp2_mul(&y, &c_y);
+        G2PointRegister::new(x, y)
+    }
+
+    fn bls12_381_g2_sub(&mut self, p: &G2PointRegister, q: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let minus_q = G2PointRegister::new(q.x, self.fp2_neg(&q.y));
+        self.bls12_381_g2_add(p, &minus_q)
+    }
+
+    /// Computes `[x]P` with a double-and-add over the bits of `|x|`, followed by a negation.
+    fn bls12_381_g2_mul_by_x(&mut self, p: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let mut result = *p;
+        for bit in bls_x_bits() {
+            result = self.bls12_381_g2_double(&result);
+            if bit {
+                result = self.bls12_381_g2_add(&result, p);
+            }
+        }
+        G2PointRegister::new(result.x, self.fp2_neg(&result.y))
+    }
+
+    /// Clears the cofactor of a point of the twist, as in `clear_cofactor`.
+    ///
+    /// The group operations use incomplete formulas, which fail for points of small order.
+    pub fn bls12_381_g2_clear_cofactor(&mut self, p: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let t_1 = self.bls12_381_g2_mul_by_x(p);
+        let t_2 = self.bls12_381_g2_psi(p);
+        let t_3 = self.bls12_381_g2_double(p);
+        let t_3 = self.bls12_381_g2_psi(&t_3);
+        let t_3 = self.bls12_381_g2_psi(&t_3);
+        let t_3 = self.bls12_381_g2_sub(&t_3, &t_2);
+        let t_2 = self.bls12_381_g2_add(&t_1, &t_2);
+        let t_2 = self.bls12_381_g2_mul_by_x(&t_2);
+        let t_3 = self.bls12_381_g2_add(&t_3, &t_2);
+        let t_3 = self.bls12_381_g2_sub(&t_3, &t_1);
+        self.bls12_381_g2_sub(&t_3, p)
+    }
+
+    /// Asserts that a point of the twist is in G2, as in `is_in_g2`.
+    ///
+    /// The multiplication by `x` uses incomplete formulas, which fail for points of small order.
+    pub fn bls12_381_g2_assert_in_subgroup(&mut self, p: &G2PointRegister)
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let psi_p = self.bls12_381_g2_psi(p);
+        let x_p = self.bls12_381_g2_mul_by_x(p);
+        self.fp2_assert_equal(&psi_p.x, &x_p.x);
+        self.fp2_assert_equal(&psi_p.y, &x_p.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::g2::tests::read_g2_point;
+    use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+    use crate::chip::field::extension::fp2::tests::{random_fp2, write_fp2};
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct MapToG2Test;
+
+    impl AirParameters for MapToG2Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 16;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    #[test]
+    fn test_bls12_381_map_to_g2_pure() {
+        let (a, b) = (sswu_a(), sswu_b());
+        let u = random_fp2();
+
+        let (x, y) = map_to_curve_simple_swu(&u);
+        assert_eq!(y.square(), &(&(&x.square() * &x) + &(&a * &x)) + &b);
+        assert_eq!(y.sgn0(), u.sgn0());
+
+        let p = map_to_g2(&u);
+        assert!(p.is_on_curve());
+
+        // The isogeny is a group homomorphism.
+        let (x_1, y_1) = map_to_curve_simple_swu(&random_fp2());
+        let slope = &(&y_1 - &y) * &(&x_1 - &x).inverse();
+        let x_3 = &(&slope.square() - &x) - &x_1;
+        let y_3 = &(&slope * &(&x - &x_3)) - &y;
+        assert_eq!(
+            iso_map(&x_3, &y_3),
+            iso_map(&x, &y).g2_add(&iso_map(&x_1, &y_1))
+        );
+
+        // The cleared point is in G2.
+        let order = Bls12381ScalarField::modulus();
+        let q = clear_cofactor(&p);
+        assert!(q.is_on_curve());
+        assert_eq!(q.g2_scalar_mul(&(&order - 1u32)), q.g2_neg());
+        assert!(is_in_g2(&q));
+        assert!(!is_in_g2(&p));
+    }
+
+    #[test]
+    fn test_bls12_381_map_to_g2() {
+        type L = MapToG2Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let u = builder.alloc_public_fp2();
+        let (sswu, point) = builder.bls12_381_map_to_g2(&u);
+        let cleared = builder.bls12_381_g2_clear_cofactor(&point);
+        builder.bls12_381_g2_assert_in_subgroup(&cleared);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let u_value = random_fp2();
+        let (x, y) = map_to_curve_simple_swu(&u_value);
+        let writer = generator.new_writer();
+        write_fp2(&writer, &sswu.u, &u_value, 0);
+        write_fp2(&writer, &sswu.x, &x, 0);
+        write_fp2(&writer, &sswu.y, &y, 0);
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+        let p = map_to_g2(&u_value);
+        assert_eq!(read_g2_point(&writer, &point, 0), p);
+        assert_eq!(read_g2_point(&writer, &cleared, 0), clear_cofactor(&p));
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark. The recursive proof is not tested, as clearing
+        // the cofactor has hundreds of thousands of public inputs.
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
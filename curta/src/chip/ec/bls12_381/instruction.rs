@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use super::params::Bls12381BaseField;
+use crate::air::AirConstraint;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::count::ByteCountInstruction;
+use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
+use crate::chip::uint::bytes::lookup_table::{ByteInstructionSet, ByteInstructions};
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperationDigestConstraint;
+use crate::chip::uint::operations::add::ByteArrayAdd;
+use crate::chip::uint::operations::instruction::{UintInstruction, UintInstructions};
+use crate::chip::uint::operations::mul::ByteArrayMul;
+use crate::chip::uint::operations::native::U32NativeInstruction;
+use crate::chip::uint::operations::sub::ByteArraySub;
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR doing arithmetic over the base field of BLS12-381 along with the
+/// `u32` operations of SHA-256, as needed to hash messages to G2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Bls12381Instruction {
+    Fp(FpInstruction<Bls12381BaseField>),
+    Uint(UintInstruction),
+}
+
+impl FromFieldInstruction<Bls12381BaseField> for Bls12381Instruction {}
+
+impl ByteInstructions for Bls12381Instruction {}
+
+impl UintInstructions for Bls12381Instruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bls12381Instruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            Bls12381Instruction::Fp(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            Bls12381Instruction::Uint(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bls12381Instruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            Bls12381Instruction::Fp(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Bls12381Instruction::Uint(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            Bls12381Instruction::Fp(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Bls12381Instruction::Uint(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<FpAddInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpAddInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpMulInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpMulInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpSubInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpSubInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpDivInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpDivInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpDenInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpDenInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpInnerProductInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpMulConstInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpNegInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpNegInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpMulSubInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpInvInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpInvInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<FpCompareInstruction<Bls12381BaseField>> for Bls12381Instruction {
+    fn from(i: FpCompareInstruction<Bls12381BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl From<UintInstruction> for Bls12381Instruction {
+    fn from(i: UintInstruction) -> Self {
+        Self::Uint(i)
+    }
+}
+
+impl From<ByteInstructionSet> for Bls12381Instruction {
+    fn from(i: ByteInstructionSet) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationInstruction> for Bls12381Instruction {
+    fn from(i: ByteOperationInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteDecodeInstruction> for Bls12381Instruction {
+    fn from(i: ByteDecodeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationDigestConstraint> for Bls12381Instruction {
+    fn from(i: ByteOperationDigestConstraint) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayAdd<4>> for Bls12381Instruction {
+    fn from(i: ByteArrayAdd<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArraySub<4>> for Bls12381Instruction {
+    fn from(i: ByteArraySub<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayMul> for Bls12381Instruction {
+    fn from(i: ByteArrayMul) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<U32NativeInstruction> for Bls12381Instruction {
+    fn from(i: U32NativeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteCountInstruction> for Bls12381Instruction {
+    fn from(i: ByteCountInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
@@ -0,0 +1,22 @@
+//! The BLS12-381 pairing-friendly curve `y^2 = x^3 + 4` over a 381-bit prime field.
+//!
+//! Ethereum consensus-layer public keys are G1 points, so these chips allow proving the
+//! aggregation of validator keys. The base field elements take 24 limbs instead of the 16 limbs
+//! of 256-bit fields, while the scalars, reduced modulo the 255-bit order of G1, still fit the
+//! 256 bits of `EllipticCurveBuilder::scalar_mul_batch`.
+//!
+//! The pairing maps G1 and the group G2 of the sextic twist over `Fp2` to the target field
+//! `Fp12`, built as the tower `Fp2 = Fp[u] / (u^2 + 1)`, `Fp6 = Fp2[v] / (v^3 - (1 + u))` and
+//! `Fp12 = Fp6[w] / (w^2 - v)` of `chip::field::extension`. The tower operations are composed of
+//! base field instructions, so the Miller loop and the final exponentiation are chips like any
+//! other.
+//!
+//! Reference: https://datatracker.ietf.org/doc/draft-irtf-cfrg-pairing-friendly-curves/
+
+pub mod g2;
+pub mod group;
+pub mod hash_to_curve;
+pub mod instruction;
+pub mod pairing;
+pub mod params;
+pub mod tower;
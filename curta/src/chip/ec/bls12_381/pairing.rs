@@ -0,0 +1,387 @@
+use num::{BigUint, Zero};
+
+use super::g2::{G2AffinePoint, G2PointRegister};
+use super::params::{Bls12381, Bls12381BaseField};
+use super::tower::{Fp12, Fp12Register, Fp2, Fp2Register, Fp6, Fp6Register};
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::AirParameters;
+
+/// The absolute value of the curve parameter `x = -0xd201000000010000`.
+///
+/// The Miller loop runs over the bits of `|x|` and the final exponentiation raises to powers of
+/// `x`. Since `x` is negative, both conjugate the results of the exponentiations by `|x|`.
+pub const BLS_X: u64 = 0xd201_0000_0001_0000;
+
+/// The bits of `|x|` below the leading one, from the most significant.
+pub(crate) fn bls_x_bits() -> impl Iterator<Item = bool> {
+    let nb_bits = 64 - BLS_X.leading_zeros();
+    (0..nb_bits - 1).rev().map(|i| (BLS_X >> i) & 1 == 1)
+}
+
+/// The line of slope `slope` through `t`, evaluated at `p` and scaled by `w^3`.
+///
+/// With `t` mapped to the curve over `Fp12` by `(x, y) -> (x / w^2, y / w^3)`, the line is
+/// `(slope * t.x - t.y) + (-slope * p.x) * v + p.y * v * w`. The scaling factor lies in a
+/// proper subfield of `Fp12` and is erased by the final exponentiation.
+fn line_evaluation(slope: &Fp2, t: &G2AffinePoint, p: &AffinePoint<Bls12381>) -> Fp12 {
+    let p_mod = Bls12381BaseField::modulus();
+    let a = &(slope * &t.x) - &t.y;
+    let b = slope.mul_by_fp(&(&p_mod - &p.x));
+    let c = Fp2::new(p.y.clone(), BigUint::zero());
+    Fp12::new(
+        Fp6::new(a, b, Fp2::zero()),
+        Fp6::new(Fp2::zero(), c, Fp2::zero()),
+    )
+}
+
+/// Computes the product of the Miller loops `f_{|x|, Q}(P)` of all pairs `(P, Q)`, conjugated
+/// to account for the sign of `x`.
+pub fn miller_loop(pairs: &[(AffinePoint<Bls12381>, G2AffinePoint)]) -> Fp12 {
+    let mut f = Fp12::one();
+    let mut t = pairs.iter().map(|(_, q)| q.clone()).collect::<Vec<_>>();
+    for bit in bls_x_bits() {
+        f = f.square();
+        for ((p, q), t) in pairs.iter().zip(t.iter_mut()) {
+            let slope = t.tangent_slope();
+            f = &f * &line_evaluation(&slope, t, p);
+            *t = t.add_with_slope(t, &slope);
+            if bit {
+                let slope = t.chord_slope(q);
+                f = &f * &line_evaluation(&slope, t, p);
+                *t = t.add_with_slope(q, &slope);
+            }
+        }
+    }
+    f.conjugate()
+}
+
+/// Computes `a^x` for a unitary element `a`.
+fn exp_by_x(a: &Fp12) -> Fp12 {
+    a.pow(&BigUint::from(BLS_X)).conjugate()
+}
+
+/// Raises `f` to the power `3 * (p^12 - 1) / r`.
+///
+/// The easy part `(p^6 - 1) * (p^2 + 1)` uses a conjugation, an inversion and the Frobenius map.
+/// The hard part uses the decomposition `3 * (p^4 - p^2 + 1) / r = (x - 1)^2 * (x + p) *
+/// (x^2 + p^2 - 1) + 3` of Hayashida, Hayasaka and Teruya, which is why the result is the cube
+/// of the reduced pairing. Since `r` is prime to 3, this does not change which products of
+/// pairings are equal to one.
+///
+/// Reference: https://eprint.iacr.org/2020/875
+pub fn final_exponentiation(f: &Fp12) -> Fp12 {
+    let f = &f.conjugate() * &f.inverse();
+    let f = &f.frobenius_map().frobenius_map() * &f;
+
+    let a = &exp_by_x(&f) * &f.conjugate();
+    let a = &exp_by_x(&a) * &a.conjugate();
+    let b = &exp_by_x(&a) * &a.frobenius_map();
+    let c = &(&exp_by_x(&exp_by_x(&b)) * &b.frobenius_map().frobenius_map()) * &b.conjugate();
+    &c * &(&f.square() * &f)
+}
+
+/// Computes the cube of the reduced Tate pairing `e(P, Q)` of `p` in G1 and `q` in G2.
+pub fn pairing(p: &AffinePoint<Bls12381>, q: &G2AffinePoint) -> Fp12 {
+    final_exponentiation(&miller_loop(&[(p.clone(), q.clone())]))
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Multiplies `f` by the line of slope `slope` through `t` evaluated at
+    /// `p = (minus_p_x, p_y)` as in `line_evaluation`, using that the line is sparse.
+    fn bls12_381_mul_by_line(
+        &mut self,
+        f: &Fp12Register,
+        slope: &Fp2Register,
+        t: &G2PointRegister,
+        minus_p_x: &FieldRegister<Bls12381BaseField>,
+        p_y: &FieldRegister<Bls12381BaseField>,
+    ) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        // The line is `l0 + l1 * w` with `l0 = a + b * v` and `l1 = c * v`.
+        let a = self.fp2_mul(slope, &t.x);
+        let a = self.fp2_sub(&a, &t.y);
+        let b = self.fp2_mul_by_fp(slope, minus_p_x);
+
+        // f0 * l0 + v * (f1 * l1)
+        let f0_l0 = mul_by_line_l0(self, &f.c0, &a, &b);
+        let f1_l1 = mul_by_line_l1(self, &f.c1, p_y);
+        let f1_l1 = self.fp6_mul_by_nonresidue(&f1_l1);
+        let c0 = self.fp6_add(&f0_l0, &f1_l1);
+
+        // f0 * l1 + f1 * l0
+        let f0_l1 = mul_by_line_l1(self, &f.c0, p_y);
+        let f1_l0 = mul_by_line_l0(self, &f.c1, &a, &b);
+        let c1 = self.fp6_add(&f0_l1, &f1_l0);
+
+        Fp12Register::new(c0, c1)
+    }
+
+    /// Computes the product of the Miller loops `f_{|x|, Q}(P)` of all pairs `(P, Q)`, conjugated
+    /// to account for the sign of `x`, as in `miller_loop`.
+    ///
+    /// The points of G2 are added in affine coordinates with incomplete formulas, which is
+    /// sound for points of order `r` since the multiples of `Q` reached by the loop are never
+    /// equal to `Q` or `-Q`.
+    pub fn bls12_381_miller_loop(
+        &mut self,
+        pairs: &[(AffinePointRegister<Bls12381>, G2PointRegister)],
+    ) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let minus_p_x = pairs
+            .iter()
+            .map(|(p, _)| self.fp_neg(&p.x))
+            .collect::<Vec<_>>();
+
+        let mut f = self.fp12_constant(&Fp12::one());
+        let mut t = pairs.iter().map(|(_, q)| *q).collect::<Vec<_>>();
+        for bit in bls_x_bits() {
+            f = self.fp12_square(&f);
+            for (((p, q), t), minus_p_x) in pairs.iter().zip(t.iter_mut()).zip(minus_p_x.iter()) {
+                let slope = self.bls12_381_g2_tangent_slope(t);
+                f = self.bls12_381_mul_by_line(&f, &slope, t, minus_p_x, &p.y);
+                *t = self.bls12_381_g2_add_with_slope(t, t, &slope);
+                if bit {
+                    let slope = self.bls12_381_g2_chord_slope(t, q);
+                    f = self.bls12_381_mul_by_line(&f, &slope, t, minus_p_x, &p.y);
+                    *t = self.bls12_381_g2_add_with_slope(t, q, &slope);
+                }
+            }
+        }
+        self.fp12_conjugate(&f)
+    }
+
+    /// Computes `a^x` for an element `a` of the cyclotomic subgroup by square and multiply over
+    /// the bits of `|x|`.
+    fn bls12_381_exp_by_x(&mut self, a: &Fp12Register) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let mut result = *a;
+        for bit in bls_x_bits() {
+            result = self.fp12_cyclotomic_square(&result);
+            if bit {
+                result = self.fp12_mul(&result, a);
+            }
+        }
+        self.fp12_conjugate(&result)
+    }
+
+    /// Raises `f` to the power `3 * (p^12 - 1) / r`, see `final_exponentiation`.
+    pub fn bls12_381_final_exponentiation(&mut self, f: &Fp12Register) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        // Easy part: f^((p^6 - 1) * (p^2 + 1)).
+        let f_conj = self.fp12_conjugate(f);
+        let f_inv = self.fp12_inverse(f);
+        let f = self.fp12_mul(&f_conj, &f_inv);
+        let f_frob = self.fp12_frobenius_map(&f);
+        let f_frob = self.fp12_frobenius_map(&f_frob);
+        let f = self.fp12_mul(&f_frob, &f);
+
+        // Hard part: f^((x - 1)^2 * (x + p) * (x^2 + p^2 - 1) + 3), where `f` is now in the
+        // cyclotomic subgroup.
+        let f_x = self.bls12_381_exp_by_x(&f);
+        let f_conj = self.fp12_conjugate(&f);
+        let a = self.fp12_mul(&f_x, &f_conj);
+        let a_x = self.bls12_381_exp_by_x(&a);
+        let a_conj = self.fp12_conjugate(&a);
+        let a = self.fp12_mul(&a_x, &a_conj);
+
+        let a_x = self.bls12_381_exp_by_x(&a);
+        let a_frob = self.fp12_frobenius_map(&a);
+        let b = self.fp12_mul(&a_x, &a_frob);
+
+        let b_x = self.bls12_381_exp_by_x(&b);
+        let b_x2 = self.bls12_381_exp_by_x(&b_x);
+        let b_frob = self.fp12_frobenius_map(&b);
+        let b_frob2 = self.fp12_frobenius_map(&b_frob);
+        let b_conj = self.fp12_conjugate(&b);
+        let c = self.fp12_mul(&b_x2, &b_frob2);
+        let c = self.fp12_mul(&c, &b_conj);
+
+        let f_sq = self.fp12_cyclotomic_square(&f);
+        let f_cube = self.fp12_mul(&f_sq, &f);
+        self.fp12_mul(&c, &f_cube)
+    }
+
+    /// Asserts that the product of the pairings `e(P, Q)` of all pairs `(P, Q)` is equal to one.
+    ///
+    /// The Miller loops of all pairs share their squarings and a single final exponentiation.
+    /// The chip is a straight-line sequence of base field instructions: a check of two pairs
+    /// takes tens of thousands of them, which act as global instructions on public inputs.
+    pub fn bls12_381_pairing_check(
+        &mut self,
+        pairs: &[(AffinePointRegister<Bls12381>, G2PointRegister)],
+    ) where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+    {
+        let f = self.bls12_381_miller_loop(pairs);
+        let product = self.bls12_381_final_exponentiation(&f);
+
+        let one = self.fp12_constant(&Fp12::one());
+        for (a, b) in product.coefficients().iter().zip(one.coefficients().iter()) {
+            self.assert_equal(&a.c0, &b.c0);
+            self.assert_equal(&a.c1, &b.c1);
+        }
+    }
+}
+
+/// Computes `x * (a + b * v)` for `x` in `Fp6` and `a, b` in `Fp2`.
+fn mul_by_line_l0<L: AirParameters>(
+    builder: &mut AirBuilder<L>,
+    x: &Fp6Register,
+    a: &Fp2Register,
+    b: &Fp2Register,
+) -> Fp6Register
+where
+    L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+{
+    // (x0 * a + (1 + u) * x2 * b) + (x0 * b + x1 * a) * v + (x1 * b + x2 * a) * v^2
+    let x0_a = builder.fp2_mul(&x.c0, a);
+    let x2_b = builder.fp2_mul(&x.c2, b);
+    let x2_b = builder.fp2_mul_by_nonresidue(&x2_b);
+    let c0 = builder.fp2_add(&x0_a, &x2_b);
+
+    let x0_b = builder.fp2_mul(&x.c0, b);
+    let x1_a = builder.fp2_mul(&x.c1, a);
+    let c1 = builder.fp2_add(&x0_b, &x1_a);
+
+    let x1_b = builder.fp2_mul(&x.c1, b);
+    let x2_a = builder.fp2_mul(&x.c2, a);
+    let c2 = builder.fp2_add(&x1_b, &x2_a);
+
+    Fp6Register::new(c0, c1, c2)
+}
+
+/// Computes `x * (c * v)` for `x` in `Fp6` and `c` in the base field.
+fn mul_by_line_l1<L: AirParameters>(
+    builder: &mut AirBuilder<L>,
+    x: &Fp6Register,
+    c: &FieldRegister<Bls12381BaseField>,
+) -> Fp6Register
+where
+    L::Instruction: FromFieldInstruction<Bls12381BaseField>,
+{
+    // (1 + u) * x2 * c + x0 * c * v + x1 * c * v^2
+    let x2_c = builder.fp2_mul_by_fp(&x.c2, c);
+    let c0 = builder.fp2_mul_by_nonresidue(&x2_c);
+    let c1 = builder.fp2_mul_by_fp(&x.c0, c);
+    let c2 = builder.fp2_mul_by_fp(&x.c1, c);
+    Fp6Register::new(c0, c1, c2)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::g2::tests::write_g2_point;
+    use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::EllipticCurve;
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct PairingCheckTest;
+
+    impl AirParameters for PairingCheckTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 16;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    #[test]
+    fn test_bls12_381_pairing() {
+        let g1 = Bls12381::generator();
+        let g2 = G2AffinePoint::generator();
+        let order = Bls12381ScalarField::modulus();
+
+        let e = pairing(&g1, &g2);
+        assert_ne!(e, Fp12::one());
+        assert_eq!(e.pow(&order), Fp12::one());
+
+        // The result is the cube of the reduced Tate pairing.
+        let p = Bls12381BaseField::modulus();
+        let exponent = (p.pow(12) - 1u32) / &order * 3u32;
+        assert_eq!(miller_loop(&[(g1.clone(), g2.clone())]).pow(&exponent), e);
+
+        // Bilinearity.
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+        let b = rng.gen_biguint_below(&order);
+        let a_g1 = g1.sw_scalar_mul(&a);
+        let b_g2 = g2.g2_scalar_mul(&b);
+        assert_eq!(pairing(&a_g1, &b_g2), e.pow(&((&a * &b) % &order)));
+
+        // e(a * G1, b * G2) * e(-(a * b) * G1, G2) = 1.
+        let minus_ab_g1 = g1.sw_scalar_mul(&(&order - (&a * &b) % &order));
+        let product = final_exponentiation(&miller_loop(&[
+            (a_g1.clone(), b_g2.clone()),
+            (minus_ab_g1, g2.clone()),
+        ]));
+        assert_eq!(product, Fp12::one());
+
+        // e(a * G1, b * G2) * e(G1, G2) != 1.
+        let product = final_exponentiation(&miller_loop(&[(a_g1, b_g2), (g1, g2)]));
+        assert_ne!(product, Fp12::one());
+    }
+
+    #[test]
+    fn test_bls12_381_pairing_check() {
+        type L = PairingCheckTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p_1 = builder.alloc_public_ec_point();
+        let q_1 = builder.alloc_public_g2_point();
+        let p_2 = builder.alloc_public_ec_point();
+        let q_2 = builder.alloc_public_g2_point();
+        builder.bls12_381_pairing_check(&[(p_1, q_1), (p_2, q_2)]);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let g1 = Bls12381::generator();
+        let g2 = G2AffinePoint::generator();
+        let order = Bls12381ScalarField::modulus();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+
+        // e(a * G1, G2) * e(-G1, a * G2) = 1.
+        let writer = generator.new_writer();
+        writer.write_ec_point(&p_1, &g1.sw_scalar_mul(&a), 0);
+        write_g2_point(&writer, &q_1, &g2, 0);
+        writer.write_ec_point(&p_2, &g1.sw_scalar_mul(&(&order - 1u32)), 0);
+        write_g2_point(&writer, &q_2, &g2.g2_scalar_mul(&a), 0);
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark. The recursive proof is not tested, as the
+        // pairing check has millions of public inputs.
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
@@ -0,0 +1,120 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// BLS12-381 G1 curve parameter
+pub struct Bls12381Parameters;
+
+pub type Bls12381 = SWCurve<Bls12381Parameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// BLS12-381 base field parameter
+pub struct Bls12381BaseField;
+
+impl FieldParameters for Bls12381BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 24;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Base field modulus:
+    //  4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        43691, 65535, 65535, 47614, 65535, 45395, 65534, 7851, 63012, 63152, 53920, 26416, 4799,
+        62341, 19332, 25719, 44247, 17227, 42934, 19227, 59034, 14719, 4586, 6657, 0, 0, 0, 0, 0,
+        0, 0, 0,
+    ];
+
+    // The witness limbs grow with the number of limbs, so they can exceed the offset of the
+    // 256-bit fields.
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// BLS12-381 scalar field parameter
+pub struct Bls12381ScalarField;
+
+impl FieldParameters for Bls12381ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  52435875175126190479447740508185965837690552500527637822603658699938581184513
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        1, 0, 65535, 65535, 23550, 65534, 41986, 21437, 55301, 2465, 55304, 13113, 32072, 10653,
+        42835, 29677, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for Bls12381Parameters {
+    type BaseField = Bls12381BaseField;
+}
+
+impl WeierstrassParameters for Bls12381Parameters {
+    const A: [u16; MAX_NB_LIMBS] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; MAX_NB_LIMBS] = [
+        4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "3685416753713387016781088315183077757961620795782546409894578378688607592378376318836054947676345821548104185464507",
+            10,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "1339506544944476473020471379941921221584933875938349620426543736416511423956333506472724655353366534992391756441569",
+            10,
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    fn prime_group_order() -> BigUint {
+        Bls12381ScalarField::modulus()
+    }
+
+    /// The scalars are reduced modulo the 255-bit group order rather than the base field.
+    fn nb_scalar_bits() -> usize {
+        Bls12381ScalarField::NB_LIMBS * Bls12381ScalarField::NB_BITS_PER_LIMB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::ec::EllipticCurve;
+
+    #[test]
+    fn test_bls12_381_generator() {
+        let p = Bls12381BaseField::modulus();
+        assert_eq!(p.bits(), 381);
+
+        let (x, y) = Bls12381Parameters::generator();
+        let lhs = (&y * &y) % &p;
+        let rhs = (&x * &x * &x + Bls12381Parameters::b_int()) % &p;
+        assert_eq!(lhs, rhs);
+
+        let g = Bls12381::generator();
+        let order = Bls12381Parameters::prime_group_order();
+        assert_eq!(Bls12381::nb_scalar_bits(), 256);
+        let minus_g = g.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g.x, g.x);
+        assert_eq!(minus_g.y, &p - &g.y);
+    }
+}
@@ -0,0 +1,23 @@
+//! The tower of extension fields of BLS12-381, with the non-residue `xi = 1 + u`.
+
+use serde::{Deserialize, Serialize};
+
+use super::params::Bls12381BaseField;
+use crate::chip::field::extension::{fp12, fp2, fp6, TowerParameters};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// BLS12-381 extension tower parameter
+pub struct Bls12381Tower;
+
+impl TowerParameters for Bls12381Tower {
+    type BaseField = Bls12381BaseField;
+
+    const NONRESIDUE: u16 = 1;
+}
+
+pub type Fp2 = fp2::Fp2<Bls12381Tower>;
+pub type Fp2Register = fp2::Fp2Register<Bls12381Tower>;
+pub type Fp6 = fp6::Fp6<Bls12381Tower>;
+pub type Fp6Register = fp6::Fp6Register<Bls12381Tower>;
+pub type Fp12 = fp12::Fp12<Bls12381Tower>;
+pub type Fp12Register = fp12::Fp12Register<Bls12381Tower>;
@@ -0,0 +1,276 @@
+//! Goldilocks digests as elements of the scalar field of BN254.
+//!
+//! A Curta proof wrapped in a BN254-based outer proof exposes its public outputs, like the
+//! digests of a hash, to a circuit over the scalar field `Fr` of BN254. The chip of this module
+//! packs every three Goldilocks elements of a digest into the `u16` limbs of one
+//! `FieldRegister<Bn254ScalarField>`, so the outer circuit only needs to recompose the range
+//! checked limbs `sum_i limb_i * 2^(16 * i)` of each packed element, which is below `2^192 < r`.
+//!
+//! The limbs of every element are constrained to be the canonical representation of the element,
+//! so each digest has a unique packing.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::weierstrass::bn254::Bn254ScalarField;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::Polynomial;
+
+/// The order of the Goldilocks field, `2^64 - 2^32 + 1`.
+const GOLDILOCKS_ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// The number of Goldilocks elements packed into an element of the scalar field of BN254.
+pub const ELEMENTS_PER_PACK: usize = 3;
+
+/// The number of `u16` limbs of a Goldilocks element.
+const LIMBS_PER_ELEMENT: usize = 4;
+
+/// Packs up to `ELEMENTS_PER_PACK` Goldilocks elements into the limbs of `result`.
+///
+/// Every element `e_j` is constrained to `e_j = sum_i limb_(4j + i) * 2^(16 * i)` with the unused
+/// limbs set to zero. As the four limbs can also represent `e_j + p` for small `e_j`, the
+/// witness `high_inv_j` of `high_j - (2^32 - 1)` forces the low half `low_j` of the limbs to
+/// vanish when the high half `high_j` is `2^32 - 1`, by `low_j * ((high_j - (2^32 - 1)) *
+/// high_inv_j - 1) = 0`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Bn254PackInstruction {
+    pub elements: ArrayRegister<ElementRegister>,
+    pub result: FieldRegister<Bn254ScalarField>,
+    high_inv: ArrayRegister<ElementRegister>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Packs the Goldilocks elements of `digest` into elements of the scalar field of BN254, in
+    /// chunks of `ELEMENTS_PER_PACK` elements.
+    pub fn goldilocks_to_bn254(
+        &mut self,
+        digest: &ArrayRegister<ElementRegister>,
+    ) -> Vec<FieldRegister<Bn254ScalarField>>
+    where
+        L::Instruction: From<Bn254PackInstruction>,
+    {
+        assert_eq!(
+            L::Field::order(),
+            GOLDILOCKS_ORDER,
+            "the packing of digests is only supported over the Goldilocks field"
+        );
+        (0..digest.len())
+            .step_by(ELEMENTS_PER_PACK)
+            .map(|start| {
+                let end = digest.len().min(start + ELEMENTS_PER_PACK);
+                let elements = digest.get_subarray(start..end);
+                let is_trace = elements.is_trace();
+
+                let (result, high_inv) = if is_trace {
+                    (
+                        self.alloc::<FieldRegister<Bn254ScalarField>>(),
+                        self.alloc_array::<ElementRegister>(elements.len()),
+                    )
+                } else {
+                    (
+                        self.alloc_public::<FieldRegister<Bn254ScalarField>>(),
+                        self.alloc_array_public::<ElementRegister>(elements.len()),
+                    )
+                };
+                let instr = Bn254PackInstruction {
+                    elements,
+                    result,
+                    high_inv,
+                };
+                if is_trace {
+                    self.register_instruction(instr);
+                } else {
+                    self.register_global_instruction(instr);
+                }
+                result
+            })
+            .collect()
+    }
+}
+
+/// The packed values of `digest` given by `goldilocks_to_bn254`, as the outer circuit expects
+/// them.
+pub fn bn254_packed_digest<F: PrimeField64>(digest: &[F]) -> Vec<BigUint> {
+    digest
+        .chunks(ELEMENTS_PER_PACK)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .rev()
+                .fold(BigUint::from(0u32), |acc, element| {
+                    (acc << 64) + element.as_canonical_u64()
+                })
+        })
+        .collect()
+}
+
+impl<AP: AirParser> AirConstraint<AP> for Bn254PackInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let elements = self.elements.eval_vec(parser);
+        let high_inv = self.high_inv.eval_vec(parser);
+        let limbs = self.result.eval(parser).coefficients;
+
+        let limb_shift = AP::Field::from_canonical_u32(1 << 16);
+        for (j, (element, high_inv)) in elements.into_iter().zip(high_inv).enumerate() {
+            let [l0, l1, l2, l3] = [0, 1, 2, 3].map(|i| limbs[LIMBS_PER_ELEMENT * j + i]);
+            let l1_shifted = parser.mul_const(l1, limb_shift);
+            let low = parser.add(l0, l1_shifted);
+            let l3_shifted = parser.mul_const(l3, limb_shift);
+            let high = parser.add(l2, l3_shifted);
+
+            // Constrain the recomposition `element = low + 2^32 * high`.
+            let high_shifted = parser.mul_const(high, AP::Field::from_canonical_u64(1 << 32));
+            let recomposition = parser.add(low, high_shifted);
+            parser.assert_eq(element, recomposition);
+
+            // Constrain `low = 0` if `high = 2^32 - 1`, to rule out the representation of
+            // `element + p`.
+            let high_minus_max = parser.sub_const(high, AP::Field::from_canonical_u32(u32::MAX));
+            let inv_product = parser.mul(high_minus_max, high_inv);
+            let one = parser.one();
+            let inv_product_minus_one = parser.sub(inv_product, one);
+            let constraint = parser.mul(low, inv_product_minus_one);
+            parser.constraint(constraint);
+        }
+
+        for limb in limbs[LIMBS_PER_ELEMENT * self.elements.len()..].iter() {
+            parser.constraint(*limb);
+        }
+    }
+}
+
+impl Bn254PackInstruction {
+    fn witness<F: PrimeField64>(elements: &[F]) -> (Polynomial<F>, Vec<F>) {
+        let mut limbs = vec![F::ZERO; Bn254ScalarField::NB_LIMBS];
+        let mut high_inv = Vec::with_capacity(elements.len());
+        for (j, element) in elements.iter().enumerate() {
+            let value = element.as_canonical_u64();
+            for (i, limb) in limbs[LIMBS_PER_ELEMENT * j..LIMBS_PER_ELEMENT * (j + 1)]
+                .iter_mut()
+                .enumerate()
+            {
+                *limb = F::from_canonical_u64((value >> (16 * i)) & 0xFFFF);
+            }
+            let high = F::from_canonical_u64(value >> 32);
+            let high_minus_max = high - F::from_canonical_u32(u32::MAX);
+            high_inv.push(high_minus_max.try_inverse().unwrap_or(F::ZERO));
+        }
+        (Polynomial::from_coefficients(limbs), high_inv)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bn254PackInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let elements = writer.read_vec(&self.elements, row_index);
+        let (result, high_inv) = Self::witness(&elements);
+        writer.write(&self.result, &result, row_index);
+        writer.write_array(&self.high_inv, high_inv, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let elements = writer.read_vec(&self.elements);
+        let (result, high_inv) = Self::witness(&elements);
+        writer.write(&self.result, &result);
+        writer.write_array(&self.high_inv, high_inv);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::utils::field_limbs_to_biguint;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Bn254PackTest;
+
+    impl AirParameters for Bn254PackTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 32;
+        const NUM_FREE_COLUMNS: usize = 10;
+        const EXTENDED_COLUMNS: usize = 57;
+
+        type Instruction = Bn254PackInstruction;
+    }
+
+    #[test]
+    fn test_goldilocks_to_bn254() {
+        type F = GoldilocksField;
+        type L = Bn254PackTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Bn254ScalarField;
+
+        let mut builder = AirBuilder::<L>::new();
+        let digest_pub = builder.alloc_array_public::<ElementRegister>(2);
+        let packed_pub = builder.goldilocks_to_bn254(&digest_pub);
+        assert_eq!(packed_pub.len(), 1);
+
+        let digest = builder.alloc_array::<ElementRegister>(4);
+        let packed = builder.goldilocks_to_bn254(&digest);
+        assert_eq!(packed.len(), 2);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        let digest_pub_values = [F::NEG_ONE, F::ZERO];
+        writer.write_array(&digest_pub, digest_pub_values, 0);
+        writer.write_global_instructions(&generator.air_data);
+        let packed_pub_value = writer.read(&packed_pub[0], 0);
+        assert_eq!(
+            vec![to_biguint(&packed_pub_value)],
+            bn254_packed_digest(&digest_pub_values)
+        );
+        for i in 0..num_rows {
+            // The largest element, the only one with a high half of `2^32 - 1`, small elements, and
+            // random elements.
+            let values = match i % 4 {
+                0 => vec![F::NEG_ONE, F::ZERO, F::ONE, F::from_canonical_u32(u32::MAX)],
+                _ => (0..4)
+                    .map(|_| F::from_canonical_u64(rng.gen::<u64>() % GOLDILOCKS_ORDER))
+                    .collect(),
+            };
+            writer.write_array(&digest, &values, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            if i < 4 {
+                let packed_values = packed
+                    .iter()
+                    .map(|limbs| to_biguint(&writer.read(limbs, i)))
+                    .collect::<Vec<_>>();
+                assert_eq!(packed_values, bn254_packed_digest(&values));
+                assert!(packed_values.iter().all(|value| *value < P::modulus()));
+            }
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    fn to_biguint(limbs: &Polynomial<GoldilocksField>) -> BigUint {
+        field_limbs_to_biguint(limbs.coefficients())
+    }
+}
@@ -0,0 +1,246 @@
+use num::{BigUint, Num, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::tower::{Fp2, Fp2Register};
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::weierstrass::bn254::{Bn254BaseField, Bn254ScalarField};
+use crate::chip::field::constants::FpConstant;
+use crate::chip::field::extension::fp2::Fp2AirWriter;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::utils::biguint_to_bits_le;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// A point of the G2 group in affine coordinates.
+///
+/// G2 lives on the sextic twist `E'(Fp2): y^2 = x^3 + 3 / (9 + u)`, which maps into the curve
+/// over `Fp12` by `(x, y) -> (x * w^2, y * w^3)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct G2AffinePoint {
+    pub x: Fp2,
+    pub y: Fp2,
+}
+
+impl G2AffinePoint {
+    pub fn new(x: Fp2, y: Fp2) -> Self {
+        Self { x, y }
+    }
+
+    /// The coefficient `b' = 3 / (9 + u)` of the twist.
+    pub fn b() -> Fp2 {
+        let three = Fp2::new(BigUint::from(3u32), BigUint::zero());
+        &three * &Fp2::one().mul_by_nonresidue().inverse()
+    }
+
+    /// The generator of G2 used by the `ecPairing` precompile.
+    pub fn generator() -> Self {
+        let coordinate = |c0: &str, c1: &str| {
+            Fp2::new(
+                BigUint::from_str_radix(c0, 10).unwrap(),
+                BigUint::from_str_radix(c1, 10).unwrap(),
+            )
+        };
+        let x = coordinate(
+            "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+            "11559732032986387107991004021392285783925812861821192530917403151452391805634",
+        );
+        let y = coordinate(
+            "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+            "4082367875863433681332203403145435568316851327593401208105741076214120093531",
+        );
+        Self::new(x, y)
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        self.y.square() == &(&self.x.square() * &self.x) + &Self::b()
+    }
+
+    /// The slope of the line through two different points `self` and `other`.
+    pub fn chord_slope(&self, other: &Self) -> Fp2 {
+        &(&other.y - &self.y) * &(&other.x - &self.x).inverse()
+    }
+
+    /// The slope `3 * x^2 / (2 * y)` of the tangent line at `self`.
+    pub fn tangent_slope(&self) -> Fp2 {
+        let x_sq = self.x.square();
+        let numerator = &(&x_sq + &x_sq) + &x_sq;
+        &numerator * &(&self.y + &self.y).inverse()
+    }
+
+    /// Computes the third intersection of the line of slope `slope` through `self` and `other`
+    /// with the curve, negated.
+    pub fn add_with_slope(&self, other: &Self, slope: &Fp2) -> Self {
+        let x = &(&slope.square() - &self.x) - &other.x;
+        let y = &(slope * &(&self.x - &x)) - &self.y;
+        Self::new(x, y)
+    }
+
+    /// Adds two different points `self` and `other` with `self != -other`.
+    pub fn g2_add(&self, other: &Self) -> Self {
+        self.add_with_slope(other, &self.chord_slope(other))
+    }
+
+    pub fn g2_double(&self) -> Self {
+        self.add_with_slope(self, &self.tangent_slope())
+    }
+
+    pub fn g2_neg(&self) -> Self {
+        Self::new(self.x.clone(), -&self.y)
+    }
+
+    pub fn g2_scalar_mul(&self, scalar: &BigUint) -> Self {
+        let mut result: Option<Self> = None;
+        let mut temp = self.clone();
+        let nb_bits = Bn254ScalarField::NB_LIMBS * Bn254ScalarField::NB_BITS_PER_LIMB;
+        for bit in biguint_to_bits_le(scalar, nb_bits) {
+            if bit {
+                result = result.map(|r| r.g2_add(&temp)).or(Some(temp.clone()));
+            }
+            temp = temp.g2_double();
+        }
+        result.unwrap()
+    }
+}
+
+/// A register for a point of G2 in affine coordinates.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct G2PointRegister {
+    pub x: Fp2Register,
+    pub y: Fp2Register,
+}
+
+impl G2PointRegister {
+    pub fn new(x: Fp2Register, y: Fp2Register) -> Self {
+        Self { x, y }
+    }
+}
+
+pub trait Bn254G2AirWriter: Fp2AirWriter {
+    fn read_bn254_g2_point(&self, data: &G2PointRegister) -> G2AffinePoint
+    where
+        Self::Field: PrimeField64,
+    {
+        G2AffinePoint::new(self.read_fp2(&data.x), self.read_fp2(&data.y))
+    }
+
+    fn write_bn254_g2_point(&mut self, data: &G2PointRegister, value: &G2AffinePoint) {
+        self.write_fp2(&data.x, &value.x);
+        self.write_fp2(&data.y, &value.y);
+    }
+}
+
+impl<W: AirWriter> Bn254G2AirWriter for W {}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_bn254_g2_point(&mut self) -> G2PointRegister {
+        G2PointRegister::new(self.alloc_fp2(), self.alloc_fp2())
+    }
+
+    pub fn alloc_public_bn254_g2_point(&mut self) -> G2PointRegister {
+        G2PointRegister::new(self.alloc_public_fp2(), self.alloc_public_fp2())
+    }
+
+    pub(crate) fn bn254_g2_chord_slope(
+        &mut self,
+        p: &G2PointRegister,
+        q: &G2PointRegister,
+    ) -> Fp2Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let numerator = self.fp2_sub(&q.y, &p.y);
+        let denominator = self.fp2_sub(&q.x, &p.x);
+        self.fp2_div(&numerator, &denominator)
+    }
+
+    pub(crate) fn bn254_g2_tangent_slope(&mut self, p: &G2PointRegister) -> Fp2Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let three = FpConstant::<Bn254BaseField>::from_u64(3);
+
+        let x_sq = self.fp2_square(&p.x);
+        let numerator = self.fp2_mul_const(&x_sq, three.limbs());
+        let denominator = self.fp2_add(&p.y, &p.y);
+        self.fp2_div(&numerator, &denominator)
+    }
+
+    pub(crate) fn bn254_g2_add_with_slope(
+        &mut self,
+        p: &G2PointRegister,
+        q: &G2PointRegister,
+        slope: &Fp2Register,
+    ) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        // x = slope^2 - p.x - q.x
+        let slope_sq = self.fp2_square(slope);
+        let x = self.fp2_sub(&slope_sq, &p.x);
+        let x = self.fp2_sub(&x, &q.x);
+
+        // y = slope * (p.x - x) - p.y
+        let p_x_minus_x = self.fp2_sub(&p.x, &x);
+        let y = self.fp2_mul(slope, &p_x_minus_x);
+        let y = self.fp2_sub(&y, &p.y);
+
+        G2PointRegister::new(x, y)
+    }
+
+    /// Adds two different points `p` and `q` of G2, assuming `p != -q`.
+    pub fn bn254_g2_add(&mut self, p: &G2PointRegister, q: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let slope = self.bn254_g2_chord_slope(p, q);
+        self.bn254_g2_add_with_slope(p, q, &slope)
+    }
+
+    /// Doubles a point `p` of G2.
+    pub fn bn254_g2_double(&mut self, p: &G2PointRegister) -> G2PointRegister
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let slope = self.bn254_g2_tangent_slope(p);
+        self.bn254_g2_add_with_slope(p, p, &slope)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::field::extension::fp2::tests::write_fp2;
+    use crate::chip::trace::writer::TraceWriter;
+
+    pub(crate) fn write_g2_point<F: PrimeField64>(
+        writer: &TraceWriter<F>,
+        data: &G2PointRegister,
+        value: &G2AffinePoint,
+        row_index: usize,
+    ) {
+        write_fp2(writer, &data.x, &value.x, row_index);
+        write_fp2(writer, &data.y, &value.y, row_index);
+    }
+
+    #[test]
+    fn test_bn254_g2_generator() {
+        let g = G2AffinePoint::generator();
+        assert!(g.is_on_curve());
+
+        let order = Bn254ScalarField::modulus();
+        let minus_g = g.g2_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g, g.g2_neg());
+
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+        let b = rng.gen_biguint_below(&order);
+        let lhs = g.g2_scalar_mul(&a).g2_add(&g.g2_scalar_mul(&b));
+        assert_eq!(lhs, g.g2_scalar_mul(&((&a + &b) % &order)));
+        assert!(lhs.is_on_curve());
+    }
+}
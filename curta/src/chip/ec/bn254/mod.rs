@@ -0,0 +1,18 @@
+//! The BN254 (alt_bn128) pairing-friendly curve `y^2 = x^3 + 3` used by the Ethereum
+//! precompiles.
+//!
+//! The curve parameters are defined in `weierstrass::bn254`, so the generic short Weierstrass
+//! chips, including `EllipticCurveBuilder::scalar_mul_batch`, already apply to the G1 group. This
+//! module adds the chips with the exact semantics of the precompiles, which encode the point at
+//! infinity as `(0, 0)`, along with the G2 group and the pairing check of `ecPairing`. The
+//! extension fields of the pairing are the towers of `chip::field::extension`.
+//!
+//! References:
+//! - https://eips.ethereum.org/EIPS/eip-196
+//! - https://eips.ethereum.org/EIPS/eip-197
+
+pub mod digest;
+pub mod g2;
+pub mod pairing;
+pub mod precompile;
+pub mod tower;
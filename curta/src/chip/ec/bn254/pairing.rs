@@ -0,0 +1,488 @@
+//! The optimal ate pairing of BN254 and the pairing check of the `ecPairing` (0x08) precompile.
+//!
+//! Reference: https://eips.ethereum.org/EIPS/eip-197
+
+use num::{BigUint, Zero};
+
+use super::g2::{G2AffinePoint, G2PointRegister};
+use super::tower::{Fp12, Fp12Register, Fp2, Fp2Register, Fp6, Fp6Register};
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::AirParameters;
+
+/// The curve parameter `x = 4965661367192848881`.
+///
+/// The final exponentiation raises to powers of `x`, and the Miller loop runs over the bits of
+/// `6 * x + 2`.
+pub const BN_X: u64 = 4_965_661_367_192_848_881;
+
+/// The length `6 * x + 2` of the Miller loop of the optimal ate pairing.
+pub const ATE_LOOP_COUNT: u128 = 6 * BN_X as u128 + 2;
+
+/// The bits of `value` below the leading one, from the most significant.
+fn bits_below_leading_one(value: u128) -> impl Iterator<Item = bool> {
+    let nb_bits = 128 - value.leading_zeros();
+    (0..nb_bits - 1).rev().map(move |i| (value >> i) & 1 == 1)
+}
+
+/// The coefficients `xi^((p - 1) / 3)`, `xi^((p - 1) / 2)` and `xi^((p^2 - 1) / 3)` of the
+/// Frobenius endomorphism of the twist.
+fn twist_frobenius_coefficients() -> [Fp2; 3] {
+    let p = Bn254BaseField::modulus();
+    let xi = Fp2::one().mul_by_nonresidue();
+    [
+        xi.pow(&((&p - 1u32) / 3u32)),
+        xi.pow(&((&p - 1u32) / 2u32)),
+        xi.pow(&((&p * &p - 1u32) / 3u32)),
+    ]
+}
+
+/// The images `pi(q)` and `-pi^2(q)` of `q` under the Frobenius endomorphism, which are `[p]q`
+/// and `-[p^2]q` for `q` in G2.
+///
+/// Since `xi^((p^2 - 1) / 2) = -1`, the negation of `pi^2(q)` leaves its `y` coordinate
+/// unchanged.
+fn twist_frobenius(q: &G2AffinePoint) -> (G2AffinePoint, G2AffinePoint) {
+    let [gamma_x, gamma_y, gamma_x2] = twist_frobenius_coefficients();
+    let q1 = G2AffinePoint::new(&q.x.conjugate() * &gamma_x, &q.y.conjugate() * &gamma_y);
+    let minus_q2 = G2AffinePoint::new(&q.x * &gamma_x2, q.y.clone());
+    (q1, minus_q2)
+}
+
+/// The line of slope `slope` through `t`, evaluated at `p`.
+///
+/// With `t` mapped to the curve over `Fp12` by `(x, y) -> (x * w^2, y * w^3)`, the line is
+/// `p.y + (-slope * p.x) * w + (slope * t.x - t.y) * v * w`.
+fn line_evaluation(slope: &Fp2, t: &G2AffinePoint, p: &AffinePoint<Bn254>) -> Fp12 {
+    let p_mod = Bn254BaseField::modulus();
+    let a = slope.mul_by_fp(&(&p_mod - &p.x));
+    let b = &(slope * &t.x) - &t.y;
+    let c = Fp2::new(p.y.clone(), BigUint::zero());
+    Fp12::new(
+        Fp6::new(c, Fp2::zero(), Fp2::zero()),
+        Fp6::new(a, b, Fp2::zero()),
+    )
+}
+
+/// Computes the product of the Miller loops `f_{6x + 2, Q}(P)` of all pairs `(P, Q)`, followed by
+/// the lines through `pi(Q)` and `-pi^2(Q)` of the optimal ate pairing.
+pub fn miller_loop(pairs: &[(AffinePoint<Bn254>, G2AffinePoint)]) -> Fp12 {
+    let mut f = Fp12::one();
+    let mut t = pairs.iter().map(|(_, q)| q.clone()).collect::<Vec<_>>();
+    for bit in bits_below_leading_one(ATE_LOOP_COUNT) {
+        f = f.square();
+        for ((p, q), t) in pairs.iter().zip(t.iter_mut()) {
+            let slope = t.tangent_slope();
+            f = &f * &line_evaluation(&slope, t, p);
+            *t = t.add_with_slope(t, &slope);
+            if bit {
+                let slope = t.chord_slope(q);
+                f = &f * &line_evaluation(&slope, t, p);
+                *t = t.add_with_slope(q, &slope);
+            }
+        }
+    }
+    for ((p, q), t) in pairs.iter().zip(t.iter_mut()) {
+        let (q1, minus_q2) = twist_frobenius(q);
+        for q in [q1, minus_q2] {
+            let slope = t.chord_slope(&q);
+            f = &f * &line_evaluation(&slope, t, p);
+            *t = t.add_with_slope(&q, &slope);
+        }
+    }
+    f
+}
+
+/// Raises `f` to the power `m * (p^12 - 1) / r` with `m = 2x * (6x^2 + 3x + 1)`.
+///
+/// The easy part `(p^6 - 1) * (p^2 + 1)` uses a conjugation, an inversion and the Frobenius map.
+/// The hard part uses the multiple `m * (p^4 - p^2 + 1) / r` of Fuentes-Castaneda, Knapp and
+/// Rodriguez-Henriquez, whose coefficients in base `p` are polynomials in `x` computed with three
+/// exponentiations by `x`. Since `m` is prime to `r`, this does not change which products of
+/// pairings are equal to one.
+///
+/// Reference: "Faster hashing to G2", Selected Areas in Cryptography 2011
+pub fn final_exponentiation(f: &Fp12) -> Fp12 {
+    let f = &f.conjugate() * &f.inverse();
+    let f = &f.frobenius_map().frobenius_map() * &f;
+
+    let x = BigUint::from(BN_X);
+    let f_2x = f.pow(&x).square();
+    let f_6x = &f_2x.square() * &f_2x;
+    let f_6x2 = f_6x.pow(&x);
+    let f_12x3 = f_6x2.square().pow(&x);
+
+    // a = f^(12x^3 + 6x^2 + 6x), b = f^(12x^3 + 6x^2 + 4x), c = f^(12x^3 + 12x^2 + 6x + 1).
+    let a = &(&f_12x3 * &f_6x2) * &f_6x;
+    let b = &a * &f_2x.conjugate();
+    let c = &(&a * &f_6x2) * &f;
+
+    let d = (&b * &f.conjugate())
+        .frobenius_map()
+        .frobenius_map()
+        .frobenius_map();
+    &(&(&b.frobenius_map() * &c) * &a.frobenius_map().frobenius_map()) * &d
+}
+
+/// Computes the optimal ate pairing `e(P, Q)^m` of `p` in G1 and `q` in G2, with the exponent
+/// `m` of `final_exponentiation`.
+pub fn pairing(p: &AffinePoint<Bn254>, q: &G2AffinePoint) -> Fp12 {
+    final_exponentiation(&miller_loop(&[(p.clone(), q.clone())]))
+}
+
+/// The result of the `ecPairing` precompile on valid points, which skips the pairs containing
+/// the point at infinity, encoded with zero coordinates.
+pub fn pairing_check(pairs: &[(AffinePoint<Bn254>, G2AffinePoint)]) -> bool {
+    let pairs = pairs
+        .iter()
+        .filter(|(p, q)| !(p.x.is_zero() && p.y.is_zero()) && !(q.x.is_zero() && q.y.is_zero()))
+        .cloned()
+        .collect::<Vec<_>>();
+    final_exponentiation(&miller_loop(&pairs)) == Fp12::one()
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Multiplies `f` by the line of slope `slope` through `t` evaluated at
+    /// `p = (minus_p_x, p_y)` as in `line_evaluation`, using that the line is sparse.
+    fn bn254_mul_by_line(
+        &mut self,
+        f: &Fp12Register,
+        slope: &Fp2Register,
+        t: &G2PointRegister,
+        minus_p_x: &FieldRegister<Bn254BaseField>,
+        p_y: &FieldRegister<Bn254BaseField>,
+    ) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        // The line is `l0 + l1 * w` with `l0 = c` and `l1 = a + b * v`.
+        let a = self.fp2_mul_by_fp(slope, minus_p_x);
+        let b = self.fp2_mul(slope, &t.x);
+        let b = self.fp2_sub(&b, &t.y);
+
+        // f0 * l0 + v * (f1 * l1)
+        let f0_l0 = mul_by_line_l0(self, &f.c0, p_y);
+        let f1_l1 = mul_by_line_l1(self, &f.c1, &a, &b);
+        let f1_l1 = self.fp6_mul_by_nonresidue(&f1_l1);
+        let c0 = self.fp6_add(&f0_l0, &f1_l1);
+
+        // f0 * l1 + f1 * l0
+        let f0_l1 = mul_by_line_l1(self, &f.c0, &a, &b);
+        let f1_l0 = mul_by_line_l0(self, &f.c1, p_y);
+        let c1 = self.fp6_add(&f0_l1, &f1_l0);
+
+        Fp12Register::new(c0, c1)
+    }
+
+    /// Computes the images `pi(q)` and `-pi^2(q)` of `q`, see `twist_frobenius`.
+    fn bn254_g2_frobenius(&mut self, q: &G2PointRegister) -> (G2PointRegister, G2PointRegister)
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let [gamma_x, gamma_y, gamma_x2] =
+            twist_frobenius_coefficients().map(|gamma| self.fp2_constant(&gamma));
+
+        let x_conj = self.fp2_conjugate(&q.x);
+        let y_conj = self.fp2_conjugate(&q.y);
+        let q1 = G2PointRegister::new(
+            self.fp2_mul(&x_conj, &gamma_x),
+            self.fp2_mul(&y_conj, &gamma_y),
+        );
+        let minus_q2 = G2PointRegister::new(self.fp2_mul(&q.x, &gamma_x2), q.y);
+        (q1, minus_q2)
+    }
+
+    /// Computes the product of the Miller loops of the optimal ate pairing of all pairs, as in
+    /// `miller_loop`.
+    ///
+    /// The points of G2 are added in affine coordinates with incomplete formulas, which is
+    /// sound for points of order `r` since the multiples of `Q` reached by the loop, including
+    /// the final additions of `pi(Q)` and `-pi^2(Q)`, are never equal to the added point or its
+    /// negation.
+    pub fn bn254_miller_loop(
+        &mut self,
+        pairs: &[(AffinePointRegister<Bn254>, G2PointRegister)],
+    ) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let minus_p_x = pairs
+            .iter()
+            .map(|(p, _)| self.fp_neg(&p.x))
+            .collect::<Vec<_>>();
+
+        let mut f = self.fp12_constant(&Fp12::one());
+        let mut t = pairs.iter().map(|(_, q)| *q).collect::<Vec<_>>();
+        for bit in bits_below_leading_one(ATE_LOOP_COUNT) {
+            f = self.fp12_square(&f);
+            for (((p, q), t), minus_p_x) in pairs.iter().zip(t.iter_mut()).zip(minus_p_x.iter()) {
+                let slope = self.bn254_g2_tangent_slope(t);
+                f = self.bn254_mul_by_line(&f, &slope, t, minus_p_x, &p.y);
+                *t = self.bn254_g2_add_with_slope(t, t, &slope);
+                if bit {
+                    let slope = self.bn254_g2_chord_slope(t, q);
+                    f = self.bn254_mul_by_line(&f, &slope, t, minus_p_x, &p.y);
+                    *t = self.bn254_g2_add_with_slope(t, q, &slope);
+                }
+            }
+        }
+        for (((p, q), t), minus_p_x) in pairs.iter().zip(t.iter_mut()).zip(minus_p_x.iter()) {
+            let (q1, minus_q2) = self.bn254_g2_frobenius(q);
+            for q in [q1, minus_q2] {
+                let slope = self.bn254_g2_chord_slope(t, &q);
+                f = self.bn254_mul_by_line(&f, &slope, t, minus_p_x, &p.y);
+                *t = self.bn254_g2_add_with_slope(t, &q, &slope);
+            }
+        }
+        f
+    }
+
+    /// Computes `a^x` for an element `a` of the cyclotomic subgroup by square and multiply over
+    /// the bits of `x`.
+    fn bn254_exp_by_x(&mut self, a: &Fp12Register) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let mut result = *a;
+        for bit in bits_below_leading_one(BN_X as u128) {
+            result = self.fp12_cyclotomic_square(&result);
+            if bit {
+                result = self.fp12_mul(&result, a);
+            }
+        }
+        result
+    }
+
+    /// Raises `f` to the power `m * (p^12 - 1) / r`, see `final_exponentiation`.
+    pub fn bn254_final_exponentiation(&mut self, f: &Fp12Register) -> Fp12Register
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        // Easy part: f^((p^6 - 1) * (p^2 + 1)).
+        let f_conj = self.fp12_conjugate(f);
+        let f_inv = self.fp12_inverse(f);
+        let f = self.fp12_mul(&f_conj, &f_inv);
+        let f_frob = self.fp12_frobenius_map(&f);
+        let f_frob = self.fp12_frobenius_map(&f_frob);
+        let f = self.fp12_mul(&f_frob, &f);
+
+        // Hard part: the powers of `f` by `x`, `2x`, `6x`, `6x^2`, `12x^2` and `12x^3`, where
+        // `f` is now in the cyclotomic subgroup.
+        let f_x = self.bn254_exp_by_x(&f);
+        let f_2x = self.fp12_cyclotomic_square(&f_x);
+        let f_4x = self.fp12_cyclotomic_square(&f_2x);
+        let f_6x = self.fp12_mul(&f_4x, &f_2x);
+        let f_6x2 = self.bn254_exp_by_x(&f_6x);
+        let f_12x2 = self.fp12_cyclotomic_square(&f_6x2);
+        let f_12x3 = self.bn254_exp_by_x(&f_12x2);
+
+        // a = f^(12x^3 + 6x^2 + 6x), b = f^(12x^3 + 6x^2 + 4x), c = f^(12x^3 + 12x^2 + 6x + 1).
+        let a = self.fp12_mul(&f_12x3, &f_6x2);
+        let a = self.fp12_mul(&a, &f_6x);
+        let f_2x_conj = self.fp12_conjugate(&f_2x);
+        let b = self.fp12_mul(&a, &f_2x_conj);
+        let c = self.fp12_mul(&a, &f_6x2);
+        let c = self.fp12_mul(&c, &f);
+
+        // b^p * c * a^(p^2) * (b / f)^(p^3)
+        let f_conj = self.fp12_conjugate(&f);
+        let d = self.fp12_mul(&b, &f_conj);
+        let d = self.fp12_frobenius_map(&d);
+        let d = self.fp12_frobenius_map(&d);
+        let d = self.fp12_frobenius_map(&d);
+        let b_frob = self.fp12_frobenius_map(&b);
+        let a_frob = self.fp12_frobenius_map(&a);
+        let a_frob2 = self.fp12_frobenius_map(&a_frob);
+
+        let result = self.fp12_mul(&b_frob, &c);
+        let result = self.fp12_mul(&result, &a_frob2);
+        self.fp12_mul(&result, &d)
+    }
+
+    /// Asserts that the product of the pairings `e(P, Q)` of all pairs `(P, Q)` is equal to one,
+    /// which is the `ecPairing` precompile returning one.
+    ///
+    /// The Miller loops of all pairs share their squarings and a single final exponentiation.
+    /// The points must not be the point at infinity, and they are not checked to be in their
+    /// prime order subgroups, which the precompile requires of the points of G2.
+    pub fn bn254_pairing_check(&mut self, pairs: &[(AffinePointRegister<Bn254>, G2PointRegister)])
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let f = self.bn254_miller_loop(pairs);
+        let product = self.bn254_final_exponentiation(&f);
+
+        let one = self.fp12_constant(&Fp12::one());
+        for (a, b) in product.coefficients().iter().zip(one.coefficients().iter()) {
+            self.assert_equal(&a.c0, &b.c0);
+            self.assert_equal(&a.c1, &b.c1);
+        }
+    }
+}
+
+/// Computes `x * c` for `x` in `Fp6` and `c` in the base field.
+fn mul_by_line_l0<L: AirParameters>(
+    builder: &mut AirBuilder<L>,
+    x: &Fp6Register,
+    c: &FieldRegister<Bn254BaseField>,
+) -> Fp6Register
+where
+    L::Instruction: FromFieldInstruction<Bn254BaseField>,
+{
+    let c0 = builder.fp2_mul_by_fp(&x.c0, c);
+    let c1 = builder.fp2_mul_by_fp(&x.c1, c);
+    let c2 = builder.fp2_mul_by_fp(&x.c2, c);
+    Fp6Register::new(c0, c1, c2)
+}
+
+/// Computes `x * (a + b * v)` for `x` in `Fp6` and `a, b` in `Fp2`.
+fn mul_by_line_l1<L: AirParameters>(
+    builder: &mut AirBuilder<L>,
+    x: &Fp6Register,
+    a: &Fp2Register,
+    b: &Fp2Register,
+) -> Fp6Register
+where
+    L::Instruction: FromFieldInstruction<Bn254BaseField>,
+{
+    // (x0 * a + xi * x2 * b) + (x0 * b + x1 * a) * v + (x1 * b + x2 * a) * v^2
+    let x0_a = builder.fp2_mul(&x.c0, a);
+    let x2_b = builder.fp2_mul(&x.c2, b);
+    let x2_b = builder.fp2_mul_by_nonresidue(&x2_b);
+    let c0 = builder.fp2_add(&x0_a, &x2_b);
+
+    let x0_b = builder.fp2_mul(&x.c0, b);
+    let x1_a = builder.fp2_mul(&x.c1, a);
+    let c1 = builder.fp2_add(&x0_b, &x1_a);
+
+    let x1_b = builder.fp2_mul(&x.c1, b);
+    let x2_a = builder.fp2_mul(&x.c2, a);
+    let c2 = builder.fp2_add(&x1_b, &x2_a);
+
+    Fp6Register::new(c0, c1, c2)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bn254::g2::tests::write_g2_point;
+    use crate::chip::ec::bn254::precompile::infinity;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::weierstrass::bn254::Bn254ScalarField;
+    use crate::chip::ec::EllipticCurve;
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct PairingCheckTest;
+
+    impl AirParameters for PairingCheckTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 16;
+        type Instruction = FpInstruction<Bn254BaseField>;
+    }
+
+    #[test]
+    fn test_bn254_pairing() {
+        let g1 = Bn254::generator();
+        let g2 = G2AffinePoint::generator();
+        let order = Bn254ScalarField::modulus();
+
+        let e = pairing(&g1, &g2);
+        assert_ne!(e, Fp12::one());
+        assert_eq!(e.pow(&order), Fp12::one());
+
+        // The result is the reduced pairing raised to `m = 2x * (6x^2 + 3x + 1)`.
+        let p = Bn254BaseField::modulus();
+        let x = BigUint::from(BN_X);
+        let m = &x * 2u32 * (&x * &x * 6u32 + &x * 3u32 + 1u32);
+        let exponent = (p.pow(12) - 1u32) / &order * m;
+        assert_eq!(miller_loop(&[(g1.clone(), g2.clone())]).pow(&exponent), e);
+
+        // The Frobenius endomorphism acts on G2 as the multiplication by `p`.
+        let (q1, minus_q2) = twist_frobenius(&g2);
+        assert_eq!(q1, g2.g2_scalar_mul(&(&p % &order)));
+        assert_eq!(minus_q2, g2.g2_scalar_mul(&(&p * &p % &order)).g2_neg());
+
+        // Bilinearity.
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+        let b = rng.gen_biguint_below(&order);
+        let a_g1 = g1.sw_scalar_mul(&a);
+        let b_g2 = g2.g2_scalar_mul(&b);
+        assert_eq!(pairing(&a_g1, &b_g2), e.pow(&((&a * &b) % &order)));
+
+        // e(a * G1, b * G2) * e(-(a * b) * G1, G2) = 1.
+        let minus_ab_g1 = g1.sw_scalar_mul(&(&order - (&a * &b) % &order));
+        assert!(pairing_check(&[
+            (a_g1.clone(), b_g2.clone()),
+            (minus_ab_g1, g2.clone()),
+        ]));
+
+        // e(a * G1, b * G2) * e(G1, G2) != 1.
+        assert!(!pairing_check(&[(a_g1.clone(), b_g2), (g1, g2.clone())]));
+
+        // The pairs with the point at infinity are skipped.
+        let g2_infinity = G2AffinePoint::new(Fp2::zero(), Fp2::zero());
+        assert!(pairing_check(&[]));
+        assert!(pairing_check(&[
+            (infinity(), g2.clone()),
+            (a_g1, g2_infinity)
+        ]));
+    }
+
+    #[test]
+    fn test_bn254_pairing_check() {
+        type L = PairingCheckTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p_1 = builder.alloc_public_ec_point();
+        let q_1 = builder.alloc_public_bn254_g2_point();
+        let p_2 = builder.alloc_public_ec_point();
+        let q_2 = builder.alloc_public_bn254_g2_point();
+        builder.bn254_pairing_check(&[(p_1, q_1), (p_2, q_2)]);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let g1 = Bn254::generator();
+        let g2 = G2AffinePoint::generator();
+        let order = Bn254ScalarField::modulus();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint_below(&order);
+
+        // e(a * G1, G2) * e(-G1, a * G2) = 1.
+        let writer = generator.new_writer();
+        writer.write_ec_point(&p_1, &g1.sw_scalar_mul(&a), 0);
+        write_g2_point(&writer, &q_1, &g2, 0);
+        writer.write_ec_point(&p_2, &Bn254::ec_neg(&g1), 0);
+        write_g2_point(&writer, &q_2, &g2.g2_scalar_mul(&a), 0);
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark. The recursive proof is not tested, as the
+        // pairing check has millions of public inputs.
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
@@ -0,0 +1,292 @@
+//! The `ecAdd` (0x06) and `ecMul` (0x07) precompiles.
+//!
+//! The precompiles take affine coordinates and encode the point at infinity as `(0, 0)`, which
+//! is not on the curve. The addition chip is complete: the prover supplies a bit for each of
+//! the exceptional cases, and every bit is forced to its honest value by the constraints.
+//!
+//! - The infinity bits force the point to `(0, 0)` when set, and force it to be on the curve
+//!   otherwise, so inputs rejected by the precompile cannot be proven.
+//! - The equality bits, when set, force the corresponding coordinates to be equal. When they are
+//!   not set, the denominator of the slope is the difference of the coordinates, and the
+//!   division asserts that it is non-zero.
+//!
+//! BN254 has a prime order, so there are no points with `y = 0`, and the tangent of a point is
+//! never vertical. The coordinates are assumed to be reduced modulo the base field modulus.
+//!
+//! Scalar multiplication is done by `EllipticCurveBuilder::scalar_mul_batch`, whose incomplete
+//! additions do not support the point at infinity as an input or as a result.
+
+use num::{BigUint, Zero};
+
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254BaseField, Bn254Parameters};
+use crate::chip::ec::weierstrass::WeierstrassParameters;
+use crate::chip::field::constants::FpConstant;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::trace::writer::TraceWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The encoding `(0, 0)` of the point at infinity.
+pub fn infinity() -> AffinePoint<Bn254> {
+    AffinePoint::new(BigUint::zero(), BigUint::zero())
+}
+
+/// The result of the `ecAdd` precompile on two valid points.
+pub fn ec_add(p: &AffinePoint<Bn254>, q: &AffinePoint<Bn254>) -> AffinePoint<Bn254> {
+    if *p == infinity() {
+        q.clone()
+    } else if *q == infinity() {
+        p.clone()
+    } else if p.x != q.x {
+        p.sw_add(q)
+    } else if p.y == q.y {
+        p.sw_double()
+    } else {
+        infinity()
+    }
+}
+
+/// The result of the `ecMul` precompile on a valid point.
+pub fn ec_mul(p: &AffinePoint<Bn254>, scalar: &BigUint) -> AffinePoint<Bn254> {
+    let scalar = scalar % Bn254Parameters::prime_group_order();
+    if *p == infinity() || scalar.is_zero() {
+        infinity()
+    } else {
+        p.sw_scalar_mul(&scalar)
+    }
+}
+
+/// The registers of an `ecAdd` precompile call.
+#[derive(Debug, Clone, Copy)]
+pub struct ECAddRegister {
+    pub p: AffinePointRegister<Bn254>,
+    pub q: AffinePointRegister<Bn254>,
+    /// Whether `p` is the point at infinity.
+    pub p_is_infinity: BitRegister,
+    /// Whether `q` is the point at infinity.
+    pub q_is_infinity: BitRegister,
+    /// Whether `p` and `q` have the same `x` coordinate.
+    pub x_is_equal: BitRegister,
+    /// Whether `p` and `q` have the same `y` coordinate.
+    pub y_is_equal: BitRegister,
+    pub result: AffinePointRegister<Bn254>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the `ecAdd` precompile on the points `p` and `q`, including the point at
+    /// infinity, doubling and the addition of opposite points.
+    pub fn bn254_ec_add(
+        &mut self,
+        p: &AffinePointRegister<Bn254>,
+        q: &AffinePointRegister<Bn254>,
+    ) -> ECAddRegister
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let p_is_infinity = self.alloc::<BitRegister>();
+        let q_is_infinity = self.alloc::<BitRegister>();
+        let x_is_equal = self.alloc::<BitRegister>();
+        let y_is_equal = self.alloc::<BitRegister>();
+
+        let zero = self.fp_zero::<Bn254BaseField>();
+        let one = self.fp_one::<Bn254BaseField>();
+
+        let p_x_sq = self.bn254_assert_valid_input(p, &p_is_infinity);
+        self.bn254_assert_valid_input(q, &q_is_infinity);
+
+        let dx = self.fp_sub(&q.x, &p.x);
+        let dx_if_equal = self.select(&x_is_equal, &dx, &zero);
+        self.assert_equal(&dx_if_equal, &zero);
+        let dy = self.fp_sub(&q.y, &p.y);
+        let dy_if_equal = self.select(&y_is_equal, &dy, &zero);
+        self.assert_equal(&dy_if_equal, &zero);
+
+        // The slope of the tangent line is `3 * x^2 / (2 * y)`. For opposite points, the
+        // numerator is zero and the denominator `dy = -2 * y` is non-zero.
+        let tangent_numerator = self.fp_mul_constant(&p_x_sq, &FpConstant::from_u64(3));
+        let tangent_denominator = self.fp_add(&p.y, &p.y);
+
+        let same_x_numerator = self.select(&y_is_equal, &tangent_numerator, &zero);
+        let numerator = self.select(&x_is_equal, &same_x_numerator, &dy);
+        let same_x_denominator = self.select(&y_is_equal, &tangent_denominator, &dy);
+        let mut denominator = self.select(&x_is_equal, &same_x_denominator, &dx);
+        denominator = self.select(&q_is_infinity, &one, &denominator);
+        denominator = self.select(&p_is_infinity, &one, &denominator);
+        let slope = self.fp_div(&numerator, &denominator);
+
+        let sum = self.sw_add_with_slope::<Bn254Parameters>(p, q, &slope);
+
+        let [x, y] = [(sum.x, p.x, q.x), (sum.y, p.y, q.y)].map(|(sum, p, q)| {
+            let same_x = self.select(&y_is_equal, &sum, &zero);
+            let mut result = self.select(&x_is_equal, &same_x, &sum);
+            result = self.select(&q_is_infinity, &p, &result);
+            self.select(&p_is_infinity, &q, &result)
+        });
+
+        ECAddRegister {
+            p: *p,
+            q: *q,
+            p_is_infinity,
+            q_is_infinity,
+            x_is_equal,
+            y_is_equal,
+            result: AffinePointRegister::new(x, y),
+        }
+    }
+
+    /// Asserts that `p` is `(0, 0)` if `is_infinity` is set and that it is on the curve
+    /// otherwise, returning `x^2`.
+    fn bn254_assert_valid_input(
+        &mut self,
+        p: &AffinePointRegister<Bn254>,
+        is_infinity: &BitRegister,
+    ) -> FieldRegister<Bn254BaseField>
+    where
+        L::Instruction: FromFieldInstruction<Bn254BaseField>,
+    {
+        let zero = self.fp_zero::<Bn254BaseField>();
+        for coordinate in [p.x, p.y] {
+            let coordinate_if_infinity = self.select(is_infinity, &coordinate, &zero);
+            self.assert_equal(&coordinate_if_infinity, &zero);
+        }
+
+        let b = self.fp_constant(&Bn254Parameters::b_int());
+        let x_sq = self.fp_mul(&p.x, &p.x);
+        let x_cube = self.fp_mul(&x_sq, &p.x);
+        let rhs = self.fp_add(&x_cube, &b);
+        let y_sq = self.fp_mul(&p.y, &p.y);
+        let rhs_if_finite = self.select(is_infinity, &y_sq, &rhs);
+        self.assert_equal(&rhs_if_finite, &y_sq);
+
+        x_sq
+    }
+}
+
+impl ECAddRegister {
+    /// Writes the input points and the bits of the exceptional cases to the row `row_index`.
+    pub fn write<F: PrimeField64>(
+        &self,
+        p: &AffinePoint<Bn254>,
+        q: &AffinePoint<Bn254>,
+        writer: &TraceWriter<F>,
+        row_index: usize,
+    ) {
+        for (register, value) in [(self.p, p), (self.q, q)] {
+            let x = to_u16_le_limbs_polynomial::<F, Bn254BaseField>(&value.x);
+            let y = to_u16_le_limbs_polynomial::<F, Bn254BaseField>(&value.y);
+            writer.write(&register.x, &x, row_index);
+            writer.write(&register.y, &y, row_index);
+        }
+
+        let bits = [
+            (self.p_is_infinity, *p == infinity()),
+            (self.q_is_infinity, *q == infinity()),
+            (self.x_is_equal, p.x == q.x),
+            (self.y_is_equal, p.y == q.y),
+        ];
+        for (register, bit) in bits {
+            writer.write(&register, &F::from_canonical_u8(bit as u8), row_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::EllipticCurve;
+    use crate::chip::field::instruction::FpInstruction;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct ECAddPrecompileTest;
+
+    impl AirParameters for ECAddPrecompileTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2400;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 3612;
+        type Instruction = FpInstruction<Bn254BaseField>;
+    }
+
+    fn random_point() -> AffinePoint<Bn254> {
+        let order = Bn254Parameters::prime_group_order();
+        let scalar = thread_rng().gen_biguint_below(&order);
+        Bn254::generator().sw_scalar_mul(&scalar)
+    }
+
+    #[test]
+    fn test_bn254_precompiles_pure() {
+        let p = random_point();
+        let q = random_point();
+        let order = Bn254Parameters::prime_group_order();
+
+        assert_eq!(ec_add(&p, &q), p.sw_add(&q));
+        assert_eq!(ec_add(&p, &p), p.sw_double());
+        assert_eq!(ec_add(&p, &Bn254::ec_neg(&p)), infinity());
+        assert_eq!(ec_add(&infinity(), &q), q);
+        assert_eq!(ec_add(&p, &infinity()), p);
+        assert_eq!(ec_add(&infinity(), &infinity()), infinity());
+
+        assert_eq!(ec_mul(&p, &BigUint::from(2u32)), p.sw_double());
+        assert_eq!(ec_mul(&p, &(&order + 1u32)), p);
+        assert_eq!(ec_mul(&p, &order), infinity());
+        assert_eq!(ec_mul(&infinity(), &BigUint::from(5u32)), infinity());
+    }
+
+    #[test]
+    fn test_bn254_ec_add() {
+        type L = ECAddPrecompileTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let add = builder.bn254_ec_add(&p, &q);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let p_int = random_point();
+        let q_int = random_point();
+        let cases = [
+            (p_int.clone(), q_int.clone()),
+            (p_int.clone(), p_int.clone()),
+            (p_int.clone(), Bn254::ec_neg(&p_int)),
+            (infinity(), q_int),
+            (p_int, infinity()),
+            (infinity(), infinity()),
+        ];
+        let writer = generator.new_writer();
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            let (p_int, q_int) = &cases[i % cases.len()];
+            add.write(p_int, q_int, &writer, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(writer.read_ec_point(&add.result, i), ec_add(p_int, q_int));
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,23 @@
+//! The tower of extension fields of BN254, with the non-residue `xi = 9 + u`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::weierstrass::bn254::Bn254BaseField;
+use crate::chip::field::extension::{fp12, fp2, fp6, TowerParameters};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// BN254 extension tower parameter
+pub struct Bn254Tower;
+
+impl TowerParameters for Bn254Tower {
+    type BaseField = Bn254BaseField;
+
+    const NONRESIDUE: u16 = 9;
+}
+
+pub type Fp2 = fp2::Fp2<Bn254Tower>;
+pub type Fp2Register = fp2::Fp2Register<Bn254Tower>;
+pub type Fp6 = fp6::Fp6<Bn254Tower>;
+pub type Fp6Register = fp6::Fp6Register<Bn254Tower>;
+pub type Fp12 = fp12::Fp12<Bn254Tower>;
+pub type Fp12Register = fp12::Fp12Register<Bn254Tower>;
@@ -0,0 +1,75 @@
+//! A registry of common curve constants as `FpConstant`s for `fp_mul_constant`.
+
+use super::edwards::ed25519::params::Ed25519BaseField;
+use super::edwards::EdwardsParameters;
+use super::weierstrass::WeierstrassParameters;
+use crate::chip::field::constants::FpConstant;
+
+/// The coefficient `d` of the twisted Edwards curve `-x^2 + y^2 = 1 + d * x^2 * y^2`.
+pub fn edwards_d<E: EdwardsParameters>() -> FpConstant<E::BaseField> {
+    FpConstant::new(&E::d_biguint())
+}
+
+/// The coefficient `a` of the short Weierstrass curve `y^2 = x^3 + a * x + b`.
+pub fn weierstrass_a<E: WeierstrassParameters>() -> FpConstant<E::BaseField> {
+    FpConstant::new(&E::a_int())
+}
+
+/// The coefficient `b` of the short Weierstrass curve `y^2 = x^3 + a * x + b`.
+pub fn weierstrass_b<E: WeierstrassParameters>() -> FpConstant<E::BaseField> {
+    FpConstant::new(&E::b_int())
+}
+
+/// The constant `a24 = (486662 - 2) / 4` of the Montgomery ladder of Curve25519.
+pub fn curve25519_a24() -> FpConstant<Ed25519BaseField> {
+    FpConstant::from_u64(121665)
+}
+
+/// The square root `2^((p - 1) / 4)` of `-1` modulo `p = 2^255 - 19`, which relates a root of
+/// a square and of its twist by `-1` in the decompression of Ed25519 points.
+pub fn ed25519_sqrt_minus_one() -> FpConstant<Ed25519BaseField> {
+    FpConstant::from_hex("2b8324804fc1df0b2b4d00993dfbd7a72f431806ad2fe478c4ee1b274a0ea0b0")
+}
+
+/// A square root of `-486664` modulo `p = 2^255 - 19`, the scaling of the birational map
+/// `x = sqrt(-486664) * u / v` from the Montgomery form of Curve25519 to Ed25519.
+pub fn ed25519_sqrt_minus_486664() -> FpConstant<Ed25519BaseField> {
+    FpConstant::from_hex("0f26edf460a006bbd27b08dc03fc4f7ec5a1d3d14b7d1a82cc6e04aaff457e06")
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, One};
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::params::Ed25519Parameters;
+    use crate::chip::ec::weierstrass::bn254::Bn254Parameters;
+    use crate::chip::field::parameters::FieldParameters;
+
+    #[test]
+    fn test_curve_constants() {
+        let p = Ed25519BaseField::modulus();
+
+        // d = -121665 / 121666.
+        let d = edwards_d::<Ed25519Parameters>().value();
+        assert_eq!((d * 121666u32 + 121665u32) % &p, BigUint::from(0u32));
+
+        assert_eq!(
+            weierstrass_a::<Bn254Parameters>().value(),
+            BigUint::from(0u32)
+        );
+        assert_eq!(
+            weierstrass_b::<Bn254Parameters>().value(),
+            BigUint::from(3u32)
+        );
+        assert_eq!(
+            BigUint::from(4u32) * curve25519_a24().value() + 2u32,
+            BigUint::from(486662u32)
+        );
+
+        let i = ed25519_sqrt_minus_one().value();
+        assert_eq!((&i * &i + BigUint::one()) % &p, BigUint::from(0u32));
+        let s = ed25519_sqrt_minus_486664().value();
+        assert_eq!((&s * &s + 486664u32) % &p, BigUint::from(0u32));
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+
+use super::params::{Ed25519, Ed25519BaseField, Ed25519Parameters};
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::RegisterSerializable;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// Ed25519 point addition. Computes `p + q = result`.
+///
+/// Given two points (x1, y1) and (x2, y2), the sum (x3, y3) is given by the complete twisted
+/// Edwards addition formulas
+///
+/// x3 = (x1 * y2 + x2 * y1) / (1 + d * f)
+/// y3 = (y1 * y2 + x1 * x2) / (1 - d * f)
+///
+/// where f = x1 * x2 * y1 * y2. As `d` is not a square in the base field, the denominators never
+/// vanish on curve points, so the formulas also hold for doubling and for the neutral element.
+///
+/// Reference: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.4
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519AddInstruction {
+    pub p: AffinePointRegister<Ed25519>,
+    pub q: AffinePointRegister<Ed25519>,
+    pub result: AffinePointRegister<Ed25519>,
+    /// a `FpMulInstruction` to compute `x1 * y1`.
+    x1_mul_y1: FpMulInstruction<Ed25519BaseField>,
+    /// a `FpMulInstruction` to compute `x2 * y2`.
+    x2_mul_y2: FpMulInstruction<Ed25519BaseField>,
+    /// a `FpMulInstruction` to compute `f = (x1 * y1) * (x2 * y2)`.
+    f: FpMulInstruction<Ed25519BaseField>,
+    /// a `FpMulConstInstruction` to compute `d * f`.
+    d_mul_f: FpMulConstInstruction<Ed25519BaseField>,
+    /// a `FpInnerProductInstruction` to compute `x1 * y2 + x2 * y1`.
+    x3_numerator: FpInnerProductInstruction<Ed25519BaseField>,
+    /// a `FpInnerProductInstruction` to compute `y1 * y2 + x1 * x2`.
+    y3_numerator: FpInnerProductInstruction<Ed25519BaseField>,
+    /// a `FpDenInstruction` to compute `x3 = x3_numerator / (1 + d * f)`.
+    x3: FpDenInstruction<Ed25519BaseField>,
+    /// a `FpDenInstruction` to compute `y3 = y3_numerator / (1 - d * f)`.
+    y3: FpDenInstruction<Ed25519BaseField>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two points `p` and `q` on the Ed25519 curve, computes the sum `p + q` in a single
+    /// instruction.
+    pub fn ed25519_add(
+        &mut self,
+        p: &AffinePointRegister<Ed25519>,
+        q: &AffinePointRegister<Ed25519>,
+    ) -> AffinePointRegister<Ed25519>
+    where
+        L::Instruction: From<Ed25519AddInstruction>,
+    {
+        let is_trace = p.x.is_trace() || p.y.is_trace() || q.x.is_trace() || q.y.is_trace();
+        let (x1, y1, x2, y2) = (p.x, p.y, q.x, q.y);
+
+        let x1_mul_y1 = FpMulInstruction {
+            a: x1,
+            b: y1,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let x2_mul_y2 = FpMulInstruction {
+            a: x2,
+            b: y2,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let f = FpMulInstruction {
+            a: x1_mul_y1.result,
+            b: x2_mul_y2.result,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let d_mul_f = FpMulConstInstruction {
+            a: f.result,
+            c: Ed25519Parameters::D,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let x3_numerator = FpInnerProductInstruction {
+            a: vec![x1, x2],
+            b: vec![y2, y1],
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let y3_numerator = FpInnerProductInstruction {
+            a: vec![y1, x1],
+            b: vec![y2, x2],
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let x3 = FpDenInstruction {
+            a: x3_numerator.result,
+            b: d_mul_f.result,
+            sign: true,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+        let y3 = FpDenInstruction {
+            a: y3_numerator.result,
+            b: d_mul_f.result,
+            sign: false,
+            result: self.ed25519_add_field_register(is_trace),
+            carry: self.ed25519_add_field_register(is_trace),
+            witness_low: self.ed25519_add_witness(is_trace),
+            witness_high: self.ed25519_add_witness(is_trace),
+        };
+
+        let result = AffinePointRegister::new(x3.result, y3.result);
+        let instr = Ed25519AddInstruction {
+            p: *p,
+            q: *q,
+            result,
+            x1_mul_y1,
+            x2_mul_y2,
+            f,
+            d_mul_f,
+            x3_numerator,
+            y3_numerator,
+            x3,
+            y3,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        result
+    }
+
+    fn ed25519_add_field_register(&mut self, is_trace: bool) -> FieldRegister<Ed25519BaseField> {
+        if is_trace {
+            self.alloc::<FieldRegister<Ed25519BaseField>>()
+        } else {
+            self.alloc_public::<FieldRegister<Ed25519BaseField>>()
+        }
+    }
+
+    fn ed25519_add_witness(&mut self, is_trace: bool) -> ArrayRegister<U16Register> {
+        if is_trace {
+            self.alloc_array::<U16Register>(Ed25519BaseField::NB_WITNESS_LIMBS)
+        } else {
+            self.alloc_array_public::<U16Register>(Ed25519BaseField::NB_WITNESS_LIMBS)
+        }
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Ed25519AddInstruction {
+    fn eval(&self, parser: &mut AP) {
+        self.x1_mul_y1.eval(parser);
+        self.x2_mul_y2.eval(parser);
+        self.f.eval(parser);
+        self.d_mul_f.eval(parser);
+        self.x3_numerator.eval(parser);
+        self.y3_numerator.eval(parser);
+        self.x3.eval(parser);
+        self.y3.eval(parser);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Ed25519AddInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        // Every operation reads the results of the previous ones.
+        self.x1_mul_y1.write(writer, row_index);
+        self.x2_mul_y2.write(writer, row_index);
+        self.f.write(writer, row_index);
+        self.d_mul_f.write(writer, row_index);
+        self.x3_numerator.write(writer, row_index);
+        self.y3_numerator.write(writer, row_index);
+        self.x3.write(writer, row_index);
+        self.y3.write(writer, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        self.x1_mul_y1.write_to_air(writer);
+        self.x2_mul_y2.write_to_air(writer);
+        self.f.write_to_air(writer);
+        self.d_mul_f.write_to_air(writer);
+        self.x3_numerator.write_to_air(writer);
+        self.y3_numerator.write_to_air(writer);
+        self.x3.write_to_air(writer);
+        self.y3.write_to_air(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::EllipticCurve;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Ed25519AddInstructionTest;
+
+    impl AirParameters for Ed25519AddInstructionTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 800;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 1209;
+        type Instruction = Ed25519AddInstruction;
+    }
+
+    #[test]
+    fn test_ed25519_add_instruction() {
+        type L = Ed25519AddInstructionTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Ed25519;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p_pub = builder.alloc_public_ec_point();
+        let q_pub = builder.alloc_public_ec_point();
+        let _r_pub = builder.ed25519_add(&p_pub, &q_pub);
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let r = builder.ed25519_add(&p, &q);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let base = E::ec_generator();
+        let mut rng = thread_rng();
+        let a = rng.gen_biguint(256);
+        let b = rng.gen_biguint(256);
+        let p_int = &base * &a;
+        let q_int = &base * &b;
+        let r_int = &p_int + &q_int;
+        let writer = generator.new_writer();
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &p_int, i);
+            writer.write_ec_point(&q, &q_int, i);
+            writer.write_ec_point(&p_pub, &p_int, i);
+            writer.write_ec_point(&q_pub, &q_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(writer.read_ec_point(&r, i), r_int);
+        });
+
+        writer.write_global_instructions(&generator.air_data);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -1,20 +1,35 @@
 use serde::{Deserialize, Serialize};
 
-use super::params::{Ed25519, Ed25519BaseField};
+use super::add::Ed25519AddInstruction;
+use super::params::{Ed25519, Ed25519BaseField, Ed25519ScalarField};
 use super::sqrt::Ed25519FpSqrtInstruction;
 use crate::air::AirConstraint;
 use crate::chip::ec::scalar::LimbBitInstruction;
 use crate::chip::ec::ECInstruction;
 use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::compare::FpCompareInstruction;
 use crate::chip::field::den::FpDenInstruction;
 use crate::chip::field::div::FpDivInstruction;
 use crate::chip::field::inner_product::FpInnerProductInstruction;
-use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
 use crate::chip::field::mul::FpMulInstruction;
 use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
 use crate::chip::field::sub::FpSubInstruction;
 use crate::chip::instruction::Instruction;
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::count::ByteCountInstruction;
+use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
+use crate::chip::uint::bytes::lookup_table::{ByteInstructionSet, ByteInstructions};
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperationDigestConstraint;
+use crate::chip::uint::operations::add::ByteArrayAdd;
+use crate::chip::uint::operations::instruction::{UintInstruction, UintInstructions};
+use crate::chip::uint::operations::mul::ByteArrayMul;
+use crate::chip::uint::operations::native::U32NativeInstruction;
+use crate::chip::uint::operations::sub::ByteArraySub;
 use crate::math::field::PrimeField64;
 use crate::polynomial::parser::PolynomialParser;
 
@@ -23,16 +38,31 @@ use crate::polynomial::parser::PolynomialParser;
 pub enum Ed25519FpInstruction {
     EC(ECInstruction<Ed25519>),
     Sqrt(Ed25519FpSqrtInstruction),
+    Add(Ed25519AddInstruction),
+    Scalar(FpInstruction<Ed25519ScalarField>),
+    Uint(UintInstruction),
 }
 
 impl FromFieldInstruction<Ed25519BaseField> for Ed25519FpInstruction {}
 
+impl FromFieldInstruction<Ed25519ScalarField> for Ed25519FpInstruction {}
+
+impl ByteInstructions for Ed25519FpInstruction {}
+
+impl UintInstructions for Ed25519FpInstruction {}
+
 impl From<Ed25519FpSqrtInstruction> for Ed25519FpInstruction {
     fn from(i: Ed25519FpSqrtInstruction) -> Self {
         Self::Sqrt(i)
     }
 }
 
+impl From<Ed25519AddInstruction> for Ed25519FpInstruction {
+    fn from(i: Ed25519AddInstruction) -> Self {
+        Self::Add(i)
+    }
+}
+
 impl<AP: PolynomialParser> AirConstraint<AP> for Ed25519FpInstruction {
     fn eval(&self, parser: &mut AP) {
         match self {
@@ -40,6 +70,15 @@ impl<AP: PolynomialParser> AirConstraint<AP> for Ed25519FpInstruction {
             Ed25519FpInstruction::Sqrt(instruction) => {
                 AirConstraint::<AP>::eval(instruction, parser)
             }
+            Ed25519FpInstruction::Add(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Ed25519FpInstruction::Scalar(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Ed25519FpInstruction::Uint(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
         }
     }
 }
@@ -53,6 +92,15 @@ impl<F: PrimeField64> Instruction<F> for Ed25519FpInstruction {
             Ed25519FpInstruction::Sqrt(instruction) => {
                 Instruction::<F>::write(instruction, writer, row_index)
             }
+            Ed25519FpInstruction::Add(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Ed25519FpInstruction::Scalar(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Ed25519FpInstruction::Uint(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
         }
     }
 
@@ -64,6 +112,15 @@ impl<F: PrimeField64> Instruction<F> for Ed25519FpInstruction {
             Ed25519FpInstruction::Sqrt(instruction) => {
                 Instruction::<F>::write_to_air(instruction, writer)
             }
+            Ed25519FpInstruction::Add(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Ed25519FpInstruction::Scalar(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Ed25519FpInstruction::Uint(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
         }
     }
 }
@@ -80,38 +137,188 @@ impl From<FpAddInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     }
 }
 
+impl From<FpAddInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpAddInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpMulInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpMulInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
 
+impl From<FpMulInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpMulInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpSubInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpSubInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
 
+impl From<FpSubInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpSubInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpDivInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpDivInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
 
+impl From<FpDivInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpDivInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpDenInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpDenInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
 
+impl From<FpDenInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpDenInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpInnerProductInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpInnerProductInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
 
+impl From<FpInnerProductInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpInnerProductInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
 impl From<FpMulConstInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
     fn from(i: FpMulConstInstruction<Ed25519BaseField>) -> Self {
         Self::EC(i.into())
     }
 }
+
+impl From<FpMulConstInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpMulConstInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpNegInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
+    fn from(i: FpNegInstruction<Ed25519BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpNegInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpNegInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
+    fn from(i: FpMulSubInstruction<Ed25519BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpMulSubInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInvInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
+    fn from(i: FpInvInstruction<Ed25519BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInvInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpInvInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpCompareInstruction<Ed25519BaseField>> for Ed25519FpInstruction {
+    fn from(i: FpCompareInstruction<Ed25519BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpCompareInstruction<Ed25519ScalarField>> for Ed25519FpInstruction {
+    fn from(i: FpCompareInstruction<Ed25519ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<UintInstruction> for Ed25519FpInstruction {
+    fn from(i: UintInstruction) -> Self {
+        Self::Uint(i)
+    }
+}
+
+impl From<ByteInstructionSet> for Ed25519FpInstruction {
+    fn from(i: ByteInstructionSet) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationInstruction> for Ed25519FpInstruction {
+    fn from(i: ByteOperationInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteDecodeInstruction> for Ed25519FpInstruction {
+    fn from(i: ByteDecodeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationDigestConstraint> for Ed25519FpInstruction {
+    fn from(i: ByteOperationDigestConstraint) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayAdd<4>> for Ed25519FpInstruction {
+    fn from(i: ByteArrayAdd<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArraySub<4>> for Ed25519FpInstruction {
+    fn from(i: ByteArraySub<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayMul> for Ed25519FpInstruction {
+    fn from(i: ByteArrayMul) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<U32NativeInstruction> for Ed25519FpInstruction {
+    fn from(i: U32NativeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteCountInstruction> for Ed25519FpInstruction {
+    fn from(i: ByteCountInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
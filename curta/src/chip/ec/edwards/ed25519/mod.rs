@@ -1,3 +1,4 @@
+pub mod add;
 pub mod decompress;
 pub mod gadget;
 pub mod instruction;
@@ -2,6 +2,7 @@ use super::params::Ed25519BaseField;
 use crate::chip::field::register::FieldRegister;
 use crate::chip::register::bit::BitRegister;
 
+#[derive(Debug, Clone, Copy)]
 pub struct CompressedPointRegister {
     pub sign: BitRegister,
     pub y: FieldRegister<Ed25519BaseField>,
@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+
+use super::params::{Jubjub, JubjubBaseField, JubjubScalarField};
+use crate::air::AirConstraint;
+use crate::chip::ec::scalar::LimbBitInstruction;
+use crate::chip::ec::ECInstruction;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR doing arithmetic over both the base field and the scalar field of
+/// Jubjub, as needed to verify RedJubjub signatures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum JubjubInstruction {
+    EC(ECInstruction<Jubjub>),
+    Scalar(FpInstruction<JubjubScalarField>),
+}
+
+impl FromFieldInstruction<JubjubBaseField> for JubjubInstruction {}
+
+impl FromFieldInstruction<JubjubScalarField> for JubjubInstruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for JubjubInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            JubjubInstruction::EC(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            JubjubInstruction::Scalar(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for JubjubInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            JubjubInstruction::EC(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            JubjubInstruction::Scalar(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            JubjubInstruction::EC(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            JubjubInstruction::Scalar(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<LimbBitInstruction> for JubjubInstruction {
+    fn from(i: LimbBitInstruction) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpAddInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpAddInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpMulInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpMulInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpSubInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpSubInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpSubInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpSubInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDivInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpDivInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDivInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpDivInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDenInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpDenInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDenInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpDenInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpInnerProductInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpInnerProductInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpMulConstInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpMulConstInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpNegInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpNegInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpNegInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpNegInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpMulSubInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpMulSubInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInvInstruction<JubjubBaseField>> for JubjubInstruction {
+    fn from(i: FpInvInstruction<JubjubBaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInvInstruction<JubjubScalarField>> for JubjubInstruction {
+    fn from(i: FpInvInstruction<JubjubScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::EllipticCurve;
+    use crate::chip::field::parameters::FieldParameters;
+    use crate::chip::field::register::FieldRegister;
+    use crate::chip::AirParameters;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct JubjubTest;
+
+    impl AirParameters for JubjubTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1000;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 1509;
+        type Instruction = JubjubInstruction;
+    }
+
+    #[test]
+    fn test_jubjub_add() {
+        type F = GoldilocksField;
+        type L = JubjubTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type E = Jubjub;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        builder.ed_assert_valid(&p);
+        builder.ed_assert_valid(&q);
+        let sum = builder.ec_add::<E>(&p, &q);
+        let expected = builder.alloc_ec_point();
+        builder.assert_equal(&sum.x, &expected.x);
+        builder.assert_equal(&sum.y, &expected.y);
+
+        // A product in the scalar field, as used by the signature challenges.
+        let a = builder.alloc::<FieldRegister<JubjubScalarField>>();
+        let b = builder.alloc::<FieldRegister<JubjubScalarField>>();
+        let ab = builder.fp_mul(&a, &b);
+        let expected_ab = builder.alloc::<FieldRegister<JubjubScalarField>>();
+        builder.assert_equal(&ab, &expected_ab);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let base = E::ec_generator();
+        let order = JubjubScalarField::modulus();
+        let mut rng = thread_rng();
+        let p_int = &base * &rng.gen_biguint_below(&order);
+        let q_int = &base * &rng.gen_biguint_below(&order);
+        let sum_int = &p_int + &q_int;
+        let a_int = rng.gen_biguint_below(&order);
+        let b_int = rng.gen_biguint_below(&order);
+        let ab_int = (&a_int * &b_int) % &order;
+        let to_limbs = to_u16_le_limbs_polynomial::<F, JubjubScalarField>;
+        let writer = generator.new_writer();
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &p_int, i);
+            writer.write_ec_point(&q, &q_int, i);
+            writer.write_ec_point(&expected, &sum_int, i);
+            writer.write(&a, &to_limbs(&a_int), i);
+            writer.write(&b, &to_limbs(&b_int), i);
+            writer.write(&expected_ab, &to_limbs(&ab_int), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        writer.write_global_instructions(&generator.air_data);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
@@ -0,0 +1,13 @@
+//! The Jubjub curve `-x^2 + y^2 = 1 + d * x^2 * y^2`, with `d = -(10240/10241)`, defined over the
+//! scalar field of BLS12-381.
+//!
+//! Jubjub is the embedded curve of Zcash Sapling, used for the Pedersen hashes of note
+//! commitments and for the RedJubjub spend authorization signatures. It is a twisted Edwards
+//! curve with `a = -1`, so the generic `EdwardsCurve` chips apply, as they do for any other curve
+//! with `a = -1` over the same field. The group has order `8 * r` for a 252-bit prime `r`, and
+//! the generator is taken in the subgroup of order `r`.
+//!
+//! Reference: https://zips.z.cash/protocol/protocol.pdf, section 5.4.9.3
+
+pub mod instruction;
+pub mod params;
@@ -0,0 +1,98 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+use crate::chip::ec::edwards::{EdwardsCurve, EdwardsParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+pub type Jubjub = EdwardsCurve<JubjubParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Jubjub curve parameter
+pub struct JubjubParameters;
+
+/// The base field of Jubjub is the scalar field of BLS12-381.
+pub type JubjubBaseField = Bls12381ScalarField;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Jubjub scalar field parameter, the field of integers modulo the prime subgroup order
+pub struct JubjubScalarField;
+
+impl FieldParameters for JubjubScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  6554484396890773809930967563523245729705921265872317281365359162392183254199
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        11447, 55031, 3678, 53399, 4226, 52424, 8339, 42600, 15104, 308, 15105, 1639, 44969, 25907,
+        46314, 3709, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for JubjubParameters {
+    type BaseField = JubjubBaseField;
+}
+
+impl EdwardsParameters for JubjubParameters {
+    // d = -(10240/10241)
+    const D: [u16; MAX_NB_LIMBS] = [
+        16049, 54836, 24534, 262, 40230, 14167, 32621, 10541, 32724, 59069, 37383, 62973, 11080,
+        19450, 6375, 10899, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn prime_group_order() -> BigUint {
+        JubjubScalarField::modulus()
+    }
+
+    /// The point `8 * (x, 11)`, the cofactor multiple of the point of y-coordinate 11 used as
+    /// the generator of the full group by the `jubjub` crate.
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "28336281903124990867587793011069573392383982287722241916350956173377953689573",
+            10,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "39385640392217313770878525135509063452020585410343666726093009378539878503883",
+            10,
+        )
+        .unwrap();
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::ec::EllipticCurve;
+
+    #[test]
+    fn test_jubjub_parameters() {
+        let p = JubjubBaseField::modulus();
+        let d = JubjubParameters::d_biguint();
+        assert_eq!((&d * 10241u32 + 10240u32) % &p, BigUint::from(0u32));
+
+        let order = JubjubScalarField::modulus();
+        assert_eq!(order.bits(), 252);
+
+        let (x, y) = JubjubParameters::generator();
+        let x_sq = (&x * &x) % &p;
+        let y_sq = (&y * &y) % &p;
+        let lhs = (&y_sq + &p - &x_sq) % &p;
+        let rhs = (1u32 + &d * &x_sq % &p * &y_sq) % &p;
+        assert_eq!(lhs, rhs);
+
+        // The generator has order `r`.
+        let g = Jubjub::ec_generator();
+        let minus_g = &g * &(&order - 1u32);
+        assert_eq!(minus_g, Jubjub::ec_neg(&g));
+        assert_eq!(&g * &order, Jubjub::neutral());
+    }
+}
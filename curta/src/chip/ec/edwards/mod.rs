@@ -11,6 +11,7 @@ pub mod add;
 pub mod assert_valid;
 pub mod bigint_operations;
 pub mod ed25519;
+pub mod jubjub;
 
 pub trait EdwardsParameters: EllipticCurveParameters {
     const D: [u16; MAX_NB_LIMBS];
@@ -4,12 +4,17 @@ use super::scalar::LimbBitInstruction;
 use super::EllipticCurve;
 use crate::air::AirConstraint;
 use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::compare::FpCompareInstruction;
 use crate::chip::field::den::FpDenInstruction;
 use crate::chip::field::div::FpDivInstruction;
 use crate::chip::field::inner_product::FpInnerProductInstruction;
 use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
 use crate::chip::field::mul::FpMulInstruction;
 use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sqrt::FpSqrtInstruction;
 use crate::chip::field::sub::FpSubInstruction;
 use crate::chip::instruction::Instruction;
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
@@ -107,3 +112,33 @@ impl<E: EllipticCurve> From<FpMulConstInstruction<E::BaseField>> for ECInstructi
         Self::Fp(i.into())
     }
 }
+
+impl<E: EllipticCurve> From<FpNegInstruction<E::BaseField>> for ECInstruction<E> {
+    fn from(i: FpNegInstruction<E::BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl<E: EllipticCurve> From<FpMulSubInstruction<E::BaseField>> for ECInstruction<E> {
+    fn from(i: FpMulSubInstruction<E::BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl<E: EllipticCurve> From<FpInvInstruction<E::BaseField>> for ECInstruction<E> {
+    fn from(i: FpInvInstruction<E::BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl<E: EllipticCurve> From<FpCompareInstruction<E::BaseField>> for ECInstruction<E> {
+    fn from(i: FpCompareInstruction<E::BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
+
+impl<E: EllipticCurve> From<FpSqrtInstruction<E::BaseField>> for ECInstruction<E> {
+    fn from(i: FpSqrtInstruction<E::BaseField>) -> Self {
+        Self::Fp(i.into())
+    }
+}
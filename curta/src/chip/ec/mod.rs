@@ -10,13 +10,19 @@ use super::AirParameters;
 use crate::machine::builder::ops::{Add, Double};
 use crate::machine::builder::Builder;
 
+pub mod bls12_381;
+pub mod bn254;
+pub mod constants;
 pub mod edwards;
 pub mod gadget;
 mod instruction_set;
+pub mod p256;
 pub mod point;
 pub mod scalar;
 pub mod scalar_mul;
+pub mod secp256k1;
 pub mod weierstrass;
+pub mod x25519;
 
 pub use instruction_set::{ECInstruction, ECInstructions};
 
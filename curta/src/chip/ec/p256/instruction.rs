@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use super::params::{P256BaseField, P256ScalarField, P256};
+use crate::air::AirConstraint;
+use crate::chip::ec::scalar::LimbBitInstruction;
+use crate::chip::ec::ECInstruction;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR doing arithmetic over both the base field and the scalar field of
+/// NIST P-256, as needed to verify ECDSA signatures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum P256Instruction {
+    EC(ECInstruction<P256>),
+    Scalar(FpInstruction<P256ScalarField>),
+}
+
+impl FromFieldInstruction<P256BaseField> for P256Instruction {}
+
+impl FromFieldInstruction<P256ScalarField> for P256Instruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for P256Instruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            P256Instruction::EC(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            P256Instruction::Scalar(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for P256Instruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            P256Instruction::EC(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            P256Instruction::Scalar(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            P256Instruction::EC(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            P256Instruction::Scalar(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<LimbBitInstruction> for P256Instruction {
+    fn from(i: LimbBitInstruction) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpAddInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpAddInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpMulInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpMulInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpSubInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpSubInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpSubInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpSubInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDivInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpDivInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDivInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpDivInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDenInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpDenInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDenInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpDenInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpInnerProductInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpInnerProductInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpMulConstInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpMulConstInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpNegInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpNegInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpNegInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpNegInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpMulSubInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpMulSubInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInvInstruction<P256BaseField>> for P256Instruction {
+    fn from(i: FpInvInstruction<P256BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInvInstruction<P256ScalarField>> for P256Instruction {
+    fn from(i: FpInvInstruction<P256ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
@@ -0,0 +1,10 @@
+//! The NIST P-256 curve `y^2 = x^3 - 3x + b`, also known as secp256r1.
+//!
+//! P-256 is the mandatory curve of WebAuthn, so proving ECDSA signatures over it allows proving
+//! passkey assertions. The curve is a short Weierstrass curve, so the generic `SWCurve` chips
+//! apply, with the doubling using the `a = -3` coefficient.
+//!
+//! Reference: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-186.pdf
+
+pub mod instruction;
+pub mod params;
@@ -0,0 +1,112 @@
+use num::{BigUint, Num};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// NIST P-256 curve parameter
+pub struct P256Parameters;
+
+pub type P256 = SWCurve<P256Parameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// NIST P-256 base field parameter
+pub struct P256BaseField;
+
+impl FieldParameters for P256BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Base field modulus:
+    //  115792089210356248762697446949407573530086143415290314195533631308867097853951
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        65535, 65535, 65535, 65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 1, 0, 65535, 65535, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    // As for secp256k1, the modulus fills all the limbs.
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// NIST P-256 scalar field parameter
+pub struct P256ScalarField;
+
+impl FieldParameters for P256ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  115792089210356248762697446949407573529996955224135760342422259061068512044369
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        9553, 64611, 51906, 62393, 40580, 42775, 64173, 48358, 65535, 65535, 65535, 65535, 0, 0,
+        65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+impl EllipticCurveParameters for P256Parameters {
+    type BaseField = P256BaseField;
+}
+
+impl WeierstrassParameters for P256Parameters {
+    // `a = -3`, represented by `p - 3`.
+    const A: [u16; MAX_NB_LIMBS] = [
+        65532, 65535, 65535, 65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 1, 0, 65535, 65535, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const B: [u16; MAX_NB_LIMBS] = [
+        24651, 10194, 15422, 15310, 45302, 52307, 1712, 25885, 34492, 30360, 48469, 46059, 37863,
+        43578, 13784, 23238, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "48439561293906451759052585252797914202762949526041747995844080717082404635286",
+            10,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "36134250956749795798585127919587881956611106672985015071877198253568414405109",
+            10,
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    fn prime_group_order() -> BigUint {
+        P256ScalarField::modulus()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p256_generator() {
+        let p = P256BaseField::modulus();
+        assert_eq!(P256Parameters::a_int() + 3u32, p);
+
+        let (x, y) = P256Parameters::generator();
+        let lhs = (&y * &y) % &p;
+        let rhs = (&x * &x * &x + P256Parameters::a_int() * &x + P256Parameters::b_int()) % &p;
+        assert_eq!(lhs, rhs);
+
+        let g = P256::generator();
+        let order = P256Parameters::prime_group_order();
+        let p_minus_g = g.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(p_minus_g.x, g.x);
+        assert_eq!(p_minus_g.y, &p - &g.y);
+    }
+}
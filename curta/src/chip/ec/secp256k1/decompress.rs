@@ -0,0 +1,232 @@
+//! Decompression of SEC1 compressed points `0x02 || x` and `0x03 || x`, where `x` is the
+//! big-endian x-coordinate and the prefix gives the parity of the y-coordinate.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::params::{Secp256k1, Secp256k1BaseField, Secp256k1Parameters};
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::weierstrass::WeierstrassParameters;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sqrt::{sqrt, FpSqrtInstruction};
+use crate::chip::register::bit::BitRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The length of a SEC1 compressed point.
+pub const COMPRESSED_POINT_BYTES: usize = 33;
+
+/// A compressed point of secp256k1, given by its x-coordinate and the parity of its
+/// y-coordinate.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Secp256k1CompressedPointRegister {
+    pub x: FieldRegister<Secp256k1BaseField>,
+    pub is_odd: BitRegister,
+}
+
+impl Secp256k1CompressedPointRegister {
+    pub fn new(x: FieldRegister<Secp256k1BaseField>, is_odd: BitRegister) -> Self {
+        Self { x, is_odd }
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_secp256k1_compressed_point(&mut self) -> Secp256k1CompressedPointRegister {
+        Secp256k1CompressedPointRegister::new(self.alloc(), self.alloc())
+    }
+
+    pub fn alloc_public_secp256k1_compressed_point(&mut self) -> Secp256k1CompressedPointRegister {
+        Secp256k1CompressedPointRegister::new(self.alloc_public(), self.alloc_public())
+    }
+
+    /// Computes the point of x-coordinate `x` whose y-coordinate has the parity `is_odd`.
+    ///
+    /// The square root `y` of `x^3 + 7` is constrained to be reduced and of parity `is_odd`, so
+    /// there is no valid trace if `x` is not the x-coordinate of a point. As for the other field
+    /// chips, `x` is not constrained to be reduced modulo the field order.
+    pub fn secp256k1_decompress(
+        &mut self,
+        compressed: &Secp256k1CompressedPointRegister,
+    ) -> AffinePointRegister<Secp256k1>
+    where
+        L::Instruction:
+            FromFieldInstruction<Secp256k1BaseField> + From<FpSqrtInstruction<Secp256k1BaseField>>,
+    {
+        let x = compressed.x;
+        let seven = self.fp_constant(&Secp256k1Parameters::b_int());
+        let x_sq = self.fp_mul(&x, &x);
+        let x_cube = self.fp_mul(&x_sq, &x);
+        let y_sq = self.fp_add(&x_cube, &seven);
+        let y = self.fp_sqrt(&y_sq, &compressed.is_odd);
+
+        AffinePointRegister::new(x, y)
+    }
+}
+
+pub trait Secp256k1CompressedPointAirWriter: AirWriter {
+    /// Writes the SEC1 compressed point `value`, which must have a valid prefix.
+    fn write_secp256k1_compressed_point(
+        &mut self,
+        data: &Secp256k1CompressedPointRegister,
+        value: &[u8; COMPRESSED_POINT_BYTES],
+    ) {
+        let is_odd = match value[0] {
+            0x02 => false,
+            0x03 => true,
+            prefix => panic!("invalid compressed point prefix {:#04x}", prefix),
+        };
+        let x = BigUint::from_bytes_be(&value[1..]);
+        self.write(&data.is_odd, &Self::Field::from_bool(is_odd));
+        self.write(
+            &data.x,
+            &to_u16_le_limbs_polynomial::<Self::Field, Secp256k1BaseField>(&x),
+        );
+    }
+}
+
+impl<W: AirWriter> Secp256k1CompressedPointAirWriter for W {}
+
+/// The point of x-coordinate `x` whose y-coordinate has the parity `is_odd`, if it exists.
+pub fn lift_x(x: &BigUint, is_odd: bool) -> Option<AffinePoint<Secp256k1>> {
+    let p = Secp256k1BaseField::modulus();
+    if x >= &p {
+        return None;
+    }
+    let y_sq = (x * x * x + Secp256k1Parameters::b_int()) % &p;
+    let y = sqrt::<Secp256k1BaseField>(&y_sq)?;
+    let y = if y.bit(0) == is_odd { y } else { (&p - y) % &p };
+    Some(AffinePoint::new(x.clone(), y))
+}
+
+/// Decompresses the SEC1 compressed point `bytes`, returning `None` if it is not a valid
+/// encoding of a point.
+pub fn decompress(bytes: &[u8; COMPRESSED_POINT_BYTES]) -> Option<AffinePoint<Secp256k1>> {
+    let is_odd = match bytes[0] {
+        0x02 => false,
+        0x03 => true,
+        _ => return None,
+    };
+    lift_x(&BigUint::from_bytes_be(&bytes[1..]), is_odd)
+}
+
+/// Compresses `point` to its SEC1 encoding.
+pub fn compress(point: &AffinePoint<Secp256k1>) -> [u8; COMPRESSED_POINT_BYTES] {
+    let mut bytes = [0u8; COMPRESSED_POINT_BYTES];
+    bytes[0] = if point.y.bit(0) { 0x03 } else { 0x02 };
+    let x = point.x.to_bytes_be();
+    bytes[COMPRESSED_POINT_BYTES - x.len()..].copy_from_slice(&x);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::secp256k1::instruction::Secp256k1Instruction;
+    use crate::chip::ec::secp256k1::params::Secp256k1ScalarField;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Secp256k1DecompressTest;
+
+    impl AirParameters for Secp256k1DecompressTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 500;
+        const NUM_FREE_COLUMNS: usize = 21;
+        const EXTENDED_COLUMNS: usize = 810;
+
+        type Instruction = Secp256k1Instruction;
+    }
+
+    #[test]
+    fn test_secp256k1_compress() {
+        let mut rng = thread_rng();
+        let order = Secp256k1ScalarField::modulus();
+        let generator = Secp256k1::generator();
+
+        // The SEC1 encoding of the generator.
+        let expected =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        assert_eq!(compress(&generator).as_slice(), expected.as_slice());
+
+        for _ in 0..10 {
+            let point = generator.sw_scalar_mul(&rng.gen_biguint_below(&order));
+            let bytes = compress(&point);
+            assert_eq!(decompress(&bytes), Some(point));
+        }
+
+        let mut bytes = compress(&generator);
+        bytes[0] = 0x04;
+        assert_eq!(decompress(&bytes), None);
+        // `x = 5` is not the x-coordinate of a point, as `5^3 + 7` is not a square.
+        assert_eq!(lift_x(&BigUint::from(5u32), false), None);
+    }
+
+    #[test]
+    fn test_secp256k1_decompress() {
+        type F = GoldilocksField;
+        type L = Secp256k1DecompressTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let compressed_pub = builder.alloc_public_secp256k1_compressed_point();
+        let point_pub = builder.secp256k1_decompress(&compressed_pub);
+        let expected_pub = builder.alloc_public_ec_point();
+        builder.assert_equal(&point_pub.x, &expected_pub.x);
+        builder.assert_equal(&point_pub.y, &expected_pub.y);
+
+        let compressed = builder.alloc_secp256k1_compressed_point();
+        let point = builder.secp256k1_decompress(&compressed);
+        let expected = builder.alloc_ec_point();
+        builder.assert_equal(&point.x, &expected.x);
+        builder.assert_equal(&point.y, &expected.y);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let order = Secp256k1ScalarField::modulus();
+        let points = (0..16)
+            .map(|_| Secp256k1::generator().sw_scalar_mul(&rng.gen_biguint_below(&order)))
+            .collect::<Vec<_>>();
+        let writer = generator.new_writer();
+        writer.write(
+            &compressed_pub.x,
+            &to_u16_le_limbs_polynomial::<F, Secp256k1BaseField>(&points[0].x),
+            0,
+        );
+        writer.write(&compressed_pub.is_odd, &F::from_bool(points[0].y.bit(0)), 0);
+        writer.write_ec_point(&expected_pub, &points[0], 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            let point = &points[i % points.len()];
+            writer.write(
+                &compressed.x,
+                &to_u16_le_limbs_polynomial::<F, Secp256k1BaseField>(&point.x),
+                i,
+            );
+            writer.write(&compressed.is_odd, &F::from_bool(point.y.bit(0)), i);
+            writer.write_ec_point(&expected, point, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
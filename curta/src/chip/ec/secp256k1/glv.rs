@@ -0,0 +1,130 @@
+//! The GLV endomorphism of secp256k1.
+//!
+//! As `p = 1 mod 3`, the base field has a nontrivial cube root of unity `beta` and the map
+//! `phi(x, y) = (beta * x, y)` is an endomorphism of the curve acting as the multiplication by a
+//! scalar `lambda`. A scalar `k` is decomposed as `k = k1 + k2 * lambda mod n` with `k1` and `k2`
+//! of at most 128 bits, so that `[k]P = [k1]P + [k2]phi(P)` takes half as many doublings.
+//!
+//! Reference: https://link.springer.com/chapter/10.1007/3-540-44647-8_11
+
+use num::{BigUint, Num};
+
+use super::params::{Secp256k1, Secp256k1BaseField, Secp256k1ScalarField};
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::utils::digits_to_biguint;
+
+/// The number of bits of the scalars of a GLV decomposition.
+pub const GLV_SCALAR_BITS: usize = 128;
+
+/// The cube root of unity `beta` of the base field.
+pub const BETA: [u16; MAX_NB_LIMBS] = [
+    494, 29077, 27688, 49465, 35221, 4853, 18805, 40176, 13545, 44084, 18334, 28260, 1808, 25980,
+    27179, 31465, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// The eigenvalue `lambda` of the endomorphism, a cube root of unity of the scalar field.
+pub const LAMBDA: [u16; MAX_NB_LIMBS] = [
+    48498, 6947, 38524, 57090, 26232, 8321, 8938, 4654, 25690, 34834, 7170, 42278, 12512, 49244,
+    44364, 21347, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// A decomposition `k = (-1)^k1_neg * k1 + (-1)^k2_neg * k2 * lambda mod n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GLVDecomposition {
+    pub k1: BigUint,
+    pub k1_neg: bool,
+    pub k2: BigUint,
+    pub k2_neg: bool,
+}
+
+/// Applies the endomorphism `phi(x, y) = (beta * x, y)` to `p`.
+pub fn endomorphism(p: &AffinePoint<Secp256k1>) -> AffinePoint<Secp256k1> {
+    let modulus = Secp256k1BaseField::modulus();
+    let x = (&p.x * digits_to_biguint(&BETA)) % &modulus;
+    AffinePoint::new(x, p.y.clone())
+}
+
+/// Decomposes `k` into two scalars of at most `GLV_SCALAR_BITS` bits.
+///
+/// This rounds `k` to the closest vector of the lattice of the decompositions of zero, spanned by
+/// `(a1, b1)` and `(a2, b2)`, as done in libsecp256k1.
+pub fn glv_decompose(k: &BigUint) -> GLVDecomposition {
+    let n = Secp256k1ScalarField::modulus();
+    let int = |value: &str| BigUint::from_str_radix(value, 16).unwrap();
+    let a1 = int("3086d221a7d46bcde86c90e49284eb15");
+    // `b1` is negative, so this is `-b1`.
+    let minus_b1 = int("e4437ed6010e88286f547fa90abfe4c3");
+    let a2 = int("114ca50f7a8e2f3f657c1108d9d44cfd8");
+    let b2 = a1.clone();
+
+    let k = k % &n;
+    let half_n = &n >> 1;
+    let c1 = (&b2 * &k + &half_n) / &n;
+    let c2 = (&minus_b1 * &k + &half_n) / &n;
+
+    let reduce = |positive: BigUint, negative: BigUint| {
+        let value = (positive + &n - negative % &n) % &n;
+        if value > half_n {
+            (&n - value, true)
+        } else {
+            (value, false)
+        }
+    };
+    let (k1, k1_neg) = reduce(k.clone(), &c1 * &a1 + &c2 * &a2);
+    let (k2, k2_neg) = reduce(&c1 * &minus_b1, &c2 * &b2);
+
+    GLVDecomposition {
+        k1,
+        k1_neg,
+        k2,
+        k2_neg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::One;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_glv_endomorphism() {
+        let g = Secp256k1::generator();
+        assert_eq!(
+            endomorphism(&g),
+            g.sw_scalar_mul(&digits_to_biguint(&LAMBDA))
+        );
+
+        let beta = digits_to_biguint(&BETA);
+        let p = Secp256k1BaseField::modulus();
+        assert_eq!(beta.modpow(&BigUint::from(3u32), &p), BigUint::one());
+    }
+
+    #[test]
+    fn test_glv_decompose() {
+        let n = Secp256k1ScalarField::modulus();
+        let lambda = digits_to_biguint(&LAMBDA);
+        let signed = |value: &BigUint, neg: bool| if neg { &n - value } else { value.clone() };
+
+        let mut rng = thread_rng();
+        let edge_cases = [
+            BigUint::from(0u32),
+            BigUint::one(),
+            &n - 1u32,
+            lambda.clone(),
+        ];
+        for k in edge_cases
+            .into_iter()
+            .chain((0..1000).map(|_| rng.gen_biguint(256) % &n))
+        {
+            let d = glv_decompose(&k);
+            assert!(d.k1.bits() as usize <= GLV_SCALAR_BITS);
+            assert!(d.k2.bits() as usize <= GLV_SCALAR_BITS);
+            let recomposed = (signed(&d.k1, d.k1_neg) + signed(&d.k2, d.k2_neg) * &lambda) % &n;
+            assert_eq!(recomposed, k);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+
+use super::params::{Secp256k1, Secp256k1BaseField, Secp256k1ScalarField};
+use crate::air::AirConstraint;
+use crate::chip::ec::scalar::LimbBitInstruction;
+use crate::chip::ec::ECInstruction;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::{FpInstruction, FromFieldInstruction};
+use crate::chip::field::inv::FpInvInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sqrt::FpSqrtInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::count::ByteCountInstruction;
+use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
+use crate::chip::uint::bytes::lookup_table::{ByteInstructionSet, ByteInstructions};
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperationDigestConstraint;
+use crate::chip::uint::operations::add::ByteArrayAdd;
+use crate::chip::uint::operations::instruction::{UintInstruction, UintInstructions};
+use crate::chip::uint::operations::mul::ByteArrayMul;
+use crate::chip::uint::operations::native::U32NativeInstruction;
+use crate::chip::uint::operations::sub::ByteArraySub;
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR doing arithmetic over both the base field and the scalar field of
+/// secp256k1, as needed to verify ECDSA signatures and to recover public keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Secp256k1Instruction {
+    EC(ECInstruction<Secp256k1>),
+    Scalar(FpInstruction<Secp256k1ScalarField>),
+    Uint(UintInstruction),
+}
+
+impl FromFieldInstruction<Secp256k1BaseField> for Secp256k1Instruction {}
+
+impl FromFieldInstruction<Secp256k1ScalarField> for Secp256k1Instruction {}
+
+impl ByteInstructions for Secp256k1Instruction {}
+
+impl UintInstructions for Secp256k1Instruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Secp256k1Instruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            Secp256k1Instruction::EC(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            Secp256k1Instruction::Scalar(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Secp256k1Instruction::Uint(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Secp256k1Instruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            Secp256k1Instruction::EC(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Secp256k1Instruction::Scalar(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Secp256k1Instruction::Uint(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            Secp256k1Instruction::EC(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Secp256k1Instruction::Scalar(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Secp256k1Instruction::Uint(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<LimbBitInstruction> for Secp256k1Instruction {
+    fn from(i: LimbBitInstruction) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpAddInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpAddInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpMulInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpMulInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpSubInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpSubInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpSubInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpSubInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDivInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpDivInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDivInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpDivInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpDenInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpDenInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDenInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpDenInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpInnerProductInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpInnerProductInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpMulConstInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpMulConstInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpNegInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpNegInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpNegInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpNegInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpMulSubInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpMulSubInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpInvInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpInvInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInvInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpInvInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpCompareInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpCompareInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpCompareInstruction<Secp256k1ScalarField>> for Secp256k1Instruction {
+    fn from(i: FpCompareInstruction<Secp256k1ScalarField>) -> Self {
+        Self::Scalar(i.into())
+    }
+}
+
+impl From<FpSqrtInstruction<Secp256k1BaseField>> for Secp256k1Instruction {
+    fn from(i: FpSqrtInstruction<Secp256k1BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<UintInstruction> for Secp256k1Instruction {
+    fn from(i: UintInstruction) -> Self {
+        Self::Uint(i)
+    }
+}
+
+impl From<ByteInstructionSet> for Secp256k1Instruction {
+    fn from(i: ByteInstructionSet) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationInstruction> for Secp256k1Instruction {
+    fn from(i: ByteOperationInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteDecodeInstruction> for Secp256k1Instruction {
+    fn from(i: ByteDecodeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteOperationDigestConstraint> for Secp256k1Instruction {
+    fn from(i: ByteOperationDigestConstraint) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayAdd<4>> for Secp256k1Instruction {
+    fn from(i: ByteArrayAdd<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArraySub<4>> for Secp256k1Instruction {
+    fn from(i: ByteArraySub<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteArrayMul> for Secp256k1Instruction {
+    fn from(i: ByteArrayMul) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<U32NativeInstruction> for Secp256k1Instruction {
+    fn from(i: U32NativeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl From<ByteCountInstruction> for Secp256k1Instruction {
+    fn from(i: ByteCountInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
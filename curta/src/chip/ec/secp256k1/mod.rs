@@ -0,0 +1,13 @@
+//! The secp256k1 curve `y^2 = x^3 + 7` over the prime field of order `2^256 - 2^32 - 977`.
+//!
+//! The curve is a short Weierstrass curve, so it implements `EllipticCurveAir` through `SWCurve`
+//! and points can be multiplied by scalars with `EllipticCurveBuilder::scalar_mul_batch`. The
+//! chips of this module use the vanishing `a` coefficient to save operations when doubling.
+//!
+//! Reference: https://www.secg.org/sec2-v2.pdf
+
+pub mod decompress;
+pub mod glv;
+pub mod group;
+pub mod instruction;
+pub mod params;
@@ -0,0 +1,138 @@
+use num::{BigUint, Num, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Secp256k1 curve parameter
+pub struct Secp256k1Parameters;
+
+pub type Secp256k1 = SWCurve<Secp256k1Parameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Secp256k1 base field parameter
+pub struct Secp256k1BaseField;
+
+impl FieldParameters for Secp256k1BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Base field modulus:
+    //  115792089237316195423570985008687907853269984665640564039457584007908834671663
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        64559, 65535, 65534, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535,
+        65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    // The modulus uses all 256 bits of the limbs, so the witness limbs can exceed the offset of
+    // the smaller fields.
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Secp256k1 scalar field parameter
+pub struct Secp256k1ScalarField;
+
+impl FieldParameters for Secp256k1ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  115792089237316195423570985008687907852837564279074904382605163141518161494337
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        16705, 53302, 24204, 49106, 41019, 44872, 56550, 47790, 65534, 65535, 65535, 65535, 65535,
+        65535, 65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 21;
+}
+
+impl EllipticCurveParameters for Secp256k1Parameters {
+    type BaseField = Secp256k1BaseField;
+}
+
+impl WeierstrassParameters for Secp256k1Parameters {
+    const A: [u16; MAX_NB_LIMBS] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; MAX_NB_LIMBS] = [
+        7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "55066263022277343669578718895168534326250603453777594175500187360389116729240",
+            10,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "32670510020758816978083085130507043184471273380659243275938904335757337482424",
+            10,
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    fn prime_group_order() -> BigUint {
+        Secp256k1ScalarField::modulus()
+    }
+
+    fn a_int() -> BigUint {
+        BigUint::zero()
+    }
+
+    fn b_int() -> BigUint {
+        BigUint::from(7u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secp256k1_field_parameters() {
+        let p = (BigUint::from(1u32) << 256u32) - (BigUint::from(1u32) << 32u32) - 977u32;
+        assert_eq!(Secp256k1BaseField::modulus(), p);
+        assert_eq!(Secp256k1BaseField::nb_bits(), 256);
+
+        let n = BigUint::from_str_radix(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            16,
+        )
+        .unwrap();
+        assert_eq!(Secp256k1ScalarField::modulus(), n);
+        assert_eq!(Secp256k1ScalarField::nb_bits(), 256);
+
+        // The limbs above `NB_LIMBS` are unused.
+        for modulus in [Secp256k1BaseField::MODULUS, Secp256k1ScalarField::MODULUS] {
+            assert!(modulus[16..].iter().all(|limb| *limb == 0));
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_generator() {
+        let p = Secp256k1BaseField::modulus();
+        let (x, y) = Secp256k1Parameters::generator();
+        let lhs = (&y * &y) % &p;
+        let rhs = (&x * &x * &x + Secp256k1Parameters::b_int()) % &p;
+        assert_eq!(lhs, rhs);
+
+        let g = Secp256k1::generator();
+        let order = Secp256k1Parameters::prime_group_order();
+        let p_minus_g = g.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(p_minus_g.x, g.x);
+        assert_eq!(p_minus_g.y, &p - &g.y);
+    }
+}
@@ -32,6 +32,27 @@ impl FieldParameters for Bn254BaseField {
     const WITNESS_OFFSET: usize = 1usize << 20;
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Bn254 scalar field parameter
+pub struct Bn254ScalarField;
+
+impl FieldParameters for Bn254ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  21888242871839275222246405745257275088548364400416034343698204186575808495617
+    const MODULUS: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        1, 61440, 62867, 17377, 28817, 31161, 59464, 10291, 22621, 33153, 17846, 47184, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
 impl EllipticCurveParameters for Bn254Parameters {
     type BaseField = Bn254BaseField;
 }
@@ -68,3 +89,48 @@ impl WeierstrassParameters for Bn254Parameters {
         BigUint::from(3u32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The BN parameter `u` of the curve.
+    const U: u64 = 4965661367192848881;
+
+    #[test]
+    fn test_bn254_field_parameters() {
+        // The moduli are `36u^4 + 36u^3 + 24u^2 + 6u + 1` and `36u^4 + 36u^3 + 18u^2 + 6u + 1`.
+        let u = BigUint::from(U);
+        let u2 = &u * &u;
+        let common = BigUint::from(36u32) * &u2 * &u2 + BigUint::from(36u32) * &u2 * &u + &u * 6u32;
+        let p = &common + &u2 * 24u32 + 1u32;
+        let r = &common + &u2 * 18u32 + 1u32;
+
+        assert_eq!(Bn254BaseField::modulus(), p);
+        assert_eq!(
+            Bn254BaseField::modulus(),
+            BigUint::from_str_radix(
+                "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+                16
+            )
+            .unwrap()
+        );
+        assert_eq!(Bn254ScalarField::modulus(), r);
+        assert_eq!(Bn254Parameters::prime_group_order(), r);
+    }
+
+    #[test]
+    fn test_bn254_generator() {
+        let p = Bn254BaseField::modulus();
+        let (x, y) = Bn254Parameters::generator();
+        let lhs = (&y * &y) % &p;
+        let rhs = (&x * &x * &x + Bn254Parameters::b_int()) % &p;
+        assert_eq!(lhs, rhs);
+
+        let g = Bn254::generator();
+        let order = Bn254Parameters::prime_group_order();
+        let minus_g = g.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g.x, g.x);
+        assert_eq!(minus_g.y, &p - &g.y);
+    }
+}
@@ -11,7 +11,10 @@ use crate::chip::AirParameters;
 pub mod biguint_operations;
 pub mod bn254;
 pub mod group;
+pub mod pallas;
+pub mod pasta;
 pub mod slope;
+pub mod vesta;
 
 /// Parameters that specify a short Weierstrass curve : y^2 = x^3 + ax + b.
 pub trait WeierstrassParameters: EllipticCurveParameters {
@@ -72,6 +75,10 @@ impl<E: WeierstrassParameters> EllipticCurve for SWCurve<E> {
         let modulus = E::BaseField::modulus();
         AffinePoint::new(p.x.clone(), modulus - &p.y)
     }
+
+    fn nb_scalar_bits() -> usize {
+        E::nb_scalar_bits()
+    }
 }
 
 impl<E: WeierstrassParameters> SWCurve<E> {
@@ -0,0 +1,89 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::FieldParameters;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Pallas curve parameter
+pub struct PallasParameters;
+
+pub type Pallas = SWCurve<PallasParameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Pallas base field parameter
+pub struct PallasBaseField;
+
+impl FieldParameters for PallasBaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Base field modulus:
+    //  28948022309329048855892746252171976963363056481941560715954676764349967630337
+    const MODULUS: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        1, 0, 12525, 39213, 63771, 2380, 39164, 8774, 0, 0, 0, 0, 0, 0, 0, 16384, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Pallas scalar field parameter, which is also the base field of Vesta
+pub struct PallasScalarField;
+
+impl FieldParameters for PallasScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  28948022309329048855892746252171976963363056481941647379679742748393362948097
+    const MODULUS: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        1, 0, 60193, 35910, 43229, 2452, 39164, 8774, 0, 0, 0, 0, 0, 0, 0, 16384, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 20;
+}
+
+impl EllipticCurveParameters for PallasParameters {
+    type BaseField = PallasBaseField;
+}
+
+impl WeierstrassParameters for PallasParameters {
+    const A: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    /// The generator `(-1, 2)` of the `pasta_curves` crate.
+    fn generator() -> (BigUint, BigUint) {
+        let x = PallasBaseField::modulus() - 1u32;
+        let y = BigUint::from(2u32);
+        (x, y)
+    }
+
+    fn prime_group_order() -> num::BigUint {
+        PallasScalarField::modulus()
+    }
+
+    fn a_int() -> BigUint {
+        BigUint::zero()
+    }
+
+    fn b_int() -> BigUint {
+        BigUint::from(5u32)
+    }
+}
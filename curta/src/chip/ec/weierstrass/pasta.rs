@@ -0,0 +1,312 @@
+//! The Pasta cycle of the Pallas and Vesta curves `y^2 = x^3 + 5`, as used by Halo2.
+//!
+//! The base field of each curve is the scalar field of the other, so the scalar field
+//! arithmetic of Pallas, needed to verify Pallas signatures and commitment openings, is the base
+//! field arithmetic of Vesta. Both curves are short Weierstrass curves with `a = 0`, so the
+//! generic `SWCurve` chips apply.
+//!
+//! Reference: https://electriccoin.co/blog/the-pasta-curves-for-halo-2-and-beyond/
+
+use serde::{Deserialize, Serialize};
+
+use super::pallas::{Pallas, PallasBaseField};
+use super::vesta::{Vesta, VestaBaseField};
+use crate::air::AirConstraint;
+use crate::chip::ec::scalar::LimbBitInstruction;
+use crate::chip::ec::ECInstruction;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::inv::FpInvInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::mul_sub::FpMulSubInstruction;
+use crate::chip::field::neg::FpNegInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR doing point arithmetic over both Pallas and Vesta.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum PastaInstruction {
+    Pallas(ECInstruction<Pallas>),
+    Vesta(ECInstruction<Vesta>),
+}
+
+impl FromFieldInstruction<PallasBaseField> for PastaInstruction {}
+
+impl FromFieldInstruction<VestaBaseField> for PastaInstruction {}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for PastaInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            PastaInstruction::Pallas(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            PastaInstruction::Vesta(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for PastaInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            PastaInstruction::Pallas(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            PastaInstruction::Vesta(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            PastaInstruction::Pallas(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            PastaInstruction::Vesta(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<LimbBitInstruction> for PastaInstruction {
+    fn from(i: LimbBitInstruction) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpAddInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpAddInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpAddInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpAddInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpMulInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpMulInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpMulInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpMulInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpSubInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpSubInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpSubInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpSubInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpDivInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpDivInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpDivInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpDivInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpDenInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpDenInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpDenInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpDenInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpInnerProductInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpInnerProductInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpMulConstInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpMulConstInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpNegInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpNegInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpNegInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpNegInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpMulSubInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpMulSubInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpMulSubInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+impl From<FpInvInstruction<PallasBaseField>> for PastaInstruction {
+    fn from(i: FpInvInstruction<PallasBaseField>) -> Self {
+        Self::Pallas(i.into())
+    }
+}
+
+impl From<FpInvInstruction<VestaBaseField>> for PastaInstruction {
+    fn from(i: FpInvInstruction<VestaBaseField>) -> Self {
+        Self::Vesta(i.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::ec::gadget::{EllipticCurveGadget, EllipticCurveWriter};
+    use crate::chip::ec::weierstrass::pallas::{PallasParameters, PallasScalarField};
+    use crate::chip::ec::weierstrass::vesta::VestaParameters;
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::parameters::FieldParameters;
+    use crate::chip::AirParameters;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct PastaAddTest;
+
+    impl AirParameters for PastaAddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2304;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 3474;
+        type Instruction = PastaInstruction;
+    }
+
+    #[test]
+    fn test_pasta_cycle() {
+        assert_eq!(
+            PallasParameters::prime_group_order(),
+            VestaBaseField::modulus()
+        );
+        assert_eq!(
+            VestaParameters::prime_group_order(),
+            PallasBaseField::modulus()
+        );
+
+        let pallas = Pallas::generator();
+        let order = PallasParameters::prime_group_order();
+        let minus_g = pallas.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g.x, pallas.x);
+        assert_eq!(minus_g.y, PallasBaseField::modulus() - &pallas.y);
+
+        let vesta = Vesta::generator();
+        let order = VestaParameters::prime_group_order();
+        let minus_g = vesta.sw_scalar_mul(&(&order - 1u32));
+        assert_eq!(minus_g.x, vesta.x);
+        assert_eq!(minus_g.y, VestaBaseField::modulus() - &vesta.y);
+    }
+
+    #[test]
+    fn test_pasta_add() {
+        type L = PastaAddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let p = builder.alloc_ec_point();
+        let q = builder.alloc_ec_point();
+        let sum = builder.sw_add::<PallasParameters>(&p, &q);
+        let expected = builder.alloc_ec_point();
+        builder.assert_equal(&sum.x, &expected.x);
+        builder.assert_equal(&sum.y, &expected.y);
+
+        let r = builder.alloc_ec_point();
+        let s = builder.alloc_ec_point();
+        let vesta_sum = builder.sw_add::<VestaParameters>(&r, &s);
+        let vesta_expected = builder.alloc_ec_point();
+        builder.assert_equal(&vesta_sum.x, &vesta_expected.x);
+        builder.assert_equal(&vesta_sum.y, &vesta_expected.y);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let order = PallasScalarField::modulus();
+        let pallas = Pallas::generator();
+        let p_int = pallas.sw_scalar_mul(&rng.gen_biguint_below(&order));
+        let q_int = pallas.sw_scalar_mul(&rng.gen_biguint_below(&order));
+        let sum_int = p_int.sw_add(&q_int);
+        let order = VestaParameters::prime_group_order();
+        let vesta = Vesta::generator();
+        let r_int = vesta.sw_scalar_mul(&rng.gen_biguint_below(&order));
+        let s_int = vesta.sw_scalar_mul(&rng.gen_biguint_below(&order));
+        let vesta_sum_int = r_int.sw_add(&s_int);
+        let writer = generator.new_writer();
+        (0..num_rows).into_par_iter().for_each(|i| {
+            writer.write_ec_point(&p, &p_int, i);
+            writer.write_ec_point(&q, &q_int, i);
+            writer.write_ec_point(&expected, &sum_int, i);
+            writer.write_ec_point(&r, &r_int, i);
+            writer.write_ec_point(&s, &s_int, i);
+            writer.write_ec_point(&vesta_expected, &vesta_sum_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        writer.write_global_instructions(&generator.air_data);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
@@ -0,0 +1,54 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::pallas::{PallasBaseField, PallasScalarField};
+use super::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::FieldParameters;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Vesta curve parameter
+pub struct VestaParameters;
+
+pub type Vesta = SWCurve<VestaParameters>;
+
+/// The base field of Vesta is the scalar field of Pallas.
+pub type VestaBaseField = PallasScalarField;
+
+/// The scalar field of Vesta is the base field of Pallas.
+pub type VestaScalarField = PallasBaseField;
+
+impl EllipticCurveParameters for VestaParameters {
+    type BaseField = VestaBaseField;
+}
+
+impl WeierstrassParameters for VestaParameters {
+    const A: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; crate::chip::field::parameters::MAX_NB_LIMBS] = [
+        5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    /// The generator `(-1, 2)` of the `pasta_curves` crate.
+    fn generator() -> (BigUint, BigUint) {
+        let x = VestaBaseField::modulus() - 1u32;
+        let y = BigUint::from(2u32);
+        (x, y)
+    }
+
+    fn prime_group_order() -> num::BigUint {
+        VestaScalarField::modulus()
+    }
+
+    fn a_int() -> BigUint {
+        BigUint::zero()
+    }
+
+    fn b_int() -> BigUint {
+        BigUint::from(5u32)
+    }
+}
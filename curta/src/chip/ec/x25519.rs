@@ -0,0 +1,318 @@
+//! The X25519 function on the Montgomery form of Curve25519, as defined in RFC 7748.
+//!
+//! A ladder takes one row per scalar bit, from the most significant bit down, so that the trace
+//! of a full ladder has 256 rows. The state of the ladder is the pair of projective
+//! u-coordinates `(x2 : z2)` and `(x3 : z3)` of the points `[n]P` and `[n + 1]P`, where `n` is the
+//! scalar read so far. The constant-time swap of the RFC is expressed as selects controlled by
+//! the scalar bit of the row.
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc7748#section-5
+
+use num::{BigUint, One, Zero};
+
+use super::constants::curve25519_a24;
+use super::edwards::ed25519::params::Ed25519BaseField;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::AirParameters;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The number of rows of a ladder, one for each bit of the scalar.
+pub const X25519_LADDER_ROWS: usize = 256;
+
+/// The state of a Montgomery ladder.
+#[derive(Debug, Clone, Copy)]
+pub struct X25519LadderState {
+    pub x2: FieldRegister<Ed25519BaseField>,
+    pub z2: FieldRegister<Ed25519BaseField>,
+    pub x3: FieldRegister<Ed25519BaseField>,
+    pub z3: FieldRegister<Ed25519BaseField>,
+}
+
+/// The registers of a Montgomery ladder computing `X25519(k, u)`.
+#[derive(Debug, Clone, Copy)]
+pub struct X25519Ladder {
+    /// The state at the beginning of the row.
+    pub state: X25519LadderState,
+    /// The state after processing the scalar bit of the row.
+    pub next: X25519LadderState,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Performs a single step of the Montgomery ladder for the base point `u` and the scalar bit
+    /// `bit`.
+    ///
+    /// The two points of `state` are swapped when `bit` is set, the first point is doubled and
+    /// the sum of the two points is computed from their difference `u`, and the results are
+    /// swapped back.
+    pub fn x25519_ladder_step(
+        &mut self,
+        u: &FieldRegister<Ed25519BaseField>,
+        state: &X25519LadderState,
+        bit: &BitRegister,
+    ) -> X25519LadderState
+    where
+        L::Instruction: FromFieldInstruction<Ed25519BaseField>,
+    {
+        // cswap(bit, (x2, z2), (x3, z3)).
+        let x2 = self.select(bit, &state.x3, &state.x2);
+        let z2 = self.select(bit, &state.z3, &state.z2);
+        let x3 = self.select(bit, &state.x2, &state.x3);
+        let z3 = self.select(bit, &state.z2, &state.z3);
+
+        let a = self.fp_add(&x2, &z2);
+        let aa = self.fp_mul(&a, &a);
+        let b = self.fp_sub(&x2, &z2);
+        let bb = self.fp_mul(&b, &b);
+        let e = self.fp_sub(&aa, &bb);
+        let c = self.fp_add(&x3, &z3);
+        let d = self.fp_sub(&x3, &z3);
+        let da = self.fp_mul(&d, &a);
+        let cb = self.fp_mul(&c, &b);
+
+        // The sum (x3 : z3) = ((DA + CB)^2 : u * (DA - CB)^2).
+        let da_plus_cb = self.fp_add(&da, &cb);
+        let sum_x = self.fp_mul(&da_plus_cb, &da_plus_cb);
+        let da_minus_cb = self.fp_sub(&da, &cb);
+        let da_minus_cb_squared = self.fp_mul(&da_minus_cb, &da_minus_cb);
+        let sum_z = self.fp_mul(u, &da_minus_cb_squared);
+
+        // The double (x2 : z2) = (AA * BB : E * (AA + a24 * E)).
+        let double_x = self.fp_mul(&aa, &bb);
+        let a24_mul_e = self.fp_mul_constant(&e, &curve25519_a24());
+        let aa_plus_a24_mul_e = self.fp_add(&aa, &a24_mul_e);
+        let double_z = self.fp_mul(&e, &aa_plus_a24_mul_e);
+
+        // cswap(bit, (x2, z2), (x3, z3)).
+        X25519LadderState {
+            x2: self.select(bit, &sum_x, &double_x),
+            z2: self.select(bit, &sum_z, &double_z),
+            x3: self.select(bit, &double_x, &sum_x),
+            z3: self.select(bit, &double_z, &sum_z),
+        }
+    }
+
+    /// Constrains `result = X25519(k, u)`, where the bit of `k` processed at each row is given by
+    /// `bit`, from the most significant bit in the first row to the least significant bit in the
+    /// last row.
+    ///
+    /// The base point `u` and the `result` must be public registers and the trace must have
+    /// `X25519_LADDER_ROWS` rows. The state of the ladder is assigned by the row instructions,
+    /// so the rows must be written in order. The scalar is not clamped by the ladder, ladders
+    /// sharing the same `bit` register prove results for the same scalar.
+    pub fn x25519_ladder(
+        &mut self,
+        bit: &BitRegister,
+        u: &FieldRegister<Ed25519BaseField>,
+        result: &FieldRegister<Ed25519BaseField>,
+    ) -> X25519Ladder
+    where
+        L::Instruction: FromFieldInstruction<Ed25519BaseField>,
+    {
+        assert!(
+            !u.is_trace() && !result.is_trace(),
+            "The base point and the result must be public"
+        );
+        let one = self.constant(&to_u16_le_limbs_polynomial::<L::Field, Ed25519BaseField>(
+            &BigUint::one(),
+        ));
+        let zero = self.constant(&to_u16_le_limbs_polynomial::<L::Field, Ed25519BaseField>(
+            &BigUint::zero(),
+        ));
+
+        // The ladder starts from the neutral element (1 : 0) and the base point (u : 1).
+        let state = X25519LadderState {
+            x2: self.alloc(),
+            z2: self.alloc(),
+            x3: self.alloc(),
+            z3: self.alloc(),
+        };
+        self.set_to_expression_first_row(&state.x2, one.expr());
+        self.set_to_expression_first_row(&state.z2, zero.expr());
+        self.set_to_expression_first_row(&state.x3, u.expr());
+        self.set_to_expression_first_row(&state.z3, one.expr());
+
+        let next = self.x25519_ladder_step(u, &state, bit);
+        self.set_to_expression_transition(&state.x2.next(), next.x2.expr());
+        self.set_to_expression_transition(&state.z2.next(), next.z2.expr());
+        self.set_to_expression_transition(&state.x3.next(), next.x3.expr());
+        self.set_to_expression_transition(&state.z3.next(), next.z3.expr());
+
+        // The result is the affine u-coordinate x2 / z2 of the last row.
+        let result_mul_z2 = self.fp_mul(result, &next.z2);
+        self.assert_equal_last_row(&result_mul_z2, &next.x2);
+
+        X25519Ladder { state, next }
+    }
+}
+
+/// Clamps a 32-byte X25519 scalar as in `decodeScalar25519`.
+pub fn x25519_clamp(scalar: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    k
+}
+
+/// Computes `X25519(k, u)` for a scalar `k` and a u-coordinate `u` given as little-endian bytes.
+///
+/// The scalar is clamped and the most significant bit of `u` is masked, as specified by the RFC.
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let k = BigUint::from_bytes_le(&x25519_clamp(scalar));
+    let mut u_bytes = *u;
+    u_bytes[31] &= 127;
+
+    let modulus = Ed25519BaseField::modulus();
+    let u = BigUint::from_bytes_le(&u_bytes) % &modulus;
+    let (x2, z2, _, _) = (0..X25519_LADDER_ROWS).rev().fold(
+        (BigUint::one(), BigUint::zero(), u.clone(), BigUint::one()),
+        |(x2, z2, x3, z3), i| x25519_ladder_step_pure(&u, (x2, z2, x3, z3), k.bit(i as u64)),
+    );
+
+    let z2_inv = z2.modpow(&(&modulus - BigUint::from(2u32)), &modulus);
+    let mut bytes = ((x2 * z2_inv) % &modulus).to_bytes_le();
+    bytes.resize(32, 0);
+    bytes.try_into().unwrap()
+}
+
+/// A step of the Montgomery ladder on the values `(x2, z2, x3, z3)`.
+fn x25519_ladder_step_pure(
+    u: &BigUint,
+    state: (BigUint, BigUint, BigUint, BigUint),
+    bit: bool,
+) -> (BigUint, BigUint, BigUint, BigUint) {
+    let p = &Ed25519BaseField::modulus();
+    let (x2, z2, x3, z3) = if bit {
+        (state.2, state.3, state.0, state.1)
+    } else {
+        state
+    };
+
+    let a = (&x2 + &z2) % p;
+    let aa = (&a * &a) % p;
+    let b = (&x2 + p - &z2) % p;
+    let bb = (&b * &b) % p;
+    let e = (&aa + p - &bb) % p;
+    let c = (&x3 + &z3) % p;
+    let d = (&x3 + p - &z3) % p;
+    let da = (d * &a) % p;
+    let cb = (c * &b) % p;
+
+    let sum_x = ((&da + &cb) * (&da + &cb)) % p;
+    let da_minus_cb = (&da + p - &cb) % p;
+    let sum_z = (u * &da_minus_cb * &da_minus_cb) % p;
+    let double_x = (&aa * &bb) % p;
+    let double_z = (&e * (&aa + curve25519_a24().value() * &e)) % p;
+
+    if bit {
+        (sum_x, sum_z, double_x, double_z)
+    } else {
+        (double_x, double_z, sum_x, sum_z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::math::prelude::*;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct X25519LadderTest;
+
+    impl AirParameters for X25519LadderTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3880;
+        const NUM_FREE_COLUMNS: usize = 3;
+        const EXTENDED_COLUMNS: usize = 5829;
+        type Instruction = FpInstruction<Ed25519BaseField>;
+    }
+
+    /// The Diffie-Hellman test vector of RFC 7748, section 6.1.
+    const ALICE_PRIVATE: &str = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+    const ALICE_PUBLIC: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+    const BOB_PRIVATE: &str = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb";
+    const BOB_PUBLIC: &str = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+    const SHARED_SECRET: &str = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+
+    fn decode(value: &str) -> [u8; 32] {
+        hex::decode(value).unwrap().try_into().unwrap()
+    }
+
+    fn base_point() -> [u8; 32] {
+        let mut u = [0u8; 32];
+        u[0] = 9;
+        u
+    }
+
+    #[test]
+    fn test_x25519_pure() {
+        let (alice, bob) = (decode(ALICE_PRIVATE), decode(BOB_PRIVATE));
+        assert_eq!(x25519(&alice, &base_point()), decode(ALICE_PUBLIC));
+        assert_eq!(x25519(&bob, &base_point()), decode(BOB_PUBLIC));
+        assert_eq!(x25519(&alice, &decode(BOB_PUBLIC)), decode(SHARED_SECRET));
+        assert_eq!(x25519(&bob, &decode(ALICE_PUBLIC)), decode(SHARED_SECRET));
+    }
+
+    #[test]
+    fn test_x25519_ladder() {
+        type L = X25519LadderTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        // Prove that Alice's public key and the shared secret with Bob are derived from the same
+        // private key.
+        let mut builder = AirBuilder::<L>::new();
+        let bit = builder.alloc::<BitRegister>();
+        let base = builder.alloc_public::<FieldRegister<Ed25519BaseField>>();
+        let public_key = builder.alloc_public::<FieldRegister<Ed25519BaseField>>();
+        let peer_public_key = builder.alloc_public::<FieldRegister<Ed25519BaseField>>();
+        let shared_secret = builder.alloc_public::<FieldRegister<Ed25519BaseField>>();
+        builder.x25519_ladder(&bit, &base, &public_key);
+        builder.x25519_ladder(&bit, &peer_public_key, &shared_secret);
+
+        let num_rows = X25519_LADDER_ROWS;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        let to_field = |bytes: [u8; 32]| {
+            to_u16_le_limbs_polynomial::<GoldilocksField, Ed25519BaseField>(
+                &BigUint::from_bytes_le(&bytes),
+            )
+        };
+        writer.write(&base, &to_field(base_point()), 0);
+        writer.write(&public_key, &to_field(decode(ALICE_PUBLIC)), 0);
+        writer.write(&peer_public_key, &to_field(decode(BOB_PUBLIC)), 0);
+        writer.write(&shared_secret, &to_field(decode(SHARED_SECRET)), 0);
+        writer.write_global_instructions(&generator.air_data);
+
+        // The state of each row is assigned from the previous one, so the rows are written in
+        // order.
+        let k = BigUint::from_bytes_le(&x25519_clamp(&decode(ALICE_PRIVATE)));
+        for i in 0..num_rows {
+            let k_bit = k.bit((num_rows - 1 - i) as u64);
+            writer.write(&bit, &GoldilocksField::from_canonical_u8(k_bit as u8), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,489 @@
+//! Field addition and subtraction over 32-bit limbs.
+//!
+//! A `FieldRegister` holds the integer in range checked 16-bit limbs, and each pair of
+//! consecutive limbs `(lo, hi)` is the split of the 32-bit limb `lo + 2^16 * hi`. The instructions
+//! of this module prove
+//!
+//! a(x) + b(x) - result(x) - carry * p(x) - (x - 2^32) * w(x) = 0
+//!
+//! in this base, so the vanishing polynomial has half the coefficients of the one of
+//! `FpAddInstruction`. The carry is a single 16-bit limb of at most `2^10`, so the coefficients of
+//! `w(x)` are less than `2^15` in absolute value and each is range checked as one 16-bit limb,
+//! shifted by `2^15`. Every coefficient of the constraint is then less than `2^50`, and the
+//! identity holds over the integers.
+//!
+//! Only linear operations are sound in this base: a product of two 32-bit limbs is already close
+//! to the Goldilocks modulus, so the coefficients of a product of polynomials would overflow. The
+//! other field instructions keep the 16-bit limbs.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{bigint_into_u16_digits, digits_to_biguint};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// The shift of the coefficients of the witness polynomial, which makes them positive.
+const WITNESS_OFFSET: u32 = 1 << 15;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpAdd32Instruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: U16Register,
+    pub(crate) witness: ArrayRegister<U16Register>,
+}
+
+/// Fp subtraction over 32-bit limbs.
+///
+/// prove a - b = c by asserting b + c = a.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpSub32Instruction<P: FieldParameters> {
+    inner: FpAdd32Instruction<P>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two field elements `a` and `b`, computes the sum `a + b = c` over 32-bit limbs.
+    pub fn fp_add_32<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpAdd32Instruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let result = if is_trace {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        let instr = self.fp_add_32_instruction(a, b, &result);
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        result
+    }
+
+    /// Given two field elements `a` and `b`, computes the difference `a - b = c` over 32-bit
+    /// limbs.
+    pub fn fp_sub_32<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpSub32Instruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let result = if is_trace {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        let instr = FpSub32Instruction {
+            inner: self.fp_add_32_instruction(&result, b, a),
+        };
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        result
+    }
+
+    /// Allocates the carry and the witness of `a + b = result`.
+    ///
+    /// The carry of a sum of two integers of `NB_LIMBS` limbs is at most `2^10` as long as the
+    /// modulus has at least `16 * NB_LIMBS - 8` bits.
+    fn fp_add_32_instruction<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) -> FpAdd32Instruction<P> {
+        assert_eq!(P::NB_LIMBS % 2, 0, "The limbs must pair into 32-bit limbs");
+        assert!(
+            P::modulus().bits() as usize + 8 >= 16 * P::NB_LIMBS,
+            "The modulus is too small for its number of limbs"
+        );
+
+        let is_trace = a.is_trace() || b.is_trace() || result.is_trace();
+        let nb_witness_limbs = P::NB_LIMBS / 2 - 1;
+        let carry: U16Register;
+        let witness: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<U16Register>();
+            witness = self.alloc_array::<U16Register>(nb_witness_limbs);
+        } else {
+            carry = self.alloc_public::<U16Register>();
+            witness = self.alloc_array_public::<U16Register>(nb_witness_limbs);
+        }
+        FpAdd32Instruction {
+            a: *a,
+            b: *b,
+            result: *result,
+            carry,
+            witness,
+        }
+    }
+}
+
+/// The polynomial in base `2^32` of an integer given by its polynomial in base `2^16`.
+fn to_u32_limbs<AP: PolynomialParser>(
+    parser: &mut AP,
+    p: &Polynomial<AP::Var>,
+) -> Polynomial<AP::Var> {
+    let limb = parser.constant(AP::Field::from_canonical_u32(1 << 16));
+    let p_low = Polynomial::from_coefficients(p.coefficients.iter().step_by(2).cloned().collect());
+    let p_high =
+        Polynomial::from_coefficients(p.coefficients.iter().skip(1).step_by(2).cloned().collect());
+    let p_high_mul_limb = parser.poly_scalar_mul(&p_high, &limb);
+    parser.poly_add(&p_low, &p_high_mul_limb)
+}
+
+/// The little-endian 32-bit limbs of `x` as a polynomial.
+fn to_u32_le_limbs_polynomial<F: Field, P: FieldParameters>(x: &BigUint) -> Polynomial<F> {
+    let limbs = bigint_into_u16_digits(x, P::NB_LIMBS)
+        .chunks(2)
+        .map(|limb| F::from_canonical_u32(limb[0] as u32 + ((limb[1] as u32) << 16)))
+        .collect();
+    Polynomial::from_coefficients(limbs)
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpAdd32Instruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_a = to_u32_limbs(parser, &p_a);
+        let p_b = self.b.eval(parser);
+        let p_b = to_u32_limbs(parser, &p_b);
+        let p_result = self.result.eval(parser);
+        let p_result = to_u32_limbs(parser, &p_result);
+        let carry = self.carry.eval(parser);
+
+        let p_a_plus_b = parser.poly_add(&p_a, &p_b);
+        let p_a_plus_b_minus_result = parser.poly_sub(&p_a_plus_b, &p_result);
+        let p_limbs =
+            parser.constant_poly(&to_u32_le_limbs_polynomial::<AP::Field, P>(&P::modulus()));
+
+        let p_mul_times_carry = parser.poly_scalar_mul(&p_limbs, &carry);
+        let p_vanishing = parser.poly_sub(&p_a_plus_b_minus_result, &p_mul_times_carry);
+
+        // Shift down the witness polynomial, multiply it by (x - 2^32) and make the constraint.
+        let p_witness_shifted = Polynomial::from_coefficients(self.witness.eval_vec(parser));
+        let offset = parser.constant(AP::Field::from_canonical_u32(WITNESS_OFFSET));
+        let p_witness = parser.poly_scalar_sub(&p_witness_shifted, &offset);
+        let root_monomial = Polynomial::from_coefficients(vec![
+            -AP::Field::from_canonical_u64(1 << 32),
+            AP::Field::ONE,
+        ]);
+        let p_witness_mul_root = parser.poly_mul_poly_const(&p_witness, &root_monomial);
+
+        let constraints = parser.poly_sub(&p_vanishing, &p_witness_mul_root);
+        for constr in constraints.coefficients {
+            parser.constraint(constr);
+        }
+    }
+}
+
+/// The integer of a polynomial in base `2^16`.
+fn to_biguint<F: PrimeField64>(p: &Polynomial<F>) -> BigUint {
+    let digits = p
+        .coefficients
+        .iter()
+        .map(|x| x.as_canonical_u64() as u16)
+        .collect::<Vec<_>>();
+    digits_to_biguint(&digits)
+}
+
+impl<P: FieldParameters> FpAdd32Instruction<P> {
+    /// Computes the carry and the witness of `a + b = result + carry * p`.
+    fn witness<F: PrimeField64>(a: &BigUint, b: &BigUint, result: &BigUint) -> (F, Vec<F>) {
+        let modulus = P::modulus();
+        let carry = (a + b - result) / &modulus;
+        debug_assert!(carry <= BigUint::from(1u32 << 10));
+        debug_assert_eq!(&carry * &modulus, a + b - result);
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u32_le_limbs_polynomial::<F, P>(&modulus);
+        let p_carry = Polynomial::from_coefficients(vec![F::from_noncanonical_biguint(carry)]);
+        let p_vanishing = &to_u32_le_limbs_polynomial::<F, P>(a)
+            + &to_u32_le_limbs_polynomial::<F, P>(b)
+            - to_u32_le_limbs_polynomial::<F, P>(result)
+            - &p_carry * &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_LIMBS / 2 - 1);
+
+        // Compute the witness by witness(x) = vanishing(x) / (x - 2^32) and shift it.
+        let p_witness = p_vanishing.root_quotient(F::from_canonical_u64(1 << 32));
+        let p_witness = p_witness
+            .coefficients
+            .iter()
+            .map(|x| *x + F::from_canonical_u32(WITNESS_OFFSET))
+            .collect::<Vec<_>>();
+        debug_assert!(p_witness.iter().all(|x| x.as_canonical_u64() < 1 << 16));
+
+        (p_carry.coefficients[0], p_witness)
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpAdd32Instruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = to_biguint(&writer.read(&self.a, row_index));
+        let b = to_biguint(&writer.read(&self.b, row_index));
+        let result = (&a + &b) % P::modulus();
+        let (carry, p_witness) = Self::witness::<F>(&a, &b, &result);
+
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        writer.write(&self.result, &p_result, row_index);
+        writer.write(&self.carry, &carry, row_index);
+        writer.write_array(&self.witness, &p_witness, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = to_biguint(&writer.read(&self.a));
+        let b = to_biguint(&writer.read(&self.b));
+        let result = (&a + &b) % P::modulus();
+        let (carry, p_witness) = Self::witness::<F>(&a, &b, &result);
+
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        writer.write(&self.result, &p_result);
+        writer.write(&self.carry, &carry);
+        writer.write_array(&self.witness, &p_witness);
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpSub32Instruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        self.inner.eval(parser);
+    }
+}
+
+// The difference `c = a - b` is reduced, and the minuend `a` of the sum `c + b = a` must be
+// reduced too for the carry to be positive.
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpSub32Instruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = to_biguint(&writer.read(&self.inner.result, row_index));
+        let b = to_biguint(&writer.read(&self.inner.b, row_index));
+        let modulus = P::modulus();
+        let c = (&modulus + &a - &b % &modulus) % &modulus;
+        let (carry, p_witness) = FpAdd32Instruction::<P>::witness::<F>(&c, &b, &a);
+
+        let p_c = to_u16_le_limbs_polynomial::<F, P>(&c);
+        writer.write(&self.inner.a, &p_c, row_index);
+        writer.write(&self.inner.carry, &carry, row_index);
+        writer.write_array(&self.inner.witness, &p_witness, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = to_biguint(&writer.read(&self.inner.result));
+        let b = to_biguint(&writer.read(&self.inner.b));
+        let modulus = P::modulus();
+        let c = (&modulus + &a - &b % &modulus) % &modulus;
+        let (carry, p_witness) = FpAdd32Instruction::<P>::witness::<F>(&c, &b, &a);
+
+        let p_c = to_u16_le_limbs_polynomial::<F, P>(&c);
+        writer.write(&self.inner.a, &p_c);
+        writer.write(&self.inner.carry, &carry);
+        writer.write_array(&self.inner.witness, &p_witness);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::add::FpAddInstruction;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpAddSub32Test;
+
+    impl AirParameters for FpAddSub32Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 80;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 129;
+
+        type Instruction = FpInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fp_add_sub_32() {
+        type F = GoldilocksField;
+        type L = FpAddSub32Test;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<FieldRegister<P>>();
+        let b_pub = builder.alloc_public::<FieldRegister<P>>();
+        let sum_pub = builder.fp_add_32(&a_pub, &b_pub);
+        let difference_pub = builder.fp_sub_32(&sum_pub, &b_pub);
+        builder.assert_equal(&difference_pub, &a_pub);
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let sum = builder.fp_add_32(&a, &b);
+        let difference = builder.fp_sub_32(&sum, &b);
+        builder.assert_equal(&difference, &a);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        let mut rng = thread_rng();
+        let a_int = rng.gen_biguint_below(&p);
+        let b_int = rng.gen_biguint_below(&p);
+        writer.write(&a_pub, &to_u16_le_limbs_polynomial::<F, P>(&a_int), 0);
+        writer.write(&b_pub, &to_u16_le_limbs_polynomial::<F, P>(&b_int), 0);
+        writer.write_global_instructions(&generator.air_data);
+        assert_eq!(
+            writer.read(&sum_pub, 0).coefficients,
+            to_u16_le_limbs_polynomial::<F, P>(&((&a_int + &b_int) % &p)).coefficients
+        );
+
+        (0..num_rows).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let a_int = rng.gen_biguint_below(&p);
+            let b_int = rng.gen_biguint_below(&p);
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpAdd16Bench;
+
+    impl AirParameters for FpAdd16Bench {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 124;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 195;
+
+        type Instruction = FpAddInstruction<Fp25519>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpAdd32Bench;
+
+    impl AirParameters for FpAdd32Bench {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 56;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 93;
+
+        type Instruction = FpAdd32Instruction<Fp25519>;
+    }
+
+    /// Proves `num_rows` additions of random elements with `add`, timed under `name`.
+    fn bench_fp_add<L: AirParameters<Field = GoldilocksField>>(
+        name: &str,
+        add: fn(
+            &mut AirBuilder<L>,
+            &FieldRegister<Fp25519>,
+            &FieldRegister<Fp25519>,
+        ) -> FieldRegister<Fp25519>,
+        timing: &mut TimingTree,
+    ) {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let _ = add(&mut builder, &a, &b);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        (0..num_rows).into_par_iter().for_each(|i| {
+            let mut rng = thread_rng();
+            let a_int = rng.gen_biguint_below(&p);
+            let b_int = rng.gen_biguint_below(&p);
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        log::info!("{}: {} columns", name, L::num_columns());
+        timed!(
+            timing,
+            log::Level::Info,
+            name,
+            test_starky(&stark, &config, &generator, &public)
+        );
+    }
+
+    #[test]
+    fn test_fp_add_limb_widths() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_fp_add_limb_widths", log::Level::Info);
+
+        // Both AIRs hold the two inputs in 32 columns. An addition takes 92 more columns over
+        // 16-bit limbs and 24 over 32-bit limbs.
+        bench_fp_add::<FpAdd16Bench>(
+            "16-bit limbs",
+            |builder, a, b| builder.fp_add(a, b),
+            &mut timing,
+        );
+        bench_fp_add::<FpAdd32Bench>(
+            "32-bit limbs",
+            |builder, a, b| builder.fp_add_32(a, b),
+            &mut timing,
+        );
+
+        timing.print();
+    }
+}
@@ -0,0 +1,349 @@
+//! Conversions between field elements and bytes.
+//!
+//! The `u16` limbs of a `FieldRegister<P>` are the `2 * P::NB_LIMBS` bytes of the integer they
+//! represent, read in the byte order given by an `Endianness`. This lets the digests of the hash
+//! AIRs, which are byte arrays, be used as field elements, and field elements be hashed.
+//!
+//! Both conversions check that the encoding is canonical, that is that the integer is reduced
+//! modulo `p`, so every field element has a unique byte encoding.
+
+use serde::{Deserialize, Serialize};
+
+use super::compare::FpCompareInstruction;
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::util::Endianness;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::Polynomial;
+
+/// Constrains `bytes` to be the encoding of the limbs of `element` in the byte order of
+/// `endianness`, by `limb_i = byte_(2i) + 2^8 * byte_(2i + 1)` for the little-endian bytes.
+///
+/// The bytes are written from the limbs if `to_bytes` is set, and the limbs from the bytes
+/// otherwise.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpBytesInstruction<P: FieldParameters> {
+    pub element: FieldRegister<P>,
+    pub bytes: ArrayRegister<ByteRegister>,
+    endianness: Endianness,
+    to_bytes: bool,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Encodes the field element `a` as `2 * P::NB_LIMBS` range checked bytes in the byte order
+    /// given by `endianness`, and asserts that `a` is reduced.
+    pub fn fp_to_bytes<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        endianness: Endianness,
+        operations: &mut ByteLookupOperations,
+    ) -> ArrayRegister<ByteRegister>
+    where
+        L::Instruction: From<FpBytesInstruction<P>>
+            + From<FpCompareInstruction<P>>
+            + From<ByteOperationInstruction>,
+    {
+        // Public bytes are range checked when the byte lookup is registered.
+        let bytes = if a.is_trace() {
+            self.alloc_array::<ByteRegister>(2 * P::NB_LIMBS)
+        } else {
+            self.alloc_array_public::<ByteRegister>(2 * P::NB_LIMBS)
+        };
+        self.register_fp_bytes(a, &bytes, endianness, true);
+        if a.is_trace() {
+            for byte in bytes.iter() {
+                self.set_byte_operation(&ByteOperation::Range(byte), operations);
+            }
+        }
+        bytes
+    }
+
+    /// Decodes the field element of the `2 * P::NB_LIMBS` bytes `bytes`, read in the byte order
+    /// given by `endianness`, and asserts that the encoding is canonical.
+    ///
+    /// The bytes are assumed to be range checked, so no further checks are needed.
+    pub fn fp_from_bytes<P: FieldParameters>(
+        &mut self,
+        bytes: &ArrayRegister<ByteRegister>,
+        endianness: Endianness,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpBytesInstruction<P>> + From<FpCompareInstruction<P>>,
+    {
+        assert_eq!(
+            bytes.len(),
+            2 * P::NB_LIMBS,
+            "the number of bytes must be twice the number of limbs"
+        );
+        let element = if bytes.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.register_fp_bytes(&element, bytes, endianness, false);
+        element
+    }
+
+    fn register_fp_bytes<P: FieldParameters>(
+        &mut self,
+        element: &FieldRegister<P>,
+        bytes: &ArrayRegister<ByteRegister>,
+        endianness: Endianness,
+        to_bytes: bool,
+    ) where
+        L::Instruction: From<FpBytesInstruction<P>> + From<FpCompareInstruction<P>>,
+    {
+        let instr = FpBytesInstruction {
+            element: *element,
+            bytes: *bytes,
+            endianness,
+            to_bytes,
+        };
+        if element.is_trace() || bytes.is_trace() {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+
+        let is_reduced = self.fp_is_reduced(element);
+        self.assert_expression_zero(is_reduced.expr() - L::Field::ONE);
+    }
+}
+
+impl<P: FieldParameters> FpBytesInstruction<P> {
+    /// The position in `bytes` of the `i`-th least significant byte.
+    fn byte_index(&self, i: usize) -> usize {
+        match self.endianness {
+            Endianness::Little => i,
+            Endianness::Big => self.bytes.len() - 1 - i,
+        }
+    }
+
+    fn limbs_from_bytes<F: PrimeField64>(&self, bytes: &[F]) -> Polynomial<F> {
+        let limbs = (0..P::NB_LIMBS)
+            .map(|i| {
+                let low = bytes[self.byte_index(2 * i)].as_canonical_u64();
+                let high = bytes[self.byte_index(2 * i + 1)].as_canonical_u64();
+                F::from_canonical_u64(low + (high << 8))
+            })
+            .collect::<Vec<_>>();
+        Polynomial::from_coefficients(limbs)
+    }
+
+    fn bytes_from_limbs<F: PrimeField64>(&self, limbs: &Polynomial<F>) -> Vec<F> {
+        let mut bytes = vec![F::ZERO; 2 * P::NB_LIMBS];
+        for (i, limb) in limbs.coefficients().iter().enumerate() {
+            let limb = limb.as_canonical_u64();
+            bytes[self.byte_index(2 * i)] = F::from_canonical_u64(limb & 0xFF);
+            bytes[self.byte_index(2 * i + 1)] = F::from_canonical_u64(limb >> 8);
+        }
+        bytes
+    }
+}
+
+impl<AP: AirParser, P: FieldParameters> AirConstraint<AP> for FpBytesInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let limbs = self.element.eval(parser).coefficients;
+        let bytes = self.bytes.eval_vec(parser);
+
+        let byte_shift = AP::Field::from_canonical_u32(1 << 8);
+        for (i, limb) in limbs.into_iter().enumerate() {
+            let low = bytes[self.byte_index(2 * i)];
+            let high = bytes[self.byte_index(2 * i + 1)];
+            let high_shifted = parser.mul_const(high, byte_shift);
+            let recomposition = parser.add(low, high_shifted);
+            parser.assert_eq(limb, recomposition);
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpBytesInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        if self.to_bytes {
+            let limbs = writer.read(&self.element, row_index);
+            writer.write_array(&self.bytes, self.bytes_from_limbs(&limbs), row_index);
+        } else {
+            let bytes = writer.read_vec(&self.bytes, row_index);
+            writer.write(&self.element, &self.limbs_from_bytes(&bytes), row_index);
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        if self.to_bytes {
+            let limbs = writer.read(&self.element);
+            writer.write_array(&self.bytes, self.bytes_from_limbs(&limbs));
+        } else {
+            let bytes = writer.read_vec(&self.bytes);
+            writer.write(&self.element, &self.limbs_from_bytes(&bytes));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::field::parameters::tests::Fp25519;
+    use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
+    use crate::chip::uint::bytes::lookup_table::ByteInstructionSet;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::polynomial::parser::PolynomialParser;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    enum FpBytesTestInstruction {
+        Fp(FpInstruction<Fp25519>),
+        Uint(UintInstruction),
+    }
+
+    impl<AP: PolynomialParser> AirConstraint<AP> for FpBytesTestInstruction {
+        fn eval(&self, parser: &mut AP) {
+            match self {
+                Self::Fp(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+                Self::Uint(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            }
+        }
+    }
+
+    impl<F: PrimeField64> Instruction<F> for FpBytesTestInstruction {
+        fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+            match self {
+                Self::Fp(instruction) => Instruction::<F>::write(instruction, writer, row_index),
+                Self::Uint(instruction) => Instruction::<F>::write(instruction, writer, row_index),
+            }
+        }
+
+        fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+            match self {
+                Self::Fp(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+                Self::Uint(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            }
+        }
+    }
+
+    impl From<FpBytesInstruction<Fp25519>> for FpBytesTestInstruction {
+        fn from(instr: FpBytesInstruction<Fp25519>) -> Self {
+            Self::Fp(instr.into())
+        }
+    }
+
+    impl From<FpCompareInstruction<Fp25519>> for FpBytesTestInstruction {
+        fn from(instr: FpCompareInstruction<Fp25519>) -> Self {
+            Self::Fp(instr.into())
+        }
+    }
+
+    impl From<ByteOperationInstruction> for FpBytesTestInstruction {
+        fn from(instr: ByteOperationInstruction) -> Self {
+            Self::Uint(instr.into())
+        }
+    }
+
+    impl From<ByteInstructionSet> for FpBytesTestInstruction {
+        fn from(instr: ByteInstructionSet) -> Self {
+            Self::Uint(instr.into())
+        }
+    }
+
+    impl From<ByteDecodeInstruction> for FpBytesTestInstruction {
+        fn from(instr: ByteDecodeInstruction) -> Self {
+            Self::Uint(instr.into())
+        }
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpBytesTest;
+
+    impl AirParameters for FpBytesTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 176;
+        const NUM_FREE_COLUMNS: usize = 250;
+        const EXTENDED_COLUMNS: usize = 450;
+
+        type Instruction = FpBytesTestInstruction;
+    }
+
+    #[test]
+    fn test_fp_bytes() {
+        type F = GoldilocksField;
+        type L = FpBytesTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let mut operations = builder.byte_operations();
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let le_bytes = builder.fp_to_bytes(&a, Endianness::Little, &mut operations);
+        let be_bytes = builder.fp_to_bytes(&a, Endianness::Big, &mut operations);
+        let a_from_be_bytes = builder.fp_from_bytes::<P>(&be_bytes, Endianness::Big);
+        builder.assert_equal(&a_from_be_bytes, &a);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // The largest field element, and random elements.
+            let a_int: BigUint = match i % 4 {
+                0 => &p - 1u32,
+                _ => rng.gen_biguint(256) % &p,
+            };
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write_row_instructions(&generator.air_data, i);
+            if i < 4 {
+                let mut expected = a_int.to_bytes_le();
+                expected.resize(32, 0);
+                let to_bytes = |bytes: Vec<F>| {
+                    bytes
+                        .into_iter()
+                        .map(|x| x.as_canonical_u64() as u8)
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(to_bytes(writer.read_vec(&le_bytes, i)), expected);
+                expected.reverse();
+                assert_eq!(to_bytes(writer.read_vec(&be_bytes, i)), expected);
+            }
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,345 @@
+//! Comparisons of multi-limb integers.
+//!
+//! The operands are the integers given by the limbs of a `FieldRegister<P>`, which need not be
+//! reduced modulo `p`. A comparison `a < b` is checked as the subtraction
+//!
+//! a + difference + 1 - b - (1 - lt) * 2^(16 * n) = 0,
+//!
+//! with a range checked `difference` of `n = P::NB_LIMBS` limbs: the bit `lt` is set exactly when
+//! the subtraction `b - a - 1` doesn't borrow. Dropping the `1` gives the comparison `a <= b`.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Integer comparison. Computes the bit `result` of `a < b` if `strict` is set, and of `a <= b`
+/// otherwise.
+///
+/// This is done by constraining `a + difference + strict - b - (1 - result) * 2^(16 * n) = 0`.
+/// As `difference` is range checked to `n = P::NB_LIMBS` limbs, the relation holds for a set
+/// `result` only if `a + strict <= b`, and for an unset `result` only if `a + strict > b`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpCompareInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub result: BitRegister,
+    strict: bool,
+    difference: FieldRegister<P>,
+    witness_low: ArrayRegister<U16Register>,
+    witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the bit of `a < b` for the integers given by the limbs of `a` and `b`.
+    pub fn fp_lt<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> BitRegister
+    where
+        L::Instruction: From<FpCompareInstruction<P>>,
+    {
+        self.fp_compare(a, b, true)
+    }
+
+    /// Computes the bit of `a <= b` for the integers given by the limbs of `a` and `b`.
+    pub fn fp_lte<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> BitRegister
+    where
+        L::Instruction: From<FpCompareInstruction<P>>,
+    {
+        self.fp_compare(a, b, false)
+    }
+
+    /// Computes the bit of `a == b` for the integers given by the limbs of `a` and `b`, as the
+    /// product of `a <= b` and `b <= a`.
+    pub fn fp_eq<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> BitRegister
+    where
+        L::Instruction: From<FpCompareInstruction<P>>,
+    {
+        let a_lte_b = self.fp_lte(a, b);
+        let b_lte_a = self.fp_lte(b, a);
+        let expression = a_lte_b.expr() * b_lte_a.expr();
+        if a.is_trace() || b.is_trace() {
+            let result = self.alloc::<BitRegister>();
+            self.set_to_expression(&result, expression);
+            result
+        } else {
+            let result = self.alloc_public::<BitRegister>();
+            self.set_to_expression_public(&result, expression);
+            result
+        }
+    }
+
+    /// Computes the bit of whether `a` is reduced, that is `a < p` for the modulus `p` of `P`.
+    pub fn fp_is_reduced<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> BitRegister
+    where
+        L::Instruction: From<FpCompareInstruction<P>>,
+    {
+        let modulus = self.fp_constant(&P::modulus());
+        self.fp_lt(a, &modulus)
+    }
+
+    fn fp_compare<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        strict: bool,
+    ) -> BitRegister
+    where
+        L::Instruction: From<FpCompareInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let instr = self.fp_compare_instruction(a, b, strict, is_trace);
+        let result = instr.result;
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        result
+    }
+
+    /// Allocates the registers of the comparison of `a` and `b` without registering it, for
+    /// instructions that constrain the comparison along with their own constraints.
+    pub(crate) fn fp_compare_instruction<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        strict: bool,
+        is_trace: bool,
+    ) -> FpCompareInstruction<P> {
+        let result: BitRegister;
+        let difference: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            result = self.alloc::<BitRegister>();
+            difference = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_LIMBS);
+        } else {
+            result = self.alloc_public::<BitRegister>();
+            difference = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_LIMBS);
+        }
+        FpCompareInstruction {
+            a: *a,
+            b: *b,
+            result,
+            strict,
+            difference,
+            witness_low,
+            witness_high,
+        }
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpCompareInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_difference = self.difference.eval(parser);
+        let result = self.result.eval(parser);
+
+        let one = parser.one();
+        let borrow = parser.sub(one, result);
+        let p_borrow_polynomial = parser.constant_poly(&util::shift_polynomial::<AP::Field, P>());
+
+        // Compute the vanishing polynomial
+        // a(x) + difference(x) + strict - b(x) - (1 - result) * x^n.
+        let p_a_plus_difference = parser.poly_add(&p_a, &p_difference);
+        let p_strict = Polynomial::from_coefficients(vec![AP::Field::from_bool(self.strict)]);
+        let p_a_plus_difference_plus_strict =
+            parser.poly_add_poly_const(&p_a_plus_difference, &p_strict);
+        let p_subtraction = parser.poly_sub(&p_a_plus_difference_plus_strict, &p_b);
+        let p_borrow = parser.poly_scalar_mul(&p_borrow_polynomial, &borrow);
+        let p_vanishing = parser.poly_sub(&p_subtraction, &p_borrow);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+    }
+}
+
+/// The values of the registers written by a `FpCompareInstruction`.
+struct CompareWitness<F> {
+    result: F,
+    difference: Polynomial<F>,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> FpCompareInstruction<P> {
+    fn witness<F: PrimeField64>(
+        &self,
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+    ) -> CompareWitness<F> {
+        let [a, b] = [p_a, p_b].map(|p| field_limbs_to_biguint(p.coefficients()));
+        let strict = BigUint::from(self.strict as u32);
+
+        // Compute the subtraction `b - a - strict`, borrowing `2^(16 * n)` if it is negative.
+        let result = &a + &strict <= b;
+        let difference = if result {
+            &b - &a - &strict
+        } else {
+            (BigUint::from(1u32) << (16 * P::NB_LIMBS)) + &b - &a - &strict
+        };
+
+        // Make little endian polynomial limbs.
+        let p_difference = to_u16_le_limbs_polynomial::<F, P>(&difference);
+        let p_strict = Polynomial::from_coefficients(vec![F::from_bool(self.strict)]);
+        let borrow = F::from_bool(!result);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing =
+            p_a + &p_difference + &p_strict - p_b - &util::shift_polynomial::<F, P>() * borrow;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_LIMBS);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        CompareWitness {
+            result: F::from_bool(result),
+            difference: p_difference,
+            witness_low,
+            witness_high,
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpCompareInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let witness = self.witness(&p_a, &p_b);
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.difference, &witness.difference, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let witness = self.witness(&p_a, &p_b);
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.difference, &witness.difference);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpCompareTest;
+
+    impl AirParameters for FpCompareTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 272;
+        const NUM_FREE_COLUMNS: usize = 12;
+        const EXTENDED_COLUMNS: usize = 417;
+
+        type Instruction = FpCompareInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fp_compare() {
+        type F = GoldilocksField;
+        type L = FpCompareTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let lt = builder.fp_lt(&a, &b);
+        let lte = builder.fp_lte(&a, &b);
+        let eq = builder.fp_eq(&a, &b);
+        let is_reduced = builder.fp_is_reduced(&a);
+        let expected = [lt, lte, eq, is_reduced].map(|bit| {
+            let expected = builder.alloc::<BitRegister>();
+            builder.assert_equal(&bit, &expected);
+            expected
+        });
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Random values, equal values, and values around the modulus.
+            let a_int = match i % 4 {
+                0 => rng.gen_biguint(256),
+                1 => &p - 1u32,
+                2 => p.clone(),
+                _ => rng.gen_biguint_below(&p),
+            };
+            let b_int = match i % 3 {
+                0 => a_int.clone(),
+                _ => rng.gen_biguint(256),
+            };
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            let bits = [a_int < b_int, a_int <= b_int, a_int == b_int, a_int < p];
+            for (expected, bit) in expected.iter().zip(bits) {
+                writer.write(expected, &F::from_bool(bit), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -1,11 +1,70 @@
-use num::{BigUint, One, Zero};
+use core::marker::PhantomData;
 
-use super::parameters::FieldParameters;
+use num::{BigUint, Num, One, Zero};
+
+use super::parameters::{FieldParameters, MAX_NB_LIMBS};
 use super::register::FieldRegister;
 use crate::chip::builder::AirBuilder;
+use crate::chip::utils::{bigint_into_u16_digits, digits_to_biguint};
 use crate::chip::AirParameters;
 use crate::polynomial::Polynomial;
 
+/// A constant of the field `P` in the limb layout of `fp_mul_const`.
+///
+/// The constructors check that the value is reduced, so a constant of one field can't be used
+/// by mistake in another field with a smaller modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FpConstant<P: FieldParameters> {
+    limbs: [u16; MAX_NB_LIMBS],
+    _marker: PhantomData<P>,
+}
+
+impl<P: FieldParameters> FpConstant<P> {
+    /// The constant of value `value`, which must be below the modulus of `P`.
+    pub fn new(value: &BigUint) -> Self {
+        assert!(
+            *value < P::modulus(),
+            "the constant {value} is not below the field modulus"
+        );
+        let mut limbs = [0u16; MAX_NB_LIMBS];
+        limbs[..P::NB_LIMBS].copy_from_slice(&bigint_into_u16_digits(value, P::NB_LIMBS));
+        Self {
+            limbs,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self::new(&BigUint::from(value))
+    }
+
+    /// The constant given by big-endian hexadecimal digits, with or without a `0x` prefix.
+    pub fn from_hex(hex: &str) -> Self {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        let value = BigUint::from_str_radix(digits, 16)
+            .unwrap_or_else(|_| panic!("invalid hexadecimal constant {hex}"));
+        Self::new(&value)
+    }
+
+    /// The constant `-value`, for a `value` below the modulus of `P`.
+    pub fn from_neg(value: &BigUint) -> Self {
+        let modulus = P::modulus();
+        assert!(
+            *value < modulus,
+            "the constant {value} is not below the field modulus"
+        );
+        Self::new(&((&modulus - value) % &modulus))
+    }
+
+    pub fn limbs(&self) -> [u16; MAX_NB_LIMBS] {
+        self.limbs
+    }
+
+    pub fn value(&self) -> BigUint {
+        digits_to_biguint(&self.limbs)
+    }
+}
+
 impl<L: AirParameters> AirBuilder<L> {
     pub fn fp_constant<P: FieldParameters>(&mut self, num: &BigUint) -> FieldRegister<P> {
         let poly =
@@ -22,3 +81,42 @@ impl<L: AirParameters> AirBuilder<L> {
         self.fp_constant(&BigUint::one())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[test]
+    fn test_fp_constant() {
+        type P = Fp25519;
+
+        let p = P::modulus();
+        let value = BigUint::from(121665u32);
+        let constant = FpConstant::<P>::new(&value);
+        assert_eq!(constant.limbs()[..3], [56129, 1, 0]);
+        assert_eq!(constant.value(), value);
+        assert_eq!(FpConstant::<P>::from_u64(121665), constant);
+        assert_eq!(FpConstant::<P>::from_hex("0x1db41"), constant);
+        assert_eq!(FpConstant::<P>::from_hex("1DB41"), constant);
+
+        let minus_one = FpConstant::<P>::from_neg(&BigUint::one());
+        assert_eq!(minus_one.value(), &p - 1u32);
+        assert_eq!(
+            FpConstant::<P>::from_neg(&BigUint::zero()).value(),
+            BigUint::zero()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not below the field modulus")]
+    fn test_fp_constant_unreduced() {
+        FpConstant::<Fp25519>::new(&Fp25519::modulus());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid hexadecimal constant")]
+    fn test_fp_constant_invalid_hex() {
+        FpConstant::<Fp25519>::from_hex("0x12g4");
+    }
+}
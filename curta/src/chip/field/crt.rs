@@ -0,0 +1,313 @@
+//! Multiplication checked modulo two coprime moduli.
+//!
+//! The relation `a * b - result - carry * p = 0` of `fp_mul` is an identity of integers of absolute
+//! value less than `2^(32 * n + 1)` for `n = P::NB_LIMBS`. By the Chinese remainder theorem, it is
+//! enough to check it modulo the order `q` of the native field and modulo `2^(16 * k)`, as long as
+//! `q * 2^(16 * k)` exceeds that bound.
+//!
+//! The check modulo `q` is a single constraint evaluating the vanishing polynomial at `2^16` in the
+//! native field. The check modulo `2^(16 * k)` only needs the carries of the `k` lowest limbs, so
+//! the instruction witnesses `k = crt_witness_limbs::<P>(q)` carries instead of the
+//! `P::NB_WITNESS_LIMBS` of `FpMulInstruction`. Over Goldilocks, this saves one carry, that is two
+//! `u16` columns, for every multiplication.
+
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Field multiplication checked modulo the native field and modulo `2^(16 * k)`, where `k` is the
+/// length of the witness arrays.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpCrtMulInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+/// The number of limb carries that a `FpCrtMulInstruction` over a native field of order
+/// `native_order` checks.
+///
+/// This is the smallest `k` with `2^(floor(log2(native_order)) + 16 * k) >= 2^(32 * n + 1)`, which
+/// bounds the absolute value of the vanishing integer, capped at `P::NB_WITNESS_LIMBS`.
+pub fn crt_witness_limbs<P: FieldParameters>(native_order: u64) -> usize {
+    let native_bits = 63 - native_order.leading_zeros() as usize;
+    let bound_bits = 2 * P::NB_BITS_PER_LIMB * P::NB_LIMBS + 1;
+    let nb_limbs = bound_bits
+        .saturating_sub(native_bits)
+        .div_ceil(P::NB_BITS_PER_LIMB);
+    nb_limbs.min(P::NB_WITNESS_LIMBS)
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given two field elements `a` and `b`, computes the product `a * b = c`, checked modulo the
+    /// native field and a power of two instead of by a full carry chain.
+    pub fn fp_mul_crt<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpCrtMulInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let nb_witness_limbs = crt_witness_limbs::<P>(L::Field::order());
+
+        let result: FieldRegister<P>;
+        let carry: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+
+        if is_trace {
+            result = self.alloc::<FieldRegister<P>>();
+            carry = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(nb_witness_limbs);
+            witness_high = self.alloc_array::<U16Register>(nb_witness_limbs);
+        } else {
+            result = self.alloc_public::<FieldRegister<P>>();
+            carry = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(nb_witness_limbs);
+            witness_high = self.alloc_array_public::<U16Register>(nb_witness_limbs);
+        }
+        let instr = FpCrtMulInstruction {
+            a: *a,
+            b: *b,
+            result,
+            carry,
+            witness_low,
+            witness_high,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        result
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpCrtMulInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+
+        // Compute the vanishing polynomial a(x) * b(x) - result(x) - carry(x) * p(x).
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_a_mul_b_minus_result = parser.poly_sub(&p_a_mul_b, &p_result);
+        let p_limbs = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+        let p_mul_times_carry = parser.poly_mul(&p_carry, &p_limbs);
+        let p_vanishing = parser.poly_sub(&p_a_mul_b_minus_result, &p_mul_times_carry);
+
+        // Check the relation modulo the native field by evaluating the vanishing polynomial at
+        // x = 2^16.
+        let limb_field = AP::Field::from_canonical_u32(1 << 16);
+        let mut power = AP::Field::ONE;
+        let mut evaluation = parser.zero();
+        for coefficient in p_vanishing.coefficients.iter() {
+            let term = parser.mul_const(*coefficient, power);
+            evaluation = parser.add(evaluation, term);
+            power *= limb_field;
+        }
+        parser.constraint(evaluation);
+
+        // Reconstruct and shift back the carries of the lowest limbs.
+        let offset = parser.constant(AP::Field::from_canonical_u32(P::WITNESS_OFFSET as u32));
+        let witness_low = self.witness_low.eval_vec(parser);
+        let witness_high = self.witness_high.eval_vec(parser);
+        let carries = witness_low
+            .into_iter()
+            .zip(witness_high)
+            .map(|(low, high)| {
+                let high_mul_limb = parser.mul_const(high, limb_field);
+                let shifted = parser.add(low, high_mul_limb);
+                parser.sub(shifted, offset)
+            })
+            .collect::<Vec<_>>();
+
+        // Check the relation modulo 2^(16 * k) by the carry chain
+        // v_i = w_(i - 1) - 2^16 * w_i of the lowest k limbs, where w_(-1) = 0.
+        let mut carry_in = parser.zero();
+        for (coefficient, carry_out) in p_vanishing.coefficients.iter().zip(carries) {
+            let carry_out_mul_limb = parser.mul_const(carry_out, limb_field);
+            let carry_chain = parser.sub(carry_in, carry_out_mul_limb);
+            parser.assert_eq(*coefficient, carry_chain);
+            carry_in = carry_out;
+        }
+    }
+}
+
+/// The values of the registers written by a `FpCrtMulInstruction`.
+struct CrtMulWitness<F> {
+    result: Polynomial<F>,
+    carry: Polynomial<F>,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> FpCrtMulInstruction<P> {
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+        nb_witness_limbs: usize,
+    ) -> CrtMulWitness<F> {
+        let [a, b] = [p_a, p_b].map(|p| field_limbs_to_biguint(p.coefficients()));
+
+        // Compute field multiplication in the integers.
+        let modulus = P::modulus();
+        let result = (&a * &b) % &modulus;
+        let carry = (&a * &b - &result) / &modulus;
+        debug_assert!(carry < modulus);
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(&carry);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = p_a * p_b - &p_result - &p_carry * &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witness and keep the carries of the lowest limbs.
+        let mut p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        p_witness.truncate(nb_witness_limbs);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        CrtMulWitness {
+            result: p_result,
+            carry: p_carry,
+            witness_low,
+            witness_high,
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpCrtMulInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let witness = Self::witness(&p_a, &p_b, self.witness_low.len());
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.carry, &witness.carry, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let witness = Self::witness(&p_a, &p_b, self.witness_low.len());
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.carry, &witness.carry);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpCrtMulTest;
+
+    impl AirParameters for FpCrtMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 138;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 216;
+
+        type Instruction = FpCrtMulInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_crt_witness_limbs() {
+        // For 256-bit integers over Goldilocks, 63 + 16 * 29 >= 513.
+        assert_eq!(crt_witness_limbs::<Fp25519>(GoldilocksField::order()), 29);
+        assert_eq!(Fp25519::NB_WITNESS_LIMBS, 30);
+        // A small native field needs all the carries.
+        assert_eq!(crt_witness_limbs::<Fp25519>(1 << 8), 30);
+    }
+
+    #[test]
+    fn test_fp_mul_crt() {
+        type F = GoldilocksField;
+        type L = FpCrtMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let result = builder.fp_mul_crt(&a, &b);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Random values, and the largest field elements.
+            let (a_int, b_int): (BigUint, BigUint) = match i % 4 {
+                0 => (&p - 1u32, &p - 1u32),
+                _ => (rng.gen_biguint(256) % &p, rng.gen_biguint(256) % &p),
+            };
+            let expected_int = (&a_int * &b_int) % &p;
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -22,13 +22,13 @@ use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct FpDenInstruction<P: FieldParameters> {
-    a: FieldRegister<P>,
-    b: FieldRegister<P>,
-    sign: bool,
+    pub(crate) a: FieldRegister<P>,
+    pub(crate) b: FieldRegister<P>,
+    pub(crate) sign: bool,
     pub result: FieldRegister<P>,
-    carry: FieldRegister<P>,
-    witness_low: ArrayRegister<U16Register>,
-    witness_high: ArrayRegister<U16Register>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
 }
 
 impl<L: AirParameters> AirBuilder<L> {
@@ -20,6 +20,11 @@ use crate::polynomial::to_u16_le_limbs_polynomial;
 /// Fp Division. Computes `a / b = result`.
 ///
 /// This is done by computing `b_inv = b^(-1)` followed by `a * b_inv = result`.
+///
+/// Constraining `result * b = a` alone would take a single multiplication, but any `result`
+/// would then satisfy `0 / 0`. The callers rely on the division failing for `b = 0`, such as the
+/// slope of an incomplete addition of points with the same x-coordinate or the nonzero checks
+/// of ECDSA, so the inverse of `b` is constrained as well.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct FpDivInstruction<P: FieldParameters> {
@@ -0,0 +1,496 @@
+use core::ops::{Mul, Sub};
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::fp2::{Fp2, Fp2Register};
+use super::fp6::{Fp6, Fp6Register};
+use super::TowerParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::AirParameters;
+
+/// An element `c0 + c1 * w` of the quadratic extension `Fp12 = Fp6[w] / (w^2 - v)`, the target
+/// field of the pairing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp12<P: TowerParameters> {
+    pub c0: Fp6<P>,
+    pub c1: Fp6<P>,
+}
+
+/// The coefficients `xi^((p - 1) * k / 6)` of the Frobenius map on the basis `w^k`.
+pub fn frobenius_coefficients<P: TowerParameters>() -> [Fp2<P>; 6] {
+    let p = P::BaseField::modulus();
+    let xi = Fp2::<P>::one().mul_by_nonresidue();
+    core::array::from_fn(|k| xi.pow(&((&p - 1u32) * k / 6u32)))
+}
+
+impl<P: TowerParameters> Fp12<P> {
+    pub fn new(c0: Fp6<P>, c1: Fp6<P>) -> Self {
+        Self { c0, c1 }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(Fp6::zero(), Fp6::zero())
+    }
+
+    pub fn one() -> Self {
+        Self::new(Fp6::one(), Fp6::zero())
+    }
+
+    /// The conjugation `c0 - c1 * w`, which is the inverse on the unitary elements of `Fp12`.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.c0.clone(), -&self.c1)
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    pub fn inverse(&self) -> Self {
+        let norm = &self.c0.square() - &self.c1.square().mul_by_nonresidue();
+        let norm_inv = norm.inverse();
+        Self::new(&self.c0 * &norm_inv, -&(&self.c1 * &norm_inv))
+    }
+
+    /// The `p`-power Frobenius map.
+    ///
+    /// Writing the element as `sum_k a_k w^k` with `a_k` in `Fp2`, the map sends each `a_k` to
+    /// `conj(a_k) * xi^((p - 1) * k / 6)`.
+    pub fn frobenius_map(&self) -> Self {
+        let gamma = frobenius_coefficients::<P>();
+        let [a0, a2, a4, a1, a3, a5] = [
+            &self.c0.c0,
+            &self.c0.c1,
+            &self.c0.c2,
+            &self.c1.c0,
+            &self.c1.c1,
+            &self.c1.c2,
+        ]
+        .map(|a| a.conjugate());
+        Self::new(
+            Fp6::new(&a0 * &gamma[0], &a2 * &gamma[2], &a4 * &gamma[4]),
+            Fp6::new(&a1 * &gamma[1], &a3 * &gamma[3], &a5 * &gamma[5]),
+        )
+    }
+
+    /// Squares an element of the cyclotomic subgroup, the elements `f` with
+    /// `f^(p^4 - p^2 + 1) = 1`, with the formulas of Granger and Scott.
+    ///
+    /// Viewing `Fp12` as a cubic extension of `Fp4 = Fp2[y] / (y^2 - xi)`, the square is obtained
+    /// from the squares in `Fp4` of `a0 + a3 * y`, `a1 + a4 * y` and `a2 + a5 * y`, where `a_k`
+    /// is the coefficient of `w^k`. The result is wrong for other elements of `Fp12`.
+    ///
+    /// Reference: https://eprint.iacr.org/2009/565
+    pub fn cyclotomic_square(&self) -> Self {
+        let fp4_square = |a: &Fp2<P>, b: &Fp2<P>| {
+            let ab = a * b;
+            let product = &(a + b) * &(a + &b.mul_by_nonresidue());
+            (&product - &(&ab + &ab.mul_by_nonresidue()), &ab + &ab)
+        };
+        // 3 * t - 2 * z and 3 * t + 2 * z.
+        let sub = |t: &Fp2<P>, z: &Fp2<P>| {
+            let d = t - z;
+            &(&d + &d) + t
+        };
+        let add = |t: &Fp2<P>, z: &Fp2<P>| {
+            let s = t + z;
+            &(&s + &s) + t
+        };
+
+        let (t0, t1) = fp4_square(&self.c0.c0, &self.c1.c1);
+        let (t2, t3) = fp4_square(&self.c1.c0, &self.c0.c2);
+        let (t4, t5) = fp4_square(&self.c0.c1, &self.c1.c2);
+        let t5 = t5.mul_by_nonresidue();
+        Self::new(
+            Fp6::new(
+                sub(&t0, &self.c0.c0),
+                sub(&t2, &self.c0.c1),
+                sub(&t4, &self.c0.c2),
+            ),
+            Fp6::new(
+                add(&t5, &self.c1.c0),
+                add(&t1, &self.c1.c1),
+                add(&t3, &self.c1.c2),
+            ),
+        )
+    }
+
+    pub fn pow(&self, exp: &BigUint) -> Self {
+        let mut result = Self::one();
+        for i in (0..exp.bits()).rev() {
+            result = result.square();
+            if exp.bit(i) {
+                result = &result * self;
+            }
+        }
+        result
+    }
+}
+
+impl<P: TowerParameters> Sub<&Fp12<P>> for &Fp12<P> {
+    type Output = Fp12<P>;
+
+    fn sub(self, other: &Fp12<P>) -> Fp12<P> {
+        Fp12::new(&self.c0 - &other.c0, &self.c1 - &other.c1)
+    }
+}
+
+impl<P: TowerParameters> Mul<&Fp12<P>> for &Fp12<P> {
+    type Output = Fp12<P>;
+
+    fn mul(self, other: &Fp12<P>) -> Fp12<P> {
+        let t0 = &self.c0 * &other.c0;
+        let t1 = &self.c1 * &other.c1;
+        let c1 = &(&self.c0 * &other.c1) + &(&self.c1 * &other.c0);
+        Fp12::new(&t0 + &t1.mul_by_nonresidue(), c1)
+    }
+}
+
+/// A register for an element `c0 + c1 * w` of `Fp12`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fp12Register<P: TowerParameters> {
+    pub c0: Fp6Register<P>,
+    pub c1: Fp6Register<P>,
+}
+
+impl<P: TowerParameters> Fp12Register<P> {
+    pub fn new(c0: Fp6Register<P>, c1: Fp6Register<P>) -> Self {
+        Self { c0, c1 }
+    }
+
+    /// The `Fp2` coefficients in the order `c0.c0, c0.c1, c0.c2, c1.c0, c1.c1, c1.c2`.
+    pub fn coefficients(&self) -> [Fp2Register<P>; 6] {
+        [
+            self.c0.c0, self.c0.c1, self.c0.c2, self.c1.c0, self.c1.c1, self.c1.c2,
+        ]
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_fp12<P: TowerParameters>(&mut self) -> Fp12Register<P> {
+        Fp12Register::new(self.alloc_fp6(), self.alloc_fp6())
+    }
+
+    pub fn alloc_public_fp12<P: TowerParameters>(&mut self) -> Fp12Register<P> {
+        Fp12Register::new(self.alloc_public_fp6(), self.alloc_public_fp6())
+    }
+
+    pub fn fp12_constant<P: TowerParameters>(&mut self, value: &Fp12<P>) -> Fp12Register<P> {
+        Fp12Register::new(self.fp6_constant(&value.c0), self.fp6_constant(&value.c1))
+    }
+
+    /// Computes `a * b` with the Karatsuba formulas, using three multiplications in `Fp6`.
+    pub fn fp12_mul<P: TowerParameters>(
+        &mut self,
+        a: &Fp12Register<P>,
+        b: &Fp12Register<P>,
+    ) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let t0 = self.fp6_mul(&a.c0, &b.c0);
+        let t1 = self.fp6_mul(&a.c1, &b.c1);
+
+        // c0 = t0 + v * t1
+        let t1_mul_nonresidue = self.fp6_mul_by_nonresidue(&t1);
+        let c0 = self.fp6_add(&t0, &t1_mul_nonresidue);
+
+        // c1 = (a0 + a1) * (b0 + b1) - t0 - t1
+        let a0_plus_a1 = self.fp6_add(&a.c0, &a.c1);
+        let b0_plus_b1 = self.fp6_add(&b.c0, &b.c1);
+        let cross = self.fp6_mul(&a0_plus_a1, &b0_plus_b1);
+        let cross = self.fp6_sub(&cross, &t0);
+        let c1 = self.fp6_sub(&cross, &t1);
+
+        Fp12Register::new(c0, c1)
+    }
+
+    /// Computes `a^2` with the complex squaring formulas, using two multiplications in `Fp6`.
+    pub fn fp12_square<P: TowerParameters>(&mut self, a: &Fp12Register<P>) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let t = self.fp6_mul(&a.c0, &a.c1);
+
+        // c0 = (a0 + a1) * (a0 + v * a1) - t - v * t
+        let a0_plus_a1 = self.fp6_add(&a.c0, &a.c1);
+        let a1_mul_nonresidue = self.fp6_mul_by_nonresidue(&a.c1);
+        let a0_plus_v_a1 = self.fp6_add(&a.c0, &a1_mul_nonresidue);
+        let product = self.fp6_mul(&a0_plus_a1, &a0_plus_v_a1);
+        let t_mul_nonresidue = self.fp6_mul_by_nonresidue(&t);
+        let product = self.fp6_sub(&product, &t);
+        let c0 = self.fp6_sub(&product, &t_mul_nonresidue);
+
+        // c1 = 2 * t
+        let c1 = self.fp6_add(&t, &t);
+
+        Fp12Register::new(c0, c1)
+    }
+
+    /// Squares an element of the cyclotomic subgroup, see `Fp12::cyclotomic_square`.
+    ///
+    /// This takes six multiplications in `Fp2` instead of the twelve of `fp12_square`, which
+    /// makes it the squaring of the exponentiations in the hard part of a final exponentiation.
+    pub fn fp12_cyclotomic_square<P: TowerParameters>(
+        &mut self,
+        a: &Fp12Register<P>,
+    ) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        // The square `(a^2 + xi * b^2) + 2 * a * b * y` of `a + b * y` in `Fp4`.
+        let mut fp4_square = |a: &Fp2Register<P>, b: &Fp2Register<P>| {
+            let ab = self.fp2_mul(a, b);
+            let a_plus_b = self.fp2_add(a, b);
+            let xi_b = self.fp2_mul_by_nonresidue(b);
+            let a_plus_xi_b = self.fp2_add(a, &xi_b);
+            let product = self.fp2_mul(&a_plus_b, &a_plus_xi_b);
+            let xi_ab = self.fp2_mul_by_nonresidue(&ab);
+            let product = self.fp2_sub(&product, &ab);
+            let c0 = self.fp2_sub(&product, &xi_ab);
+            let c1 = self.fp2_add(&ab, &ab);
+            (c0, c1)
+        };
+        let (t0, t1) = fp4_square(&a.c0.c0, &a.c1.c1);
+        let (t2, t3) = fp4_square(&a.c1.c0, &a.c0.c2);
+        let (t4, t5) = fp4_square(&a.c0.c1, &a.c1.c2);
+        let t5 = self.fp2_mul_by_nonresidue(&t5);
+
+        // 3 * t - 2 * z for the coefficients of `c0` and 3 * t + 2 * z for those of `c1`.
+        let mut combine = |t: &Fp2Register<P>, z: &Fp2Register<P>, is_sub: bool| {
+            let t_z = if is_sub {
+                self.fp2_sub(t, z)
+            } else {
+                self.fp2_add(t, z)
+            };
+            let t_z = self.fp2_add(&t_z, &t_z);
+            self.fp2_add(&t_z, t)
+        };
+        let c0 = Fp6Register::new(
+            combine(&t0, &a.c0.c0, true),
+            combine(&t2, &a.c0.c1, true),
+            combine(&t4, &a.c0.c2, true),
+        );
+        let c1 = Fp6Register::new(
+            combine(&t5, &a.c1.c0, false),
+            combine(&t1, &a.c1.c1, false),
+            combine(&t3, &a.c1.c2, false),
+        );
+        Fp12Register::new(c0, c1)
+    }
+
+    pub fn fp12_conjugate<P: TowerParameters>(&mut self, a: &Fp12Register<P>) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c1 = self.fp6_neg(&a.c1);
+        Fp12Register::new(a.c0, c1)
+    }
+
+    /// Computes `a^(-1) = (a0 - a1 * w) / (a0^2 - v * a1^2)`.
+    pub fn fp12_inverse<P: TowerParameters>(&mut self, a: &Fp12Register<P>) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let a0_sq = self.fp6_square(&a.c0);
+        let a1_sq = self.fp6_square(&a.c1);
+        let a1_sq = self.fp6_mul_by_nonresidue(&a1_sq);
+        let norm = self.fp6_sub(&a0_sq, &a1_sq);
+        let norm_inv = self.fp6_inverse(&norm);
+
+        let c0 = self.fp6_mul(&a.c0, &norm_inv);
+        let c1 = self.fp6_mul(&a.c1, &norm_inv);
+        let c1 = self.fp6_neg(&c1);
+        Fp12Register::new(c0, c1)
+    }
+
+    /// The `p`-power Frobenius map, see `Fp12::frobenius_map`.
+    pub fn fp12_frobenius_map<P: TowerParameters>(&mut self, a: &Fp12Register<P>) -> Fp12Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let gamma = frobenius_coefficients::<P>();
+        // The coefficients of `w^0, ..., w^5`.
+        let mut coefficients = [a.c0.c0, a.c1.c0, a.c0.c1, a.c1.c1, a.c0.c2, a.c1.c2];
+        for (k, a_k) in coefficients.iter_mut().enumerate() {
+            *a_k = self.fp2_conjugate(a_k);
+            if k > 0 {
+                let gamma_k = self.fp2_constant(&gamma[k]);
+                *a_k = self.fp2_mul(a_k, &gamma_k);
+            }
+        }
+        let [b0, b1, b2, b3, b4, b5] = coefficients;
+        Fp12Register::new(Fp6Register::new(b0, b2, b4), Fp6Register::new(b1, b3, b5))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::params::Bls12381BaseField;
+    use crate::chip::ec::bls12_381::tower::Bls12381Tower;
+    use crate::chip::ec::bn254::tower::Bn254Tower;
+    use crate::chip::field::extension::fp2::tests::{random_fp2, read_fp2, write_fp2};
+    use crate::chip::field::extension::fp6::tests::random_fp6;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::trace::writer::TraceWriter;
+    use crate::math::prelude::*;
+
+    fn random_fp12<P: TowerParameters>() -> Fp12<P> {
+        Fp12::new(random_fp6(), random_fp6())
+    }
+
+    fn coefficients<P: TowerParameters>(value: &Fp12<P>) -> [&Fp2<P>; 6] {
+        [
+            &value.c0.c0,
+            &value.c0.c1,
+            &value.c0.c2,
+            &value.c1.c0,
+            &value.c1.c1,
+            &value.c1.c2,
+        ]
+    }
+
+    pub(crate) fn write_fp12<F: PrimeField64, P: TowerParameters>(
+        writer: &TraceWriter<F>,
+        data: &Fp12Register<P>,
+        value: &Fp12<P>,
+        row_index: usize,
+    ) {
+        for (register, coefficient) in data.coefficients().iter().zip(coefficients(value)) {
+            write_fp2(writer, register, coefficient, row_index);
+        }
+    }
+
+    pub(crate) fn read_fp12<F: PrimeField64, P: TowerParameters>(
+        writer: &TraceWriter<F>,
+        data: &Fp12Register<P>,
+        row_index: usize,
+    ) -> Fp12<P> {
+        let [a0, a1, a2, a3, a4, a5] = data
+            .coefficients()
+            .map(|register| read_fp2(writer, &register, row_index));
+        Fp12::new(Fp6::new(a0, a1, a2), Fp6::new(a3, a4, a5))
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Fp12OpsTest;
+
+    impl AirParameters for Fp12OpsTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 516;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 783;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    fn check_fp12_arithmetic<P: TowerParameters>() {
+        let a = random_fp12::<P>();
+        let b = random_fp12::<P>();
+
+        assert_eq!(&(&a * &b) * &b.inverse(), a);
+        assert_eq!(a.square(), &a * &a);
+        assert_eq!(&(&a * &b) - &(&b * &a), Fp12::zero());
+
+        // w^2 = v.
+        let w = Fp12::<P>::new(Fp6::zero(), Fp6::one());
+        let v = Fp12::new(Fp6::new(Fp2::zero(), Fp2::one(), Fp2::zero()), Fp6::zero());
+        assert_eq!(w.square(), v);
+
+        // The Frobenius map is the p-th power and has order 12.
+        let p = P::BaseField::modulus();
+        assert_eq!(a.frobenius_map(), a.pow(&p));
+        let mut a_frob = a.clone();
+        for _ in 0..12 {
+            a_frob = a_frob.frobenius_map();
+        }
+        assert_eq!(a_frob, a);
+
+        // Elements of the form f^(p^6 - 1) are unitary, so their conjugate is their inverse.
+        let unitary = &a.conjugate() * &a.inverse();
+        assert_eq!(&unitary * &unitary.conjugate(), Fp12::one());
+
+        // Elements of the form f^((p^6 - 1) * (p^2 + 1)) are in the cyclotomic subgroup.
+        let cyclotomic = &unitary.frobenius_map().frobenius_map() * &unitary;
+        assert_eq!(cyclotomic.cyclotomic_square(), cyclotomic.square());
+        assert_ne!(a.cyclotomic_square(), a.square());
+    }
+
+    #[test]
+    fn test_fp12_arithmetic() {
+        check_fp12_arithmetic::<Bls12381Tower>();
+        check_fp12_arithmetic::<Bn254Tower>();
+    }
+
+    #[test]
+    fn test_fp12_ops() {
+        type L = Fp12OpsTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        // The Fp12 operations act on public inputs as global instructions.
+        let a_pub = builder.alloc_public_fp12::<Bls12381Tower>();
+        let b_pub = builder.alloc_public_fp12::<Bls12381Tower>();
+        let mul_pub = builder.fp12_mul(&a_pub, &b_pub);
+        let square_pub = builder.fp12_square(&a_pub);
+        let inverse_pub = builder.fp12_inverse(&a_pub);
+        let frobenius_pub = builder.fp12_frobenius_map(&a_pub);
+        let conjugate_pub = builder.fp12_conjugate(&a_pub);
+        let c_pub = builder.alloc_public_fp12::<Bls12381Tower>();
+        let cyclotomic_square_pub = builder.fp12_cyclotomic_square(&c_pub);
+
+        let x = builder.alloc_fp2::<Bls12381Tower>();
+        let y = builder.alloc_fp2::<Bls12381Tower>();
+        let z = builder.fp2_mul(&x, &y);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let a_int = random_fp12::<Bls12381Tower>();
+        let b_int = random_fp12::<Bls12381Tower>();
+        let x_int = random_fp2::<Bls12381Tower>();
+        let y_int = random_fp2::<Bls12381Tower>();
+        let z_int = &x_int * &y_int;
+        let writer = generator.new_writer();
+        write_fp12(&writer, &a_pub, &a_int, 0);
+        write_fp12(&writer, &b_pub, &b_int, 0);
+        let c_int = &a_int.conjugate() * &a_int.inverse();
+        let c_int = &c_int.frobenius_map().frobenius_map() * &c_int;
+        write_fp12(&writer, &c_pub, &c_int, 0);
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            write_fp2(&writer, &x, &x_int, i);
+            write_fp2(&writer, &y, &y_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(read_fp2(&writer, &z, i), z_int);
+        });
+
+        assert_eq!(read_fp12(&writer, &mul_pub, 0), &a_int * &b_int);
+        assert_eq!(read_fp12(&writer, &square_pub, 0), a_int.square());
+        assert_eq!(read_fp12(&writer, &inverse_pub, 0), a_int.inverse());
+        assert_eq!(read_fp12(&writer, &frobenius_pub, 0), a_int.frobenius_map());
+        assert_eq!(read_fp12(&writer, &conjugate_pub, 0), a_int.conjugate());
+        assert_eq!(
+            read_fp12(&writer, &cyclotomic_square_pub, 0),
+            c_int.square()
+        );
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,552 @@
+use core::marker::PhantomData;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::TowerParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::utils::field_limbs_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// An element `c0 + c1 * u` of the quadratic extension `Fp2 = Fp[u] / (u^2 + 1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp2<P: TowerParameters> {
+    pub c0: BigUint,
+    pub c1: BigUint,
+    _marker: PhantomData<P>,
+}
+
+impl<P: TowerParameters> Fp2<P> {
+    pub fn new(c0: BigUint, c1: BigUint) -> Self {
+        let p = P::BaseField::modulus();
+        Self {
+            c0: c0 % &p,
+            c1: c1 % &p,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(BigUint::zero(), BigUint::zero())
+    }
+
+    pub fn one() -> Self {
+        Self::new(BigUint::one(), BigUint::zero())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.c0.is_zero() && self.c1.is_zero()
+    }
+
+    pub fn conjugate(&self) -> Self {
+        let p = P::BaseField::modulus();
+        Self::new(self.c0.clone(), &p - &self.c1)
+    }
+
+    pub fn mul_by_fp(&self, b: &BigUint) -> Self {
+        Self::new(&self.c0 * b, &self.c1 * b)
+    }
+
+    /// Multiplies by the non-residue `xi = c + u` used to build `Fp6` on top of `Fp2`.
+    pub fn mul_by_nonresidue(&self) -> Self {
+        let p = P::BaseField::modulus();
+        let c = BigUint::from(P::NONRESIDUE);
+        Self::new(&c * &self.c0 + &p - &self.c1, &self.c0 + &c * &self.c1)
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    pub fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "cannot invert zero");
+        let p = P::BaseField::modulus();
+        let norm = (&self.c0 * &self.c0 + &self.c1 * &self.c1) % &p;
+        let norm_inv = norm.modpow(&(&p - 2u32), &p);
+        Self::new(&self.c0 * &norm_inv, (&p - &self.c1) * &norm_inv)
+    }
+
+    pub fn pow(&self, exp: &BigUint) -> Self {
+        let mut result = Self::one();
+        for i in (0..exp.bits()).rev() {
+            result = result.square();
+            if exp.bit(i) {
+                result = &result * self;
+            }
+        }
+        result
+    }
+
+    /// An element of `Fp2` is a square if and only if its norm `c0^2 + c1^2` is a square in `Fp`.
+    pub fn is_square(&self) -> bool {
+        let p = P::BaseField::modulus();
+        let norm = (&self.c0 * &self.c0 + &self.c1 * &self.c1) % &p;
+        norm.is_zero() || norm.modpow(&((&p - 1u32) >> 1), &p).is_one()
+    }
+
+    /// A square root of `self`, if it exists.
+    ///
+    /// Uses the algorithm for `p = 3 mod 4` from https://eprint.iacr.org/2012/685 (Algorithm 9).
+    pub fn sqrt(&self) -> Option<Self> {
+        let p = P::BaseField::modulus();
+        let minus_one = Self::new(&p - 1u32, BigUint::zero());
+
+        let a1 = self.pow(&((&p - 3u32) >> 2));
+        let alpha = &a1.square() * self;
+        let x0 = &a1 * self;
+        let root = if alpha == minus_one {
+            &Self::new(BigUint::zero(), BigUint::one()) * &x0
+        } else {
+            let b = (&Self::one() + &alpha).pow(&((&p - 1u32) >> 1));
+            &b * &x0
+        };
+        (root.square() == *self).then_some(root)
+    }
+
+    /// The sign `sgn0` of RFC 9380, which is the parity of `c0`, or of `c1` if `c0` is zero.
+    pub fn sgn0(&self) -> bool {
+        self.c0.bit(0) || (self.c0.is_zero() && self.c1.bit(0))
+    }
+}
+
+impl<P: TowerParameters> Add<&Fp2<P>> for &Fp2<P> {
+    type Output = Fp2<P>;
+
+    fn add(self, other: &Fp2<P>) -> Fp2<P> {
+        Fp2::new(&self.c0 + &other.c0, &self.c1 + &other.c1)
+    }
+}
+
+impl<P: TowerParameters> Sub<&Fp2<P>> for &Fp2<P> {
+    type Output = Fp2<P>;
+
+    fn sub(self, other: &Fp2<P>) -> Fp2<P> {
+        let p = P::BaseField::modulus();
+        Fp2::new(&self.c0 + &p - &other.c0, &self.c1 + &p - &other.c1)
+    }
+}
+
+impl<P: TowerParameters> Mul<&Fp2<P>> for &Fp2<P> {
+    type Output = Fp2<P>;
+
+    fn mul(self, other: &Fp2<P>) -> Fp2<P> {
+        let p = P::BaseField::modulus();
+        let a0_b0 = &self.c0 * &other.c0;
+        let a1_b1 = &self.c1 * &other.c1;
+        Fp2::new(
+            a0_b0 + &p * &p - a1_b1,
+            &self.c0 * &other.c1 + &self.c1 * &other.c0,
+        )
+    }
+}
+
+impl<P: TowerParameters> Neg for &Fp2<P> {
+    type Output = Fp2<P>;
+
+    fn neg(self) -> Fp2<P> {
+        &Fp2::zero() - self
+    }
+}
+
+/// A register for an element `c0 + c1 * u` of `Fp2`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fp2Register<P: TowerParameters> {
+    pub c0: FieldRegister<P::BaseField>,
+    pub c1: FieldRegister<P::BaseField>,
+}
+
+impl<P: TowerParameters> Fp2Register<P> {
+    pub fn new(c0: FieldRegister<P::BaseField>, c1: FieldRegister<P::BaseField>) -> Self {
+        Self { c0, c1 }
+    }
+}
+
+pub trait Fp2AirWriter: AirWriter {
+    fn read_fp2<P: TowerParameters>(&self, data: &Fp2Register<P>) -> Fp2<P>
+    where
+        Self::Field: PrimeField64,
+    {
+        let c0 = self.read(&data.c0);
+        let c1 = self.read(&data.c1);
+        Fp2::new(
+            field_limbs_to_biguint(c0.coefficients()),
+            field_limbs_to_biguint(c1.coefficients()),
+        )
+    }
+
+    fn write_fp2<P: TowerParameters>(&mut self, data: &Fp2Register<P>, value: &Fp2<P>) {
+        for (register, coefficient) in [(data.c0, &value.c0), (data.c1, &value.c1)] {
+            let limbs = to_u16_le_limbs_polynomial::<Self::Field, P::BaseField>(coefficient);
+            self.write(&register, &limbs);
+        }
+    }
+}
+
+impl<W: AirWriter> Fp2AirWriter for W {}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_fp2<P: TowerParameters>(&mut self) -> Fp2Register<P> {
+        Fp2Register::new(self.alloc(), self.alloc())
+    }
+
+    pub fn alloc_public_fp2<P: TowerParameters>(&mut self) -> Fp2Register<P> {
+        Fp2Register::new(self.alloc_public(), self.alloc_public())
+    }
+
+    pub fn fp2_constant<P: TowerParameters>(&mut self, value: &Fp2<P>) -> Fp2Register<P> {
+        Fp2Register::new(self.fp_constant(&value.c0), self.fp_constant(&value.c1))
+    }
+
+    pub fn fp2_assert_equal<P: TowerParameters>(&mut self, a: &Fp2Register<P>, b: &Fp2Register<P>) {
+        self.assert_equal(&a.c0, &b.c0);
+        self.assert_equal(&a.c1, &b.c1);
+    }
+
+    pub fn fp2_add<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        b: &Fp2Register<P>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_add(&a.c0, &b.c0);
+        let c1 = self.fp_add(&a.c1, &b.c1);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_sub<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        b: &Fp2Register<P>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_sub(&a.c0, &b.c0);
+        let c1 = self.fp_sub(&a.c1, &b.c1);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_neg<P: TowerParameters>(&mut self, a: &Fp2Register<P>) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_neg(&a.c0);
+        let c1 = self.fp_neg(&a.c1);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_conjugate<P: TowerParameters>(&mut self, a: &Fp2Register<P>) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c1 = self.fp_neg(&a.c1);
+        Fp2Register::new(a.c0, c1)
+    }
+
+    /// Computes `a * b` as `(a0 * b0 - a1 * b1) + (a0 * b1 + a1 * b0) * u`, with a
+    /// multiply-subtract and an inner product.
+    pub fn fp2_mul<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        b: &Fp2Register<P>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_mul_sub(&a.c0, &b.c0, &a.c1, &b.c1);
+        let c1 = self.fp_inner_product(&[a.c0, a.c1], &[b.c1, b.c0]);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_square<P: TowerParameters>(&mut self, a: &Fp2Register<P>) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        self.fp2_mul(a, a)
+    }
+
+    pub fn fp2_mul_by_fp<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        b: &FieldRegister<P::BaseField>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_mul(&a.c0, b);
+        let c1 = self.fp_mul(&a.c1, b);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_mul_const<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        c: [u16; MAX_NB_LIMBS],
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp_mul_const(&a.c0, c);
+        let c1 = self.fp_mul_const(&a.c1, c);
+        Fp2Register::new(c0, c1)
+    }
+
+    /// Multiplies by the non-residue `xi = c + u`: `(c * a0 - a1) + (a0 + c * a1) * u`.
+    ///
+    /// The products by `c` are skipped when `c = 1`.
+    pub fn fp2_mul_by_nonresidue<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let (c_a0, c_a1) = if P::NONRESIDUE == 1 {
+            (a.c0, a.c1)
+        } else {
+            let mut c = [0u16; MAX_NB_LIMBS];
+            c[0] = P::NONRESIDUE;
+            (self.fp_mul_const(&a.c0, c), self.fp_mul_const(&a.c1, c))
+        };
+        let c0 = self.fp_sub(&c_a0, &a.c1);
+        let c1 = self.fp_add(&a.c0, &c_a1);
+        Fp2Register::new(c0, c1)
+    }
+
+    /// Computes `a^(-1) = (a0 - a1 * u) / (a0^2 + a1^2)`.
+    ///
+    /// The norm is inverted once, and `-a1 / norm` is computed as `0 * 0 - a1 * norm^(-1)`.
+    pub fn fp2_inverse<P: TowerParameters>(&mut self, a: &Fp2Register<P>) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let norm = self.fp_inner_product(&[a.c0, a.c1], &[a.c0, a.c1]);
+        let norm_inv = self.fp_inv(&norm);
+        let zero = self.fp_zero();
+        let c0 = self.fp_mul(&a.c0, &norm_inv);
+        let c1 = self.fp_mul_sub(&zero, &zero, &a.c1, &norm_inv);
+        Fp2Register::new(c0, c1)
+    }
+
+    pub fn fp2_div<P: TowerParameters>(
+        &mut self,
+        a: &Fp2Register<P>,
+        b: &Fp2Register<P>,
+    ) -> Fp2Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let b_inv = self.fp2_inverse(b);
+        self.fp2_mul(a, &b_inv)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::params::Bls12381BaseField;
+    use crate::chip::ec::bls12_381::tower::Bls12381Tower;
+    use crate::chip::ec::bn254::tower::Bn254Tower;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::trace::writer::TraceWriter;
+    use crate::chip::utils::field_limbs_to_biguint;
+    use crate::math::prelude::*;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    pub(crate) fn random_fp2<P: TowerParameters>() -> Fp2<P> {
+        let p = P::BaseField::modulus();
+        let mut rng = thread_rng();
+        Fp2::new(rng.gen_biguint_below(&p), rng.gen_biguint_below(&p))
+    }
+
+    pub(crate) fn write_fp2<F: PrimeField64, P: TowerParameters>(
+        writer: &TraceWriter<F>,
+        data: &Fp2Register<P>,
+        value: &Fp2<P>,
+        row_index: usize,
+    ) {
+        for (register, coefficient) in [(data.c0, &value.c0), (data.c1, &value.c1)] {
+            writer.write(
+                &register,
+                &to_u16_le_limbs_polynomial::<F, P::BaseField>(coefficient),
+                row_index,
+            );
+        }
+    }
+
+    pub(crate) fn read_fp2<F: PrimeField64, P: TowerParameters>(
+        writer: &TraceWriter<F>,
+        data: &Fp2Register<P>,
+        row_index: usize,
+    ) -> Fp2<P> {
+        let c0 = writer.read(&data.c0, row_index);
+        let c1 = writer.read(&data.c1, row_index);
+        Fp2::new(
+            field_limbs_to_biguint(c0.coefficients()),
+            field_limbs_to_biguint(c1.coefficients()),
+        )
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Fp2MulTest;
+
+    impl AirParameters for Fp2MulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 376;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 573;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    pub struct Fp2InverseTest;
+
+    impl AirParameters for Fp2InverseTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 608;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 921;
+        type Instruction = FpInstruction<Bls12381BaseField>;
+    }
+
+    fn check_fp2_arithmetic<P: TowerParameters>() {
+        let a = random_fp2::<P>();
+        let b = random_fp2::<P>();
+
+        assert_eq!(&(&a * &b) * &b.inverse(), a);
+        assert_eq!(&(&a + &b) - &b, a);
+        assert_eq!(&a + &(-&a), Fp2::zero());
+
+        // u^2 = -1 and (c + u) * (c - u) = c^2 + 1.
+        let u = Fp2::<P>::new(BigUint::zero(), BigUint::one());
+        assert_eq!(u.square(), -&Fp2::one());
+        let xi = Fp2::<P>::one().mul_by_nonresidue();
+        let c = BigUint::from(P::NONRESIDUE);
+        assert_eq!(
+            &xi * &xi.conjugate(),
+            Fp2::new(&c * &c + 1u32, BigUint::zero())
+        );
+
+        // The Frobenius map on Fp2 is the conjugation.
+        let p = P::BaseField::modulus();
+        assert_eq!(a.pow(&p), a.conjugate());
+    }
+
+    #[test]
+    fn test_fp2_arithmetic() {
+        check_fp2_arithmetic::<Bls12381Tower>();
+        check_fp2_arithmetic::<Bn254Tower>();
+    }
+
+    fn check_fp2_sqrt<P: TowerParameters>() {
+        let a = random_fp2::<P>();
+        let a_sq = a.square();
+        assert!(a_sq.is_square());
+        let root = a_sq.sqrt().unwrap();
+        assert!(root == a || root == -&a);
+
+        // The non-residue xi is not a square, so neither is its product with a square.
+        let xi = Fp2::<P>::one().mul_by_nonresidue();
+        assert!(!xi.is_square());
+        assert!((&a_sq * &xi).sqrt().is_none());
+
+        assert!(!Fp2::<P>::new(BigUint::from(2u32), BigUint::zero()).sgn0());
+        assert!(Fp2::<P>::new(BigUint::zero(), BigUint::one()).sgn0());
+    }
+
+    #[test]
+    fn test_fp2_sqrt() {
+        check_fp2_sqrt::<Bls12381Tower>();
+        check_fp2_sqrt::<Bn254Tower>();
+    }
+
+    #[test]
+    fn test_fp2_mul() {
+        type L = Fp2MulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_fp2::<Bls12381Tower>();
+        let b = builder.alloc_fp2::<Bls12381Tower>();
+        let c = builder.fp2_mul(&a, &b);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let a_int = random_fp2::<Bls12381Tower>();
+        let b_int = random_fp2::<Bls12381Tower>();
+        let c_int = &a_int * &b_int;
+        let writer = generator.new_writer();
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            write_fp2(&writer, &a, &a_int, i);
+            write_fp2(&writer, &b, &b_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(read_fp2(&writer, &c, i), c_int);
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[test]
+    fn test_fp2_inverse() {
+        type L = Fp2InverseTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a = builder.alloc_fp2::<Bls12381Tower>();
+        let a_inv = builder.fp2_inverse(&a);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        writer.write_global_instructions(&generator.air_data);
+        (0..num_rows).into_par_iter().for_each(|i| {
+            let a_int = random_fp2::<Bls12381Tower>();
+            write_fp2(&writer, &a, &a_int, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            assert_eq!(read_fp2(&writer, &a_inv, i), a_int.inverse());
+        });
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,307 @@
+use core::ops::{Add, Mul, Neg, Sub};
+
+use serde::{Deserialize, Serialize};
+
+use super::fp2::{Fp2, Fp2Register};
+use super::TowerParameters;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::AirParameters;
+
+/// An element `c0 + c1 * v + c2 * v^2` of the cubic extension `Fp6 = Fp2[v] / (v^3 - xi)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fp6<P: TowerParameters> {
+    pub c0: Fp2<P>,
+    pub c1: Fp2<P>,
+    pub c2: Fp2<P>,
+}
+
+impl<P: TowerParameters> Fp6<P> {
+    pub fn new(c0: Fp2<P>, c1: Fp2<P>, c2: Fp2<P>) -> Self {
+        Self { c0, c1, c2 }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(Fp2::zero(), Fp2::zero(), Fp2::zero())
+    }
+
+    pub fn one() -> Self {
+        Self::new(Fp2::one(), Fp2::zero(), Fp2::zero())
+    }
+
+    /// Multiplies by `v`, the non-residue used to build `Fp12` on top of `Fp6`.
+    pub fn mul_by_nonresidue(&self) -> Self {
+        Self::new(
+            self.c2.mul_by_nonresidue(),
+            self.c0.clone(),
+            self.c1.clone(),
+        )
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    pub fn inverse(&self) -> Self {
+        let t0 = &self.c0.square() - &(&self.c1 * &self.c2).mul_by_nonresidue();
+        let t1 = &self.c2.square().mul_by_nonresidue() - &(&self.c0 * &self.c1);
+        let t2 = &self.c1.square() - &(&self.c0 * &self.c2);
+        let norm = &(&self.c0 * &t0) + &(&(&self.c2 * &t1) + &(&self.c1 * &t2)).mul_by_nonresidue();
+        let norm_inv = norm.inverse();
+        Self::new(&t0 * &norm_inv, &t1 * &norm_inv, &t2 * &norm_inv)
+    }
+}
+
+impl<P: TowerParameters> Add<&Fp6<P>> for &Fp6<P> {
+    type Output = Fp6<P>;
+
+    fn add(self, other: &Fp6<P>) -> Fp6<P> {
+        Fp6::new(
+            &self.c0 + &other.c0,
+            &self.c1 + &other.c1,
+            &self.c2 + &other.c2,
+        )
+    }
+}
+
+impl<P: TowerParameters> Sub<&Fp6<P>> for &Fp6<P> {
+    type Output = Fp6<P>;
+
+    fn sub(self, other: &Fp6<P>) -> Fp6<P> {
+        Fp6::new(
+            &self.c0 - &other.c0,
+            &self.c1 - &other.c1,
+            &self.c2 - &other.c2,
+        )
+    }
+}
+
+impl<P: TowerParameters> Mul<&Fp6<P>> for &Fp6<P> {
+    type Output = Fp6<P>;
+
+    fn mul(self, other: &Fp6<P>) -> Fp6<P> {
+        let t0 = &self.c0 * &other.c0;
+        let t1 = &self.c1 * &other.c1;
+        let t2 = &self.c2 * &other.c2;
+        let c0 = &(&(&self.c1 * &other.c2) + &(&self.c2 * &other.c1)).mul_by_nonresidue() + &t0;
+        let c1 = &(&(&self.c0 * &other.c1) + &(&self.c1 * &other.c0)) + &t2.mul_by_nonresidue();
+        let c2 = &(&(&self.c0 * &other.c2) + &(&self.c2 * &other.c0)) + &t1;
+        Fp6::new(c0, c1, c2)
+    }
+}
+
+impl<P: TowerParameters> Neg for &Fp6<P> {
+    type Output = Fp6<P>;
+
+    fn neg(self) -> Fp6<P> {
+        Fp6::new(-&self.c0, -&self.c1, -&self.c2)
+    }
+}
+
+/// A register for an element `c0 + c1 * v + c2 * v^2` of `Fp6`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fp6Register<P: TowerParameters> {
+    pub c0: Fp2Register<P>,
+    pub c1: Fp2Register<P>,
+    pub c2: Fp2Register<P>,
+}
+
+impl<P: TowerParameters> Fp6Register<P> {
+    pub fn new(c0: Fp2Register<P>, c1: Fp2Register<P>, c2: Fp2Register<P>) -> Self {
+        Self { c0, c1, c2 }
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn alloc_fp6<P: TowerParameters>(&mut self) -> Fp6Register<P> {
+        Fp6Register::new(self.alloc_fp2(), self.alloc_fp2(), self.alloc_fp2())
+    }
+
+    pub fn alloc_public_fp6<P: TowerParameters>(&mut self) -> Fp6Register<P> {
+        Fp6Register::new(
+            self.alloc_public_fp2(),
+            self.alloc_public_fp2(),
+            self.alloc_public_fp2(),
+        )
+    }
+
+    pub fn fp6_constant<P: TowerParameters>(&mut self, value: &Fp6<P>) -> Fp6Register<P> {
+        Fp6Register::new(
+            self.fp2_constant(&value.c0),
+            self.fp2_constant(&value.c1),
+            self.fp2_constant(&value.c2),
+        )
+    }
+
+    pub fn fp6_add<P: TowerParameters>(
+        &mut self,
+        a: &Fp6Register<P>,
+        b: &Fp6Register<P>,
+    ) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp2_add(&a.c0, &b.c0);
+        let c1 = self.fp2_add(&a.c1, &b.c1);
+        let c2 = self.fp2_add(&a.c2, &b.c2);
+        Fp6Register::new(c0, c1, c2)
+    }
+
+    pub fn fp6_sub<P: TowerParameters>(
+        &mut self,
+        a: &Fp6Register<P>,
+        b: &Fp6Register<P>,
+    ) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp2_sub(&a.c0, &b.c0);
+        let c1 = self.fp2_sub(&a.c1, &b.c1);
+        let c2 = self.fp2_sub(&a.c2, &b.c2);
+        Fp6Register::new(c0, c1, c2)
+    }
+
+    pub fn fp6_neg<P: TowerParameters>(&mut self, a: &Fp6Register<P>) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp2_neg(&a.c0);
+        let c1 = self.fp2_neg(&a.c1);
+        let c2 = self.fp2_neg(&a.c2);
+        Fp6Register::new(c0, c1, c2)
+    }
+
+    /// Multiplies by `v`: `xi * a2 + a0 * v + a1 * v^2`.
+    pub fn fp6_mul_by_nonresidue<P: TowerParameters>(
+        &mut self,
+        a: &Fp6Register<P>,
+    ) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let c0 = self.fp2_mul_by_nonresidue(&a.c2);
+        Fp6Register::new(c0, a.c0, a.c1)
+    }
+
+    /// Computes `a * b` with the Karatsuba formulas, using six multiplications in `Fp2`.
+    pub fn fp6_mul<P: TowerParameters>(
+        &mut self,
+        a: &Fp6Register<P>,
+        b: &Fp6Register<P>,
+    ) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        let t0 = self.fp2_mul(&a.c0, &b.c0);
+        let t1 = self.fp2_mul(&a.c1, &b.c1);
+        let t2 = self.fp2_mul(&a.c2, &b.c2);
+
+        // c0 = t0 + xi * ((a1 + a2) * (b1 + b2) - t1 - t2)
+        let a1_plus_a2 = self.fp2_add(&a.c1, &a.c2);
+        let b1_plus_b2 = self.fp2_add(&b.c1, &b.c2);
+        let cross = self.fp2_mul(&a1_plus_a2, &b1_plus_b2);
+        let cross = self.fp2_sub(&cross, &t1);
+        let cross = self.fp2_sub(&cross, &t2);
+        let cross = self.fp2_mul_by_nonresidue(&cross);
+        let c0 = self.fp2_add(&t0, &cross);
+
+        // c1 = (a0 + a1) * (b0 + b1) - t0 - t1 + xi * t2
+        let a0_plus_a1 = self.fp2_add(&a.c0, &a.c1);
+        let b0_plus_b1 = self.fp2_add(&b.c0, &b.c1);
+        let cross = self.fp2_mul(&a0_plus_a1, &b0_plus_b1);
+        let cross = self.fp2_sub(&cross, &t0);
+        let cross = self.fp2_sub(&cross, &t1);
+        let t2_mul_nonresidue = self.fp2_mul_by_nonresidue(&t2);
+        let c1 = self.fp2_add(&cross, &t2_mul_nonresidue);
+
+        // c2 = (a0 + a2) * (b0 + b2) - t0 - t2 + t1
+        let a0_plus_a2 = self.fp2_add(&a.c0, &a.c2);
+        let b0_plus_b2 = self.fp2_add(&b.c0, &b.c2);
+        let cross = self.fp2_mul(&a0_plus_a2, &b0_plus_b2);
+        let cross = self.fp2_sub(&cross, &t0);
+        let cross = self.fp2_sub(&cross, &t2);
+        let c2 = self.fp2_add(&cross, &t1);
+
+        Fp6Register::new(c0, c1, c2)
+    }
+
+    pub fn fp6_square<P: TowerParameters>(&mut self, a: &Fp6Register<P>) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        self.fp6_mul(a, a)
+    }
+
+    /// Computes `a^(-1)` from the adjugate of the multiplication matrix of `a`, which reduces
+    /// the inversion to a single inversion in `Fp2`.
+    pub fn fp6_inverse<P: TowerParameters>(&mut self, a: &Fp6Register<P>) -> Fp6Register<P>
+    where
+        L::Instruction: FromFieldInstruction<P::BaseField>,
+    {
+        // t0 = a0^2 - xi * a1 * a2
+        let a0_sq = self.fp2_square(&a.c0);
+        let a1_a2 = self.fp2_mul(&a.c1, &a.c2);
+        let a1_a2 = self.fp2_mul_by_nonresidue(&a1_a2);
+        let t0 = self.fp2_sub(&a0_sq, &a1_a2);
+
+        // t1 = xi * a2^2 - a0 * a1
+        let a2_sq = self.fp2_square(&a.c2);
+        let a2_sq = self.fp2_mul_by_nonresidue(&a2_sq);
+        let a0_a1 = self.fp2_mul(&a.c0, &a.c1);
+        let t1 = self.fp2_sub(&a2_sq, &a0_a1);
+
+        // t2 = a1^2 - a0 * a2
+        let a1_sq = self.fp2_square(&a.c1);
+        let a0_a2 = self.fp2_mul(&a.c0, &a.c2);
+        let t2 = self.fp2_sub(&a1_sq, &a0_a2);
+
+        // norm = a0 * t0 + xi * (a2 * t1 + a1 * t2)
+        let a2_t1 = self.fp2_mul(&a.c2, &t1);
+        let a1_t2 = self.fp2_mul(&a.c1, &t2);
+        let sum = self.fp2_add(&a2_t1, &a1_t2);
+        let sum = self.fp2_mul_by_nonresidue(&sum);
+        let a0_t0 = self.fp2_mul(&a.c0, &t0);
+        let norm = self.fp2_add(&a0_t0, &sum);
+        let norm_inv = self.fp2_inverse(&norm);
+
+        let c0 = self.fp2_mul(&t0, &norm_inv);
+        let c1 = self.fp2_mul(&t1, &norm_inv);
+        let c2 = self.fp2_mul(&t2, &norm_inv);
+        Fp6Register::new(c0, c1, c2)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::chip::ec::bls12_381::tower::Bls12381Tower;
+    use crate::chip::ec::bn254::tower::Bn254Tower;
+    use crate::chip::field::extension::fp2::tests::random_fp2;
+
+    pub(crate) fn random_fp6<P: TowerParameters>() -> Fp6<P> {
+        Fp6::new(random_fp2(), random_fp2(), random_fp2())
+    }
+
+    fn check_fp6_arithmetic<P: TowerParameters>() {
+        let a = random_fp6::<P>();
+        let b = random_fp6::<P>();
+
+        assert_eq!(&(&a * &b) * &b.inverse(), a);
+        assert_eq!(&(&a + &b) - &b, a);
+        assert_eq!(&a + &(-&a), Fp6::zero());
+        assert_eq!(&(&a * &b) + &(&a * &a), &a * &(&a + &b));
+
+        // v^3 = xi.
+        let v = Fp6::<P>::new(Fp2::zero(), Fp2::one(), Fp2::zero());
+        let xi = Fp6::new(Fp2::one().mul_by_nonresidue(), Fp2::zero(), Fp2::zero());
+        assert_eq!(&v.square() * &v, xi);
+        assert_eq!(a.mul_by_nonresidue(), &a * &v);
+    }
+
+    #[test]
+    fn test_fp6_arithmetic() {
+        check_fp6_arithmetic::<Bls12381Tower>();
+        check_fp6_arithmetic::<Bn254Tower>();
+    }
+}
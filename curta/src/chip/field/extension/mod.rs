@@ -0,0 +1,28 @@
+//! Towers of extension fields over an emulated prime field `Fp`, used as the target fields of
+//! pairings.
+//!
+//! The tower is `Fp2 = Fp[u] / (u^2 + 1)`, `Fp6 = Fp2[v] / (v^3 - xi)` and
+//! `Fp12 = Fp6[w] / (w^2 - v)`, for a non-residue `xi = c + u` of `Fp2`. The operations are
+//! composed of base field instructions, so they act on trace and public registers alike.
+
+use core::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::parameters::FieldParameters;
+
+pub mod fp12;
+pub mod fp2;
+pub mod fp6;
+
+pub trait TowerParameters:
+    Send + Sync + Copy + 'static + Debug + PartialEq + Eq + Serialize + DeserializeOwned
+{
+    /// The base field, whose modulus must be `3 mod 4` for `u^2 + 1` to be irreducible.
+    type BaseField: FieldParameters;
+
+    /// The constant `c` of the non-residue `xi = c + u`, which is neither a square nor a cube in
+    /// `Fp2`.
+    const NONRESIDUE: u16;
+}
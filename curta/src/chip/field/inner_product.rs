@@ -24,12 +24,12 @@ use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct FpInnerProductInstruction<P: FieldParameters> {
-    a: Vec<FieldRegister<P>>,
-    b: Vec<FieldRegister<P>>,
+    pub(crate) a: Vec<FieldRegister<P>>,
+    pub(crate) b: Vec<FieldRegister<P>>,
     pub result: FieldRegister<P>,
-    carry: FieldRegister<P>,
-    witness_low: ArrayRegister<U16Register>,
-    witness_high: ArrayRegister<U16Register>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
 }
 
 impl<L: AirParameters> AirBuilder<L> {
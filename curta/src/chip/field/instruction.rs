@@ -1,13 +1,24 @@
 use serde::{Deserialize, Serialize};
 
 use super::add::FpAddInstruction;
+use super::add32::{FpAdd32Instruction, FpSub32Instruction};
+use super::bytes::FpBytesInstruction;
+use super::compare::FpCompareInstruction;
+use super::crt::FpCrtMulInstruction;
 use super::den::FpDenInstruction;
 use super::div::FpDivInstruction;
 use super::inner_product::FpInnerProductInstruction;
+use super::inv::FpInvInstruction;
+use super::is_square::FpIsSquareInstruction;
+use super::montgomery::FpMontMulInstruction;
 use super::mul::FpMulInstruction;
 use super::mul_const::FpMulConstInstruction;
+use super::mul_sub::FpMulSubInstruction;
+use super::neg::FpNegInstruction;
 use super::parameters::FieldParameters;
+use super::sqrt::FpSqrtInstruction;
 use super::sub::FpSubInstruction;
+use super::wide_mul::FpWideMulInstruction;
 use crate::air::AirConstraint;
 use crate::chip::instruction::Instruction;
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
@@ -24,6 +35,18 @@ pub enum FpInstruction<P: FieldParameters> {
     Den(FpDenInstruction<P>),
     Sub(FpSubInstruction<P>),
     Div(FpDivInstruction<P>),
+    Inv(FpInvInstruction<P>),
+    Sqrt(FpSqrtInstruction<P>),
+    IsSquare(FpIsSquareInstruction<P>),
+    Neg(FpNegInstruction<P>),
+    MulSub(FpMulSubInstruction<P>),
+    MontMul(FpMontMulInstruction<P>),
+    Compare(FpCompareInstruction<P>),
+    WideMul(FpWideMulInstruction<P>),
+    CrtMul(FpCrtMulInstruction<P>),
+    Bytes(FpBytesInstruction<P>),
+    Add32(FpAdd32Instruction<P>),
+    Sub32(FpSub32Instruction<P>),
 }
 
 pub trait FromFieldInstruction<P: FieldParameters>:
@@ -34,6 +57,9 @@ pub trait FromFieldInstruction<P: FieldParameters>:
     + From<FpMulConstInstruction<P>>
     + From<FpInnerProductInstruction<P>>
     + From<FpDenInstruction<P>>
+    + From<FpNegInstruction<P>>
+    + From<FpMulSubInstruction<P>>
+    + From<FpInvInstruction<P>>
 {
 }
 
@@ -49,6 +75,18 @@ impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpInstructi
             FpInstruction::Den(instruction) => AirConstraint::<AP>::eval(instruction, parser),
             FpInstruction::Sub(instruction) => AirConstraint::<AP>::eval(instruction, parser),
             FpInstruction::Div(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Inv(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Sqrt(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::IsSquare(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Neg(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::MulSub(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::MontMul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Compare(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::WideMul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::CrtMul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Bytes(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Add32(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            FpInstruction::Sub32(instruction) => AirConstraint::<AP>::eval(instruction, parser),
         }
     }
 }
@@ -77,6 +115,42 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInstruction<P> {
             FpInstruction::Div(instruction) => {
                 Instruction::<F>::write(instruction, writer, row_index)
             }
+            FpInstruction::Inv(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Sqrt(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::IsSquare(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Neg(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::MulSub(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::MontMul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Compare(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::WideMul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::CrtMul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Bytes(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Add32(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            FpInstruction::Sub32(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
         }
     }
 
@@ -93,6 +167,36 @@ impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInstruction<P> {
             FpInstruction::Den(instruction) => Instruction::<F>::write_to_air(instruction, writer),
             FpInstruction::Sub(instruction) => Instruction::<F>::write_to_air(instruction, writer),
             FpInstruction::Div(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            FpInstruction::Inv(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            FpInstruction::Sqrt(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            FpInstruction::IsSquare(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::Neg(instruction) => Instruction::<F>::write_to_air(instruction, writer),
+            FpInstruction::MulSub(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::MontMul(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::Compare(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::WideMul(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::CrtMul(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::Bytes(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::Add32(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            FpInstruction::Sub32(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
         }
     }
 }
@@ -138,3 +242,75 @@ impl<P: FieldParameters> From<FpDivInstruction<P>> for FpInstruction<P> {
         FpInstruction::Div(instr)
     }
 }
+
+impl<P: FieldParameters> From<FpInvInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpInvInstruction<P>) -> Self {
+        FpInstruction::Inv(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpSqrtInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpSqrtInstruction<P>) -> Self {
+        FpInstruction::Sqrt(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpIsSquareInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpIsSquareInstruction<P>) -> Self {
+        FpInstruction::IsSquare(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpNegInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpNegInstruction<P>) -> Self {
+        FpInstruction::Neg(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpMulSubInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpMulSubInstruction<P>) -> Self {
+        FpInstruction::MulSub(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpMontMulInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpMontMulInstruction<P>) -> Self {
+        FpInstruction::MontMul(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpCompareInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpCompareInstruction<P>) -> Self {
+        FpInstruction::Compare(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpWideMulInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpWideMulInstruction<P>) -> Self {
+        FpInstruction::WideMul(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpCrtMulInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpCrtMulInstruction<P>) -> Self {
+        FpInstruction::CrtMul(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpBytesInstruction<P>> for FpInstruction<P> {
+    fn from(instr: FpBytesInstruction<P>) -> Self {
+        FpInstruction::Bytes(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpAdd32Instruction<P>> for FpInstruction<P> {
+    fn from(instr: FpAdd32Instruction<P>) -> Self {
+        FpInstruction::Add32(instr)
+    }
+}
+
+impl<P: FieldParameters> From<FpSub32Instruction<P>> for FpInstruction<P> {
+    fn from(instr: FpSub32Instruction<P>) -> Self {
+        FpInstruction::Sub32(instr)
+    }
+}
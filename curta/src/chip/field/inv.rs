@@ -0,0 +1,302 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::mul::FpMulInstruction;
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::RegisterSerializable;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp Inversion. Computes `a^(-1) = result`.
+///
+/// The inverse is witnessed and constrained by `a * result = 1`, which takes a single
+/// multiplication instead of the two of `FpDivInstruction`. There is no valid trace for `a = 0`,
+/// so the inversion also asserts that `a` is nonzero modulo the field order.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpInvInstruction<P: FieldParameters> {
+    /// a `FpMulInstruction` to check `a * result = 1`.
+    multiplication: FpMulInstruction<P>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a nonzero field element `a`, computes its inverse `a^(-1)`.
+    pub fn fp_inv<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpInvInstruction<P>>,
+    {
+        let result = if a.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_fp_inv(a, &result);
+        result
+    }
+
+    pub fn set_fp_inv<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpInvInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || result.is_trace();
+        let one = self.fp_one();
+
+        let carry: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            carry = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+
+        // check that a * result = one.
+        let multiplication = FpMulInstruction {
+            a: *a,
+            b: *result,
+            result: one,
+            carry,
+            witness_low,
+            witness_high,
+        };
+
+        let instr = FpInvInstruction { multiplication };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    /// Given nonzero field elements `a_0, ..., a_(n-1)`, computes their inverses with a single
+    /// inversion by Montgomery's trick.
+    ///
+    /// The prefix products `c_i = a_0 * ... * a_i` are inverted through `c_(n-1)^(-1)`, from
+    /// which `a_i^(-1) = c_i^(-1) * c_(i-1)` and `c_(i-1)^(-1) = c_i^(-1) * a_i`. This takes
+    /// `3 * (n - 1)` multiplications instead of `n - 1` more inversions. As for `fp_inv`, there is
+    /// no valid trace if any of the elements is zero.
+    pub fn fp_batch_inv<P: FieldParameters>(
+        &mut self,
+        elements: &[FieldRegister<P>],
+    ) -> Vec<FieldRegister<P>>
+    where
+        L::Instruction: From<FpInvInstruction<P>> + From<FpMulInstruction<P>>,
+    {
+        let Some((first, rest)) = elements.split_first() else {
+            return Vec::new();
+        };
+
+        let mut prefix_products = vec![*first];
+        for element in rest {
+            let product = self.fp_mul(prefix_products.last().unwrap(), element);
+            prefix_products.push(product);
+        }
+
+        let mut inverses = vec![*first; elements.len()];
+        let mut prefix_inverse = self.fp_inv(prefix_products.last().unwrap());
+        for i in (1..elements.len()).rev() {
+            inverses[i] = self.fp_mul(&prefix_inverse, &prefix_products[i - 1]);
+            prefix_inverse = self.fp_mul(&prefix_inverse, &elements[i]);
+        }
+        inverses[0] = prefix_inverse;
+        inverses
+    }
+}
+
+impl<P: FieldParameters> FpInvInstruction<P> {
+    /// The inverse of the value `p_a` of `a`, which is zero if there is no inverse.
+    fn inverse<F: PrimeField64>(p_a: &Polynomial<F>) -> Polynomial<F> {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+
+        let modulus = P::modulus();
+        let a_inv_int = if (&a % &modulus).is_zero() {
+            BigUint::zero()
+        } else {
+            a.modpow(&(&modulus - BigUint::from(2u64)), &modulus)
+        };
+        to_u16_le_limbs_polynomial::<F, P>(&a_inv_int)
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpInvInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        self.multiplication.eval(parser);
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpInvInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.multiplication.a, row_index);
+        writer.write(&self.multiplication.b, &Self::inverse(&p_a), row_index);
+        self.multiplication.write(writer, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.multiplication.a);
+        writer.write(&self.multiplication.b, &Self::inverse(&p_a));
+        self.multiplication.write_to_air(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpInvTest;
+
+    impl AirParameters for FpInvTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 124;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 195;
+
+        type Instruction = FpInvInstruction<Fp25519>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpBatchInvTest;
+
+    impl AirParameters for FpBatchInvTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1192;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 1797;
+
+        type Instruction = FpInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fpinv() {
+        type F = GoldilocksField;
+        type L = FpInvTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<FieldRegister<P>>();
+        let _ = builder.fp_inv(&a_pub);
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let a_inv = builder.fp_inv(&a);
+        let a_inv_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&a_inv, &a_inv_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        let a_pub_int = rng.gen_biguint_range(&BigUint::from(1u32), &p);
+        writer.write(&a_pub, &to_u16_le_limbs_polynomial::<F, P>(&a_pub_int), 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            let a_int = rng.gen_biguint_range(&BigUint::from(1u32), &p);
+            let a_inv_int = a_int.modpow(&(&p - BigUint::from(2u32)), &p);
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(
+                &a_inv_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&a_inv_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[test]
+    fn test_fp_batch_inv() {
+        type F = GoldilocksField;
+        type L = FpBatchInvTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        const BATCH_SIZE: usize = 4;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let elements = (0..BATCH_SIZE)
+            .map(|_| builder.alloc::<FieldRegister<P>>())
+            .collect::<Vec<_>>();
+        let inverses = builder.fp_batch_inv(&elements);
+        let expected = (0..BATCH_SIZE)
+            .map(|_| builder.alloc::<FieldRegister<P>>())
+            .collect::<Vec<_>>();
+        for (inverse, expected) in inverses.iter().zip(expected.iter()) {
+            builder.assert_equal(inverse, expected);
+        }
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            for (element, expected) in elements.iter().zip(expected.iter()) {
+                let a_int = rng.gen_biguint_range(&BigUint::from(1u32), &p);
+                let a_inv_int = a_int.modpow(&(&p - BigUint::from(2u32)), &p);
+                writer.write(element, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+                writer.write(expected, &to_u16_le_limbs_polynomial::<F, P>(&a_inv_int), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,412 @@
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::sqrt::sqrt;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{digits_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp Quadratic Residuosity. Computes the bit `is_square` of whether `a` is a square.
+///
+/// For the smallest non-residue `n` of the field, exactly one of `a` and `n * a` is a square when
+/// `a` is nonzero. This is done by witnessing a square root `root` of `a` if `is_square` is set,
+/// and of `n * a` otherwise, and by constraining
+///
+/// `root^2 - a - (1 - is_square) * ((n - 1) * a - n * p) - root_carry * p = 0`.
+///
+/// The multiple `n * p` keeps the carry nonnegative when `a` is reduced, which the instruction
+/// assumes. As zero is both a square and `n` times a square, the relation
+/// `a * inverse - (1 - is_square) - inverse_carry * p = 0` forces `is_square` to be set and
+/// proves that `a` is nonzero otherwise.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpIsSquareInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub is_square: BitRegister,
+    pub(crate) non_residue: u16,
+    pub(crate) root: FieldRegister<P>,
+    pub(crate) root_carry: FieldRegister<P>,
+    pub(crate) root_witness_low: ArrayRegister<U16Register>,
+    pub(crate) root_witness_high: ArrayRegister<U16Register>,
+    pub(crate) inverse: FieldRegister<P>,
+    pub(crate) inverse_carry: FieldRegister<P>,
+    pub(crate) inverse_witness_low: ArrayRegister<U16Register>,
+    pub(crate) inverse_witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a reduced field element `a`, computes the bit of whether `a` is a square, such as
+    /// the candidate `x^2` of a compressed point.
+    pub fn fp_is_square<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> BitRegister
+    where
+        L::Instruction: From<FpIsSquareInstruction<P>>,
+    {
+        let is_square = if a.is_trace() {
+            self.alloc::<BitRegister>()
+        } else {
+            self.alloc_public::<BitRegister>()
+        };
+        self.set_fp_is_square(a, &is_square);
+        is_square
+    }
+
+    pub fn set_fp_is_square<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        is_square: &BitRegister,
+    ) where
+        L::Instruction: From<FpIsSquareInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || is_square.is_trace();
+
+        let root: FieldRegister<P>;
+        let root_carry: FieldRegister<P>;
+        let root_witness_low: ArrayRegister<U16Register>;
+        let root_witness_high: ArrayRegister<U16Register>;
+        let inverse: FieldRegister<P>;
+        let inverse_carry: FieldRegister<P>;
+        let inverse_witness_low: ArrayRegister<U16Register>;
+        let inverse_witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            root = self.alloc::<FieldRegister<P>>();
+            root_carry = self.alloc::<FieldRegister<P>>();
+            root_witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            root_witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            inverse = self.alloc::<FieldRegister<P>>();
+            inverse_carry = self.alloc::<FieldRegister<P>>();
+            inverse_witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            inverse_witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            root = self.alloc_public::<FieldRegister<P>>();
+            root_carry = self.alloc_public::<FieldRegister<P>>();
+            root_witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            root_witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            inverse = self.alloc_public::<FieldRegister<P>>();
+            inverse_carry = self.alloc_public::<FieldRegister<P>>();
+            inverse_witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            inverse_witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+
+        let instr = FpIsSquareInstruction {
+            a: *a,
+            is_square: *is_square,
+            non_residue: non_residue::<P>(),
+            root,
+            root_carry,
+            root_witness_low,
+            root_witness_high,
+            inverse,
+            inverse_carry,
+            inverse_witness_low,
+            inverse_witness_high,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpIsSquareInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_root = self.root.eval(parser);
+        let p_root_carry = self.root_carry.eval(parser);
+        let p_inverse = self.inverse.eval(parser);
+        let p_inverse_carry = self.inverse_carry.eval(parser);
+        let is_square = self.is_square.eval(parser);
+        let one = parser.one();
+        let is_not_square = parser.sub(one, is_square);
+
+        let n = AP::Field::from_canonical_u16(self.non_residue);
+        let p_modulus_limbs = Polynomial::from_iter(util::modulus_field_iter::<AP::Field, P>());
+        let p_modulus = parser.constant_poly(&p_modulus_limbs);
+        let p_n_mul_modulus = parser.constant_poly(&(&p_modulus_limbs * n));
+
+        // Compute the vanishing polynomial
+        // root(x)^2 - a(x) - (1 - is_square) * ((n - 1) * a(x) - n * p(x)) - root_carry(x) * p(x).
+        let p_root_squared = parser.poly_mul(&p_root, &p_root);
+        let p_root_squared_minus_a = parser.poly_sub(&p_root_squared, &p_a);
+        let p_a_mul_n_minus_one = parser.poly_mul_const(&p_a, &(n - AP::Field::ONE));
+        let p_shift = parser.poly_sub(&p_a_mul_n_minus_one, &p_n_mul_modulus);
+        let p_shift_if_not_square = parser.poly_scalar_mul(&p_shift, &is_not_square);
+        let p_root_squared_minus_target =
+            parser.poly_sub(&p_root_squared_minus_a, &p_shift_if_not_square);
+        let p_root_carry_mul_modulus = parser.poly_mul(&p_root_carry, &p_modulus);
+        let p_root_vanishing =
+            parser.poly_sub(&p_root_squared_minus_target, &p_root_carry_mul_modulus);
+
+        let p_root_witness_low =
+            Polynomial::from_coefficients(self.root_witness_low.eval_vec(parser));
+        let p_root_witness_high =
+            Polynomial::from_coefficients(self.root_witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(
+            parser,
+            &p_root_vanishing,
+            &p_root_witness_low,
+            &p_root_witness_high,
+        );
+
+        // Compute the vanishing polynomial
+        // a(x) * inverse(x) - (1 - is_square) - inverse_carry(x) * p(x).
+        let p_a_mul_inverse = parser.poly_mul(&p_a, &p_inverse);
+        let p_is_not_square = Polynomial::from_coefficients(vec![is_not_square]);
+        let p_a_mul_inverse_minus_bit = parser.poly_sub(&p_a_mul_inverse, &p_is_not_square);
+        let p_inverse_carry_mul_modulus = parser.poly_mul(&p_inverse_carry, &p_modulus);
+        let p_inverse_vanishing =
+            parser.poly_sub(&p_a_mul_inverse_minus_bit, &p_inverse_carry_mul_modulus);
+
+        let p_inverse_witness_low =
+            Polynomial::from_coefficients(self.inverse_witness_low.eval_vec(parser));
+        let p_inverse_witness_high =
+            Polynomial::from_coefficients(self.inverse_witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(
+            parser,
+            &p_inverse_vanishing,
+            &p_inverse_witness_low,
+            &p_inverse_witness_high,
+        );
+    }
+}
+
+/// The values of the registers written by a `FpIsSquareInstruction`.
+struct IsSquareWitness<F> {
+    is_square: F,
+    root: Polynomial<F>,
+    root_carry: Polynomial<F>,
+    root_witness_low: Vec<F>,
+    root_witness_high: Vec<F>,
+    inverse: Polynomial<F>,
+    inverse_carry: Polynomial<F>,
+    inverse_witness_low: Vec<F>,
+    inverse_witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> FpIsSquareInstruction<P> {
+    fn witness<F: PrimeField64>(p_a: &Polynomial<F>, non_residue: u16) -> IsSquareWitness<F> {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+        let modulus = P::modulus();
+        assert!(a < modulus, "the element must be reduced");
+        let n = BigUint::from(non_residue);
+
+        // Compute the root, the inverse and the carries in the integers.
+        let (is_square, root, inverse) = match sqrt::<P>(&a) {
+            Some(root) => (true, root, BigUint::zero()),
+            None => {
+                let root = sqrt::<P>(&(&n * &a)).expect("n * a is a square");
+                let inverse = a.modpow(&(&modulus - 2u32), &modulus);
+                (false, root, inverse)
+            }
+        };
+        let (root_carry, inverse_carry) = if is_square {
+            ((&root * &root - &a) / &modulus, BigUint::zero())
+        } else {
+            (
+                (&root * &root + &n * (&modulus - &a)) / &modulus,
+                (&a * &inverse - BigUint::one()) / &modulus,
+            )
+        };
+        let is_not_square = F::from_bool(!is_square);
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_root = to_u16_le_limbs_polynomial::<F, P>(&root);
+        let p_root_carry = to_u16_le_limbs_polynomial::<F, P>(&root_carry);
+        let p_inverse = to_u16_le_limbs_polynomial::<F, P>(&inverse);
+        let p_inverse_carry = to_u16_le_limbs_polynomial::<F, P>(&inverse_carry);
+
+        // Compute the vanishing polynomials.
+        let n = F::from_canonical_u16(non_residue);
+        let p_shift = p_a * (n - F::ONE) - &p_modulus * n;
+        let p_root_vanishing =
+            &p_root * &p_root - p_a - &p_shift * is_not_square - &p_root_carry * &p_modulus;
+        debug_assert_eq!(p_root_vanishing.degree(), P::NB_WITNESS_LIMBS);
+        let p_inverse_vanishing = p_a * &p_inverse
+            - Polynomial::from_coefficients(vec![is_not_square])
+            - &p_inverse_carry * &p_modulus;
+        debug_assert_eq!(p_inverse_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witnesses.
+        let p_root_witness =
+            util::compute_root_quotient_and_shift(&p_root_vanishing, P::WITNESS_OFFSET);
+        let (root_witness_low, root_witness_high) = split_u32_limbs_to_u16_limbs(&p_root_witness);
+        let p_inverse_witness =
+            util::compute_root_quotient_and_shift(&p_inverse_vanishing, P::WITNESS_OFFSET);
+        let (inverse_witness_low, inverse_witness_high) =
+            split_u32_limbs_to_u16_limbs(&p_inverse_witness);
+
+        IsSquareWitness {
+            is_square: F::from_bool(is_square),
+            root: p_root,
+            root_carry: p_root_carry,
+            root_witness_low,
+            root_witness_high,
+            inverse: p_inverse,
+            inverse_carry: p_inverse_carry,
+            inverse_witness_low,
+            inverse_witness_high,
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpIsSquareInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let witness = Self::witness(&p_a, self.non_residue);
+
+        writer.write(&self.is_square, &witness.is_square, row_index);
+        writer.write(&self.root, &witness.root, row_index);
+        writer.write(&self.root_carry, &witness.root_carry, row_index);
+        writer.write_array(&self.root_witness_low, &witness.root_witness_low, row_index);
+        writer.write_array(
+            &self.root_witness_high,
+            &witness.root_witness_high,
+            row_index,
+        );
+        writer.write(&self.inverse, &witness.inverse, row_index);
+        writer.write(&self.inverse_carry, &witness.inverse_carry, row_index);
+        writer.write_array(
+            &self.inverse_witness_low,
+            &witness.inverse_witness_low,
+            row_index,
+        );
+        writer.write_array(
+            &self.inverse_witness_high,
+            &witness.inverse_witness_high,
+            row_index,
+        );
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let witness = Self::witness(&p_a, self.non_residue);
+
+        writer.write(&self.is_square, &witness.is_square);
+        writer.write(&self.root, &witness.root);
+        writer.write(&self.root_carry, &witness.root_carry);
+        writer.write_array(&self.root_witness_low, &witness.root_witness_low);
+        writer.write_array(&self.root_witness_high, &witness.root_witness_high);
+        writer.write(&self.inverse, &witness.inverse);
+        writer.write(&self.inverse_carry, &witness.inverse_carry);
+        writer.write_array(&self.inverse_witness_low, &witness.inverse_witness_low);
+        writer.write_array(&self.inverse_witness_high, &witness.inverse_witness_high);
+    }
+}
+
+/// The smallest quadratic non-residue modulo the odd prime order of `P`.
+pub fn non_residue<P: FieldParameters>() -> u16 {
+    let p = P::modulus();
+    let euler_exp = (&p - 1u32) >> 1;
+    let minus_one = &p - 1u32;
+    (2..=u16::MAX)
+        .find(|n| BigUint::from(*n).modpow(&euler_exp, &p) == minus_one)
+        .expect("the smallest non-residue fits in a limb")
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+    use crate::chip::ec::weierstrass::bn254::Bn254BaseField;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpIsSquareTest;
+
+    impl AirParameters for FpIsSquareTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 200;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 309;
+
+        type Instruction = FpIsSquareInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_non_residue() {
+        assert_eq!(non_residue::<Fp25519>(), 2);
+        assert_eq!(non_residue::<Bn254BaseField>(), 3);
+        assert_eq!(non_residue::<Bls12381ScalarField>(), 5);
+    }
+
+    #[test]
+    fn test_fp_is_square() {
+        type F = GoldilocksField;
+        type L = FpIsSquareTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<FieldRegister<P>>();
+        let _ = builder.fp_is_square(&a_pub);
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let is_square = builder.fp_is_square(&a);
+        let is_square_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&is_square, &is_square_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        let a_pub_int = rng.gen_biguint_below(&p);
+        writer.write(&a_pub, &to_u16_le_limbs_polynomial::<F, P>(&a_pub_int), 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            // Zero, squares and random elements, half of which are not squares.
+            let a_int = match i % 4 {
+                0 => BigUint::zero(),
+                1 => {
+                    let x = rng.gen_biguint_below(&p);
+                    &x * &x % &p
+                }
+                _ => rng.gen_biguint_below(&p),
+            };
+            let is_square_int = sqrt::<P>(&a_int).is_some();
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&is_square_expected, &F::from_bool(is_square_int), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -28,15 +28,30 @@
 //! overflow.
 
 pub mod add;
+pub mod add32;
+pub mod bytes;
+pub mod compare;
 pub mod constants;
+pub mod crt;
 pub mod den;
 pub mod div;
+pub mod extension;
 pub mod inner_product;
 pub mod instruction;
+pub mod inv;
+pub mod is_square;
+pub mod modexp;
+pub mod montgomery;
 pub mod mul;
 pub mod mul_const;
+pub mod mul_sub;
+pub mod neg;
 pub mod ops;
 pub mod parameters;
+pub mod pow;
+pub mod reduce;
 pub mod register;
+pub mod sqrt;
 pub mod sub;
 mod util;
+pub mod wide_mul;
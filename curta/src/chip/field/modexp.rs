@@ -0,0 +1,938 @@
+//! Modular arithmetic with a modulus given by a register.
+//!
+//! The field instructions reduce by the constant modulus of their `FieldParameters`. The
+//! instructions of this module instead take the modulus as an input, as needed by the `modexp`
+//! precompile of Ethereum or by RSA, so a single AIR can check operations under different moduli
+//! such as the public keys of many RSA signatures. The modulus is range checked as a
+//! `FieldRegister` and every result is proven to be the canonical residue.
+//!
+//! Only the limb layout of `P` is used: the number of limbs sets the bit width of the operands and
+//! `P::WITNESS_OFFSET` must bound the witness coefficients, which grow with the number of limbs.
+
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// The check `result + difference + 1 - m = 0` of a `result < m` for a modulus `m` given by a
+/// register, shared by the instructions of this module.
+///
+/// As `difference` is range checked to `P::NB_LIMBS` limbs, the relation shows that `result` is
+/// the canonical residue, and there is no valid trace for `m = 0`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct ReducedCheck<P: FieldParameters> {
+    difference: FieldRegister<P>,
+    witness_low: ArrayRegister<U16Register>,
+    witness_high: ArrayRegister<U16Register>,
+}
+
+/// The values of the registers written by a `ReducedCheck`.
+struct ReducedWitness<F> {
+    difference: Polynomial<F>,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> ReducedCheck<P> {
+    fn alloc<L: AirParameters>(builder: &mut AirBuilder<L>, is_trace: bool) -> Self {
+        if is_trace {
+            Self {
+                difference: builder.alloc::<FieldRegister<P>>(),
+                witness_low: builder.alloc_array::<U16Register>(P::NB_LIMBS - 1),
+                witness_high: builder.alloc_array::<U16Register>(P::NB_LIMBS - 1),
+            }
+        } else {
+            Self {
+                difference: builder.alloc_public::<FieldRegister<P>>(),
+                witness_low: builder.alloc_array_public::<U16Register>(P::NB_LIMBS - 1),
+                witness_high: builder.alloc_array_public::<U16Register>(P::NB_LIMBS - 1),
+            }
+        }
+    }
+
+    fn eval<AP: PolynomialParser>(
+        &self,
+        parser: &mut AP,
+        p_result: &Polynomial<AP::Var>,
+        p_modulus: &Polynomial<AP::Var>,
+    ) {
+        let p_difference = self.difference.eval(parser);
+
+        // Compute the vanishing polynomial result(x) + difference(x) + 1 - m(x).
+        let p_result_plus_difference = parser.poly_add(p_result, &p_difference);
+        let p_one = Polynomial::from_coefficients(vec![AP::Field::ONE]);
+        let p_result_plus_difference_plus_one =
+            parser.poly_add_poly_const(&p_result_plus_difference, &p_one);
+        let p_vanishing = parser.poly_sub(&p_result_plus_difference_plus_one, p_modulus);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+    }
+
+    fn witness<F: PrimeField64>(result: &BigUint, modulus: &BigUint) -> ReducedWitness<F> {
+        debug_assert!(result < modulus);
+        let difference = modulus - result - BigUint::one();
+
+        // Make little endian polynomial limbs.
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(result);
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(modulus);
+        let p_difference = to_u16_le_limbs_polynomial::<F, P>(&difference);
+        let p_one = to_u16_le_limbs_polynomial::<F, P>(&BigUint::one());
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = &p_result + &p_difference + &p_one - &p_modulus;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_LIMBS - 1);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        ReducedWitness {
+            difference: p_difference,
+            witness_low,
+            witness_high,
+        }
+    }
+
+    fn write<F: PrimeField64>(
+        &self,
+        writer: &TraceWriter<F>,
+        witness: &ReducedWitness<F>,
+        row_index: usize,
+    ) {
+        writer.write(&self.difference, &witness.difference, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+    }
+
+    fn write_to_air<F: PrimeField64>(
+        &self,
+        writer: &mut impl AirWriter<Field = F>,
+        witness: &ReducedWitness<F>,
+    ) {
+        writer.write(&self.difference, &witness.difference);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+    }
+}
+
+/// Modular addition. Computes `a + b mod m = result` for a modulus `m` given by a register.
+///
+/// This is done by constraining `a + b - carry * m - result = 0` together with the
+/// `ReducedCheck` of `result < m`. The carry takes `P::NB_LIMBS` limbs, so `a + b` must be below
+/// `m * 2^(16 * P::NB_LIMBS)`, which holds as soon as one of `a` and `b` is below `m`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ModAddInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub modulus: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+    pub(crate) reduced: ReducedCheck<P>,
+}
+
+/// Modular subtraction. Computes `a - b mod m = result` for a modulus `m` given by a register.
+///
+/// This is done by constraining `result + b - carry * m - a = 0` together with the
+/// `ReducedCheck` of `result < m`. The carry is nonnegative only if `a <= result + b`, so `a`
+/// must be below `m`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ModSubInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub modulus: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+    pub(crate) reduced: ReducedCheck<P>,
+}
+
+/// Modular multiplication. Computes `a * b mod m = result` for a modulus `m` given by a register.
+///
+/// This is done by constraining `a * b - quotient * m - result = 0` together with the
+/// `ReducedCheck` of `result < m`. The quotient takes `P::NB_LIMBS` limbs, so `a * b` must be
+/// below `m * 2^(16 * P::NB_LIMBS)`, which holds as soon as one of `a` and `b` is below `m`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ModMulInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub modulus: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) quotient: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+    pub(crate) reduced: ReducedCheck<P>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given `a`, `b` and a nonzero modulus `m`, computes the reduced sum `a + b mod m`.
+    pub fn mod_add<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<ModAddInstruction<P>>,
+    {
+        let result = if a.is_trace() || b.is_trace() || modulus.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_mod_add(a, b, modulus, &result);
+        result
+    }
+
+    pub fn set_mod_add<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<ModAddInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || modulus.is_trace() || result.is_trace();
+
+        let carry: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            carry = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+        let reduced = ReducedCheck::alloc(self, is_trace);
+        let instr = ModAddInstruction {
+            a: *a,
+            b: *b,
+            modulus: *modulus,
+            result: *result,
+            carry,
+            witness_low,
+            witness_high,
+            reduced,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    /// Given `a` below a nonzero modulus `m` and `b`, computes the reduced difference
+    /// `a - b mod m`.
+    pub fn mod_sub<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<ModSubInstruction<P>>,
+    {
+        let result = if a.is_trace() || b.is_trace() || modulus.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_mod_sub(a, b, modulus, &result);
+        result
+    }
+
+    pub fn set_mod_sub<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<ModSubInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || modulus.is_trace() || result.is_trace();
+
+        let carry: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            carry = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+        let reduced = ReducedCheck::alloc(self, is_trace);
+        let instr = ModSubInstruction {
+            a: *a,
+            b: *b,
+            modulus: *modulus,
+            result: *result,
+            carry,
+            witness_low,
+            witness_high,
+            reduced,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    /// Given `a`, `b` and a nonzero modulus `m`, computes the reduced product `a * b mod m`.
+    pub fn mod_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        let result = if a.is_trace() || b.is_trace() || modulus.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_mod_mul(a, b, modulus, &result);
+        result
+    }
+
+    pub fn set_mod_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        modulus: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || modulus.is_trace() || result.is_trace();
+
+        let quotient: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            quotient = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            quotient = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+        let reduced = ReducedCheck::alloc(self, is_trace);
+        let instr = ModMulInstruction {
+            a: *a,
+            b: *b,
+            modulus: *modulus,
+            result: *result,
+            quotient,
+            witness_low,
+            witness_high,
+            reduced,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    /// Given `base` below a nonzero modulus `m`, computes `base^exponent mod m` for a nonzero
+    /// exponent known when building the AIR, such as the public exponent `65537` of RSA.
+    ///
+    /// The exponent is processed by the sliding windows of `fp_pow`, so there is no selection and
+    /// the exponent `65537` takes 17 modular multiplications.
+    pub fn mod_pow<P: FieldParameters>(
+        &mut self,
+        base: &FieldRegister<P>,
+        exponent: &BigUint,
+        modulus: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        self.pow_by_sliding_windows(base, exponent, |builder, x, y| {
+            builder.mod_mul(x, y, modulus)
+        })
+        .expect("the exponent must be nonzero")
+    }
+
+    /// Given `base`, the little-endian bits of `exponent` and a nonzero modulus `m`, computes
+    /// `base^exponent mod m`.
+    ///
+    /// The bits are processed from the most significant by square-and-multiply, where a selection
+    /// by the bit keeps or drops the multiplication by `base`. Every bit of the exponent takes two
+    /// modular multiplications, so the exponent width should be the smallest one the application
+    /// allows.
+    pub fn mod_exp<P: FieldParameters>(
+        &mut self,
+        base: &FieldRegister<P>,
+        exponent: &ArrayRegister<BitRegister>,
+        modulus: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        assert!(
+            !exponent.is_empty(),
+            "the exponent must have at least one bit"
+        );
+
+        // The first squaring reduces `1` modulo `m`, so the result is always reduced.
+        let mut result = self.fp_one::<P>();
+        for i in (0..exponent.len()).rev() {
+            let bit = exponent.get(i);
+            let square = self.mod_mul(&result, &result, modulus);
+            let product = self.mod_mul(&square, base, modulus);
+            result = self.select(&bit, &product, &square);
+        }
+        result
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for ModAddInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_modulus = self.modulus.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+
+        // Compute the vanishing polynomial a(x) + b(x) - carry(x) * m(x) - result(x).
+        let p_a_plus_b = parser.poly_add(&p_a, &p_b);
+        let p_carry_mul_modulus = parser.poly_mul(&p_carry, &p_modulus);
+        let p_a_plus_b_minus_carry = parser.poly_sub(&p_a_plus_b, &p_carry_mul_modulus);
+        let p_vanishing = parser.poly_sub(&p_a_plus_b_minus_carry, &p_result);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+
+        self.reduced.eval(parser, &p_result, &p_modulus);
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for ModSubInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_modulus = self.modulus.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+
+        // Compute the vanishing polynomial result(x) + b(x) - carry(x) * m(x) - a(x).
+        let p_result_plus_b = parser.poly_add(&p_result, &p_b);
+        let p_carry_mul_modulus = parser.poly_mul(&p_carry, &p_modulus);
+        let p_result_plus_b_minus_carry = parser.poly_sub(&p_result_plus_b, &p_carry_mul_modulus);
+        let p_vanishing = parser.poly_sub(&p_result_plus_b_minus_carry, &p_a);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+
+        self.reduced.eval(parser, &p_result, &p_modulus);
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for ModMulInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_modulus = self.modulus.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_quotient = self.quotient.eval(parser);
+
+        // Compute the vanishing polynomial a(x) * b(x) - quotient(x) * m(x) - result(x).
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_quotient_mul_modulus = parser.poly_mul(&p_quotient, &p_modulus);
+        let p_a_mul_b_minus_quotient = parser.poly_sub(&p_a_mul_b, &p_quotient_mul_modulus);
+        let p_vanishing = parser.poly_sub(&p_a_mul_b_minus_quotient, &p_result);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+
+        self.reduced.eval(parser, &p_result, &p_modulus);
+    }
+}
+
+/// The values of the registers written by an instruction of this module, where `carry` is the
+/// quotient of a multiplication.
+struct ModWitness<F> {
+    result: Polynomial<F>,
+    carry: Polynomial<F>,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+    reduced: ReducedWitness<F>,
+}
+
+impl<F: PrimeField64> ModWitness<F> {
+    /// Computes the witness of the relation `lhs - carry * m - rhs = 0` of a reduced `result`,
+    /// where `lhs` and `rhs` are the polynomials of the sides of the relation.
+    fn new<P: FieldParameters>(
+        result: &BigUint,
+        carry: &BigUint,
+        modulus: &BigUint,
+        p_lhs: &Polynomial<F>,
+        p_rhs: &Polynomial<F>,
+        p_modulus: &Polynomial<F>,
+    ) -> Self {
+        debug_assert!(*carry < BigUint::one() << (16 * P::NB_LIMBS));
+
+        // Make little endian polynomial limbs.
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(result);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(carry);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = p_lhs - &(&p_carry * p_modulus) - p_rhs;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        ModWitness {
+            result: p_result,
+            carry: p_carry,
+            witness_low,
+            witness_high,
+            reduced: ReducedCheck::<P>::witness(result, modulus),
+        }
+    }
+}
+
+impl<P: FieldParameters> ModAddInstruction<P> {
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+        p_modulus: &Polynomial<F>,
+    ) -> ModWitness<F> {
+        let [a, b, modulus] =
+            [p_a, p_b, p_modulus].map(|p| field_limbs_to_biguint(p.coefficients()));
+        assert!(!modulus.is_zero(), "the modulus must be nonzero");
+
+        // Compute the modular addition in the integers.
+        let result = (&a + &b) % &modulus;
+        let carry = (&a + &b) / &modulus;
+
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        ModWitness::new::<P>(
+            &result,
+            &carry,
+            &modulus,
+            &(p_a + p_b),
+            &p_result,
+            p_modulus,
+        )
+    }
+}
+
+impl<P: FieldParameters> ModSubInstruction<P> {
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+        p_modulus: &Polynomial<F>,
+    ) -> ModWitness<F> {
+        let [a, b, modulus] =
+            [p_a, p_b, p_modulus].map(|p| field_limbs_to_biguint(p.coefficients()));
+        assert!(!modulus.is_zero(), "the modulus must be nonzero");
+
+        // Compute the modular subtraction in the integers.
+        let result = (&modulus - &b % &modulus + &a % &modulus) % &modulus;
+        assert!(a <= &result + &b, "`a` must be below the modulus");
+        let carry = (&result + &b - &a) / &modulus;
+
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        ModWitness::new::<P>(
+            &result,
+            &carry,
+            &modulus,
+            &(&p_result + p_b),
+            p_a,
+            p_modulus,
+        )
+    }
+}
+
+impl<P: FieldParameters> ModMulInstruction<P> {
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+        p_modulus: &Polynomial<F>,
+    ) -> ModWitness<F> {
+        let [a, b, modulus] =
+            [p_a, p_b, p_modulus].map(|p| field_limbs_to_biguint(p.coefficients()));
+        assert!(!modulus.is_zero(), "the modulus must be nonzero");
+
+        // Compute the modular multiplication in the integers.
+        let result = (&a * &b) % &modulus;
+        let quotient = (&a * &b) / &modulus;
+
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        ModWitness::new::<P>(
+            &result,
+            &quotient,
+            &modulus,
+            &(p_a * p_b),
+            &p_result,
+            p_modulus,
+        )
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for ModAddInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let p_modulus = writer.read(&self.modulus, row_index);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.carry, &witness.carry, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+        self.reduced.write(writer, &witness.reduced, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let p_modulus = writer.read(&self.modulus);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.carry, &witness.carry);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+        self.reduced.write_to_air(writer, &witness.reduced);
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for ModSubInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let p_modulus = writer.read(&self.modulus, row_index);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.carry, &witness.carry, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+        self.reduced.write(writer, &witness.reduced, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let p_modulus = writer.read(&self.modulus);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.carry, &witness.carry);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+        self.reduced.write_to_air(writer, &witness.reduced);
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for ModMulInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let p_modulus = writer.read(&self.modulus, row_index);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.quotient, &witness.carry, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+        self.reduced.write(writer, &witness.reduced, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let p_modulus = writer.read(&self.modulus);
+        let witness = Self::witness(&p_a, &p_b, &p_modulus);
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.quotient, &witness.carry);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+        self.reduced.write_to_air(writer, &witness.reduced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::MAX_NB_LIMBS;
+
+    /// The layout of 64-bit operands, whose modulus is not used.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct U64Limbs;
+
+    impl FieldParameters for U64Limbs {
+        const NB_BITS_PER_LIMB: usize = 16;
+        const NB_LIMBS: usize = 4;
+        const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+        const MODULUS: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+        const WITNESS_OFFSET: usize = 1usize << 20;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct ModAddTest;
+
+    impl AirParameters for ModAddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 46;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 78;
+
+        type Instruction = ModAddInstruction<U64Limbs>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct ModSubTest;
+
+    impl AirParameters for ModSubTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 46;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 78;
+
+        type Instruction = ModSubInstruction<U64Limbs>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct ModExpTest;
+
+    impl AirParameters for ModExpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 524;
+        const NUM_FREE_COLUMNS: usize = 10;
+        const EXTENDED_COLUMNS: usize = 795;
+
+        type Instruction = ModMulInstruction<U64Limbs>;
+    }
+
+    #[test]
+    fn test_mod_exp() {
+        type F = GoldilocksField;
+        type L = ModExpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = U64Limbs;
+
+        const EXPONENT_BITS: usize = 8;
+
+        let mut builder = AirBuilder::<L>::new();
+        let base = builder.alloc::<FieldRegister<P>>();
+        let exponent = builder.alloc_array::<BitRegister>(EXPONENT_BITS);
+        let modulus = builder.alloc::<FieldRegister<P>>();
+        let result = builder.mod_exp(&base, &exponent, &modulus);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let bound = BigUint::one() << 64;
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Moduli of one, and bases above the modulus.
+            let modulus_int = match i % 4 {
+                0 => BigUint::one(),
+                _ => rng.gen_biguint_range(&BigUint::one(), &bound),
+            };
+            let base_int = rng.gen_biguint_below(&bound);
+            let exponent_int = rng.gen::<u8>();
+            let expected_int = base_int.modpow(&BigUint::from(exponent_int), &modulus_int);
+
+            writer.write(&base, &to_u16_le_limbs_polynomial::<F, P>(&base_int), i);
+            writer.write_array(
+                &exponent,
+                (0..EXPONENT_BITS).map(|j| F::from_canonical_u8((exponent_int >> j) & 1)),
+                i,
+            );
+            writer.write(
+                &modulus,
+                &to_u16_le_limbs_polynomial::<F, P>(&modulus_int),
+                i,
+            );
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[test]
+    fn test_mod_add() {
+        type F = GoldilocksField;
+        type L = ModAddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = U64Limbs;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let modulus = builder.alloc::<FieldRegister<P>>();
+        let result = builder.mod_add(&a, &b, &modulus);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let bound = BigUint::one() << 64;
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // A different modulus on every row, with `b` above the modulus.
+            let modulus_int = rng.gen_biguint_range(&BigUint::one(), &bound);
+            let a_int = rng.gen_biguint_below(&modulus_int);
+            let b_int = rng.gen_biguint_below(&bound);
+            let expected_int = (&a_int + &b_int) % &modulus_int;
+
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(
+                &modulus,
+                &to_u16_le_limbs_polynomial::<F, P>(&modulus_int),
+                i,
+            );
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    #[test]
+    fn test_mod_sub() {
+        type F = GoldilocksField;
+        type L = ModSubTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = U64Limbs;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let modulus = builder.alloc::<FieldRegister<P>>();
+        let result = builder.mod_sub(&a, &b, &modulus);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let bound = BigUint::one() << 64;
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // A different modulus on every row, with `b` above the modulus.
+            let modulus_int = rng.gen_biguint_range(&BigUint::one(), &bound);
+            let a_int = rng.gen_biguint_below(&modulus_int);
+            let b_int = rng.gen_biguint_below(&bound);
+            let expected_int = (&modulus_int - &b_int % &modulus_int + &a_int) % &modulus_int;
+
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(
+                &modulus,
+                &to_u16_le_limbs_polynomial::<F, P>(&modulus_int),
+                i,
+            );
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -0,0 +1,348 @@
+//! Field multiplication in Montgomery form.
+//!
+//! An element `a` is represented by `a * R mod p` for `R = 2^(16 * P::NB_LIMBS)`, and the
+//! Montgomery product of `a` and `b` is `a * b * R^(-1) mod p`, so the product of two
+//! representatives is the representative of the product. The constraint checks the Montgomery
+//! reduction `a * b + m * p = R * t` directly, where multiplying by `R` shifts the limbs of `t`,
+//! instead of dividing `a * b` by `p`. An element is converted once into Montgomery form by
+//! `fp_to_montgomery` and back by `fp_from_montgomery`, which makes the representation worth it
+//! for long chains of multiplications like the Miller loop of a pairing.
+
+use num::{BigUint, One};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Montgomery multiplication. Computes `a * b * R^(-1) mod p = result`.
+///
+/// This is done by constraining `a * b + carry * p - R * (result + quotient * p) = 0`, where
+/// `quotient * p + result` is the value `t` of the Montgomery reduction. As all the terms but
+/// the witness are range checked, the relation shows that `result * R = a * b` modulo `p`, and an
+/// honest prover takes the reduced `result`. The shift by `R` takes one more witness limb than
+/// `FpMulInstruction`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpMontMulInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) quotient: U16Register,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given the Montgomery forms of `a` and `b`, computes the Montgomery form of `a * b`.
+    pub fn fp_mont_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMontMulInstruction<P>>,
+    {
+        let result = if a.is_trace() || b.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_fp_mont_mul(a, b, &result);
+        result
+    }
+
+    pub fn set_fp_mont_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpMontMulInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || result.is_trace();
+
+        let carry: FieldRegister<P>;
+        let quotient: U16Register;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<FieldRegister<P>>();
+            quotient = self.alloc::<U16Register>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+        } else {
+            carry = self.alloc_public::<FieldRegister<P>>();
+            quotient = self.alloc_public::<U16Register>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+        }
+        let instr = FpMontMulInstruction {
+            a: *a,
+            b: *b,
+            result: *result,
+            carry,
+            quotient,
+            witness_low,
+            witness_high,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    /// Converts `a` into its Montgomery form `a * R mod p`, as the Montgomery product of `a` and
+    /// `R^2 mod p`.
+    pub fn fp_to_montgomery<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMontMulInstruction<P>>,
+    {
+        let modulus = P::modulus();
+        let r_squared = (BigUint::one() << (32 * P::NB_LIMBS)) % &modulus;
+        let r_squared = self.fp_constant(&r_squared);
+        self.fp_mont_mul(a, &r_squared)
+    }
+
+    /// Converts the Montgomery form of `a` back into `a`, as its Montgomery product with `1`.
+    pub fn fp_from_montgomery<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMontMulInstruction<P>>,
+    {
+        let one = self.fp_one();
+        self.fp_mont_mul(a, &one)
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpMontMulInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+        let quotient = self.quotient.eval(parser);
+        let p_modulus = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+
+        // Compute the vanishing polynomial
+        // a(x) * b(x) + carry(x) * p(x) - x^n * (result(x) + quotient * p(x)).
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_carry_mul_modulus = parser.poly_mul(&p_carry, &p_modulus);
+        let p_a_mul_b_plus_carry = parser.poly_add(&p_a_mul_b, &p_carry_mul_modulus);
+        let p_quotient_mul_modulus = parser.poly_scalar_mul(&p_modulus, &quotient);
+        let p_reduction = parser.poly_add(&p_result, &p_quotient_mul_modulus);
+        let p_reduction_shifted =
+            parser.poly_mul_poly_const(&p_reduction, &util::shift_polynomial::<AP::Field, P>());
+        let p_vanishing = parser.poly_sub(&p_a_mul_b_plus_carry, &p_reduction_shifted);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+    }
+}
+
+/// The inverse of the odd `a` modulo `2^bits`, by Newton iterations which double the number of
+/// correct bits.
+fn inverse_mod_power_of_two(a: &BigUint, bits: usize) -> BigUint {
+    assert!(
+        a.bit(0),
+        "only odd numbers are invertible modulo a power of two"
+    );
+    let mask = (BigUint::one() << bits) - 1u32;
+    let two = BigUint::from(2u32);
+    let mut inverse = BigUint::one();
+    let mut correct_bits = 1;
+    while correct_bits < bits {
+        let a_mul_inverse = (a * &inverse) & &mask;
+        inverse = (&inverse * ((&two + &mask + 1u32 - a_mul_inverse) & &mask)) & &mask;
+        correct_bits *= 2;
+    }
+    inverse
+}
+
+/// The values of the registers written by a `FpMontMulInstruction`.
+struct MontMulWitness<F> {
+    result: Polynomial<F>,
+    carry: Polynomial<F>,
+    quotient: F,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> FpMontMulInstruction<P> {
+    fn witness<F: PrimeField64>(p_a: &Polynomial<F>, p_b: &Polynomial<F>) -> MontMulWitness<F> {
+        let [a, b] = [p_a, p_b].map(|p| field_limbs_to_biguint(p.coefficients()));
+        let modulus = P::modulus();
+        let r_bits = 16 * P::NB_LIMBS;
+        let mask = (BigUint::one() << r_bits) - 1u32;
+
+        // Compute the Montgomery reduction in the integers, with `carry = -a * b / p mod R`.
+        let a_mul_b = &a * &b;
+        let minus_modulus_inv = (&mask + 1u32 - inverse_mod_power_of_two(&modulus, r_bits)) & &mask;
+        let carry = ((&a_mul_b & &mask) * minus_modulus_inv) & &mask;
+        let reduction = (&a_mul_b + &carry * &modulus) >> r_bits;
+        let result = &reduction % &modulus;
+        let quotient = &reduction / &modulus;
+        debug_assert!(quotient < BigUint::from(1u32 << 16));
+        let quotient = F::from_canonical_u64(quotient.iter_u64_digits().next().unwrap_or(0));
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(&carry);
+
+        // Compute the vanishing polynomial.
+        let p_reduction = &p_result + &(&p_modulus * quotient);
+        let p_vanishing =
+            p_a * p_b + &p_carry * &p_modulus - &p_reduction * &util::shift_polynomial::<F, P>();
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS + 1);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        MontMulWitness {
+            result: p_result,
+            carry: p_carry,
+            quotient,
+            witness_low,
+            witness_high,
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpMontMulInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let witness = Self::witness(&p_a, &p_b);
+
+        writer.write(&self.result, &witness.result, row_index);
+        writer.write(&self.carry, &witness.carry, row_index);
+        writer.write(&self.quotient, &witness.quotient, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let witness = Self::witness(&p_a, &p_b);
+
+        writer.write(&self.result, &witness.result);
+        writer.write(&self.carry, &witness.carry);
+        writer.write(&self.quotient, &witness.quotient);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpMontMulTest;
+
+    impl AirParameters for FpMontMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 428;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 651;
+
+        type Instruction = FpMontMulInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_inverse_mod_power_of_two() {
+        let mut rng = thread_rng();
+        for bits in [1, 16, 255, 256] {
+            let a = rng.gen_biguint(bits as u64) | BigUint::one();
+            let inverse = inverse_mod_power_of_two(&a, bits);
+            let mask = (BigUint::one() << bits) - 1u32;
+            assert_eq!((a * inverse) & mask, BigUint::one());
+        }
+    }
+
+    #[test]
+    fn test_fp_mont_mul() {
+        type F = GoldilocksField;
+        type L = FpMontMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        // The product of `a` and `b` through their Montgomery forms.
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let a_mont = builder.fp_to_montgomery(&a);
+        let b_mont = builder.fp_to_montgomery(&b);
+        let product_mont = builder.fp_mont_mul(&a_mont, &b_mont);
+        let product = builder.fp_from_montgomery(&product_mont);
+        let product_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&product, &product_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Reduced values and unreduced values in `[p, 2^256)`.
+            let a_int = match i % 2 {
+                0 => rng.gen_biguint(256),
+                _ => rng.gen_biguint_below(&p),
+            };
+            let b_int = rng.gen_biguint_below(&p);
+            let product_int = &a_int * &b_int % &p;
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(
+                &product_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&product_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -1,6 +1,7 @@
 use num::{BigUint, Zero};
 use serde::{Deserialize, Serialize};
 
+use super::constants::FpConstant;
 use super::parameters::{FieldParameters, MAX_NB_LIMBS};
 use super::register::FieldRegister;
 use super::util;
@@ -70,6 +71,19 @@ impl<L: AirParameters> AirBuilder<L> {
         }
         result
     }
+
+    /// Given a field element `a` and a constant `c` of the same field, computes the product
+    /// `a * c`.
+    pub fn fp_mul_constant<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        c: &FpConstant<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMulConstInstruction<P>>,
+    {
+        self.fp_mul_const(a, c.limbs())
+    }
 }
 
 impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpMulConstInstruction<P> {
@@ -198,6 +212,7 @@ mod tests {
 
     use super::*;
     use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::params::Bls12381BaseField;
     use crate::chip::field::parameters::tests::Fp25519;
 
     #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
@@ -214,6 +229,20 @@ mod tests {
         type Instruction = FpMulConstInstruction<Fp25519>;
     }
 
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct Bls12381FpMulConstTest;
+
+    impl AirParameters for Bls12381FpMulConstTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 188;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 291;
+
+        type Instruction = FpMulConstInstruction<Bls12381BaseField>;
+    }
+
     #[test]
     fn test_fpmul_const() {
         type F = GoldilocksField;
@@ -263,4 +292,49 @@ mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &public);
     }
+
+    #[test]
+    fn test_fpmul_const_24_limbs() {
+        type F = GoldilocksField;
+        type L = Bls12381FpMulConstTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Bls12381BaseField;
+
+        let p = P::modulus();
+        let c = FpConstant::<P>::new(&(&p - 3u32));
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let result = builder.fp_mul_constant(&a, &c);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let a_int = rng.gen_biguint_below(&p);
+            let expected_int = &a_int * c.value() % &p;
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
 }
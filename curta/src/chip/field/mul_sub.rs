@@ -0,0 +1,305 @@
+//! Implements the non-native difference of two products as an "instruction".
+//!
+//! The sum `a * b + c * d` is an inner product of length two, see `inner_product.rs`. For the
+//! difference, the integer `a * b - c * d` may be negative, so the vanishing polynomial is
+//! offset by the multiple `p(x) * p(x)` of the modulus:
+//!
+//! a(x) * b(x) + p(x) * p(x) - c(x) * d(x) - result(x) - carry(x) * p(x)
+//!
+//! For inputs reduced modulo `p`, the carry is in `[0, 2p)`, as for an inner product of length
+//! two.
+
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp multiply-subtract. Computes `a * b - c * d = result`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpMulSubInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub c: FieldRegister<P>,
+    pub d: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given field elements `a`, `b`, `c` and `d`, computes `a * b - c * d` in a single
+    /// instruction.
+    pub fn fp_mul_sub<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        c: &FieldRegister<P>,
+        d: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMulSubInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || c.is_trace() || d.is_trace();
+        let result = if is_trace {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_fp_mul_sub(a, b, c, d, &result);
+        result
+    }
+
+    pub fn set_fp_mul_sub<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        c: &FieldRegister<P>,
+        d: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpMulSubInstruction<P>>,
+    {
+        let is_trace =
+            a.is_trace() || b.is_trace() || c.is_trace() || d.is_trace() || result.is_trace();
+
+        let carry: FieldRegister<P>;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<FieldRegister<P>>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+        } else {
+            carry = self.alloc_public::<FieldRegister<P>>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+        }
+
+        let instr = FpMulSubInstruction {
+            a: *a,
+            b: *b,
+            c: *c,
+            d: *d,
+            result: *result,
+            carry,
+            witness_low,
+            witness_high,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpMulSubInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_c = self.c.eval(parser);
+        let p_d = self.d.eval(parser);
+        let p_result = self.result.eval(parser);
+        let p_carry = self.carry.eval(parser);
+
+        let p_modulus = Polynomial::from_iter(util::modulus_field_iter::<AP::Field, P>());
+        let p_modulus_squared = parser.constant_poly(&(&p_modulus * &p_modulus));
+        let p_limbs = parser.constant_poly(&p_modulus);
+
+        // Compute the vanishing polynomial
+        // a(x) * b(x) + p(x) * p(x) - c(x) * d(x) - result(x) - carry(x) * p(x).
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_c_mul_d = parser.poly_mul(&p_c, &p_d);
+        let p_offset_a_mul_b = parser.poly_add(&p_a_mul_b, &p_modulus_squared);
+        let p_difference = parser.poly_sub(&p_offset_a_mul_b, &p_c_mul_d);
+        let p_difference_minus_result = parser.poly_sub(&p_difference, &p_result);
+        let p_carry_mul_modulus = parser.poly_mul(&p_carry, &p_limbs);
+        let p_vanishing = parser.poly_sub(&p_difference_minus_result, &p_carry_mul_modulus);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high)
+    }
+}
+
+impl<P: FieldParameters> FpMulSubInstruction<P> {
+    /// The limbs of the result, the carry and the witness limbs from the values of the inputs.
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        p_b: &Polynomial<F>,
+        p_c: &Polynomial<F>,
+        p_d: &Polynomial<F>,
+    ) -> (Polynomial<F>, Polynomial<F>, Vec<F>, Vec<F>) {
+        let [a, b, c, d] = [p_a, p_b, p_c, p_d].map(|p| field_limbs_to_biguint(p.coefficients()));
+
+        // Compute the offset difference in the integers.
+        let modulus = &P::modulus();
+        let modulus_squared = &(modulus * modulus);
+        let offset_difference = a * b + modulus_squared - c * d;
+        let result = &(&offset_difference % modulus);
+        let carry = &((&offset_difference - result) / modulus);
+        assert!(result < modulus);
+        assert!(carry < &(2u32 * modulus));
+        assert_eq!(carry * modulus, offset_difference - result);
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(result);
+        let p_carry = to_u16_le_limbs_polynomial::<F, P>(carry);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing =
+            p_a * p_b + &p_modulus * &p_modulus - p_c * p_d - &p_result - &p_carry * &p_modulus;
+        assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        (p_result, p_carry, p_witness_low, p_witness_high)
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpMulSubInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let p_c = writer.read(&self.c, row_index);
+        let p_d = writer.read(&self.d, row_index);
+        let (p_result, p_carry, p_witness_low, p_witness_high) =
+            Self::witness(&p_a, &p_b, &p_c, &p_d);
+
+        writer.write(&self.result, &p_result, row_index);
+        writer.write(&self.carry, &p_carry, row_index);
+        writer.write_array(&self.witness_low, &p_witness_low, row_index);
+        writer.write_array(&self.witness_high, &p_witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let p_c = writer.read(&self.c);
+        let p_d = writer.read(&self.d);
+        let (p_result, p_carry, p_witness_low, p_witness_high) =
+            Self::witness(&p_a, &p_b, &p_c, &p_d);
+
+        writer.write(&self.result, &p_result);
+        writer.write(&self.carry, &p_carry);
+        writer.write_array(&self.witness_low, &p_witness_low);
+        writer.write_array(&self.witness_high, &p_witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpMulSubTest;
+
+    impl AirParameters for FpMulSubTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 172;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 267;
+
+        type Instruction = FpMulSubInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fp_mul_sub() {
+        type F = GoldilocksField;
+        type L = FpMulSubTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+        type Fp = FieldRegister<P>;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<Fp>();
+        let b_pub = builder.alloc_public::<Fp>();
+        let c_pub = builder.alloc_public::<Fp>();
+        let d_pub = builder.alloc_public::<Fp>();
+        let _ = builder.fp_mul_sub(&a_pub, &b_pub, &c_pub, &d_pub);
+
+        let a = builder.alloc::<Fp>();
+        let b = builder.alloc::<Fp>();
+        let c = builder.alloc::<Fp>();
+        let d = builder.alloc::<Fp>();
+        let result = builder.fp_mul_sub(&a, &b, &c, &d);
+        let expected = builder.alloc::<Fp>();
+        builder.assert_equal(&result, &expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for register in [a_pub, b_pub, c_pub, d_pub] {
+            let value = rng.gen_biguint_below(&p);
+            writer.write(&register, &to_u16_le_limbs_polynomial::<F, P>(&value), 0);
+        }
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            let a_int = rng.gen_biguint_below(&p);
+            let b_int = rng.gen_biguint_below(&p);
+            // Make `c * d` larger than `a * b` on every other row.
+            let (c_int, d_int) = if i % 2 == 0 {
+                (&p - 1u32, rng.gen_biguint_below(&p))
+            } else {
+                (rng.gen_biguint_below(&p), rng.gen_biguint_below(&p))
+            };
+            let ab = &a_int * &b_int % &p;
+            let cd = &c_int * &d_int % &p;
+            let expected_int = (&p + ab - cd) % &p;
+
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(&c, &to_u16_le_limbs_polynomial::<F, P>(&c_int), i);
+            writer.write(&d, &to_u16_le_limbs_polynomial::<F, P>(&d_int), i);
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&expected_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
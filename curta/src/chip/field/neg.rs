@@ -0,0 +1,295 @@
+use num::{BigUint, One};
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{digits_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp Negation. Computes `-a = result`.
+///
+/// This is done by constraining `a + result - carry * p = 0`. As `a` and `result` are both below
+/// `2^(16 * NB_LIMBS)`, the carry is a single 16-bit limb, so the vanishing polynomial has the
+/// degree of the operands and the witness takes `NB_LIMBS - 1` limbs instead of the
+/// `NB_WITNESS_LIMBS` of a subtraction from zero.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpNegInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub result: FieldRegister<P>,
+    pub(crate) carry: U16Register,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a field element `a`, computes the negation `-a = c`.
+    pub fn fp_neg<P: FieldParameters>(&mut self, a: &FieldRegister<P>) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpNegInstruction<P>>,
+    {
+        let result = if a.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_fp_neg(a, &result);
+
+        result
+    }
+
+    pub fn set_fp_neg<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpNegInstruction<P>>,
+    {
+        // The carry of `a + result` is at most `(2^(16 * NB_LIMBS) - 1 + p - 1) / p`, which must
+        // fit in the 16-bit carry limb.
+        let modulus = P::modulus();
+        let max_carry = ((BigUint::one() << (16 * P::NB_LIMBS)) + &modulus - 2u32) / &modulus;
+        assert!(
+            max_carry < BigUint::from(1u32 << 16),
+            "the carry of FpNeg does not fit in 16 bits for a {}-bit modulus with {} limbs",
+            modulus.bits(),
+            P::NB_LIMBS
+        );
+
+        let is_trace = a.is_trace() || result.is_trace();
+        let carry: U16Register;
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            carry = self.alloc::<U16Register>();
+            witness_low = self.alloc_array::<U16Register>(P::NB_LIMBS - 1);
+            witness_high = self.alloc_array::<U16Register>(P::NB_LIMBS - 1);
+        } else {
+            carry = self.alloc_public::<U16Register>();
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_LIMBS - 1);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_LIMBS - 1);
+        }
+        let instr = FpNegInstruction {
+            a: *a,
+            result: *result,
+            carry,
+            witness_low,
+            witness_high,
+        };
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+}
+
+// Constraints for FpNegInstruction
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpNegInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_result = self.result.eval(parser);
+        let carry = self.carry.eval(parser);
+
+        let p_a_plus_result = parser.poly_add(&p_a, &p_result);
+        let p_limbs = parser.constant_poly(&Polynomial::from_iter(util::modulus_field_iter::<
+            AP::Field,
+            P,
+        >()));
+
+        let p_mul_times_carry = parser.poly_scalar_mul(&p_limbs, &carry);
+        let p_vanishing = parser.poly_sub(&p_a_plus_result, &p_mul_times_carry);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high)
+    }
+}
+
+impl<P: FieldParameters> FpNegInstruction<P> {
+    /// The limbs of the negation of `a`, the carry and the witness limbs.
+    fn witness<F: PrimeField64>(p_a: &Polynomial<F>) -> (Polynomial<F>, F, Vec<F>, Vec<F>) {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+
+        // Compute field negation in the integers.
+        let modulus = P::modulus();
+        let result = (&modulus - &a % &modulus) % &modulus;
+        let carry = (&a + &result) / &modulus;
+        debug_assert!(result < modulus);
+        debug_assert_eq!(&carry * &modulus, &a + &result);
+        let carry = F::from_canonical_u64(carry.iter_u64_digits().next().unwrap_or(0));
+
+        // Make little endian polynomial limbs.
+        let p_modulus = to_u16_le_limbs_polynomial::<F, P>(&modulus);
+        let p_result = to_u16_le_limbs_polynomial::<F, P>(&result);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = p_a + &p_result - &p_modulus * carry;
+        debug_assert_eq!(p_vanishing.degree(), P::NB_LIMBS - 1);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (p_witness_low, p_witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        (p_result, carry, p_witness_low, p_witness_high)
+    }
+}
+
+// Instruction trait
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpNegInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let (p_result, carry, p_witness_low, p_witness_high) = Self::witness(&p_a);
+
+        writer.write(&self.result, &p_result, row_index);
+        writer.write(&self.carry, &carry, row_index);
+        writer.write_array(&self.witness_low, &p_witness_low, row_index);
+        writer.write_array(&self.witness_high, &p_witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let (p_result, carry, p_witness_low, p_witness_high) = Self::witness(&p_a);
+
+        writer.write(&self.result, &p_result);
+        writer.write(&self.carry, &carry);
+        writer.write_array(&self.witness_low, &p_witness_low);
+        writer.write_array(&self.witness_high, &p_witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+    use crate::chip::field::parameters::MAX_NB_LIMBS;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpNegTest;
+
+    impl AirParameters for FpNegTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 79;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 126;
+
+        type Instruction = FpNegInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fpneg() {
+        type F = GoldilocksField;
+        type L = FpNegTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<FieldRegister<P>>();
+        let _ = builder.fp_neg(&a_pub);
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let neg_a = builder.fp_neg(&a);
+        let neg_a_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&neg_a, &neg_a_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        let a_pub_int = rng.gen_biguint_below(&p);
+        writer.write(&a_pub, &to_u16_le_limbs_polynomial::<F, P>(&a_pub_int), 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            // Zero, reduced values and unreduced values in `[p, 2^256)`.
+            let a_int = match i % 4 {
+                0 => BigUint::from(0u32),
+                1 => rng.gen_biguint_range(&p, &(BigUint::from(1u32) << 256)),
+                _ => rng.gen_biguint_below(&p),
+            };
+            let neg_a_int = (&p - &a_int % &p) % &p;
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(
+                &neg_a_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&neg_a_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
+    /// A 224-bit modulus in 16 limbs, for which the carry of a negation can exceed 16 bits.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct WideLimbsModulus;
+
+    impl FieldParameters for WideLimbsModulus {
+        const NB_BITS_PER_LIMB: usize = 16;
+        const NB_LIMBS: usize = 16;
+        const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+        const MODULUS: [u16; MAX_NB_LIMBS] = [
+            65517, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535, 65535,
+            65535, 65535, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        const WITNESS_OFFSET: usize = 1usize << 20;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpNegWideLimbsTest;
+
+    impl AirParameters for FpNegWideLimbsTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 79;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 126;
+
+        type Instruction = FpNegInstruction<WideLimbsModulus>;
+    }
+
+    #[test]
+    #[should_panic(expected = "the carry of FpNeg does not fit in 16 bits")]
+    fn test_fpneg_wide_limbs() {
+        let mut builder = AirBuilder::<FpNegWideLimbsTest>::new();
+        let a = builder.alloc::<FieldRegister<WideLimbsModulus>>();
+        let _ = builder.fp_neg(&a);
+    }
+}
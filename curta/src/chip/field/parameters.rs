@@ -6,6 +6,8 @@ use rand::rngs::OsRng;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+/// The largest number of limbs of a `FieldParameters`, which holds moduli of up to 512 bits such
+/// as the 381-bit base field of BLS12-381 in 24 limbs.
 pub const MAX_NB_LIMBS: usize = 32;
 pub const LIMB: u32 = 2u32.pow(16);
 
@@ -0,0 +1,198 @@
+//! Exponentiation of field elements by a fixed exponent.
+
+use num::BigUint;
+
+use super::mul::FpMulInstruction;
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::chip::builder::AirBuilder;
+use crate::chip::AirParameters;
+
+/// The maximal number of bits of a window of `fp_pow`.
+const WINDOW_BITS: usize = 4;
+
+/// Splits `exponent` into windows of at most `WINDOW_BITS` bits ending with a one, from the most
+/// significant.
+///
+/// Each window is a pair `(digit, low)` of an odd digit and the index of its lowest bit, so that
+/// `exponent` is the sum of the `digit * 2^low`.
+fn sliding_windows(exponent: &BigUint) -> Vec<(u64, usize)> {
+    let mut windows = Vec::new();
+    let mut high = exponent.bits() as usize;
+    while high > 0 {
+        if !exponent.bit(high as u64 - 1) {
+            high -= 1;
+            continue;
+        }
+        let mut low = high.saturating_sub(WINDOW_BITS);
+        while !exponent.bit(low as u64) {
+            low += 1;
+        }
+        let digit = (low..high)
+            .rev()
+            .fold(0, |acc, i| (acc << 1) | exponent.bit(i as u64) as u64);
+        windows.push((digit, low));
+        high = low;
+    }
+    windows
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `a^exponent` for an exponent known when building the AIR, such as `p - 2` for an
+    /// inversion by Fermat's little theorem or `(p + 3) / 8` for a square root candidate in
+    /// `Fp25519`.
+    ///
+    /// The exponent is processed by sliding windows, see `sliding_windows`. The odd powers of `a`
+    /// up to the largest digit are computed once and shared by all the windows, and the other
+    /// multiplications are the squarings between the windows.
+    pub fn fp_pow<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        exponent: &BigUint,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpMulInstruction<P>>,
+    {
+        self.pow_by_sliding_windows(a, exponent, |builder, x, y| builder.fp_mul(x, y))
+            .unwrap_or_else(|| self.fp_one())
+    }
+
+    /// Computes `a^exponent` by sliding windows with the multiplication `mul`, or returns `None`
+    /// for a zero exponent.
+    pub(crate) fn pow_by_sliding_windows<T: Copy>(
+        &mut self,
+        a: &T,
+        exponent: &BigUint,
+        mut mul: impl FnMut(&mut Self, &T, &T) -> T,
+    ) -> Option<T> {
+        let windows = sliding_windows(exponent);
+        let max_digit = windows.iter().map(|(digit, _)| *digit).max()?;
+
+        // The odd powers `a, a^3, ..., a^max_digit`.
+        let mut odd_powers = vec![*a];
+        if max_digit > 1 {
+            let a_squared = mul(self, a, a);
+            for k in 1..=(max_digit as usize / 2) {
+                let power = mul(self, &odd_powers[k - 1], &a_squared);
+                odd_powers.push(power);
+            }
+        }
+
+        let (digit, mut low) = windows[0];
+        let mut result = odd_powers[digit as usize / 2];
+        for &(digit, next_low) in windows[1..].iter() {
+            for _ in next_low..low {
+                result = mul(self, &result, &result);
+            }
+            result = mul(self, &result, &odd_powers[digit as usize / 2]);
+            low = next_low;
+        }
+        for _ in 0..low {
+            result = mul(self, &result, &result);
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use num::{One, Zero};
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+    use crate::chip::utils::field_limbs_to_biguint;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpPowTest;
+
+    impl AirParameters for FpPowTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 9;
+
+        type Instruction = FpMulInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        let p = Fp25519::modulus();
+        let exponents = [
+            BigUint::zero(),
+            BigUint::one(),
+            BigUint::from(0b1011_0001_1100u32),
+            &p - 2u32,
+            (&p + 3u32) >> 3,
+        ];
+        for exponent in exponents {
+            let windows = sliding_windows(&exponent);
+            for (digit, _) in windows.iter() {
+                assert_eq!(digit % 2, 1);
+                assert!(*digit < 1 << WINDOW_BITS);
+            }
+            let value = windows.iter().fold(BigUint::zero(), |acc, (digit, low)| {
+                acc + (BigUint::from(*digit) << *low)
+            });
+            assert_eq!(value, exponent);
+        }
+    }
+
+    #[test]
+    fn test_fp_pow() {
+        type F = GoldilocksField;
+        type L = FpPowTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+        let exponents = [
+            BigUint::zero(),
+            BigUint::one(),
+            BigUint::from(1u32 << 10),
+            &p - 2u32,
+            (&p + 3u32) >> 3,
+        ];
+
+        // The powers act on a public input as global instructions.
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc_public::<FieldRegister<P>>();
+        let powers = exponents
+            .iter()
+            .map(|exponent| builder.fp_pow(&a, exponent))
+            .collect::<Vec<_>>();
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let a_int = rng.gen_biguint_below(&p);
+        let writer = generator.new_writer();
+        writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), 0);
+        writer.write_global_instructions(&generator.air_data);
+        for (power, exponent) in powers.iter().zip(exponents.iter()) {
+            let value = writer.read(power, 0);
+            assert_eq!(
+                field_limbs_to_biguint(value.coefficients()),
+                a_int.modpow(exponent, &p)
+            );
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
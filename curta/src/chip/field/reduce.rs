@@ -0,0 +1,240 @@
+//! Reduction of integers modulo the order of a field.
+//!
+//! The field chips take inputs of `NB_LIMBS` limbs of 16 bits, which may hold any integer below
+//! `2^(16 * NB_LIMBS)`, and constrain their results modulo the field order. This makes it
+//! possible to reduce integers coming from elsewhere, such as the x-coordinate of a point
+//! compared with a signature modulo the group order, or the 512-bit digest reduced to an EdDSA
+//! challenge.
+//!
+//! As for the other field chips, the results are only constrained modulo the field order, so
+//! they must be compared with canonical values written by the prover.
+
+use num::{BigUint, One};
+
+use super::add::FpAddInstruction;
+use super::mul_const::FpMulConstInstruction;
+use super::parameters::{FieldParameters, MAX_NB_LIMBS};
+use super::register::FieldRegister;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::RegisterSerializable;
+use crate::chip::utils::bigint_into_u16_digits;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Reduces the element `a` of the field `Q` modulo the order of `P`, where both fields have
+    /// the same number of limbs.
+    pub fn fp_reduce<Q: FieldParameters, P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<Q>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpAddInstruction<P>>,
+    {
+        assert_eq!(
+            Q::NB_LIMBS,
+            P::NB_LIMBS,
+            "Fields must have the same number of limbs"
+        );
+        let a = FieldRegister::<P>::from_register_unsafe(*a.register());
+        let zero = self.fp_zero();
+        self.fp_add(&a, &zero)
+    }
+
+    /// Reduces the integer of little-endian 16-bit limbs `limbs` modulo the order of `P`.
+    ///
+    /// The number of limbs must be a multiple of `NB_LIMBS`. The limbs are split into chunks of
+    /// `NB_LIMBS` limbs, which are combined by Horner's rule with a multiplication by the
+    /// constant `2^(16 * NB_LIMBS) mod p`.
+    pub fn fp_reduce_wide<P: FieldParameters>(
+        &mut self,
+        limbs: &ArrayRegister<U16Register>,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpAddInstruction<P>> + From<FpMulConstInstruction<P>>,
+    {
+        assert!(
+            !limbs.is_empty() && limbs.len() % P::NB_LIMBS == 0,
+            "Number of limbs must be a nonzero multiple of {}",
+            P::NB_LIMBS
+        );
+        let shift = (BigUint::one() << (P::NB_BITS_PER_LIMB * P::NB_LIMBS)) % P::modulus();
+        let mut shift_limbs = [0u16; MAX_NB_LIMBS];
+        shift_limbs[..P::NB_LIMBS].copy_from_slice(&bigint_into_u16_digits(&shift, P::NB_LIMBS));
+
+        let num_chunks = limbs.len() / P::NB_LIMBS;
+        let chunk = |i: usize| {
+            FieldRegister::<P>::from_register_unsafe(
+                *limbs
+                    .get_subarray(i * P::NB_LIMBS..(i + 1) * P::NB_LIMBS)
+                    .register(),
+            )
+        };
+        let mut result = self.fp_reduce::<P, P>(&chunk(num_chunks - 1));
+        for i in (0..num_chunks - 1).rev() {
+            let shifted = self.fp_mul_const(&result, shift_limbs);
+            result = self.fp_add(&shifted, &chunk(i));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::edwards::ed25519::params::{Ed25519Parameters, Ed25519ScalarField};
+    use crate::chip::ec::edwards::EdwardsParameters;
+    use crate::chip::ec::secp256k1::params::{
+        Secp256k1BaseField, Secp256k1Parameters, Secp256k1ScalarField,
+    };
+    use crate::chip::ec::weierstrass::bn254::{Bn254Parameters, Bn254ScalarField};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpReduceWideTest;
+
+    impl AirParameters for FpReduceWideTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 256;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 393;
+
+        type Instruction = FpInstruction<Ed25519ScalarField>;
+    }
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpReduceTest;
+
+    impl AirParameters for FpReduceTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 64;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 105;
+
+        type Instruction = FpInstruction<Secp256k1ScalarField>;
+    }
+
+    #[test]
+    fn test_scalar_field_orders() {
+        assert_eq!(
+            Ed25519ScalarField::modulus(),
+            Ed25519Parameters::prime_group_order()
+        );
+        assert_eq!(
+            Secp256k1ScalarField::modulus(),
+            Secp256k1Parameters::prime_group_order()
+        );
+        assert_eq!(
+            Bn254ScalarField::modulus(),
+            Bn254Parameters::prime_group_order()
+        );
+    }
+
+    #[test]
+    fn test_fp_reduce_wide() {
+        type F = GoldilocksField;
+        type L = FpReduceWideTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Ed25519ScalarField;
+
+        let mut builder = AirBuilder::<L>::new();
+        let limbs = builder.alloc_array::<U16Register>(2 * P::NB_LIMBS);
+        let result = builder.fp_reduce_wide::<P>(&limbs);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+        let low = FieldRegister::<P>::from_register_unsafe(
+            *limbs.get_subarray(0..P::NB_LIMBS).register(),
+        );
+        let high = FieldRegister::<P>::from_register_unsafe(
+            *limbs.get_subarray(P::NB_LIMBS..2 * P::NB_LIMBS).register(),
+        );
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let order = P::modulus();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // A 512-bit integer, as for the digest of an EdDSA challenge.
+            let low_int = rng.gen_biguint(256);
+            let high_int = rng.gen_biguint(256);
+            let value = (&high_int << 256) + &low_int;
+            writer.write(&low, &to_u16_le_limbs_polynomial::<F, P>(&low_int), i);
+            writer.write(&high, &to_u16_le_limbs_polynomial::<F, P>(&high_int), i);
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&(value % &order)),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        writer.write_global_instructions(&generator.air_data);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+
+    #[test]
+    fn test_fp_reduce() {
+        type F = GoldilocksField;
+        type L = FpReduceTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type Q = Secp256k1BaseField;
+        type P = Secp256k1ScalarField;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<Q>>();
+        let result = builder.fp_reduce::<Q, P>(&a);
+        let expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&result, &expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let (p, n) = (Q::modulus(), P::modulus());
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Elements of the base field, half of them in `[n, p)`.
+            let a_int = if i % 2 == 0 {
+                rng.gen_biguint_range(&n, &p)
+            } else {
+                rng.gen_biguint_below(&p)
+            };
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, Q>(&a_int), i);
+            writer.write(
+                &expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&(&a_int % &n)),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        writer.write_global_instructions(&generator.air_data);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
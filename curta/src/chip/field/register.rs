@@ -106,6 +106,7 @@ mod tests {
     use crate::chip::field::parameters::FieldParameters;
     use crate::chip::field::register::FieldRegister;
     use crate::chip::memory::time::Time;
+    use crate::chip::register::bit::BitRegister;
     use crate::chip::AirParameters;
     use crate::machine::builder::Builder;
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
@@ -129,6 +130,66 @@ mod tests {
         type Instruction = FpInstruction<Fp25519>;
     }
 
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpSelectTest;
+
+    impl AirParameters for FpSelectTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 48;
+        const NUM_FREE_COLUMNS: usize = 3;
+        const EXTENDED_COLUMNS: usize = 81;
+
+        type Instruction = FpInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fp_select() {
+        type F = GoldilocksField;
+        type L = FpSelectTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let p = Fp25519::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let bit = builder.alloc::<BitRegister>();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let result = builder.select(&bit, &a, &b);
+
+        let num_rows = 1 << 16;
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            let a_int = rng.gen_biguint(256) % &p;
+            let b_int = rng.gen_biguint(256) % &p;
+            let p_a = Polynomial::<F>::from_biguint_field(&a_int, 16, 16);
+            let p_b = Polynomial::<F>::from_biguint_field(&b_int, 16, 16);
+            let bit_value = i % 3 == 0;
+            writer.write(&bit, &F::from_canonical_u8(bit_value as u8), i);
+            writer.write(&a, &p_a, i);
+            writer.write(&b, &p_b, i);
+            writer.write_row_instructions(&generator.air_data, i);
+            let expected = if bit_value { p_a } else { p_b };
+            assert_eq!(writer.read(&result, i), expected);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+
     #[test]
     fn test_fp_memory_add() {
         type F = GoldilocksField;
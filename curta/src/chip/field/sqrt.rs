@@ -0,0 +1,416 @@
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::compare::FpCompareInstruction;
+use super::mul::FpMulInstruction;
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp Square Root over any field. Computes `sqrt(a) = result`.
+///
+/// This is done by witnessing the square root of `a` whose least significant bit is `is_odd`, and
+/// then constraining that `result * result == a` and `result < p`. As `result` is reduced, the
+/// bit selects one of the two roots, as the sign of a compressed point does: otherwise `p + r`
+/// would pass for a root `r` of the opposite parity. There is no valid trace if `a` is not a
+/// quadratic residue, which can be checked beforehand with `sqrt`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpSqrtInstruction<P: FieldParameters> {
+    /// a `FpMulInstruction` to compute `result * result = a`.
+    square: FpMulInstruction<P>,
+    /// The least significant bit of the square root.
+    is_odd: BitRegister,
+    /// Witness the bits of the least significant limb (skipping the first bit).
+    limb_witness: ArrayRegister<BitRegister>,
+    /// a `FpCompareInstruction` to compute `result < p`, whose bit is constrained to be set.
+    reduced: FpCompareInstruction<P>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a square field element `a`, computes its square root whose least significant bit is
+    /// `is_odd`.
+    pub fn fp_sqrt<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        is_odd: &BitRegister,
+    ) -> FieldRegister<P>
+    where
+        L::Instruction: From<FpSqrtInstruction<P>>,
+    {
+        let result = if a.is_trace() || is_odd.is_trace() {
+            self.alloc::<FieldRegister<P>>()
+        } else {
+            self.alloc_public::<FieldRegister<P>>()
+        };
+        self.set_fp_sqrt(a, is_odd, &result);
+        result
+    }
+
+    pub fn set_fp_sqrt<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        is_odd: &BitRegister,
+        result: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpSqrtInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || is_odd.is_trace() || result.is_trace();
+        let instr = self.fp_sqrt_instruction(a, is_odd, result, is_trace);
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+
+    fn fp_sqrt_instruction<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        is_odd: &BitRegister,
+        result: &FieldRegister<P>,
+        is_trace: bool,
+    ) -> FpSqrtInstruction<P> {
+        let square_carry: FieldRegister<P>;
+        let square_witness_low: ArrayRegister<U16Register>;
+        let square_witness_high: ArrayRegister<U16Register>;
+        let limb_witness: ArrayRegister<BitRegister>;
+
+        if is_trace {
+            square_carry = self.alloc::<FieldRegister<P>>();
+            square_witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            square_witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS);
+            limb_witness = self.alloc_array::<BitRegister>(P::NB_BITS_PER_LIMB - 1);
+        } else {
+            square_carry = self.alloc_public::<FieldRegister<P>>();
+            square_witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            square_witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS);
+            limb_witness = self.alloc_array_public::<BitRegister>(P::NB_BITS_PER_LIMB - 1);
+        }
+
+        // check that result * result == a
+        let square = FpMulInstruction {
+            a: *result,
+            b: *result,
+            result: *a,
+            carry: square_carry,
+            witness_low: square_witness_low,
+            witness_high: square_witness_high,
+        };
+
+        let modulus = self.fp_constant::<P>(&P::modulus());
+        let reduced = self.fp_compare_instruction(result, &modulus, true, is_trace);
+
+        FpSqrtInstruction {
+            square,
+            is_odd: *is_odd,
+            limb_witness,
+            reduced,
+        }
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpSqrtInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        // Assert that result * result == a
+        self.square.eval(parser);
+
+        // Assert that the least significant bit of the square root is `is_odd`, by witnessing all
+        // other bits of the least significant limb.
+        let mut acc = self.is_odd.eval(parser);
+        for (i, bit) in self.limb_witness.iter().enumerate() {
+            let bit = bit.eval(parser);
+            let two_i = parser.constant(AP::Field::from_canonical_u32(1 << (i + 1)));
+            let bit_two_i = parser.mul(two_i, bit);
+            acc = parser.add(acc, bit_two_i);
+        }
+        let limb = self.square.a.eval(parser).coefficients[0];
+        parser.assert_eq(limb, acc);
+
+        // Assert that the square root is reduced.
+        self.reduced.eval(parser);
+        let is_reduced = self.reduced.result.eval(parser);
+        let one = parser.one();
+        parser.assert_eq(is_reduced, one);
+    }
+}
+
+impl<P: FieldParameters> FpSqrtInstruction<P> {
+    /// The limbs of the square root of `a` of parity `is_odd` and the bits of its least
+    /// significant limb. The root is zero if `a` is not a square.
+    fn witness<F: PrimeField64>(
+        p_a: &Polynomial<F>,
+        is_odd: F,
+    ) -> (Polynomial<F>, impl Iterator<Item = F>) {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let modulus = P::modulus();
+        let beta = sqrt::<P>(&digits_to_biguint(&a_digits)).unwrap_or_default();
+        let beta = if beta.bit(0) == (is_odd == F::ONE) {
+            beta
+        } else {
+            (&modulus - beta) % &modulus
+        };
+        let p_beta = to_u16_le_limbs_polynomial::<F, P>(&beta);
+
+        let limb = p_beta.coefficients[0].as_canonical_u64();
+        let limb_bits = (0..P::NB_BITS_PER_LIMB)
+            .map(move |i| F::from_canonical_u64((limb >> i) & 1))
+            .skip(1);
+        (p_beta, limb_bits)
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpSqrtInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.square.result, row_index);
+        let is_odd = writer.read(&self.is_odd, row_index);
+        let (p_beta, limb_bits) = Self::witness(&p_a, is_odd);
+
+        writer.write(&self.square.a, &p_beta, row_index);
+        writer.write_array(&self.limb_witness, limb_bits, row_index);
+        self.square.write(writer, row_index);
+        self.reduced.write(writer, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.square.result);
+        let is_odd = writer.read(&self.is_odd);
+        let (p_beta, limb_bits) = Self::witness(&p_a, is_odd);
+
+        writer.write(&self.square.a, &p_beta);
+        writer.write_array(&self.limb_witness, limb_bits);
+        self.square.write_to_air(writer);
+        self.reduced.write_to_air(writer);
+    }
+}
+
+/// A square root of `a` modulo the order of `P` computed with the Tonelli-Shanks algorithm, or
+/// `None` if `a` is not a quadratic residue.
+pub fn sqrt<P: FieldParameters>(a: &BigUint) -> Option<BigUint> {
+    let p = P::modulus();
+    let a = a % &p;
+    if a.is_zero() {
+        return Some(a);
+    }
+    let one = BigUint::one();
+    let euler_exp = (&p - 1u32) >> 1;
+    if a.modpow(&euler_exp, &p) != one {
+        return None;
+    }
+
+    // Write `p - 1 = q * 2^s` with `q` odd.
+    let s = (&p - 1u32).trailing_zeros().unwrap();
+    let q = (&p - 1u32) >> s;
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&euler_exp, &p) == one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, &p);
+    let mut t = a.modpow(&q, &p);
+    let mut root = a.modpow(&((&q + 1u32) >> 1), &p);
+    while t != one {
+        // The least `i` such that `t^(2^i) = 1`, which is smaller than `m`.
+        let mut i = 0;
+        let mut t_pow = t.clone();
+        while t_pow != one {
+            t_pow = &t_pow * &t_pow % &p;
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), &p);
+        m = i;
+        c = &b * &b % &p;
+        t = t * &c % &p;
+        root = root * &b % &p;
+    }
+    Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+    use crate::chip::ec::secp256k1::params::Secp256k1BaseField;
+    use crate::chip::ec::weierstrass::bn254::Bn254BaseField;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpSqrtTest;
+
+    impl AirParameters for FpSqrtTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 172;
+        const NUM_FREE_COLUMNS: usize = 20;
+        const EXTENDED_COLUMNS: usize = 267;
+
+        type Instruction = FpSqrtInstruction<Bls12381ScalarField>;
+    }
+
+    fn check_sqrt<P: FieldParameters>() {
+        let mut rng = thread_rng();
+        let p = P::modulus();
+        for _ in 0..32 {
+            let x = rng.gen_biguint_below(&p);
+            let x_sq = &x * &x % &p;
+            let root = sqrt::<P>(&x_sq).unwrap();
+            assert!(root == x || root == (&p - &x) % &p);
+        }
+        // Exactly one of `a` and `-a` is a square when `p = 3 mod 4`, and both or none otherwise.
+        let a = rng.gen_biguint_range(&BigUint::one(), &p);
+        let is_square = sqrt::<P>(&a).is_some();
+        let neg_is_square = sqrt::<P>(&(&p - &a)).is_some();
+        assert_eq!(is_square == neg_is_square, !p.bit(1));
+    }
+
+    #[test]
+    fn test_sqrt() {
+        // Fields of 2-adicity 2, 1, 1 and 32.
+        check_sqrt::<Fp25519>();
+        check_sqrt::<Bn254BaseField>();
+        check_sqrt::<Secp256k1BaseField>();
+        check_sqrt::<Bls12381ScalarField>();
+
+        // 5 is the smallest non-residue modulo the BLS12-381 scalar field order.
+        assert_eq!(sqrt::<Bls12381ScalarField>(&BigUint::from(5u32)), None);
+        assert!(sqrt::<Bls12381ScalarField>(&BigUint::from(4u32)).is_some());
+    }
+
+    #[test]
+    fn test_fpsqrt() {
+        type F = GoldilocksField;
+        type L = FpSqrtTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Bls12381ScalarField;
+
+        let p = P::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let a_pub = builder.alloc_public::<FieldRegister<P>>();
+        let is_odd_pub = builder.alloc_public::<BitRegister>();
+        let _ = builder.fp_sqrt(&a_pub, &is_odd_pub);
+
+        let a = builder.alloc::<FieldRegister<P>>();
+        let is_odd = builder.alloc::<BitRegister>();
+        let root = builder.fp_sqrt(&a, &is_odd);
+        let root_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&root, &root_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        let x = rng.gen_biguint_below(&p);
+        writer.write(
+            &a_pub,
+            &to_u16_le_limbs_polynomial::<F, P>(&(&x * &x % &p)),
+            0,
+        );
+        writer.write(&is_odd_pub, &F::from_bool(x.bit(0)), 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            let x = rng.gen_biguint_below(&p);
+            let is_odd_value = rng.gen_bool(0.5);
+            let root_int = if x.bit(0) == is_odd_value {
+                x.clone()
+            } else {
+                (&p - &x) % &p
+            };
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&(&x * &x % &p)), i);
+            writer.write(&is_odd, &F::from_bool(is_odd_value), i);
+            writer.write(
+                &root_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&root_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fpsqrt_unreduced_root() {
+        type F = GoldilocksField;
+        type L = FpSqrtTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Bls12381ScalarField;
+
+        let p = P::modulus();
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let is_odd = builder.alloc::<BitRegister>();
+        let root = builder.alloc::<FieldRegister<P>>();
+        let instr = builder.fp_sqrt_instruction(&a, &is_odd, &root, true);
+        builder.register_instruction(instr);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let x = rng.gen_biguint_below(&p);
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&(&x * &x % &p)), i);
+            writer.write(&is_odd, &F::from_bool(x.bit(0)), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        // Replace the root of the first row by `p + r` for a small root `r`, whose parity is the
+        // opposite of the parity of `r` as `p` is odd. The square and the parity hold, so only the
+        // range of the root rejects it.
+        let r = BigUint::from(3u32);
+        let unreduced_root = &p + &r;
+        writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&(&r * &r)), 0);
+        writer.write(&is_odd, &F::from_bool(unreduced_root.bit(0)), 0);
+        let p_root = to_u16_le_limbs_polynomial::<F, P>(&unreduced_root);
+        let limb = p_root.coefficients[0].as_canonical_u64();
+        writer.write(&root, &p_root, 0);
+        writer.write_array(
+            &instr.limb_witness,
+            (1..P::NB_BITS_PER_LIMB).map(|i| F::from_canonical_u64((limb >> i) & 1)),
+            0,
+        );
+        instr.square.write(&writer, 0);
+        instr.reduced.write(&writer, 0);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        test_starky(&stark, &config, &generator, &public);
+    }
+}
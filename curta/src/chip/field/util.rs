@@ -41,6 +41,13 @@ pub fn modulus_field_iter<F: Field, P: FieldParameters>() -> impl Iterator<Item
         .take(P::NB_LIMBS)
 }
 
+/// The polynomial `x^NB_LIMBS`, which shifts an integer by `2^(16 * P::NB_LIMBS)`.
+pub fn shift_polynomial<F: Field, P: FieldParameters>() -> Polynomial<F> {
+    let mut coefficients = vec![F::ZERO; P::NB_LIMBS];
+    coefficients.push(F::ONE);
+    Polynomial::from_coefficients(coefficients)
+}
+
 #[inline]
 pub fn compute_root_quotient_and_shift<F: PrimeField64>(
     p_vanishing: &Polynomial<F>,
@@ -0,0 +1,267 @@
+//! Multiplication of multi-limb integers without modular reduction.
+//!
+//! The product of two integers of `n = P::NB_LIMBS` limbs takes `2 * n` limbs, which are
+//! returned as the low and the high half of the product. This gives the intermediate products of
+//! Barrett or Montgomery reductions and of RSA, where the reduction modulo `p` of `fp_mul` would
+//! lose the high half.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::FieldParameters;
+use super::register::FieldRegister;
+use super::util;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::{field_limbs_to_biguint, split_u32_limbs_to_u16_limbs};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Wide multiplication. Computes `a * b = low + high * 2^(16 * n)` for `n = P::NB_LIMBS`.
+///
+/// This is done by constraining `a * b - low - x^n * high = 0` at `x = 2^16`. As `low` and `high`
+/// are range checked to `n` limbs, they are the unique halves of the product. The leading
+/// coefficient of `high` takes one more witness limb than `FpMulInstruction`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct FpWideMulInstruction<P: FieldParameters> {
+    pub a: FieldRegister<P>,
+    pub b: FieldRegister<P>,
+    pub low: FieldRegister<P>,
+    pub high: FieldRegister<P>,
+    pub(crate) witness_low: ArrayRegister<U16Register>,
+    pub(crate) witness_high: ArrayRegister<U16Register>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given the integers `a` and `b`, computes the low and the high half of the product `a * b`.
+    pub fn fp_wide_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+    ) -> (FieldRegister<P>, FieldRegister<P>)
+    where
+        L::Instruction: From<FpWideMulInstruction<P>>,
+    {
+        let (low, high) = if a.is_trace() || b.is_trace() {
+            (
+                self.alloc::<FieldRegister<P>>(),
+                self.alloc::<FieldRegister<P>>(),
+            )
+        } else {
+            (
+                self.alloc_public::<FieldRegister<P>>(),
+                self.alloc_public::<FieldRegister<P>>(),
+            )
+        };
+        self.set_fp_wide_mul(a, b, &low, &high);
+        (low, high)
+    }
+
+    pub fn set_fp_wide_mul<P: FieldParameters>(
+        &mut self,
+        a: &FieldRegister<P>,
+        b: &FieldRegister<P>,
+        low: &FieldRegister<P>,
+        high: &FieldRegister<P>,
+    ) where
+        L::Instruction: From<FpWideMulInstruction<P>>,
+    {
+        let is_trace = a.is_trace() || b.is_trace() || low.is_trace() || high.is_trace();
+
+        let witness_low: ArrayRegister<U16Register>;
+        let witness_high: ArrayRegister<U16Register>;
+        if is_trace {
+            witness_low = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+            witness_high = self.alloc_array::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+        } else {
+            witness_low = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+            witness_high = self.alloc_array_public::<U16Register>(P::NB_WITNESS_LIMBS + 1);
+        }
+        let instr = FpWideMulInstruction {
+            a: *a,
+            b: *b,
+            low: *low,
+            high: *high,
+            witness_low,
+            witness_high,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for FpWideMulInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        let p_a = self.a.eval(parser);
+        let p_b = self.b.eval(parser);
+        let p_low = self.low.eval(parser);
+        let p_high = self.high.eval(parser);
+
+        // Compute the vanishing polynomial a(x) * b(x) - low(x) - x^n * high(x).
+        let p_a_mul_b = parser.poly_mul(&p_a, &p_b);
+        let p_a_mul_b_minus_low = parser.poly_sub(&p_a_mul_b, &p_low);
+        let p_high_shifted =
+            parser.poly_mul_poly_const(&p_high, &util::shift_polynomial::<AP::Field, P>());
+        let p_vanishing = parser.poly_sub(&p_a_mul_b_minus_low, &p_high_shifted);
+
+        let p_witness_low = Polynomial::from_coefficients(self.witness_low.eval_vec(parser));
+        let p_witness_high = Polynomial::from_coefficients(self.witness_high.eval_vec(parser));
+        util::eval_field_operation::<AP, P>(parser, &p_vanishing, &p_witness_low, &p_witness_high);
+    }
+}
+
+/// The values of the registers written by a `FpWideMulInstruction`.
+struct WideMulWitness<F> {
+    low: Polynomial<F>,
+    high: Polynomial<F>,
+    witness_low: Vec<F>,
+    witness_high: Vec<F>,
+}
+
+impl<P: FieldParameters> FpWideMulInstruction<P> {
+    fn witness<F: PrimeField64>(p_a: &Polynomial<F>, p_b: &Polynomial<F>) -> WideMulWitness<F> {
+        let [a, b] = [p_a, p_b].map(|p| field_limbs_to_biguint(p.coefficients()));
+
+        // Split the product in the integers.
+        let product = &a * &b;
+        let low = &product % (BigUint::from(1u32) << (16 * P::NB_LIMBS));
+        let high = &product >> (16 * P::NB_LIMBS);
+
+        // Make little endian polynomial limbs.
+        let p_low = to_u16_le_limbs_polynomial::<F, P>(&low);
+        let p_high = to_u16_le_limbs_polynomial::<F, P>(&high);
+
+        // Compute the vanishing polynomial.
+        let p_vanishing = p_a * p_b - &p_low - &p_high * &util::shift_polynomial::<F, P>();
+        debug_assert_eq!(p_vanishing.degree(), P::NB_WITNESS_LIMBS + 1);
+
+        // Compute the witness.
+        let p_witness = util::compute_root_quotient_and_shift(&p_vanishing, P::WITNESS_OFFSET);
+        let (witness_low, witness_high) = split_u32_limbs_to_u16_limbs(&p_witness);
+
+        WideMulWitness {
+            low: p_low,
+            high: p_high,
+            witness_low,
+            witness_high,
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for FpWideMulInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_b = writer.read(&self.b, row_index);
+        let witness = Self::witness(&p_a, &p_b);
+
+        writer.write(&self.low, &witness.low, row_index);
+        writer.write(&self.high, &witness.high, row_index);
+        writer.write_array(&self.witness_low, &witness.witness_low, row_index);
+        writer.write_array(&self.witness_high, &witness.witness_high, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_b = writer.read(&self.b);
+        let witness = Self::witness(&p_a, &p_b);
+
+        writer.write(&self.low, &witness.low);
+        writer.write(&self.high, &witness.high);
+        writer.write_array(&self.witness_low, &witness.witness_low);
+        writer.write_array(&self.witness_high, &witness.witness_high);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::field::parameters::tests::Fp25519;
+
+    #[derive(Clone, Debug, Copy, Serialize, Deserialize)]
+    struct FpWideMulTest;
+
+    impl AirParameters for FpWideMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 158;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 246;
+
+        type Instruction = FpWideMulInstruction<Fp25519>;
+    }
+
+    #[test]
+    fn test_fp_wide_mul() {
+        type F = GoldilocksField;
+        type L = FpWideMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type P = Fp25519;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<FieldRegister<P>>();
+        let b = builder.alloc::<FieldRegister<P>>();
+        let (low, high) = builder.fp_wide_mul(&a, &b);
+        let low_expected = builder.alloc::<FieldRegister<P>>();
+        let high_expected = builder.alloc::<FieldRegister<P>>();
+        builder.assert_equal(&low, &low_expected);
+        builder.assert_equal(&high, &high_expected);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let bound = BigUint::from(1u32) << 256;
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Random values, and the largest values whose product fills all the limbs.
+            let (a_int, b_int) = match i % 4 {
+                0 => (&bound - 1u32, &bound - 1u32),
+                _ => (rng.gen_biguint(256), rng.gen_biguint(256)),
+            };
+            let product = &a_int * &b_int;
+            let low_int = &product % &bound;
+            let high_int = &product >> 256;
+            writer.write(&a, &to_u16_le_limbs_polynomial::<F, P>(&a_int), i);
+            writer.write(&b, &to_u16_le_limbs_polynomial::<F, P>(&b_int), i);
+            writer.write(
+                &low_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&low_int),
+                i,
+            );
+            writer.write(
+                &high_expected,
+                &to_u16_le_limbs_polynomial::<F, P>(&high_int),
+                i,
+            );
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -219,6 +219,7 @@ impl<
             &writer,
             max_num_chunks * NUM_MIX_ROUNDS,
         );
+        writer.write_global_instructions(&trace_generator.air_data);
 
         for i in 0..num_rows {
             writer.write_row_instructions(&trace_generator.air_data, i);
@@ -1258,6 +1258,7 @@ mod tests {
                 &writer,
                 num_rows / 4,
             );
+            writer.write_global_instructions(&generator.air_data);
             let mut msg_to_check = 0;
             for i in 0..num_rows {
                 writer.write_row_instructions(&generator.air_data, i);
@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use super::ConstraintInstruction;
+use super::{ConstraintInstruction, Instruction};
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
 use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
 use crate::math::prelude::*;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -25,3 +30,48 @@ impl<AP: AirParser> AirConstraint<AP> for BitConstraint {
         }
     }
 }
+
+/// Constrains `bits` to be the little-endian bit decomposition of `value`.
+///
+/// The bits themselves are assumed to be constrained boolean, which is the case for all
+/// allocated bit registers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitDecomposition<F> {
+    pub value: ArithmeticExpression<F>,
+    pub bits: ArrayRegister<BitRegister>,
+}
+
+impl<F: Field, AP: AirParser<Field = F>> AirConstraint<AP> for BitDecomposition<F> {
+    fn eval(&self, parser: &mut AP) {
+        assert_eq!(self.value.size, 1, "Can only decompose a single element");
+        let value = self.value.eval(parser)[0];
+
+        let mut sum = parser.zero();
+        let mut power = F::ONE;
+        for bit in self.bits.iter() {
+            let bit = bit.eval(parser);
+            let term = parser.mul_const(bit, power);
+            sum = parser.add(sum, term);
+            power *= F::from_canonical_u8(2);
+        }
+        parser.assert_eq(value, sum);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for BitDecomposition<F> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let value = writer.read_expression(&self.value, row_index)[0].as_canonical_u64();
+        for (i, bit) in self.bits.iter().enumerate() {
+            let bit_value = F::from_canonical_u64((value >> i) & 1);
+            writer.write(&bit, &bit_value, row_index);
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let value = writer.read_expression(&self.value)[0].as_canonical_u64();
+        for (i, bit) in self.bits.iter().enumerate() {
+            let bit_value = F::from_canonical_u64((value >> i) & 1);
+            writer.write(&bit, &bit_value);
+        }
+    }
+}
@@ -11,6 +11,8 @@ pub mod bit;
 pub mod clock;
 pub mod cycle;
 pub mod empty;
+pub mod power;
+pub mod segment;
 pub mod set;
 
 pub trait Instruction<F: Field>:
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use super::set::AirInstruction;
+use super::Instruction;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// Writes `value^exponent` to `result`.
+///
+/// The instruction adds no constraints. It computes witnesses, such as roots, whose correctness
+/// is cheaper to check by other constraints than to compute by them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerInstruction<F> {
+    pub value: ArithmeticExpression<F>,
+    pub exponent: u64,
+    pub result: ElementRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates a register holding `value^exponent`, without constraining it.
+    ///
+    /// The caller is responsible for constraining the result.
+    pub fn unconstrained_power(
+        &mut self,
+        value: ArithmeticExpression<L::Field>,
+        exponent: u64,
+    ) -> ElementRegister {
+        assert_eq!(value.size, 1, "Can only raise a single element to a power");
+        let is_trace = value.is_trace();
+        let result = if is_trace {
+            self.alloc::<ElementRegister>()
+        } else {
+            self.alloc_public::<ElementRegister>()
+        };
+        let instr = AirInstruction::Power(PowerInstruction {
+            value,
+            exponent,
+            result,
+        });
+        if is_trace {
+            self.register_air_instruction_internal(instr);
+        } else {
+            self.register_global_air_instruction_internal(instr);
+        }
+        result
+    }
+}
+
+impl<F: Field, AP: AirParser<Field = F>> AirConstraint<AP> for PowerInstruction<F> {
+    fn eval(&self, _parser: &mut AP) {}
+}
+
+impl<F: Field> Instruction<F> for PowerInstruction<F> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let value = writer.read_expression(&self.value, row_index)[0];
+        writer.write(&self.result, &value.pow(self.exponent), row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let value = writer.read_expression(&self.value)[0];
+        writer.write(&self.result, &value.pow(self.exponent));
+    }
+}
@@ -0,0 +1,99 @@
+use core::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::Instruction;
+use crate::air::parser::{AirParser, BoundaryMultipliers, MulParser};
+use crate::air::AirConstraint;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::math::prelude::*;
+
+/// The selector columns of a row segment.
+///
+/// `selector` is one on the rows of the segment, `transition` is one on all of them except the
+/// last one, and `first_row` is one on the first row of the segment only.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SegmentFilter {
+    pub selector: BitRegister,
+    pub transition: BitRegister,
+    pub first_row: BitRegister,
+}
+
+/// Writes the selector columns of a segment occupying the trace rows in `rows`.
+///
+/// The values are constrained by the builder when the segment is opened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentSelectorInstruction {
+    pub(crate) filter: SegmentFilter,
+    pub(crate) rows: Range<usize>,
+}
+
+impl SegmentFilter {
+    /// A parser whose constraints are only enforced on the rows of the segment.
+    ///
+    /// Transition constraints are not enforced between the last row of the segment and the next
+    /// one, and first and last row constraints are enforced on the first and last rows of the
+    /// segment. All constraints are further multiplied by `filter`, if given.
+    pub(crate) fn mul_parser<'a, AP: AirParser>(
+        &self,
+        parser: &'a mut AP,
+        filter: Option<AP::Var>,
+    ) -> MulParser<'a, AP> {
+        let selector = self.selector.eval(parser);
+        let transition = self.transition.eval(parser);
+        let first_row = self.first_row.eval(parser);
+        let last_row = parser.sub(selector, transition);
+
+        let mut multipliers = [selector, transition, first_row, last_row];
+        if let Some(filter) = filter {
+            for multiplier in multipliers.iter_mut() {
+                *multiplier = parser.mul(*multiplier, filter);
+            }
+        }
+        let [selector, transition, first_row, last_row] = multipliers;
+
+        MulParser::with_boundary(
+            parser,
+            selector,
+            BoundaryMultipliers {
+                transition,
+                first_row,
+                last_row,
+            },
+        )
+    }
+}
+
+impl SegmentSelectorInstruction {
+    fn values<F: Field>(&self, row_index: usize) -> [F; 3] {
+        let selector = self.rows.contains(&row_index);
+        let transition = selector && row_index + 1 < self.rows.end;
+        let first_row = row_index == self.rows.start;
+        [selector, transition, first_row].map(|value| F::from_canonical_u8(value as u8))
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for SegmentSelectorInstruction {
+    fn eval(&self, _parser: &mut AP) {}
+}
+
+impl<F: Field> Instruction<F> for SegmentSelectorInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let [selector, transition, first_row] = self.values(row_index);
+        writer.write(&self.filter.selector, &selector, row_index);
+        writer.write(&self.filter.transition, &transition, row_index);
+        writer.write(&self.filter.first_row, &first_row, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let row_index = writer
+            .row_index()
+            .expect("Segment selectors can only be written to trace rows");
+        let [selector, transition, first_row] = self.values(row_index);
+        writer.write(&self.filter.selector, &selector);
+        writer.write(&self.filter.transition, &transition);
+        writer.write(&self.filter.first_row, &first_row);
+    }
+}
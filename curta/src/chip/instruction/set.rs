@@ -4,9 +4,11 @@ use log::debug;
 use serde::{Deserialize, Serialize};
 
 use super::assign::AssignInstruction;
-use super::bit::BitConstraint;
+use super::bit::{BitConstraint, BitDecomposition};
 use super::clock::ClockInstruction;
 use super::cycle::{Cycle, ProcessIdInstruction};
+use super::power::PowerInstruction;
+use super::segment::{SegmentFilter, SegmentSelectorInstruction};
 use super::Instruction;
 use crate::air::parser::{AirParser, MulParser};
 use crate::air::AirConstraint;
@@ -14,6 +16,7 @@ use crate::chip::arithmetic::expression::ArithmeticExpression;
 use crate::chip::bool::SelectInstruction;
 use crate::chip::memory::instruction::MemoryInstruction;
 use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::register::memory::MemorySlice;
 use crate::chip::trace::writer::TraceWriter;
@@ -23,6 +26,8 @@ use crate::math::prelude::*;
 pub enum AirInstruction<F, I> {
     CustomInstruction(I),
     BitConstraint(BitConstraint),
+    BitDecomposition(BitDecomposition<F>),
+    Power(PowerInstruction<F>),
     Assign(AssignInstruction<F>),
     Select(SelectInstruction),
     Cycle(Cycle<F>),
@@ -31,6 +36,8 @@ pub enum AirInstruction<F, I> {
     Filtered(ArithmeticExpression<F>, Arc<Self>),
     Mem(MemoryInstruction<F>),
     Watch(String, ArrayRegister<ElementRegister>),
+    SegmentSelector(SegmentSelectorInstruction),
+    Segment(SegmentFilter, Arc<Self>),
 }
 
 impl<F: Field, AP: AirParser<Field = F>, I> AirConstraint<AP> for AirInstruction<F, I>
@@ -41,6 +48,8 @@ where
         match self {
             AirInstruction::CustomInstruction(i) => AirConstraint::<AP>::eval(i, parser),
             AirInstruction::BitConstraint(i) => AirConstraint::<AP>::eval(i, parser),
+            AirInstruction::BitDecomposition(i) => AirConstraint::<AP>::eval(i, parser),
+            AirInstruction::Power(i) => AirConstraint::<AP>::eval(i, parser),
             AirInstruction::Assign(i) => AirConstraint::<AP>::eval(i, parser),
             AirInstruction::Select(i) => AirConstraint::<AP>::eval(i, parser),
             AirInstruction::Cycle(i) => AirConstraint::<AP>::eval(i, parser),
@@ -63,15 +72,45 @@ where
             }
             AirInstruction::Mem(i) => AirConstraint::<AP>::eval(i, parser),
             AirInstruction::Watch(_, _) => {}
+            AirInstruction::SegmentSelector(i) => AirConstraint::<AP>::eval(i, parser),
+            AirInstruction::Segment(segment, instr) => {
+                let (filter, instr) = match instr.as_ref() {
+                    AirInstruction::Filtered(expression, instr) => {
+                        assert_eq!(
+                            expression.size, 1,
+                            "Expression multiplying instruction must be of size 1"
+                        );
+                        (Some(expression.eval(parser)[0]), instr.as_ref())
+                    }
+                    instr => (None, instr),
+                };
+                let mut mul_parser = segment.mul_parser(parser, filter);
+                match instr {
+                    AirInstruction::CustomInstruction(i) => i.eval(&mut mul_parser),
+                    AirInstruction::BitConstraint(i) => i.eval(&mut mul_parser),
+                    AirInstruction::BitDecomposition(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Power(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Assign(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Select(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Cycle(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Clock(i) => i.eval(&mut mul_parser),
+                    AirInstruction::ProcessId(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Mem(i) => i.eval(&mut mul_parser),
+                    AirInstruction::Watch(_, _) => {}
+                    _ => unreachable!("Instructions cannot be assigned to two segments"),
+                }
+            }
         }
     }
 }
 
-impl<F: Field, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
+impl<F: PrimeField64, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
     fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
         match self {
             AirInstruction::CustomInstruction(i) => i.write(writer, row_index),
             AirInstruction::BitConstraint(i) => Instruction::<F>::write(i, writer, row_index),
+            AirInstruction::BitDecomposition(i) => Instruction::<F>::write(i, writer, row_index),
+            AirInstruction::Power(i) => Instruction::<F>::write(i, writer, row_index),
             AirInstruction::Select(i) => Instruction::<F>::write(i, writer, row_index),
             AirInstruction::Assign(i) => Instruction::<F>::write(i, writer, row_index),
             AirInstruction::Cycle(i) => Instruction::<F>::write(i, writer, row_index),
@@ -88,6 +127,13 @@ impl<F: Field, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
                 let value = writer.read_vec(register, row_index);
                 debug!("row {}: , {}: {:?}", row_index, name, value);
             }
+            AirInstruction::SegmentSelector(i) => Instruction::<F>::write(i, writer, row_index),
+            AirInstruction::Segment(segment, i) => {
+                let selector = writer.read(&segment.selector, row_index);
+                if selector == F::ONE {
+                    i.write(writer, row_index)
+                }
+            }
         }
     }
 
@@ -95,6 +141,8 @@ impl<F: Field, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
         match self {
             AirInstruction::CustomInstruction(i) => i.write_to_air(writer),
             AirInstruction::BitConstraint(i) => i.write_to_air(writer),
+            AirInstruction::BitDecomposition(i) => i.write_to_air(writer),
+            AirInstruction::Power(i) => i.write_to_air(writer),
             AirInstruction::Select(i) => i.write_to_air(writer),
             AirInstruction::Assign(i) => i.write_to_air(writer),
             AirInstruction::Cycle(i) => i.write_to_air(writer),
@@ -116,6 +164,13 @@ impl<F: Field, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
                     debug!("{}: {:?}", name, value);
                 }
             }
+            AirInstruction::SegmentSelector(i) => i.write_to_air(writer),
+            AirInstruction::Segment(segment, i) => {
+                let selector = writer.read(&segment.selector);
+                if selector == F::ONE {
+                    i.write_to_air(writer)
+                }
+            }
         }
     }
 }
@@ -131,6 +186,13 @@ impl<F, I> AirInstruction<F, I> {
         AirInstruction::BitConstraint(BitConstraint(*register))
     }
 
+    pub fn bit_decomposition(
+        value: ArithmeticExpression<F>,
+        bits: ArrayRegister<BitRegister>,
+    ) -> Self {
+        AirInstruction::BitDecomposition(BitDecomposition { value, bits })
+    }
+
     pub fn assign(assignment: AssignInstruction<F>) -> Self {
         AirInstruction::Assign(assignment)
     }
@@ -2,7 +2,9 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use self::constraint::Constraint;
+use self::debug::ConstraintLabels;
 use self::instruction::Instruction;
+use crate::air::PublicChallengeDatum;
 use crate::math::extension::cubic::parameters::CubicParameters;
 use crate::math::prelude::*;
 use crate::plonky2::stark::Starky;
@@ -12,6 +14,7 @@ pub mod arithmetic;
 pub mod bool;
 pub mod builder;
 pub mod constraint;
+pub mod debug;
 pub mod ec;
 pub mod field;
 pub mod hash;
@@ -63,8 +66,11 @@ pub trait AirParameters:
 pub struct Chip<L: AirParameters> {
     constraints: Vec<Constraint<L>>,
     global_constraints: Vec<Constraint<L>>,
+    labels: ConstraintLabels,
+    constraint_degree: usize,
     pub execution_trace_length: usize,
     pub num_challenges: usize,
+    pub public_challenge_data: PublicChallengeDatum,
     pub num_public_values: usize,
     pub num_global_values: usize,
 }
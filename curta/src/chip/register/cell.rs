@@ -2,5 +2,6 @@
 pub enum CellType {
     U16,
     Bit,
+    Byte,
     Element,
 }
@@ -17,7 +17,7 @@ pub struct BusChannel<T, E> {
     pub out_channel: CubicRegister,
     table_accumulator: CubicRegister,
     challenge: CubicRegister,
-    entries: Vec<LogEntry<T>>,
+    pub(crate) entries: Vec<LogEntry<T>>,
     row_accumulators: Vec<CubicRegister>,
     _marker: PhantomData<E>,
 }
@@ -109,16 +109,28 @@ impl<T: EvalCubic, F: Field, E: CubicParameters<F>> LogLookupTable<T, F, E> {
         &mut self,
         builder: &mut AirBuilder<L>,
         values: &[T],
+    ) -> LogLookupValues<T, F, E> {
+        let entries = values
+            .iter()
+            .map(|value| LogEntry::input(*value))
+            .collect::<Vec<_>>();
+        self.new_lookup_entries(builder, &entries)
+    }
+
+    pub(crate) fn new_lookup_entries<L: AirParameters<Field = F, CubicParams = E>>(
+        &mut self,
+        builder: &mut AirBuilder<L>,
+        entries: &[LogEntry<T>],
     ) -> LogLookupValues<T, F, E> {
         let mut trace_values = Vec::new();
         let mut public_values = Vec::new();
 
-        for value in values.iter() {
-            match value.register() {
-                MemorySlice::Public(..) => public_values.push(LogEntry::input(*value)),
-                MemorySlice::Local(..) => trace_values.push(LogEntry::input(*value)),
+        for entry in entries.iter() {
+            match entry.value().register() {
+                MemorySlice::Public(..) => public_values.push(entry.clone()),
+                MemorySlice::Local(..) => trace_values.push(entry.clone()),
                 MemorySlice::Next(..) => unreachable!("Next register not supported for lookup"),
-                MemorySlice::Global(..) => public_values.push(LogEntry::input(*value)),
+                MemorySlice::Global(..) => public_values.push(entry.clone()),
                 MemorySlice::Challenge(..) => unreachable!("Cannot lookup challenge register"),
             }
         }
@@ -155,7 +167,20 @@ impl<F: Field, E: CubicParameters<F>> LogLookupTable<ElementRegister, F, E> {
         builder: &mut AirBuilder<L>,
         values: &[ElementRegister],
     ) -> LogLookupValues<ElementRegister, F, E> {
-        let lookup_values = self.new_lookup_values(builder, values);
+        let entries = values
+            .iter()
+            .map(|value| LogEntry::input(*value))
+            .collect::<Vec<_>>();
+        self.register_lookup_entries(builder, &entries)
+    }
+
+    /// Registers lookups of the values of `entries`, each counted with the entry's multiplicity.
+    pub fn register_lookup_entries<L: AirParameters<Field = F, CubicParams = E>>(
+        &mut self,
+        builder: &mut AirBuilder<L>,
+        entries: &[LogEntry<ElementRegister>],
+    ) -> LogLookupValues<ElementRegister, F, E> {
+        let lookup_values = self.new_lookup_entries(builder, entries);
         lookup_values.register_constraints(builder);
         builder
             .lookup_values
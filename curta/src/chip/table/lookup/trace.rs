@@ -1,5 +1,3 @@
-use plonky2_maybe_rayon::MaybeIntoParIter;
-
 use super::LogLookupTable;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::cubic::EvalCubic;
@@ -59,38 +57,40 @@ impl<F: PrimeField> TraceWriter<F> {
         public_entries: &[LogEntry<T>],
         table_index: impl Fn(T::Value<F>) -> (usize, usize),
     ) -> AirTrace<F> {
-        let mut multiplicities_trace = AirTrace::new_with_value(num_table_columns, num_rows, 0u32);
+        let mut multiplicities_trace =
+            AirTrace::new_with_value(num_table_columns, num_rows, F::ZERO);
 
-        // Count the multiplicities in the trace
+        // Count the multiplicities in the trace, skipping the entries with zero multiplicity.
         let trace = self.read_trace().unwrap();
         for row in trace.rows() {
             for entry in trace_entries.iter() {
+                let multiplier = entry.read_from_slice(row).multiplier;
+                if multiplier == F::ZERO {
+                    continue;
+                }
                 let value = entry.value().read_from_slice(row);
                 let (row_index, col_index) = table_index(value);
                 assert!(col_index < num_table_columns);
                 assert!(row_index < num_rows);
-                multiplicities_trace.row_mut(row_index)[col_index] += 1;
+                multiplicities_trace.row_mut(row_index)[col_index] += multiplier;
             }
         }
 
         // Count the multiplicities in public inputs
         let public_slice = self.public.read().unwrap();
         for entry in public_entries.iter() {
+            let multiplier = entry.read_from_slice(&public_slice).multiplier;
+            if multiplier == F::ZERO {
+                continue;
+            }
             let value = entry.value().read_from_slice(&public_slice);
             let (row_index, col_index) = table_index(value);
             assert!(col_index < num_table_columns);
             assert!(row_index < num_rows);
-            multiplicities_trace.row_mut(row_index)[col_index] += 1;
+            multiplicities_trace.row_mut(row_index)[col_index] += multiplier;
         }
 
-        AirTrace::from_rows(
-            multiplicities_trace
-                .values
-                .into_par_iter()
-                .map(F::from_canonical_u32)
-                .collect(),
-            num_table_columns,
-        )
+        multiplicities_trace
     }
 
     pub fn write_lookup_multiplicities<const N: usize>(
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::writer::{AirWriter, TraceWriter};
+use crate::chip::builder::segment::Segment;
 use crate::chip::instruction::set::AirInstruction;
 use crate::chip::memory::pointer::accumulate::PointerAccumulator;
 use crate::chip::register::cubic::CubicRegister;
@@ -31,6 +32,7 @@ pub struct AirTraceData<L: AirParameters> {
         LookupTable<L::Field, L::CubicParams>,
         LookupValues<L::Field, L::CubicParams>,
     )>,
+    pub segments: Vec<Segment>,
 }
 
 impl<L: AirParameters> AirTraceData<L> {
@@ -41,6 +43,10 @@ impl<L: AirParameters> AirTraceData<L> {
         }
     }
 
+    /// Writes the global instructions, which compute public values from the public inputs.
+    ///
+    /// All the public inputs must be written before, including the values that the `write`
+    /// methods of the gadgets in `machine` write, and the public outputs can only be read after.
     #[inline]
     pub fn write_global_instructions(&self, writer: &mut impl AirWriter<Field = L::Field>) {
         for instruction in self.global_instructions.iter() {
@@ -145,6 +145,11 @@ impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator
 
                 self.air_data.write_extended_trace(&writer);
 
+                // Check the constraints on the full trace to report the failing instruction, if
+                // any.
+                #[cfg(feature = "debug-constraints")]
+                air.check_constraints(&writer)?;
+
                 let trace = self.trace_clone();
                 let extended_trace_values = trace
                     .rows_par()
@@ -0,0 +1,355 @@
+//! Checkpointing of long trace generations.
+//!
+//! The trace is filled chunk by chunk. After each chunk is written, the checkpoint records a
+//! digest of its contents. Every few chunks, the checkpoint is saved to disk together with the
+//! partially filled trace, the public values, and the memory state. After a crash, the
+//! computation can be resumed from the saved state, skipping all the chunks that were already
+//! completed.
+
+use core::ops::ControlFlow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize, Serialize};
+
+use super::data::{AirWriterChunkMut, AirWriterData};
+use crate::chip::trace::data::AirTraceData;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The progress of a trace generation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    num_rows: usize,
+    chunk_size: usize,
+    /// A digest of the public values once the global instructions have been written.
+    public_digest: Option<u64>,
+    /// A digest of each completed chunk.
+    chunk_digests: Vec<Option<u64>>,
+    /// The file to which the checkpoint is saved.
+    path: Option<PathBuf>,
+    /// The number of completed chunks between two saves.
+    save_interval: usize,
+}
+
+impl Checkpoint {
+    pub fn new(num_rows: usize, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+        assert_eq!(
+            num_rows % chunk_size,
+            0,
+            "number of rows must be a multiple of the chunk size"
+        );
+        Self {
+            num_rows,
+            chunk_size,
+            public_digest: None,
+            chunk_digests: vec![None; num_rows / chunk_size],
+            path: None,
+            save_interval: 1,
+        }
+    }
+
+    /// Saves the checkpoint to `path` every `save_interval` completed chunks.
+    pub fn with_path(mut self, path: impl Into<PathBuf>, save_interval: usize) -> Self {
+        assert!(save_interval > 0, "save interval must be positive");
+        self.path = Some(path.into());
+        self.save_interval = save_interval;
+        self
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn num_chunks(&self) -> usize {
+        self.chunk_digests.len()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn num_completed_chunks(&self) -> usize {
+        self.chunk_digests.iter().filter(|d| d.is_some()).count()
+    }
+
+    pub fn is_chunk_completed(&self, index: usize) -> bool {
+        self.chunk_digests[index].is_some()
+    }
+
+    /// Whether the global instructions and all the chunks of the trace have been written.
+    pub fn is_complete(&self) -> bool {
+        self.public_digest.is_some() && self.chunk_digests.iter().all(Option::is_some)
+    }
+
+    /// Checks that `writer_data` agrees with all the digests recorded by the checkpoint.
+    pub fn verify<F: PrimeField64>(&self, writer_data: &AirWriterData<F>) -> Result<()> {
+        ensure!(
+            writer_data.trace.height() == self.num_rows,
+            "checkpoint has {} rows but the trace has {}",
+            self.num_rows,
+            writer_data.trace.height()
+        );
+        if let Some(expected) = self.public_digest {
+            ensure!(
+                digest(&writer_data.public) == expected,
+                "checkpoint digest mismatch for the public values"
+            );
+        }
+        for (i, (chunk, expected)) in writer_data
+            .trace
+            .chunks(self.chunk_size)
+            .zip(self.chunk_digests.iter())
+            .enumerate()
+        {
+            if let Some(expected) = expected {
+                ensure!(
+                    digest(chunk.values) == *expected,
+                    "checkpoint digest mismatch for chunk {}",
+                    i
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the checkpoint together with the trace data to `path`.
+    ///
+    /// The data is first written to a temporary file which is then renamed, so that a crash
+    /// while saving never leaves a corrupted checkpoint behind.
+    pub fn save<F: PrimeField64>(
+        &self,
+        writer_data: &AirWriterData<F>,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = bincode::serialize(&(self, writer_data))
+            .map_err(|e| anyhow!("failed to serialize checkpoint: {}", e))?;
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Loads a checkpoint and its trace data from `path`, checking the recorded digests.
+    pub fn load<F: PrimeField64>(path: impl AsRef<Path>) -> Result<(Self, AirWriterData<F>)> {
+        let bytes = fs::read(path)?;
+        let (checkpoint, writer_data): (Self, AirWriterData<F>) = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow!("failed to deserialize checkpoint: {}", e))?;
+        checkpoint.verify(&writer_data)?;
+        Ok((checkpoint, writer_data))
+    }
+
+    fn save_if_set<F: PrimeField64>(&self, writer_data: &AirWriterData<F>) -> Result<()> {
+        match &self.path {
+            Some(path) => self.save(writer_data, path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes the global and trace instructions of `air_data`, resuming from `checkpoint`.
+///
+/// All the inputs to the trace are assumed to be written in `writer_data` beforehand.
+pub fn write_trace_instructions_resumable<L: AirParameters>(
+    air_data: &AirTraceData<L>,
+    writer_data: &mut AirWriterData<L::Field>,
+    checkpoint: &mut Checkpoint,
+) -> Result<()> {
+    write_trace_resumable(air_data, writer_data, checkpoint, |_| {
+        ControlFlow::Continue(())
+    })
+}
+
+/// Writes the global and trace instructions of `air_data`, resuming from `checkpoint`.
+///
+/// Before writing the instructions of a chunk that is not yet completed, `write_inputs` is called
+/// to fill in the inputs of that chunk. Returning `ControlFlow::Break` stops the generation. In
+/// that case, the chunks completed since the last save are not persisted.
+pub fn write_trace_resumable<L: AirParameters>(
+    air_data: &AirTraceData<L>,
+    writer_data: &mut AirWriterData<L::Field>,
+    checkpoint: &mut Checkpoint,
+    mut write_inputs: impl FnMut(&mut AirWriterChunkMut<'_, L::Field>) -> ControlFlow<()>,
+) -> Result<()> {
+    checkpoint.verify(writer_data)?;
+
+    // Write the global instructions.
+    if checkpoint.public_digest.is_none() {
+        air_data.write_global_instructions(&mut writer_data.public_writer());
+        checkpoint.public_digest = Some(digest(&writer_data.public));
+        checkpoint.save_if_set(writer_data)?;
+    }
+
+    // Write the trace instructions of all chunks that are not yet completed.
+    let chunk_size = checkpoint.chunk_size;
+    let mut num_written = 0;
+    for index in 0..checkpoint.num_chunks() {
+        if checkpoint.is_chunk_completed(index) {
+            continue;
+        }
+        let mut chunk = writer_data.chunk(index, chunk_size);
+        if write_inputs(&mut chunk).is_break() {
+            return Ok(());
+        }
+        for i in 0..chunk_size {
+            let mut writer = chunk.row_writer(i);
+            air_data.write_trace_instructions(&mut writer);
+        }
+        checkpoint.chunk_digests[index] = Some(digest(chunk.trace.values));
+
+        num_written += 1;
+        if num_written % checkpoint.save_interval == 0 {
+            checkpoint.save_if_set(writer_data)?;
+        }
+    }
+
+    if num_written % checkpoint.save_interval != 0 {
+        checkpoint.save_if_set(writer_data)?;
+    }
+    Ok(())
+}
+
+/// A 64-bit FNV-1a digest of a slice of field elements.
+fn digest<F: PrimeField64>(values: &[F]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    values
+        .iter()
+        .flat_map(|value| value.as_canonical_u64().to_le_bytes())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::Register;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::machine::builder::Builder;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CheckpointTest;
+
+    impl AirParameters for CheckpointTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 6;
+        const EXTENDED_COLUMNS: usize = 12;
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        type L = CheckpointTest;
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_checkpoint_resume", log::Level::Debug);
+
+        let mut builder = StarkBuilder::<L>::new();
+        let p = builder.alloc_public::<ElementRegister>();
+        let q = builder.public_expression::<ElementRegister>(p.expr() * p.expr());
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let _c = builder.expression::<ElementRegister>(a.expr() * b.expr() + q.expr());
+
+        let num_rows = 1 << 10;
+        let chunk_size = 1 << 6;
+        let stark = builder.build::<C, 2>(num_rows);
+        let air_data = &stark.air_data;
+
+        let new_writer_data = || {
+            let mut writer_data = AirWriterData::new(air_data, num_rows);
+            writer_data
+                .public_writer()
+                .write(&p, &F::from_canonical_u64(5));
+            writer_data
+        };
+        let write_chunk_inputs = |chunk: &mut AirWriterChunkMut<'_, F>| {
+            for i in 0..chunk_size {
+                let mut writer = chunk.row_writer(i);
+                let row = writer.row_index().unwrap();
+                writer.write(&a, &F::from_canonical_usize(row + 1));
+                writer.write(&b, &F::from_canonical_usize(3 * row + 7));
+            }
+        };
+
+        // An uninterrupted run.
+        let mut writer_data = new_writer_data();
+        let mut checkpoint = Checkpoint::new(num_rows, chunk_size);
+        write_trace_resumable(air_data, &mut writer_data, &mut checkpoint, |chunk| {
+            write_chunk_inputs(chunk);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(checkpoint.is_complete());
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        // A run that stops after five chunks, with a save every two chunks.
+        let path = std::env::temp_dir().join(format!(
+            "curta_test_checkpoint_resume_{}.bin",
+            std::process::id()
+        ));
+        let mut writer_data = new_writer_data();
+        let mut checkpoint = Checkpoint::new(num_rows, chunk_size).with_path(&path, 2);
+        let mut num_chunks = 0;
+        write_trace_resumable(air_data, &mut writer_data, &mut checkpoint, |chunk| {
+            if num_chunks == 5 {
+                return ControlFlow::Break(());
+            }
+            num_chunks += 1;
+            write_chunk_inputs(chunk);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(!checkpoint.is_complete());
+        drop(writer_data);
+
+        // Tampering with a completed chunk is detected when loading.
+        let (saved_checkpoint, mut saved_data) = Checkpoint::load::<F>(&path).unwrap();
+        assert_eq!(saved_checkpoint.num_completed_chunks(), 4);
+        saved_data.trace.row_mut(3)[0] += F::ONE;
+        assert!(saved_checkpoint.verify(&saved_data).is_err());
+
+        // Resume from the saved state.
+        let (mut checkpoint, mut writer_data) = Checkpoint::load::<F>(&path).unwrap();
+        write_trace_resumable(air_data, &mut writer_data, &mut checkpoint, |chunk| {
+            write_chunk_inputs(chunk);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(checkpoint.is_complete());
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(writer_data.trace.values, trace.values);
+        assert_eq!(writer_data.public, public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        let resumed_proof = stark
+            .prove(&writer_data.trace, &writer_data.public, &mut timing)
+            .unwrap();
+        assert_eq!(proof, resumed_proof);
+        stark.verify(resumed_proof, &writer_data.public).unwrap();
+
+        timing.print();
+    }
+}
@@ -1,6 +1,7 @@
 use core::hash::Hash;
 
 use plonky2_maybe_rayon::{IndexedParallelIterator, MaybeIntoParIter, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
 use super::public::PublicWriter;
 use super::row::RowWriter;
@@ -12,7 +13,7 @@ use crate::math::field::Field;
 use crate::trace::view::TraceViewMut;
 use crate::trace::AirTrace;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AirWriterData<T: PartialEq + Eq + Hash> {
     pub trace: AirTrace<T>,
     pub public: Vec<T>,
@@ -83,6 +84,28 @@ impl<T: PartialEq + Eq + Hash> AirWriterData<T> {
             })
     }
 
+    /// The chunk of index `index` when the trace is split into chunks of size `chunk_size`.
+    #[inline]
+    pub fn chunk(&mut self, index: usize, chunk_size: usize) -> AirWriterChunkMut<'_, T>
+    where
+        T: Clone,
+    {
+        let height = self.trace.height();
+        assert_eq!(height % chunk_size, 0);
+        let trace = self
+            .trace
+            .chunks_mut(chunk_size)
+            .nth(index)
+            .expect("chunk index out of bounds");
+        AirWriterChunkMut {
+            trace,
+            public: &self.public,
+            memory: self.memory.clone(),
+            height,
+            initial_row: index * chunk_size,
+        }
+    }
+
     #[inline]
     pub fn chunks_par(
         &mut self,
@@ -22,6 +22,7 @@ use crate::trace::window::TraceWindow;
 use crate::trace::window_parser::TraceWindowParser;
 use crate::trace::AirTrace;
 
+pub mod checkpoint;
 pub mod data;
 pub mod public;
 pub mod row;
@@ -0,0 +1,305 @@
+//! Counting the set bits, leading zeros and trailing zeros of bytes with a lookup table.
+//!
+//! The table has an entry for every byte `x`, whose digest packs the byte with `popcount(x)`,
+//! `clz(x)`, `ctz(x)` and whether `x` is zero,
+//! `x + 2^8 * popcount(x) + 2^12 * clz(x) + 2^16 * ctz(x) + 2^20 * (x == 0)`.
+//! The table rows are constrained from the bit decomposition of `x`. A lookup holds the counts as
+//! bits, so the packing of its digest is unique and the digest binds the counts to the byte.
+
+use core::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::register::ByteRegister;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::table::log_derivative::entry::LogEntry;
+use crate::chip::table::lookup::table::LogLookupTable;
+use crate::chip::table::lookup::values::LogLookupValues;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::trace::AirTrace;
+
+/// The number of bits holding the counts of a byte in a `ByteCountInstruction`.
+const NUM_COUNT_BITS: usize = 13;
+
+/// The counts of a byte, as looked up in a `ByteCountTable`.
+///
+/// The counts are held as the little-endian bits of
+/// `popcount + 2^4 * clz + 2^8 * ctz + 2^12 * (byte == 0)`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ByteCountInstruction {
+    pub byte: ByteRegister,
+    counts: ArrayRegister<BitRegister>,
+    digest: ElementRegister,
+}
+
+/// A lookup table of the counts of all bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteCountTable<F, E> {
+    pub byte: ElementRegister,
+    multiplicities: ArrayRegister<ElementRegister>,
+    pub lookup: LogLookupTable<ElementRegister, F, E>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ByteCountOperations {
+    pub values: Vec<LogEntry<ElementRegister>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteCountMultiplicityData {
+    trace_values: Vec<LogEntry<ElementRegister>>,
+    public_values: Vec<LogEntry<ElementRegister>>,
+}
+
+impl ByteCountOperations {
+    pub fn new() -> Self {
+        ByteCountOperations { values: Vec::new() }
+    }
+}
+
+impl Default for ByteCountOperations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The digest of the counts of a byte.
+fn count_digest_expression<F: Field>(
+    byte: ArithmeticExpression<F>,
+    popcount: ArithmeticExpression<F>,
+    leading_zeros: ArithmeticExpression<F>,
+    trailing_zeros: ArithmeticExpression<F>,
+    is_zero: ArithmeticExpression<F>,
+) -> ArithmeticExpression<F> {
+    byte + popcount * F::from_canonical_u32(1 << 8)
+        + leading_zeros * F::from_canonical_u32(1 << 12)
+        + trailing_zeros * F::from_canonical_u32(1 << 16)
+        + is_zero * F::from_canonical_u32(1 << 20)
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    pub fn new_byte_count_table(&mut self) -> ByteCountTable<L::Field, L::CubicParams> {
+        let byte = self.alloc::<ElementRegister>();
+        let bits = self.alloc_array::<BitRegister>(8);
+        self.register_air_instruction_internal(AirInstruction::bit_decomposition(
+            byte.expr(),
+            bits,
+        ));
+
+        // `leading[i]` is one if the bits `i..8` are all zero, and `trailing[i]` is one if the
+        // bits `0..=i` are all zero.
+        let leading = self.alloc_array::<BitRegister>(8);
+        let trailing = self.alloc_array::<BitRegister>(8);
+        self.set_to_expression(&leading.get(7), bits.get(7).not_expr());
+        self.set_to_expression(&trailing.get(0), bits.get(0).not_expr());
+        for i in 1..8 {
+            let j = 7 - i;
+            self.set_to_expression(
+                &leading.get(j),
+                leading.get(j + 1).expr() * bits.get(j).not_expr(),
+            );
+            self.set_to_expression(
+                &trailing.get(i),
+                trailing.get(i - 1).expr() * bits.get(i).not_expr(),
+            );
+        }
+
+        let sum = |array: ArrayRegister<BitRegister>| {
+            array
+                .iter()
+                .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr())
+        };
+        let digest = self.alloc::<ElementRegister>();
+        self.set_to_expression(
+            &digest,
+            count_digest_expression(
+                byte.expr(),
+                sum(bits),
+                sum(leading),
+                sum(trailing),
+                leading.get(0).expr(),
+            ),
+        );
+
+        let multiplicities = self.alloc_array::<ElementRegister>(1);
+        let lookup = self.new_lookup(&[digest], &multiplicities);
+
+        ByteCountTable {
+            byte,
+            multiplicities,
+            lookup,
+        }
+    }
+
+    pub fn byte_count_operations(&mut self) -> ByteCountOperations {
+        ByteCountOperations::new()
+    }
+
+    /// Looks up the counts of `byte` in the count table.
+    pub fn byte_count(
+        &mut self,
+        byte: &ByteRegister,
+        operations: &mut ByteCountOperations,
+    ) -> ByteCountInstruction
+    where
+        L::Instruction: From<ByteCountInstruction>,
+    {
+        let instruction = ByteCountInstruction {
+            byte: *byte,
+            counts: self.alloc_array::<BitRegister>(NUM_COUNT_BITS),
+            digest: self.alloc::<ElementRegister>(),
+        };
+        operations
+            .values
+            .push(self.segment_lookup_entry(instruction.digest));
+        self.register_instruction(instruction);
+        instruction
+    }
+
+    /// Registers the byte counts in `operations` with the count table.
+    pub fn register_byte_count_lookup(
+        &mut self,
+        table: &mut ByteCountTable<L::Field, L::CubicParams>,
+        operations: ByteCountOperations,
+    ) -> ByteCountMultiplicityData {
+        let LogLookupValues {
+            trace_values,
+            public_values,
+            ..
+        } = table
+            .lookup
+            .register_lookup_entries(self, &operations.values);
+
+        ByteCountMultiplicityData {
+            trace_values,
+            public_values,
+        }
+    }
+
+    pub fn constraint_byte_count_table(
+        &mut self,
+        table: &ByteCountTable<L::Field, L::CubicParams>,
+    ) {
+        self.constrain_element_lookup_table(table.lookup.clone())
+    }
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>> ByteCountTable<F, E> {
+    pub fn multiplicities(&self) -> ArrayRegister<ElementRegister> {
+        self.multiplicities
+    }
+
+    /// Writes the byte `i mod 256` to every row `i`, from which the rest of the row is computed
+    /// by the table instructions.
+    pub fn write_table_entries(&self, writer: &TraceWriter<F>) {
+        let num_rows = writer.read_trace().unwrap().height();
+        for i in 0..num_rows {
+            writer.write(&self.byte, &F::from_canonical_usize(i % 256), i);
+        }
+    }
+}
+
+impl ByteCountMultiplicityData {
+    /// Counts the lookups of every byte, all recorded in the first 256 rows of the table.
+    pub fn get_multiplicities<F: PrimeField64>(&self, writer: &TraceWriter<F>) -> AirTrace<F> {
+        let num_rows = writer.read_trace().unwrap().height();
+        writer.get_multiplicities_from_fn(
+            1,
+            num_rows,
+            &self.trace_values,
+            &self.public_values,
+            |digest| ((digest.as_canonical_u64() & 0xFF) as usize, 0),
+        )
+    }
+}
+
+impl ByteCountInstruction {
+    /// The number of set bits of the byte.
+    pub fn popcount<F: Field>(&self) -> ArithmeticExpression<F> {
+        self.count_expression(0..4)
+    }
+
+    /// The number of leading zeros of the byte, which is 8 for the zero byte.
+    pub fn leading_zeros<F: Field>(&self) -> ArithmeticExpression<F> {
+        self.count_expression(4..8)
+    }
+
+    /// The number of trailing zeros of the byte, which is 8 for the zero byte.
+    pub fn trailing_zeros<F: Field>(&self) -> ArithmeticExpression<F> {
+        self.count_expression(8..12)
+    }
+
+    /// The bit of whether the byte is zero.
+    pub fn is_zero(&self) -> BitRegister {
+        self.counts.get(12)
+    }
+
+    fn count_expression<F: Field>(&self, range: Range<usize>) -> ArithmeticExpression<F> {
+        self.counts
+            .get_subarray(range)
+            .iter()
+            .enumerate()
+            .fold(ArithmeticExpression::zero(), |acc, (j, bit)| {
+                acc + bit.expr() * F::from_canonical_u32(1 << j)
+            })
+    }
+
+    fn digest_expression<F: Field>(&self) -> ArithmeticExpression<F> {
+        count_digest_expression(
+            self.byte.expr(),
+            self.popcount(),
+            self.leading_zeros(),
+            self.trailing_zeros(),
+            self.is_zero().expr(),
+        )
+    }
+
+    /// The packed counts of `byte`.
+    fn packed_counts(byte: u8) -> u32 {
+        byte.count_ones()
+            | byte.leading_zeros() << 4
+            | byte.trailing_zeros() << 8
+            | ((byte == 0) as u32) << 12
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for ByteCountInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let digest = self.digest_expression().eval(parser)[0];
+        let expected = self.digest.eval(parser);
+        parser.assert_eq(digest, expected);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for ByteCountInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let byte = writer.read(&self.byte, row_index).as_canonical_u64() as u8;
+        let counts = Self::packed_counts(byte);
+        for (j, bit) in self.counts.iter().enumerate() {
+            writer.write(&bit, &F::from_canonical_u32((counts >> j) & 1), row_index);
+        }
+        let digest = F::from_canonical_u32(byte as u32 + (counts << 8));
+        writer.write(&self.digest, &digest, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let byte = writer.read(&self.byte).as_canonical_u64() as u8;
+        let counts = Self::packed_counts(byte);
+        for (j, bit) in self.counts.iter().enumerate() {
+            writer.write(&bit, &F::from_canonical_u32((counts >> j) & 1));
+        }
+        let digest = F::from_canonical_u32(byte as u32 + (counts << 8));
+        writer.write(&self.digest, &digest);
+    }
+}
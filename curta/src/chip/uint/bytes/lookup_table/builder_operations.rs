@@ -1,8 +1,9 @@
 use crate::chip::register::element::ElementRegister;
+use crate::chip::table::log_derivative::entry::LogEntry;
 
 #[derive(Debug, Clone)]
 pub struct ByteLookupOperations {
-    pub values: Vec<ElementRegister>,
+    pub values: Vec<LogEntry<ElementRegister>>,
 }
 
 impl ByteLookupOperations {
@@ -8,7 +8,7 @@ use super::bit_operations::not::Not;
 use super::bit_operations::xor::Xor;
 use super::decode::ByteDecodeInstruction;
 use super::operations::instruction::ByteOperationInstruction;
-use super::operations::value::ByteOperationDigestConstraint;
+use super::operations::value::{ByteOperation, ByteOperationDigestConstraint};
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
 use crate::chip::builder::AirBuilder;
@@ -51,14 +51,24 @@ impl<L: AirParameters> AirBuilder<L> {
         ByteLookupOperations::new()
     }
 
+    /// Registers the byte operations in `operations` with the lookup table.
+    ///
+    /// All public bytes allocated so far are range checked as part of the lookup.
     pub fn register_byte_lookup(
         &mut self,
         table: &mut ByteLogLookupTable<L::Field, L::CubicParams>,
-        operations: ByteLookupOperations,
-    ) -> ByteMultiplicityData {
+        mut operations: ByteLookupOperations,
+    ) -> ByteMultiplicityData
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        for byte in core::mem::take(&mut self.public_bytes) {
+            self.set_public_inputs_byte_operation(&ByteOperation::Range(byte), &mut operations);
+        }
+
         let lookup_values = table
             .lookup
-            .register_lookup_values(self, &operations.values);
+            .register_lookup_entries(self, &operations.values);
 
         let LogLookupValues {
             trace_values,
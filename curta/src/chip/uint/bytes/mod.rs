@@ -1,4 +1,5 @@
 pub mod bit_operations;
+pub mod count;
 pub mod decode;
 pub mod lookup_table;
 pub mod operations;
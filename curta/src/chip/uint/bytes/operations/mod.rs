@@ -6,6 +6,7 @@ use super::lookup_table::builder_operations::ByteLookupOperations;
 use super::register::ByteRegister;
 use crate::chip::builder::AirBuilder;
 use crate::chip::register::element::ElementRegister;
+use crate::chip::table::log_derivative::entry::LogEntry;
 use crate::chip::AirParameters;
 
 pub mod instruction;
@@ -40,7 +41,7 @@ impl<L: AirParameters> AirBuilder<L> {
         let digest = self.alloc::<ElementRegister>();
 
         let instr = ByteOperationInstruction::new(*op, digest, false);
-        lookup_values.values.push(digest);
+        lookup_values.values.push(self.segment_lookup_entry(digest));
         self.register_instruction(instr);
     }
 
@@ -56,7 +57,7 @@ impl<L: AirParameters> AirBuilder<L> {
         let digest = self.alloc_public::<ElementRegister>();
 
         let instr = ByteOperationInstruction::new(*op, digest, true);
-        lookup_values.values.push(digest);
+        lookup_values.values.push(LogEntry::input(digest));
         self.register_global_instruction(instr);
     }
 }
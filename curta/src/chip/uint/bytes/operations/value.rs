@@ -451,57 +451,66 @@ impl ByteOperation<u8> {
 }
 
 impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates public registers for an operation of the same kind as `op`.
+    ///
+    /// The registers are not range checked on allocation since the operation lookup already
+    /// constrains them to be bytes.
     pub fn alloc_public_byte_operation_from_template<T>(
         &mut self,
         op: &ByteOperation<T>,
     ) -> ByteOperation<ByteRegister> {
         match op {
             ByteOperation::And(_, _, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let b = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let b = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::And(a, b, result)
             }
             ByteOperation::Xor(_, _, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let b = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let b = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::Xor(a, b, result)
             }
             ByteOperation::Shr(_, _, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let b = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let b = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::Shr(a, b, result)
             }
             ByteOperation::ShrConst(_, b, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::ShrConst(a, *b, result)
             }
             ByteOperation::ShrCarry(_, b, _, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
-                ByteOperation::ShrCarry(a, *b, result, self.alloc_public::<ByteRegister>())
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
+                ByteOperation::ShrCarry(
+                    a,
+                    *b,
+                    result,
+                    self.alloc_public_unchecked::<ByteRegister>(),
+                )
             }
             ByteOperation::Rot(_, _, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let b = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let b = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::Rot(a, b, result)
             }
             ByteOperation::RotConst(_, b, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::RotConst(a, *b, result)
             }
             ByteOperation::Not(_, _) => {
-                let a = self.alloc_public::<ByteRegister>();
-                let result = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
+                let result = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::Not(a, result)
             }
             ByteOperation::Range(_) => {
-                let a = self.alloc_public::<ByteRegister>();
+                let a = self.alloc_public_unchecked::<ByteRegister>();
                 ByteOperation::Range(a)
             }
         }
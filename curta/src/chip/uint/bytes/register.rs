@@ -15,7 +15,7 @@ impl ByteRegister {
 }
 
 impl RegisterSerializable for ByteRegister {
-    const CELL: CellType = CellType::Element;
+    const CELL: CellType = CellType::Byte;
 
     fn register(&self) -> &MemorySlice {
         &self.0
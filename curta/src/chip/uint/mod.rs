@@ -1,4 +1,5 @@
 pub mod bytes;
 pub mod operations;
+pub mod packing;
 pub mod register;
 pub mod util;
@@ -10,7 +10,9 @@ use crate::chip::trace::writer::{AirWriter, TraceWriter};
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
 use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
 use crate::chip::uint::bytes::operations::value::ByteOperation;
-use crate::chip::uint::register::{ByteArrayRegister, U32Register, U64Register};
+use crate::chip::uint::register::{
+    ByteArrayRegister, U128Register, U256Register, U32Register, U64Register,
+};
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
 
@@ -161,6 +163,130 @@ impl<L: AirParameters> AirBuilder<L> {
         let (result, _) = self.carrying_add_u64(a, b, &None, operations);
         result
     }
+
+    pub fn set_add_u128(
+        &mut self,
+        a: &U128Register,
+        b: &U128Register,
+        in_carry: &Option<BitRegister>,
+        result: &U128Register,
+        out_carry: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        self.set_add_u32_limbs(a, b, in_carry, result, out_carry, operations);
+    }
+
+    pub fn carrying_add_u128(
+        &mut self,
+        a: &U128Register,
+        b: &U128Register,
+        in_carry: &Option<BitRegister>,
+        operations: &mut ByteLookupOperations,
+    ) -> (U128Register, BitRegister)
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<U128Register>();
+        let out_carry = self.alloc::<BitRegister>();
+        self.set_add_u128(a, b, in_carry, &result, &out_carry, operations);
+
+        (result, out_carry)
+    }
+
+    pub fn add_u128(
+        &mut self,
+        a: &U128Register,
+        b: &U128Register,
+        operations: &mut ByteLookupOperations,
+    ) -> U128Register
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let (result, _) = self.carrying_add_u128(a, b, &None, operations);
+        result
+    }
+
+    pub fn set_add_u256(
+        &mut self,
+        a: &U256Register,
+        b: &U256Register,
+        in_carry: &Option<BitRegister>,
+        result: &U256Register,
+        out_carry: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        self.set_add_u32_limbs(a, b, in_carry, result, out_carry, operations);
+    }
+
+    pub fn carrying_add_u256(
+        &mut self,
+        a: &U256Register,
+        b: &U256Register,
+        in_carry: &Option<BitRegister>,
+        operations: &mut ByteLookupOperations,
+    ) -> (U256Register, BitRegister)
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<U256Register>();
+        let out_carry = self.alloc::<BitRegister>();
+        self.set_add_u256(a, b, in_carry, &result, &out_carry, operations);
+
+        (result, out_carry)
+    }
+
+    pub fn add_u256(
+        &mut self,
+        a: &U256Register,
+        b: &U256Register,
+        operations: &mut ByteLookupOperations,
+    ) -> U256Register
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let (result, _) = self.carrying_add_u256(a, b, &None, operations);
+        result
+    }
+
+    /// Adds `a` and `b` as a chain of additions of their `u32` limbs, from the least significant.
+    pub(crate) fn set_add_u32_limbs<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        in_carry: &Option<BitRegister>,
+        result: &ByteArrayRegister<N>,
+        out_carry: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let a_as_register = a.to_le_limbs::<4>();
+        let b_as_register = b.to_le_limbs::<4>();
+        let result_as_register = result.to_le_limbs::<4>();
+
+        let num_limbs = N / 4;
+        let mut carry = *in_carry;
+        for i in 0..num_limbs {
+            let limb_carry = if i == num_limbs - 1 {
+                *out_carry
+            } else {
+                self.alloc::<BitRegister>()
+            };
+            self.set_add_u32(
+                &a_as_register.get(i),
+                &b_as_register.get(i),
+                &carry,
+                &result_as_register.get(i),
+                &limb_carry,
+                operations,
+            );
+            carry = Some(limb_carry);
+        }
+    }
 }
 
 impl<AP: AirParser, const N: usize> AirConstraint<AP> for ByteArrayAdd<N> {
@@ -0,0 +1,85 @@
+use super::sub::ByteArraySub;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::register::ByteArrayRegister;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the bit of `a < b` as integers, which is the borrow of `a - b`.
+    ///
+    /// The subtraction is done on the `u32` limbs of the arrays, so `N` must be a multiple of 4.
+    pub fn uint_lt<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let (_, borrow) = self.borrowing_sub(a, b, &None, operations);
+        borrow
+    }
+
+    /// Computes the bit of `a <= b` as integers, which is the negation of `b < a`.
+    pub fn uint_lte<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let b_lt_a = self.uint_lt(b, a, operations);
+        let result = self.alloc::<BitRegister>();
+        self.set_to_expression(&result, b_lt_a.not_expr());
+        result
+    }
+
+    /// Computes the bit of `a > b` as integers, which is `b < a`.
+    pub fn uint_gt<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        self.uint_lt(b, a, operations)
+    }
+
+    /// Computes the bit of `a >= b` as integers, which is `b <= a`.
+    pub fn uint_gte<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        self.uint_lte(b, a, operations)
+    }
+
+    /// Computes the bit of `a == b`, which holds if neither `a < b` nor `b < a`.
+    pub fn uint_eq<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let a_lt_b = self.uint_lt(a, b, operations);
+        let b_lt_a = self.uint_lt(b, a, operations);
+        let result = self.alloc::<BitRegister>();
+        self.set_to_expression(&result, a_lt_b.not_expr() - b_lt_a.expr());
+        result
+    }
+}
@@ -0,0 +1,101 @@
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::count::{ByteCountInstruction, ByteCountOperations};
+use crate::chip::uint::register::ByteArrayRegister;
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the number of set bits of `a`.
+    pub fn uint_popcount<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        operations: &mut ByteCountOperations,
+    ) -> ElementRegister
+    where
+        L::Instruction: From<ByteCountInstruction>,
+    {
+        let counts = self.uint_byte_counts(a, operations);
+        let result = self.alloc::<ElementRegister>();
+        self.set_to_expression(
+            &result,
+            counts
+                .iter()
+                .fold(ArithmeticExpression::zero(), |acc, count| {
+                    acc + count.popcount()
+                }),
+        );
+        result
+    }
+
+    /// Computes the number of leading zeros of `a`, which is `8 * N` if `a` is zero.
+    ///
+    /// The number of leading zeros of `!a` gives the number of leading ones.
+    pub fn uint_leading_zeros<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        operations: &mut ByteCountOperations,
+    ) -> ElementRegister
+    where
+        L::Instruction: From<ByteCountInstruction>,
+    {
+        let counts = self.uint_byte_counts(a, operations);
+        let from_most_significant = counts.into_iter().rev().collect::<Vec<_>>();
+        self.zero_run_count(&from_most_significant, ByteCountInstruction::leading_zeros)
+    }
+
+    /// Computes the number of trailing zeros of `a`, which is `8 * N` if `a` is zero.
+    pub fn uint_trailing_zeros<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        operations: &mut ByteCountOperations,
+    ) -> ElementRegister
+    where
+        L::Instruction: From<ByteCountInstruction>,
+    {
+        let counts = self.uint_byte_counts(a, operations);
+        self.zero_run_count(&counts, ByteCountInstruction::trailing_zeros)
+    }
+
+    fn uint_byte_counts<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        operations: &mut ByteCountOperations,
+    ) -> Vec<ByteCountInstruction>
+    where
+        L::Instruction: From<ByteCountInstruction>,
+    {
+        a.to_le_bytes()
+            .iter()
+            .map(|byte| self.byte_count(&byte, operations))
+            .collect()
+    }
+
+    /// Sums the zeros counted by `count` over `counts`, up to the first nonzero byte.
+    ///
+    /// The count of each byte is added only if all the bytes before it in `counts` are zero.
+    fn zero_run_count(
+        &mut self,
+        counts: &[ByteCountInstruction],
+        count: fn(&ByteCountInstruction) -> ArithmeticExpression<L::Field>,
+    ) -> ElementRegister {
+        let mut sum = count(&counts[0]);
+        let mut all_zero = counts[0].is_zero();
+        for (i, byte_count) in counts.iter().enumerate().skip(1) {
+            sum = sum + all_zero.expr() * count(byte_count);
+            if i < counts.len() - 1 {
+                let next_all_zero = self.alloc::<BitRegister>();
+                self.set_to_expression(
+                    &next_all_zero,
+                    all_zero.expr() * byte_count.is_zero().expr(),
+                );
+                all_zero = next_all_zero;
+            }
+        }
+        let result = self.alloc::<ElementRegister>();
+        self.set_to_expression(&result, sum);
+        result
+    }
+}
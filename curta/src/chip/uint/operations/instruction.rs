@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
 use super::add::ByteArrayAdd;
+use super::mul::ByteArrayMul;
+use super::native::U32NativeInstruction;
+use super::sub::ByteArraySub;
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
 use crate::chip::instruction::Instruction;
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::count::ByteCountInstruction;
 use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
 use crate::chip::uint::bytes::lookup_table::{ByteInstructionSet, ByteInstructions};
 use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
@@ -15,10 +19,20 @@ use crate::math::prelude::*;
 pub enum UintInstruction {
     Bit(ByteInstructionSet),
     Add(ByteArrayAdd<4>),
+    Sub(ByteArraySub<4>),
+    Mul(ByteArrayMul),
+    Native(U32NativeInstruction),
+    Count(ByteCountInstruction),
 }
 
 pub trait UintInstructions:
-    ByteInstructions + From<UintInstruction> + From<ByteArrayAdd<4>>
+    ByteInstructions
+    + From<UintInstruction>
+    + From<ByteArrayAdd<4>>
+    + From<ByteArraySub<4>>
+    + From<ByteArrayMul>
+    + From<U32NativeInstruction>
+    + From<ByteCountInstruction>
 {
 }
 
@@ -31,6 +45,10 @@ impl<AP: AirParser> AirConstraint<AP> for UintInstruction {
         match self {
             Self::Bit(op) => op.eval(parser),
             Self::Add(op) => op.eval(parser),
+            Self::Sub(op) => op.eval(parser),
+            Self::Mul(op) => op.eval(parser),
+            Self::Native(op) => op.eval(parser),
+            Self::Count(op) => op.eval(parser),
         }
     }
 }
@@ -40,6 +58,10 @@ impl<F: PrimeField64> Instruction<F> for UintInstruction {
         match self {
             Self::Bit(op) => Instruction::<F>::write(op, writer, row_index),
             Self::Add(op) => Instruction::<F>::write(op, writer, row_index),
+            Self::Sub(op) => Instruction::<F>::write(op, writer, row_index),
+            Self::Mul(op) => Instruction::<F>::write(op, writer, row_index),
+            Self::Native(op) => Instruction::<F>::write(op, writer, row_index),
+            Self::Count(op) => Instruction::<F>::write(op, writer, row_index),
         }
     }
 
@@ -47,6 +69,10 @@ impl<F: PrimeField64> Instruction<F> for UintInstruction {
         match self {
             Self::Bit(op) => Instruction::<F>::write_to_air(op, writer),
             Self::Add(op) => Instruction::<F>::write_to_air(op, writer),
+            Self::Sub(op) => Instruction::<F>::write_to_air(op, writer),
+            Self::Mul(op) => Instruction::<F>::write_to_air(op, writer),
+            Self::Native(op) => Instruction::<F>::write_to_air(op, writer),
+            Self::Count(op) => Instruction::<F>::write_to_air(op, writer),
         }
     }
 }
@@ -63,6 +89,30 @@ impl From<ByteArrayAdd<4>> for UintInstruction {
     }
 }
 
+impl From<ByteArraySub<4>> for UintInstruction {
+    fn from(op: ByteArraySub<4>) -> Self {
+        Self::Sub(op)
+    }
+}
+
+impl From<ByteArrayMul> for UintInstruction {
+    fn from(op: ByteArrayMul) -> Self {
+        Self::Mul(op)
+    }
+}
+
+impl From<U32NativeInstruction> for UintInstruction {
+    fn from(op: U32NativeInstruction) -> Self {
+        Self::Native(op)
+    }
+}
+
+impl From<ByteCountInstruction> for UintInstruction {
+    fn from(op: ByteCountInstruction) -> Self {
+        Self::Count(op)
+    }
+}
+
 impl From<ByteOperationInstruction> for UintInstruction {
     fn from(op: ByteOperationInstruction) -> Self {
         Self::Bit(op.into())
@@ -83,13 +133,19 @@ impl From<ByteOperationDigestConstraint> for UintInstruction {
 
 #[cfg(test)]
 mod tests {
+    use num::bigint::RandBigInt;
+    use num::BigUint;
     use rand::{thread_rng, Rng};
 
     use super::*;
     pub use crate::chip::builder::tests::*;
     use crate::chip::builder::AirBuilder;
     use crate::chip::register::bit::BitRegister;
-    use crate::chip::uint::register::ByteArrayRegister;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::uint::register::{
+        ByteArrayRegister, U128Register, U160Register, U256Register, U32Register, U64Register,
+    };
+    use crate::chip::uint::util::{u32_to_le_field_bytes, u64_to_le_field_bytes};
     use crate::chip::AirParameters;
     use crate::math::field::Field;
 
@@ -107,6 +163,132 @@ mod tests {
         const NUM_ARITHMETIC_COLUMNS: usize = 0;
     }
 
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct U128OpTest;
+
+    impl AirParameters for U128OpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1700;
+        const EXTENDED_COLUMNS: usize = 1000;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CompareTest;
+
+    impl AirParameters for CompareTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 600;
+        const EXTENDED_COLUMNS: usize = 400;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct SignedOpTest;
+
+    impl AirParameters for SignedOpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1200;
+        const EXTENDED_COLUMNS: usize = 500;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct VarShiftTest;
+
+    impl AirParameters for VarShiftTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1400;
+        const EXTENDED_COLUMNS: usize = 450;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct BswapTest;
+
+    impl AirParameters for BswapTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 200;
+        const EXTENDED_COLUMNS: usize = 100;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct CountTest;
+
+    impl AirParameters for CountTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 700;
+        const EXTENDED_COLUMNS: usize = 200;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct WideMulTest;
+
+    impl AirParameters for WideMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 500;
+        const EXTENDED_COLUMNS: usize = 300;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct U160OpTest;
+
+    impl AirParameters for U160OpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 300;
+        const EXTENDED_COLUMNS: usize = 200;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct U256OpTest;
+
+    impl AirParameters for U256OpTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1500;
+        const EXTENDED_COLUMNS: usize = 1000;
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     struct U64OpTest;
 
@@ -366,4 +548,977 @@ mod tests {
         // Test the recursive proof.
         test_recursive_starky(stark, config, generator, &[]);
     }
+
+    #[test]
+    fn test_u128_operations() {
+        type F = GoldilocksField;
+        type L = U128OpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a = builder.alloc::<U128Register>();
+        let b = builder.alloc::<U128Register>();
+
+        let (a_plus_b, carry) = builder.carrying_add_u128(&a, &b, &None, &mut operations);
+        let add_expected = builder.alloc::<U128Register>();
+        builder.assert_equal(&a_plus_b, &add_expected);
+        let carry_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&carry, &carry_expected);
+
+        let (a_minus_b, borrow) = builder.borrowing_sub(&a, &b, &None, &mut operations);
+        let sub_expected = builder.alloc::<U128Register>();
+        builder.assert_equal(&a_minus_b, &sub_expected);
+        let borrow_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&borrow, &borrow_expected);
+
+        let a_mul_b = builder.wrapping_mul(&a, &b, &mut operations);
+        let mul_expected = builder.alloc::<U128Register>();
+        builder.assert_equal(&a_mul_b, &mul_expected);
+
+        let mut rng = thread_rng();
+
+        let mut shift_vals = vec![];
+        let mut shr_expected_vec = vec![];
+        let mut rot_expected_vec = vec![];
+
+        let num_ops = 4;
+
+        for i in 0..num_ops {
+            let shift = if i == 0 {
+                64usize
+            } else {
+                rng.gen::<u64>() as usize
+            };
+            shift_vals.push(shift);
+
+            let a_shr = builder.alloc::<U128Register>();
+            builder.set_bit_shr(&a, shift, &a_shr, &mut operations);
+            let shr_expected = builder.alloc::<U128Register>();
+            builder.assert_equal(&a_shr, &shr_expected);
+            let a_shr_second = builder.alloc::<U128Register>(); // To guarantee even number of operations
+            builder.set_bit_shr(&a, shift, &a_shr_second, &mut operations);
+            shr_expected_vec.push(shr_expected);
+
+            let a_rot = builder.alloc::<U128Register>();
+            builder.set_bit_rotate_right(&a, shift, &a_rot, &mut operations);
+            let rot_expected = builder.alloc::<U128Register>();
+            builder.assert_equal(&a_rot, &rot_expected);
+            rot_expected_vec.push(rot_expected);
+        }
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        let to_field = |a: u128| a.to_le_bytes().map(F::from_canonical_u8);
+
+        byte_table.write_table_entries(&writer);
+        for i in 0..num_rows {
+            // Make sure the wraparound at 2^128 is covered.
+            let (a_val, b_val) = match i {
+                0 => (u128::MAX, u128::MAX),
+                1 => (0, 1),
+                _ => (rng.gen::<u128>(), rng.gen::<u128>()),
+            };
+            writer.write(&a, &to_field(a_val), i);
+            writer.write(&b, &to_field(b_val), i);
+
+            let (add_val, carry_val) = a_val.overflowing_add(b_val);
+            writer.write(&add_expected, &to_field(add_val), i);
+            writer.write(&carry_expected, &F::from_canonical_u8(carry_val as u8), i);
+
+            let (sub_val, borrow_val) = a_val.overflowing_sub(b_val);
+            writer.write(&sub_expected, &to_field(sub_val), i);
+            writer.write(&borrow_expected, &F::from_canonical_u8(borrow_val as u8), i);
+
+            writer.write(&mul_expected, &to_field(a_val.wrapping_mul(b_val)), i);
+
+            for k in 0..num_ops {
+                let shr_val = a_val >> (shift_vals[k] % 128);
+                writer.write(&shr_expected_vec[k], &to_field(shr_val), i);
+                let rot_val = a_val.rotate_right(shift_vals[k] as u32);
+                writer.write(&rot_expected_vec[k], &to_field(rot_val), i);
+            }
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_u256_operations() {
+        type F = GoldilocksField;
+        type L = U256OpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a = builder.alloc::<U256Register>();
+        let b = builder.alloc::<U256Register>();
+
+        let (a_plus_b, carry) = builder.carrying_add_u256(&a, &b, &None, &mut operations);
+        let add_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_plus_b, &add_expected);
+        let carry_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&carry, &carry_expected);
+
+        let a_minus_b = builder.wrapping_sub(&a, &b, &mut operations);
+        let sub_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_minus_b, &sub_expected);
+
+        let a_mul_b = builder.wrapping_mul(&a, &b, &mut operations);
+        let mul_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_mul_b, &mul_expected);
+
+        let a_lt_b = builder.uint_lt(&a, &b, &mut operations);
+        let lt_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&a_lt_b, &lt_expected);
+
+        let a_lte_b = builder.uint_lte(&a, &b, &mut operations);
+        let lte_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&a_lte_b, &lte_expected);
+
+        let a_eq_b = builder.uint_eq(&a, &b, &mut operations);
+        let eq_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&a_eq_b, &eq_expected);
+
+        let a_and_b = builder.bitwise_and(&a, &b, &mut operations);
+        let and_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_and_b, &and_expected);
+
+        let a_xor_b = builder.bitwise_xor(&a, &b, &mut operations);
+        let xor_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_xor_b, &xor_expected);
+
+        let a_not = builder.bitwise_not(&a, &mut operations);
+        let not_expected = builder.alloc::<U256Register>();
+        builder.assert_equal(&a_not, &not_expected);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        let modulus = BigUint::from(1u32) << 256;
+        let max = &modulus - 1u32;
+        let to_field = |a: &BigUint| {
+            let mut bytes = a.to_bytes_le();
+            bytes.resize(32, 0);
+            let values: [F; 32] = core::array::from_fn(|i| F::from_canonical_u8(bytes[i]));
+            values
+        };
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // Make sure the wraparound at 2^256 and equal values are covered.
+            let (a_val, b_val) = match i {
+                0 => (max.clone(), max.clone()),
+                1 => (BigUint::from(0u32), BigUint::from(1u32)),
+                _ => {
+                    let a_val = rng.gen_biguint(256);
+                    let b_val = if rng.gen::<bool>() {
+                        a_val.clone()
+                    } else {
+                        rng.gen_biguint(256)
+                    };
+                    (a_val, b_val)
+                }
+            };
+            writer.write(&a, &to_field(&a_val), i);
+            writer.write(&b, &to_field(&b_val), i);
+
+            let sum = &a_val + &b_val;
+            writer.write(&add_expected, &to_field(&(&sum % &modulus)), i);
+            writer.write(
+                &carry_expected,
+                &F::from_canonical_u8((sum >= modulus) as u8),
+                i,
+            );
+
+            let difference = (&a_val + &modulus - &b_val) % &modulus;
+            writer.write(&sub_expected, &to_field(&difference), i);
+
+            let product = (&a_val * &b_val) % &modulus;
+            writer.write(&mul_expected, &to_field(&product), i);
+
+            writer.write(
+                &lt_expected,
+                &F::from_canonical_u8((a_val < b_val) as u8),
+                i,
+            );
+            writer.write(
+                &lte_expected,
+                &F::from_canonical_u8((a_val <= b_val) as u8),
+                i,
+            );
+            writer.write(
+                &eq_expected,
+                &F::from_canonical_u8((a_val == b_val) as u8),
+                i,
+            );
+
+            writer.write(&and_expected, &to_field(&(&a_val & &b_val)), i);
+            writer.write(&xor_expected, &to_field(&(&a_val ^ &b_val)), i);
+            writer.write(&not_expected, &to_field(&(&max ^ &a_val)), i);
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_u160_operations() {
+        type F = GoldilocksField;
+        type L = U160OpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let digest = builder.alloc_array::<U64Register>(4);
+        let address = builder.u160_from_keccak_digest(&digest);
+        let address_expected = builder.alloc::<U160Register>();
+        builder.assert_equal(&address, &address_expected);
+
+        let other = builder.alloc::<U160Register>();
+        let eq = builder.uint_eq(&address, &other, &mut operations);
+        let eq_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&eq, &eq_expected);
+        let lt = builder.uint_lt(&address, &other, &mut operations);
+        let lt_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&lt, &lt_expected);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        // The little-endian integer of big-endian address bytes.
+        let to_field = |be_bytes: &[u8]| {
+            let values: [F; 20] = core::array::from_fn(|i| F::from_canonical_u8(be_bytes[19 - i]));
+            values
+        };
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            let digest_val = rng.gen::<[u8; 32]>();
+            let address_val = &digest_val[12..];
+            let other_val = if i % 2 == 0 {
+                address_val.to_vec()
+            } else {
+                rng.gen::<[u8; 20]>().to_vec()
+            };
+            writer.write_array(
+                &digest,
+                digest_val.chunks_exact(8).map(|lane| {
+                    u64_to_le_field_bytes::<F>(u64::from_le_bytes(lane.try_into().unwrap()))
+                }),
+                i,
+            );
+            writer.write(&address_expected, &to_field(address_val), i);
+            writer.write(&other, &to_field(&other_val), i);
+
+            // Big-endian byte arrays compare as the integers they represent.
+            let eq_val = address_val == other_val.as_slice();
+            let lt_val = address_val < other_val.as_slice();
+            writer.write(&eq_expected, &F::from_canonical_u8(eq_val as u8), i);
+            writer.write(&lt_expected, &F::from_canonical_u8(lt_val as u8), i);
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_u32_u64_comparisons() {
+        type F = GoldilocksField;
+        type L = CompareTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a_32 = builder.alloc::<U32Register>();
+        let b_32 = builder.alloc::<U32Register>();
+        let a_64 = builder.alloc::<U64Register>();
+        let b_64 = builder.alloc::<U64Register>();
+
+        // The comparisons lt, lte, gt, gte and eq, in this order.
+        let results_32 = [
+            builder.uint_lt(&a_32, &b_32, &mut operations),
+            builder.uint_lte(&a_32, &b_32, &mut operations),
+            builder.uint_gt(&a_32, &b_32, &mut operations),
+            builder.uint_gte(&a_32, &b_32, &mut operations),
+            builder.uint_eq(&a_32, &b_32, &mut operations),
+        ];
+        let results_64 = [
+            builder.uint_lt(&a_64, &b_64, &mut operations),
+            builder.uint_lte(&a_64, &b_64, &mut operations),
+            builder.uint_gt(&a_64, &b_64, &mut operations),
+            builder.uint_gte(&a_64, &b_64, &mut operations),
+            builder.uint_eq(&a_64, &b_64, &mut operations),
+        ];
+        let expected_32 = builder.alloc_array::<BitRegister>(5);
+        let expected_64 = builder.alloc_array::<BitRegister>(5);
+        for (result, expected) in results_32.iter().zip(expected_32.iter()) {
+            builder.assert_equal(result, &expected);
+        }
+        for (result, expected) in results_64.iter().zip(expected_64.iter()) {
+            builder.assert_equal(result, &expected);
+        }
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        let to_bits = |a: u64, b: u64| [a < b, a <= b, a > b, a >= b, a == b].map(|x| x as u8);
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // Equal values, values differing only in the high limb, and random values.
+            let (a_val, b_val) = match i % 4 {
+                0 => {
+                    let a_val = rng.gen::<u64>();
+                    (a_val, a_val)
+                }
+                1 => {
+                    let a_val = rng.gen::<u64>();
+                    (a_val, a_val ^ (1 << 63))
+                }
+                _ => (rng.gen::<u64>(), rng.gen::<u64>()),
+            };
+            let (a_32_val, b_32_val) = (a_val as u32, b_val as u32);
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val), i);
+            writer.write(&b_32, &u32_to_le_field_bytes(b_32_val), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_val), i);
+            writer.write(&b_64, &u64_to_le_field_bytes(b_val), i);
+
+            writer.write_array(
+                &expected_32,
+                to_bits(a_32_val as u64, b_32_val as u64).map(F::from_canonical_u8),
+                i,
+            );
+            writer.write_array(
+                &expected_64,
+                to_bits(a_val, b_val).map(F::from_canonical_u8),
+                i,
+            );
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_signed_operations() {
+        type F = GoldilocksField;
+        type L = SignedOpTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a_32 = builder.alloc::<U32Register>();
+        let b_32 = builder.alloc::<U32Register>();
+        let a_64 = builder.alloc::<U64Register>();
+        let b_64 = builder.alloc::<U64Register>();
+
+        // The shifts are chosen to guarantee an even number of byte operations.
+        const SHIFTS_32: [usize; 2] = [5, 16];
+        const SHIFTS_64: [usize; 2] = [5, 37];
+
+        let (add_32, add_overflow_32) = builder.overflowing_int_add(&a_32, &b_32, &mut operations);
+        let (sub_32, sub_overflow_32) = builder.overflowing_int_sub(&a_32, &b_32, &mut operations);
+        let mul_32 = builder.int_mul(&a_32, &b_32, &mut operations);
+        let sar_32 = SHIFTS_32.map(|shift| builder.int_sar(&a_32, shift, &mut operations));
+        // The comparisons lt, lte, gt, gte and the overflow of add and sub, in this order.
+        let bits_32 = [
+            builder.int_lt(&a_32, &b_32, &mut operations),
+            builder.int_lte(&a_32, &b_32, &mut operations),
+            builder.int_gt(&a_32, &b_32, &mut operations),
+            builder.int_gte(&a_32, &b_32, &mut operations),
+            add_overflow_32,
+            sub_overflow_32,
+        ];
+
+        let (add_64, add_overflow_64) = builder.overflowing_int_add(&a_64, &b_64, &mut operations);
+        let (sub_64, sub_overflow_64) = builder.overflowing_int_sub(&a_64, &b_64, &mut operations);
+        let mul_64 = builder.int_mul(&a_64, &b_64, &mut operations);
+        let sar_64 = SHIFTS_64.map(|shift| builder.int_sar(&a_64, shift, &mut operations));
+        let bits_64 = [
+            builder.int_lt(&a_64, &b_64, &mut operations),
+            builder.int_lte(&a_64, &b_64, &mut operations),
+            builder.int_gt(&a_64, &b_64, &mut operations),
+            builder.int_gte(&a_64, &b_64, &mut operations),
+            add_overflow_64,
+            sub_overflow_64,
+        ];
+
+        let [add_32_expected, sub_32_expected, mul_32_expected] =
+            [(); 3].map(|_| builder.alloc::<U32Register>());
+        let sar_32_expected = [(); 2].map(|_| builder.alloc::<U32Register>());
+        let bits_32_expected = builder.alloc_array::<BitRegister>(6);
+        let [add_64_expected, sub_64_expected, mul_64_expected] =
+            [(); 3].map(|_| builder.alloc::<U64Register>());
+        let sar_64_expected = [(); 2].map(|_| builder.alloc::<U64Register>());
+        let bits_64_expected = builder.alloc_array::<BitRegister>(6);
+
+        builder.assert_equal(&add_32, &add_32_expected);
+        builder.assert_equal(&sub_32, &sub_32_expected);
+        builder.assert_equal(&mul_32, &mul_32_expected);
+        builder.assert_equal(&add_64, &add_64_expected);
+        builder.assert_equal(&sub_64, &sub_64_expected);
+        builder.assert_equal(&mul_64, &mul_64_expected);
+        for (result, expected) in sar_32.iter().zip(sar_32_expected.iter()) {
+            builder.assert_equal(result, expected);
+        }
+        for (result, expected) in sar_64.iter().zip(sar_64_expected.iter()) {
+            builder.assert_equal(result, expected);
+        }
+        for (result, expected) in bits_32.iter().zip(bits_32_expected.iter()) {
+            builder.assert_equal(result, &expected);
+        }
+        for (result, expected) in bits_64.iter().zip(bits_64_expected.iter()) {
+            builder.assert_equal(result, &expected);
+        }
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // Values of the same sign, extreme values, equal values, and random values.
+            let (a_val, b_val) = match i % 4 {
+                0 => {
+                    let (a_val, b_val) = (rng.gen::<i64>(), rng.gen::<i64>());
+                    (a_val, b_val ^ ((a_val ^ b_val) & i64::MIN))
+                }
+                1 => (i64::MIN, rng.gen::<i64>() | i64::MAX),
+                2 => {
+                    let a_val = rng.gen::<i64>();
+                    (a_val, a_val)
+                }
+                _ => (rng.gen::<i64>(), rng.gen::<i64>()),
+            };
+            let (a_32_val, b_32_val) = (a_val as i32, b_val as i32);
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val as u32), i);
+            writer.write(&b_32, &u32_to_le_field_bytes(b_32_val as u32), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_val as u64), i);
+            writer.write(&b_64, &u64_to_le_field_bytes(b_val as u64), i);
+
+            let (add_32_val, add_overflow_32_val) = a_32_val.overflowing_add(b_32_val);
+            let (sub_32_val, sub_overflow_32_val) = a_32_val.overflowing_sub(b_32_val);
+            let mul_32_val = a_32_val.wrapping_mul(b_32_val);
+            let write_32 = |register: &U32Register, value: i32| {
+                writer.write(register, &u32_to_le_field_bytes(value as u32), i)
+            };
+            write_32(&add_32_expected, add_32_val);
+            write_32(&sub_32_expected, sub_32_val);
+            write_32(&mul_32_expected, mul_32_val);
+            for (register, shift) in sar_32_expected.iter().zip(SHIFTS_32) {
+                write_32(register, a_32_val >> shift);
+            }
+            let bits_32_val = [
+                a_32_val < b_32_val,
+                a_32_val <= b_32_val,
+                a_32_val > b_32_val,
+                a_32_val >= b_32_val,
+                add_overflow_32_val,
+                sub_overflow_32_val,
+            ];
+            writer.write_array(
+                &bits_32_expected,
+                bits_32_val.map(|x| F::from_canonical_u8(x as u8)),
+                i,
+            );
+
+            let (add_64_val, add_overflow_64_val) = a_val.overflowing_add(b_val);
+            let (sub_64_val, sub_overflow_64_val) = a_val.overflowing_sub(b_val);
+            let mul_64_val = a_val.wrapping_mul(b_val);
+            let write_64 = |register: &U64Register, value: i64| {
+                writer.write(register, &u64_to_le_field_bytes(value as u64), i)
+            };
+            write_64(&add_64_expected, add_64_val);
+            write_64(&sub_64_expected, sub_64_val);
+            write_64(&mul_64_expected, mul_64_val);
+            for (register, shift) in sar_64_expected.iter().zip(SHIFTS_64) {
+                write_64(register, a_val >> shift);
+            }
+            let bits_64_val = [
+                a_val < b_val,
+                a_val <= b_val,
+                a_val > b_val,
+                a_val >= b_val,
+                add_overflow_64_val,
+                sub_overflow_64_val,
+            ];
+            writer.write_array(
+                &bits_64_expected,
+                bits_64_val.map(|x| F::from_canonical_u8(x as u8)),
+                i,
+            );
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_variable_shifts_and_rotations() {
+        type F = GoldilocksField;
+        type L = VarShiftTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a_32 = builder.alloc::<U32Register>();
+        let shift_32 = builder.alloc::<ElementRegister>();
+        let a_64 = builder.alloc::<U64Register>();
+        let shift_64 = builder.alloc::<ElementRegister>();
+
+        let shr_32 = builder.bit_shr_var(&a_32, &shift_32, &mut operations);
+        let shr_64 = builder.bit_shr_var(&a_64, &shift_64, &mut operations);
+
+        let shl_32 = builder.bit_shl_var(&a_32, &shift_32, &mut operations);
+        let shl_64 = builder.bit_shl_var(&a_64, &shift_64, &mut operations);
+
+        let sar_32 = builder.int_sar_var(&a_32, &shift_32, &mut operations);
+        let sar_64 = builder.int_sar_var(&a_64, &shift_64, &mut operations);
+
+        let rot_32 = builder.bit_rotate_right_var(&a_32, &shift_32, &mut operations);
+        let rot_64 = builder.bit_rotate_right_var(&a_64, &shift_64, &mut operations);
+
+        let shr_32_expected = builder.alloc::<U32Register>();
+        let shr_64_expected = builder.alloc::<U64Register>();
+        let shl_32_expected = builder.alloc::<U32Register>();
+        let shl_64_expected = builder.alloc::<U64Register>();
+        let sar_32_expected = builder.alloc::<U32Register>();
+        let sar_64_expected = builder.alloc::<U64Register>();
+        let rot_32_expected = builder.alloc::<U32Register>();
+        let rot_64_expected = builder.alloc::<U64Register>();
+        builder.assert_equal(&shr_32, &shr_32_expected);
+        builder.assert_equal(&shr_64, &shr_64_expected);
+        builder.assert_equal(&shl_32, &shl_32_expected);
+        builder.assert_equal(&shl_64, &shl_64_expected);
+        builder.assert_equal(&sar_32, &sar_32_expected);
+        builder.assert_equal(&sar_64, &sar_64_expected);
+        builder.assert_equal(&rot_32, &rot_32_expected);
+        builder.assert_equal(&rot_64, &rot_64_expected);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            let a_val = rng.gen::<u64>();
+            let a_32_val = a_val as u32;
+            // The first rows cover the extreme amounts `0` and `8 * N - 1`.
+            let (shift_32_val, shift_64_val) = match i {
+                0 => (0, 0),
+                1 => (31, 63),
+                _ => (rng.gen_range(0..32), rng.gen_range(0..64)),
+            };
+
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val), i);
+            writer.write(&shift_32, &F::from_canonical_u32(shift_32_val), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_val), i);
+            writer.write(&shift_64, &F::from_canonical_u32(shift_64_val), i);
+
+            writer.write(
+                &shr_32_expected,
+                &u32_to_le_field_bytes(a_32_val >> shift_32_val),
+                i,
+            );
+            writer.write(
+                &shr_64_expected,
+                &u64_to_le_field_bytes(a_val >> shift_64_val),
+                i,
+            );
+            writer.write(
+                &shl_32_expected,
+                &u32_to_le_field_bytes(a_32_val << shift_32_val),
+                i,
+            );
+            writer.write(
+                &shl_64_expected,
+                &u64_to_le_field_bytes(a_val << shift_64_val),
+                i,
+            );
+            writer.write(
+                &sar_32_expected,
+                &u32_to_le_field_bytes(((a_32_val as i32) >> shift_32_val) as u32),
+                i,
+            );
+            writer.write(
+                &sar_64_expected,
+                &u64_to_le_field_bytes(((a_val as i64) >> shift_64_val) as u64),
+                i,
+            );
+            writer.write(
+                &rot_32_expected,
+                &u32_to_le_field_bytes(a_32_val.rotate_right(shift_32_val)),
+                i,
+            );
+            writer.write(
+                &rot_64_expected,
+                &u64_to_le_field_bytes(a_val.rotate_right(shift_64_val)),
+                i,
+            );
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_bswap() {
+        type F = GoldilocksField;
+        type L = BswapTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        // Add big-endian words by swapping them to little-endian and back.
+        let a_32 = builder.alloc::<U32Register>();
+        let b_32 = builder.alloc::<U32Register>();
+        let a_64 = builder.alloc::<U64Register>();
+        let b_64 = builder.alloc::<U64Register>();
+
+        let (a_32_le, b_32_le) = (builder.bswap(&a_32), builder.bswap(&b_32));
+        let sum_32_le = builder.add_u32(&a_32_le, &b_32_le, &mut operations);
+        let sum_32 = builder.bswap(&sum_32_le);
+
+        let (a_64_le, b_64_le) = (builder.bswap(&a_64), builder.bswap(&b_64));
+        let sum_64_le = builder.add_u64(&a_64_le, &b_64_le, &mut operations);
+        let sum_64 = builder.bswap(&sum_64_le);
+
+        let sum_32_expected = builder.alloc::<U32Register>();
+        let sum_64_expected = builder.alloc::<U64Register>();
+        builder.assert_equal(&sum_32, &sum_32_expected);
+        builder.assert_equal(&sum_64, &sum_64_expected);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            let (a_32_val, b_32_val) = (rng.gen::<u32>(), rng.gen::<u32>());
+            let (a_64_val, b_64_val) = (rng.gen::<u64>(), rng.gen::<u64>());
+
+            // The registers hold the big-endian bytes of the values.
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val.swap_bytes()), i);
+            writer.write(&b_32, &u32_to_le_field_bytes(b_32_val.swap_bytes()), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_64_val.swap_bytes()), i);
+            writer.write(&b_64, &u64_to_le_field_bytes(b_64_val.swap_bytes()), i);
+
+            let sum_32_val = a_32_val.wrapping_add(b_32_val).swap_bytes();
+            let sum_64_val = a_64_val.wrapping_add(b_64_val).swap_bytes();
+            writer.write(&sum_32_expected, &u32_to_le_field_bytes(sum_32_val), i);
+            writer.write(&sum_64_expected, &u64_to_le_field_bytes(sum_64_val), i);
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_bit_counts() {
+        type F = GoldilocksField;
+        type L = CountTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_count_operations();
+
+        let a_32 = builder.alloc::<U32Register>();
+        let a_64 = builder.alloc::<U64Register>();
+
+        // The popcount, leading zeros and trailing zeros, in this order.
+        let counts = [
+            builder.uint_popcount(&a_32, &mut operations),
+            builder.uint_leading_zeros(&a_32, &mut operations),
+            builder.uint_trailing_zeros(&a_32, &mut operations),
+            builder.uint_popcount(&a_64, &mut operations),
+            builder.uint_leading_zeros(&a_64, &mut operations),
+            builder.uint_trailing_zeros(&a_64, &mut operations),
+        ];
+        let counts_expected = builder.alloc_array::<ElementRegister>(6);
+        for (count, expected) in counts.iter().zip(counts_expected.iter()) {
+            builder.assert_equal(count, &expected);
+        }
+
+        let mut count_table = builder.new_byte_count_table();
+        let count_data = builder.register_byte_count_lookup(&mut count_table, operations);
+        builder.constraint_byte_count_table(&count_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        count_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // Zero, values with runs of zero bytes at both ends, and random values.
+            let a_val = match i % 4 {
+                0 => 0,
+                1 => (rng.gen::<u64>() >> rng.gen_range(0..64)) << rng.gen_range(0..64),
+                _ => rng.gen::<u64>(),
+            };
+            let a_32_val = a_val as u32;
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_val), i);
+
+            let counts_val = [
+                a_32_val.count_ones(),
+                a_32_val.leading_zeros(),
+                a_32_val.trailing_zeros(),
+                a_val.count_ones(),
+                a_val.leading_zeros(),
+                a_val.trailing_zeros(),
+            ];
+            writer.write_array(&counts_expected, counts_val.map(F::from_canonical_u32), i);
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = count_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(count_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        type L = WideMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+
+        let mut operations = builder.byte_operations();
+
+        let a_32 = builder.alloc::<U32Register>();
+        let b_32 = builder.alloc::<U32Register>();
+        let a_64 = builder.alloc::<U64Register>();
+        let b_64 = builder.alloc::<U64Register>();
+
+        let (low_32, high_32) = builder.widening_mul(&a_32, &b_32, &mut operations);
+        let (low_64, high_64) = builder.widening_mul_u64(&a_64, &b_64, &mut operations);
+
+        let [low_32_expected, high_32_expected] = [(); 2].map(|_| builder.alloc::<U32Register>());
+        let [low_64_expected, high_64_expected] = [(); 2].map(|_| builder.alloc::<U64Register>());
+        builder.assert_equal(&low_32, &low_32_expected);
+        builder.assert_equal(&high_32, &high_32_expected);
+        builder.assert_equal(&low_64, &low_64_expected);
+        builder.assert_equal(&high_64, &high_64_expected);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // The largest factors give the largest column sums and carries.
+            let (a_val, b_val) = match i % 4 {
+                0 => (u64::MAX, u64::MAX),
+                _ => (rng.gen::<u64>(), rng.gen::<u64>()),
+            };
+            let (a_32_val, b_32_val) = (a_val as u32, b_val as u32);
+            writer.write(&a_32, &u32_to_le_field_bytes(a_32_val), i);
+            writer.write(&b_32, &u32_to_le_field_bytes(b_32_val), i);
+            writer.write(&a_64, &u64_to_le_field_bytes(a_val), i);
+            writer.write(&b_64, &u64_to_le_field_bytes(b_val), i);
+
+            let product_32 = a_32_val as u64 * b_32_val as u64;
+            let product_64 = a_val as u128 * b_val as u128;
+            writer.write(
+                &low_32_expected,
+                &u32_to_le_field_bytes(product_32 as u32),
+                i,
+            );
+            writer.write(
+                &high_32_expected,
+                &u32_to_le_field_bytes((product_32 >> 32) as u32),
+                i,
+            );
+            writer.write(
+                &low_64_expected,
+                &u64_to_le_field_bytes(product_64 as u64),
+                i,
+            );
+            writer.write(
+                &high_64_expected,
+                &u64_to_le_field_bytes((product_64 >> 64) as u64),
+                i,
+            );
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
 }
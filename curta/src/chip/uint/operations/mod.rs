@@ -1,7 +1,13 @@
 pub mod add;
 pub mod and;
+pub mod compare;
+pub mod count;
 pub mod instruction;
+pub mod mul;
+pub mod native;
 pub mod not;
 pub mod rotate;
 pub mod shr;
+pub mod signed;
+pub mod sub;
 pub mod xor;
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::register::{ByteArrayRegister, U64Register};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The largest number of bytes of the factors of a `ByteArrayMul`, for which the column carries fit
+/// in two bytes.
+pub const MAX_MUL_BYTES: usize = 64;
+
+/// Multiplying byte arrays, keeping the lowest `m` bytes of the product, where `m` is the number of
+/// bytes of `result`.
+///
+/// For every byte position `k < m`, constrains the column sum
+/// `sum_{i + j = k} a_i * b_j + c_{k - 1} = result_k + 2^8 * c_k`, where the carry `c_k` is range
+/// checked as two bytes and `c_{-1} = 0`. The carry out of the last column is dropped, so the
+/// product is computed modulo `2^(8 * m)`, and it is exact for `m = a.len() + b.len()`. As every
+/// column sum is less than `2^24`, the constraints do not wrap around the field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ByteArrayMul {
+    pub a: ArrayRegister<ByteRegister>,
+    pub b: ArrayRegister<ByteRegister>,
+    pub result: ArrayRegister<ByteRegister>,
+    carries: ArrayRegister<ByteRegister>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `a * b` modulo `2^(8 * N)`.
+    pub fn wrapping_mul<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        self.set_wrapping_mul(a, b, &result, operations);
+        result
+    }
+
+    pub fn set_wrapping_mul<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        result: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        self.set_mul_bytes(a, b, &result.to_le_bytes(), operations);
+    }
+
+    /// Computes the full product of `a` and `b`, returning its low and high halves.
+    pub fn widening_mul<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> (ByteArrayRegister<N>, ByteArrayRegister<N>)
+    where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        let product = self.alloc_array::<ByteRegister>(2 * N);
+        self.set_mul_bytes(a, b, &product, operations);
+
+        let low =
+            ByteArrayRegister::<N>::from_register_unsafe(*product.get_subarray(0..N).register());
+        let high = ByteArrayRegister::<N>::from_register_unsafe(
+            *product.get_subarray(N..2 * N).register(),
+        );
+        (low, high)
+    }
+
+    /// Computes the 128-bit product of `a` and `b`, returning its low and high 64-bit halves.
+    pub fn widening_mul_u64(
+        &mut self,
+        a: &U64Register,
+        b: &U64Register,
+        operations: &mut ByteLookupOperations,
+    ) -> (U64Register, U64Register)
+    where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        self.widening_mul(a, b, operations)
+    }
+
+    /// Constrains `result` to be the lowest `result.len()` bytes of the product of `a` and `b`.
+    fn set_mul_bytes<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        result: &ArrayRegister<ByteRegister>,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        assert!(
+            N <= MAX_MUL_BYTES,
+            "ByteArrayMul only supports up to {} bytes",
+            MAX_MUL_BYTES
+        );
+        let carries = self.alloc_array::<ByteRegister>(2 * result.len());
+        let mul = ByteArrayMul {
+            a: a.to_le_bytes(),
+            b: b.to_le_bytes(),
+            result: *result,
+            carries,
+        };
+        self.register_instruction(mul);
+
+        for byte in result.iter().chain(carries.iter()) {
+            let range = ByteOperation::Range(byte);
+            self.set_byte_operation(&range, operations);
+        }
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for ByteArrayMul {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval_vec(parser);
+        let b = self.b.eval_vec(parser);
+        let result = self.result.eval_vec(parser);
+        let carries = self.carries.eval_vec(parser);
+
+        let byte_shift = AP::Field::from_canonical_u32(1 << 8);
+        let mut carry_in = parser.zero();
+        for (k, result_byte) in result.into_iter().enumerate() {
+            let mut column = carry_in;
+            let skip = (k + 1).saturating_sub(b.len());
+            for (i, a_byte) in a.iter().enumerate().take(k + 1).skip(skip) {
+                let product = parser.mul(*a_byte, b[k - i]);
+                column = parser.add(column, product);
+            }
+
+            let carry_high = parser.mul_const(carries[2 * k + 1], byte_shift);
+            let carry_out = parser.add(carries[2 * k], carry_high);
+            let carry_out_shifted = parser.mul_const(carry_out, byte_shift);
+            let result_plus_carry = parser.add(result_byte, carry_out_shifted);
+            parser.assert_eq(column, result_plus_carry);
+            carry_in = carry_out;
+        }
+    }
+}
+
+impl ByteArrayMul {
+    fn witness<F: PrimeField64>(a: &[F], b: &[F], n: usize) -> (Vec<F>, Vec<F>) {
+        let mut result = Vec::with_capacity(n);
+        let mut carries = Vec::with_capacity(2 * n);
+        let mut carry = 0u64;
+        for k in 0..n {
+            let start = (k + 1).saturating_sub(b.len());
+            let column = (start..=k.min(a.len() - 1)).fold(carry, |acc, i| {
+                acc + a[i].as_canonical_u64() * b[k - i].as_canonical_u64()
+            });
+            result.push(F::from_canonical_u64(column & 0xFF));
+            carry = column >> 8;
+            debug_assert!(carry < 1 << 16);
+            carries.push(F::from_canonical_u64(carry & 0xFF));
+            carries.push(F::from_canonical_u64(carry >> 8));
+        }
+        (result, carries)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for ByteArrayMul {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = writer.read_vec(&self.a, row_index);
+        let b = writer.read_vec(&self.b, row_index);
+        let (result, carries) = Self::witness(&a, &b, self.result.len());
+
+        writer.write_array(&self.result, result, row_index);
+        writer.write_array(&self.carries, carries, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = writer.read_vec(&self.a);
+        let b = writer.read_vec(&self.b);
+        let (result, carries) = Self::witness(&a, &b, self.result.len());
+
+        writer.write_array(&self.result, result);
+        writer.write_array(&self.carries, carries);
+    }
+}
@@ -0,0 +1,602 @@
+//! Arithmetic on `u32` values stored as a single field element.
+//!
+//! The values are kept in `ElementRegister`s and their membership in `[0, 2^32)` is enforced by a
+//! decomposition into two `u16` limbs which are range checked against the arithmetic range table.
+//! No byte lookups are used, which makes these operations suitable for counters, indices and
+//! length arithmetic. When byte-level access is needed, the values can be converted to a
+//! `U32Register`.
+//!
+//! The limbs are allocated in the arithmetic columns. An `AirBuilder` range checks them against a
+//! table in the trace itself, which then must have `2^16` rows. A `BytesBuilder` instead looks
+//! them up in its lookup trace, so its main trace can have any number of rows.
+
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::register::U32Register;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The order of the Goldilocks field, `2^64 - 2^32 + 1`.
+const GOLDILOCKS_ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// Decomposition of a native `u32` value into two `u16` limbs, `value = limbs[0] + 2^16 limbs[1]`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U32NativeDecompose {
+    pub value: ElementRegister,
+    limbs: ArrayRegister<U16Register>,
+}
+
+/// The addition `a + b = result + 2^32 * carry` of two native `u32` values.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U32NativeAdd {
+    pub a: ElementRegister,
+    pub b: ElementRegister,
+    pub result: ElementRegister,
+    pub carry: BitRegister,
+}
+
+/// The multiplication `a * b = low + 2^32 * high` of two native `u32` values.
+///
+/// Since `(2^32 - 1)^2 < 2^64 - 2^32 + 1`, the product never wraps around the Goldilocks modulus.
+/// The only non-canonical representations of the product have `high = 2^32 - 1`, which is ruled
+/// out by the witness `high_inv` of `high - (2^32 - 1)`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U32NativeMul {
+    pub a: ElementRegister,
+    pub b: ElementRegister,
+    pub low: ElementRegister,
+    pub high: ElementRegister,
+    high_inv: ElementRegister,
+}
+
+/// The little-endian byte representation of a native `u32` value.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U32NativeToBytes {
+    pub value: ElementRegister,
+    pub result: U32Register,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum U32NativeInstruction {
+    Decompose(U32NativeDecompose),
+    Add(U32NativeAdd),
+    Mul(U32NativeMul),
+    ToBytes(U32NativeToBytes),
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates an element register whose value is constrained to be in `[0, 2^32)`.
+    pub fn alloc_u32_native(&mut self) -> ElementRegister
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        let value = self.alloc::<ElementRegister>();
+        self.range_check_u32_native(&value);
+        value
+    }
+
+    /// Allocates a public element register whose value is constrained to be in `[0, 2^32)`.
+    pub fn alloc_public_u32_native(&mut self) -> ElementRegister
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        let value = self.alloc_public::<ElementRegister>();
+        self.range_check_u32_native(&value);
+        value
+    }
+
+    /// Constrains the value of `value` to be in `[0, 2^32)` by decomposing it into two `u16`
+    /// limbs.
+    pub fn range_check_u32_native(&mut self, value: &ElementRegister)
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        if value.is_trace() {
+            let limbs = self.alloc_array::<U16Register>(2);
+            let instr = U32NativeDecompose {
+                value: *value,
+                limbs,
+            };
+            self.register_instruction(U32NativeInstruction::Decompose(instr));
+        } else {
+            let limbs = self.alloc_array_public::<U16Register>(2);
+            let instr = U32NativeDecompose {
+                value: *value,
+                limbs,
+            };
+            self.register_global_instruction(U32NativeInstruction::Decompose(instr));
+        }
+    }
+
+    /// Computes `a + b` as native `u32` values, returning the result modulo `2^32` and the carry.
+    ///
+    /// The inputs are assumed to be in `[0, 2^32)`, the result is range checked.
+    pub fn add_u32_native(
+        &mut self,
+        a: &ElementRegister,
+        b: &ElementRegister,
+    ) -> (ElementRegister, BitRegister)
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let (result, carry) = if is_trace {
+            (self.alloc::<ElementRegister>(), self.alloc::<BitRegister>())
+        } else {
+            (
+                self.alloc_public::<ElementRegister>(),
+                self.alloc_public::<BitRegister>(),
+            )
+        };
+        self.set_add_u32_native(a, b, &result, &carry);
+        (result, carry)
+    }
+
+    pub fn set_add_u32_native(
+        &mut self,
+        a: &ElementRegister,
+        b: &ElementRegister,
+        result: &ElementRegister,
+        carry: &BitRegister,
+    ) where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        assert!(
+            L::Field::order() > 1 << 33,
+            "native u32 addition requires a field of order larger than 2^33"
+        );
+        let is_trace = a.is_trace() || b.is_trace() || result.is_trace() || carry.is_trace();
+        let instr = U32NativeInstruction::Add(U32NativeAdd {
+            a: *a,
+            b: *b,
+            result: *result,
+            carry: *carry,
+        });
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        self.range_check_u32_native(result);
+    }
+
+    /// Computes the full product `a * b` of native `u32` values, returning the low and high
+    /// 32 bits of the result.
+    ///
+    /// The inputs are assumed to be in `[0, 2^32)`, the outputs are range checked.
+    pub fn mul_u32_native(
+        &mut self,
+        a: &ElementRegister,
+        b: &ElementRegister,
+    ) -> (ElementRegister, ElementRegister)
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        let is_trace = a.is_trace() || b.is_trace();
+        let (low, high) = if is_trace {
+            (
+                self.alloc::<ElementRegister>(),
+                self.alloc::<ElementRegister>(),
+            )
+        } else {
+            (
+                self.alloc_public::<ElementRegister>(),
+                self.alloc_public::<ElementRegister>(),
+            )
+        };
+        self.set_mul_u32_native(a, b, &low, &high);
+        (low, high)
+    }
+
+    pub fn set_mul_u32_native(
+        &mut self,
+        a: &ElementRegister,
+        b: &ElementRegister,
+        low: &ElementRegister,
+        high: &ElementRegister,
+    ) where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        assert_eq!(
+            L::Field::order(),
+            GOLDILOCKS_ORDER,
+            "native u32 multiplication is only supported over the Goldilocks field"
+        );
+        let is_trace = a.is_trace() || b.is_trace() || low.is_trace() || high.is_trace();
+        let high_inv = if is_trace {
+            self.alloc::<ElementRegister>()
+        } else {
+            self.alloc_public::<ElementRegister>()
+        };
+        let instr = U32NativeInstruction::Mul(U32NativeMul {
+            a: *a,
+            b: *b,
+            low: *low,
+            high: *high,
+            high_inv,
+        });
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+        self.range_check_u32_native(low);
+        self.range_check_u32_native(high);
+    }
+
+    /// Computes `a * b mod 2^32` for native `u32` values.
+    pub fn wrapping_mul_u32_native(
+        &mut self,
+        a: &ElementRegister,
+        b: &ElementRegister,
+    ) -> ElementRegister
+    where
+        L::Instruction: From<U32NativeInstruction>,
+    {
+        let (low, _) = self.mul_u32_native(a, b);
+        low
+    }
+
+    /// Converts a native `u32` value into its byte representation.
+    ///
+    /// The bytes of the result are range checked using the byte lookup.
+    pub fn u32_native_to_bytes(
+        &mut self,
+        value: &ElementRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> U32Register
+    where
+        L::Instruction: From<U32NativeInstruction> + From<ByteOperationInstruction>,
+    {
+        let result = if value.is_trace() {
+            self.alloc::<U32Register>()
+        } else {
+            self.alloc_public_unchecked::<U32Register>()
+        };
+        self.set_u32_native_to_bytes(value, &result, operations);
+        result
+    }
+
+    pub fn set_u32_native_to_bytes(
+        &mut self,
+        value: &ElementRegister,
+        result: &U32Register,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<U32NativeInstruction> + From<ByteOperationInstruction>,
+    {
+        let instr = U32NativeInstruction::ToBytes(U32NativeToBytes {
+            value: *value,
+            result: *result,
+        });
+        if value.is_trace() || result.is_trace() {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+
+        for byte in result.to_le_bytes() {
+            let result_range = ByteOperation::Range(byte);
+            self.set_byte_operation(&result_range, operations);
+        }
+    }
+
+    /// Converts a `U32Register` into a native `u32` value.
+    ///
+    /// The bytes of `value` are assumed to be range checked, so no further checks are needed.
+    pub fn u32_native_from_bytes(&mut self, value: &U32Register) -> ElementRegister {
+        let bytes = value.to_le_bytes();
+        let expression = bytes
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte.expr() * L::Field::from_canonical_u32(1 << (8 * i)))
+            .reduce(|acc, x| acc + x)
+            .unwrap();
+
+        if value.is_trace() {
+            let result = self.alloc::<ElementRegister>();
+            self.set_to_expression(&result, expression);
+            result
+        } else {
+            let result = self.alloc_public::<ElementRegister>();
+            self.set_to_expression_public(&result, expression);
+            result
+        }
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U32NativeDecompose {
+    fn eval(&self, parser: &mut AP) {
+        let value = self.value.eval(parser);
+        let limbs = self.limbs.eval_array::<_, 2>(parser);
+
+        let high_shifted = parser.mul_const(limbs[1], AP::Field::from_canonical_u32(1 << 16));
+        let limb_sum = parser.add(limbs[0], high_shifted);
+        parser.assert_eq(value, limb_sum);
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U32NativeAdd {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval(parser);
+        let b = self.b.eval(parser);
+        let result = self.result.eval(parser);
+        let carry = self.carry.eval(parser);
+
+        let a_plus_b = parser.add(a, b);
+        let carry_times_mod = parser.mul_const(carry, AP::Field::from_canonical_u64(1 << 32));
+        let result_plus_carry = parser.add(result, carry_times_mod);
+        parser.assert_eq(a_plus_b, result_plus_carry);
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U32NativeMul {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval(parser);
+        let b = self.b.eval(parser);
+        let low = self.low.eval(parser);
+        let high = self.high.eval(parser);
+        let high_inv = self.high_inv.eval(parser);
+
+        let a_times_b = parser.mul(a, b);
+        let high_times_mod = parser.mul_const(high, AP::Field::from_canonical_u64(1 << 32));
+        let low_plus_high = parser.add(low, high_times_mod);
+        parser.assert_eq(a_times_b, low_plus_high);
+
+        // Constrain `high != 2^32 - 1` to get a unique representation of the product.
+        let high_minus_max = parser.sub_const(high, AP::Field::from_canonical_u32(u32::MAX));
+        let inv_product = parser.mul(high_minus_max, high_inv);
+        let one = parser.one();
+        parser.assert_eq(inv_product, one);
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U32NativeToBytes {
+    fn eval(&self, parser: &mut AP) {
+        let value = self.value.eval(parser);
+        let bytes = self.result.eval(parser);
+
+        let mut bytes_val = parser.zero();
+        for (i, byte) in bytes.into_iter().enumerate() {
+            let byte_times_mult =
+                parser.mul_const(byte, AP::Field::from_canonical_u32(1 << (8 * i)));
+            bytes_val = parser.add(bytes_val, byte_times_mult);
+        }
+        parser.assert_eq(value, bytes_val);
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U32NativeInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            Self::Decompose(op) => op.eval(parser),
+            Self::Add(op) => op.eval(parser),
+            Self::Mul(op) => op.eval(parser),
+            Self::ToBytes(op) => op.eval(parser),
+        }
+    }
+}
+
+impl U32NativeDecompose {
+    fn limb_values<F: PrimeField64>(value: F) -> [F; 2] {
+        let value = value.as_canonical_u64();
+        debug_assert!(value <= u32::MAX as u64, "value {} is not a u32", value);
+        [
+            F::from_canonical_u64(value & 0xFFFF),
+            F::from_canonical_u64((value >> 16) & 0xFFFF),
+        ]
+    }
+}
+
+impl U32NativeAdd {
+    fn result_values<F: PrimeField64>(a: F, b: F) -> (F, F) {
+        let a_val = a.as_canonical_u64() as u32;
+        let b_val = b.as_canonical_u64() as u32;
+        let (result, carry) = a_val.overflowing_add(b_val);
+        (
+            F::from_canonical_u32(result),
+            F::from_canonical_u8(carry as u8),
+        )
+    }
+}
+
+impl U32NativeMul {
+    fn result_values<F: PrimeField64>(a: F, b: F) -> (F, F, F) {
+        let a_val = a.as_canonical_u64();
+        let b_val = b.as_canonical_u64();
+        let product = a_val * b_val;
+        let low = F::from_canonical_u64(product & 0xFFFF_FFFF);
+        let high = F::from_canonical_u64(product >> 32);
+        let high_inv = (high - F::from_canonical_u32(u32::MAX)).inverse();
+        (low, high, high_inv)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for U32NativeInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            Self::Decompose(op) => {
+                let value = writer.read(&op.value, row_index);
+                let limbs = U32NativeDecompose::limb_values(value);
+                writer.write_array(&op.limbs, limbs, row_index);
+            }
+            Self::Add(op) => {
+                let a = writer.read(&op.a, row_index);
+                let b = writer.read(&op.b, row_index);
+                let (result, carry) = U32NativeAdd::result_values(a, b);
+                writer.write(&op.result, &result, row_index);
+                writer.write(&op.carry, &carry, row_index);
+            }
+            Self::Mul(op) => {
+                let a = writer.read(&op.a, row_index);
+                let b = writer.read(&op.b, row_index);
+                let (low, high, high_inv) = U32NativeMul::result_values(a, b);
+                writer.write(&op.low, &low, row_index);
+                writer.write(&op.high, &high, row_index);
+                writer.write(&op.high_inv, &high_inv, row_index);
+            }
+            Self::ToBytes(op) => {
+                let value = writer.read(&op.value, row_index);
+                let bytes = (value.as_canonical_u64() as u32)
+                    .to_le_bytes()
+                    .map(F::from_canonical_u8);
+                writer.write(&op.result, &bytes, row_index);
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            Self::Decompose(op) => {
+                let value = writer.read(&op.value);
+                let limbs = U32NativeDecompose::limb_values(value);
+                writer.write_array(&op.limbs, limbs);
+            }
+            Self::Add(op) => {
+                let a = writer.read(&op.a);
+                let b = writer.read(&op.b);
+                let (result, carry) = U32NativeAdd::result_values(a, b);
+                writer.write(&op.result, &result);
+                writer.write(&op.carry, &carry);
+            }
+            Self::Mul(op) => {
+                let a = writer.read(&op.a);
+                let b = writer.read(&op.b);
+                let (low, high, high_inv) = U32NativeMul::result_values(a, b);
+                writer.write(&op.low, &low);
+                writer.write(&op.high, &high);
+                writer.write(&op.high_inv, &high_inv);
+            }
+            Self::ToBytes(op) => {
+                let value = writer.read(&op.value);
+                let bytes = (value.as_canonical_u64() as u32)
+                    .to_le_bytes()
+                    .map(F::from_canonical_u8);
+                writer.write(&op.result, &bytes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct U32NativeTest;
+
+    impl AirParameters for U32NativeTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 10;
+        const NUM_FREE_COLUMNS: usize = 200;
+        const EXTENDED_COLUMNS: usize = 300;
+    }
+
+    #[test]
+    fn test_u32_native_operations() {
+        type F = GoldilocksField;
+        type L = U32NativeTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let mut operations = builder.byte_operations();
+
+        let a = builder.alloc_u32_native();
+        let b = builder.alloc_u32_native();
+
+        let (sum, carry) = builder.add_u32_native(&a, &b);
+        let sum_expected = builder.alloc::<ElementRegister>();
+        builder.assert_equal(&sum, &sum_expected);
+        let carry_expected = builder.alloc::<BitRegister>();
+        builder.assert_equal(&carry, &carry_expected);
+
+        let (low, high) = builder.mul_u32_native(&a, &b);
+        let low_expected = builder.alloc::<ElementRegister>();
+        builder.assert_equal(&low, &low_expected);
+        let high_expected = builder.alloc::<ElementRegister>();
+        builder.assert_equal(&high, &high_expected);
+
+        // Convert the sum to bytes and back.
+        let sum_bytes = builder.u32_native_to_bytes(&sum, &mut operations);
+        let sum_bytes_expected = builder.alloc::<U32Register>();
+        builder.assert_equal(&sum_bytes, &sum_bytes_expected);
+        let sum_from_bytes = builder.u32_native_from_bytes(&sum_bytes);
+        builder.assert_equal(&sum_from_bytes, &sum);
+
+        let mut byte_table = builder.new_byte_lookup_table();
+        let byte_data = builder.register_byte_lookup(&mut byte_table, operations);
+        builder.constraint_byte_lookup_table(&byte_table);
+
+        let (air, trace_data) = builder.build();
+
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+
+        byte_table.write_table_entries(&writer);
+        let mut rng = thread_rng();
+        for i in 0..num_rows {
+            // Make sure the wraparound at 2^32 and the edge values are covered.
+            let (a_val, b_val) = match i {
+                0 => (u32::MAX, 1),
+                1 => (u32::MAX, u32::MAX),
+                2 => (0, 0),
+                3 => (1 << 31, 1 << 31),
+                _ => (rng.gen::<u32>(), rng.gen::<u32>()),
+            };
+            writer.write(&a, &F::from_canonical_u32(a_val), i);
+            writer.write(&b, &F::from_canonical_u32(b_val), i);
+
+            let (sum_val, carry_val) = a_val.overflowing_add(b_val);
+            writer.write(&sum_expected, &F::from_canonical_u32(sum_val), i);
+            writer.write(&carry_expected, &F::from_canonical_u8(carry_val as u8), i);
+            writer.write(
+                &sum_bytes_expected,
+                &sum_val.to_le_bytes().map(F::from_canonical_u8),
+                i,
+            );
+
+            let product = a_val as u64 * b_val as u64;
+            writer.write(&low_expected, &F::from_canonical_u32(product as u32), i);
+            writer.write(
+                &high_expected,
+                &F::from_canonical_u32((product >> 32) as u32),
+                i,
+            );
+
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        let multiplicities = byte_data.get_multiplicities(&writer);
+        writer.write_lookup_multiplicities(byte_table.multiplicities(), &[multiplicities]);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
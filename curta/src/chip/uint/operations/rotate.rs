@@ -1,6 +1,7 @@
 use core::array::from_fn;
 
 use crate::chip::builder::AirBuilder;
+use crate::chip::register::element::ElementRegister;
 use crate::chip::register::Register;
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
 use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
@@ -74,4 +75,44 @@ impl<L: AirParameters> AirBuilder<L> {
         self.set_bit_rotate_right(a, rotation, &result, operations);
         result
     }
+
+    /// Computes the right rotation of `a` by an amount held in a register.
+    ///
+    /// The amount is decomposed into `log2(8 * N)` bits, which constrains it to be less than
+    /// `8 * N`, and each bit selects whether to rotate by the corresponding power of two. `N` must
+    /// be a power of two.
+    pub fn bit_rotate_right_var<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        rotation: &ElementRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let rotation_bits = self.shift_amount_bits::<N>(rotation);
+
+        let mut result = *a;
+        for (k, bit) in rotation_bits.iter().enumerate() {
+            let rotation = 1 << k;
+            result = if rotation % 8 == 0 {
+                // Rotating by whole bytes only permutes the bytes, which needs no lookups.
+                let rotated = self.alloc::<ByteArrayRegister<N>>();
+                let (result_bytes, rotated_bytes) = (result.to_le_bytes(), rotated.to_le_bytes());
+                for i in 0..N {
+                    let byte = result_bytes.get(i).expr();
+                    let rotated_byte = result_bytes.get((i + rotation / 8) % N).expr();
+                    self.set_to_expression(
+                        &rotated_bytes.get(i),
+                        byte.clone() + bit.expr() * (rotated_byte - byte),
+                    );
+                }
+                rotated
+            } else {
+                let rotated = self.bit_rotate_right(&result, rotation, operations);
+                self.select(&bit, &rotated, &result)
+            };
+        }
+        result
+    }
 }
@@ -1,5 +1,9 @@
 use crate::chip::arithmetic::expression::ArithmeticExpression;
 use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
 use crate::chip::register::Register;
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
 use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
@@ -61,4 +65,167 @@ impl<L: AirParameters> AirBuilder<L> {
             carry = next_carry.expr();
         }
     }
+
+    /// Computes `a >> shift` for a shift amount held in a register.
+    ///
+    /// The amount is decomposed into `log2(8 * N)` bits, which constrains it to be less than
+    /// `8 * N`, and each bit selects whether to shift by the corresponding power of two. `N` must
+    /// be a power of two.
+    pub fn bit_shr_var<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: &ElementRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        self.bit_shift_var(a, shift, false, operations)
+    }
+
+    pub fn bit_shl<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        self.set_bit_shl(a, shift, &result, operations);
+        result
+    }
+
+    /// Sets `result` to `a << shift`.
+    ///
+    /// Each byte is split by a `ShrCarry` lookup by `8 - shift % 8` into its upper bits, which
+    /// move to the next byte, and its lower bits, which stay in place.
+    pub fn set_bit_shl<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: usize,
+        result: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let a_bytes = a.to_le_bytes();
+        let result_bytes = result.to_le_bytes();
+
+        let shift = shift % (N * 8);
+        let byte_shift = shift / 8;
+        let bit_shift = shift % 8;
+
+        for i in 0..byte_shift {
+            self.assert_zero(&result_bytes.get(i));
+        }
+
+        if bit_shift == 0 {
+            for i in byte_shift..N {
+                self.set_to_expression(&result_bytes.get(i), a_bytes.get(i - byte_shift).expr());
+            }
+            return;
+        }
+
+        let mult = L::Field::from_canonical_u32(1 << bit_shift);
+        let mut carry = ArithmeticExpression::zero();
+        for i in byte_shift..N {
+            let (high_bits, low_bits) =
+                (self.alloc::<ByteRegister>(), self.alloc::<ByteRegister>());
+            let shr_carry = ByteOperation::ShrCarry(
+                a_bytes.get(i - byte_shift),
+                (8 - bit_shift) as u8,
+                high_bits,
+                low_bits,
+            );
+            self.set_byte_operation(&shr_carry, operations);
+            let expected_res = low_bits.expr() * mult + carry.clone();
+            self.set_to_expression(&result_bytes.get(i), expected_res);
+            carry = high_bits.expr();
+        }
+    }
+
+    /// Computes `a << shift` for a shift amount held in a register.
+    ///
+    /// As in `bit_shr_var`, the amount is constrained to be less than `8 * N` and `N` must be a
+    /// power of two.
+    pub fn bit_shl_var<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: &ElementRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        self.bit_shift_var(a, shift, true, operations)
+    }
+
+    /// Shifts `a` left if `left` is set and right otherwise, by the amount held in `shift`.
+    fn bit_shift_var<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: &ElementRegister,
+        left: bool,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let shift_bits = self.shift_amount_bits::<N>(shift);
+
+        let mut result = *a;
+        for (k, bit) in shift_bits.iter().enumerate() {
+            let shift = 1 << k;
+            result = if shift % 8 == 0 {
+                // Shifting by whole bytes only moves the bytes, which needs no lookups.
+                let shifted = self.alloc::<ByteArrayRegister<N>>();
+                let (result_bytes, shifted_bytes) = (result.to_le_bytes(), shifted.to_le_bytes());
+                for i in 0..N {
+                    let byte = result_bytes.get(i).expr();
+                    let source = if left {
+                        i.checked_sub(shift / 8)
+                    } else {
+                        Some(i + shift / 8).filter(|&j| j < N)
+                    };
+                    let shifted_byte = source
+                        .map(|j| result_bytes.get(j).expr())
+                        .unwrap_or_else(ArithmeticExpression::zero);
+                    self.set_to_expression(
+                        &shifted_bytes.get(i),
+                        byte.clone() + bit.expr() * (shifted_byte - byte),
+                    );
+                }
+                shifted
+            } else {
+                let shifted = if left {
+                    self.bit_shl(&result, shift, operations)
+                } else {
+                    self.bit_shr(&result, shift, operations)
+                };
+                self.select(&bit, &shifted, &result)
+            };
+        }
+        result
+    }
+
+    /// Decomposes a shift amount of a `ByteArrayRegister<N>` into `log2(8 * N)` bits.
+    pub(crate) fn shift_amount_bits<const N: usize>(
+        &mut self,
+        shift: &ElementRegister,
+    ) -> ArrayRegister<BitRegister> {
+        assert!(
+            N.is_power_of_two(),
+            "variable shifts require N to be a power of two, got {}",
+            N
+        );
+        let num_bits = (N * 8).trailing_zeros() as usize;
+        let bits = self.alloc_array::<BitRegister>(num_bits);
+        self.register_air_instruction_internal(AirInstruction::bit_decomposition(
+            shift.expr(),
+            bits,
+        ));
+        bits
+    }
 }
@@ -0,0 +1,303 @@
+use super::add::ByteArrayAdd;
+use super::mul::ByteArrayMul;
+use super::sub::ByteArraySub;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::register::ByteArrayRegister;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the sign bit of `a` as a two's complement integer.
+    ///
+    /// The sign is the most significant bit of the last byte, extracted with a shift lookup.
+    pub fn int_sign_bit<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let sign_byte = self.alloc::<ByteRegister>();
+        let shr = ByteOperation::ShrConst(a.to_le_bytes().get(N - 1), 7, sign_byte);
+        self.set_byte_operation(&shr, operations);
+
+        let sign = self.alloc::<BitRegister>();
+        self.set_to_expression(&sign, sign_byte.expr());
+        sign
+    }
+
+    /// Computes `a + b` modulo `2^(8 * N)`, returning the result and whether the addition of `a`
+    /// and `b` as signed integers overflows.
+    ///
+    /// The addition is done on the `u32` limbs of the arrays, so `N` must be a multiple of 4.
+    pub fn overflowing_int_add<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> (ByteArrayRegister<N>, BitRegister)
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        let carry = self.alloc::<BitRegister>();
+        self.set_add_u32_limbs(a, b, &None, &result, &carry, operations);
+
+        // The addition overflows if `a` and `b` have the same sign and the result does not.
+        let a_sign = self.int_sign_bit(a, operations);
+        let b_sign = self.int_sign_bit(b, operations);
+        let result_sign = self.int_sign_bit(&result, operations);
+        let signs_differ = self.bit_xor(&a_sign, &b_sign);
+        let result_sign_differs = self.bit_xor(&a_sign, &result_sign);
+
+        let overflow = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &overflow,
+            signs_differ.not_expr() * result_sign_differs.expr(),
+        );
+        (result, overflow)
+    }
+
+    /// Computes `a + b` modulo `2^(8 * N)` for signed integers `a` and `b`.
+    ///
+    /// In two's complement, this coincides with the unsigned operation.
+    pub fn int_add<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteArrayAdd<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        let carry = self.alloc::<BitRegister>();
+        self.set_add_u32_limbs(a, b, &None, &result, &carry, operations);
+        result
+    }
+
+    /// Computes `a - b` modulo `2^(8 * N)`, returning the result and whether the subtraction of
+    /// `a` and `b` as signed integers overflows.
+    ///
+    /// The subtraction is done on the `u32` limbs of the arrays, so `N` must be a multiple of 4.
+    pub fn overflowing_int_sub<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> (ByteArrayRegister<N>, BitRegister)
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.wrapping_sub(a, b, operations);
+
+        // The subtraction overflows if `a` and `b` have different signs and the result does not
+        // have the sign of `a`.
+        let a_sign = self.int_sign_bit(a, operations);
+        let b_sign = self.int_sign_bit(b, operations);
+        let result_sign = self.int_sign_bit(&result, operations);
+        let signs_differ = self.bit_xor(&a_sign, &b_sign);
+        let result_sign_differs = self.bit_xor(&a_sign, &result_sign);
+
+        let overflow = self.alloc::<BitRegister>();
+        self.set_to_expression(&overflow, signs_differ.expr() * result_sign_differs.expr());
+        (result, overflow)
+    }
+
+    /// Computes `a - b` modulo `2^(8 * N)` for signed integers `a` and `b`.
+    ///
+    /// In two's complement, this coincides with the unsigned operation.
+    pub fn int_sub<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        self.wrapping_sub(a, b, operations)
+    }
+
+    /// Computes `a * b` modulo `2^(8 * N)` for signed integers `a` and `b`.
+    ///
+    /// In two's complement, this coincides with the unsigned operation.
+    pub fn int_mul<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteArrayMul> + From<ByteOperationInstruction>,
+    {
+        self.wrapping_mul(a, b, operations)
+    }
+
+    /// Computes the bit of `a < b` as signed integers.
+    ///
+    /// If the signs of `a` and `b` differ, `a < b` exactly when `a` is negative. Otherwise, the
+    /// comparison agrees with the unsigned one.
+    pub fn int_lt<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let a_sign = self.int_sign_bit(a, operations);
+        let b_sign = self.int_sign_bit(b, operations);
+        let signs_differ = self.bit_xor(&a_sign, &b_sign);
+        let unsigned_lt = self.uint_lt(a, b, operations);
+
+        let result = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &result,
+            signs_differ.expr() * a_sign.expr() + signs_differ.not_expr() * unsigned_lt.expr(),
+        );
+        result
+    }
+
+    /// Computes the bit of `a <= b` as signed integers, which is the negation of `b < a`.
+    pub fn int_lte<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let b_lt_a = self.int_lt(b, a, operations);
+        let result = self.alloc::<BitRegister>();
+        self.set_to_expression(&result, b_lt_a.not_expr());
+        result
+    }
+
+    /// Computes the bit of `a > b` as signed integers, which is `b < a`.
+    pub fn int_gt<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        self.int_lt(b, a, operations)
+    }
+
+    /// Computes the bit of `a >= b` as signed integers, which is `b <= a`.
+    pub fn int_gte<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> BitRegister
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        self.int_lte(b, a, operations)
+    }
+
+    /// Computes the arithmetic shift right of `a` by a constant `shift`, filling the vacated bits
+    /// with the sign bit of `a`.
+    pub fn int_sar<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: usize,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        self.set_int_sar(a, shift, &result, operations);
+        result
+    }
+
+    pub fn set_int_sar<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: usize,
+        result: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let shift = shift % (N * 8);
+        let sign = self.int_sign_bit(a, operations);
+        let logical_shr = self.bit_shr(a, shift, operations);
+
+        // The bits vacated by the logical shift are zero, so adding the sign mask does not carry.
+        let fill_start = N * 8 - shift;
+        let result_bytes = result.to_le_bytes();
+        for (i, byte) in logical_shr.to_le_bytes().iter().enumerate() {
+            let mask = (0..8)
+                .filter(|j| 8 * i + j >= fill_start)
+                .fold(0u32, |acc, j| acc | (1 << j));
+            let mask = L::Field::from_canonical_u32(mask);
+            self.set_to_expression(&result_bytes.get(i), byte.expr() + sign.expr() * mask);
+        }
+    }
+
+    /// Computes the arithmetic shift right of `a` by a shift amount held in a register.
+    ///
+    /// Complementing a negative `a` makes it non-negative, so its arithmetic shift is the
+    /// complement of the logical shift of the complement. The amount is constrained as in
+    /// `bit_shr_var`.
+    pub fn int_sar_var<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        shift: &ElementRegister,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteOperationInstruction>,
+    {
+        let sign = self.int_sign_bit(a, operations);
+        let complemented = self.complement_if(a, &sign);
+        let logical_shr = self.bit_shr_var(&complemented, shift, operations);
+        self.complement_if(&logical_shr, &sign)
+    }
+
+    /// Computes the bitwise complement of `a` if `bit` is set, and `a` otherwise.
+    fn complement_if<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        bit: &BitRegister,
+    ) -> ByteArrayRegister<N> {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        let max = L::Field::from_canonical_u8(u8::MAX);
+        let two = L::Field::from_canonical_u8(2);
+        for (byte, result_byte) in a.to_le_bytes().iter().zip(result.to_le_bytes().iter()) {
+            self.set_to_expression(
+                &result_byte,
+                byte.expr()
+                    + bit.expr() * (ArithmeticExpression::from_constant(max) - byte.expr() * two),
+            );
+        }
+        result
+    }
+
+    /// Computes the bit `a xor b`.
+    fn bit_xor(&mut self, a: &BitRegister, b: &BitRegister) -> BitRegister {
+        let result = self.alloc::<BitRegister>();
+        self.set_to_expression(
+            &result,
+            a.expr() + b.expr() - a.expr() * b.expr() * L::Field::from_canonical_u8(2),
+        );
+        result
+    }
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::register::{ByteArrayRegister, U32Register};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// Subtracting byte arrays as elements mod 2^{8 * N}
+///
+/// Constrains `a - b - in_borrow = result - 2^{8 * N} * result_borrow`. Assumes 2^N < FIELD_SIZE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteArraySub<const N: usize> {
+    pub a: ByteArrayRegister<N>,
+    pub b: ByteArrayRegister<N>,
+    in_borrow: Option<BitRegister>,
+    pub result: ByteArrayRegister<N>,
+    result_borrow: BitRegister,
+}
+
+impl<const N: usize> ByteArraySub<N> {
+    pub fn new(
+        a: ByteArrayRegister<N>,
+        b: ByteArrayRegister<N>,
+        in_borrow: Option<BitRegister>,
+        result: ByteArrayRegister<N>,
+        result_borrow: BitRegister,
+    ) -> Self {
+        Self {
+            a,
+            b,
+            in_borrow,
+            result,
+            result_borrow,
+        }
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes `a - b - in_borrow` modulo `2^(8 * N)`, returning the result and the borrow.
+    ///
+    /// The subtraction is done on the `u32` limbs of the arrays, so `N` must be a multiple of 4.
+    pub fn borrowing_sub<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        in_borrow: &Option<BitRegister>,
+        operations: &mut ByteLookupOperations,
+    ) -> (ByteArrayRegister<N>, BitRegister)
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let result = self.alloc::<ByteArrayRegister<N>>();
+        let out_borrow = self.alloc::<BitRegister>();
+        self.set_sub(a, b, in_borrow, &result, &out_borrow, operations);
+
+        (result, out_borrow)
+    }
+
+    /// Computes `a - b` modulo `2^(8 * N)`.
+    pub fn wrapping_sub<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        operations: &mut ByteLookupOperations,
+    ) -> ByteArrayRegister<N>
+    where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let (result, _) = self.borrowing_sub(a, b, &None, operations);
+        result
+    }
+
+    pub fn set_sub<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        b: &ByteArrayRegister<N>,
+        in_borrow: &Option<BitRegister>,
+        result: &ByteArrayRegister<N>,
+        out_borrow: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let a_as_register = a.to_le_limbs::<4>();
+        let b_as_register = b.to_le_limbs::<4>();
+        let result_as_register = result.to_le_limbs::<4>();
+
+        let num_limbs = N / 4;
+        let mut borrow = *in_borrow;
+        for i in 0..num_limbs {
+            let limb_borrow = if i == num_limbs - 1 {
+                *out_borrow
+            } else {
+                self.alloc::<BitRegister>()
+            };
+            self.set_sub_u32(
+                &a_as_register.get(i),
+                &b_as_register.get(i),
+                &borrow,
+                &result_as_register.get(i),
+                &limb_borrow,
+                operations,
+            );
+            borrow = Some(limb_borrow);
+        }
+    }
+
+    pub fn set_sub_u32(
+        &mut self,
+        a: &U32Register,
+        b: &U32Register,
+        in_borrow: &Option<BitRegister>,
+        result: &U32Register,
+        out_borrow: &BitRegister,
+        operations: &mut ByteLookupOperations,
+    ) where
+        L::Instruction: From<ByteArraySub<4>> + From<ByteOperationInstruction>,
+    {
+        let sub = ByteArraySub::<4>::new(*a, *b, *in_borrow, *result, *out_borrow);
+        self.register_instruction(sub);
+
+        for byte in result.to_le_bytes() {
+            let result_range = ByteOperation::Range(byte);
+            self.set_byte_operation(&result_range, operations);
+        }
+    }
+}
+
+impl<AP: AirParser, const N: usize> AirConstraint<AP> for ByteArraySub<N> {
+    fn eval(&self, parser: &mut AP) {
+        assert!(N <= 4, "ByteArraySub<N> only supports N <= 4");
+        let a = self.a.eval(parser);
+        let b = self.b.eval(parser);
+        let in_borrow = self.in_borrow.map(|x| x.eval(parser));
+        let result = self.result.eval(parser);
+        let result_borrow = self.result_borrow.eval(parser);
+
+        let mut a_val = parser.zero();
+        let mut b_val = parser.zero();
+        let mut result_val = parser.zero();
+
+        for (i, ((a_byte, b_byte), res_byte)) in a.into_iter().zip(b).zip(result).enumerate() {
+            let mult = AP::Field::from_canonical_u32(1 << (8 * i));
+            let a_byte_times_mult = parser.mul_const(a_byte, mult);
+            let b_byte_times_mult = parser.mul_const(b_byte, mult);
+            let res_byte_times_mult = parser.mul_const(res_byte, mult);
+
+            a_val = parser.add(a_val, a_byte_times_mult);
+            b_val = parser.add(b_val, b_byte_times_mult);
+            result_val = parser.add(result_val, res_byte_times_mult);
+        }
+
+        let b_plus_borrow = match in_borrow {
+            Some(borrow) => parser.add(b_val, borrow),
+            None => b_val,
+        };
+        let a_minus_b = parser.sub(a_val, b_plus_borrow);
+        let two_power = AP::Field::from_canonical_u64(1 << (8 * N));
+        let borrow_times_mod = parser.mul_const(result_borrow, two_power);
+        let result_minus_borrow = parser.sub(result_val, borrow_times_mod);
+        let constraint = parser.sub(a_minus_b, result_minus_borrow);
+        parser.constraint(constraint);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for ByteArraySub<4> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = writer.read(&self.a, row_index);
+        let b = writer.read(&self.b, row_index);
+        let in_borrow = self.in_borrow.map(|x| writer.read(&x, row_index));
+
+        let a_val = u32::from_le_bytes(a.map(|x| x.as_canonical_u64() as u8));
+        let b_val = u32::from_le_bytes(b.map(|x| x.as_canonical_u64() as u8));
+        let in_borrow_val = in_borrow
+            .map(|x| x.as_canonical_u64() as u8 == 1)
+            .unwrap_or(false);
+
+        let (result, result_borrow) = a_val.borrowing_sub(b_val, in_borrow_val);
+        let result_bytes = result.to_le_bytes().map(|x| F::from_canonical_u8(x));
+
+        writer.write(&self.result, &result_bytes, row_index);
+        writer.write(
+            &self.result_borrow,
+            &F::from_canonical_u8(result_borrow as u8),
+            row_index,
+        );
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = writer.read(&self.a);
+        let b = writer.read(&self.b);
+        let in_borrow = self.in_borrow.map(|x| writer.read(&x));
+
+        let a_val = u32::from_le_bytes(a.map(|x| x.as_canonical_u64() as u8));
+        let b_val = u32::from_le_bytes(b.map(|x| x.as_canonical_u64() as u8));
+        let in_borrow_val = in_borrow
+            .map(|x| x.as_canonical_u64() as u8 == 1)
+            .unwrap_or(false);
+
+        let (result, result_borrow) = a_val.borrowing_sub(b_val, in_borrow_val);
+        let result_bytes = result.to_le_bytes().map(|x| F::from_canonical_u8(x));
+
+        writer.write(&self.result, &result_bytes);
+        writer.write(
+            &self.result_borrow,
+            &F::from_canonical_u8(result_borrow as u8),
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::bytes::register::ByteRegister;
+use super::register::{ByteArrayRegister, U160Register, U64Register};
+use super::util::Endianness;
+use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::AirParameters;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Packs `bytes` into `U64Register` words, each read from eight consecutive bytes in the byte
+    /// order given by `endianness`.
+    ///
+    /// Little-endian words are a view of the same registers. Big-endian words are new registers
+    /// whose bytes are constrained to be the reversal of the corresponding chunk of `bytes`.
+    pub fn u64_words_from_bytes(
+        &mut self,
+        bytes: &ArrayRegister<ByteRegister>,
+        endianness: Endianness,
+    ) -> ArrayRegister<U64Register> {
+        assert_eq!(
+            bytes.len() % 8,
+            0,
+            "Number of bytes must be a multiple of 8"
+        );
+        let le_bytes = match endianness {
+            Endianness::Little => *bytes,
+            Endianness::Big => self.reverse_u64_bytes(bytes),
+        };
+        ArrayRegister::from_register_unsafe(*le_bytes.register())
+    }
+
+    /// Unpacks `words` into bytes, writing each word as eight consecutive bytes in the byte order
+    /// given by `endianness`.
+    ///
+    /// This is the inverse of `u64_words_from_bytes`.
+    pub fn u64_words_to_bytes(
+        &mut self,
+        words: &ArrayRegister<U64Register>,
+        endianness: Endianness,
+    ) -> ArrayRegister<ByteRegister> {
+        let le_bytes = ArrayRegister::<ByteRegister>::from_register_unsafe(*words.register());
+        match endianness {
+            Endianness::Little => le_bytes,
+            Endianness::Big => self.reverse_u64_bytes(&le_bytes),
+        }
+    }
+
+    /// Returns the Ethereum address of the Keccak-256 digest given by the four little-endian
+    /// lanes `digest`, which is the integer of the last 20 bytes of the digest read in big-endian
+    /// order.
+    ///
+    /// The address is a new register whose bytes are constrained to be the last 20 bytes of the
+    /// digest in reverse order, so it can be compared with other `U160Register` integers.
+    pub fn u160_from_keccak_digest(&mut self, digest: &ArrayRegister<U64Register>) -> U160Register {
+        assert_eq!(digest.len(), 4, "a Keccak-256 digest has four lanes");
+        let digest_bytes = ArrayRegister::<ByteRegister>::from_register_unsafe(*digest.register());
+        let address = if digest.is_trace() {
+            self.alloc::<U160Register>()
+        } else {
+            self.alloc_public_unchecked::<U160Register>()
+        };
+
+        for (i, target) in address.to_le_bytes().iter().enumerate() {
+            let source = digest_bytes.get(digest_bytes.len() - 1 - i);
+            if digest.is_trace() {
+                self.set_to_expression(&target, source.expr());
+            } else {
+                self.set_to_expression_public(&target, source.expr());
+            }
+        }
+        address
+    }
+
+    /// Returns `a` with the order of its bytes reversed.
+    ///
+    /// This converts between the little-endian and big-endian readings of the same bytes, for
+    /// example to apply little-endian arithmetic to the words of a big-endian protocol.
+    pub fn bswap<const N: usize>(&mut self, a: &ByteArrayRegister<N>) -> ByteArrayRegister<N> {
+        let result = if a.is_trace() {
+            self.alloc::<ByteArrayRegister<N>>()
+        } else {
+            self.alloc_public_unchecked::<ByteArrayRegister<N>>()
+        };
+        self.set_bswap(a, &result);
+        result
+    }
+
+    /// Constrains the bytes of `result` to be the bytes of `a` in reverse order.
+    pub fn set_bswap<const N: usize>(
+        &mut self,
+        a: &ByteArrayRegister<N>,
+        result: &ByteArrayRegister<N>,
+    ) {
+        let (a_bytes, result_bytes) = (a.to_le_bytes(), result.to_le_bytes());
+        for i in 0..N {
+            let source = a_bytes.get(Endianness::Big.byte_index::<N>(i));
+            let target = result_bytes.get(i);
+            if result.is_trace() {
+                self.set_to_expression(&target, source.expr());
+            } else {
+                self.set_to_expression_public(&target, source.expr());
+            }
+        }
+    }
+
+    /// Returns new registers holding `bytes` with each chunk of eight bytes reversed.
+    fn reverse_u64_bytes(
+        &mut self,
+        bytes: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ByteRegister> {
+        let reversed = if bytes.is_trace() {
+            self.alloc_array::<ByteRegister>(bytes.len())
+        } else {
+            self.alloc_array_public_unchecked::<ByteRegister>(bytes.len())
+        };
+
+        for i in 0..bytes.len() {
+            let source = bytes.get(8 * (i / 8) + Endianness::Big.byte_index::<8>(i % 8));
+            let target = reversed.get(i);
+            if bytes.is_trace() {
+                self.set_to_expression(&target, source.expr());
+            } else {
+                self.set_to_expression_public(&target, source.expr());
+            }
+        }
+        reversed
+    }
+}
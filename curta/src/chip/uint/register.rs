@@ -19,6 +19,9 @@ pub struct ByteArrayRegister<const N: usize>(MemorySlice);
 
 pub type U32Register = ByteArrayRegister<4>;
 pub type U64Register = ByteArrayRegister<8>;
+pub type U128Register = ByteArrayRegister<16>;
+pub type U160Register = ByteArrayRegister<20>;
+pub type U256Register = ByteArrayRegister<32>;
 
 impl<const N: usize> ByteArrayRegister<N> {
     pub fn to_le_bytes(&self) -> ArrayRegister<ByteRegister> {
@@ -37,7 +40,7 @@ impl<const N: usize> ByteArrayRegister<N> {
 }
 
 impl<const N: usize> RegisterSerializable for ByteArrayRegister<N> {
-    const CELL: CellType = CellType::Element;
+    const CELL: CellType = CellType::Byte;
 
     fn register(&self) -> &MemorySlice {
         &self.0
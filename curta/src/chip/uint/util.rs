@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::math::field::{Field, PrimeField64};
 
 #[inline]
@@ -19,3 +21,57 @@ pub fn u64_to_le_field_bytes<F: Field>(value: u64) -> [F; 8] {
 pub fn u64_from_le_field_bytes<F: PrimeField64>(bytes: &[F; 8]) -> u64 {
     u64::from_le_bytes(bytes.map(|x| x.as_canonical_u64() as u8))
 }
+
+#[inline]
+pub fn u32_to_be_field_bytes<F: Field>(value: u32) -> [F; 4] {
+    value.to_be_bytes().map(F::from_canonical_u8)
+}
+
+#[inline]
+pub fn u32_from_be_field_bytes<F: PrimeField64>(bytes: &[F; 4]) -> u32 {
+    u32::from_be_bytes(bytes.map(|x| x.as_canonical_u64() as u8))
+}
+
+#[inline]
+pub fn u64_to_be_field_bytes<F: Field>(value: u64) -> [F; 8] {
+    value.to_be_bytes().map(F::from_canonical_u8)
+}
+
+#[inline]
+pub fn u64_from_be_field_bytes<F: PrimeField64>(bytes: &[F; 8]) -> u64 {
+    u64::from_be_bytes(bytes.map(|x| x.as_canonical_u64() as u8))
+}
+
+/// The byte order in which integers are packed into or read from a sequence of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    #[inline]
+    pub fn u64_to_field_bytes<F: Field>(self, value: u64) -> [F; 8] {
+        match self {
+            Endianness::Little => u64_to_le_field_bytes(value),
+            Endianness::Big => u64_to_be_field_bytes(value),
+        }
+    }
+
+    #[inline]
+    pub fn u64_from_field_bytes<F: PrimeField64>(self, bytes: &[F; 8]) -> u64 {
+        match self {
+            Endianness::Little => u64_from_le_field_bytes(bytes),
+            Endianness::Big => u64_from_be_field_bytes(bytes),
+        }
+    }
+
+    /// The position of the `i`-th least significant byte of an `N`-byte integer.
+    #[inline]
+    pub fn byte_index<const N: usize>(self, i: usize) -> usize {
+        match self {
+            Endianness::Little => i,
+            Endianness::Big => N - 1 - i,
+        }
+    }
+}
@@ -48,6 +48,9 @@ pub trait Builder: Sized {
     }
 
     /// Allocates a register in public inputs.
+    ///
+    /// The value of the register is validated according to its type, e.g. bits are constrained to
+    /// be boolean and bytes are range checked.
     fn alloc_public<T: Register>(&mut self) -> T {
         self.api().alloc_public()
     }
@@ -57,6 +60,24 @@ pub trait Builder: Sized {
         self.api().alloc_array_public(len)
     }
 
+    /// Allocates a register in public inputs without validating its value.
+    ///
+    /// The caller is responsible for constraining the value of the register.
+    fn alloc_public_unchecked<T: Register>(&mut self) -> T {
+        self.api().alloc_public_unchecked()
+    }
+
+    /// Allocates an array register in public inputs without validating its values.
+    fn alloc_array_public_unchecked<T: Register>(&mut self, len: usize) -> ArrayRegister<T> {
+        self.api().alloc_array_public_unchecked(len)
+    }
+
+    /// Allocates an element register in public inputs whose value is constrained to be in
+    /// `[0, max]`.
+    fn alloc_public_bounded(&mut self, max: u64) -> ElementRegister {
+        self.api().alloc_public_bounded(max)
+    }
+
     /// Allocates a constant register with set value `value`.
     fn constant<T: Register>(&mut self, value: &T::Value<Self::Field>) -> T {
         self.api().constant(value)
@@ -293,7 +314,7 @@ pub trait Builder: Sized {
         &mut self,
         expression: ArithmeticExpression<Self::Field>,
     ) -> T {
-        let register = self.alloc_public::<T>();
+        let register = self.alloc_public_unchecked::<T>();
         self.set_to_expression(&register, expression);
         register
     }
@@ -436,6 +457,18 @@ pub trait Builder: Sized {
     {
         self.api().bit_decomposition(limb, start_bit, end_bit)
     }
+
+    /// Opens a segment of `num_rows` rows named `name` and returns its selector.
+    ///
+    /// See `AirBuilder::begin_segment` for details.
+    fn begin_segment(&mut self, name: &str, num_rows: usize) -> BitRegister {
+        self.api().begin_segment(name, num_rows)
+    }
+
+    /// Closes the open segment.
+    fn end_segment(&mut self) {
+        self.api().end_segment()
+    }
 }
 
 impl<L: AirParameters> Builder for AirBuilder<L> {
@@ -1,7 +1,11 @@
 use super::air::ByteParameters;
-use super::stark::ByteStark;
+use super::stark::{ByteRangeData, ByteStark};
+use crate::air::RAirData;
 use crate::chip::builder::AirBuilder;
+use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::RegisterSerializable;
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
 use crate::chip::uint::operations::instruction::UintInstructions;
 use crate::chip::AirParameters;
@@ -39,6 +43,7 @@ where
     pub fn new() -> Self {
         let mut api = AirBuilder::<L>::new();
         let clk = api.clock();
+        api.internal_range_check = false;
         api.init_local_memory();
         BytesBuilder {
             api,
@@ -64,8 +69,42 @@ where
         let multiplicity_data = api.register_byte_lookup(&mut lookup_table, operations);
         lookup_builder.constraint_byte_lookup_table(&lookup_table);
 
-        let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
+        // The arithmetic columns and public arithmetic registers are range checked in the lookup
+        // trace, which has a row for every `u16` value.
+        let values = ArrayRegister::<ElementRegister>::from_register_unsafe(MemorySlice::Local(
+            0,
+            L::NUM_ARITHMETIC_COLUMNS,
+        ))
+        .into_iter()
+        .chain(api.global_arithmetic.iter().copied())
+        .collect::<Vec<_>>();
+        let range_data = (!values.is_empty()).then(|| {
+            let table = lookup_builder.clock();
+            let multiplicity = lookup_builder.alloc_array::<ElementRegister>(1);
+            let mut table_data = lookup_builder.new_lookup(&[table], &multiplicity);
+            let lookup_values = table_data.register_lookup_values(&mut api, &values);
+            lookup_builder.constrain_element_lookup_table(table_data);
+            ByteRangeData {
+                table,
+                multiplicity,
+                lookup_values,
+            }
+        });
+
         let (air, trace_data) = api.build();
+        let config = StarkyConfig::<C, D>::standard_fast_config_with_degree(
+            num_rows,
+            air.constraint_degree(),
+        );
+        if let Some(segment) = trace_data.segments.last() {
+            assert!(
+                segment.rows.end <= num_rows,
+                "Segment {} ends at row {}, but the trace has {} rows",
+                segment.name,
+                segment.rows.end,
+                num_rows
+            );
+        }
         let stark = Starky::new(air);
 
         let lookup_config = StarkyConfig::<C, D>::standard_fast_config(NUM_LOOKUP_ROWS);
@@ -81,6 +120,7 @@ where
             lookup_stark,
             lookup_air_data: lookup_trace_data,
             lookup_table,
+            range_data,
         }
     }
 }
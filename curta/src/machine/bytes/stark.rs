@@ -13,12 +13,16 @@ use super::air::ByteParameters;
 use super::proof::{
     ByteStarkChallenges, ByteStarkChallengesTarget, ByteStarkProof, ByteStarkProofTarget,
 };
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::table::lookup::values::LogLookupValues;
 use crate::chip::trace::data::AirTraceData;
 use crate::chip::trace::writer::{InnerWriterData, TraceWriter};
 use crate::chip::uint::bytes::lookup_table::multiplicity_data::ByteMultiplicityData;
 use crate::chip::uint::bytes::lookup_table::table::ByteLogLookupTable;
 use crate::chip::{AirParameters, Chip};
 use crate::machine::bytes::builder::NUM_LOOKUP_ROWS;
+use crate::math::prelude::*;
 use crate::maybe_rayon::*;
 use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
 use crate::plonky2::stark::prover::{AirCommitment, StarkyProver};
@@ -40,6 +44,16 @@ pub struct ByteStark<L: AirParameters, C, const D: usize> {
     pub(crate) lookup_stark: Starky<Chip<ByteParameters<L::Field, L::CubicParams>>>,
     pub(crate) lookup_air_data: AirTraceData<ByteParameters<L::Field, L::CubicParams>>,
     pub(crate) lookup_table: ByteLogLookupTable<L::Field, L::CubicParams>,
+    pub(crate) range_data: Option<ByteRangeData<L::Field, L::CubicParams>>,
+}
+
+/// The `u16` range checks of the arithmetic columns, looked up in a table of the lookup trace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct ByteRangeData<F, E> {
+    pub(crate) table: ElementRegister,
+    pub(crate) multiplicity: ArrayRegister<ElementRegister>,
+    pub(crate) lookup_values: LogLookupValues<ElementRegister, F, E>,
 }
 
 impl<L: AirParameters, C, const D: usize> ByteStark<L, C, D>
@@ -96,6 +110,11 @@ where
 
         // Write lookup table values
         self.lookup_table.write_table_entries(&lookup_writer);
+        if let Some(range_data) = &self.range_data {
+            for i in 0..NUM_LOOKUP_ROWS {
+                lookup_writer.write(&range_data.table, &L::Field::from_canonical_usize(i), i);
+            }
+        }
         for i in 0..NUM_LOOKUP_ROWS {
             lookup_writer.write_row_instructions(&self.lookup_air_data, i);
         }
@@ -103,6 +122,16 @@ where
         let multiplicities = self.multiplicity_data.get_multiplicities(&main_writer);
         lookup_writer
             .write_lookup_multiplicities(self.lookup_table.multiplicities(), &[multiplicities]);
+        if let Some(range_data) = &self.range_data {
+            let multiplicities = main_writer.get_multiplicities_from_fn(
+                1,
+                NUM_LOOKUP_ROWS,
+                &range_data.lookup_values.trace_values,
+                &range_data.lookup_values.public_values,
+                |element| (element.as_canonical_u64() as usize, 0),
+            );
+            lookup_writer.write_lookup_multiplicities(range_data.multiplicity, &[multiplicities]);
+        }
 
         (main_writer, lookup_writer)
     }
@@ -138,9 +167,9 @@ where
         public_values: &[L::Field],
         challenger: &mut Challenger<L::Field, C::Hasher>,
         timing: &mut TimingTree,
-    ) -> (AirCommitment<L::Field, C, D>, AirCommitment<L::Field, C, D>) {
+    ) -> Result<(AirCommitment<L::Field, C, D>, AirCommitment<L::Field, C, D>)> {
         // Absorve public values into the challenger.
-        challenger.observe_elements(public_values);
+        let public_challenges = self.stark.observe_public_inputs(challenger, public_values);
 
         // Generate execution traces.
         let (main_writer, lookup_writer) =
@@ -187,7 +216,10 @@ where
         challenger.observe_cap(&lookup_execution_commitment.merkle_tree.cap);
 
         // Get random AIR challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
         // Save challenges to both writers.
         main_writer
             .challenges
@@ -203,6 +235,13 @@ where
         // Generate extended traces.
         self.generate_extended_traces(&main_writer, &lookup_writer);
 
+        // Check the constraints on the full traces to report the failing instruction, if any.
+        #[cfg(feature = "debug-constraints")]
+        {
+            self.stark.air.check_constraints(&main_writer)?;
+            self.lookup_stark.air.check_constraints(&lookup_writer)?;
+        }
+
         let InnerWriterData {
             trace: main_trace,
             public: main_public,
@@ -255,7 +294,7 @@ where
         challenger.observe_cap(&lookup_extended_commitment.merkle_tree.cap);
 
         // Return the air commitments.
-        (
+        Ok((
             AirCommitment {
                 trace_commitments: vec![main_execution_commitment, main_extended_commitment],
                 public_inputs: main_public,
@@ -268,7 +307,7 @@ where
                 global_values: lookup_global,
                 challenges: global_challenges,
             },
-        )
+        ))
     }
 
     pub fn prove(
@@ -284,7 +323,7 @@ where
         let (main_air_commitment, lookup_air_commitment) = timed!(
             timing,
             "Generate stark trace",
-            self.generate_trace(execution_trace, public_values, &mut challenger, timing)
+            self.generate_trace(execution_trace, public_values, &mut challenger, timing)?
         );
 
         // Generate individual stark proofs.
@@ -329,14 +368,19 @@ where
         let mut challenger = Challenger::<L::Field, C::Hasher>::new();
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges = self
+            .stark
+            .observe_public_inputs(&mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.main_proof.trace_caps[0]);
         challenger.observe_cap(&proof.lookup_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
@@ -429,14 +473,21 @@ where
         let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges =
+            self.stark
+                .observe_public_inputs_target(builder, &mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.main_proof.trace_caps[0]);
         challenger.observe_cap(&proof.lookup_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(builder, self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges = challenger.get_n_challenges(
+            builder,
+            self.stark.air.num_challenges - data.num_challenges(),
+        );
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
@@ -527,14 +578,18 @@ mod tests {
 
     use super::*;
     use crate::chip::memory::time::Time;
+    use crate::chip::register::bit::BitRegister;
     use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::u16::U16Register;
     use crate::chip::register::Register;
     use crate::chip::trace::writer::InnerWriterData;
+    use crate::chip::uint::bytes::register::ByteRegister;
     use crate::chip::uint::operations::instruction::UintInstruction;
     use crate::chip::uint::register::U32Register;
     use crate::chip::uint::util::u32_to_le_field_bytes;
     use crate::machine::builder::Builder;
     use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::tests::{assert_rejected, verifies};
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
     use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
@@ -650,7 +705,8 @@ mod tests {
         let c = builder.and(&a, &b);
         builder.store(&a_ptr, c, &clk.advance(), None, None, None);
 
-        let a_final = builder.alloc_public::<U32Register>();
+        // The final value is constrained to be equal to `c` in the last row.
+        let a_final = builder.alloc_public_unchecked::<U32Register>();
 
         let num_rows = 1 << 5;
 
@@ -752,7 +808,8 @@ mod tests {
         let c = builder.and(&a, &b);
         builder.store(&a_0_trace, c, &clk.advance(), None, None, None);
 
-        let a_final = builder.alloc_public::<U32Register>();
+        // The final value is constrained to be equal to `c` in the last row.
+        let a_final = builder.alloc_public_unchecked::<U32Register>();
 
         builder.free(&a_0, a_final, &Time::constant(num_rows));
         builder.set_to_expression_last_row(&a_final, c.expr());
@@ -800,4 +857,144 @@ mod tests {
 
         timing.print();
     }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct PublicInputTest;
+
+    impl AirParameters for PublicInputTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 18;
+        const EXTENDED_COLUMNS: usize = 24;
+    }
+
+    /// Proves a stark with a public bit, a public byte and a bounded public element set to the
+    /// given values, and returns whether the proof verifies.
+    fn verify_public_inputs(bit: u64, byte: u64, bounded: u64, max: u64) -> bool {
+        type L = PublicInputTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let mut builder = BytesBuilder::<L>::new();
+
+        let a = builder.alloc::<U32Register>();
+        let b = builder.alloc::<U32Register>();
+        let _ = builder.and(&a, &b);
+
+        let bit_pub = builder.alloc_public::<BitRegister>();
+        let byte_pub = builder.alloc_public::<ByteRegister>();
+        let bounded_pub = builder.alloc_public_bounded(max);
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let writer = TraceWriter::new(&stark.air_data, num_rows);
+        writer.write(&bit_pub, &F::from_canonical_u64(bit), 0);
+        writer.write(&byte_pub, &F::from_canonical_u64(byte), 0);
+        writer.write(&bounded_pub, &F::from_canonical_u64(bounded), 0);
+        writer.write_global_instructions(&stark.air_data);
+
+        let mut rng = rand::thread_rng();
+        for i in 0..num_rows {
+            writer.write(&a, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+            writer.write(&b, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+            writer.write_row_instructions(&stark.air_data, i);
+        }
+
+        let InnerWriterData { trace, public, .. } = writer.into_inner().unwrap();
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_public_input_validation() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        assert!(verify_public_inputs(1, 255, 10, 10));
+        assert!(verify_public_inputs(0, 0, 0, 10));
+
+        // A "bit" with value 2.
+        assert!(!verify_public_inputs(2, 0, 0, 10));
+        // A "byte" with value 300.
+        assert!(!verify_public_inputs(0, 300, 0, 10));
+        // A bounded value above its bound.
+        assert!(!verify_public_inputs(0, 0, 11, 10));
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RangeCheckTest;
+
+    impl AirParameters for RangeCheckTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1;
+        const NUM_FREE_COLUMNS: usize = 18;
+        const EXTENDED_COLUMNS: usize = 30;
+    }
+
+    type RangeCheckStark = ByteStark<RangeCheckTest, CurtaPoseidonGoldilocksConfig, 2>;
+
+    /// A stark of `2^5` rows with a `u16` limb in the arithmetic columns and a public `u16` limb,
+    /// and its trace and public values with the limbs set to the given values.
+    fn range_check_trace(
+        limb: u64,
+        public_limb: u64,
+    ) -> (
+        RangeCheckStark,
+        AirTrace<GoldilocksField>,
+        Vec<GoldilocksField>,
+    ) {
+        type L = RangeCheckTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let mut builder = BytesBuilder::<L>::new();
+
+        let a = builder.alloc::<U32Register>();
+        let b = builder.alloc::<U32Register>();
+        let _ = builder.and(&a, &b);
+
+        let limb_reg = builder.alloc::<U16Register>();
+        let public_limb_reg = builder.alloc_public::<U16Register>();
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let writer = TraceWriter::new(&stark.air_data, num_rows);
+        writer.write(&public_limb_reg, &F::from_canonical_u64(public_limb), 0);
+        writer.write_global_instructions(&stark.air_data);
+
+        let mut rng = rand::thread_rng();
+        for i in 0..num_rows {
+            writer.write(&a, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+            writer.write(&b, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+            writer.write(&limb_reg, &F::from_canonical_u64(limb), i);
+            writer.write_row_instructions(&stark.air_data, i);
+        }
+
+        let InnerWriterData { trace, public, .. } = writer.into_inner().unwrap();
+        (stark, trace, public)
+    }
+
+    #[test]
+    fn test_range_checks_in_lookup_trace() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for (limb, public_limb) in [(0, 0), (u16::MAX as u64, u16::MAX as u64)] {
+            let (stark, trace, public) = range_check_trace(limb, public_limb);
+            assert!(verifies(&stark, &trace, &public));
+        }
+
+        // An out-of-range value has no row in the range table, in the arithmetic columns or in
+        // the public values.
+        for (limb, public_limb) in [(1 << 16, 0), (0, 1 << 16)] {
+            let (stark, trace, public) = range_check_trace(limb, public_limb);
+            assert_rejected(&stark, &trace, &public);
+        }
+    }
 }
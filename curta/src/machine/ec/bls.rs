@@ -0,0 +1,287 @@
+//! BLS signature verification over BLS12-381.
+//!
+//! Public keys are points of G1 and signatures are points of G2, as for Ethereum validators. A
+//! signature `S` on a message `m` under the secret keys `sk_1, ..., sk_n` is the aggregate
+//! `sum_i [sk_i]H(m)` of the individual signatures, where `H` hashes to G2. It is valid under
+//! the public keys `P_i = [sk_i]G` if
+//!
+//! e(P_1 + ... + P_n, H(m)) = e(G, S)
+//!
+//! which the gadget checks as `e(P, H(m)) * e(-G, S) = 1` with a single final exponentiation.
+//!
+//! The gadget hashes the message to `H(m)` with `BytesBuilder::bls12_381_hash_to_g2` in the same
+//! AIR, with the domain separation tag `DST`, and checks that the signature is in G2.
+//!
+//! The public keys are not checked to be in G1. They must come with a valid proof of
+//! possession, whose verification includes the check and prevents rogue key attacks on the
+//! aggregate.
+//!
+//! Reference: https://datatracker.ietf.org/doc/draft-irtf-cfrg-bls-signature/
+
+use num::BigUint;
+
+use super::hash_to_curve::{hash_to_g2, HashToG2Gadget};
+use crate::chip::ec::bls12_381::g2::{G2AffinePoint, G2AirWriter, G2PointRegister};
+use crate::chip::ec::bls12_381::hash_to_curve::is_in_g2;
+use crate::chip::ec::bls12_381::pairing::{final_exponentiation, miller_loop};
+use crate::chip::ec::bls12_381::params::{Bls12381, Bls12381BaseField};
+use crate::chip::ec::bls12_381::tower::Fp12;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::EllipticCurve;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The domain separation tag of the proof of possession scheme used by Ethereum.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The public registers of a BLS signature verification.
+#[derive(Debug, Clone)]
+pub struct BLSGadget {
+    pub pubkeys: Vec<AffinePointRegister<Bls12381>>,
+    pub signature: G2PointRegister,
+    /// The hash `H(m)` of the message to G2.
+    pub hash: HashToG2Gadget,
+}
+
+/// The values of a BLS signature verification.
+#[derive(Debug, Clone)]
+pub struct BLSWitness {
+    pub pubkeys: Vec<AffinePoint<Bls12381>>,
+    pub msg: Vec<u8>,
+    pub signature: G2AffinePoint,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions
+        + FromFieldInstruction<Bls12381BaseField>
+        + From<FpCompareInstruction<Bls12381BaseField>>,
+{
+    /// Verifies the aggregate signature `signature` of the message given by the public bytes
+    /// `msg` under the public keys `pubkeys`, in a trace of `num_rows` rows.
+    ///
+    /// `num_rows` must be as in `bls12_381_hash_to_g2` for the tag `DST`. All the registers must
+    /// be public, and their values are written by `BLSGadget::write` before the global
+    /// instructions.
+    ///
+    /// The public keys are aggregated with incomplete additions, so they must be distinct and no
+    /// partial sum may be equal to the next key up to sign. Both hold for keys with a proof of
+    /// possession, which the caller must have verified.
+    pub fn bls_verify(
+        &mut self,
+        pubkeys: &[AffinePointRegister<Bls12381>],
+        msg: &ArrayRegister<ByteRegister>,
+        signature: &G2PointRegister,
+        num_rows: usize,
+    ) -> BLSGadget {
+        let (first, rest) = pubkeys.split_first().expect("No public keys");
+        assert!(
+            pubkeys.iter().all(|p| !p.x.is_trace() && !p.y.is_trace())
+                && [signature.x, signature.y]
+                    .iter()
+                    .all(|c| !c.c0.is_trace() && !c.c1.is_trace()),
+            "Inputs must be public"
+        );
+
+        let hash = self.bls12_381_hash_to_g2(msg, DST, num_rows);
+        self.api().bls12_381_g2_assert_in_subgroup(signature);
+
+        let aggregate = rest
+            .iter()
+            .fold(*first, |sum, p| self.api().bls12_381_add(&sum, p));
+
+        let p = Bls12381BaseField::modulus();
+        let g = Bls12381::generator();
+        let minus_g = AffinePointRegister::new(
+            self.api().fp_constant(&g.x),
+            self.api().fp_constant(&(&p - &g.y)),
+        );
+
+        self.api()
+            .bls12_381_pairing_check(&[(aggregate, hash.point), (minus_g, *signature)]);
+
+        BLSGadget {
+            pubkeys: pubkeys.to_vec(),
+            signature: *signature,
+            hash,
+        }
+    }
+}
+
+impl BLSGadget {
+    /// Writes the public keys, the message and the values of its hash, and the signature.
+    pub fn write<F: Field>(&self, witness: &BLSWitness, writer: &mut impl AirWriter<Field = F>) {
+        assert_eq!(self.pubkeys.len(), witness.pubkeys.len());
+        for (register, value) in self.pubkeys.iter().zip(witness.pubkeys.iter()) {
+            writer.write_ec_point(register, value);
+        }
+        self.hash.write(&witness.msg, writer);
+        writer.write_g2_point(&self.signature, &witness.signature);
+    }
+}
+
+impl BLSWitness {
+    /// The sum of the public keys.
+    pub fn aggregate_pubkey(&self) -> AffinePoint<Bls12381> {
+        let (first, rest) = self.pubkeys.split_first().expect("No public keys");
+        rest.iter().fold(first.clone(), |sum, p| sum.sw_add(p))
+    }
+
+    /// Checks the signature outside of the AIR.
+    pub fn verify(&self) -> bool {
+        let minus_g = Bls12381::ec_neg(&Bls12381::generator());
+        let product = final_exponentiation(&miller_loop(&[
+            (self.aggregate_pubkey(), hash_to_g2(&self.msg, DST)),
+            (minus_g, self.signature.clone()),
+        ]));
+        is_in_g2(&self.signature) && product == Fp12::one()
+    }
+
+    /// Signs `msg`, hashed to G2 with the domain separation tag `DST`, with each of the secret
+    /// keys `secret_keys` and aggregates the signatures.
+    pub fn sign(secret_keys: &[BigUint], msg: &[u8]) -> Self {
+        let g = Bls12381::generator();
+        let message = hash_to_g2(msg, DST);
+        let pubkeys = secret_keys
+            .iter()
+            .map(|sk| g.sw_scalar_mul(sk))
+            .collect::<Vec<_>>();
+        let signatures = secret_keys
+            .iter()
+            .map(|sk| message.g2_scalar_mul(sk))
+            .collect::<Vec<_>>();
+        let (first, rest) = signatures.split_first().expect("No secret keys");
+        let signature = rest.iter().fold(first.clone(), |sum, s| sum.g2_add(s));
+        Self {
+            pubkeys,
+            msg: msg.to_vec(),
+            signature,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::bls12_381::hash_to_curve::map_to_g2;
+    use crate::chip::ec::bls12_381::instruction::Bls12381Instruction;
+    use crate::chip::ec::bls12_381::params::Bls12381ScalarField;
+    use crate::chip::ec::bls12_381::tower::Fp2;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::ec::builder::EllipticCurveBuilder;
+    use crate::machine::ec::hash_to_curve::hash_to_g2_num_rows;
+    use crate::machine::tests::verifies_with;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct BLSTest;
+
+    impl AirParameters for BLSTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Bls12381Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1204;
+        const EXTENDED_COLUMNS: usize = 764;
+    }
+
+    fn witness(nb_keys: usize, msg: &[u8]) -> BLSWitness {
+        let order = Bls12381ScalarField::modulus();
+        let mut rng = thread_rng();
+        let secret_keys = (0..nb_keys)
+            .map(|_| rng.gen_biguint_below(&order))
+            .collect::<Vec<_>>();
+        BLSWitness::sign(&secret_keys, msg)
+    }
+
+    /// A point of the twist of order dividing the cofactor of G2.
+    fn torsion_point() -> G2AffinePoint {
+        let order = Bls12381ScalarField::modulus();
+        map_to_g2(&Fp2::new(BigUint::from(1u32), BigUint::from(2u32))).g2_scalar_mul(&order)
+    }
+
+    #[test]
+    fn test_bls_verify_pure() {
+        let witness = witness(3, b"message");
+        assert!(witness.verify());
+
+        let mut tampered = witness.clone();
+        tampered.msg = b"tampered".to_vec();
+        assert!(!tampered.verify());
+
+        let mut missing_key = witness.clone();
+        missing_key.pubkeys.pop();
+        assert!(!missing_key.verify());
+
+        let mut outside_g2 = witness;
+        outside_g2.signature = outside_g2.signature.g2_add(&torsion_point());
+        assert!(!is_in_g2(&outside_g2.signature));
+        assert!(!outside_g2.verify());
+    }
+
+    /// Proves the verification of `witness` and returns whether the proof verifies.
+    fn prove(witness: &BLSWitness) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = BLSTest;
+
+        let num_rows = hash_to_g2_num_rows(witness.msg.len(), DST.len());
+        let mut builder = BytesBuilder::<L>::new();
+        let pubkeys = (0..witness.pubkeys.len())
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let msg = builder.alloc_array_public::<ByteRegister>(witness.msg.len());
+        let signature = builder.api().alloc_public_g2_point();
+        let gadget = builder.bls_verify(&pubkeys, &msg, &signature, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        verifies_with(&stark, || {
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(witness, &mut writer);
+            stark.air_data.write_global_instructions(&mut writer);
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+
+            (writer_data.trace, writer_data.public)
+        })
+    }
+
+    #[test]
+    fn test_bls_verify() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let witness = witness(3, b"message");
+        assert!(prove(&witness));
+
+        let mut tampered = witness.clone();
+        tampered.msg = b"massage".to_vec();
+        assert!(!prove(&tampered));
+
+        let mut outside_g2 = witness;
+        outside_g2.signature = outside_g2.signature.g2_add(&torsion_point());
+        assert!(!prove(&outside_g2));
+    }
+}
@@ -71,7 +71,7 @@ pub trait EllipticCurveBuilder<E: EllipticCurveAir<Self::Parameters>>: Builder {
         let nb_scalar_bits = E::nb_scalar_bits();
         let nb_bits_log = nb_scalar_bits.ilog2();
         assert_eq!(
-            E::nb_scalar_bits(),
+            1 << nb_bits_log,
             nb_scalar_bits,
             "Scalar size must be a power of 2"
         );
@@ -278,8 +278,13 @@ mod tests {
     use serde::{Deserialize, Serialize};
 
     use super::*;
+    use crate::chip::ec::bls12_381::params::{Bls12381, Bls12381Parameters};
+    use crate::chip::ec::bn254::precompile::ec_mul;
     use crate::chip::ec::edwards::ed25519::params::Ed25519;
     use crate::chip::ec::gadget::EllipticCurveAirWriter;
+    use crate::chip::ec::secp256k1::params::{Secp256k1, Secp256k1Parameters};
+    use crate::chip::ec::weierstrass::bn254::{Bn254, Bn254Parameters};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
     use crate::chip::ec::{ECInstruction, EllipticCurve};
     use crate::chip::trace::writer::data::AirWriterData;
     use crate::chip::trace::writer::AirWriter;
@@ -415,4 +420,245 @@ mod tests {
 
         timing.print();
     }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Secp256k1ScalarMulTest;
+
+    impl AirParameters for Secp256k1ScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Secp256k1>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    #[test]
+    fn test_secp256k1_scalar_mul() {
+        type F = GoldilocksField;
+        type L = Secp256k1ScalarMulTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type E = Secp256k1;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Secp256k1 Scalar mul", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+
+        let num_ops = 2;
+        let points = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let scalars = (0..num_ops)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(8))
+            .map(ECScalarRegister::<E>::new)
+            .collect::<Vec<_>>();
+        let results = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+
+        builder.scalar_mul_batch(&points, &scalars, &results);
+
+        let num_rows = num_ops * 256;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let order = Secp256k1Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for ((point_reg, scalar_reg), result_reg) in
+            points.iter().zip(scalars.iter()).zip(results.iter())
+        {
+            let point = E::generator().sw_scalar_mul(&(rng.gen_biguint(256) % &order));
+            let scalar = rng.gen_biguint(256) % &order;
+            let result = point.sw_scalar_mul(&scalar);
+            writer.write_ec_point(point_reg, &point);
+            writer.write_ec_point(result_reg, &result);
+
+            let mut limb_values = scalar.to_u32_digits();
+            limb_values.resize(8, 0);
+            for (limb_reg, limb) in scalar_reg.limbs.iter().zip_eq(limb_values) {
+                writer.write(&limb_reg, &F::from_canonical_u32(limb));
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        writer_data.chunks_par(256).for_each(|mut chunk| {
+            for i in 0..256 {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Bls12381ScalarMulTest;
+
+    impl AirParameters for Bls12381ScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Bls12381>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3320;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 5034;
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_mul() {
+        type F = GoldilocksField;
+        type L = Bls12381ScalarMulTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type E = Bls12381;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("BLS12-381 Scalar mul", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+
+        let num_ops = 2;
+        let points = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let scalars = (0..num_ops)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(8))
+            .map(ECScalarRegister::<E>::new)
+            .collect::<Vec<_>>();
+        let results = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+
+        builder.scalar_mul_batch(&points, &scalars, &results);
+
+        let num_rows = num_ops * 256;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let order = Bls12381Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for ((point_reg, scalar_reg), result_reg) in
+            points.iter().zip(scalars.iter()).zip(results.iter())
+        {
+            let point = E::generator().sw_scalar_mul(&(rng.gen_biguint(256) % &order));
+            let scalar = rng.gen_biguint(256) % &order;
+            let result = point.sw_scalar_mul(&scalar);
+            writer.write_ec_point(point_reg, &point);
+            writer.write_ec_point(result_reg, &result);
+
+            let mut limb_values = scalar.to_u32_digits();
+            limb_values.resize(8, 0);
+            for (limb_reg, limb) in scalar_reg.limbs.iter().zip_eq(limb_values) {
+                writer.write(&limb_reg, &F::from_canonical_u32(limb));
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        writer_data.chunks_par(256).for_each(|mut chunk| {
+            for i in 0..256 {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Bn254ScalarMulTest;
+
+    impl AirParameters for Bn254ScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Bn254>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    #[test]
+    fn test_bn254_scalar_mul() {
+        type F = GoldilocksField;
+        type L = Bn254ScalarMulTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type E = Bn254;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("BN254 Scalar mul", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+
+        let num_ops = 2;
+        let points = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let scalars = (0..num_ops)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(8))
+            .map(ECScalarRegister::<E>::new)
+            .collect::<Vec<_>>();
+        let results = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+
+        builder.scalar_mul_batch(&points, &scalars, &results);
+
+        let num_rows = num_ops * 256;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let order = Bn254Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for ((point_reg, scalar_reg), result_reg) in
+            points.iter().zip(scalars.iter()).zip(results.iter())
+        {
+            let point = E::generator().sw_scalar_mul(&(rng.gen_biguint(256) % &order));
+            // The precompile takes any 256-bit scalar.
+            let scalar = rng.gen_biguint(256);
+            let result = ec_mul(&point, &scalar);
+            writer.write_ec_point(point_reg, &point);
+            writer.write_ec_point(result_reg, &result);
+
+            let mut limb_values = scalar.to_u32_digits();
+            limb_values.resize(8, 0);
+            for (limb_reg, limb) in scalar_reg.limbs.iter().zip_eq(limb_values) {
+                writer.write(&limb_reg, &F::from_canonical_u32(limb));
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        writer_data.chunks_par(256).for_each(|mut chunk| {
+            for i in 0..256 {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
 }
@@ -0,0 +1,870 @@
+//! ECDSA signature verification over secp256k1 and NIST P-256 as defined in SEC 1.
+//!
+//! A signature `(r, s)` on a message hash `z` under the public key `Q` is valid if `r` and `s`
+//! are in `[1, n)` and
+//!
+//! r = x([u1]G + [u2]Q) mod n
+//!
+//! where `n` is the group order, `G` is the base point, `u1 = z / s` and `u2 = r / s` over the
+//! scalar field. The gadget inverts `s` in the scalar field, computes `[u1]G` and `[u2]Q` with a
+//! single scalar multiplication batch, adds the two points and compares the x-coordinate of the
+//! sum, reduced modulo `n`, with `r`.
+//!
+//! The message hash is a public input of `ecdsa_verify`, computed by `ECDSAWitness` from the raw
+//! message. `BytesBuilder::ecdsa_verify_sha256` instead takes the padded SHA-256 chunks of the
+//! message, and the digest is computed by the SHA-256 AIR in the same trace as the scalar
+//! multiplications and reduced modulo `n`.
+//!
+//! Many signatures can be verified in one STARK with `ecdsa_verify_batch`, which shares the
+//! range checks of the field instructions across the signatures and computes all the `[u1]G`
+//! with the precomputed window tables of `fixed_base`, so that each signature only takes the
+//! 256 rows of its variable-base scalar multiplication.
+//!
+//! Reference: https://www.secg.org/sec1-v2.pdf, section 4.1.4
+
+use itertools::Itertools;
+use num::{BigUint, Zero};
+
+use super::builder::EllipticCurveBuilder;
+use super::fixed_base::{sw_fixed_base_mul_padded, SWGenerator};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::p256::params::{P256Parameters, P256ScalarField, P256};
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::scalar::ECScalarRegister;
+use crate::chip::ec::secp256k1::params::{Secp256k1, Secp256k1Parameters, Secp256k1ScalarField};
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::{ECInstructions, EllipticCurve, EllipticCurveAir};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The number of rows of the trace of `BytesBuilder::ecdsa_verify_sha256`, taken by a scalar
+/// multiplication batch of two operations.
+pub const VERIFY_NUM_ROWS: usize = 512;
+
+/// A short Weierstrass curve with ECDSA signatures.
+pub trait ECDSAParameters: WeierstrassParameters {
+    /// The field of integers modulo the group order.
+    type ScalarField: FieldParameters;
+}
+
+impl ECDSAParameters for Secp256k1Parameters {
+    type ScalarField = Secp256k1ScalarField;
+}
+
+impl ECDSAParameters for P256Parameters {
+    type ScalarField = P256ScalarField;
+}
+
+/// The public registers of an ECDSA signature verification.
+#[derive(Debug, Clone, Copy)]
+pub struct ECDSAGadget<E: ECDSAParameters> {
+    pub pubkey: AffinePointRegister<SWCurve<E>>,
+    pub msg_hash: FieldRegister<E::ScalarField>,
+    pub r: FieldRegister<E::ScalarField>,
+    pub s: FieldRegister<E::ScalarField>,
+    /// The result of `[u1]G`.
+    u1_mul_g: AffinePointRegister<SWCurve<E>>,
+    /// The result of `[u2]Q`.
+    u2_mul_q: AffinePointRegister<SWCurve<E>>,
+}
+
+pub type ECDSASecp256k1Gadget = ECDSAGadget<Secp256k1Parameters>;
+
+/// The public inputs of a signature verification in a batch.
+#[derive(Debug, Clone, Copy)]
+pub struct ECDSAInputRegisters<E: ECDSAParameters> {
+    pub pubkey: AffinePointRegister<SWCurve<E>>,
+    pub msg_hash: FieldRegister<E::ScalarField>,
+    pub r: FieldRegister<E::ScalarField>,
+    pub s: FieldRegister<E::ScalarField>,
+}
+
+/// The public registers of a batch of ECDSA signature verifications.
+#[derive(Debug, Clone)]
+pub struct ECDSABatchGadget<E: ECDSAParameters> {
+    pub signatures: Vec<ECDSAGadget<E>>,
+    /// The little-endian bits of each `u1`, the scalars of the fixed-base multiplications.
+    u1_bits: Vec<ArrayRegister<BitRegister>>,
+}
+
+pub type ECDSAP256Gadget = ECDSAGadget<P256Parameters>;
+
+/// The public registers of an ECDSA signature verification of a message hashed with SHA-256.
+#[derive(Debug, Clone)]
+pub struct ECDSASHA256Gadget<E: ECDSAParameters> {
+    /// The chunks of the message, padded with `SHA256::pad`.
+    pub padded_chunks: Vec<ArrayRegister<U32Register>>,
+    pub signature: ECDSAGadget<E>,
+    /// The digest of the message, computed by the SHA-256 AIR.
+    digest: SHA256DigestRegister,
+}
+
+/// The values of an ECDSA signature verification.
+#[derive(Debug, Clone)]
+pub struct ECDSAWitness<E: ECDSAParameters> {
+    pub pubkey: AffinePoint<SWCurve<E>>,
+    /// The message hash `z`, reduced modulo the group order.
+    pub msg_hash: BigUint,
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+pub type ECDSASecp256k1Witness = ECDSAWitness<Secp256k1Parameters>;
+
+pub type ECDSAP256Witness = ECDSAWitness<P256Parameters>;
+
+pub trait ECDSABuilder: Builder {
+    /// Verifies the signature `(r, s)` of `msg_hash` under `pubkey`.
+    ///
+    /// All the registers must be public, and their values are written by `ECDSAGadget::write`
+    /// before the global instructions. The verification takes a scalar multiplication batch of
+    /// two operations, so the trace must have 512 rows.
+    ///
+    /// The x-coordinate of `[u1]G + [u2]Q` is reduced modulo `n` with `fp_reduce` before it is
+    /// compared with `r`. The points `[u1]G` and `[u2]Q` must be distinct.
+    fn ecdsa_verify<E: ECDSAParameters>(
+        &mut self,
+        pubkey: &AffinePointRegister<SWCurve<E>>,
+        msg_hash: &FieldRegister<E::ScalarField>,
+        r: &FieldRegister<E::ScalarField>,
+        s: &FieldRegister<E::ScalarField>,
+    ) -> ECDSAGadget<E>
+    where
+        Self::Instruction: ECInstructions<SWCurve<E>> + FromFieldInstruction<E::ScalarField>,
+    {
+        assert_eq!(E::BaseField::NB_LIMBS, E::ScalarField::NB_LIMBS);
+        assert!(
+            !pubkey.x.is_trace() && !msg_hash.is_trace() && !r.is_trace() && !s.is_trace(),
+            "Inputs must be public"
+        );
+        // The inversions fail for `r = 0` and `s = 0`.
+        let one = self.api().fp_one();
+        self.api().fp_div(&one, r);
+        let u1 = self.api().fp_div(msg_hash, s);
+        let u2 = self.api().fp_div(r, s);
+        let u1 = self.ecdsa_scalar::<E>(&u1);
+        let u2 = self.ecdsa_scalar::<E>(&u2);
+
+        let g = self.generator();
+        let u1_mul_g = self.alloc_public_ec_point();
+        let u2_mul_q = self.alloc_public_ec_point();
+        self.scalar_mul_batch([g, *pubkey], [u1, u2], [u1_mul_g, u2_mul_q]);
+
+        let sum = self.api().sw_add::<E>(&u1_mul_g, &u2_mul_q);
+        let sum_x = self.api().fp_reduce::<E::BaseField, E::ScalarField>(&sum.x);
+        self.assert_equal(&sum_x, r);
+
+        ECDSAGadget {
+            pubkey: *pubkey,
+            msg_hash: *msg_hash,
+            r: *r,
+            s: *s,
+            u1_mul_g,
+            u2_mul_q,
+        }
+    }
+
+    /// Allocates the public inputs of a signature verification.
+    fn alloc_public_ecdsa_inputs<E: ECDSAParameters>(&mut self) -> ECDSAInputRegisters<E>
+    where
+        SWCurve<E>: EllipticCurveAir<Self::Parameters>,
+    {
+        ECDSAInputRegisters {
+            pubkey: self.alloc_public_ec_point(),
+            msg_hash: self.alloc_public(),
+            r: self.alloc_public(),
+            s: self.alloc_public(),
+        }
+    }
+
+    /// Verifies the signatures of `inputs`, which must be public registers whose values are
+    /// written by `ECDSABatchGadget::write` before the global instructions.
+    ///
+    /// The products `[u2]Q` take a single scalar multiplication batch, and the products `[u1]G`
+    /// use the window tables of `sw_fixed_base_mul_padded`, padded to the same number of rows. The
+    /// trace must have `(256 * inputs.len()).next_power_of_two()` rows, half as many as with one
+    /// `ecdsa_verify` per signature.
+    ///
+    /// The restrictions of `ecdsa_verify` apply, and in addition the message hash must be
+    /// nonzero modulo `n`, since the fixed-base multiplication does not support `u1 = 0`.
+    fn ecdsa_verify_batch<E: ECDSAParameters>(
+        &mut self,
+        inputs: &[ECDSAInputRegisters<E>],
+    ) -> ECDSABatchGadget<E>
+    where
+        Self::Instruction: ECInstructions<SWCurve<E>> + FromFieldInstruction<E::ScalarField>,
+    {
+        assert_eq!(E::BaseField::NB_LIMBS, E::ScalarField::NB_LIMBS);
+        assert!(!inputs.is_empty(), "Batch must not be empty");
+        let nb_scalar_bits = SWCurve::<E>::nb_scalar_bits();
+        let num_rows = (inputs.len() * nb_scalar_bits).next_power_of_two();
+
+        let one = self.api().fp_one();
+        let mut u1_bits = Vec::with_capacity(inputs.len());
+        let mut u2_scalars = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            assert!(
+                !input.pubkey.x.is_trace()
+                    && !input.msg_hash.is_trace()
+                    && !input.r.is_trace()
+                    && !input.s.is_trace(),
+                "Inputs must be public"
+            );
+            // The inversions fail for `r = 0` and `s = 0`.
+            self.api().fp_div(&one, &input.r);
+            let u1 = self.api().fp_div(&input.msg_hash, &input.s);
+            let u2 = self.api().fp_div(&input.r, &input.s);
+
+            // Decompose `u1` into bits, which are then constrained to be smaller than `n` by the
+            // fixed-base multiplication.
+            let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*u1.register());
+            let bits = self.alloc_array_public::<BitRegister>(nb_scalar_bits);
+            for (i, limb) in limbs.iter().enumerate() {
+                let value = (0..16)
+                    .map(|j| bits.get(16 * i + j).expr() * Self::Field::from_canonical_u32(1 << j))
+                    .fold(ArithmeticExpression::zero(), |acc, term| acc + term);
+                self.assert_expression_zero(value - limb.expr());
+            }
+            u1_bits.push(bits);
+            u2_scalars.push(self.ecdsa_scalar::<E>(&u2));
+        }
+
+        let u1_mul_g = sw_fixed_base_mul_padded::<Self, SWGenerator<E>>(self, &u1_bits, num_rows);
+        let u2_mul_q: Vec<AffinePointRegister<SWCurve<E>>> = inputs
+            .iter()
+            .map(|_| self.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        self.scalar_mul_batch(
+            inputs.iter().map(|input| input.pubkey),
+            &u2_scalars,
+            &u2_mul_q,
+        );
+
+        let signatures = inputs
+            .iter()
+            .zip_eq(u1_mul_g.iter().zip_eq(u2_mul_q.iter()))
+            .map(|(input, (u1_mul_g, u2_mul_q))| {
+                let sum = self.api().sw_add::<E>(u1_mul_g, u2_mul_q);
+                let sum_x = self.api().fp_reduce::<E::BaseField, E::ScalarField>(&sum.x);
+                self.assert_equal(&sum_x, &input.r);
+                ECDSAGadget {
+                    pubkey: input.pubkey,
+                    msg_hash: input.msg_hash,
+                    r: input.r,
+                    s: input.s,
+                    u1_mul_g: *u1_mul_g,
+                    u2_mul_q: *u2_mul_q,
+                }
+            })
+            .collect();
+
+        ECDSABatchGadget {
+            signatures,
+            u1_bits,
+        }
+    }
+
+    /// Verifies an ECDSA signature over secp256k1.
+    fn ecdsa_verify_secp256k1(
+        &mut self,
+        pubkey: &AffinePointRegister<Secp256k1>,
+        msg_hash: &FieldRegister<Secp256k1ScalarField>,
+        r: &FieldRegister<Secp256k1ScalarField>,
+        s: &FieldRegister<Secp256k1ScalarField>,
+    ) -> ECDSASecp256k1Gadget
+    where
+        Self::Instruction: ECInstructions<Secp256k1> + FromFieldInstruction<Secp256k1ScalarField>,
+    {
+        self.ecdsa_verify(pubkey, msg_hash, r, s)
+    }
+
+    /// Verifies an ECDSA signature over NIST P-256, as used by WebAuthn assertions.
+    fn ecdsa_verify_p256(
+        &mut self,
+        pubkey: &AffinePointRegister<P256>,
+        msg_hash: &FieldRegister<P256ScalarField>,
+        r: &FieldRegister<P256ScalarField>,
+        s: &FieldRegister<P256ScalarField>,
+    ) -> ECDSAP256Gadget
+    where
+        Self::Instruction: ECInstructions<P256> + FromFieldInstruction<P256ScalarField>,
+    {
+        self.ecdsa_verify(pubkey, msg_hash, r, s)
+    }
+
+    /// Converts a scalar field element into the 32-bit limbs of a scalar multiplication.
+    fn ecdsa_scalar<E: ECDSAParameters>(
+        &mut self,
+        scalar: &FieldRegister<E::ScalarField>,
+    ) -> ECScalarRegister<SWCurve<E>> {
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*scalar.register());
+        let scalar_limbs = self.alloc_array_public::<ElementRegister>(limbs.len() / 2);
+        for (i, limb) in scalar_limbs.iter().enumerate() {
+            let expr = limbs.get(2 * i).expr()
+                + limbs.get(2 * i + 1).expr() * Self::Field::from_canonical_u32(1 << 16);
+            self.api().set_to_expression_public(&limb, expr);
+        }
+        ECScalarRegister::new(scalar_limbs)
+    }
+}
+
+impl<B: Builder> ECDSABuilder for B {}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Verifies the signature `(r, s)` under `pubkey` of the message given by `padded_chunks`,
+    /// its chunks padded with `SHA256::pad`.
+    ///
+    /// All the registers must be public, and their values are written by
+    /// `ECDSASHA256Gadget::write` before the global instructions. The trace must have
+    /// `VERIFY_NUM_ROWS` rows, so the padded message has at most eight chunks. The restrictions
+    /// of `ecdsa_verify` apply.
+    pub fn ecdsa_verify_sha256<E: ECDSAParameters>(
+        &mut self,
+        pubkey: &AffinePointRegister<SWCurve<E>>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        r: &FieldRegister<E::ScalarField>,
+        s: &FieldRegister<E::ScalarField>,
+    ) -> ECDSASHA256Gadget<E>
+    where
+        L::Instruction: ECInstructions<SWCurve<E>> + FromFieldInstruction<E::ScalarField>,
+    {
+        assert!(
+            padded_chunks.iter().all(|chunk| !chunk.is_trace()),
+            "Inputs must be public"
+        );
+        let digest = self.sha256(&[padded_chunks.to_vec()], VERIFY_NUM_ROWS)[0];
+
+        // The message hash is the digest as a big-endian integer, reduced modulo `n`. Both
+        // curves have a 256-bit group order, so the digest is not truncated. The reduction is
+        // only constrained modulo `n`, which doesn't change the verification.
+        let digest_byte = |k: usize| digest.get(k / 4).to_le_bytes().get(3 - k % 4);
+        let digest_int = self.alloc_public_unchecked::<FieldRegister<E::ScalarField>>();
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*digest_int.register());
+        for (i, limb) in limbs.iter().enumerate() {
+            let value: ArithmeticExpression<L::Field> = digest_byte(31 - 2 * i).expr()
+                + digest_byte(30 - 2 * i).expr() * L::Field::from_canonical_u32(1 << 8);
+            self.set_to_expression(&limb, value);
+        }
+        let msg_hash = self
+            .api()
+            .fp_reduce::<E::ScalarField, E::ScalarField>(&digest_int);
+
+        let signature = self.ecdsa_verify(pubkey, &msg_hash, r, s);
+        ECDSASHA256Gadget {
+            padded_chunks: padded_chunks.to_vec(),
+            signature,
+            digest,
+        }
+    }
+}
+
+impl<E: ECDSAParameters> ECDSAGadget<E> {
+    /// Writes the public key, the message hash, the signature and the scalar multiplication
+    /// results.
+    pub fn write<F: Field>(
+        &self,
+        witness: &ECDSAWitness<E>,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        writer.write_ec_point(&self.pubkey, &witness.pubkey);
+        for (register, value) in [self.msg_hash, self.r, self.s].iter().zip_eq([
+            &witness.msg_hash,
+            &witness.r,
+            &witness.s,
+        ]) {
+            writer.write(
+                register,
+                &to_u16_le_limbs_polynomial::<F, E::ScalarField>(value),
+            );
+        }
+
+        let (u1, u2) = witness.scalars();
+        writer.write_ec_point(
+            &self.u1_mul_g,
+            &SWCurve::<E>::generator().sw_scalar_mul(&u1),
+        );
+        writer.write_ec_point(&self.u2_mul_q, &witness.pubkey.sw_scalar_mul(&u2));
+    }
+}
+
+impl<E: ECDSAParameters> ECDSASHA256Gadget<E> {
+    /// Writes the chunks of `msg`, its digest and the values of the signature verification,
+    /// where the message hash of `witness` is `ECDSAWitness::msg_hash(msg)`.
+    pub fn write<F: Field>(
+        &self,
+        witness: &ECDSAWitness<E>,
+        msg: &[u8],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for (chunk, values) in self
+            .padded_chunks
+            .iter()
+            .zip_eq(SHA256::pad(msg).chunks_exact(16))
+        {
+            writer.write_array(
+                chunk,
+                values.iter().map(|word| u32_to_le_field_bytes(*word)),
+            );
+        }
+        let digest: ArrayRegister<U32Register> = self.digest.into();
+        writer.write_array(&digest, SHA256::digest(msg).map(u32_to_le_field_bytes));
+        self.signature.write(witness, writer);
+    }
+}
+
+impl<E: ECDSAParameters> ECDSABatchGadget<E> {
+    /// Writes the values of all the signatures of the batch, in the order of the inputs.
+    pub fn write<F: Field>(
+        &self,
+        witnesses: &[ECDSAWitness<E>],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for ((gadget, bits), witness) in self
+            .signatures
+            .iter()
+            .zip_eq(self.u1_bits.iter())
+            .zip_eq(witnesses)
+        {
+            gadget.write(witness, writer);
+            let (u1, _) = witness.scalars();
+            for (j, bit) in bits.iter().enumerate() {
+                writer.write(&bit, &F::from_bool(u1.bit(j as u64)));
+            }
+        }
+    }
+}
+
+impl<E: ECDSAParameters> ECDSAWitness<E> {
+    /// Computes the witness of the signature `(r, s)` of `msg` under `pubkey`, where the message
+    /// is hashed with SHA-256.
+    pub fn new(pubkey: AffinePoint<SWCurve<E>>, msg: &[u8], r: BigUint, s: BigUint) -> Self {
+        Self {
+            pubkey,
+            msg_hash: Self::msg_hash(msg),
+            r,
+            s,
+        }
+    }
+
+    /// The SHA-256 digest of `msg`, interpreted as a big-endian integer and reduced modulo the
+    /// group order.
+    ///
+    /// Both curves have a 256-bit group order, so the digest is not truncated.
+    pub fn msg_hash(msg: &[u8]) -> BigUint {
+        let digest = SHA256::digest(msg)
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        BigUint::from_bytes_be(&digest) % E::ScalarField::modulus()
+    }
+
+    /// The scalars `u1 = z / s` and `u2 = r / s`.
+    fn scalars(&self) -> (BigUint, BigUint) {
+        let n = E::ScalarField::modulus();
+        let s_inv = self.s.modpow(&(&n - 2u32), &n);
+        ((&self.msg_hash * &s_inv) % &n, (&self.r * &s_inv) % &n)
+    }
+
+    /// Checks the signature outside of the AIR.
+    pub fn verify(&self) -> bool {
+        let n = E::ScalarField::modulus();
+        let is_in_range = |x: &BigUint| !x.is_zero() && x < &n;
+        if !is_in_range(&self.r) || !is_in_range(&self.s) {
+            return false;
+        }
+        let (u1, u2) = self.scalars();
+        let u1_mul_g = SWCurve::<E>::generator().sw_scalar_mul(&u1);
+        let u2_mul_q = self.pubkey.sw_scalar_mul(&u2);
+        if u1_mul_g.x == u2_mul_q.x && u1_mul_g.y == u2_mul_q.y {
+            return u1_mul_g.sw_double().x % &n == self.r;
+        }
+        u1_mul_g.sw_add(&u2_mul_q).x % &n == self.r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::p256::instruction::P256Instruction;
+    use crate::chip::ec::secp256k1::instruction::Secp256k1Instruction;
+    use crate::chip::ec::secp256k1::params::Secp256k1BaseField;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::tests::verifies_with;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::maybe_rayon::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECDSASecp256k1Test;
+
+    impl AirParameters for ECDSASecp256k1Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECDSAP256Test;
+
+    impl AirParameters for ECDSAP256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = P256Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECDSASHA256Test;
+
+    impl AirParameters for ECDSASHA256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 1219;
+        const EXTENDED_COLUMNS: usize = 4030;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECDSABatchSecp256k1Test;
+
+    impl AirParameters for ECDSABatchSecp256k1Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 4368;
+        const NUM_FREE_COLUMNS: usize = 38;
+        const EXTENDED_COLUMNS: usize = 6660;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECDSABatchP256Test;
+
+    impl AirParameters for ECDSABatchP256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = P256Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 4368;
+        const NUM_FREE_COLUMNS: usize = 38;
+        const EXTENDED_COLUMNS: usize = 6660;
+    }
+
+    /// A signature as (public key x, public key y, message, r, s).
+    type Vector = (
+        &'static str,
+        &'static str,
+        &'static str,
+        &'static str,
+        &'static str,
+    );
+
+    /// Deterministic signatures of RFC 6979 with SHA-256 and a low `s`.
+    const SECP256K1_VECTORS: [Vector; 3] = [
+        (
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+            "Satoshi Nakamoto",
+            "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8",
+            "2442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+        ),
+        (
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "b7c52588d95c3b9aa25b0403f1eef75702e84bb7597aabe663b82f6f04ef2777",
+            "Satoshi Nakamoto",
+            "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d0",
+            "6b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5",
+        ),
+        (
+            "92df7b245b81aa637ab4e867c8d511008f79161a97d64f2ac709600352f7acbc",
+            "e9bfdf1b13fa0cb1de4521e5386cde3a1cd26c5ab584989d07bbed58a5419f62",
+            "Alan Turing",
+            "7063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c",
+            "58dfcc1e00a35e1572f366ffe34ba0fc47db1e7189759b9fb233c5b05ab388ea",
+        ),
+    ];
+
+    /// The P-256 signatures with SHA-256 of RFC 6979, section A.2.5.
+    const P256_VECTORS: [Vector; 2] = [
+        (
+            "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+            "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299",
+            "sample",
+            "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716",
+            "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+        ),
+        (
+            "60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6",
+            "7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299",
+            "test",
+            "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367",
+            "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083",
+        ),
+    ];
+
+    fn witness<E: ECDSAParameters>(vector: &Vector, msg: Option<&[u8]>) -> ECDSAWitness<E> {
+        let (x, y, expected_msg, r, s) = *vector;
+        let hex_int = |value: &str| BigUint::parse_bytes(value.as_bytes(), 16).unwrap();
+        let pubkey = AffinePoint::new(hex_int(x), hex_int(y));
+        let msg = msg.unwrap_or(expected_msg.as_bytes());
+        ECDSAWitness::new(pubkey, msg, hex_int(r), hex_int(s))
+    }
+
+    /// A signature of secp256k1 whose point `[u1]G + [u2]Q` has an x-coordinate in `[n, p)`, so
+    /// that `r` is this coordinate minus `n`.
+    ///
+    /// The point `R` is the first point with an x-coordinate above `n`, and the public key is
+    /// `Q = [s / r]R - [z / r]G`, so that `sR = zG + rQ` for any `z` and `s`.
+    fn unreduced_x_witness() -> ECDSASecp256k1Witness {
+        let p = Secp256k1BaseField::modulus();
+        let n = Secp256k1ScalarField::modulus();
+        let point = num::range(&n + 1u32, p.clone())
+            .find_map(|x| {
+                let rhs = (x.pow(3) + 7u32) % &p;
+                let y = rhs.modpow(&((&p + 1u32) >> 2), &p);
+                ((&y * &y) % &p == rhs).then(|| AffinePoint::new(x, y))
+            })
+            .unwrap();
+
+        let r = &point.x - &n;
+        let (msg_hash, s) = (BigUint::from(3u32), BigUint::from(5u32));
+        let r_inv = r.modpow(&(&n - 2u32), &n);
+        let pubkey = point
+            .sw_scalar_mul(&((&s * &r_inv) % &n))
+            .sw_add(&Secp256k1::generator().sw_scalar_mul(&(&n - (&msg_hash * &r_inv) % &n)));
+        ECDSAWitness {
+            pubkey,
+            msg_hash,
+            r,
+            s,
+        }
+    }
+
+    fn test_ecdsa_verify<E: ECDSAParameters, L>(
+        witnesses: &[ECDSAWitness<E>],
+        verify: impl Fn(
+            &mut EmulatedBuilder<L>,
+            &AffinePointRegister<SWCurve<E>>,
+            &FieldRegister<E::ScalarField>,
+            &FieldRegister<E::ScalarField>,
+            &FieldRegister<E::ScalarField>,
+        ) -> ECDSAGadget<E>,
+    ) where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        L::Instruction: ECInstructions<SWCurve<E>>,
+    {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_ecdsa_verify", log::Level::Debug);
+
+        for witness in witnesses {
+            let mut builder = EmulatedBuilder::<L>::new();
+            let pubkey = builder.alloc_public_ec_point();
+            let msg_hash = builder.alloc_public::<FieldRegister<E::ScalarField>>();
+            let r = builder.alloc_public::<FieldRegister<E::ScalarField>>();
+            let s = builder.alloc_public::<FieldRegister<E::ScalarField>>();
+            let gadget = verify(&mut builder, &pubkey, &msg_hash, &r, &s);
+
+            let num_rows = 512;
+            let stark = builder.build::<C, 2>(num_rows);
+
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(witness, &mut writer);
+            stark.air_data.write_global_instructions(&mut writer);
+            writer_data.chunks_par(256).for_each(|mut chunk| {
+                for j in 0..256 {
+                    let mut writer = chunk.window_writer(j);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            });
+
+            let (trace, public) = (writer_data.trace, writer_data.public);
+            let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+            stark.verify(proof, &public).unwrap();
+        }
+
+        timing.print();
+    }
+
+    /// Proves the verification of the signature `witness` of the message `msg` hashed in the
+    /// AIR, and returns whether the proof verifies.
+    fn prove_sha256(witness: &ECDSASecp256k1Witness, msg: &[u8]) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = ECDSASHA256Test;
+
+        let num_rows = VERIFY_NUM_ROWS;
+        let mut builder = BytesBuilder::<L>::new();
+        let pubkey = builder.alloc_public_ec_point();
+        let padded_chunks = (0..SHA256::pad(msg).len() / 16)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let r = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let s = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let gadget = builder.ecdsa_verify_sha256(&pubkey, &padded_chunks, &r, &s);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        verifies_with(&stark, || {
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(witness, msg, &mut writer);
+            stark.air_data.write_global_instructions(&mut writer);
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+
+            (writer_data.trace, writer_data.public)
+        })
+    }
+
+    fn test_ecdsa_verify_batch<E: ECDSAParameters, L>(vectors: &[Vector])
+    where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        L::Instruction: ECInstructions<SWCurve<E>> + FromFieldInstruction<E::ScalarField>,
+    {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_ecdsa_verify_batch", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let inputs = vectors
+            .iter()
+            .map(|_| builder.alloc_public_ecdsa_inputs::<E>())
+            .collect::<Vec<_>>();
+        let gadget = builder.ecdsa_verify_batch(&inputs);
+
+        let num_rows = (256 * vectors.len()).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let witnesses = vectors
+            .iter()
+            .map(|vector| witness(vector, None))
+            .collect::<Vec<_>>();
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(&witnesses, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        writer_data.chunks_par(256).for_each(|mut chunk| {
+            for j in 0..256 {
+                let mut writer = chunk.window_writer(j);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_ecdsa_verify_pure() {
+        for vector in SECP256K1_VECTORS.iter() {
+            assert!(witness::<Secp256k1Parameters>(vector, None).verify());
+            assert!(!witness::<Secp256k1Parameters>(vector, Some(b"tampered".as_slice())).verify());
+        }
+        for vector in P256_VECTORS.iter() {
+            assert!(witness::<P256Parameters>(vector, None).verify());
+            assert!(!witness::<P256Parameters>(vector, Some(b"tampered".as_slice())).verify());
+        }
+        assert!(unreduced_x_witness().verify());
+    }
+
+    #[test]
+    fn test_ecdsa_verify_secp256k1() {
+        let mut witnesses = SECP256K1_VECTORS
+            .iter()
+            .map(|vector| witness(vector, None))
+            .collect::<Vec<_>>();
+        witnesses.push(unreduced_x_witness());
+        test_ecdsa_verify::<Secp256k1Parameters, ECDSASecp256k1Test>(
+            &witnesses,
+            |builder, pubkey, msg_hash, r, s| {
+                builder.ecdsa_verify_secp256k1(pubkey, msg_hash, r, s)
+            },
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_verify_p256() {
+        let witnesses = P256_VECTORS
+            .iter()
+            .map(|vector| witness(vector, None))
+            .collect::<Vec<_>>();
+        test_ecdsa_verify::<P256Parameters, ECDSAP256Test>(
+            &witnesses,
+            |builder, pubkey, msg_hash, r, s| builder.ecdsa_verify_p256(pubkey, msg_hash, r, s),
+        );
+    }
+
+    #[test]
+    fn test_ecdsa_verify_sha256() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for vector in SECP256K1_VECTORS.iter() {
+            let msg = vector.2.as_bytes();
+            assert!(prove_sha256(&witness(vector, None), msg));
+
+            // The signature of the message doesn't verify for the hash of another message.
+            assert!(!prove_sha256(&witness(vector, None), b"tampered"));
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_verify_batch_secp256k1() {
+        test_ecdsa_verify_batch::<Secp256k1Parameters, ECDSABatchSecp256k1Test>(&SECP256K1_VECTORS);
+    }
+
+    #[test]
+    fn test_ecdsa_verify_batch_p256() {
+        test_ecdsa_verify_batch::<P256Parameters, ECDSABatchP256Test>(&P256_VECTORS);
+    }
+}
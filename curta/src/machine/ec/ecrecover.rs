@@ -0,0 +1,355 @@
+//! Ethereum-style public key recovery from an ECDSA signature over secp256k1.
+//!
+//! Given a signature `(r, s)` of a message hash `z` and the parity `v` of the y-coordinate of the
+//! signature point `R`, the signer's public key is
+//!
+//! Q = r^(-1) * (s * R - z * G) = [u1]G + [u2]R
+//!
+//! where `R` is the point of x-coordinate `r` and `y`-parity `v`, `u1 = -z / r` and `u2 = s / r`
+//! over the scalar field. The gadget lifts `r` to `R` with the reduced square root of parity `v`
+//! in the base field, computes `[u1]G` and `[u2]R` with a single scalar multiplication batch and
+//! adds the two points.
+//!
+//! The recovered key is a public output. The Ethereum address it hashes to is left to the
+//! caller, as is the hash of the message.
+//!
+//! Reference: https://www.secg.org/sec1-v2.pdf, section 4.1.6
+
+use itertools::Itertools;
+use num::{BigUint, Zero};
+
+use super::builder::EllipticCurveBuilder;
+use super::ecdsa::ECDSABuilder;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::secp256k1::decompress::{lift_x, Secp256k1CompressedPointRegister};
+use crate::chip::ec::secp256k1::params::{
+    Secp256k1, Secp256k1BaseField, Secp256k1Parameters, Secp256k1ScalarField,
+};
+use crate::chip::ec::ECInstructions;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sqrt::FpSqrtInstruction;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The public registers of a public key recovery.
+#[derive(Debug, Clone, Copy)]
+pub struct ECRecoverGadget {
+    pub msg_hash: FieldRegister<Secp256k1ScalarField>,
+    pub r: FieldRegister<Secp256k1ScalarField>,
+    pub s: FieldRegister<Secp256k1ScalarField>,
+    /// The parity of the y-coordinate of `R`.
+    pub v: BitRegister,
+    /// The recovered public key.
+    pub pubkey: AffinePointRegister<Secp256k1>,
+    /// The result of `[u1]G`.
+    u1_mul_g: AffinePointRegister<Secp256k1>,
+    /// The result of `[u2]R`.
+    u2_mul_r: AffinePointRegister<Secp256k1>,
+}
+
+/// The values of a public key recovery.
+#[derive(Debug, Clone)]
+pub struct ECRecoverWitness {
+    /// The message hash `z`, reduced modulo the group order.
+    pub msg_hash: BigUint,
+    pub r: BigUint,
+    pub s: BigUint,
+    pub v: bool,
+}
+
+pub trait ECRecoverBuilder: Builder {
+    /// Recovers the public key that signed `msg_hash` with the signature `(r, s, v)`.
+    ///
+    /// All the registers must be public, and their values are written by
+    /// `ECRecoverGadget::write` before the global instructions. The recovery takes a scalar
+    /// multiplication batch of two operations, so the trace must have 512 rows.
+    ///
+    /// The x-coordinate of `R` is taken to be `r`, so the recovery ids `2` and `3`, for which
+    /// it is `r + n`, are not supported. The message hash must not vanish and the points `[u1]G`
+    /// and `[u2]R` must be distinct.
+    fn ecrecover(
+        &mut self,
+        msg_hash: &FieldRegister<Secp256k1ScalarField>,
+        r: &FieldRegister<Secp256k1ScalarField>,
+        s: &FieldRegister<Secp256k1ScalarField>,
+        v: &BitRegister,
+    ) -> ECRecoverGadget
+    where
+        Self::Instruction: ECInstructions<Secp256k1>
+            + FromFieldInstruction<Secp256k1ScalarField>
+            + From<FpSqrtInstruction<Secp256k1BaseField>>,
+    {
+        assert!(
+            !msg_hash.is_trace() && !r.is_trace() && !s.is_trace() && !v.is_trace(),
+            "Inputs must be public"
+        );
+
+        // Lift `r` to the point `R` with the y-coordinate of parity `v`, which binds `v` to `R`.
+        let x = FieldRegister::<Secp256k1BaseField>::from_register_unsafe(*r.register());
+        let sig_r = self
+            .api()
+            .secp256k1_decompress(&Secp256k1CompressedPointRegister::new(x, *v));
+
+        // The inversion of `r` fails for `r = 0`.
+        let z_div_r = self.api().fp_div(msg_hash, r);
+        let zero = self.api().fp_zero();
+        let u1 = self.api().fp_sub(&zero, &z_div_r);
+        let u2 = self.api().fp_div(s, r);
+        let u1 = self.ecdsa_scalar::<Secp256k1Parameters>(&u1);
+        let u2 = self.ecdsa_scalar::<Secp256k1Parameters>(&u2);
+
+        let g = self.generator();
+        let u1_mul_g = self.alloc_public_ec_point();
+        let u2_mul_r = self.alloc_public_ec_point();
+        self.scalar_mul_batch([g, sig_r], [u1, u2], [u1_mul_g, u2_mul_r]);
+
+        let pubkey = self.api().secp256k1_add(&u1_mul_g, &u2_mul_r);
+
+        ECRecoverGadget {
+            msg_hash: *msg_hash,
+            r: *r,
+            s: *s,
+            v: *v,
+            pubkey,
+            u1_mul_g,
+            u2_mul_r,
+        }
+    }
+}
+
+impl<B: Builder> ECRecoverBuilder for B {}
+
+impl ECRecoverGadget {
+    /// Writes the message hash, the signature and the scalar multiplication results.
+    ///
+    /// The recovered key can be read once the global instructions are written.
+    pub fn write<F: Field>(
+        &self,
+        witness: &ECRecoverWitness,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for (register, value) in [self.msg_hash, self.r, self.s].iter().zip_eq([
+            &witness.msg_hash,
+            &witness.r,
+            &witness.s,
+        ]) {
+            writer.write(
+                register,
+                &to_u16_le_limbs_polynomial::<F, Secp256k1ScalarField>(value),
+            );
+        }
+        writer.write(&self.v, &F::from_bool(witness.v));
+
+        let (u1, u2) = witness.scalars();
+        let sig_r = witness.sig_r().expect("invalid signature");
+        writer.write_ec_point(&self.u1_mul_g, &Secp256k1::generator().sw_scalar_mul(&u1));
+        writer.write_ec_point(&self.u2_mul_r, &sig_r.sw_scalar_mul(&u2));
+    }
+}
+
+impl ECRecoverWitness {
+    /// Parses a message hash and a 65-byte signature `r || s || v`, where `v` is either the
+    /// recovery id or the recovery id plus 27, as in Ethereum transactions.
+    pub fn new(msg_hash: &[u8; 32], sig: &[u8; 65]) -> Self {
+        let v = match sig[64] {
+            0 | 27 => false,
+            1 | 28 => true,
+            v => panic!("unsupported recovery id {}", v),
+        };
+        Self {
+            msg_hash: BigUint::from_bytes_be(msg_hash) % Secp256k1ScalarField::modulus(),
+            r: BigUint::from_bytes_be(&sig[..32]),
+            s: BigUint::from_bytes_be(&sig[32..64]),
+            v,
+        }
+    }
+
+    /// The point `R` of x-coordinate `r` and y-parity `v`, if it exists.
+    fn sig_r(&self) -> Option<AffinePoint<Secp256k1>> {
+        lift_x(&self.r, self.v)
+    }
+
+    /// The scalars `u1 = -z / r` and `u2 = s / r`.
+    fn scalars(&self) -> (BigUint, BigUint) {
+        let n = Secp256k1ScalarField::modulus();
+        let r_inv = self.r.modpow(&(&n - 2u32), &n);
+        let u1 = (&n - (&self.msg_hash * &r_inv) % &n) % &n;
+        (u1, (&self.s * &r_inv) % &n)
+    }
+
+    /// Recovers the public key outside of the AIR.
+    pub fn recover(&self) -> Option<AffinePoint<Secp256k1>> {
+        let n = Secp256k1ScalarField::modulus();
+        let is_in_range = |x: &BigUint| !x.is_zero() && x < &n;
+        if !is_in_range(&self.r) || !is_in_range(&self.s) || self.msg_hash.is_zero() {
+            return None;
+        }
+        let sig_r = self.sig_r()?;
+        let (u1, u2) = self.scalars();
+        let u1_mul_g = Secp256k1::generator().sw_scalar_mul(&u1);
+        let u2_mul_r = sig_r.sw_scalar_mul(&u2);
+        if u1_mul_g.x == u2_mul_r.x {
+            return None;
+        }
+        Some(u1_mul_g.sw_add(&u2_mul_r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::secp256k1::instruction::Secp256k1Instruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::tests::assert_rejected;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::maybe_rayon::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECRecoverTest;
+
+    impl AirParameters for ECRecoverTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    /// The signatures of the `ecdsa` tests in the `r || s || v` format, as (SHA-256 message hash,
+    /// signature, public key x, public key y).
+    const VECTORS: [(&str, &str, &str, &str); 3] = [
+        (
+            "a0dc65ffca799873cbea0ac274015b9526505daaaed385155425f7337704883e",
+            "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e51c",
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        ),
+        (
+            "a0dc65ffca799873cbea0ac274015b9526505daaaed385155425f7337704883e",
+            "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d06b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed51b",
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "b7c52588d95c3b9aa25b0403f1eef75702e84bb7597aabe663b82f6f04ef2777",
+        ),
+        (
+            "4ba38d48a60f1b29e9eb726eaff08b2e83d8d81e031666fee50e85900d7dc1ef",
+            "7063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c58dfcc1e00a35e1572f366ffe34ba0fc47db1e7189759b9fb233c5b05ab388ea1b",
+            "92df7b245b81aa637ab4e867c8d511008f79161a97d64f2ac709600352f7acbc",
+            "e9bfdf1b13fa0cb1de4521e5386cde3a1cd26c5ab584989d07bbed58a5419f62",
+        ),
+    ];
+
+    fn vector(index: usize) -> (ECRecoverWitness, AffinePoint<Secp256k1>) {
+        let (msg_hash, sig, x, y) = VECTORS[index];
+        let msg_hash: [u8; 32] = hex::decode(msg_hash).unwrap().try_into().unwrap();
+        let sig: [u8; 65] = hex::decode(sig).unwrap().try_into().unwrap();
+        let hex_int = |value: &str| BigUint::parse_bytes(value.as_bytes(), 16).unwrap();
+        let pubkey = AffinePoint::new(hex_int(x), hex_int(y));
+        (ECRecoverWitness::new(&msg_hash, &sig), pubkey)
+    }
+
+    #[test]
+    fn test_ecrecover_pure() {
+        for i in 0..VECTORS.len() {
+            let (mut witness, pubkey) = vector(i);
+            assert_eq!(witness.recover(), Some(pubkey.clone()));
+            witness.v = !witness.v;
+            assert_ne!(witness.recover(), Some(pubkey));
+        }
+    }
+
+    #[test]
+    fn test_ecrecover() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = ECRecoverTest;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_ecrecover", log::Level::Debug);
+
+        for i in 0..VECTORS.len() {
+            let mut builder = EmulatedBuilder::<L>::new();
+            let msg_hash = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+            let r = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+            let s = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+            let v = builder.alloc_public::<BitRegister>();
+            let gadget = builder.ecrecover(&msg_hash, &r, &s, &v);
+
+            let num_rows = 512;
+            let stark = builder.build::<C, 2>(num_rows);
+
+            let (witness, pubkey) = vector(i);
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(&witness, &mut writer);
+            stark.air_data.write_global_instructions(&mut writer);
+            assert_eq!(writer.read_ec_point(&gadget.pubkey), pubkey);
+            writer_data.chunks_par(256).for_each(|mut chunk| {
+                for j in 0..256 {
+                    let mut writer = chunk.window_writer(j);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            });
+
+            let (trace, public) = (writer_data.trace, writer_data.public);
+            let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+            stark.verify(proof, &public).unwrap();
+        }
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_ecrecover_wrong_parity() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = ECRecoverTest;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let msg_hash = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let r = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let s = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let v = builder.alloc_public::<BitRegister>();
+        let gadget = builder.ecrecover(&msg_hash, &r, &s, &v);
+
+        let num_rows = 512;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write the recovery with the opposite parity of `R`, then claim the parity `v`.
+        let (witness, pubkey) = vector(0);
+        let mut flipped = witness.clone();
+        flipped.v = !witness.v;
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(&flipped, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        assert_ne!(writer.read_ec_point(&gadget.pubkey), pubkey);
+        writer.write(&gadget.v, &F::from_bool(witness.v));
+        writer_data.chunks_par(256).for_each(|mut chunk| {
+            for j in 0..256 {
+                let mut writer = chunk.window_writer(j);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        assert_rejected(&stark, &trace, &public);
+    }
+}
@@ -0,0 +1,758 @@
+//! ECVRF proof verification with the `ECVRF-EDWARDS25519-SHA512-TAI` suite of RFC 9381.
+//!
+//! A proof `pi = (Gamma, c, s)` of the input `alpha` under the public key `Y` is valid if
+//!
+//! U = [s]B - [c]Y
+//! V = [s]H - [c]Gamma
+//!
+//! hash to the challenge `c = SHA-512(0x03 || 0x02 || Y || H || Gamma || U || V || 0x00)[..16]`,
+//! where `H = [8]H'` and `H'` is the first point found by hashing `0x03 || 0x01 || Y || alpha ||
+//! ctr || 0x00` with SHA-512 for increasing values of the counter. The output of the VRF is
+//! `beta = SHA-512(0x03 || 0x03 || [8]Gamma || 0x00)`.
+//!
+//! All the hashes are computed by a single SHA-512 AIR in the same trace as the scalar
+//! multiplications, one for each counter that fits in the trace. The prover selects the counter
+//! of `H'` and shows that the hashes of the smaller counters do not decode to a point: their
+//! coordinate `y` gives a ratio `(y^2 - 1) / (d * y^2 + 1)` which is not a square, as twice the
+//! ratio has a square root and `2` is not a square modulo `p`. The points `H`, `U`, `V` and
+//! `[8]Gamma` are given by their canonical encodings, which are the bytes hashed by the AIR, and
+//! the gadget checks
+//!
+//! [s]B = U + [c]Y
+//! [s]H = V + [c]Gamma
+//!
+//! along with `H = [8]H'` and the cofactor multiple of `Gamma`, with a single scalar
+//! multiplication batch.
+//!
+//! Reference: https://www.rfc-editor.org/rfc/rfc9381#section-5.3
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+use itertools::Itertools;
+use num::{BigUint, Zero};
+
+use super::builder::EllipticCurveBuilder;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::edwards::ed25519::add::Ed25519AddInstruction;
+use crate::chip::ec::edwards::ed25519::decompress::decompress;
+use crate::chip::ec::edwards::ed25519::params::{
+    Ed25519, Ed25519BaseField, Ed25519Parameters, Ed25519ScalarField,
+};
+use crate::chip::ec::edwards::ed25519::sqrt::{sqrt, Ed25519FpSqrtInstruction};
+use crate::chip::ec::edwards::EdwardsParameters;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::ec::scalar::ECScalarRegister;
+use crate::chip::ec::{ECInstructions, EllipticCurve};
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::machine::hash::sha::sha512::register::SHA512DigestRegister;
+use crate::machine::hash::sha::sha512::SHA512;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The identifier of the `ECVRF-EDWARDS25519-SHA512-TAI` suite.
+pub const SUITE_STRING: u8 = 0x03;
+
+/// The length of a proof `Gamma || c || s`.
+pub const PROOF_BYTES: usize = 80;
+
+/// The length of the challenge `c`.
+pub const CHALLENGE_BYTES: usize = 16;
+
+/// The number of rows of the trace of `BytesBuilder::ecvrf_verify`, taken by a scalar
+/// multiplication batch of four operations.
+pub const VERIFY_NUM_ROWS: usize = 1024;
+
+/// The number of rows taken by the compression of a SHA-512 chunk.
+const CHUNK_ROWS: usize = 80;
+
+/// The lengths of the inputs of the challenge and of the output hashes.
+const CHALLENGE_INPUT_BYTES: usize = 3 + 5 * 32;
+const OUTPUT_INPUT_BYTES: usize = 3 + 32;
+
+/// The number of counters of `encode_to_curve` tried by `BytesBuilder::ecvrf_verify` for an
+/// input of `alpha_len` bytes, as many as fit in the trace along with the other hashes.
+pub fn encode_attempts(alpha_len: usize) -> usize {
+    let num_chunks = |len: usize| SHA512::pad(&vec![0; len]).len() / 16;
+    let free_chunks = (VERIFY_NUM_ROWS / CHUNK_ROWS)
+        .saturating_sub(num_chunks(CHALLENGE_INPUT_BYTES) + num_chunks(OUTPUT_INPUT_BYTES));
+    free_chunks / num_chunks(36 + alpha_len)
+}
+
+/// The registers of an ECVRF proof verification.
+#[derive(Debug, Clone)]
+pub struct ECVRFGadget {
+    pub pubkey: ArrayRegister<ByteRegister>,
+    pub alpha: ArrayRegister<ByteRegister>,
+    /// The bytes of the proof `Gamma || c || s`.
+    pub proof: ArrayRegister<ByteRegister>,
+    /// The output `beta`, computed by the SHA-512 AIR.
+    pub beta: SHA512DigestRegister,
+    /// The bits selecting the counter of `H'`, one for each attempt.
+    ctr_bits: ArrayRegister<BitRegister>,
+    /// The square roots showing that the attempts before the counter don't decode.
+    non_square_roots: Vec<FieldRegister<Ed25519BaseField>>,
+    /// The encodings of `H`, `U`, `V` and `[8]Gamma`.
+    points: [ArrayRegister<ByteRegister>; 4],
+    /// The digests of the attempts of `encode_to_curve`.
+    encode_digests: Vec<SHA512DigestRegister>,
+    challenge_digest: SHA512DigestRegister,
+    /// The results of `[s]B`, `[c]Y`, `[s]H` and `[c]Gamma`.
+    products: [AffinePointRegister<Ed25519>; 4],
+}
+
+/// The values of an ECVRF proof verification, computed from the raw proof.
+#[derive(Debug, Clone)]
+pub struct ECVRFWitness {
+    pub pubkey: CompressedEdwardsY,
+    pub alpha: Vec<u8>,
+    pub gamma: CompressedEdwardsY,
+    pub c: BigUint,
+    pub s: BigUint,
+    /// The counter of `H'` in `encode_to_curve`.
+    pub ctr: u8,
+    /// The point `H'` given by the try-and-increment hash.
+    pub hash_point: CompressedEdwardsY,
+    /// The point `H = [8]H'`.
+    pub h: CompressedEdwardsY,
+    pub u: CompressedEdwardsY,
+    pub v: CompressedEdwardsY,
+    /// The point `[8]Gamma` hashed to the output.
+    pub cofactor_gamma: CompressedEdwardsY,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions
+        + ECInstructions<Ed25519>
+        + From<FpCompareInstruction<Ed25519BaseField>>
+        + From<FpCompareInstruction<Ed25519ScalarField>>
+        + From<Ed25519FpSqrtInstruction>
+        + From<Ed25519AddInstruction>,
+{
+    /// Allocates the public key, the input `alpha` of `alpha_len` bytes and the proof, and
+    /// verifies the proof.
+    ///
+    /// The registers are public, and their values are written by `ECVRFGadget::write` before the
+    /// global instructions. As `ECVRFWitness::verify`, the AIR rejects non-canonical encodings,
+    /// values `s >= L` and public keys of small order.
+    ///
+    /// The trace must have `VERIFY_NUM_ROWS` rows, which limits the counter of `H'` to less than
+    /// `encode_attempts(alpha_len)`, nine for inputs of up to 75 bytes. A valid proof with a
+    /// larger counter, which happens with probability `2^-encode_attempts(alpha_len)`, can't be
+    /// proven, nor one where an earlier attempt has a square ratio and fails to decode for
+    /// another reason, which happens with negligible probability.
+    pub fn ecvrf_verify(&mut self, alpha_len: usize) -> ECVRFGadget {
+        let num_attempts = encode_attempts(alpha_len);
+        assert!(num_attempts > 0, "Input of {} bytes is too long", alpha_len);
+
+        let pubkey = self.alloc_array_public::<ByteRegister>(32);
+        let alpha = self.alloc_array_public::<ByteRegister>(alpha_len);
+        let proof = self.alloc_array_public::<ByteRegister>(PROOF_BYTES);
+        let points = [(); 4].map(|_| self.alloc_array_public::<ByteRegister>(32));
+        let [h, u, v, cofactor_gamma] = points;
+        let gamma = proof.get_subarray(0..32);
+
+        let constant =
+            |byte: u8| ArithmeticExpression::from_constant(L::Field::from_canonical_u8(byte));
+        let bytes = |array: ArrayRegister<ByteRegister>| {
+            array.iter().map(|byte| byte.expr()).collect::<Vec<_>>()
+        };
+        let mut inputs = (0..num_attempts)
+            .map(|ctr| {
+                [
+                    vec![constant(SUITE_STRING), constant(0x01)],
+                    bytes(pubkey),
+                    bytes(alpha),
+                    vec![constant(ctr as u8), constant(0x00)],
+                ]
+                .concat()
+            })
+            .collect::<Vec<_>>();
+        inputs.push(
+            [
+                vec![constant(SUITE_STRING), constant(0x02)],
+                bytes(pubkey),
+                bytes(h),
+                bytes(gamma),
+                bytes(u),
+                bytes(v),
+                vec![constant(0x00)],
+            ]
+            .concat(),
+        );
+        inputs.push(
+            [
+                vec![constant(SUITE_STRING), constant(0x03)],
+                bytes(cofactor_gamma),
+                vec![constant(0x00)],
+            ]
+            .concat(),
+        );
+
+        let mut padded_chunks = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_index_values = Vec::new();
+        for input in inputs.iter() {
+            padded_chunks.extend(self.ecvrf_hash_chunks(input));
+            end_bits_values.resize(padded_chunks.len() - 1, L::Field::ZERO);
+            end_bits_values.push(L::Field::ONE);
+            digest_index_values.push(L::Field::from_canonical_usize(padded_chunks.len() - 1));
+        }
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices = self.constant_array::<ElementRegister>(&digest_index_values);
+        let digests = inputs
+            .iter()
+            .map(|_| self.alloc_public::<SHA512DigestRegister>())
+            .collect::<Vec<_>>();
+        SHA512::sha_with_digests_in_rows(
+            self,
+            &padded_chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &digests,
+            VERIFY_NUM_ROWS,
+        );
+        let encode_digests = digests[..num_attempts].to_vec();
+        let (challenge_digest, beta) = (digests[num_attempts], digests[num_attempts + 1]);
+
+        // Byte `k` of a digest is byte `7 - k % 8` of the big-endian word `k / 8`.
+        let digest_byte = |digest: &SHA512DigestRegister, k: usize| {
+            digest.get(k / 8).to_le_bytes().get(7 - k % 8)
+        };
+
+        // The counter of `H'` is given by the only set bit among the attempts.
+        let ctr_bits = self.alloc_array_public::<BitRegister>(num_attempts);
+        let num_set_bits = ctr_bits
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr());
+        self.assert_expression_zero(num_set_bits - ArithmeticExpression::one());
+        let hash_point_bytes = self.alloc_array_public_unchecked::<ByteRegister>(32);
+        for (k, byte) in hash_point_bytes.iter().enumerate() {
+            let selected = ctr_bits
+                .iter()
+                .zip_eq(encode_digests.iter())
+                .fold(ArithmeticExpression::zero(), |acc, (bit, digest)| {
+                    acc + bit.expr() * digest_byte(digest, k).expr()
+                });
+            self.set_to_expression(&byte, selected);
+        }
+
+        // The attempts before the counter give a coordinate `y` for which the ratio
+        // `(y^2 - 1) / (d * y^2 + 1)` is not a square, as twice the ratio is one. The denominator
+        // doesn't vanish since `-1 / d` is not a square.
+        let one = self.api().fp_one::<Ed25519BaseField>();
+        let mut non_square_roots = Vec::with_capacity(num_attempts - 1);
+        for (i, digest) in encode_digests.iter().enumerate().take(num_attempts - 1) {
+            let is_before_ctr = (i + 1..num_attempts)
+                .fold(ArithmeticExpression::zero(), |acc, j| {
+                    acc + ctr_bits.get(j).expr()
+                });
+            let y_bytes = (0..32).map(|k| digest_byte(digest, k)).collect::<Vec<_>>();
+            let y = self.ed25519_compressed_point(&y_bytes).y;
+            let yy = self.api().fp_mul(&y, &y);
+            let numerator = self.api().fp_sub(&yy, &one);
+            let d_mul_yy = self.api().fp_mul_const(&yy, Ed25519Parameters::D);
+            let denominator = self.api().fp_add(&d_mul_yy, &one);
+            let ratio = self.api().fp_div(&numerator, &denominator);
+            let twice_ratio = self.api().fp_add(&ratio, &ratio);
+
+            let root = self.alloc_public::<FieldRegister<Ed25519BaseField>>();
+            let root_squared = self.api().fp_mul(&root, &root);
+            let difference = self.api().fp_sub(&root_squared, &twice_ratio);
+            let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*difference.register());
+            for limb in limbs.iter() {
+                self.assert_expression_zero(limb.expr() * is_before_ctr.clone());
+            }
+            non_square_roots.push(root);
+        }
+
+        let y_point = self.ecvrf_point(&pubkey);
+        let gamma_point = self.ecvrf_point(&gamma);
+        let hash_point = self.ecvrf_point(&hash_point_bytes);
+        let [h_point, u_point, v_point, cofactor_gamma_point] =
+            points.map(|bytes| self.ecvrf_point(&bytes));
+
+        let [cofactor_y, cofactor_hash_point, cofactor_gamma_mul] =
+            [y_point, hash_point, gamma_point]
+                .map(|point| (0..3).fold(point, |acc, _| self.api().ed25519_add(&acc, &acc)));
+        self.assert_equal(&cofactor_hash_point.x, &h_point.x);
+        self.assert_equal(&cofactor_hash_point.y, &h_point.y);
+        self.assert_equal(&cofactor_gamma_mul.x, &cofactor_gamma_point.x);
+        self.assert_equal(&cofactor_gamma_mul.y, &cofactor_gamma_point.y);
+
+        // A public key of small order has `[8]Y = (0, 1)`, and any other key has a non-zero
+        // x-coordinate for `[8]Y`, which is checked to be invertible.
+        self.api().fp_inv(&cofactor_y.x);
+
+        // The challenge is the truncated digest, and `s` is canonical.
+        let c = self.ecvrf_scalar(&proof.get_subarray(32..32 + CHALLENGE_BYTES));
+        for k in 0..CHALLENGE_BYTES {
+            self.assert_expression_zero(
+                proof.get(32 + k).expr() - digest_byte(&challenge_digest, k).expr(),
+            );
+        }
+        let s_bytes = proof.get_subarray(32 + CHALLENGE_BYTES..PROOF_BYTES);
+        let s = self.ecvrf_scalar(&s_bytes);
+        let s_field = self.alloc_public_unchecked::<FieldRegister<Ed25519ScalarField>>();
+        let s_limbs = ArrayRegister::<U16Register>::from_register_unsafe(*s_field.register());
+        for (i, limb) in s_limbs.iter().enumerate() {
+            self.set_to_expression(
+                &limb,
+                s_bytes.get(2 * i).expr()
+                    + s_bytes.get(2 * i + 1).expr() * L::Field::from_canonical_u32(1 << 8),
+            );
+        }
+        let is_reduced = self.api().fp_is_reduced(&s_field);
+        self.assert_expression_zero(is_reduced.not_expr());
+
+        let b = self.generator();
+        let products: [AffinePointRegister<Ed25519>; 4] =
+            [(); 4].map(|_| self.alloc_public_ec_point());
+        let [s_mul_b, c_mul_y, s_mul_h, c_mul_gamma] = products;
+        self.scalar_mul_batch([b, y_point, h_point, gamma_point], [s, c, s, c], products);
+
+        let u_plus_c_mul_y = self.api().ed25519_add(&u_point, &c_mul_y);
+        self.assert_equal(&s_mul_b.x, &u_plus_c_mul_y.x);
+        self.assert_equal(&s_mul_b.y, &u_plus_c_mul_y.y);
+        let v_plus_c_mul_gamma = self.api().ed25519_add(&v_point, &c_mul_gamma);
+        self.assert_equal(&s_mul_h.x, &v_plus_c_mul_gamma.x);
+        self.assert_equal(&s_mul_h.y, &v_plus_c_mul_gamma.y);
+
+        ECVRFGadget {
+            pubkey,
+            alpha,
+            proof,
+            beta,
+            ctr_bits,
+            non_square_roots,
+            points,
+            encode_digests,
+            challenge_digest,
+            products,
+        }
+    }
+
+    /// Allocates the chunks of the SHA-512 input whose bytes are `input`, padded with
+    /// `SHA512::pad`.
+    fn ecvrf_hash_chunks(
+        &mut self,
+        input: &[ArithmeticExpression<L::Field>],
+    ) -> Vec<ArrayRegister<U64Register>> {
+        let padding = SHA512::pad(&vec![0; input.len()]);
+        let chunks = (0..padding.len() / 16)
+            .map(|_| self.alloc_array_public_unchecked::<U64Register>(16))
+            .collect::<Vec<_>>();
+        // Byte `k` of the input is byte `7 - k % 8` of the big-endian word `k / 8`.
+        for k in 0..8 * padding.len() {
+            let byte = chunks[k / 128]
+                .get(k % 128 / 8)
+                .to_le_bytes()
+                .get(7 - k % 8);
+            let value = input.get(k).cloned().unwrap_or_else(|| {
+                let padding_byte = padding[k / 8].to_be_bytes()[k % 8];
+                ArithmeticExpression::from_constant(L::Field::from_canonical_u8(padding_byte))
+            });
+            self.set_to_expression(&byte, value);
+        }
+        chunks
+    }
+
+    /// Decompresses the point encoded by the 32 bytes `bytes`, whose coordinate `y` must be
+    /// canonical.
+    fn ecvrf_point(&mut self, bytes: &ArrayRegister<ByteRegister>) -> AffinePointRegister<Ed25519> {
+        let point = self.ed25519_compressed_point(&bytes.iter().collect::<Vec<_>>());
+        let is_reduced = self.api().fp_is_reduced(&point.y);
+        self.assert_expression_zero(is_reduced.not_expr());
+        self.api().ed25519_decompress(&point)
+    }
+
+    /// The scalar of the little-endian bytes `bytes`, of which there are at most 32.
+    fn ecvrf_scalar(&mut self, bytes: &ArrayRegister<ByteRegister>) -> ECScalarRegister<Ed25519> {
+        let limbs = self.alloc_array_public_unchecked::<ElementRegister>(8);
+        for (j, limb) in limbs.iter().enumerate() {
+            let value =
+                (4 * j..bytes.len().min(4 * j + 4)).fold(ArithmeticExpression::zero(), |acc, k| {
+                    acc + bytes.get(k).expr() * L::Field::from_canonical_u32(1 << (8 * (k % 4)))
+                });
+            self.set_to_expression(&limb, value);
+        }
+        ECScalarRegister::new(limbs)
+    }
+}
+
+impl ECVRFGadget {
+    /// Writes the public key, the input, the proof and the intermediate values of the
+    /// verification.
+    pub fn write<F: PrimeField64>(
+        &self,
+        witness: &ECVRFWitness,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        let num_attempts = self.ctr_bits.len();
+        let ctr = witness.ctr as usize;
+        assert!(
+            ctr < num_attempts,
+            "Counter {} doesn't fit in the trace",
+            ctr
+        );
+
+        let field_bytes =
+            |bytes: &[u8]| bytes.iter().map(|b| F::from_canonical_u8(*b)).collect_vec();
+        writer.write_array(&self.pubkey, field_bytes(witness.pubkey.as_bytes()));
+        writer.write_array(&self.alpha, field_bytes(&witness.alpha));
+        writer.write_array(&self.proof, field_bytes(&witness.proof_bytes()));
+        for (register, point) in
+            self.points
+                .iter()
+                .zip_eq([&witness.h, &witness.u, &witness.v, &witness.cofactor_gamma])
+        {
+            writer.write_array(register, field_bytes(point.as_bytes()));
+        }
+        writer.write_array(
+            &self.ctr_bits,
+            (0..num_attempts).map(|i| if i == ctr { F::ONE } else { F::ZERO }),
+        );
+
+        let encode_inputs = (0..num_attempts)
+            .map(|i| ECVRFWitness::encode_input(witness.pubkey.as_bytes(), &witness.alpha, i as u8))
+            .collect::<Vec<_>>();
+        for (i, root) in self.non_square_roots.iter().enumerate() {
+            let value = if i < ctr {
+                ECVRFWitness::non_square_root(&ECVRFWitness::hash(&encode_inputs[i]))
+            } else {
+                BigUint::zero()
+            };
+            writer.write(
+                root,
+                &to_u16_le_limbs_polynomial::<F, Ed25519BaseField>(&value),
+            );
+        }
+        for (digest, input) in self.encode_digests.iter().zip_eq(encode_inputs.iter()) {
+            writer.write_array(
+                &digest.as_array(),
+                SHA512::hash(input).map(u64_to_le_field_bytes),
+            );
+        }
+        let challenge_input = ECVRFWitness::challenge_input([
+            &witness.pubkey,
+            &witness.h,
+            &witness.gamma,
+            &witness.u,
+            &witness.v,
+        ]);
+        writer.write_array(
+            &self.challenge_digest.as_array(),
+            SHA512::hash(&challenge_input).map(u64_to_le_field_bytes),
+        );
+        writer.write_array(
+            &self.beta.as_array(),
+            SHA512::hash(&witness.proof_to_hash_input()).map(u64_to_le_field_bytes),
+        );
+
+        let y = decompress(&witness.pubkey);
+        let h = decompress(&witness.h);
+        let gamma = decompress(&witness.gamma);
+        let values = [
+            &Ed25519::ec_generator() * &witness.s,
+            &y * &witness.c,
+            &h * &witness.s,
+            &gamma * &witness.c,
+        ];
+        for (register, value) in self.products.iter().zip_eq(values.iter()) {
+            writer.write_ec_point(register, value);
+        }
+    }
+}
+
+impl ECVRFWitness {
+    /// Parses the 32-byte public key and the 80-byte proof `Gamma || c || s` of `alpha`.
+    ///
+    /// Returns `None` if a point does not decode or if no counter gives a point in
+    /// `encode_to_curve`, in which case the proof is invalid.
+    pub fn new(pubkey: &[u8; 32], proof: &[u8; PROOF_BYTES], alpha: &[u8]) -> Option<Self> {
+        let pubkey = CompressedEdwardsY(*pubkey);
+        let gamma = CompressedEdwardsY(proof[..32].try_into().unwrap());
+        let c = BigUint::from_bytes_le(&proof[32..32 + CHALLENGE_BYTES]);
+        let s = BigUint::from_bytes_le(&proof[32 + CHALLENGE_BYTES..]);
+
+        let y_point = pubkey.decompress()?;
+        let gamma_point = gamma.decompress()?;
+        let (ctr, hash_point) = Self::encode_to_curve(pubkey.as_bytes(), alpha)?;
+        let h_point = hash_point.decompress()?.mul_by_cofactor();
+
+        let (c_scalar, s_scalar) = (Self::scalar(&c), Self::scalar(&s));
+        let u = s_scalar * ED25519_BASEPOINT_POINT - c_scalar * y_point;
+        let v = s_scalar * h_point - c_scalar * gamma_point;
+
+        Some(Self {
+            pubkey,
+            alpha: alpha.to_vec(),
+            gamma,
+            c,
+            s,
+            ctr,
+            hash_point,
+            h: h_point.compress(),
+            u: u.compress(),
+            v: v.compress(),
+            cofactor_gamma: gamma_point.mul_by_cofactor().compress(),
+        })
+    }
+
+    /// The counter and the point `H'` of `ECVRF_encode_to_curve_try_and_increment`, before the
+    /// multiplication by the cofactor.
+    pub fn encode_to_curve(pubkey: &[u8; 32], alpha: &[u8]) -> Option<(u8, CompressedEdwardsY)> {
+        (0..=u8::MAX).find_map(|ctr| {
+            let digest = Self::hash(&Self::encode_input(pubkey, alpha, ctr));
+            let point = CompressedEdwardsY(digest[..32].try_into().unwrap());
+            point.decompress().map(|_| (ctr, point))
+        })
+    }
+
+    /// The challenge `SHA-512(0x03 || 0x02 || Y || H || Gamma || U || V || 0x00)`, truncated to
+    /// its first 16 bytes and interpreted as a little-endian integer.
+    pub fn challenge(points: [&CompressedEdwardsY; 5]) -> BigUint {
+        BigUint::from_bytes_le(&Self::hash(&Self::challenge_input(points))[..CHALLENGE_BYTES])
+    }
+
+    /// The output `beta = SHA-512(0x03 || 0x03 || [8]Gamma || 0x00)` of the VRF.
+    pub fn proof_to_hash(&self) -> [u8; 64] {
+        Self::hash(&self.proof_to_hash_input())
+    }
+
+    /// The bytes of the proof `Gamma || c || s`.
+    pub fn proof_bytes(&self) -> [u8; PROOF_BYTES] {
+        let scalar_bytes = |value: &BigUint, len: usize| {
+            let mut bytes = value.to_bytes_le();
+            bytes.resize(len, 0);
+            bytes
+        };
+        [
+            self.gamma.as_bytes().as_slice(),
+            &scalar_bytes(&self.c, CHALLENGE_BYTES),
+            &scalar_bytes(&self.s, PROOF_BYTES - 32 - CHALLENGE_BYTES),
+        ]
+        .concat()
+        .try_into()
+        .unwrap()
+    }
+
+    /// Checks the proof outside of the AIR.
+    ///
+    /// Besides the challenge, this rejects non-canonical values of `s` and public keys of small
+    /// order.
+    pub fn verify(&self) -> bool {
+        if self.s >= Ed25519::prime_group_order() {
+            return false;
+        }
+        let is_small_order = self
+            .pubkey
+            .decompress()
+            .map_or(true, |point| point.is_small_order());
+        if is_small_order {
+            return false;
+        }
+        self.c == Self::challenge([&self.pubkey, &self.h, &self.gamma, &self.u, &self.v])
+    }
+
+    /// The input `0x03 || 0x01 || Y || alpha || ctr || 0x00` of an attempt of `encode_to_curve`.
+    fn encode_input(pubkey: &[u8; 32], alpha: &[u8], ctr: u8) -> Vec<u8> {
+        [
+            &[SUITE_STRING, 0x01],
+            pubkey.as_slice(),
+            alpha,
+            &[ctr, 0x00],
+        ]
+        .concat()
+    }
+
+    fn challenge_input(points: [&CompressedEdwardsY; 5]) -> Vec<u8> {
+        [
+            [SUITE_STRING, 0x02].as_slice(),
+            &points.iter().flat_map(|p| p.to_bytes()).collect::<Vec<_>>(),
+            &[0x00],
+        ]
+        .concat()
+    }
+
+    fn proof_to_hash_input(&self) -> Vec<u8> {
+        [
+            [SUITE_STRING, 0x03].as_slice(),
+            self.cofactor_gamma.as_bytes(),
+            &[0x00],
+        ]
+        .concat()
+    }
+
+    /// The square root of twice the ratio `(y^2 - 1) / (d * y^2 + 1)` for the coordinate `y` of
+    /// an attempt of `encode_to_curve` whose ratio is not a square.
+    fn non_square_root(digest: &[u8; 64]) -> BigUint {
+        let modulus = Ed25519BaseField::modulus();
+        let mut y_bytes = digest[..32].to_vec();
+        y_bytes[31] &= 0x7f;
+        let y = BigUint::from_bytes_le(&y_bytes);
+        let yy = &y * &y % &modulus;
+        let numerator = (&yy + &modulus - 1u32) % &modulus;
+        let denominator = (Ed25519Parameters::d_biguint() * &yy + 1u32) % &modulus;
+        let inverse = denominator.modpow(&(&modulus - 2u32), &modulus);
+        sqrt(numerator * inverse * 2u32 % &modulus)
+    }
+
+    fn hash(input: &[u8]) -> [u8; 64] {
+        SHA512::hash(input)
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    fn scalar(value: &BigUint) -> Scalar {
+        let mut bytes = value.to_bytes_le();
+        bytes.resize(32, 0);
+        Scalar::from_bytes_mod_order(bytes.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::instruction::Ed25519FpInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::tests::verifies_with;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct ECVRFTest;
+
+    impl AirParameters for ECVRFTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Ed25519FpInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3264;
+        const NUM_FREE_COLUMNS: usize = 1420;
+        const EXTENDED_COLUMNS: usize = 5904;
+    }
+
+    /// Examples 16 to 18 of RFC 9381, appendix B.3, as (public key, alpha, proof, beta).
+    const RFC9381_VECTORS: [(&str, &str, &str, &str); 3] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+            "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+            "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "9bc0f79119cc5604bf02d23b4caede71393cedfbb191434dd016d30177ccbf8096bb474e53895c362d8628ee9f9ea3c0e52c7a5c691b6c18c9979866568add7a2d41b00b05081ed0f58ee5e31b3a970e",
+            "645427e5d00c62a23fb703732fa5d892940935942101e456ecca7bb217c61c452118fec1219202a0edcf038bb6373241578be7217ba85a2687f7a0310b2df19f",
+        ),
+    ];
+
+    fn witness(index: usize, alpha: Option<&[u8]>) -> ECVRFWitness {
+        let (pubkey, expected_alpha, proof, _) = RFC9381_VECTORS[index];
+        let pubkey: [u8; 32] = hex::decode(pubkey).unwrap().try_into().unwrap();
+        let proof: [u8; PROOF_BYTES] = hex::decode(proof).unwrap().try_into().unwrap();
+        let expected_alpha = hex::decode(expected_alpha).unwrap();
+        ECVRFWitness::new(&pubkey, &proof, alpha.unwrap_or(&expected_alpha[..])).unwrap()
+    }
+
+    #[test]
+    fn test_ecvrf_verify_pure() {
+        // The point `H` of example 16.
+        let expected_h =
+            hex::decode("91bbed02a99461df1ad4c6564a5f5d829d0b90cfc7903e7a5797bd658abf3318")
+                .unwrap();
+        assert_eq!(
+            witness(0, None).h.as_bytes().as_slice(),
+            expected_h.as_slice()
+        );
+        // Example 17 takes a second attempt in `encode_to_curve`.
+        assert_eq!(
+            (0..RFC9381_VECTORS.len())
+                .map(|i| witness(i, None).ctr)
+                .collect::<Vec<_>>(),
+            [0, 1, 0]
+        );
+
+        for (i, (_, _, _, beta)) in RFC9381_VECTORS.iter().enumerate() {
+            let witness_i = witness(i, None);
+            assert!(witness_i.verify());
+            assert_eq!(hex::encode(witness_i.proof_to_hash()), *beta);
+            assert!(!witness(i, Some(b"tampered".as_slice())).verify());
+        }
+    }
+
+    /// Proves the verification of `witness` and returns whether the proof verifies.
+    fn prove(witness: &ECVRFWitness) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = ECVRFTest;
+
+        let num_rows = VERIFY_NUM_ROWS;
+        let mut builder = BytesBuilder::<L>::new();
+        let gadget = builder.ecvrf_verify(witness.alpha.len());
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        verifies_with(&stark, || {
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(witness, &mut writer);
+            stark.air_data.write_global_instructions(&mut writer);
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+
+            (writer_data.trace, writer_data.public)
+        })
+    }
+
+    #[test]
+    fn test_ecvrf_verify() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for i in 0..RFC9381_VECTORS.len() {
+            assert!(prove(&witness(i, None)));
+
+            // The points of the proof don't match the hash of another input.
+            let mut tampered = witness(i, None);
+            tampered.alpha = b"tampered".to_vec();
+            assert!(!prove(&tampered));
+        }
+    }
+}
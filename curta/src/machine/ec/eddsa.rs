@@ -0,0 +1,800 @@
+//! Ed25519 signature verification as defined in RFC 8032.
+//!
+//! A signature `(R, S)` on a message `M` under the public key `A` is valid if
+//!
+//! [S]B = R + [k]A
+//!
+//! where `B` is the base point and `k = SHA-512(R || A || M) mod L` is the challenge. The gadget
+//! decompresses `A` and `R`, computes `[S]B` and `[k]A` with a single scalar multiplication batch
+//! and checks the equation on the public values.
+//!
+//! The input `R || A || M` of the hash is a public input given by its padded SHA-512 chunks, whose
+//! first 64 bytes hold the compressed points `R` and `A`. The digest is computed by the SHA-512
+//! AIR in the same trace as the scalar multiplications, then reduced modulo `L` into the scalar
+//! of `[k]A`.
+//!
+//! A batch of signatures can instead be verified with a single multi-scalar multiplication, by
+//! checking the cofactored equation
+//!
+//! [8] ([sum_i z_i * S_i]B - sum_i [z_i]R_i - sum_i [z_i * k_i]A_i) = 0
+//!
+//! for random 128-bit coefficients `z_i`. The low and high 64 bits of each coefficient are given
+//! by two verifier challenges, drawn once the signatures of the batch are observed and before the
+//! trace of the multi-scalar multiplication is committed.
+//!
+//! Reference: https://datatracker.ietf.org/doc/html/rfc8032#section-5.1.7
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use itertools::Itertools;
+use num::BigUint;
+
+use super::builder::EllipticCurveBuilder;
+use super::msm::{MSMBuilder, MSMGadget};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::edwards::ed25519::add::Ed25519AddInstruction;
+use crate::chip::ec::edwards::ed25519::decompress::decompress;
+use crate::chip::ec::edwards::ed25519::gadget::{CompressedPointAirWriter, CompressedPointGadget};
+use crate::chip::ec::edwards::ed25519::params::{
+    Ed25519, Ed25519BaseField, Ed25519Parameters, Ed25519ScalarField,
+};
+use crate::chip::ec::edwards::ed25519::point::CompressedPointRegister;
+use crate::chip::ec::edwards::ed25519::sqrt::Ed25519FpSqrtInstruction;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::scalar::ECScalarRegister;
+use crate::chip::ec::{ECInstructions, EllipticCurve};
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::machine::hash::sha::sha512::register::SHA512DigestRegister;
+use crate::machine::hash::sha::sha512::SHA512;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The number of bits of the coefficients of a batch verification.
+pub const BATCH_COEFFICIENT_BITS: usize = 128;
+
+/// The number of rows of the trace of `BytesBuilder::ed25519_verify`, taken by a scalar
+/// multiplication batch of two operations.
+pub const VERIFY_NUM_ROWS: usize = 512;
+
+/// The number of 16-bit limbs of a SHA-512 digest.
+const DIGEST_LIMBS: usize = 32;
+
+/// The public registers of an Ed25519 signature verification.
+#[derive(Debug, Clone)]
+pub struct Ed25519VerifyGadget {
+    /// The chunks of `R || A || M`, padded with `SHA512::pad`.
+    pub padded_chunks: Vec<ArrayRegister<U64Register>>,
+    pub pubkey: CompressedPointRegister,
+    pub sig_r: CompressedPointRegister,
+    pub sig_s: ECScalarRegister<Ed25519>,
+    /// The challenge `k`, computed from the digest.
+    pub challenge: ECScalarRegister<Ed25519>,
+    /// The SHA-512 digest of `R || A || M`, computed by the SHA-512 AIR.
+    digest: SHA512DigestRegister,
+    /// The result of `[S]B`.
+    s_mul_b: AffinePointRegister<Ed25519>,
+    /// The result of `[k]A`.
+    k_mul_a: AffinePointRegister<Ed25519>,
+}
+
+/// The values of an Ed25519 signature verification, computed from the raw signature.
+#[derive(Debug, Clone)]
+pub struct Ed25519VerifyWitness {
+    pub pubkey: CompressedEdwardsY,
+    pub sig_r: CompressedEdwardsY,
+    pub sig_s: BigUint,
+    pub msg: Vec<u8>,
+    /// The challenge `k = SHA-512(R || A || M) mod L`.
+    pub challenge: BigUint,
+}
+
+/// The public inputs of a signature verification in a batch.
+#[derive(Debug, Clone, Copy)]
+pub struct Ed25519VerifyInputRegisters {
+    pub pubkey: CompressedPointRegister,
+    pub sig_r: CompressedPointRegister,
+    pub sig_s: FieldRegister<Ed25519ScalarField>,
+    pub challenge: FieldRegister<Ed25519ScalarField>,
+}
+
+/// The public registers of a batch of Ed25519 signature verifications.
+#[derive(Debug, Clone)]
+pub struct Ed25519BatchVerifyGadget {
+    pub inputs: Vec<Ed25519VerifyInputRegisters>,
+    /// The coefficients `z_i` of the linear combination, given by the challenges.
+    pub coefficients: Vec<FieldRegister<Ed25519ScalarField>>,
+    /// The multi-scalar multiplication of the points `R_i`, `A_i` and `B`.
+    msm: MSMGadget<Ed25519>,
+}
+
+/// The values of a batch of Ed25519 signature verifications.
+#[derive(Debug, Clone)]
+pub struct Ed25519BatchVerifyWitness {
+    pub signatures: Vec<Ed25519VerifyWitness>,
+    /// The coefficients `z_i`, given by the challenges.
+    pub coefficients: Vec<BigUint>,
+}
+
+pub trait Ed25519VerifyBuilder: Builder {
+    /// Allocates the public inputs of a signature verification in a batch.
+    fn alloc_public_ed25519_verify_inputs(&mut self) -> Ed25519VerifyInputRegisters {
+        Ed25519VerifyInputRegisters {
+            pubkey: self.api().alloc_public_ec_compressed_point(),
+            sig_r: self.api().alloc_public_ec_compressed_point(),
+            sig_s: self.alloc_public(),
+            challenge: self.alloc_public(),
+        }
+    }
+
+    /// Verifies the signatures of `inputs` with a single multi-scalar multiplication.
+    ///
+    /// The inputs must be public registers whose values are written by
+    /// `Ed25519BatchVerifyGadget::write_inputs`, after which the prover gets the challenges of the
+    /// coefficients from `Starky::public_challenges`. These challenges are drawn after observing
+    /// all the public inputs allocated before this call, which must then be written as well.
+    ///
+    /// The multi-scalar multiplication takes `(2 * inputs.len() + 17) * 64` rows, so the trace
+    /// must have at least this number of rows rounded up to a power of two, against 512 rows per
+    /// signature for `ed25519_verify`. As for `ed_msm`, the trace must be generated sequentially.
+    ///
+    /// The equation is multiplied by the cofactor, so a batch may be accepted while a signature
+    /// with a small order component in `R` or `A` is rejected by `ed25519_verify`.
+    fn ed25519_verify_batch(
+        &mut self,
+        inputs: &[Ed25519VerifyInputRegisters],
+    ) -> Ed25519BatchVerifyGadget
+    where
+        Self::Instruction: ECInstructions<Ed25519>
+            + FromFieldInstruction<Ed25519ScalarField>
+            + From<Ed25519FpSqrtInstruction>
+            + From<Ed25519AddInstruction>,
+    {
+        assert!(!inputs.is_empty(), "Batch must not be empty");
+        for input in inputs {
+            assert!(
+                !input.pubkey.y.is_trace()
+                    && !input.sig_r.y.is_trace()
+                    && !input.sig_s.is_trace()
+                    && !input.challenge.is_trace(),
+                "Inputs must be public"
+            );
+        }
+        let challenges = self
+            .api()
+            .alloc_array_public_challenge::<ElementRegister>(2 * inputs.len());
+        let coefficients = (0..inputs.len())
+            .map(|i| coefficient(self, &challenges.get_subarray(2 * i..2 * i + 2)))
+            .collect::<Vec<_>>();
+
+        // The points `R_i`, then `A_i`, then `B`, with the scalars `z_i`, `z_i * k_i` and
+        // `-sum_i z_i * S_i`.
+        let mut points = Vec::with_capacity(2 * inputs.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * inputs.len() + 1);
+        for (input, z) in inputs.iter().zip_eq(coefficients.iter()) {
+            points.push(self.api().ed25519_decompress(&input.sig_r));
+            scalars.push(*z);
+        }
+        let mut sum = self.api().fp_zero();
+        for (input, z) in inputs.iter().zip_eq(coefficients.iter()) {
+            points.push(self.api().ed25519_decompress(&input.pubkey));
+            scalars.push(self.api().fp_mul(z, &input.challenge));
+            let z_mul_s = self.api().fp_mul(z, &input.sig_s);
+            sum = self.api().fp_add(&sum, &z_mul_s);
+        }
+        points.push(self.generator());
+        let zero = self.api().fp_zero();
+        scalars.push(self.api().fp_sub(&zero, &sum));
+
+        let scalar_bits = scalars
+            .iter()
+            .map(|scalar| scalar_bits(self, scalar))
+            .collect::<Vec<_>>();
+        let msm = self.ed_msm::<Ed25519Parameters>(&points, &scalar_bits);
+
+        // The scalars are only constrained modulo the group order, which changes the result by
+        // a small order point cleared by the cofactor.
+        let result = (0..3).fold(msm.result, |acc, _| self.double(&acc));
+        let one = self.api().fp_one();
+        self.assert_equal(&result.x, &zero);
+        self.assert_equal(&result.y, &one);
+
+        Ed25519BatchVerifyGadget {
+            inputs: inputs.to_vec(),
+            coefficients,
+            msm,
+        }
+    }
+}
+
+impl<B: Builder> Ed25519VerifyBuilder for B {}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions
+        + ECInstructions<Ed25519>
+        + From<FpAddInstruction<Ed25519ScalarField>>
+        + From<FpMulConstInstruction<Ed25519ScalarField>>
+        + From<FpCompareInstruction<Ed25519ScalarField>>
+        + From<Ed25519FpSqrtInstruction>
+        + From<Ed25519AddInstruction>,
+{
+    /// Verifies the signature `(R, sig_s)` of a message `M` under the public key `A`.
+    ///
+    /// The input `R || A || M` is given by `padded_chunks`, its chunks padded with
+    /// `SHA512::pad`, from which the compressed points `R` and `A` are read. The challenge is the
+    /// digest of the chunks reduced modulo `L`, and is constrained to be canonical. All the
+    /// registers must be public, and their values are written by `Ed25519VerifyGadget::write`
+    /// before the global instructions.
+    ///
+    /// The trace must have `VERIFY_NUM_ROWS` rows, so the padded input has at most six chunks.
+    pub fn ed25519_verify(
+        &mut self,
+        sig_s: &ECScalarRegister<Ed25519>,
+        padded_chunks: &[ArrayRegister<U64Register>],
+    ) -> Ed25519VerifyGadget {
+        assert!(
+            !sig_s.limbs.is_trace() && padded_chunks.iter().all(|chunk| !chunk.is_trace()),
+            "Inputs must be public"
+        );
+
+        // Byte `k` of the input is byte `7 - k % 8` of the big-endian word `k / 8`.
+        let msg_byte = |k: usize| padded_chunks[0].get(k / 8).to_le_bytes().get(7 - k % 8);
+        let sig_r_bytes = (0..32).map(msg_byte).collect::<Vec<_>>();
+        let pubkey_bytes = (32..64).map(msg_byte).collect::<Vec<_>>();
+        let sig_r = self.ed25519_compressed_point(&sig_r_bytes);
+        let pubkey = self.ed25519_compressed_point(&pubkey_bytes);
+
+        let last_chunk = padded_chunks.len() - 1;
+        let mut end_bits_values = vec![L::Field::ZERO; padded_chunks.len()];
+        end_bits_values[last_chunk] = L::Field::ONE;
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices =
+            self.constant_array::<ElementRegister>(&[L::Field::from_canonical_usize(last_chunk)]);
+        let digest = self.alloc_public::<SHA512DigestRegister>();
+        SHA512::sha_with_digests_in_rows(
+            self,
+            padded_chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &[digest],
+            VERIFY_NUM_ROWS,
+        );
+
+        // The digest is a little-endian integer, reduced modulo `L`. The reduction is only
+        // constrained modulo `L`, so the result is also checked to be canonical.
+        let digest_byte = |k: usize| digest.get(k / 8).to_le_bytes().get(7 - k % 8);
+        let digest_limbs = self.alloc_array_public_unchecked::<U16Register>(DIGEST_LIMBS);
+        for (i, limb) in digest_limbs.iter().enumerate() {
+            self.set_to_expression(
+                &limb,
+                digest_byte(2 * i).expr()
+                    + digest_byte(2 * i + 1).expr() * L::Field::from_canonical_u32(1 << 8),
+            );
+        }
+        let reduced = self
+            .api()
+            .fp_reduce_wide::<Ed25519ScalarField>(&digest_limbs);
+        let is_reduced = self.api().fp_is_reduced(&reduced);
+        self.assert_expression_zero(is_reduced.not_expr());
+
+        let reduced_limbs = ArrayRegister::<U16Register>::from_register_unsafe(*reduced.register());
+        let challenge =
+            ECScalarRegister::new(self.alloc_array_public_unchecked::<ElementRegister>(8));
+        for (j, limb) in challenge.limbs.iter().enumerate() {
+            self.set_to_expression(
+                &limb,
+                reduced_limbs.get(2 * j).expr()
+                    + reduced_limbs.get(2 * j + 1).expr() * L::Field::from_canonical_u32(1 << 16),
+            );
+        }
+
+        let a = self.api().ed25519_decompress(&pubkey);
+        let r = self.api().ed25519_decompress(&sig_r);
+        let b = self.generator();
+
+        let s_mul_b = self.alloc_public_ec_point();
+        let k_mul_a = self.alloc_public_ec_point();
+        self.scalar_mul_batch([b, a], [*sig_s, challenge], [s_mul_b, k_mul_a]);
+
+        let r_plus_k_mul_a = self.api().ed25519_add(&r, &k_mul_a);
+        self.assert_equal(&s_mul_b.x, &r_plus_k_mul_a.x);
+        self.assert_equal(&s_mul_b.y, &r_plus_k_mul_a.y);
+
+        Ed25519VerifyGadget {
+            padded_chunks: padded_chunks.to_vec(),
+            pubkey,
+            sig_r,
+            sig_s: *sig_s,
+            challenge,
+            digest,
+            s_mul_b,
+            k_mul_a,
+        }
+    }
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// The compressed point of the 32 little-endian bytes `bytes`, whose top bit is the sign.
+    pub(crate) fn ed25519_compressed_point(
+        &mut self,
+        bytes: &[ByteRegister],
+    ) -> CompressedPointRegister {
+        let y = self.alloc_public_unchecked::<FieldRegister<Ed25519BaseField>>();
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*y.register());
+        let limb_expr = |low: &ByteRegister, high: ArithmeticExpression<L::Field>| {
+            low.expr() + high * L::Field::from_canonical_u32(1 << 8)
+        };
+        for i in 0..15 {
+            self.set_to_expression(
+                &limbs.get(i),
+                limb_expr(&bytes[2 * i], bytes[2 * i + 1].expr()),
+            );
+        }
+
+        // The top limb holds the low seven bits of the last byte, whose top bit is the sign.
+        let low_bits = self.constant::<ByteRegister>(&L::Field::from_canonical_u8(0x7f));
+        let masked = self.alloc_public_unchecked::<ByteRegister>();
+        let op = ByteOperation::And(bytes[31], low_bits, masked);
+        self.api
+            .set_public_inputs_byte_operation(&op, &mut self.operations);
+        self.set_to_expression(&limbs.get(15), limb_expr(&bytes[30], masked.expr()));
+
+        let sign = self.alloc_public_unchecked::<BitRegister>();
+        self.set_to_expression(
+            &sign,
+            (bytes[31].expr() - masked.expr()) * L::Field::from_canonical_u32(1 << 7).inverse(),
+        );
+        CompressedPointRegister::new(sign, y)
+    }
+}
+
+/// Decomposes `scalar` into public little-endian bits.
+fn scalar_bits<B: Builder>(
+    builder: &mut B,
+    scalar: &FieldRegister<Ed25519ScalarField>,
+) -> ArrayRegister<BitRegister> {
+    let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*scalar.register());
+    let bits = builder.alloc_array_public::<BitRegister>(Ed25519::nb_scalar_bits());
+    for (i, limb) in limbs.iter().enumerate() {
+        let value = (0..16)
+            .map(|j| bits.get(16 * i + j).expr() * B::Field::from_canonical_u32(1 << j))
+            .fold(ArithmeticExpression::zero(), |acc, term| acc + term);
+        builder.assert_expression_zero(value - limb.expr());
+    }
+    bits
+}
+
+/// A coefficient of a batch verification, whose low and high 64 bits are given by `challenges`.
+///
+/// Each half is constrained to be equal to its challenge modulo the field characteristic, which
+/// leaves two possible halves for the challenges below `2^64` minus the characteristic.
+fn coefficient<B: Builder>(
+    builder: &mut B,
+    challenges: &ArrayRegister<ElementRegister>,
+) -> FieldRegister<Ed25519ScalarField> {
+    let coefficient = builder.alloc_public::<FieldRegister<Ed25519ScalarField>>();
+    let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*coefficient.register());
+    for (i, challenge) in challenges.iter().enumerate() {
+        let value = (0..4)
+            .map(|j| limbs.get(4 * i + j).expr() * B::Field::from_canonical_u64(1 << (16 * j)))
+            .fold(ArithmeticExpression::zero(), |acc, term| acc + term);
+        builder.assert_expression_zero(value - challenge.expr());
+    }
+    for limb in limbs.iter().skip(BATCH_COEFFICIENT_BITS / 16) {
+        builder.assert_expression_zero(limb.expr());
+    }
+    coefficient
+}
+
+impl Ed25519VerifyGadget {
+    /// Writes the chunks of `R || A || M`, its digest, the scalar `S` and the scalar
+    /// multiplication results.
+    pub fn write<F: Field>(
+        &self,
+        witness: &Ed25519VerifyWitness,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        let input = witness.hash_input();
+        for (chunk, values) in self
+            .padded_chunks
+            .iter()
+            .zip_eq(SHA512::pad(&input).chunks_exact(16))
+        {
+            writer.write_array(
+                chunk,
+                values.iter().map(|word| u64_to_le_field_bytes(*word)),
+            );
+        }
+        let digest: ArrayRegister<U64Register> = self.digest.as_array();
+        writer.write_array(&digest, SHA512::hash(&input).map(u64_to_le_field_bytes));
+        Self::write_scalar(&self.sig_s, &witness.sig_s, writer);
+
+        let a = decompress(&witness.pubkey);
+        writer.write_ec_point(&self.s_mul_b, &(&Ed25519::ec_generator() * &witness.sig_s));
+        writer.write_ec_point(&self.k_mul_a, &(&a * &witness.challenge));
+    }
+
+    fn write_scalar<F: Field>(
+        register: &ECScalarRegister<Ed25519>,
+        value: &BigUint,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        let mut limbs = value.to_u32_digits();
+        limbs.resize(8, 0);
+        for (limb_reg, limb) in register.limbs.iter().zip_eq(limbs) {
+            writer.write(&limb_reg, &F::from_canonical_u32(limb));
+        }
+    }
+}
+
+impl Ed25519VerifyWitness {
+    /// Parses the 32-byte public key and the 64-byte signature `R || S` and computes the
+    /// challenge of `msg`.
+    pub fn new(pubkey: &[u8; 32], sig: &[u8; 64], msg: &[u8]) -> Self {
+        let sig_r: [u8; 32] = sig[..32].try_into().unwrap();
+        Self {
+            pubkey: CompressedEdwardsY(*pubkey),
+            sig_r: CompressedEdwardsY(sig_r),
+            sig_s: BigUint::from_bytes_le(&sig[32..]),
+            msg: msg.to_vec(),
+            challenge: Self::challenge(&sig_r, pubkey, msg),
+        }
+    }
+
+    /// The input `R || A || M` of the challenge hash.
+    pub fn hash_input(&self) -> Vec<u8> {
+        [self.sig_r.0.as_slice(), self.pubkey.0.as_slice(), &self.msg].concat()
+    }
+
+    /// The challenge `SHA-512(R || A || M)`, interpreted as a little-endian integer and reduced
+    /// modulo the group order.
+    pub fn challenge(sig_r: &[u8; 32], pubkey: &[u8; 32], msg: &[u8]) -> BigUint {
+        let input = [sig_r.as_slice(), pubkey.as_slice(), msg].concat();
+        let digest = SHA512::hash(&input)
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        BigUint::from_bytes_le(&digest) % Ed25519::prime_group_order()
+    }
+
+    /// Checks the signature outside of the AIR.
+    ///
+    /// Besides the group equation, this rejects non-canonical values of `S`, which the AIR leaves
+    /// to the caller.
+    pub fn verify(&self) -> bool {
+        if self.sig_s >= Ed25519::prime_group_order() {
+            return false;
+        }
+        let a = decompress(&self.pubkey);
+        let r = decompress(&self.sig_r);
+        let s_mul_b: AffinePoint<Ed25519> = &Ed25519::ec_generator() * &self.sig_s;
+        s_mul_b == r + &(&a * &self.challenge)
+    }
+}
+
+impl Ed25519BatchVerifyGadget {
+    /// Writes the inputs of the batch, which are observed by the verifier before drawing the
+    /// challenges of the coefficients.
+    pub fn write_inputs<F: PrimeField64>(
+        &self,
+        signatures: &[Ed25519VerifyWitness],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for (input, signature) in self.inputs.iter().zip_eq(signatures.iter()) {
+            writer.write_ec_compressed_point(&input.pubkey, &signature.pubkey);
+            writer.write_ec_compressed_point(&input.sig_r, &signature.sig_r);
+            Self::write_scalar(&input.sig_s, &signature.sig_s, writer);
+            Self::write_scalar(&input.challenge, &signature.challenge, writer);
+        }
+    }
+
+    /// Writes the coefficients and the multi-scalar multiplication of the batch, whose inputs
+    /// are written by `write_inputs`.
+    pub fn write<F: PrimeField64>(
+        &self,
+        witness: &Ed25519BatchVerifyWitness,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for (register, value) in self.coefficients.iter().zip_eq(witness.coefficients.iter()) {
+            Self::write_scalar(register, value, writer);
+        }
+        let (points, scalars) = witness.msm_terms();
+        self.msm.write(&points, &scalars, writer);
+    }
+
+    fn write_scalar<F: PrimeField64>(
+        register: &FieldRegister<Ed25519ScalarField>,
+        value: &BigUint,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        writer.write(
+            register,
+            &to_u16_le_limbs_polynomial::<F, Ed25519ScalarField>(value),
+        );
+    }
+}
+
+impl Ed25519BatchVerifyWitness {
+    /// The batch of `signatures`, with the coefficients given by the public challenges of the
+    /// AIR.
+    pub fn new<F: PrimeField64>(signatures: Vec<Ed25519VerifyWitness>, challenges: &[F]) -> Self {
+        assert_eq!(
+            challenges.len(),
+            2 * signatures.len(),
+            "Expected two challenges per signature"
+        );
+        let coefficients = Self::coefficients(challenges);
+        Self {
+            signatures,
+            coefficients,
+        }
+    }
+
+    /// The coefficient `z_i` is `c_{2i} + 2^64 * c_{2i + 1}` for the challenges `c_j`.
+    pub fn coefficients<F: PrimeField64>(challenges: &[F]) -> Vec<BigUint> {
+        challenges
+            .chunks_exact(2)
+            .map(|halves| {
+                BigUint::from(halves[0].as_canonical_u64())
+                    + (BigUint::from(halves[1].as_canonical_u64()) << 64)
+            })
+            .collect()
+    }
+
+    /// The points and scalars of the multi-scalar multiplication checked by
+    /// `ed25519_verify_batch`.
+    fn msm_terms(&self) -> (Vec<AffinePoint<Ed25519>>, Vec<BigUint>) {
+        let order = Ed25519::prime_group_order();
+        let mut points = Vec::with_capacity(2 * self.signatures.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * self.signatures.len() + 1);
+        for (signature, z) in self.signatures.iter().zip_eq(self.coefficients.iter()) {
+            points.push(decompress(&signature.sig_r));
+            scalars.push(z.clone());
+        }
+        let mut sum = BigUint::from(0u32);
+        for (signature, z) in self.signatures.iter().zip_eq(self.coefficients.iter()) {
+            points.push(decompress(&signature.pubkey));
+            scalars.push(z * &signature.challenge % &order);
+            sum = (sum + z * &signature.sig_s) % &order;
+        }
+        points.push(Ed25519::ec_generator());
+        scalars.push((&order - sum) % &order);
+        (points, scalars)
+    }
+
+    /// Checks the batch equation outside of the AIR, rejecting non-canonical values of `S`.
+    pub fn verify(&self) -> bool {
+        let order = Ed25519::prime_group_order();
+        if self
+            .signatures
+            .iter()
+            .any(|signature| signature.sig_s >= order)
+        {
+            return false;
+        }
+        let (points, scalars) = self.msm_terms();
+        let result = points
+            .iter()
+            .zip(scalars.iter())
+            .fold(Ed25519::neutral(), |acc, (point, scalar)| {
+                &acc + &(point * scalar)
+            });
+        &result * BigUint::from(8u32) == Ed25519::neutral()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::instruction::Ed25519FpInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519VerifyTest;
+
+    impl AirParameters for Ed25519VerifyTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Ed25519FpInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1632;
+        const NUM_FREE_COLUMNS: usize = 1400;
+        const EXTENDED_COLUMNS: usize = 3400;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519BatchVerifyTest;
+
+    impl AirParameters for Ed25519BatchVerifyTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Ed25519FpInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2448;
+        const NUM_FREE_COLUMNS: usize = 40;
+        const EXTENDED_COLUMNS: usize = 3753;
+    }
+
+    /// Test vectors 1 and 2 of RFC 8032, section 7.1, as (public key, signature, message).
+    const RFC8032_VECTORS: [(&str, &str, &str); 2] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            "",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            "72",
+        ),
+    ];
+
+    fn witness(index: usize, msg: Option<&[u8]>) -> Ed25519VerifyWitness {
+        let (pubkey, sig, expected_msg) = RFC8032_VECTORS[index];
+        let pubkey: [u8; 32] = hex::decode(pubkey).unwrap().try_into().unwrap();
+        let sig: [u8; 64] = hex::decode(sig).unwrap().try_into().unwrap();
+        let expected_msg = hex::decode(expected_msg).unwrap();
+        Ed25519VerifyWitness::new(&pubkey, &sig, msg.unwrap_or(&expected_msg[..]))
+    }
+
+    #[test]
+    fn test_ed25519_verify_pure() {
+        for i in 0..RFC8032_VECTORS.len() {
+            assert!(witness(i, None).verify());
+            assert!(!witness(i, Some(b"tampered".as_slice())).verify());
+        }
+    }
+
+    /// Proves the verification of `witness` and returns whether the proof verifies.
+    fn prove(witness: &Ed25519VerifyWitness) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = Ed25519VerifyTest;
+
+        let num_rows = VERIFY_NUM_ROWS;
+        let mut builder = BytesBuilder::<L>::new();
+        let sig_s = ECScalarRegister::new(builder.alloc_array_public::<ElementRegister>(8));
+        let padded_chunks = (0..SHA512::pad(&witness.hash_input()).len() / 16)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let gadget = builder.ed25519_verify(&sig_s, &padded_chunks);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(witness, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_ed25519_verify() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for i in 0..RFC8032_VECTORS.len() {
+            assert!(prove(&witness(i, None)));
+
+            // The challenge of the signed message doesn't match the hash of another message.
+            let mut tampered = witness(i, None);
+            tampered.msg = b"tampered".to_vec();
+            assert!(!prove(&tampered));
+        }
+    }
+
+    /// Proves the batch verification of `signatures` and returns whether the proof verifies.
+    ///
+    /// The first challenge of the coefficients is shifted by `challenge_shift` when writing the
+    /// trace.
+    fn prove_batch(signatures: &[Ed25519VerifyWitness], challenge_shift: u64) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = Ed25519BatchVerifyTest;
+        type F = GoldilocksField;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let inputs = signatures
+            .iter()
+            .map(|_| builder.alloc_public_ed25519_verify_inputs())
+            .collect::<Vec<_>>();
+        let gadget = builder.ed25519_verify_batch(&inputs);
+
+        let num_rows = ((2 * inputs.len() + 17) * 64).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        gadget.write_inputs(signatures, &mut writer_data.public_writer());
+        let mut challenges = stark
+            .stark()
+            .public_challenges::<F, <C as CurtaConfig<2>>::Hasher>(&writer_data.public);
+        challenges[0] += F::from_canonical_u64(challenge_shift);
+        let witness = Ed25519BatchVerifyWitness::new(signatures.to_vec(), &challenges);
+
+        let mut writer = writer_data.public_writer();
+        gadget.write(&witness, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_ed25519_verify_batch_pure() {
+        let signatures = (0..RFC8032_VECTORS.len())
+            .map(|i| witness(i, None))
+            .collect::<Vec<_>>();
+        let challenges = vec![-GoldilocksField::ONE; 2 * signatures.len()];
+        let batch = Ed25519BatchVerifyWitness::new(signatures.clone(), &challenges);
+        assert!(batch.verify());
+        assert!(batch
+            .coefficients
+            .iter()
+            .all(|z| z.bits() <= BATCH_COEFFICIENT_BITS as u64));
+
+        let mut tampered = signatures;
+        tampered[1] = witness(1, Some(b"tampered".as_slice()));
+        assert!(!Ed25519BatchVerifyWitness::new(tampered, &challenges).verify());
+    }
+
+    #[test]
+    fn test_ed25519_verify_batch() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let signatures = (0..RFC8032_VECTORS.len())
+            .map(|i| witness(i, None))
+            .collect::<Vec<_>>();
+        assert!(prove_batch(&signatures, 0));
+
+        // Coefficients not given by the challenges are rejected.
+        assert!(!prove_batch(&signatures, 1));
+
+        // A signature on another message is rejected.
+        let mut tampered = signatures;
+        tampered[0] = witness(0, Some(b"tampered".as_slice()));
+        assert!(!prove_batch(&tampered, 0));
+    }
+}
@@ -0,0 +1,811 @@
+//! Scalar multiplication by a fixed base point with precomputed window tables.
+//!
+//! The scalar is split into windows of `WINDOW_BITS` bits. For each window `i` and each digit
+//! `d`, the point `d * 16^i * P` is precomputed at build time and stored in a read-only memory
+//! table. Every row of a scalar multiplication cycle looks up the table entry of one window and
+//! adds it to the running sum, so no doublings are needed and a 256-bit scalar takes 64 rows
+//! instead of the 256 rows of the variable-base gadget.
+//!
+//! The addition law of Edwards curves is complete, so the running sum starts at the neutral
+//! element. The short Weierstrass addition is not, and the neutral element is not affine, so the
+//! running sum starts at an offset point `R` and every table entry is shifted by another offset
+//! point `R'`. Both are hashed to the curve, so a partial sum equal to a table entry, or to its
+//! negative, would give a discrete logarithm relation between `P`, `R` and `R'`. The offsets are
+//! removed by a last addition of `-(R + num_windows * R')`.
+
+use core::marker::PhantomData;
+
+use log::debug;
+use num::BigUint;
+use plonky2::util::log2_ceil;
+
+use super::builder::EllipticCurveBuilder;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::edwards::{EdwardsCurve, EdwardsParameters};
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::{EllipticCurve, EllipticCurveAir};
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::machine::builder::Builder;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+
+/// The number of scalar bits handled by a single table lookup.
+pub const WINDOW_BITS: usize = 4;
+
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+
+/// The domain separation tag of the offset points of short Weierstrass curves.
+const OFFSET_DST: &[u8] = b"CURTA_FIXED_BASE_OFFSET_";
+
+/// A base point known at compile time.
+pub trait FixedBase: 'static {
+    type Parameters: EdwardsParameters;
+
+    fn base_point() -> AffinePoint<EdwardsCurve<Self::Parameters>>;
+}
+
+/// The generator of the prime order subgroup of an Edwards curve.
+#[derive(Debug, Clone, Copy)]
+pub struct EdwardsBasepoint<E>(PhantomData<E>);
+
+impl<E: EdwardsParameters> FixedBase for EdwardsBasepoint<E> {
+    type Parameters = E;
+
+    fn base_point() -> AffinePoint<EdwardsCurve<E>> {
+        EdwardsCurve::<E>::ec_generator()
+    }
+}
+
+/// A base point of a short Weierstrass curve of prime order known at compile time.
+pub trait SWFixedBase: 'static {
+    type Parameters: WeierstrassParameters;
+
+    fn base_point() -> AffinePoint<SWCurve<Self::Parameters>>;
+}
+
+/// The generator of a short Weierstrass curve, such as `G` for secp256k1.
+#[derive(Debug, Clone, Copy)]
+pub struct SWGenerator<E>(PhantomData<E>);
+
+impl<E: WeierstrassParameters> SWFixedBase for SWGenerator<E> {
+    type Parameters = E;
+
+    fn base_point() -> AffinePoint<SWCurve<E>> {
+        SWCurve::<E>::generator()
+    }
+}
+
+/// The precomputed values of a fixed-base scalar multiplication.
+struct FixedBaseTable<E: EllipticCurve> {
+    /// The entries `d * 16^i * P + offset` indexed by `WINDOW_SIZE * i + d`.
+    entries: Vec<AffinePoint<E>>,
+    /// The value of the running sum at the beginning of each cycle.
+    initial: AffinePoint<E>,
+    /// The point added to the running sum at the end of each cycle, if any.
+    final_offset: Option<AffinePoint<E>>,
+    /// The digit of the first window of the scalar of dummy operations, and their result.
+    dummy_digit: usize,
+    dummy_result: AffinePoint<E>,
+}
+
+pub trait FixedBaseBuilder: Builder {
+    /// Computes `scalar * P` for the fixed base point `P` given by `B`.
+    ///
+    /// The scalar is given by its little-endian bits and is constrained to be smaller than the
+    /// group order. See `ed_fixed_base_mul_batch` for the layout of the trace.
+    fn ed_fixed_base_mul<B: FixedBase>(
+        &mut self,
+        scalar_bits: &ArrayRegister<BitRegister>,
+    ) -> AffinePointRegister<EdwardsCurve<B::Parameters>>
+    where
+        EdwardsCurve<B::Parameters>: EllipticCurveAir<Self::Parameters>,
+    {
+        self.ed_fixed_base_mul_batch::<B>(&[*scalar_bits])[0]
+    }
+
+    /// Computes `scalar * P` for each of `scalars` and the fixed base point `P` given by `B`.
+    ///
+    /// The scalars must be public registers of little-endian bits and are constrained to be
+    /// smaller than the group order. The returned results are public registers whose values must
+    /// be written by the prover, before the global instructions.
+    ///
+    /// Each scalar multiplication takes a cycle of `nb_scalar_bits / WINDOW_BITS` rows, and the
+    /// trace must have `(scalars.len() * nb_scalar_bits / WINDOW_BITS).next_power_of_two()` rows.
+    fn ed_fixed_base_mul_batch<B: FixedBase>(
+        &mut self,
+        scalars: &[ArrayRegister<BitRegister>],
+    ) -> Vec<AffinePointRegister<EdwardsCurve<B::Parameters>>>
+    where
+        EdwardsCurve<B::Parameters>: EllipticCurveAir<Self::Parameters>,
+    {
+        let num_windows = EdwardsCurve::<B::Parameters>::nb_scalar_bits() / WINDOW_BITS;
+        let neutral = EdwardsCurve::<B::Parameters>::neutral();
+
+        let mut entries = Vec::with_capacity(num_windows * WINDOW_SIZE);
+        let mut window_base = B::base_point();
+        for _ in 0..num_windows {
+            let mut point = neutral.clone();
+            for _ in 0..WINDOW_SIZE {
+                entries.push(point.clone());
+                point = &point + &window_base;
+            }
+            window_base = point;
+        }
+
+        // Dummy operations multiply by zero and result in the neutral element.
+        let table = FixedBaseTable {
+            entries,
+            initial: neutral.clone(),
+            final_offset: None,
+            dummy_digit: 0,
+            dummy_result: neutral,
+        };
+        let order = EdwardsCurve::<B::Parameters>::prime_group_order();
+        let num_rows = (scalars.len() * num_windows).next_power_of_two();
+        fixed_base_mul_batch(self, scalars, &order, table, num_rows)
+    }
+
+    /// Computes `scalar * P` for the fixed base point `P` of a short Weierstrass curve given by
+    /// `B`.
+    ///
+    /// See `sw_fixed_base_mul_batch` for the restrictions on the scalar and the curve.
+    fn sw_fixed_base_mul<B: SWFixedBase>(
+        &mut self,
+        scalar_bits: &ArrayRegister<BitRegister>,
+    ) -> AffinePointRegister<SWCurve<B::Parameters>>
+    where
+        SWCurve<B::Parameters>: EllipticCurveAir<Self::Parameters>,
+    {
+        self.sw_fixed_base_mul_batch::<B>(&[*scalar_bits])[0]
+    }
+
+    /// Computes `scalar * P` for each of `scalars` and the fixed base point `P` of a short
+    /// Weierstrass curve given by `B`.
+    ///
+    /// The curve must have prime order and a base field of order `3 mod 4`, which holds for
+    /// secp256k1, P-256 and BN254. The scalars must be public registers of little-endian bits and
+    /// are constrained to be nonzero and smaller than the group order, since the result would be
+    /// the point at infinity otherwise.
+    ///
+    /// The layout of the trace is the same as for `ed_fixed_base_mul_batch`, with one more
+    /// addition per row to remove the offsets.
+    fn sw_fixed_base_mul_batch<B: SWFixedBase>(
+        &mut self,
+        scalars: &[ArrayRegister<BitRegister>],
+    ) -> Vec<AffinePointRegister<SWCurve<B::Parameters>>>
+    where
+        SWCurve<B::Parameters>: EllipticCurveAir<Self::Parameters>,
+    {
+        let num_rows = (scalars.len() * SWCurve::<B::Parameters>::nb_scalar_bits() / WINDOW_BITS)
+            .next_power_of_two();
+        sw_fixed_base_mul_padded::<Self, B>(self, scalars, num_rows)
+    }
+}
+
+impl<B: Builder> FixedBaseBuilder for B {}
+
+/// Computes `scalar * P` for each of `scalars` and the fixed base point `P` given by `B`, in a
+/// trace of `num_rows` rows.
+///
+/// This is `sw_fixed_base_mul_batch` padded with dummy operations to fill a longer trace, so that
+/// it can share a trace with other gadgets whose cycles span `num_rows` rows.
+pub(crate) fn sw_fixed_base_mul_padded<Bd, B>(
+    builder: &mut Bd,
+    scalars: &[ArrayRegister<BitRegister>],
+    num_rows: usize,
+) -> Vec<AffinePointRegister<SWCurve<B::Parameters>>>
+where
+    Bd: Builder,
+    B: SWFixedBase,
+    SWCurve<B::Parameters>: EllipticCurveAir<Bd::Parameters>,
+{
+    let order = B::Parameters::prime_group_order();
+    fixed_base_mul_batch(
+        builder,
+        scalars,
+        &order,
+        sw_fixed_base_table::<B>(),
+        num_rows,
+    )
+}
+
+/// The window table of the fixed base point given by `B`, shifted by the offset points.
+fn sw_fixed_base_table<B: SWFixedBase>() -> FixedBaseTable<SWCurve<B::Parameters>> {
+    let num_windows = SWCurve::<B::Parameters>::nb_scalar_bits() / WINDOW_BITS;
+    let base = B::base_point();
+    let initial = sw_offset_point::<B::Parameters>(b"initial");
+    let entry_offset = sw_offset_point::<B::Parameters>(b"entry");
+
+    let mut entries = Vec::with_capacity(num_windows * WINDOW_SIZE);
+    for i in 0..num_windows {
+        entries.push(entry_offset.clone());
+        for d in 1..WINDOW_SIZE {
+            let multiple = base.sw_scalar_mul(&(BigUint::from(d) << (WINDOW_BITS * i)));
+            entries.push(multiple.sw_add(&entry_offset));
+        }
+    }
+
+    let total_offset = initial.sw_add(&entry_offset.sw_scalar_mul(&BigUint::from(num_windows)));
+
+    // Dummy operations multiply by one, since a zero scalar is not supported.
+    FixedBaseTable {
+        entries,
+        initial,
+        final_offset: Some(SWCurve::<B::Parameters>::ec_neg(&total_offset)),
+        dummy_digit: 1,
+        dummy_result: base,
+    }
+}
+
+/// Hashes `label` to a point of a short Weierstrass curve of prime order, by incrementing the
+/// hashed `x` coordinate until it is on the curve.
+fn sw_offset_point<E: WeierstrassParameters>(label: &[u8]) -> AffinePoint<SWCurve<E>> {
+    let p = E::BaseField::modulus();
+    assert_eq!(
+        &p % 4u32,
+        BigUint::from(3u32),
+        "Base field order must be 3 mod 4"
+    );
+    let bytes = SHA256::expand_message_xmd(label, OFFSET_DST, 64);
+    let mut x = BigUint::from_bytes_be(&bytes) % &p;
+    loop {
+        let rhs = (&x * &x * &x + E::a_int() * &x + E::b_int()) % &p;
+        let y = rhs.modpow(&((&p + 1u32) >> 2), &p);
+        if (&y * &y) % &p == rhs {
+            return AffinePoint::new(x, y);
+        }
+        x = (x + 1u32) % &p;
+    }
+}
+
+/// Registers the scalar multiplications of `scalars` by the fixed base point of `table`.
+fn fixed_base_mul_batch<B, E>(
+    builder: &mut B,
+    scalars: &[ArrayRegister<BitRegister>],
+    order: &BigUint,
+    table: FixedBaseTable<E>,
+    num_rows: usize,
+) -> Vec<AffinePointRegister<E>>
+where
+    B: Builder,
+    E: EllipticCurveAir<B::Parameters>,
+{
+    let nb_scalar_bits = E::nb_scalar_bits();
+    let num_windows = nb_scalar_bits / WINDOW_BITS;
+    assert!(
+        num_windows.is_power_of_two(),
+        "Number of windows must be a power of 2"
+    );
+    assert_eq!(table.entries.len(), num_windows * WINDOW_SIZE);
+
+    let num_ops = scalars.len();
+    debug!("AIR degree before padding: {}", num_ops * num_windows);
+    assert!(
+        num_rows.is_power_of_two(),
+        "Number of rows must be a power of 2"
+    );
+    assert!(
+        num_rows >= num_ops * num_windows,
+        "Number of rows is too small"
+    );
+    assert!(log2_ceil(num_rows) < 31, "AIR degree is too large");
+    debug!("AIR degree after padding: {}", num_rows);
+    let num_dummy_ops = num_rows / num_windows - num_ops;
+
+    let zero = Time::zero();
+    let digit_ptr = builder.uninit_slice::<ElementRegister>();
+    let x_ptr = builder.uninit_slice::<FieldRegister<E::BaseField>>();
+    let y_ptr = builder.uninit_slice::<FieldRegister<E::BaseField>>();
+
+    // The number of reads of each table entry, given as a sum of indicators over the digits of
+    // all scalars. Dummy operations read the entry of `dummy_digit` in the first window and the
+    // first entry of every other window.
+    let mut reads = (0..num_windows * WINDOW_SIZE)
+        .map(|index| {
+            let dummy_index = if index < WINDOW_SIZE {
+                table.dummy_digit
+            } else {
+                0
+            };
+            let count = if index % WINDOW_SIZE == dummy_index {
+                num_dummy_ops
+            } else {
+                0
+            };
+            ArithmeticExpression::from_constant(B::Field::from_canonical_usize(count))
+        })
+        .collect::<Vec<_>>();
+
+    let results = scalars
+        .iter()
+        .enumerate()
+        .map(|(k, scalar)| {
+            assert_eq!(
+                scalar.len(),
+                nb_scalar_bits,
+                "Scalar must have {} bits",
+                nb_scalar_bits
+            );
+            assert!(!scalar.is_trace(), "Scalar bits must be public");
+            assert_bits_less_than(builder, scalar, order);
+            if table.final_offset.is_some() {
+                assert_bits_nonzero(builder, scalar);
+            }
+
+            for i in 0..num_windows {
+                let bits = (0..WINDOW_BITS)
+                    .map(|j| scalar.get(WINDOW_BITS * i + j).expr())
+                    .collect::<Vec<_>>();
+                let digit_expr = bits
+                    .iter()
+                    .enumerate()
+                    .map(|(j, bit)| bit.clone() * B::Field::from_canonical_usize(1 << j))
+                    .fold(ArithmeticExpression::zero(), |acc, term| acc + term);
+                let digit = builder.public_expression::<ElementRegister>(digit_expr);
+                builder.store(
+                    &digit_ptr.get(k * num_windows + i),
+                    digit,
+                    &zero,
+                    None,
+                    None,
+                    None,
+                );
+
+                for (d, count) in reads[WINDOW_SIZE * i..WINDOW_SIZE * (i + 1)]
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let indicator = bits.iter().enumerate().fold(
+                        ArithmeticExpression::one(),
+                        |acc, (j, bit)| {
+                            if (d >> j) & 1 == 1 {
+                                acc * bit.clone()
+                            } else {
+                                acc * (ArithmeticExpression::one() - bit.clone())
+                            }
+                        },
+                    );
+                    *count = count.clone() + indicator;
+                }
+            }
+
+            let result: AffinePointRegister<E> = builder.alloc_public_ec_point();
+            builder.free(&x_ptr.get(k), result.x, &zero);
+            builder.free(&y_ptr.get(k), result.y, &zero);
+            result
+        })
+        .collect::<Vec<_>>();
+
+    let dummy_result = AffinePointRegister::<E>::new(
+        builder.api().fp_constant(&table.dummy_result.x),
+        builder.api().fp_constant(&table.dummy_result.y),
+    );
+    let dummy_digit =
+        builder.constant::<ElementRegister>(&B::Field::from_canonical_usize(table.dummy_digit));
+    let zero_digit = builder.constant::<ElementRegister>(&B::Field::ZERO);
+    for k in num_ops..(num_ops + num_dummy_ops) {
+        for i in 0..num_windows {
+            let digit = if i == 0 { dummy_digit } else { zero_digit };
+            builder.store(
+                &digit_ptr.get(k * num_windows + i),
+                digit,
+                &zero,
+                None,
+                None,
+                None,
+            );
+        }
+        builder.free(&x_ptr.get(k), dummy_result.x, &zero);
+        builder.free(&y_ptr.get(k), dummy_result.y, &zero);
+    }
+
+    // Store the table entries, each with multiplicity given by its number of reads.
+    let table_x = builder.uninit_slice::<FieldRegister<E::BaseField>>();
+    let table_y = builder.uninit_slice::<FieldRegister<E::BaseField>>();
+    for (index, entry) in table.entries.iter().enumerate() {
+        let multiplicity = builder.public_expression::<ElementRegister>(reads[index].clone());
+        let x = builder.api().fp_constant(&entry.x);
+        let y = builder.api().fp_constant(&entry.y);
+        builder.store(
+            &table_x.get(index),
+            x,
+            &zero,
+            Some(multiplicity),
+            None,
+            None,
+        );
+        builder.store(
+            &table_y.get(index),
+            y,
+            &zero,
+            Some(multiplicity),
+            None,
+            None,
+        );
+    }
+
+    // Each row of a cycle reads the digit of its window and the corresponding table entry.
+    let cycle = builder.cycle(num_windows.ilog2() as usize);
+    let process_id = builder.process_id(num_windows, cycle.end_bit);
+    let clk = builder.clk();
+    let digit = builder.load(&digit_ptr.get_at(clk), &zero, None, None);
+    let window = clk.expr() - process_id.expr() * B::Field::from_canonical_usize(num_windows);
+    let table_index = builder.expression::<ElementRegister>(
+        window * B::Field::from_canonical_usize(WINDOW_SIZE) + digit.expr(),
+    );
+    let entry_x = builder.load(&table_x.get_at(table_index), &zero, None, None);
+    let entry_y = builder.load(&table_y.get_at(table_index), &zero, None, None);
+    let entry = AffinePointRegister::new(entry_x, entry_y);
+
+    // The running sum is reset to its initial value at the beginning of each cycle.
+    let initial = AffinePointRegister::<E>::new(
+        builder.api().fp_constant(&table.initial.x),
+        builder.api().fp_constant(&table.initial.y),
+    );
+    let sum: AffinePointRegister<E> = builder.alloc_ec_point();
+    let sum_next = builder.add(&sum, &entry);
+    builder.set_to_expression_first_row(&sum.x, initial.x.expr());
+    builder.set_to_expression_first_row(&sum.y, initial.y.expr());
+    builder.select_next_ec_point(cycle.end_bit, &initial, &sum_next, &sum);
+
+    let result_next = match &table.final_offset {
+        Some(offset) => {
+            let offset = AffinePointRegister::<E>::new(
+                builder.api().fp_constant(&offset.x),
+                builder.api().fp_constant(&offset.y),
+            );
+            builder.add(&sum_next, &offset)
+        }
+        None => sum_next,
+    };
+
+    let end_flag = Some(cycle.end_bit.as_element());
+    builder.store(
+        &x_ptr.get_at(process_id),
+        result_next.x,
+        &zero,
+        end_flag,
+        None,
+        None,
+    );
+    builder.store(
+        &y_ptr.get_at(process_id),
+        result_next.y,
+        &zero,
+        end_flag,
+        None,
+        None,
+    );
+
+    results
+}
+
+/// Constrains the integer with little-endian bits `bits` to be smaller than `bound`.
+///
+/// Scanning from the most significant bit, `eq` tracks whether all the bits so far are equal to
+/// those of `bound`. The integer is smaller than `bound` if and only if exactly one position has
+/// a zero bit where `bound` has a one bit and all more significant bits are equal.
+fn assert_bits_less_than<B: Builder>(
+    builder: &mut B,
+    bits: &ArrayRegister<BitRegister>,
+    bound: &BigUint,
+) {
+    let mut bound_bits = bound.to_radix_le(2);
+    assert!(
+        bound_bits.len() <= bits.len(),
+        "Bound does not fit in the number of bits"
+    );
+    bound_bits.resize(bits.len(), 0);
+
+    let mut eq = ArithmeticExpression::<B::Field>::one();
+    let mut lt = ArithmeticExpression::<B::Field>::zero();
+    for (i, bound_bit) in bound_bits.iter().enumerate().rev() {
+        let bit = bits.get(i).expr();
+        let eq_next = if *bound_bit == 1 {
+            lt = lt + eq.clone() * (ArithmeticExpression::one() - bit.clone());
+            eq * bit
+        } else {
+            eq * (ArithmeticExpression::one() - bit)
+        };
+        eq = builder.public_expression::<ElementRegister>(eq_next).expr();
+    }
+    builder.assert_expression_zero(lt - B::Field::ONE);
+}
+
+/// Constrains the integer with little-endian bits `bits` to be nonzero.
+fn assert_bits_nonzero<B: Builder>(builder: &mut B, bits: &ArrayRegister<BitRegister>) {
+    let mut is_zero = ArithmeticExpression::<B::Field>::one();
+    for bit in bits.iter() {
+        let is_zero_next = is_zero * (ArithmeticExpression::one() - bit.expr());
+        is_zero = builder
+            .public_expression::<ElementRegister>(is_zero_next)
+            .expr();
+    }
+    builder.assert_expression_zero(is_zero);
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+    use curve25519_dalek::scalar::Scalar;
+    use num::bigint::RandBigInt;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::params::{Ed25519, Ed25519Parameters};
+    use crate::chip::ec::gadget::EllipticCurveAirWriter;
+    use crate::chip::ec::scalar::ECScalarRegister;
+    use crate::chip::ec::secp256k1::params::{Secp256k1, Secp256k1BaseField, Secp256k1Parameters};
+    use crate::chip::ec::ECInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    type Basepoint = EdwardsBasepoint<Ed25519Parameters>;
+    type Secp256k1Generator = SWGenerator<Secp256k1Parameters>;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519FixedBaseTest;
+
+    impl AirParameters for Ed25519FixedBaseTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Ed25519>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1632;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 2502;
+    }
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Secp256k1FixedBaseTest;
+
+    impl AirParameters for Secp256k1FixedBaseTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Secp256k1>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3330;
+    }
+
+    /// Parameters with enough columns for both scalar multiplication gadgets, only used to count
+    /// the allocated columns.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519ColumnCountTest;
+
+    impl AirParameters for Ed25519ColumnCountTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Ed25519>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 4000;
+        const NUM_FREE_COLUMNS: usize = 100;
+        const EXTENDED_COLUMNS: usize = 6000;
+    }
+
+    fn compress(point: &AffinePoint<Ed25519>) -> [u8; 32] {
+        let mut bytes = point.y.to_bytes_le();
+        bytes.resize(32, 0);
+        bytes[31] |= (point.x.to_bytes_le()[0] & 1) << 7;
+        bytes.try_into().unwrap()
+    }
+
+    /// Proves the fixed-base multiplication of each of `scalars`, writing `results` as the
+    /// claimed products, and returns whether the proof verifies.
+    fn prove_fixed_base_mul(scalars: &[BigUint], results: &[AffinePoint<Ed25519>]) -> bool {
+        type F = GoldilocksField;
+        type L = Ed25519FixedBaseTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let nb_bits = Ed25519::nb_scalar_bits();
+        let scalar_registers = scalars
+            .iter()
+            .map(|_| builder.alloc_array_public::<BitRegister>(nb_bits))
+            .collect::<Vec<_>>();
+        let result_registers = builder.ed_fixed_base_mul_batch::<Basepoint>(&scalar_registers);
+
+        let num_rows = (scalars.len() * nb_bits / WINDOW_BITS).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (((bits, result_reg), scalar), result) in scalar_registers
+            .iter()
+            .zip(result_registers.iter())
+            .zip(scalars)
+            .zip(results)
+        {
+            let mut scalar_bits = scalar.to_radix_le(2);
+            scalar_bits.resize(nb_bits, 0);
+            writer.write_array(bits, scalar_bits.into_iter().map(F::from_canonical_u8));
+            writer.write_ec_point(result_reg, result);
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_ed25519_fixed_base_mul() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut rng = thread_rng();
+        let order = Ed25519::prime_group_order();
+        let generator = Ed25519::ec_generator();
+        let scalars = (0..3)
+            .map(|_| rng.gen_biguint(256) % &order)
+            .collect::<Vec<_>>();
+        let results = scalars
+            .iter()
+            .map(|scalar| &generator * scalar)
+            .collect::<Vec<_>>();
+
+        // Check the expected results against the precomputed basepoint table of `dalek`.
+        for (scalar, result) in scalars.iter().zip(results.iter()) {
+            let mut bytes = scalar.to_bytes_le();
+            bytes.resize(32, 0);
+            let dalek_scalar = Scalar::from_bytes_mod_order(bytes.try_into().unwrap());
+            let expected = ED25519_BASEPOINT_TABLE * &dalek_scalar;
+            assert_eq!(compress(result), expected.compress().to_bytes());
+        }
+
+        assert!(prove_fixed_base_mul(&scalars, &results));
+
+        // A wrong result is rejected.
+        let mut wrong_results = results.clone();
+        wrong_results[1] = &wrong_results[1] + &generator;
+        assert!(!prove_fixed_base_mul(&scalars, &wrong_results));
+
+        // The scalar must be smaller than the group order.
+        let neutral = Ed25519::neutral();
+        assert!(!prove_fixed_base_mul(&[order], &[neutral]));
+    }
+
+    #[test]
+    fn test_fixed_base_mul_savings() {
+        type L = Ed25519ColumnCountTest;
+        type E = Ed25519;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let nb_bits = E::nb_scalar_bits();
+
+        let mut fixed_builder = EmulatedBuilder::<L>::new();
+        let scalar = fixed_builder.alloc_array_public::<BitRegister>(nb_bits);
+        fixed_builder.ed_fixed_base_mul::<Basepoint>(&scalar);
+        let (fixed_arithmetic, fixed_free, _) = fixed_builder.api.num_allocated_columns();
+
+        let mut variable_builder = EmulatedBuilder::<L>::new();
+        let point: AffinePointRegister<E> = variable_builder.alloc_public_ec_point();
+        let scalar = ECScalarRegister::<E>::new(
+            variable_builder.alloc_array_public::<ElementRegister>(nb_bits / 32),
+        );
+        let result: AffinePointRegister<E> = variable_builder.alloc_public_ec_point();
+        variable_builder.scalar_mul_batch([point], [scalar], [result]);
+        let (variable_arithmetic, variable_free, _) = variable_builder.api.num_allocated_columns();
+
+        let fixed_rows = nb_bits / WINDOW_BITS;
+        let variable_rows = nb_bits;
+        debug!(
+            "fixed base: {} rows per scalar, {} arithmetic and {} free columns",
+            fixed_rows, fixed_arithmetic, fixed_free
+        );
+        debug!(
+            "variable base: {} rows per scalar, {} arithmetic and {} free columns",
+            variable_rows, variable_arithmetic, variable_free
+        );
+        assert_eq!(variable_rows, 4 * fixed_rows);
+        assert!(fixed_arithmetic < variable_arithmetic);
+    }
+
+    /// Proves the fixed-base multiplication of each of `scalars` by the generator of secp256k1,
+    /// writing `results` as the claimed products, and returns whether the proof verifies.
+    fn prove_sw_fixed_base_mul(scalars: &[BigUint], results: &[AffinePoint<Secp256k1>]) -> bool {
+        type F = GoldilocksField;
+        type L = Secp256k1FixedBaseTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let nb_bits = Secp256k1::nb_scalar_bits();
+        let scalar_registers = scalars
+            .iter()
+            .map(|_| builder.alloc_array_public::<BitRegister>(nb_bits))
+            .collect::<Vec<_>>();
+        let result_registers =
+            builder.sw_fixed_base_mul_batch::<Secp256k1Generator>(&scalar_registers);
+
+        let num_rows = (scalars.len() * nb_bits / WINDOW_BITS).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (((bits, result_reg), scalar), result) in scalar_registers
+            .iter()
+            .zip(result_registers.iter())
+            .zip(scalars)
+            .zip(results)
+        {
+            let mut scalar_bits = scalar.to_radix_le(2);
+            scalar_bits.resize(nb_bits, 0);
+            writer.write_array(bits, scalar_bits.into_iter().map(F::from_canonical_u8));
+            writer.write_ec_point(result_reg, result);
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_secp256k1_fixed_base_mul() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let initial = sw_offset_point::<Secp256k1Parameters>(b"initial");
+        let entry_offset = sw_offset_point::<Secp256k1Parameters>(b"entry");
+        let b = Secp256k1Parameters::b_int();
+        for point in [&initial, &entry_offset] {
+            let p = Secp256k1BaseField::modulus();
+            assert_eq!(
+                (&point.y * &point.y) % &p,
+                (&point.x * &point.x * &point.x + &b) % &p
+            );
+        }
+        assert_ne!(initial, entry_offset);
+
+        let mut rng = thread_rng();
+        let order = Secp256k1Parameters::prime_group_order();
+        let generator = Secp256k1::generator();
+        let mut scalars = (0..3)
+            .map(|_| rng.gen_biguint_below(&order))
+            .collect::<Vec<_>>();
+        // The top window holds the largest digit and all the other windows are zero.
+        scalars[2] = BigUint::from(15u32) << (WINDOW_BITS * 63);
+        let results = scalars
+            .iter()
+            .map(|scalar| generator.sw_scalar_mul(scalar))
+            .collect::<Vec<_>>();
+        assert!(prove_sw_fixed_base_mul(&scalars, &results));
+
+        // A wrong result is rejected.
+        let mut wrong_results = results.clone();
+        wrong_results[0] = wrong_results[0].sw_add(&generator);
+        assert!(!prove_sw_fixed_base_mul(&scalars, &wrong_results));
+
+        // The scalar must be nonzero.
+        assert!(!prove_sw_fixed_base_mul(
+            &[BigUint::from(0u32)],
+            &[generator]
+        ));
+    }
+}
@@ -0,0 +1,470 @@
+//! Scalar multiplication over secp256k1 with the GLV endomorphism.
+//!
+//! Each scalar `k` is decomposed as `k = k1 + k2 * lambda mod n` with signed scalars `k1` and
+//! `k2` of at most 128 bits, as in `chip::ec::secp256k1::glv`. The decomposition is a public
+//! witness constrained to recombine to `k`. The signs are absorbed in the points, so that
+//!
+//! [k]P = [|k1|]P1 + [|k2|]phi(P2)
+//!
+//! where `P1 = (-1)^k1_neg * P`, `P2 = (-1)^k2_neg * P` and `phi(x, y) = (beta * x, y)`. Every row
+//! of a cycle doubles the running power `2^i * P1`, derives `2^i * phi(P2)` from it with a single
+//! multiplication by `beta`, and adds both to the running sum according to the bits of `|k1|` and
+//! `|k2|`. A scalar multiplication thus takes 128 rows instead of the 256 rows of
+//! `EllipticCurveBuilder::scalar_mul_batch`, at the cost of a second point addition per row.
+//!
+//! As in `scalar_mul_batch`, the additions are incomplete, so the intermediate sums must differ
+//! from the points they are added to, which holds unless the scalars are chosen adversarially.
+
+use itertools::Itertools;
+use log::debug;
+use num::BigUint;
+use plonky2::util::log2_ceil;
+
+use super::builder::EllipticCurveBuilder;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::ec::secp256k1::glv::{glv_decompose, BETA, GLV_SCALAR_BITS, LAMBDA};
+use crate::chip::ec::secp256k1::params::{Secp256k1, Secp256k1BaseField, Secp256k1ScalarField};
+use crate::chip::ec::ECInstructions;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The number of 32-bit limbs of a GLV scalar.
+const NB_GLV_LIMBS: usize = GLV_SCALAR_BITS / 32;
+
+/// The public registers of the GLV decomposition of a scalar.
+#[derive(Debug, Clone, Copy)]
+struct GLVDecompositionRegister {
+    k1: FieldRegister<Secp256k1ScalarField>,
+    k1_neg: BitRegister,
+    k2: FieldRegister<Secp256k1ScalarField>,
+    k2_neg: BitRegister,
+}
+
+/// The decompositions of the scalars of a GLV scalar multiplication batch.
+#[derive(Debug, Clone)]
+pub struct GLVScalarMulGadget {
+    decompositions: Vec<GLVDecompositionRegister>,
+}
+
+pub trait GLVBuilder: Builder {
+    /// Computes `scalar * point` for each of `points` and `scalars` and constrains the result to
+    /// be the corresponding entry of `results`.
+    ///
+    /// All the registers must be public. The points, scalars and results are written by the
+    /// caller and the scalar decompositions by `GLVScalarMulGadget::write`, all before the
+    /// global instructions. Each scalar multiplication takes a cycle of 128 rows, and the trace
+    /// must have `(points.len() * 128).next_power_of_two()` rows.
+    ///
+    /// The scalars must not vanish.
+    fn secp256k1_glv_scalar_mul_batch(
+        &mut self,
+        points: &[AffinePointRegister<Secp256k1>],
+        scalars: &[FieldRegister<Secp256k1ScalarField>],
+        results: &[AffinePointRegister<Secp256k1>],
+    ) -> GLVScalarMulGadget
+    where
+        Self::Instruction: ECInstructions<Secp256k1> + FromFieldInstruction<Secp256k1ScalarField>,
+    {
+        let nb_bits_log = GLV_SCALAR_BITS.ilog2() as usize;
+        let cycle_size = self.constant(&Self::Field::from_canonical_usize(GLV_SCALAR_BITS));
+        let cycle_32_size = self.constant(&Self::Field::from_canonical_u32(32));
+        let cycle = self.cycle(nb_bits_log);
+        let cycle_32 = self.cycle(5);
+
+        let temp_x_ptr = self.uninit_slice::<FieldRegister<Secp256k1BaseField>>();
+        let temp_y_ptr = self.uninit_slice::<FieldRegister<Secp256k1BaseField>>();
+        let x_ptr = self.uninit_slice::<FieldRegister<Secp256k1BaseField>>();
+        let y_ptr = self.uninit_slice::<FieldRegister<Secp256k1BaseField>>();
+        let k1_limb_ptr = self.uninit_slice::<ElementRegister>();
+        let k2_limb_ptr = self.uninit_slice::<ElementRegister>();
+        let flip_ptr = self.uninit_slice::<BitRegister>();
+        let zero = Time::zero();
+
+        let decompositions = points
+            .iter()
+            .zip_eq(scalars)
+            .zip_eq(results)
+            .enumerate()
+            .map(|(i, ((point, scalar), result))| {
+                assert!(
+                    !point.x.is_trace() && !scalar.is_trace() && !result.x.is_trace(),
+                    "Inputs must be public"
+                );
+                let decomposition = glv_decomposition(self, scalar);
+
+                // Store `P1`, the point with the sign of `k1`.
+                let zero_field = self.api().fp_zero();
+                let neg_y = self.api().fp_sub(&zero_field, &point.y);
+                let y = self.select(decomposition.k1_neg, &neg_y, &point.y);
+                let time = Time::constant(GLV_SCALAR_BITS * i);
+                self.store(&temp_x_ptr.get(i), point.x, &time, None, None, None);
+                self.store(&temp_y_ptr.get(i), y, &time, None, None, None);
+
+                // Store whether `P2` is the opposite of `P1`.
+                let (k1_neg, k2_neg) = (decomposition.k1_neg.expr(), decomposition.k2_neg.expr());
+                let flip = self.public_expression::<BitRegister>(
+                    k1_neg.clone() + k2_neg.clone()
+                        - k1_neg * k2_neg * Self::Field::from_canonical_u32(2),
+                );
+                self.store(&flip_ptr.get(i), flip, &zero, Some(cycle_size), None, None);
+
+                // Store the limbs of both scalars.
+                let k1_limbs = glv_scalar_limbs(self, &decomposition.k1);
+                let k2_limbs = glv_scalar_limbs(self, &decomposition.k2);
+                for j in 0..NB_GLV_LIMBS {
+                    let index = i * NB_GLV_LIMBS + j;
+                    for (limb_ptr, limbs) in [(&k1_limb_ptr, k1_limbs), (&k2_limb_ptr, k2_limbs)] {
+                        self.store(
+                            &limb_ptr.get(index),
+                            limbs.get(j),
+                            &zero,
+                            Some(cycle_32_size),
+                            None,
+                            None,
+                        );
+                    }
+                }
+
+                self.free(&x_ptr.get(i), result.x, &zero);
+                self.free(&y_ptr.get(i), result.y, &zero);
+
+                decomposition
+            })
+            .collect::<Vec<_>>();
+
+        let num_ops = decompositions.len();
+        debug!("AIR degree before padding: {}", num_ops * GLV_SCALAR_BITS);
+        let degree_log = log2_ceil(num_ops * GLV_SCALAR_BITS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        let num_dummy_ops = (1 << degree_log) / GLV_SCALAR_BITS - num_ops;
+
+        // Insert dummy entries where necessary, with `k1 = 1` and `k2 = 0`.
+        let generator = self.generator();
+        let mut one_limbs = vec![Self::Field::ONE];
+        one_limbs.resize(NB_GLV_LIMBS, Self::Field::ZERO);
+        let one_limbs = self.constant_array::<ElementRegister>(&one_limbs);
+        let zero_limbs = self.constant_array::<ElementRegister>(&[Self::Field::ZERO; NB_GLV_LIMBS]);
+        let no_flip = self.constant::<BitRegister>(&Self::Field::ZERO);
+        for i in num_ops..(num_ops + num_dummy_ops) {
+            let time = Time::constant(GLV_SCALAR_BITS * i);
+            self.store(&temp_x_ptr.get(i), generator.x, &time, None, None, None);
+            self.store(&temp_y_ptr.get(i), generator.y, &time, None, None, None);
+            self.store(
+                &flip_ptr.get(i),
+                no_flip,
+                &zero,
+                Some(cycle_size),
+                None,
+                None,
+            );
+            for j in 0..NB_GLV_LIMBS {
+                let index = i * NB_GLV_LIMBS + j;
+                for (limb_ptr, limbs) in [(&k1_limb_ptr, one_limbs), (&k2_limb_ptr, zero_limbs)] {
+                    self.store(
+                        &limb_ptr.get(index),
+                        limbs.get(j),
+                        &zero,
+                        Some(cycle_32_size),
+                        None,
+                        None,
+                    );
+                }
+            }
+
+            self.free(&x_ptr.get(i), generator.x, &zero);
+            self.free(&y_ptr.get(i), generator.y, &zero);
+        }
+
+        // Load the scalar limbs and decompose them to bits.
+        let process_id = self.process_id(GLV_SCALAR_BITS, cycle.end_bit);
+        let process_id_u32 = self.process_id(32, cycle_32.end_bit);
+        let k1_limb = self.load(&k1_limb_ptr.get_at(process_id_u32), &zero, None, None);
+        let k2_limb = self.load(&k2_limb_ptr.get_at(process_id_u32), &zero, None, None);
+        let k1_bit = self.bit_decomposition(k1_limb, cycle_32.start_bit, cycle_32.end_bit);
+        let k2_bit = self.bit_decomposition(k2_limb, cycle_32.start_bit, cycle_32.end_bit);
+        let flip = self.load(&flip_ptr.get_at(process_id), &zero, None, None);
+
+        // Keep track of whether the result is the identity, as in `double_and_add`.
+        let is_res_valid = self.alloc::<BitRegister>();
+        self.set_to_expression_first_row(&is_res_valid, Self::Field::ZERO.into());
+        let is_res_valid_k1 =
+            self.expression(is_res_valid.expr() + k1_bit.expr() * is_res_valid.not_expr());
+        let is_res_valid_k2 =
+            self.expression(is_res_valid_k1.expr() + k2_bit.expr() * is_res_valid_k1.not_expr());
+        self.select_next(
+            cycle.end_bit,
+            &cycle.start_bit,
+            &is_res_valid_k2,
+            &is_res_valid,
+        );
+
+        // Load `temp = 2^i * P1` and assign `temp_next = temp + temp`.
+        let temp_x_ptr = temp_x_ptr.get_at(process_id);
+        let temp_y_ptr = temp_y_ptr.get_at(process_id);
+        let clk = Time::from_element(self.clk());
+        let temp_x = self.load(&temp_x_ptr, &clk, None, None);
+        let temp_y = self.load(&temp_y_ptr, &clk, None, None);
+        let temp = AffinePointRegister::<Secp256k1>::new(temp_x, temp_y);
+        let not_end_bit = self.expression(cycle.end_bit.not_expr());
+        let temp_next = self.double(&temp);
+        self.store(
+            &temp_x_ptr,
+            temp_next.x,
+            &clk.advance(),
+            Some(not_end_bit),
+            None,
+            None,
+        );
+        self.store(
+            &temp_y_ptr,
+            temp_next.y,
+            &clk.advance(),
+            Some(not_end_bit),
+            None,
+            None,
+        );
+
+        // The endomorphism gives `2^i * phi(P2)` from `temp`.
+        let phi_x = self.api().fp_mul_const(&temp.x, BETA);
+        let zero_field = self.zero::<FieldRegister<Secp256k1BaseField>>();
+        let neg_temp_y = self.api().fp_sub(&zero_field, &temp.y);
+        let phi_y = self.select(flip, &neg_temp_y, &temp.y);
+        let temp_phi = AffinePointRegister::new(phi_x, phi_y);
+
+        // Allocate the intermeddiate result.
+        let result = self.alloc_ec_point();
+
+        // Add `temp` if the bit of `k1` is set. When the result is not valid, the sum is not used
+        // and `temp_next` is a safe addend.
+        let addend = self.select_ec_point(is_res_valid, &result, &temp_next);
+        let sum = self.add(&temp, &addend);
+        let res_plus_temp = self.select_ec_point(is_res_valid, &sum, &temp);
+        let result_k1 = self.select_ec_point(k1_bit, &res_plus_temp, &result);
+
+        // Add `temp_phi` if the bit of `k2` is set, with `temp` as the safe addend, since `phi`
+        // acts as the multiplication by `lambda != 1`.
+        let addend = self.select_ec_point(is_res_valid_k1, &result_k1, &temp);
+        let sum = self.add(&temp_phi, &addend);
+        let res_plus_temp_phi = self.select_ec_point(is_res_valid_k1, &sum, &temp_phi);
+        let result_next = self.select_ec_point(k2_bit, &res_plus_temp_phi, &result_k1);
+
+        // Reset the intermediate result to the dummy point (0, 0) at the beginning of each cycle.
+        let dummy_point = AffinePointRegister::new(zero_field, zero_field);
+        self.set_to_expression_first_row(&result.x, zero_field.expr());
+        self.set_to_expression_first_row(&result.y, zero_field.expr());
+        self.select_next_ec_point(cycle.end_bit, &dummy_point, &result_next, &result);
+
+        let end_flag = Some(cycle.end_bit.as_element());
+        self.store(
+            &x_ptr.get_at(process_id),
+            result_next.x,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+        self.store(
+            &y_ptr.get_at(process_id),
+            result_next.y,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+
+        GLVScalarMulGadget { decompositions }
+    }
+}
+
+impl<B: Builder> GLVBuilder for B {}
+
+/// Allocates a public GLV decomposition of `scalar` and constrains it to recombine to
+/// `scalar`.
+fn glv_decomposition<B: Builder>(
+    builder: &mut B,
+    scalar: &FieldRegister<Secp256k1ScalarField>,
+) -> GLVDecompositionRegister
+where
+    B::Instruction: FromFieldInstruction<Secp256k1ScalarField>,
+{
+    let k1 = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+    let k2 = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+    let k1_neg = builder.alloc_public::<BitRegister>();
+    let k2_neg = builder.alloc_public::<BitRegister>();
+
+    // Both scalars have at most `GLV_SCALAR_BITS` bits.
+    for k in [k1, k2] {
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*k.register());
+        for limb in limbs.iter().skip(GLV_SCALAR_BITS / 16) {
+            builder.assert_expression_zero(limb.expr());
+        }
+    }
+
+    // Assert that `(-1)^k1_neg * k1 + (-1)^k2_neg * k2 * lambda = scalar`.
+    let zero = builder.api().fp_zero();
+    let neg_k1 = builder.api().fp_sub(&zero, &k1);
+    let signed_k1 = builder.select(k1_neg, &neg_k1, &k1);
+    let neg_k2 = builder.api().fp_sub(&zero, &k2);
+    let signed_k2 = builder.select(k2_neg, &neg_k2, &k2);
+    let lambda_k2 = builder.api().fp_mul_const(&signed_k2, LAMBDA);
+    let recomposed = builder.api().fp_add(&signed_k1, &lambda_k2);
+    builder.assert_equal(&recomposed, scalar);
+
+    GLVDecompositionRegister {
+        k1,
+        k1_neg,
+        k2,
+        k2_neg,
+    }
+}
+
+/// Converts a GLV scalar into its 32-bit limbs.
+fn glv_scalar_limbs<B: Builder>(
+    builder: &mut B,
+    scalar: &FieldRegister<Secp256k1ScalarField>,
+) -> ArrayRegister<ElementRegister> {
+    let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*scalar.register());
+    let scalar_limbs = builder.alloc_array_public::<ElementRegister>(NB_GLV_LIMBS);
+    for (i, limb) in scalar_limbs.iter().enumerate() {
+        let expr = limbs.get(2 * i).expr()
+            + limbs.get(2 * i + 1).expr() * B::Field::from_canonical_u32(1 << 16);
+        builder.api().set_to_expression_public(&limb, expr);
+    }
+    scalar_limbs
+}
+
+impl GLVScalarMulGadget {
+    /// Writes the decompositions of `scalars`.
+    pub fn write<F: Field>(&self, scalars: &[BigUint], writer: &mut impl AirWriter<Field = F>) {
+        for (register, scalar) in self.decompositions.iter().zip_eq(scalars) {
+            let decomposition = glv_decompose(scalar);
+            writer.write(
+                &register.k1,
+                &to_u16_le_limbs_polynomial::<F, Secp256k1ScalarField>(&decomposition.k1),
+            );
+            writer.write(
+                &register.k2,
+                &to_u16_le_limbs_polynomial::<F, Secp256k1ScalarField>(&decomposition.k2),
+            );
+            writer.write(&register.k1_neg, &F::from_bool(decomposition.k1_neg));
+            writer.write(&register.k2_neg, &F::from_bool(decomposition.k2_neg));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::gadget::EllipticCurveAirWriter;
+    use crate::chip::ec::secp256k1::instruction::Secp256k1Instruction;
+    use crate::chip::ec::secp256k1::params::Secp256k1Parameters;
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::maybe_rayon::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Secp256k1GLVScalarMulTest;
+
+    impl AirParameters for Secp256k1GLVScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3416;
+        const NUM_FREE_COLUMNS: usize = 24;
+        const EXTENDED_COLUMNS: usize = 5250;
+    }
+
+    #[test]
+    fn test_secp256k1_glv_scalar_mul() {
+        type F = GoldilocksField;
+        type L = Secp256k1GLVScalarMulTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type E = Secp256k1;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Secp256k1 GLV scalar mul", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+
+        let num_ops = 2;
+        let points = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let scalars = (0..num_ops)
+            .map(|_| builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>())
+            .collect::<Vec<_>>();
+        let results = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+
+        let gadget = builder.secp256k1_glv_scalar_mul_batch(&points, &scalars, &results);
+
+        let num_rows = num_ops * GLV_SCALAR_BITS;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let order = Secp256k1Parameters::prime_group_order();
+        let mut rng = thread_rng();
+        let scalar_values = (0..num_ops)
+            .map(|_| rng.gen_biguint(256) % &order)
+            .collect::<Vec<_>>();
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (((point_reg, scalar_reg), result_reg), scalar) in points
+            .iter()
+            .zip(scalars.iter())
+            .zip(results.iter())
+            .zip(scalar_values.iter())
+        {
+            let point = E::generator().sw_scalar_mul(&(rng.gen_biguint(256) % &order));
+            let result = point.sw_scalar_mul(scalar);
+            writer.write_ec_point(point_reg, &point);
+            writer.write_ec_point(result_reg, &result);
+            writer.write(
+                scalar_reg,
+                &to_u16_le_limbs_polynomial::<F, Secp256k1ScalarField>(scalar),
+            );
+        }
+        gadget.write(&scalar_values, &mut writer);
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        writer_data
+            .chunks_par(GLV_SCALAR_BITS)
+            .for_each(|mut chunk| {
+                for i in 0..GLV_SCALAR_BITS {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
@@ -0,0 +1,288 @@
+//! Hashing to the G2 group of BLS12-381 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite.
+//!
+//! A message is hashed to two elements `u_0, u_1` of `Fp2` by `hash_to_field`, which expands it
+//! with `SHA256::expand_message_xmd`. Each element is mapped to the twist with the simplified SWU
+//! map followed by the 3-isogeny, and the sum of the two points is multiplied by the cofactor.
+//!
+//! The gadget proves the whole hash in a single AIR. The expansion is computed by the SHA-256
+//! AIR from the public bytes of the message, and every 64 bytes of its output are reduced modulo
+//! `p` into a coordinate of `u_0` and `u_1`, which are then mapped to G2.
+//!
+//! Reference: https://www.rfc-editor.org/rfc/rfc9380
+
+use num::BigUint;
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::bls12_381::g2::{G2AffinePoint, G2PointRegister};
+use crate::chip::ec::bls12_381::hash_to_curve::{
+    clear_cofactor, map_to_curve_simple_swu, map_to_g2, SSWURegister,
+};
+use crate::chip::ec::bls12_381::params::Bls12381BaseField;
+use crate::chip::ec::bls12_381::tower::{Fp2, Fp2Register};
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::extension::fp2::Fp2AirWriter;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+
+/// The number of expanded bytes for each base field element, `ceil((381 + 128) / 8)` for the
+/// 128-bit security level.
+const FIELD_ELEMENT_BYTES: usize = 64;
+
+/// Hashes `msg` to two elements of `Fp2` with the domain separation tag `dst`.
+pub fn hash_to_field(msg: &[u8], dst: &[u8]) -> [Fp2; 2] {
+    let bytes = SHA256::expand_message_xmd(msg, dst, 4 * FIELD_ELEMENT_BYTES);
+    let element = |i: usize| {
+        BigUint::from_bytes_be(&bytes[i * FIELD_ELEMENT_BYTES..(i + 1) * FIELD_ELEMENT_BYTES])
+    };
+    [
+        Fp2::new(element(0), element(1)),
+        Fp2::new(element(2), element(3)),
+    ]
+}
+
+/// Hashes `msg` to a point of G2 with the domain separation tag `dst`.
+pub fn hash_to_g2(msg: &[u8], dst: &[u8]) -> G2AffinePoint {
+    let [u_0, u_1] = hash_to_field(msg, dst);
+    clear_cofactor(&map_to_g2(&u_0).g2_add(&map_to_g2(&u_1)))
+}
+
+/// The number of rows of the trace of `BytesBuilder::bls12_381_hash_to_g2` for a message of
+/// `msg_len` bytes and a domain separation tag of `dst_len` bytes, taken by the SHA-256 chunks of
+/// the expansion.
+pub fn hash_to_g2_num_rows(msg_len: usize, dst_len: usize) -> usize {
+    let num_chunks =
+        SHA256::expand_message_xmd_num_chunks(msg_len, dst_len, 4 * FIELD_ELEMENT_BYTES);
+    (64 * num_chunks).next_power_of_two()
+}
+
+/// The public registers of a hash to G2.
+#[derive(Debug, Clone)]
+pub struct HashToG2Gadget {
+    pub msg: ArrayRegister<ByteRegister>,
+    pub dst: Vec<u8>,
+    /// The simplified SWU maps of the field elements `u_0` and `u_1` hashed from the message.
+    pub sswu: [SSWURegister; 2],
+    /// The hash of the message in G2.
+    pub point: G2PointRegister,
+    /// The digests `b_0, ..., b_ell` of the expansion of the message.
+    digests: Vec<SHA256DigestRegister>,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions
+        + FromFieldInstruction<Bls12381BaseField>
+        + From<FpCompareInstruction<Bls12381BaseField>>,
+{
+    /// Hashes the message given by the public bytes `msg` to G2 with the domain separation tag
+    /// `dst`, in a trace of `num_rows` rows.
+    ///
+    /// `num_rows` must be the number of rows the AIR is built with, a power of two of at least
+    /// `hash_to_g2_num_rows(msg.len(), dst.len())`. The values of the message, the digests of
+    /// its expansion and the images of the simplified SWU map are written by
+    /// `HashToG2Gadget::write` before the global instructions. The exceptional inputs of
+    /// `bls12_381_map_to_curve_simple_swu` are not supported, so proving fails with negligible
+    /// probability.
+    pub fn bls12_381_hash_to_g2(
+        &mut self,
+        msg: &ArrayRegister<ByteRegister>,
+        dst: &[u8],
+        num_rows: usize,
+    ) -> HashToG2Gadget {
+        assert!(!msg.is_trace(), "The message must be public");
+        let digests = self.expand_message_xmd(msg, dst, 4 * FIELD_ELEMENT_BYTES, num_rows);
+
+        // Byte `k` of the uniform bytes is byte `k % 32` of the digest `b_(1 + k / 32)`, which
+        // is byte `3 - k % 4` of its big-endian word `k % 32 / 4`.
+        let uniform_byte = |k: usize| {
+            digests[1 + k / 32]
+                .get(k % 32 / 4)
+                .to_le_bytes()
+                .get(3 - k % 4)
+        };
+        let elements = (0..4)
+            .map(|i| {
+                let bytes = (0..FIELD_ELEMENT_BYTES)
+                    .map(|k| uniform_byte(i * FIELD_ELEMENT_BYTES + k))
+                    .collect::<Vec<_>>();
+                self.bls12_381_reduce_bytes(&bytes)
+            })
+            .collect::<Vec<_>>();
+        let u_0 = Fp2Register::new(elements[0], elements[1]);
+        let u_1 = Fp2Register::new(elements[2], elements[3]);
+
+        let (sswu_0, q_0) = self.api().bls12_381_map_to_g2(&u_0);
+        let (sswu_1, q_1) = self.api().bls12_381_map_to_g2(&u_1);
+
+        let sum = self.api().bls12_381_g2_add(&q_0, &q_1);
+        let point = self.api().bls12_381_g2_clear_cofactor(&sum);
+
+        HashToG2Gadget {
+            msg: *msg,
+            dst: dst.to_vec(),
+            sswu: [sswu_0, sswu_1],
+            point,
+            digests,
+        }
+    }
+
+    /// The integer of the big-endian bytes `bytes`, reduced modulo `p`.
+    ///
+    /// The reduction is only constrained modulo `p`, and its result is checked to be canonical
+    /// where the sign matters, in `bls12_381_map_to_curve_simple_swu`.
+    fn bls12_381_reduce_bytes(
+        &mut self,
+        bytes: &[ByteRegister],
+    ) -> FieldRegister<Bls12381BaseField> {
+        // The 32 limbs of the bytes are padded with zeros to a multiple of `NB_LIMBS`.
+        let num_limbs =
+            Bls12381BaseField::NB_LIMBS * (bytes.len() / 2).div_ceil(Bls12381BaseField::NB_LIMBS);
+        let limbs = self.alloc_array_public_unchecked::<U16Register>(num_limbs);
+        for (i, limb) in limbs.iter().enumerate() {
+            let value = if 2 * i < bytes.len() {
+                let n = bytes.len() - 1 - 2 * i;
+                bytes[n].expr() + bytes[n - 1].expr() * L::Field::from_canonical_u32(1 << 8)
+            } else {
+                ArithmeticExpression::zero()
+            };
+            self.set_to_expression(&limb, value);
+        }
+        self.api().fp_reduce_wide::<Bls12381BaseField>(&limbs)
+    }
+}
+
+impl HashToG2Gadget {
+    /// Writes the message `msg`, the digests of its expansion and the images of the simplified
+    /// SWU map of the field elements hashed from it.
+    pub fn write<F: Field>(&self, msg: &[u8], writer: &mut impl AirWriter<Field = F>) {
+        writer.write_array(&self.msg, msg.iter().map(|b| F::from_canonical_u8(*b)));
+        let digests = SHA256::expand_message_xmd_digests(msg, &self.dst, 4 * FIELD_ELEMENT_BYTES);
+        for (digest, value) in self.digests.iter().zip(digests) {
+            writer.write_array(&digest.as_array(), value.map(u32_to_le_field_bytes));
+        }
+        for (sswu, u) in self.sswu.iter().zip(hash_to_field(msg, &self.dst)) {
+            let (x, y) = map_to_curve_simple_swu(&u);
+            writer.write_fp2(&sswu.x, &x);
+            writer.write_fp2(&sswu.y, &y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Num;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::bls12_381::g2::G2AirWriter;
+    use crate::chip::ec::bls12_381::instruction::Bls12381Instruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::tests::verifies_with;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct HashToG2Test;
+
+    impl AirParameters for HashToG2Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Bls12381Instruction;
+
+        const NUM_FREE_COLUMNS: usize = 1204;
+        const EXTENDED_COLUMNS: usize = 764;
+    }
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    fn fp2(c0: &str, c1: &str) -> Fp2 {
+        Fp2::new(
+            BigUint::from_str_radix(c0, 16).unwrap(),
+            BigUint::from_str_radix(c1, 16).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_hash_to_g2_pure() {
+        // The test vectors of RFC 9380, Appendix K.1 and Appendix J.10.1.
+        let expanded =
+            SHA256::expand_message_xmd(b"", b"QUUX-V01-CS02-with-expander-SHA256-128", 32);
+        assert_eq!(
+            hex::encode(expanded),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+
+        let point = hash_to_g2(b"", DST);
+        let expected = G2AffinePoint::new(
+            fp2(
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+            ),
+            fp2(
+                "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+            ),
+        );
+        assert_eq!(point, expected);
+    }
+
+    /// Proves the hash of `msg` to G2, with the message bytes replaced by `written` in the trace,
+    /// and returns whether the proof verifies.
+    fn prove(msg: &[u8], written: &[u8]) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = HashToG2Test;
+        type F = GoldilocksField;
+
+        let num_rows = hash_to_g2_num_rows(msg.len(), DST.len());
+        let mut builder = BytesBuilder::<L>::new();
+        let msg_bytes = builder.alloc_array_public::<ByteRegister>(msg.len());
+        let gadget = builder.bls12_381_hash_to_g2(&msg_bytes, DST, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        verifies_with(&stark, || {
+            let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+            let mut writer = writer_data.public_writer();
+            gadget.write(msg, &mut writer);
+            writer.write_array(
+                &gadget.msg,
+                written.iter().map(|b| F::from_canonical_u8(*b)),
+            );
+            stark.air_data.write_global_instructions(&mut writer);
+            assert_eq!(writer.read_g2_point(&gadget.point), hash_to_g2(msg, DST));
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+
+            (writer_data.trace, writer_data.public)
+        })
+    }
+
+    #[test]
+    fn test_hash_to_g2() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        assert!(prove(b"abc", b"abc"));
+        // The point and the expansion of a message don't match another message.
+        assert!(!prove(b"abc", b"abd"));
+    }
+}
@@ -1,2 +1,12 @@
+pub mod bls;
 pub mod builder;
+pub mod ecdsa;
+pub mod ecrecover;
+pub mod ecvrf;
+pub mod eddsa;
+pub mod fixed_base;
+pub mod glv;
+pub mod hash_to_curve;
+pub mod msm;
 pub mod scalar_mul;
+pub mod schnorr;
@@ -0,0 +1,467 @@
+//! Multi-scalar multiplication with Pippenger's bucket method.
+//!
+//! Computing `sum_i s_i * P_i` with one scalar multiplication per pair needs a double-and-add
+//! cycle for every term. Instead, each scalar is split into windows of `WINDOW_BITS` bits and,
+//! for every window `w`, the point `P_i` is added to the bucket indexed by the digit `d_{i, w}`.
+//! The buckets of a window are then reduced to the window sum `W_w = sum_d d * B_{w, d}`, and
+//! the result is `sum_w 16^w * W_w`.
+//!
+//! The trace has two segments sharing the bucket memory:
+//! - `msm_accumulate` has one row per point and window, which adds the point to its bucket.
+//! - `msm_reduce` has a cycle of `WINDOW_SIZE` rows per window, which sums the buckets from the
+//!   highest digit down with the running sum trick and stores the window sum.
+//!
+//! Both segments have a single row layout, so the columns of the bucket additions are shared by
+//! all the terms instead of growing with their number.
+
+use log::debug;
+use num::BigUint;
+
+use super::builder::EllipticCurveBuilder;
+use super::fixed_base::WINDOW_BITS;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::edwards::{EdwardsCurve, EdwardsParameters};
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::{EllipticCurve, EllipticCurveAir, EllipticCurveParameters};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+
+/// The public registers of a multi-scalar multiplication.
+#[derive(Debug, Clone)]
+pub struct MSMGadget<E: EllipticCurve> {
+    pub points: Vec<AffinePointRegister<E>>,
+    /// The little-endian bits of the scalars.
+    pub scalars: Vec<ArrayRegister<BitRegister>>,
+    /// The sum `W_w = sum_i d_{i, w} * P_i` of each window `w`.
+    pub window_sums: Vec<AffinePointRegister<E>>,
+    /// The combination `sum_i s_i * P_i`.
+    pub result: AffinePointRegister<E>,
+}
+
+pub trait MSMBuilder: Builder {
+    /// Computes `sum_i scalars[i] * points[i]`.
+    ///
+    /// The points and the little-endian bits of the scalars must be public registers, whose
+    /// values are written by `MSMGadget::write` before the global instructions. The points are
+    /// assumed to be on the curve, which can be checked with `ed_assert_valid`.
+    ///
+    /// The gadget opens two segments after the last one opened so far, which must end at a
+    /// multiple of `nb_scalar_bits / WINDOW_BITS`, and uses
+    /// `(points.len() + WINDOW_SIZE) * nb_scalar_bits / WINDOW_BITS` rows. As the segments read
+    /// buckets written by previous rows, the trace must be generated sequentially.
+    ///
+    /// Each bucket is read exactly once in each of its versions, so the reads of a bucket form
+    /// a chain from its initial value to the read of the reduction, possibly with disjoint
+    /// cycles. A cycle of complete additions sums to the neutral element, so the reduction
+    /// reads the sum of all the points added to the bucket, regardless of the order chosen by
+    /// the prover.
+    fn ed_msm<P: EdwardsParameters>(
+        &mut self,
+        points: &[AffinePointRegister<EdwardsCurve<P>>],
+        scalars: &[ArrayRegister<BitRegister>],
+    ) -> MSMGadget<EdwardsCurve<P>>
+    where
+        EdwardsCurve<P>: EllipticCurveAir<Self::Parameters>,
+    {
+        assert_eq!(points.len(), scalars.len(), "Expected one scalar per point");
+        assert!(!points.is_empty(), "No points");
+        let nb_scalar_bits = EdwardsCurve::<P>::nb_scalar_bits();
+        let num_windows = nb_scalar_bits / WINDOW_BITS;
+        assert!(
+            num_windows.is_power_of_two(),
+            "Number of windows must be a power of 2"
+        );
+        for (point, scalar) in points.iter().zip(scalars.iter()) {
+            assert!(
+                !point.x.is_trace() && !point.y.is_trace(),
+                "Points must be public"
+            );
+            assert_eq!(
+                scalar.len(),
+                nb_scalar_bits,
+                "Scalar must have {} bits",
+                nb_scalar_bits
+            );
+            assert!(!scalar.is_trace(), "Scalar bits must be public");
+        }
+
+        let num_points = points.len();
+        let accumulate_rows = num_points * num_windows;
+        let reduce_rows = WINDOW_SIZE * num_windows;
+        let start = self.api().segments().last().map_or(0, |s| s.rows.end);
+        assert_eq!(
+            start % num_windows,
+            0,
+            "Segments must start at a multiple of the number of windows"
+        );
+        debug!(
+            "MSM of {} points: rows {}..{}",
+            num_points,
+            start,
+            start + accumulate_rows + reduce_rows
+        );
+
+        let zero = Time::zero();
+        let field = |n: usize| Self::Field::from_canonical_usize(n);
+        let digit_ptr = self.uninit_slice::<ElementRegister>();
+        let point_x =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+        let point_y =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+        let bucket_x =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+        let bucket_y =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+        let window_x =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+        let window_y =
+            self.uninit_slice::<FieldRegister<<P as EllipticCurveParameters>::BaseField>>();
+
+        // Each point is read once per window, and each digit once.
+        let point_reads = self.constant::<ElementRegister>(&field(num_windows));
+        for (i, (point, scalar)) in points.iter().zip(scalars.iter()).enumerate() {
+            self.store(
+                &point_x.get(i),
+                point.x,
+                &zero,
+                Some(point_reads),
+                None,
+                None,
+            );
+            self.store(
+                &point_y.get(i),
+                point.y,
+                &zero,
+                Some(point_reads),
+                None,
+                None,
+            );
+            for w in 0..num_windows {
+                let digit_expr = (0..WINDOW_BITS)
+                    .map(|j| scalar.get(WINDOW_BITS * w + j).expr() * field(1 << j))
+                    .fold(ArithmeticExpression::zero(), |acc, term| acc + term);
+                let digit = self.public_expression::<ElementRegister>(digit_expr);
+                self.store(
+                    &digit_ptr.get(i * num_windows + w),
+                    digit,
+                    &zero,
+                    None,
+                    None,
+                    None,
+                );
+            }
+        }
+
+        // All the buckets start at the neutral element.
+        let neutral = EdwardsCurve::<P>::neutral();
+        let neutral_point = AffinePointRegister::<EdwardsCurve<P>>::new(
+            self.api().fp_constant(&neutral.x),
+            self.api().fp_constant(&neutral.y),
+        );
+        for index in 0..num_windows * WINDOW_SIZE {
+            self.store(
+                &bucket_x.get(index),
+                neutral_point.x,
+                &zero,
+                None,
+                None,
+                None,
+            );
+            self.store(
+                &bucket_y.get(index),
+                neutral_point.y,
+                &zero,
+                None,
+                None,
+                None,
+            );
+        }
+
+        // The cycles are indexed from the first row of the trace, so they are registered outside
+        // of the segments.
+        let point_cycle = self.cycle(num_windows.ilog2() as usize);
+        let point_id = self.process_id(num_windows, point_cycle.end_bit);
+        let bucket_cycle = self.cycle(WINDOW_BITS);
+        let window_id = self.process_id(WINDOW_SIZE, bucket_cycle.end_bit);
+        let clk = self.clk();
+
+        // The row `start + i * num_windows + w` adds the point `P_i` to the bucket of `d_{i, w}`.
+        self.begin_segment("msm_accumulate", accumulate_rows);
+        let row = clk.expr() - field(start);
+        let digit_index = self.expression::<ElementRegister>(row.clone());
+        let digit = self.load(&digit_ptr.get_at(digit_index), &zero, None, None);
+        let point_index =
+            self.expression::<ElementRegister>(point_id.expr() - field(start / num_windows));
+        let point = AffinePointRegister::<EdwardsCurve<P>>::new(
+            self.load(&point_x.get_at(point_index), &zero, None, None),
+            self.load(&point_y.get_at(point_index), &zero, None, None),
+        );
+        let window = row - point_index.expr() * field(num_windows);
+        let bucket_index =
+            self.expression::<ElementRegister>(window * field(WINDOW_SIZE) + digit.expr());
+        let bucket = AffinePointRegister::<EdwardsCurve<P>>::new(
+            self.load(&bucket_x.get_at(bucket_index), &zero, None, None),
+            self.load(&bucket_y.get_at(bucket_index), &zero, None, None),
+        );
+        let bucket_next = self.add(&bucket, &point);
+        self.store(
+            &bucket_x.get_at(bucket_index),
+            bucket_next.x,
+            &zero,
+            None,
+            None,
+            None,
+        );
+        self.store(
+            &bucket_y.get_at(bucket_index),
+            bucket_next.y,
+            &zero,
+            None,
+            None,
+            None,
+        );
+        self.end_segment();
+
+        // The row `k` of the cycle of window `w` reads the bucket of the digit `15 - k`. After it,
+        // `running` is the sum of the buckets of digits at least `15 - k`, so the sum of the
+        // values of `running` before each row is `sum_d d * B_{w, d}`.
+        let reduce_start = start + accumulate_rows;
+        self.begin_segment("msm_reduce", reduce_rows);
+        let window = self
+            .expression::<ElementRegister>(window_id.expr() - field(reduce_start / WINDOW_SIZE));
+        let k = clk.expr() - window_id.expr() * field(WINDOW_SIZE);
+        let bucket_index = self.expression::<ElementRegister>(
+            window.expr() * field(WINDOW_SIZE)
+                + ArithmeticExpression::from_constant(field(WINDOW_SIZE - 1))
+                - k,
+        );
+        let bucket = AffinePointRegister::<EdwardsCurve<P>>::new(
+            self.load(&bucket_x.get_at(bucket_index), &zero, None, None),
+            self.load(&bucket_y.get_at(bucket_index), &zero, None, None),
+        );
+
+        let running: AffinePointRegister<EdwardsCurve<P>> = self.alloc_ec_point();
+        let sum: AffinePointRegister<EdwardsCurve<P>> = self.alloc_ec_point();
+        let running_next = self.add(&running, &bucket);
+        let sum_next = self.add(&sum, &running);
+        for (register, next) in [(running, running_next), (sum, sum_next)] {
+            self.set_to_expression_first_row(&register.x, neutral_point.x.expr());
+            self.set_to_expression_first_row(&register.y, neutral_point.y.expr());
+            self.select_next_ec_point(bucket_cycle.end_bit, &neutral_point, &next, &register);
+        }
+
+        let end_flag = Some(bucket_cycle.end_bit.as_element());
+        self.store(
+            &window_x.get_at(window),
+            sum_next.x,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+        self.store(
+            &window_y.get_at(window),
+            sum_next.y,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+        self.end_segment();
+
+        let window_sums = (0..num_windows)
+            .map(|w| {
+                let window_sum: AffinePointRegister<EdwardsCurve<P>> = self.alloc_public_ec_point();
+                self.free(&window_x.get(w), window_sum.x, &zero);
+                self.free(&window_y.get(w), window_sum.y, &zero);
+                window_sum
+            })
+            .collect::<Vec<_>>();
+
+        // Combine the window sums with Horner's rule.
+        let (last, rest) = window_sums.split_last().unwrap();
+        let result = rest.iter().rev().fold(*last, |acc, window_sum| {
+            let acc = (0..WINDOW_BITS).fold(acc, |acc, _| self.double(&acc));
+            self.add(&acc, window_sum)
+        });
+
+        MSMGadget {
+            points: points.to_vec(),
+            scalars: scalars.to_vec(),
+            window_sums,
+            result,
+        }
+    }
+}
+
+impl<B: Builder> MSMBuilder for B {}
+
+/// The window sums `W_w = sum_i d_{i, w} * P_i` of the multi-scalar multiplication of `points`
+/// and `scalars`.
+pub fn window_sums<P: EdwardsParameters>(
+    points: &[AffinePoint<EdwardsCurve<P>>],
+    scalars: &[BigUint],
+) -> Vec<AffinePoint<EdwardsCurve<P>>> {
+    let num_windows = EdwardsCurve::<P>::nb_scalar_bits() / WINDOW_BITS;
+    let digits = scalars
+        .iter()
+        .map(|scalar| {
+            let mut digits = scalar.to_radix_le(WINDOW_SIZE as u32);
+            assert!(digits.len() <= num_windows, "Scalar is too large");
+            digits.resize(num_windows, 0);
+            digits
+        })
+        .collect::<Vec<_>>();
+    (0..num_windows)
+        .map(|w| {
+            points
+                .iter()
+                .zip(digits.iter())
+                .fold(EdwardsCurve::<P>::neutral(), |acc, (point, digits)| {
+                    &acc + &(point * BigUint::from(digits[w]))
+                })
+        })
+        .collect()
+}
+
+impl<P: EdwardsParameters> MSMGadget<EdwardsCurve<P>> {
+    /// Writes the points, the bits of the scalars and the window sums.
+    pub fn write<F: PrimeField64>(
+        &self,
+        points: &[AffinePoint<EdwardsCurve<P>>],
+        scalars: &[BigUint],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        assert_eq!(self.points.len(), points.len());
+        assert_eq!(self.scalars.len(), scalars.len());
+        for (register, value) in self.points.iter().zip(points.iter()) {
+            writer.write_ec_point(register, value);
+        }
+        for (register, value) in self.scalars.iter().zip(scalars.iter()) {
+            let mut bits = value.to_radix_le(2);
+            bits.resize(register.len(), 0);
+            writer.write_array(register, bits.into_iter().map(F::from_canonical_u8));
+        }
+        for (register, value) in self
+            .window_sums
+            .iter()
+            .zip(window_sums(points, scalars).iter())
+        {
+            writer.write_ec_point(register, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::edwards::ed25519::params::{Ed25519, Ed25519Parameters};
+    use crate::chip::ec::ECInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519MSMTest;
+
+    impl AirParameters for Ed25519MSMTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Ed25519>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2448;
+        const NUM_FREE_COLUMNS: usize = 40;
+        const EXTENDED_COLUMNS: usize = 3753;
+    }
+
+    /// Proves the multi-scalar multiplication of `points` and `scalars`, writing `window_sums`
+    /// as the claimed window sums, and returns the result if the proof verifies.
+    fn prove_msm(
+        points: &[AffinePoint<Ed25519>],
+        scalars: &[BigUint],
+        window_sums: &[AffinePoint<Ed25519>],
+    ) -> Option<AffinePoint<Ed25519>> {
+        type L = Ed25519MSMTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let nb_bits = Ed25519::nb_scalar_bits();
+        let point_registers = points
+            .iter()
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        let scalar_registers = scalars
+            .iter()
+            .map(|_| builder.alloc_array_public::<BitRegister>(nb_bits))
+            .collect::<Vec<_>>();
+        let gadget = builder.ed_msm::<Ed25519Parameters>(&point_registers, &scalar_registers);
+
+        let num_rows = ((points.len() + WINDOW_SIZE) * nb_bits / WINDOW_BITS).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(points, scalars, &mut writer);
+        for (register, value) in gadget.window_sums.iter().zip(window_sums.iter()) {
+            writer.write_ec_point(register, value);
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        let result = writer.read_ec_point(&gadget.result);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public).then_some(result)
+    }
+
+    #[test]
+    fn test_ed25519_msm() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut rng = thread_rng();
+        let order = Ed25519::prime_group_order();
+        let generator = Ed25519::ec_generator();
+        let points = (0..3)
+            .map(|_| &generator * rng.gen_biguint_below(&order))
+            .collect::<Vec<_>>();
+        let mut scalars = (0..3).map(|_| rng.gen_biguint(256)).collect::<Vec<_>>();
+        scalars[2] = BigUint::from(0u32);
+
+        let expected = points
+            .iter()
+            .zip(scalars.iter())
+            .fold(Ed25519::neutral(), |acc, (point, scalar)| {
+                &acc + &(point * scalar)
+            });
+        let sums = window_sums(&points, &scalars);
+        assert_eq!(prove_msm(&points, &scalars, &sums), Some(expected));
+
+        // Wrong window sums are rejected.
+        let mut wrong_sums = sums;
+        wrong_sums[5] = &wrong_sums[5] + &generator;
+        assert_eq!(prove_msm(&points, &scalars, &wrong_sums), None);
+    }
+}
@@ -0,0 +1,519 @@
+//! BIP-340 Schnorr signature verification over secp256k1.
+//!
+//! A public key is the x-coordinate of a point `P` with an even y-coordinate. A signature
+//! `(r, s)` on a message `m` is valid if
+//!
+//! R = [s]G - [e]P
+//!
+//! is not the identity, has an even y-coordinate and has x-coordinate `r`, where the challenge
+//! `e` is the tagged hash `SHA256(SHA256(tag) || SHA256(tag) || r || x(P) || m) mod n` with
+//! the tag `BIP0340/challenge`. The gadget lifts the public key with the even square root in the
+//! base field, computes `[s]G` and `[-e]P` with a single scalar multiplication batch and adds the
+//! two points. The y-coordinate of `R` is checked to be its own even square root.
+//!
+//! The input `r || x(P) || m` of the challenge is a public input given by its padded SHA-256
+//! chunks, from which `r` and `x(P)` are read. The tagged hash is computed by the SHA-256 AIR in
+//! the same trace as the scalar multiplications and reduced modulo `n`. The AIR also checks the
+//! ranges `x(P) < p`, `r < p` and `s < n`.
+//!
+//! Reference: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+
+use itertools::Itertools;
+use num::{BigUint, Zero};
+
+use super::builder::EllipticCurveBuilder;
+use super::ecdsa::ECDSABuilder;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::secp256k1::decompress::lift_x;
+use crate::chip::ec::secp256k1::params::{
+    Secp256k1, Secp256k1BaseField, Secp256k1Parameters, Secp256k1ScalarField,
+};
+use crate::chip::ec::weierstrass::WeierstrassParameters;
+use crate::chip::ec::ECInstructions;
+use crate::chip::field::compare::FpCompareInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::field::sqrt::FpSqrtInstruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The tag of the BIP-340 challenge hash.
+pub const CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+/// The number of rows of the trace of `BytesBuilder::schnorr_verify`, taken by a scalar
+/// multiplication batch of two operations.
+pub const VERIFY_NUM_ROWS: usize = 512;
+
+/// The public registers of a Schnorr signature verification.
+#[derive(Debug, Clone)]
+pub struct SchnorrGadget {
+    /// The chunks of `r || x(P) || m`, padded with `SHA256::tagged_pad`.
+    pub padded_chunks: Vec<ArrayRegister<U32Register>>,
+    /// The x-only public key.
+    pub pubkey_x: FieldRegister<Secp256k1BaseField>,
+    /// The challenge `e`, reduced modulo the group order.
+    pub challenge: FieldRegister<Secp256k1ScalarField>,
+    pub r: FieldRegister<Secp256k1BaseField>,
+    pub s: FieldRegister<Secp256k1ScalarField>,
+    /// The tagged hash of `r || x(P) || m`, computed by the SHA-256 AIR.
+    digest: SHA256DigestRegister,
+    /// The result of `[s]G`.
+    s_mul_g: AffinePointRegister<Secp256k1>,
+    /// The result of `[-e]P`.
+    neg_e_mul_p: AffinePointRegister<Secp256k1>,
+}
+
+/// The values of a Schnorr signature verification.
+#[derive(Debug, Clone)]
+pub struct SchnorrWitness {
+    pub pubkey_x: BigUint,
+    /// The challenge `e`, reduced modulo the group order.
+    pub challenge: BigUint,
+    pub r: BigUint,
+    pub s: BigUint,
+    pub msg: Vec<u8>,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions
+        + ECInstructions<Secp256k1>
+        + FromFieldInstruction<Secp256k1ScalarField>
+        + From<FpCompareInstruction<Secp256k1BaseField>>
+        + From<FpCompareInstruction<Secp256k1ScalarField>>
+        + From<FpSqrtInstruction<Secp256k1BaseField>>,
+{
+    /// Verifies the signature `(r, s)` of a message `m` under an x-only public key.
+    ///
+    /// The input `r || x(P) || m` of the challenge is given by `padded_chunks`, its chunks padded
+    /// with `SHA256::tagged_pad`, from which `r` and `x(P)` are read. All the registers must be
+    /// public, and their values are written by `SchnorrGadget::write` before the global
+    /// instructions.
+    ///
+    /// The trace must have `VERIFY_NUM_ROWS` rows, so the padded input has at most seven chunks.
+    /// The challenge and `s` must not vanish and the points `[s]G` and `[e]P` must be distinct,
+    /// which holds for all but a negligible fraction of the signatures.
+    pub fn schnorr_verify(
+        &mut self,
+        s: &FieldRegister<Secp256k1ScalarField>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+    ) -> SchnorrGadget {
+        self.schnorr_verify_with_parity(s, padded_chunks, [false, false])
+    }
+
+    /// Verifies the signature with the y-coordinates of `P` and `R` constrained to the parities
+    /// `is_odd`. Only even parities verify BIP-340 signatures; the AIRs of the other parities have
+    /// the same layout, which the tests use to write traces of signatures with odd points.
+    fn schnorr_verify_with_parity(
+        &mut self,
+        s: &FieldRegister<Secp256k1ScalarField>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        is_odd: [bool; 2],
+    ) -> SchnorrGadget {
+        assert!(
+            !s.is_trace() && padded_chunks.iter().all(|chunk| !chunk.is_trace()),
+            "Inputs must be public"
+        );
+
+        // Byte `k` of the input is byte `3 - k % 4` of the big-endian word `k / 4`.
+        let input_byte = |k: usize| padded_chunks[0].get(k / 4).to_le_bytes().get(3 - k % 4);
+        let r_bytes = (0..32).map(input_byte).collect::<Vec<_>>();
+        let pubkey_bytes = (32..64).map(input_byte).collect::<Vec<_>>();
+        let r = self.schnorr_field_element::<Secp256k1BaseField>(&r_bytes);
+        let pubkey_x = self.schnorr_field_element::<Secp256k1BaseField>(&pubkey_bytes);
+
+        // The challenge is the digest as a big-endian integer, reduced modulo `n`. The reduction
+        // is only constrained modulo `n`, which doesn't change the scalar multiplication.
+        let digest = self.tagged_hash(CHALLENGE_TAG, &[padded_chunks.to_vec()], VERIFY_NUM_ROWS)[0];
+        let digest_bytes = (0..32)
+            .map(|k| digest.get(k / 4).to_le_bytes().get(3 - k % 4))
+            .collect::<Vec<_>>();
+        let digest_int = self.schnorr_field_element::<Secp256k1ScalarField>(&digest_bytes);
+        let challenge = self
+            .api()
+            .fp_reduce::<Secp256k1ScalarField, Secp256k1ScalarField>(&digest_int);
+
+        for is_reduced in [
+            self.api().fp_is_reduced(&pubkey_x),
+            self.api().fp_is_reduced(&r),
+            self.api().fp_is_reduced(s),
+        ] {
+            self.assert_expression_zero(is_reduced.not_expr());
+        }
+
+        let [pubkey_is_odd, sig_r_is_odd] =
+            is_odd.map(|is_odd| self.constant::<BitRegister>(&L::Field::from_bool(is_odd)));
+
+        // Lift the public key to the point with an even y-coordinate.
+        let seven = self.api().fp_constant(&Secp256k1Parameters::b_int());
+        let x_sq = self.api().fp_mul(&pubkey_x, &pubkey_x);
+        let x_cube = self.api().fp_mul(&x_sq, &pubkey_x);
+        let y_sq = self.api().fp_add(&x_cube, &seven);
+        let y = self.api().fp_sqrt(&y_sq, &pubkey_is_odd);
+        let pubkey = AffinePointRegister::new(pubkey_x, y);
+
+        let zero = self.api().fp_zero();
+        let neg_e = self.api().fp_sub(&zero, &challenge);
+        let s_scalar = self.ecdsa_scalar::<Secp256k1Parameters>(s);
+        let neg_e = self.ecdsa_scalar::<Secp256k1Parameters>(&neg_e);
+
+        let g = self.generator();
+        let s_mul_g = self.alloc_public_ec_point();
+        let neg_e_mul_p = self.alloc_public_ec_point();
+        self.scalar_mul_batch([g, pubkey], [s_scalar, neg_e], [s_mul_g, neg_e_mul_p]);
+
+        // Assert that `R` has x-coordinate `r` and an even y-coordinate, which is then the reduced
+        // even square root of its square.
+        let sig_r = self.api().secp256k1_add(&s_mul_g, &neg_e_mul_p);
+        self.assert_equal(&sig_r.x, &r);
+        let sig_r_y_sq = self.api().fp_mul(&sig_r.y, &sig_r.y);
+        let sig_r_y = self.api().fp_sqrt(&sig_r_y_sq, &sig_r_is_odd);
+        self.assert_equal(&sig_r.y, &sig_r_y);
+
+        SchnorrGadget {
+            padded_chunks: padded_chunks.to_vec(),
+            pubkey_x,
+            challenge,
+            r,
+            s: *s,
+            digest,
+            s_mul_g,
+            neg_e_mul_p,
+        }
+    }
+
+    /// The integer of the 32 big-endian bytes `bytes`, as the limbs of a register of `P`.
+    fn schnorr_field_element<P: FieldParameters>(
+        &mut self,
+        bytes: &[ByteRegister],
+    ) -> FieldRegister<P> {
+        let element = self.alloc_public_unchecked::<FieldRegister<P>>();
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*element.register());
+        for (i, limb) in limbs.iter().enumerate() {
+            let value: ArithmeticExpression<L::Field> = bytes[31 - 2 * i].expr()
+                + bytes[30 - 2 * i].expr() * L::Field::from_canonical_u32(1 << 8);
+            self.set_to_expression(&limb, value);
+        }
+        element
+    }
+}
+
+impl SchnorrGadget {
+    /// Writes the chunks of `r || x(P) || m`, its tagged hash, `s` and the scalar multiplication
+    /// results.
+    pub fn write<F: Field>(
+        &self,
+        witness: &SchnorrWitness,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        let input = witness.hash_input();
+        for (chunk, values) in self
+            .padded_chunks
+            .iter()
+            .zip_eq(SHA256::tagged_pad(&input).chunks_exact(16))
+        {
+            writer.write_array(
+                chunk,
+                values.iter().map(|word| u32_to_le_field_bytes(*word)),
+            );
+        }
+        let digest: ArrayRegister<U32Register> = self.digest.into();
+        writer.write_array(
+            &digest,
+            SchnorrWitness::tagged_digest(&input).map(u32_to_le_field_bytes),
+        );
+        writer.write(
+            &self.s,
+            &to_u16_le_limbs_polynomial::<F, Secp256k1ScalarField>(&witness.s),
+        );
+
+        let n = Secp256k1ScalarField::modulus();
+        let pubkey = witness.pubkey().expect("invalid public key");
+        let neg_e = (&n - &witness.challenge % &n) % &n;
+        writer.write_ec_point(
+            &self.s_mul_g,
+            &Secp256k1::generator().sw_scalar_mul(&witness.s),
+        );
+        writer.write_ec_point(&self.neg_e_mul_p, &pubkey.sw_scalar_mul(&neg_e));
+    }
+}
+
+impl SchnorrWitness {
+    /// Parses an x-only public key and a 64-byte signature `r || s` of `msg`, and computes the
+    /// challenge.
+    pub fn new(pubkey: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> Self {
+        Self {
+            pubkey_x: BigUint::from_bytes_be(pubkey),
+            challenge: Self::challenge(pubkey, msg, sig),
+            r: BigUint::from_bytes_be(&sig[..32]),
+            s: BigUint::from_bytes_be(&sig[32..]),
+            msg: msg.to_vec(),
+        }
+    }
+
+    /// The challenge `e`, the tagged hash of `r || x(P) || msg` interpreted as a big-endian
+    /// integer and reduced modulo the group order.
+    pub fn challenge(pubkey: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> BigUint {
+        let input = [&sig[..32], pubkey.as_slice(), msg].concat();
+        let digest = Self::tagged_digest(&input)
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        BigUint::from_bytes_be(&digest) % Secp256k1ScalarField::modulus()
+    }
+
+    /// The input `r || x(P) || msg` of the challenge hash.
+    pub fn hash_input(&self) -> Vec<u8> {
+        let be_bytes = |value: &BigUint| {
+            let bytes = value.to_bytes_be();
+            [vec![0u8; 32 - bytes.len()], bytes].concat()
+        };
+        [
+            be_bytes(&self.r),
+            be_bytes(&self.pubkey_x),
+            self.msg.clone(),
+        ]
+        .concat()
+    }
+
+    /// The words of the tagged hash `SHA256(SHA256(tag) || SHA256(tag) || input)` of the
+    /// challenge.
+    fn tagged_digest(input: &[u8]) -> [u32; 8] {
+        let mut preimage = SHA256::tag_block(CHALLENGE_TAG)
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        preimage.extend_from_slice(input);
+        SHA256::digest(&preimage)
+    }
+
+    /// The point `P` of the public key with an even y-coordinate, if it exists.
+    fn pubkey(&self) -> Option<AffinePoint<Secp256k1>> {
+        lift_x(&self.pubkey_x, false)
+    }
+
+    /// Checks the signature outside of the AIR.
+    pub fn verify(&self) -> bool {
+        let p = Secp256k1BaseField::modulus();
+        let n = Secp256k1ScalarField::modulus();
+        if self.r >= p || self.s >= n || self.s.is_zero() || self.challenge.is_zero() {
+            return false;
+        }
+        let Some(pubkey) = self.pubkey() else {
+            return false;
+        };
+        let s_mul_g = Secp256k1::generator().sw_scalar_mul(&self.s);
+        let neg_e_mul_p = pubkey.sw_scalar_mul(&(&n - &self.challenge));
+        let sig_r = if s_mul_g.x != neg_e_mul_p.x {
+            s_mul_g.sw_add(&neg_e_mul_p)
+        } else if s_mul_g.y == neg_e_mul_p.y {
+            s_mul_g.sw_double()
+        } else {
+            // The sum is the identity.
+            return false;
+        };
+        !sig_r.y.bit(0) && sig_r.x == self.r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::secp256k1::instruction::Secp256k1Instruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::machine::bytes::stark::ByteStark;
+    use crate::machine::tests::{assert_rejected, verifies};
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::trace::AirTrace;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct SchnorrTest;
+
+    impl AirParameters for SchnorrTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Secp256k1Instruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2184;
+        const NUM_FREE_COLUMNS: usize = 1300;
+        const EXTENDED_COLUMNS: usize = 4100;
+    }
+
+    /// The valid signatures of the BIP-340 test vectors, as (public key, message, signature).
+    const VECTORS: [(&str, &str, &str); 5] = [
+        (
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+        ),
+        (
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+            "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+            "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de33418906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+        ),
+        (
+            "dd308afec5777e13121fa72b9cc1b7cc0139715309b086c960e18fd969774eb8",
+            "7e2d58d8b3bcdf1abadec7829054f90dda9805aab56c77333024b9d0a508b75c",
+            "5831aaeed7b44bb74e5eab94ba9d4294c49bcf2a60728d8b4c200f50dd313c1bab745879a5ad954a72c45a91c3a51d3c7adea98d82f8481e0e1e03674a6f3fb7",
+        ),
+        (
+            "25d1dff95105f5253c4022f628a996ad3a0d95fbf21d468a1b33f8c160d8f517",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "7eb0509757e246f19449885651611cb965ecc1a187dd51b64fda1edc9637d5ec97582b9cb13db3933705b32ba982af5af25fd78881ebb32771fc5922efc66ea3",
+        ),
+        (
+            "d69c3509bb99e412e68b0fe8544e72837dfa30746d8be2aa65975f29d22dc7b9",
+            "4df3c3f68fcc83b27e9d42c90431a72499f17875c81a599b566c9889b9696703",
+            "00000000000000000000003b78ce563f89a0ed9414f5aa28ad0d96d6795f9c6376afb1548af603b3eb45c9f8207dee1060cb71c04e80f593060b07d28308d7f4",
+        ),
+    ];
+
+    fn witness(index: usize, msg: Option<&[u8]>) -> SchnorrWitness {
+        let (pubkey, expected_msg, sig) = VECTORS[index];
+        let pubkey: [u8; 32] = hex::decode(pubkey).unwrap().try_into().unwrap();
+        let sig: [u8; 64] = hex::decode(sig).unwrap().try_into().unwrap();
+        let msg = msg.map_or_else(|| hex::decode(expected_msg).unwrap(), |msg| msg.to_vec());
+        SchnorrWitness::new(&pubkey, &msg, &sig)
+    }
+
+    #[test]
+    fn test_schnorr_verify_pure() {
+        for i in 0..VECTORS.len() {
+            assert!(witness(i, None).verify());
+            assert!(!witness(i, Some(b"tampered".as_slice())).verify());
+        }
+    }
+
+    type SchnorrStark = ByteStark<SchnorrTest, CurtaPoseidonGoldilocksConfig, 2>;
+
+    /// The stark verifying the signature of `witness` with the y-coordinates of `P` and `R` of
+    /// parities `is_odd`, and its gadget.
+    fn schnorr_stark(witness: &SchnorrWitness, is_odd: [bool; 2]) -> (SchnorrStark, SchnorrGadget) {
+        let mut builder = BytesBuilder::<SchnorrTest>::new();
+        let s = builder.alloc_public::<FieldRegister<Secp256k1ScalarField>>();
+        let padded_chunks = (0..SHA256::tagged_pad(&witness.hash_input()).len() / 16)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let gadget = builder.schnorr_verify_with_parity(&s, &padded_chunks, is_odd);
+        let stark = builder.build::<CurtaPoseidonGoldilocksConfig, 2>(VERIFY_NUM_ROWS);
+        (stark, gadget)
+    }
+
+    /// The trace and public values of the verification of `witness`, with the public key lifted
+    /// to the point of y-parity `pubkey_is_odd`.
+    fn schnorr_trace(
+        stark: &SchnorrStark,
+        gadget: &SchnorrGadget,
+        witness: &SchnorrWitness,
+        pubkey_is_odd: bool,
+    ) -> (AirTrace<GoldilocksField>, Vec<GoldilocksField>) {
+        let num_rows = VERIFY_NUM_ROWS;
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(witness, &mut writer);
+        let n = Secp256k1ScalarField::modulus();
+        let pubkey = lift_x(&witness.pubkey_x, pubkey_is_odd).unwrap();
+        writer.write_ec_point(
+            &gadget.neg_e_mul_p,
+            &pubkey.sw_scalar_mul(&(&n - &witness.challenge)),
+        );
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+        (writer_data.trace, writer_data.public)
+    }
+
+    /// Proves the verification of `witness` and returns whether the proof verifies.
+    fn prove(witness: &SchnorrWitness) -> bool {
+        let (stark, gadget) = schnorr_stark(witness, [false, false]);
+        let (trace, public) = schnorr_trace(&stark, &gadget, witness, false);
+        verifies(&stark, &trace, &public)
+    }
+
+    /// The least scalar `k` such that `[k]G` has a y-coordinate of parity `is_odd`.
+    fn scalar_with_parity(is_odd: bool) -> BigUint {
+        (1u32..)
+            .map(BigUint::from)
+            .find(|k| Secp256k1::generator().sw_scalar_mul(k).y.bit(0) == is_odd)
+            .unwrap()
+    }
+
+    /// The signature of `msg` with the secret key `d` and the nonce `k`, which are not negated
+    /// to make `[d]G` and `[k]G` even.
+    fn sign(d: &BigUint, k: &BigUint, msg: &[u8]) -> SchnorrWitness {
+        let n = Secp256k1ScalarField::modulus();
+        let be_bytes = |value: &BigUint| {
+            let bytes = value.to_bytes_be();
+            [vec![0u8; 32 - bytes.len()], bytes].concat()
+        };
+        let pubkey: [u8; 32] = be_bytes(&Secp256k1::generator().sw_scalar_mul(d).x)
+            .try_into()
+            .unwrap();
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&be_bytes(&Secp256k1::generator().sw_scalar_mul(k).x));
+        let challenge = SchnorrWitness::challenge(&pubkey, msg, &sig);
+        sig[32..].copy_from_slice(&be_bytes(&((k + challenge * d) % &n)));
+        SchnorrWitness::new(&pubkey, msg, &sig)
+    }
+
+    #[test]
+    fn test_schnorr_verify() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        for i in 0..3 {
+            assert!(prove(&witness(i, None)));
+
+            // The challenge of the signed message doesn't match the hash of another message.
+            let mut tampered = witness(i, None);
+            tampered.msg = b"tampered".to_vec();
+            assert!(!prove(&tampered));
+        }
+    }
+
+    #[test]
+    fn test_schnorr_verify_odd_points() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let [even, odd] = [false, true].map(scalar_with_parity);
+        // The signatures verify against `P = [d]G` and `R = [k]G`, one of which is odd.
+        for (d, k, is_odd) in [(&odd, &even, [true, false]), (&even, &odd, [false, true])] {
+            let witness = sign(d, k, b"message");
+            assert!(!witness.verify());
+
+            // The trace of the AIR with the odd parity is valid, and is rejected by the AIR
+            // constraining both points to be even.
+            let (odd_stark, odd_gadget) = schnorr_stark(&witness, is_odd);
+            let (trace, public) = schnorr_trace(&odd_stark, &odd_gadget, &witness, is_odd[0]);
+            assert!(verifies(&odd_stark, &trace, &public));
+
+            let (stark, _) = schnorr_stark(&witness, [false, false]);
+            assert_rejected(&stark, &trace, &public);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use super::stark::EmulatedStark;
 use super::RangeParameters;
+use crate::air::RAirData;
 use crate::chip::builder::AirBuilder;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::element::ElementRegister;
@@ -67,8 +68,11 @@ impl<L: AirParameters> EmulatedBuilder<L> {
         let lookup_values = table_data.register_lookup_values(&mut api, &values);
         lookup_builder.constrain_element_lookup_table(table_data);
 
-        let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
         let (air, trace_data) = api.build();
+        let config = StarkyConfig::<C, D>::standard_fast_config_with_degree(
+            num_rows,
+            air.constraint_degree(),
+        );
         let stark = Starky::new(air);
 
         let lookup_config = StarkyConfig::<C, D>::standard_fast_config(NUM_LOOKUP_ROWS);
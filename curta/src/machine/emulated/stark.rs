@@ -155,9 +155,9 @@ where
         public_values: &[L::Field],
         challenger: &mut Challenger<L::Field, C::Hasher>,
         timing: &mut TimingTree,
-    ) -> (AirCommitment<L::Field, C, D>, AirCommitment<L::Field, C, D>) {
+    ) -> Result<(AirCommitment<L::Field, C, D>, AirCommitment<L::Field, C, D>)> {
         // Absorve public values into the challenger.
-        challenger.observe_elements(public_values);
+        let public_challenges = self.stark.observe_public_inputs(challenger, public_values);
 
         // Generate execution traces.
         let (main_writer, lookup_writer) =
@@ -204,7 +204,10 @@ where
         challenger.observe_cap(&lookup_execution_commitment.merkle_tree.cap);
 
         // Get random AIR challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
         // Save challenges to both writers.
         main_writer
             .challenges
@@ -220,6 +223,13 @@ where
         // Generate extended traces.
         self.generate_extended_traces(&main_writer, &lookup_writer);
 
+        // Check the constraints on the full traces to report the failing instruction, if any.
+        #[cfg(feature = "debug-constraints")]
+        {
+            self.stark.air.check_constraints(&main_writer)?;
+            self.lookup_stark.air.check_constraints(&lookup_writer)?;
+        }
+
         let InnerWriterData {
             trace: main_trace,
             public: main_public,
@@ -272,7 +282,7 @@ where
         challenger.observe_cap(&lookup_extended_commitment.merkle_tree.cap);
 
         // Return the air commitments.
-        (
+        Ok((
             AirCommitment {
                 trace_commitments: vec![main_execution_commitment, main_extended_commitment],
                 public_inputs: main_public,
@@ -285,7 +295,7 @@ where
                 global_values: lookup_global,
                 challenges: global_challenges,
             },
-        )
+        ))
     }
 
     pub fn prove(
@@ -301,7 +311,7 @@ where
         let (main_air_commitment, lookup_air_commitment) = timed!(
             timing,
             "Generate stark trace",
-            self.generate_trace(execution_trace, public_values, &mut challenger, timing)
+            self.generate_trace(execution_trace, public_values, &mut challenger, timing)?
         );
 
         // Generate individual stark proofs.
@@ -346,14 +356,19 @@ where
         let mut challenger = Challenger::<L::Field, C::Hasher>::new();
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges = self
+            .stark
+            .observe_public_inputs(&mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.main_proof.trace_caps[0]);
         challenger.observe_cap(&proof.lookup_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
@@ -446,14 +461,21 @@ where
         let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges =
+            self.stark
+                .observe_public_inputs_target(builder, &mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.main_proof.trace_caps[0]);
         challenger.observe_cap(&proof.lookup_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(builder, self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges = challenger.get_n_challenges(
+            builder,
+            self.stark.air.num_challenges - data.num_challenges(),
+        );
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
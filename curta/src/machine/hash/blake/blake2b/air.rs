@@ -2,7 +2,7 @@ use log::debug;
 use plonky2::util::log2_ceil;
 
 use super::data::{BLAKE2BConstNums, BLAKE2BConsts, BLAKE2BData};
-use super::{BLAKE2BAir, COMPRESS_LENGTH, IV, STATE_SIZE};
+use super::{BLAKE2BAir, BLAKE2BParameters, COMPRESS_LENGTH, STATE_SIZE};
 use crate::chip::memory::instruction::MemorySliceIndex;
 use crate::chip::memory::pointer::slice::Slice;
 use crate::chip::memory::time::Time;
@@ -53,6 +53,14 @@ impl<L: AirParameters> BLAKE2BAir<L>
         )
     }
 
+    /// Hashes the messages given by `padded_chunks`.
+    ///
+    /// This is the message level layer on top of the compression function: the state of the first
+    /// compress of each message is set to the IV, the state of every other compress is the output
+    /// of the previous one, and only the words of the digest compresses covering `digest_length`
+    /// bytes are returned. The `salt` and `personal` fields of the parameter block default to
+    /// zero.
+    #[allow(clippy::too_many_arguments)]
     pub fn blake2b(
         builder: &mut BytesBuilder<L>,
         padded_chunks: &[ArrayRegister<U64Register>],
@@ -61,6 +69,145 @@ impl<L: AirParameters> BLAKE2BAir<L>
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: &ArrayRegister<ElementRegister>,
         num_messages: &ElementRegister,
+        digest_length: usize,
+        salt: Option<[u8; 16]>,
+        personal: Option<[u8; 16]>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        let parameters = BLAKE2BParameters {
+            salt: salt.unwrap_or_default(),
+            personal: personal.unwrap_or_default(),
+            ..BLAKE2BParameters::new(digest_length, 0)
+        };
+        Self::blake2b_compressions(
+            builder,
+            padded_chunks,
+            t_values,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            num_messages,
+            None,
+            &parameters,
+        )
+    }
+
+    /// Computes the keyed BLAKE2b hash of the messages given by `padded_chunks`.
+    ///
+    /// `key` is the key block, i.e. the key of `key_length` bytes padded with zeros to sixteen
+    /// words. The key block must be the first chunk of every message and is accounted for in the
+    /// `t_values`, as in the keyed mode of the specification.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blake2b_keyed(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        t_values: &ArrayRegister<U64Register>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+        key: &ArrayRegister<U64Register>,
+        key_length: usize,
+        digest_length: usize,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        assert!(
+            0 < key_length && key_length <= 64,
+            "BLAKE2b keys must be between 1 and 64 bytes"
+        );
+        assert_eq!(key.len(), MSG_ARRAY_SIZE);
+
+        // The bytes of the key block after the key are zero.
+        for (i, byte) in key.iter().flat_map(|word| word.to_le_bytes().iter()).enumerate() {
+            if i >= key_length {
+                builder.assert_expression_zero(byte.expr());
+            }
+        }
+
+        // The first chunk of a message is either the first chunk of the trace or the one following
+        // an end bit.
+        for (i, chunk) in padded_chunks.iter().enumerate() {
+            for (word, key_word) in chunk.iter().zip(key.iter()) {
+                match i {
+                    0 => builder.assert_equal(&word, &key_word),
+                    _ => {
+                        let is_first = end_bits.get(i - 1);
+                        builder.assert_expressions_equal(
+                            is_first.expr() * word.expr(),
+                            is_first.expr() * key_word.expr(),
+                        );
+                    }
+                }
+            }
+        }
+
+        Self::blake2b_compressions(
+            builder,
+            padded_chunks,
+            t_values,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            num_messages,
+            None,
+            &BLAKE2BParameters::new(digest_length, key_length),
+        )
+    }
+
+    /// Applies the compression function to each tuple `(h_in[i], m[i], t[i], last[i])`.
+    ///
+    /// Every compress is independent, there is no message framing. The returned registers are
+    /// public and contain the full output state of each compress.
+    pub fn blake2b_compress_batch(
+        builder: &mut BytesBuilder<L>,
+        h_in: &[ArrayRegister<U64Register>],
+        m: &[ArrayRegister<U64Register>],
+        t: &ArrayRegister<U64Register>,
+        last: &ArrayRegister<BitRegister>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        let num_compresses = m.len();
+        assert_eq!(h_in.len(), num_compresses);
+        assert_eq!(t.len(), num_compresses);
+        assert_eq!(last.len(), num_compresses);
+
+        // Every compress is treated as a message of its own whose initial state is `h_in`. Setting
+        // all the end bits makes sure that no state is carried between compresses.
+        let end_bits = builder.constant_array::<BitRegister>(&vec![L::Field::ONE; num_compresses]);
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &(0..num_compresses)
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let num_messages =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_compresses));
+
+        Self::blake2b_compressions(
+            builder,
+            m,
+            t,
+            &end_bits,
+            last,
+            &digest_indices,
+            &num_messages,
+            Some(h_in),
+            &BLAKE2BParameters::new(8 * STATE_SIZE, 0),
+        )
+    }
+
+    /// The compression function over all the chunks of the trace.
+    ///
+    /// If `initial_states` is `None`, the state at the start of the first compress of a message
+    /// is the IV given by `parameters`. Otherwise, it is given for every compress by
+    /// `initial_states`, in which case all the words of the output state are returned.
+    #[allow(clippy::too_many_arguments)]
+    fn blake2b_compressions(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        t_values: &ArrayRegister<U64Register>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+        initial_states: Option<&[ArrayRegister<U64Register>]>,
+        parameters: &BLAKE2BParameters,
     ) -> Vec<ArrayRegister<U64Register>> {
         let data = Self::blake2b_data(
             builder,
@@ -70,6 +217,8 @@ impl<L: AirParameters> BLAKE2BAir<L>
             digest_bits,
             digest_indices,
             num_messages,
+            initial_states,
+            parameters,
         );
 
         let state_ptr = builder.uninit_slice();
@@ -77,7 +226,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
 
         // Create the public registers to input the expected digests.
         let hash_state_public = (0..num_digests)
-            .map(|_| builder.alloc_array_public(4))
+            .map(|_| builder.alloc_array_public(data.public.num_digest_words()))
             .collect::<Vec<_>>();
 
         for (i, h_slice) in data
@@ -123,6 +272,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
     #[allow(clippy::too_many_arguments)]
     pub fn blake2b_const(
         builder: &mut BytesBuilder<L>,
+        num_rows: usize,
         num_rows_element: &ElementRegister,
         num_messages_element: &ElementRegister,
         num_real_compresses: usize,
@@ -131,6 +281,8 @@ impl<L: AirParameters> BLAKE2BAir<L>
         num_total_mix_iterations: usize,
         num_mix_iterations_last_compress: usize,
         const_nums: &BLAKE2BConstNums,
+        initial_states: Option<&[ArrayRegister<U64Register>]>,
+        parameters: &BLAKE2BParameters,
     ) -> BLAKE2BConsts<L> {
         assert!(DUMMY_INDEX < L::Field::order());
         let dummy_index: ElementRegister =
@@ -146,24 +298,55 @@ impl<L: AirParameters> BLAKE2BAir<L>
         let first_compress_h_read_ts: ElementRegister =
             builder.constant(&L::Field::from_canonical_u64(FIRST_COMPRESS_H_READ_TS));
 
-        let iv_values = builder.constant_array::<U64Register>(&IV.map(u64_to_le_field_bytes));
-        let iv: Slice<crate::chip::uint::register::ByteArrayRegister<8>> = builder.uninit_slice();
-        for (i, value) in iv_values.iter().enumerate() {
-            builder.store(
-                &iv.get(i),
-                value,
-                &Time::zero(),
-                Some(*num_messages_element),
-                Some("iv".to_string()),
-                Some(MemorySliceIndex::Index(i)),
-            );
-        }
-        // The dummy iv value is read twice at the rows other than the first 4 rows of the each messages's
-        // first compress round.
-        let num_dummy_iv_reads = builder.public_expression(
-            (num_rows_element.expr() - (num_messages_element.expr() * const_nums.const_4.expr()))
-                * const_nums.const_2.expr(),
+        let iv_values = builder.constant_array::<U64Register>(
+            &parameters.initial_state().map(u64_to_le_field_bytes),
         );
+        let iv: Slice<crate::chip::uint::register::ByteArrayRegister<8>> = builder.uninit_slice();
+        let num_dummy_iv_reads = match initial_states {
+            None => {
+                for (i, value) in iv_values.iter().enumerate() {
+                    builder.store(
+                        &iv.get(i),
+                        value,
+                        &Time::zero(),
+                        Some(*num_messages_element),
+                        Some("iv".to_string()),
+                        Some(MemorySliceIndex::Index(i)),
+                    );
+                }
+                // The dummy iv value is read twice at the rows other than the first 4 rows of the
+                // each messages's first compress round.
+                builder.public_expression(
+                    (num_rows_element.expr()
+                        - (num_messages_element.expr() * const_nums.const_4.expr()))
+                        * const_nums.const_2.expr(),
+                )
+            }
+            Some(states) => {
+                // The initial state of each compress is stored at `compress_id * STATE_SIZE`. It is
+                // read once in the first 4 rows of the compress and once in its last row.
+                for (compress_id, state) in states.iter().enumerate() {
+                    assert_eq!(state.len(), STATE_SIZE);
+                    for (j, word) in state.iter().enumerate() {
+                        let index = compress_id * STATE_SIZE + j;
+                        builder.store(
+                            &iv.get(index),
+                            word,
+                            &Time::zero(),
+                            Some(const_nums.const_2),
+                            Some("iv".to_string()),
+                            Some(MemorySliceIndex::Index(index)),
+                        );
+                    }
+                }
+                // The dummy iv value is read twice at the rows other than the first 4 rows of each
+                // compress, and 8 times at the rows other than the last row of each compress.
+                let num_compresses = states.len();
+                builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+                    (num_rows - num_compresses * 4) * 2 + (num_rows - num_compresses) * STATE_SIZE,
+                ))
+            }
+        };
 
         builder.store(
             &iv.get_at(dummy_index),
@@ -283,6 +466,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
         num_dummy_compresses: usize,
         length_last_compress: usize,
         length_last_compress_element: &ElementRegister,
+        digest_every_compress: bool,
     ) -> BLAKE2BTraceData {
         let (cycle_3_end_bit, cycle_4_end_bit, cycle_8_end_bit, cycle_96_end_bit) =
             Self::cycles_end_bits(builder);
@@ -511,7 +695,11 @@ impl<L: AirParameters> BLAKE2BAir<L>
             Some("digest_bit".to_string()),
             Some(MemorySliceIndex::IndexElement(compress_id)),
         );
-        let is_digest_row = builder.expression(cycle_96_end_bit.expr() * at_digest_compress.expr());
+        let is_digest_row = if digest_every_compress {
+            builder.expression(cycle_96_end_bit.expr() * at_dummy_compress.not_expr())
+        } else {
+            builder.expression(cycle_96_end_bit.expr() * at_digest_compress.expr())
+        };
         builder.watch(&compress_id, "compress id");
         builder.watch(&at_first_compress, "at first compress");
         builder.watch(&at_digest_compress, "at digest compress");
@@ -681,6 +869,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn blake2b_data(
         builder: &mut BytesBuilder<L>,
         padded_chunks: &[ArrayRegister<U64Register>],
@@ -689,8 +878,14 @@ impl<L: AirParameters> BLAKE2BAir<L>
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: &ArrayRegister<ElementRegister>,
         num_messages_element: &ElementRegister,
+        initial_states: Option<&[ArrayRegister<U64Register>]>,
+        parameters: &BLAKE2BParameters,
     ) -> BLAKE2BData<L> {
         assert_eq!(padded_chunks.len(), end_bits.len());
+        assert!(
+            0 < parameters.digest_length && parameters.digest_length <= 8 * STATE_SIZE,
+            "BLAKE2b digests must be between 1 and 64 bytes"
+        );
 
         let num_real_compresses = padded_chunks.len();
         debug!("num_real_compresses: {}", num_real_compresses);
@@ -722,11 +917,14 @@ impl<L: AirParameters> BLAKE2BAir<L>
             t_values: *t_values,
             end_bits: *end_bits,
             digest_indices: *digest_indices,
+            initial_states: initial_states.map(|states| states.to_vec()),
+            digest_length: parameters.digest_length,
         };
 
         // create the consts data
         let consts = Self::blake2b_const(
             builder,
+            1 << degree_log,
             &num_rows_element,
             num_messages_element,
             num_real_compresses,
@@ -735,6 +933,8 @@ impl<L: AirParameters> BLAKE2BAir<L>
             num_total_mixes,
             num_mixes_last_compress,
             &const_nums,
+            initial_states,
+            parameters,
         );
 
         // create the trace data
@@ -748,6 +948,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
             num_dummy_compresses,
             length_last_compress,
             &length_last_compress_element,
+            initial_states.is_some(),
         );
 
         // create the memory data
@@ -846,8 +1047,20 @@ impl<L: AirParameters> BLAKE2BAir<L>
                 * data.trace.at_first_compress.expr()
                 * data.trace.at_dummy_compress.not_expr()),
         );
-        let iv_idx_1 = builder.select(read_dummy_iv_idx, &data.consts.dummy_index, &init_idx_1);
-        let iv_idx_2 = builder.select(read_dummy_iv_idx, &data.consts.dummy_index, &init_idx_2);
+        let (iv_base_idx_1, iv_base_idx_2) = if data.public.initial_states.is_some() {
+            // Each compress has its own initial state, stored at `compress_id * STATE_SIZE`.
+            let base_idx_1 = builder.expression(
+                data.trace.compress_id.expr() * data.const_nums.const_8.expr() + init_idx_1.expr(),
+            );
+            let base_idx_2 = builder.expression(
+                data.trace.compress_id.expr() * data.const_nums.const_8.expr() + init_idx_2.expr(),
+            );
+            (base_idx_1, base_idx_2)
+        } else {
+            (init_idx_1, init_idx_2)
+        };
+        let iv_idx_1 = builder.select(read_dummy_iv_idx, &data.consts.dummy_index, &iv_base_idx_1);
+        let iv_idx_2 = builder.select(read_dummy_iv_idx, &data.consts.dummy_index, &iv_base_idx_2);
 
         let iv_value_1 = builder.load(
             &data.consts.iv.get_at(iv_idx_1),
@@ -1233,8 +1446,34 @@ impl<L: AirParameters> BLAKE2BAir<L>
             &data.consts.dummy_ts,
             &data.const_nums.const_0,
         );
+
+        // If the initial states are given for every compress, read the dummy iv value unless we
+        // are at the last row of a compress that is not a dummy compress.
+        let read_dummy_iv_idx = data.public.initial_states.as_ref().map(|_| {
+            builder.expression::<BitRegister>(
+                data.const_nums.const_1.expr()
+                    - (data.trace.is_compress_final_row.expr()
+                        * data.trace.at_dummy_compress.not_expr()),
+            )
+        });
         for i in 0..STATE_SIZE {
             let i_element = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(i));
+            let iv_value = match read_dummy_iv_idx {
+                None => data.consts.iv_values.get(i),
+                Some(read_dummy_iv_idx) => {
+                    let mut iv_idx = builder.expression(
+                        data.trace.compress_id.expr() * data.const_nums.const_8.expr()
+                            + i_element.expr(),
+                    );
+                    iv_idx = builder.select(read_dummy_iv_idx, &data.consts.dummy_index, &iv_idx);
+                    builder.load(
+                        &data.consts.iv.get_at(iv_idx),
+                        &Time::zero(),
+                        Some("iv".to_string()),
+                        Some(MemorySliceIndex::IndexElement(iv_idx)),
+                    )
+                }
+            };
             let mut h_idx = builder.expression(
                 data.trace.previous_compress_id.expr() * data.const_nums.const_8.expr()
                     + i_element.expr(),
@@ -1248,11 +1487,7 @@ impl<L: AirParameters> BLAKE2BAir<L>
             );
 
             // If we are at the first compress of a message, then use the iv values instead of the h values.
-            h_value = builder.select(
-                data.trace.at_first_compress,
-                &data.consts.iv_values.get(i),
-                &h_value,
-            );
+            h_value = builder.select(data.trace.at_first_compress, &iv_value, &h_value);
             builder.set_to_expression(&h_workspace_1.get(i), h_value.expr());
         }
 
@@ -1337,8 +1572,8 @@ impl<L: AirParameters> BLAKE2BAir<L>
             );
 
             // If this is the digest row, then also store the calculated digest.
-            // Only need to do so for the first 4 entries of h.
-            if i < 4 {
+            // Only need to do so for the first `num_digest_words` entries of h.
+            if i < data.public.num_digest_words() {
                 builder.store(
                     &state_ptr.get(i),
                     xor,
@@ -11,6 +11,12 @@ impl<L: AirParameters> BytesBuilder<L>
     where
         L::Instruction: UintInstructions,
 {
+    /// Proves the BLAKE2b hash of the messages given by `padded_chunks`.
+    ///
+    /// The digests are `digest_length` bytes long, at most 64, and are returned as the words of
+    /// the state that cover them. `salt` and `personal` set the corresponding fields of the
+    /// parameter block, which are zero by default.
+    #[allow(clippy::too_many_arguments)]
     pub fn blake2b(
         &mut self,
         padded_chunks: &[ArrayRegister<U64Register>],
@@ -19,6 +25,9 @@ impl<L: AirParameters> BytesBuilder<L>
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: &ArrayRegister<ElementRegister>,
         num_messages: &ElementRegister,
+        digest_length: usize,
+        salt: Option<[u8; 16]>,
+        personal: Option<[u8; 16]>,
     ) -> Vec<ArrayRegister<U64Register>> {
         BLAKE2BAir::blake2b(
             self,
@@ -28,8 +37,57 @@ impl<L: AirParameters> BytesBuilder<L>
             digest_bits,
             digest_indices,
             num_messages,
+            digest_length,
+            salt,
+            personal,
         )
     }
+
+    /// Proves the keyed BLAKE2b hash of the messages given by `padded_chunks`.
+    ///
+    /// `key` holds the key of `key_length` bytes padded with zeros to a full block. That block
+    /// must be the first chunk of every message, with the `t_values` counting its 128 bytes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn blake2b_keyed(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        t_values: &ArrayRegister<U64Register>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+        key: &ArrayRegister<U64Register>,
+        key_length: usize,
+        digest_length: usize,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        BLAKE2BAir::blake2b_keyed(
+            self,
+            padded_chunks,
+            t_values,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            num_messages,
+            key,
+            key_length,
+            digest_length,
+        )
+    }
+
+    /// Proves the BLAKE2b compression function on each tuple `(h_in[i], m[i], t[i], last[i])`.
+    ///
+    /// The compresses are batched in a single trace, which must have
+    /// `(96 * m.len()).next_power_of_two()` rows. Returns the output state of each compress as
+    /// public registers.
+    pub fn blake2b_compress(
+        &mut self,
+        h_in: &[ArrayRegister<U64Register>],
+        m: &[ArrayRegister<U64Register>],
+        t: &ArrayRegister<U64Register>,
+        last: &ArrayRegister<BitRegister>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        BLAKE2BAir::blake2b_compress_batch(self, h_in, m, t, last)
+    }
 }
 
 #[cfg(test)]
@@ -45,17 +103,19 @@ pub mod test_utils {
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::timed;
     use plonky2::util::timing::TimingTree;
+    use rand::{thread_rng, Rng};
     use serde::{Deserialize, Serialize};
 
     use super::*;
+    use crate::chip::uint::bytes::register::ByteRegister;
     use crate::chip::uint::operations::instruction::UintInstruction;
-    use crate::chip::uint::util::u64_to_le_field_bytes;
+    use crate::chip::uint::util::{u64_to_le_field_bytes, Endianness};
     use crate::chip::AirParameters;
     use crate::machine::builder::Builder;
     use crate::machine::bytes::builder::BytesBuilder;
     use crate::machine::hash::blake::blake2b::pure::BLAKE2BPure;
     use crate::machine::hash::blake::blake2b::utils::BLAKE2BUtil;
-    use crate::machine::hash::blake::blake2b::IV;
+    use crate::machine::hash::blake::blake2b::{parameter_iv, BLAKE2BParameters, IV};
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
     use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
@@ -174,7 +234,7 @@ pub mod test_utils {
         let digest_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
         // let digest_indices = builder.alloc_array_public(17 * msgs.len());
         let digest_indices = builder.alloc_array_public(msgs.len());
-        let num_messages = builder.alloc_public();
+        let num_messages = builder.alloc_public_bounded(num_rounds as u64);
         let hash_state = builder.blake2b(
             &padded_chunks,
             &t_values,
@@ -182,6 +242,9 @@ pub mod test_utils {
             &digest_bits,
             &digest_indices,
             &num_messages,
+            32,
+            None,
+            None,
         );
 
         let stark = builder.build::<C, 2>(num_rows);
@@ -277,4 +340,669 @@ pub mod test_utils {
 
         timing.print();
     }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE2BCompressTest;
+
+    impl AirParameters for BLAKE2BCompressTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1800;
+        const EXTENDED_COLUMNS: usize = 900;
+    }
+
+    #[test]
+    fn test_blake2b_compress() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_compress", log::Level::Info);
+
+        let num_compresses = 4;
+        let num_rows = (96 * num_compresses).next_power_of_two();
+
+        let mut builder = BytesBuilder::<BLAKE2BCompressTest>::new();
+        let h_in = (0..num_compresses)
+            .map(|_| builder.alloc_array_public::<U64Register>(8))
+            .collect::<Vec<_>>();
+        let m = (0..num_compresses)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let t = builder.alloc_array_public::<U64Register>(num_compresses);
+        let last = builder.alloc_array_public::<BitRegister>(num_compresses);
+        let h_out = builder.blake2b_compress(&h_in, &m, &t, &last);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut rng = thread_rng();
+        for i in 0..num_compresses {
+            let h_value: [u64; 8] = rng.gen();
+            let m_value = (0..128).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            let t_value = rng.gen::<u64>();
+            let last_value = rng.gen::<bool>();
+
+            writer.write_array(&h_in[i], h_value.map(u64_to_le_field_bytes));
+            writer.write_array(
+                &m[i],
+                m_value
+                    .chunks_exact(8)
+                    .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j]))),
+            );
+            writer.write(&t.get(i), &u64_to_le_field_bytes(t_value));
+            writer.write(&last.get(i), &F::from_canonical_u8(last_value as u8));
+
+            let mut state = h_value;
+            let h_out_value = BLAKE2BPure::compress(&m_value, &mut state, t_value, last_value);
+            writer.write_array(&h_out[i], h_out_value.map(u64_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_blake2b_compress_endianness() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_compress_endianness", log::Level::Info);
+
+        let num_rows = (96 * 2usize).next_power_of_two();
+        let mut builder = BytesBuilder::<BLAKE2BCompressTest>::new();
+
+        // The same message, supplied as little-endian and as big-endian bytes.
+        let le_msg = builder.alloc_array_public::<ByteRegister>(128);
+        let be_msg = builder.alloc_array_public::<ByteRegister>(128);
+        let m = [
+            builder.api.u64_words_from_bytes(&le_msg, Endianness::Little),
+            builder.api.u64_words_from_bytes(&be_msg, Endianness::Big),
+        ];
+        let h_in = builder.constant_array::<U64Register>(&IV.map(u64_to_le_field_bytes));
+        let t = builder.constant_array::<U64Register>(&[u64_to_le_field_bytes(128); 2]);
+        let last = builder.constant_array::<BitRegister>(&[F::ONE; 2]);
+        let h_out = builder.blake2b_compress(&[h_in, h_in], &m, &t, &last);
+        for (a, b) in h_out[0].iter().zip(h_out[1].iter()) {
+            builder.assert_equal(&a, &b);
+        }
+
+        let le_digest = builder
+            .api
+            .u64_words_to_bytes(&h_out[0].get_subarray(0..4), Endianness::Little);
+        let be_digest = builder
+            .api
+            .u64_words_to_bytes(&h_out[1].get_subarray(0..4), Endianness::Big);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut rng = thread_rng();
+        let msg = (0..128).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let be_msg_value = msg
+            .chunks_exact(8)
+            .flat_map(|word| word.iter().rev().copied())
+            .collect::<Vec<_>>();
+        writer.write_array(&le_msg, msg.iter().map(|b| F::from_canonical_u8(*b)));
+        writer.write_array(&be_msg, be_msg_value.iter().map(|b| F::from_canonical_u8(*b)));
+
+        let mut state = IV;
+        let h_out_value = BLAKE2BPure::compress(&msg, &mut state, 128, true);
+        for h in h_out.iter() {
+            writer.write_array(h, h_out_value.map(u64_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        let digest = h_out_value[..4]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        let be_digest_value = h_out_value[..4]
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        assert_eq!(writer.read_vec(&le_digest), digest);
+        assert_eq!(writer.read_vec(&be_digest), be_digest_value);
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_blake2b_compress_rfc7693() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_compress_rfc7693", log::Level::Info);
+
+        // The single compression of BLAKE2b-512("abc") from appendix A of RFC 7693, starting from
+        // the IV mixed with the parameter block of an unkeyed 64-byte digest.
+        let mut h_value = IV;
+        h_value[0] ^= 0x01010040;
+        let mut m_value = [0u8; 128];
+        m_value[..3].copy_from_slice(b"abc");
+        let expected = hex::decode(
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        )
+        .unwrap();
+        let h_out_value: [u64; 8] = core::array::from_fn(|i| {
+            u64::from_le_bytes(expected[8 * i..8 * i + 8].try_into().unwrap())
+        });
+
+        let mut state = h_value;
+        assert_eq!(
+            BLAKE2BPure::compress(&m_value, &mut state, 3, true),
+            h_out_value
+        );
+
+        let num_rows = 128;
+        let mut builder = BytesBuilder::<BLAKE2BCompressTest>::new();
+        let h_in = builder.constant_array::<U64Register>(&h_value.map(u64_to_le_field_bytes));
+        let m = builder.constant_array::<U64Register>(
+            &m_value
+                .chunks_exact(8)
+                .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j])))
+                .collect::<Vec<_>>(),
+        );
+        let t = builder.constant_array::<U64Register>(&[u64_to_le_field_bytes(3)]);
+        let last = builder.constant_array::<BitRegister>(&[F::ONE]);
+        let h_out = builder.blake2b_compress(&[h_in], &[m], &t, &last);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        writer.write_array(&h_out[0], h_out_value.map(u64_to_le_field_bytes));
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE2BSegmentTest;
+
+    impl AirParameters for BLAKE2BSegmentTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 2600;
+        const EXTENDED_COLUMNS: usize = 1400;
+    }
+
+    #[test]
+    fn test_blake2b_compress_segments() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_compress_segments", log::Level::Info);
+
+        let num_compresses = 2;
+        let blake_rows = (96 * num_compresses).next_power_of_two();
+        let u64_rows = 256;
+        let num_rows = blake_rows + u64_rows;
+
+        let mut builder = BytesBuilder::<BLAKE2BSegmentTest>::new();
+
+        builder.begin_segment("blake2b", blake_rows);
+        let h_in = (0..num_compresses)
+            .map(|_| builder.alloc_array_public::<U64Register>(8))
+            .collect::<Vec<_>>();
+        let m = (0..num_compresses)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let t = builder.alloc_array_public::<U64Register>(num_compresses);
+        let last = builder.alloc_array_public::<BitRegister>(num_compresses);
+        let h_out = builder.blake2b_compress(&h_in, &m, &t, &last);
+        builder.end_segment();
+
+        builder.begin_segment("u64", u64_rows);
+        let a = builder.alloc::<U64Register>();
+        let b = builder.alloc::<U64Register>();
+        let sum = builder.alloc::<U64Register>();
+        let xor = builder.alloc::<U64Register>();
+        let sum_result = builder.add(a, b);
+        let xor_result = builder.xor(a, b);
+        builder.assert_equal(&sum_result, &sum);
+        builder.assert_equal(&xor_result, &xor);
+        builder.end_segment();
+
+        let stark = builder.build::<C, 2>(num_rows);
+        let segment_rows = stark
+            .air_data
+            .segments
+            .iter()
+            .map(|segment| segment.rows.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(segment_rows, vec![0..blake_rows, blake_rows..num_rows]);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut rng = thread_rng();
+        for i in 0..num_compresses {
+            let h_value: [u64; 8] = rng.gen();
+            let m_value = (0..128).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            let t_value = rng.gen::<u64>();
+            let last_value = rng.gen::<bool>();
+
+            writer.write_array(&h_in[i], h_value.map(u64_to_le_field_bytes));
+            writer.write_array(
+                &m[i],
+                m_value
+                    .chunks_exact(8)
+                    .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j]))),
+            );
+            writer.write(&t.get(i), &u64_to_le_field_bytes(t_value));
+            writer.write(&last.get(i), &F::from_canonical_u8(last_value as u8));
+
+            let mut state = h_value;
+            let h_out_value = BLAKE2BPure::compress(&m_value, &mut state, t_value, last_value);
+            writer.write_array(&h_out[i], h_out_value.map(u64_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                // The u64 registers are only written on the rows of their own segment.
+                if i >= blake_rows {
+                    let a_value = rng.gen::<u64>();
+                    let b_value = rng.gen::<u64>();
+                    writer.write(&a, &u64_to_le_field_bytes(a_value));
+                    writer.write(&b, &u64_to_le_field_bytes(b_value));
+                    writer.write(&sum, &u64_to_le_field_bytes(a_value.wrapping_add(b_value)));
+                    writer.write(&xor, &u64_to_le_field_bytes(a_value ^ b_value));
+                }
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_blake2b_keyed() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_keyed", log::Level::Info);
+
+        let key = b"key";
+        let msgs = [b"abc".to_vec(), b"".to_vec()];
+        let expected_digests = [
+            "0330531d097355a3f72e80d55c1245ccf79f1704431c6e3887938320442c23c0",
+            "e65edfce5a36261cd824cb0f0da736b1109dcf20d2b831d598f337bb3552a3e4",
+        ];
+
+        // Every message starts with the key block, an empty message is only the key block.
+        let key_block = BLAKE2BUtil::pad(key, 1);
+        let mut padded_chunks_values = Vec::new();
+        let mut t_values_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            padded_chunks_values.push(key_block.clone());
+            t_values_values.push(128u64);
+            if !msg.is_empty() {
+                padded_chunks_values.push(BLAKE2BUtil::pad(msg, 1));
+                t_values_values.push(128 + msg.len() as u64);
+            }
+            end_bits_values.resize(padded_chunks_values.len() - 1, false);
+            end_bits_values.push(true);
+            digest_indices_values.push(padded_chunks_values.len() - 1);
+        }
+        let num_rounds = padded_chunks_values.len();
+        let num_rows = (96 * num_rounds).next_power_of_two();
+
+        let mut builder = BytesBuilder::<BLAKE2BTest>::new();
+        let padded_chunks = (0..num_rounds)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let t_values = builder.alloc_array_public::<U64Register>(num_rounds);
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(msgs.len());
+        let num_messages = builder.alloc_public_bounded(num_rounds as u64);
+        let key_register = builder.alloc_array_public::<U64Register>(16);
+        let digests = builder.blake2b_keyed(
+            &padded_chunks,
+            &t_values,
+            &end_bits,
+            &end_bits,
+            &digest_indices,
+            &num_messages,
+            &key_register,
+            key.len(),
+            32,
+        );
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let to_words = |block: &[u8]| {
+            block
+                .chunks_exact(8)
+                .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j])))
+                .collect::<Vec<_>>()
+        };
+        writer.write(&num_messages, &F::from_canonical_usize(msgs.len()));
+        writer.write_array(&key_register, to_words(&key_block));
+
+        let mut digests_iter = digests.iter();
+        let mut state = parameter_iv(key.len(), 32);
+        for i in 0..num_rounds {
+            writer.write_array(&padded_chunks[i], to_words(&padded_chunks_values[i]));
+            writer.write(&t_values.get(i), &u64_to_le_field_bytes(t_values_values[i]));
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+
+            BLAKE2BPure::compress(
+                &padded_chunks_values[i],
+                &mut state,
+                t_values_values[i],
+                end_bits_values[i],
+            );
+            if end_bits_values[i] {
+                writer.write_array(
+                    digests_iter.next().unwrap(),
+                    state[0..4].iter().map(|x| u64_to_le_field_bytes(*x)),
+                );
+                state = parameter_iv(key.len(), 32);
+            }
+        }
+        for (i, digest_index) in digest_indices_values.iter().enumerate() {
+            writer.write(
+                &digest_indices.get(i),
+                &F::from_canonical_usize(*digest_index),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, expected) in digests.iter().zip_eq(expected_digests) {
+            let digest_bytes = writer
+                .read_array::<_, 4>(digest)
+                .iter()
+                .flatten()
+                .map(|x| F::as_canonical_u64(x) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(digest_bytes, hex::decode(expected).unwrap());
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    fn test_blake2b_parameters(msgs: &[Vec<u8>], parameters: BLAKE2BParameters) {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_parameters", log::Level::Info);
+
+        let mut padded_chunks_values = Vec::new();
+        let mut t_values_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            let num_chunks = msg.len().div_ceil(128).max(1);
+            let padded_msg = BLAKE2BUtil::pad(msg, num_chunks as u64);
+            for (i, chunk) in padded_msg.chunks_exact(128).enumerate() {
+                padded_chunks_values.push(chunk.to_vec());
+                t_values_values.push(if i == num_chunks - 1 {
+                    msg.len() as u64
+                } else {
+                    128 * (i as u64 + 1)
+                });
+                end_bits_values.push(i == num_chunks - 1);
+            }
+            digest_indices_values.push(padded_chunks_values.len() - 1);
+        }
+        let num_rounds = padded_chunks_values.len();
+        let num_rows = (96 * num_rounds).next_power_of_two();
+
+        let mut builder = BytesBuilder::<BLAKE2BTest>::new();
+        let padded_chunks = (0..num_rounds)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let t_values = builder.alloc_array_public::<U64Register>(num_rounds);
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(msgs.len());
+        let num_messages = builder.alloc_public_bounded(num_rounds as u64);
+        let digests = builder.blake2b(
+            &padded_chunks,
+            &t_values,
+            &end_bits,
+            &end_bits,
+            &digest_indices,
+            &num_messages,
+            parameters.digest_length,
+            Some(parameters.salt),
+            Some(parameters.personal),
+        );
+        let num_digest_words = parameters.digest_length.div_ceil(8);
+        assert!(digests.iter().all(|digest| digest.len() == num_digest_words));
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        writer.write(&num_messages, &F::from_canonical_usize(msgs.len()));
+        let mut digests_iter = digests.iter();
+        let mut state = parameters.initial_state();
+        for i in 0..num_rounds {
+            writer.write_array(
+                &padded_chunks[i],
+                padded_chunks_values[i]
+                    .chunks_exact(8)
+                    .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j]))),
+            );
+            writer.write(&t_values.get(i), &u64_to_le_field_bytes(t_values_values[i]));
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+
+            BLAKE2BPure::compress(
+                &padded_chunks_values[i],
+                &mut state,
+                t_values_values[i],
+                end_bits_values[i],
+            );
+            if end_bits_values[i] {
+                writer.write_array(
+                    digests_iter.next().unwrap(),
+                    state[..num_digest_words]
+                        .iter()
+                        .map(|x| u64_to_le_field_bytes(*x)),
+                );
+                state = parameters.initial_state();
+            }
+        }
+        for (i, digest_index) in digest_indices_values.iter().enumerate() {
+            writer.write(
+                &digest_indices.get(i),
+                &F::from_canonical_usize(*digest_index),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, msg) in digests.iter().zip_eq(msgs.iter()) {
+            let digest_bytes = digest
+                .iter()
+                .flat_map(|word| writer.read(&word))
+                .take(parameters.digest_length)
+                .map(|x| F::as_canonical_u64(&x) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(digest_bytes, BLAKE2BPure::hash(msg, &[], &parameters));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_blake2b_digest_length() {
+        let msgs = [b"abc".to_vec(), vec![b'a'; 200]];
+        test_blake2b_parameters(&msgs, BLAKE2BParameters::new(64, 0));
+        test_blake2b_parameters(&msgs, BLAKE2BParameters::new(20, 0));
+    }
+
+    #[test]
+    fn test_blake2b_personalized() {
+        let msgs = [b"abc".to_vec(), b"".to_vec()];
+        let mut personal = [0u8; 16];
+        personal.copy_from_slice(b"substrate-ext!!!");
+        let parameters = BLAKE2BParameters {
+            salt: core::array::from_fn(|i| i as u8),
+            personal,
+            ..BLAKE2BParameters::new(32, 0)
+        };
+        test_blake2b_parameters(&msgs, parameters);
+    }
 }
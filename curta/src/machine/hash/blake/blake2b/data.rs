@@ -1,4 +1,4 @@
-use super::{MIX_LENGTH, MSG_ARRAY_SIZE, NUM_MIX_ROUNDS};
+use super::{MIX_LENGTH, MSG_ARRAY_SIZE, NUM_MIX_ROUNDS, STATE_SIZE};
 use crate::chip::memory::instruction::MemorySliceIndex;
 use crate::chip::memory::pointer::slice::Slice;
 use crate::chip::memory::time::Time;
@@ -24,6 +24,21 @@ pub struct BLAKE2BPublicData {
     pub t_values: ArrayRegister<U64Register>,
     pub end_bits: ArrayRegister<BitRegister>,
     pub digest_indices: ArrayRegister<ElementRegister>,
+    /// The initial state of every compress, if the compresses are not chained into messages.
+    pub initial_states: Option<Vec<ArrayRegister<U64Register>>>,
+    /// The length of the digests in bytes.
+    pub digest_length: usize,
+}
+
+impl BLAKE2BPublicData {
+    /// The number of words of the state that are output at each digest.
+    pub fn num_digest_words(&self) -> usize {
+        if self.initial_states.is_some() {
+            STATE_SIZE
+        } else {
+            self.digest_length.div_ceil(8)
+        }
+    }
 }
 
 pub struct BLAKE2BTraceData {
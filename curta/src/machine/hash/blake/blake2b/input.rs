@@ -0,0 +1,220 @@
+use super::padding::BLAKE2BMessage;
+use super::pure::BLAKE2BPure;
+use super::utils::BLAKE2BUtil;
+use super::{BLAKE2BParameters, MSG_ARRAY_SIZE};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The public registers of the inputs of `BytesBuilder::blake2b`.
+///
+/// The values of all the registers are written from the raw messages by `BLAKE2BInput::write`.
+#[derive(Debug, Clone)]
+pub struct BLAKE2BInput {
+    pub padded_chunks: Vec<ArrayRegister<U64Register>>,
+    pub t_values: ArrayRegister<U64Register>,
+    pub end_bits: ArrayRegister<BitRegister>,
+    pub digest_bits: ArrayRegister<BitRegister>,
+    pub digest_indices: ArrayRegister<ElementRegister>,
+    pub num_messages: ElementRegister,
+    /// The number of chunks of every message.
+    max_chunks: Vec<usize>,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Allocates the inputs of `blake2b` for messages of at most `max_chunks[i]` chunks.
+    pub fn alloc_blake2b_input(&mut self, max_chunks: &[usize]) -> BLAKE2BInput {
+        assert!(
+            max_chunks.iter().all(|n| *n > 0),
+            "A message has at least one chunk"
+        );
+        let num_chunks = max_chunks.iter().sum::<usize>();
+        BLAKE2BInput {
+            padded_chunks: (0..num_chunks)
+                .map(|_| self.alloc_array_public::<U64Register>(MSG_ARRAY_SIZE))
+                .collect(),
+            t_values: self.alloc_array_public::<U64Register>(num_chunks),
+            end_bits: self.alloc_array_public::<BitRegister>(num_chunks),
+            digest_bits: self.alloc_array_public::<BitRegister>(num_chunks),
+            digest_indices: self.alloc_array_public::<ElementRegister>(max_chunks.len()),
+            num_messages: self.alloc_public_bounded(num_chunks as u64),
+            max_chunks: max_chunks.to_vec(),
+        }
+    }
+}
+
+impl BLAKE2BInput {
+    /// Writes the padded chunks, the `t` values, the end and digest bits, the digest indices and
+    /// the number of messages of `msgs`.
+    ///
+    /// Each message is padded with zeros to its number of chunks. The digest is taken at the
+    /// chunk holding the last byte of the message and the message ends at its last chunk.
+    pub fn write<F: Field>(&self, msgs: &[&[u8]], writer: &mut impl AirWriter<Field = F>) {
+        assert_eq!(msgs.len(), self.max_chunks.len());
+        writer.write(&self.num_messages, &F::from_canonical_usize(msgs.len()));
+
+        let mut index = 0;
+        for (i, (msg, num_chunks)) in msgs.iter().zip(self.max_chunks.iter()).enumerate() {
+            let digest_chunk = BLAKE2BMessage::digest_chunk(msg.len());
+            let padded_msg = BLAKE2BUtil::pad(msg, *num_chunks as u64);
+            for (k, chunk) in padded_msg.chunks_exact(8 * MSG_ARRAY_SIZE).enumerate() {
+                writer.write_array(
+                    &self.padded_chunks[index],
+                    chunk.chunks_exact(8).map(|word| {
+                        core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j]))
+                    }),
+                );
+                writer.write(
+                    &self.t_values.get(index),
+                    &u64_to_le_field_bytes(Self::t_value(msg, k)),
+                );
+                writer.write(
+                    &self.end_bits.get(index),
+                    &F::from_canonical_u8((k == num_chunks - 1) as u8),
+                );
+                writer.write(
+                    &self.digest_bits.get(index),
+                    &F::from_canonical_u8((k == digest_chunk) as u8),
+                );
+                if k == digest_chunk {
+                    writer.write(&self.digest_indices.get(i), &F::from_canonical_usize(index));
+                }
+                index += 1;
+            }
+        }
+    }
+
+    /// Writes the `digests` returned by `blake2b` for `msgs` and the given parameters.
+    pub fn write_digests<F: Field>(
+        &self,
+        msgs: &[&[u8]],
+        digests: &[ArrayRegister<U64Register>],
+        parameters: &BLAKE2BParameters,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        assert_eq!(msgs.len(), self.max_chunks.len());
+        assert_eq!(digests.len(), msgs.len());
+        for ((msg, num_chunks), digest) in msgs.iter().zip(self.max_chunks.iter()).zip(digests) {
+            let digest_chunk = BLAKE2BMessage::digest_chunk(msg.len());
+            let padded_msg = BLAKE2BUtil::pad(msg, *num_chunks as u64);
+            let mut state = parameters.initial_state();
+            for (k, chunk) in padded_msg
+                .chunks_exact(8 * MSG_ARRAY_SIZE)
+                .enumerate()
+                .take(digest_chunk + 1)
+            {
+                BLAKE2BPure::compress(chunk, &mut state, Self::t_value(msg, k), k == digest_chunk);
+            }
+            writer.write_array(
+                digest,
+                state[..digest.len()]
+                    .iter()
+                    .map(|x| u64_to_le_field_bytes(*x)),
+            );
+        }
+    }
+
+    /// The number of bytes of `msg` compressed up to the end of the chunk `k`.
+    fn t_value(msg: &[u8], k: usize) -> u64 {
+        if k == BLAKE2BMessage::digest_chunk(msg.len()) {
+            msg.len() as u64
+        } else {
+            (8 * MSG_ARRAY_SIZE * (k + 1)) as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE2BInputTest;
+
+    impl AirParameters for BLAKE2BInputTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1527;
+        const EXTENDED_COLUMNS: usize = 708;
+    }
+
+    #[test]
+    fn test_blake2b_input() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_input", log::Level::Info);
+
+        let long_msg = vec![0xab; 200];
+        let msgs: [&[u8]; 3] = [b"abc", &long_msg, b""];
+        let max_chunks = [2, 2, 1];
+        let parameters = BLAKE2BParameters::new(32, 0);
+
+        let mut builder = BytesBuilder::<BLAKE2BInputTest>::new();
+        let input = builder.alloc_blake2b_input(&max_chunks);
+        let digests = builder.blake2b(
+            &input.padded_chunks,
+            &input.t_values,
+            &input.end_bits,
+            &input.digest_bits,
+            &input.digest_indices,
+            &input.num_messages,
+            parameters.digest_length,
+            None,
+            None,
+        );
+
+        let num_rows = (96 * input.padded_chunks.len()).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        input.write(&msgs, &mut writer);
+        input.write_digests(&msgs, &digests, &parameters, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, msg) in digests.iter().zip(msgs.iter()) {
+            let digest_bytes = digest
+                .iter()
+                .flat_map(|word| writer.read(&word))
+                .map(|x| F::as_canonical_u64(&x) as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(digest_bytes, BLAKE2BPure::hash(msg, &[], &parameters));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
@@ -1,6 +1,8 @@
 pub mod air;
 pub mod builder;
 pub mod data;
+pub mod input;
+pub mod padding;
 pub mod pure;
 pub mod register;
 pub mod utils;
@@ -27,10 +29,10 @@ pub const IV: [u64; STATE_SIZE] = [
     0x5be0cd19137e2179,
 ];
 
-// Note that for this blake2b implementation, we don't support a key input and
-// we assume that the output is 32 bytes
-// So that means the initial hash entry to be
+// Note that `IV` is the initial state of an unkeyed hash with a 32 byte output,
+// so that means the initial hash entry to be
 // 0x6a09e667f3bcc908 xor 0x01010020
+// The initial state for other parameters is given by `parameter_iv`.
 const COMPRESS_IV: [u64; STATE_SIZE] = [
     0x6a09e667f3bcc908,
     0xbb67ae8584caa73b,
@@ -42,6 +44,50 @@ const COMPRESS_IV: [u64; STATE_SIZE] = [
     0x5be0cd19137e2179,
 ];
 
+/// The initial state of a hash with a key of `key_length` bytes and a digest of `digest_length`
+/// bytes, obtained by XORing the parameter block into the first word of `COMPRESS_IV`.
+pub const fn parameter_iv(key_length: usize, digest_length: usize) -> [u64; STATE_SIZE] {
+    let mut iv = COMPRESS_IV;
+    iv[0] ^= 0x01010000 ^ ((key_length as u64) << 8) ^ digest_length as u64;
+    iv
+}
+
+/// The fields of the BLAKE2b parameter block supported by the AIR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BLAKE2BParameters {
+    /// The length of the digest in bytes.
+    pub digest_length: usize,
+    /// The length of the key in bytes, zero for unkeyed hashing.
+    pub key_length: usize,
+    pub salt: [u8; 16],
+    pub personal: [u8; 16],
+}
+
+impl BLAKE2BParameters {
+    pub const fn new(digest_length: usize, key_length: usize) -> Self {
+        Self {
+            digest_length,
+            key_length,
+            salt: [0; 16],
+            personal: [0; 16],
+        }
+    }
+
+    /// The initial state of a hash with these parameters.
+    pub fn initial_state(&self) -> [u64; STATE_SIZE] {
+        let mut iv = parameter_iv(self.key_length, self.digest_length);
+        for (i, word) in self
+            .salt
+            .chunks_exact(8)
+            .chain(self.personal.chunks_exact(8))
+            .enumerate()
+        {
+            iv[4 + i] ^= u64::from_le_bytes(word.try_into().unwrap());
+        }
+        iv
+    }
+}
+
 const V_INDICES: [[u8; 4]; MIX_LENGTH] = [
     [0, 4, 8, 12],
     [1, 5, 9, 13],
@@ -0,0 +1,412 @@
+//! BLAKE2b messages whose padding is constrained by the AIR from their lengths.
+//!
+//! The prover gives the message bytes and a bit for every byte of the chunks marking whether the
+//! byte belongs to the message. The bits are constrained to be ones followed by zeros, as many
+//! ones as the length, so that the bytes past the length must be zero and the `t` values follow
+//! from the length.
+//!
+//! The length is only known at proving time, up to the size of the chunks of the message. The
+//! digest is taken at the chunk holding the last byte of the message, and the chunks after it
+//! are no-op chunks whose compressions do not affect the digest.
+
+use super::pure::BLAKE2BPure;
+use super::utils::BLAKE2BUtil;
+use super::{BLAKE2BAir, BLAKE2BParameters, MSG_ARRAY_SIZE};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The number of bytes of a chunk.
+const CHUNK_BYTES: usize = 8 * MSG_ARRAY_SIZE;
+
+/// The number of low bytes of a `t` value that may be non-zero, so that its bytes determine it
+/// without wrapping around the field.
+const T_VALUE_BYTES: usize = 4;
+
+/// The registers of a message whose padding is constrained from its length.
+#[derive(Debug, Clone)]
+pub struct BLAKE2BMessage {
+    pub chunks: Vec<ArrayRegister<U64Register>>,
+    /// The length of the message in bytes.
+    pub length: ElementRegister,
+    /// A bit for every byte of the chunks, set if the byte belongs to the message.
+    in_message: ArrayRegister<BitRegister>,
+}
+
+/// The registers of a batch of messages hashed by `BytesBuilder::blake2b_padded`.
+#[derive(Debug, Clone)]
+pub struct BLAKE2BPaddingGadget {
+    pub messages: Vec<BLAKE2BMessage>,
+    pub digests: Vec<ArrayRegister<U64Register>>,
+    t_values: Vec<ArrayRegister<U64Register>>,
+    digest_length: usize,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Allocates a message of at most `max_chunks` chunks, whose values are given by the prover.
+    pub fn alloc_blake2b_message(&mut self, max_chunks: usize) -> BLAKE2BMessage {
+        assert!(max_chunks > 0, "A message has at least one chunk");
+        BLAKE2BMessage {
+            chunks: (0..max_chunks)
+                .map(|_| self.alloc_array_public::<U64Register>(MSG_ARRAY_SIZE))
+                .collect(),
+            length: self.alloc_public::<ElementRegister>(),
+            in_message: self.alloc_array_public::<BitRegister>(max_chunks * CHUNK_BYTES),
+        }
+    }
+
+    /// Allocates a message made of the first `length` bytes of `bytes`, which must be public
+    /// registers.
+    ///
+    /// The message takes enough chunks for `bytes.len()` bytes whatever its length.
+    pub fn blake2b_message_from_bytes(
+        &mut self,
+        bytes: &[ByteRegister],
+        length: &ElementRegister,
+    ) -> BLAKE2BMessage {
+        let num_chunks = bytes.len().div_ceil(CHUNK_BYTES).max(1);
+        let message = BLAKE2BMessage {
+            chunks: (0..num_chunks)
+                .map(|_| self.alloc_array_public::<U64Register>(MSG_ARRAY_SIZE))
+                .collect(),
+            length: *length,
+            in_message: self.alloc_array_public::<BitRegister>(num_chunks * CHUNK_BYTES),
+        };
+
+        // The length is at most the number of bytes.
+        if bytes.len() < message.in_message.len() {
+            self.assert_expression_zero(message.in_message.get(bytes.len()).expr());
+        }
+
+        // The chunks hold the bytes of the message, the bytes past the length are zero.
+        let chunk_bytes = message
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .flat_map(|word| word.to_le_bytes().iter());
+        for ((chunk_byte, byte), bit) in chunk_bytes.zip(bytes).zip(message.in_message.iter()) {
+            self.assert_expressions_equal(chunk_byte.expr(), byte.expr() * bit.expr());
+        }
+        message
+    }
+
+    /// Proves the unkeyed BLAKE2b hash of `messages`, whose padding is constrained by the AIR.
+    ///
+    /// The bytes of every message past its length must be zero. Unlike `blake2b`, the `t` values,
+    /// the digest bits and the digest indices are derived from the lengths, so the prover only
+    /// gives the bytes and the lengths of the messages. Each message takes all of its chunks
+    /// whatever its length, so the trace must have `(96 * num_chunks).next_power_of_two()` rows.
+    pub fn blake2b_padded(
+        &mut self,
+        messages: &[BLAKE2BMessage],
+        digest_length: usize,
+    ) -> BLAKE2BPaddingGadget {
+        let num_chunks = messages.iter().map(|m| m.num_chunks()).sum::<usize>();
+        let t_array = self.alloc_array_public::<U64Register>(num_chunks);
+        let digest_bits = self.alloc_array_public_unchecked::<BitRegister>(num_chunks);
+        let digest_indices = self.alloc_array_public_unchecked::<ElementRegister>(messages.len());
+        let mut t_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        for (message, digest_index) in messages.iter().zip(digest_indices.iter()) {
+            let offset = end_bits_values.len();
+            let range = offset..offset + message.num_chunks();
+            let message_t_values = t_array.get_subarray(range.clone());
+            let selectors = self.blake2b_padding_constraints(message, &message_t_values);
+
+            let mut index = ArithmeticExpression::zero();
+            for ((k, digest_bit), selector) in range
+                .clone()
+                .zip(digest_bits.get_subarray(range).iter())
+                .zip(selectors)
+            {
+                index = index + selector.clone() * L::Field::from_canonical_usize(k);
+                self.api().set_to_expression_public(&digest_bit, selector);
+            }
+            self.api().set_to_expression_public(&digest_index, index);
+
+            t_values.push(message_t_values);
+            end_bits_values
+                .extend((0..message.num_chunks()).map(|k| k == message.num_chunks() - 1));
+        }
+        let chunks = messages
+            .iter()
+            .flat_map(|message| message.chunks.iter().copied())
+            .collect::<Vec<_>>();
+
+        let end_bits = self.constant_array::<BitRegister>(
+            &end_bits_values
+                .iter()
+                .map(|bit| L::Field::from_canonical_u8(*bit as u8))
+                .collect::<Vec<_>>(),
+        );
+        let num_messages =
+            self.constant::<ElementRegister>(&L::Field::from_canonical_usize(messages.len()));
+
+        let digests = BLAKE2BAir::blake2b(
+            self,
+            &chunks,
+            &t_array,
+            &end_bits,
+            &digest_bits,
+            &digest_indices,
+            &num_messages,
+            digest_length,
+            None,
+            None,
+        );
+
+        BLAKE2BPaddingGadget {
+            messages: messages.to_vec(),
+            digests,
+            t_values,
+            digest_length,
+        }
+    }
+
+    /// Constrains the bytes of `message` past its length to be zero and `t_values` to count the
+    /// bytes of the message up to the end of every chunk.
+    ///
+    /// Returns the selectors of the chunks, which are `1` at the chunk holding the last byte of
+    /// the message, or at the first chunk if the message is empty, and `0` elsewhere.
+    fn blake2b_padding_constraints(
+        &mut self,
+        message: &BLAKE2BMessage,
+        t_values: &ArrayRegister<U64Register>,
+    ) -> Vec<ArithmeticExpression<L::Field>> {
+        let num_chunks = message.chunks.len();
+        let bits = message.in_message;
+
+        // The bits are ones followed by zeros, as many ones as the length.
+        for i in 1..bits.len() {
+            self.assert_expression_zero(bits.get(i).expr() * bits.get(i - 1).not_expr());
+        }
+        let count = bits
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr());
+        self.assert_expressions_equal(count, message.length.expr());
+
+        // The bytes past the length are zero.
+        let bytes = message
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .flat_map(|word| word.to_le_bytes().iter());
+        for (byte, bit) in bytes.zip(bits.iter()) {
+            self.assert_expression_zero(byte.expr() * bit.not_expr());
+        }
+
+        // A chunk is selected if it holds a byte of the message and the next chunk does not.
+        let first_bits = (0..num_chunks)
+            .map(|k| bits.get(CHUNK_BYTES * k).expr())
+            .collect::<Vec<_>>();
+        let selectors = (0..num_chunks)
+            .map(|k| {
+                let first_bit = match k {
+                    0 => ArithmeticExpression::one(),
+                    _ => first_bits[k].clone(),
+                };
+                match first_bits.get(k + 1) {
+                    Some(next_bit) => first_bit - next_bit.clone(),
+                    None => first_bit,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The `t` value of the selected chunk is the length, the others count full chunks.
+        for ((k, t), selector) in t_values.iter().enumerate().zip(selectors.iter()) {
+            let t_bytes = t.to_le_bytes();
+            for byte in t_bytes.iter().skip(T_VALUE_BYTES) {
+                self.assert_expression_zero(byte.expr());
+            }
+            let value = t_bytes
+                .iter()
+                .take(T_VALUE_BYTES)
+                .enumerate()
+                .fold(ArithmeticExpression::zero(), |acc, (j, byte)| {
+                    acc + byte.expr() * L::Field::from_canonical_u32(1 << (8 * j))
+                });
+            let full = L::Field::from_canonical_usize(CHUNK_BYTES * (k + 1));
+            let expected = selector.clone() * (message.length.expr() - full) + full;
+            self.assert_expressions_equal(value, expected);
+        }
+        selectors
+    }
+}
+
+impl BLAKE2BMessage {
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The index of the chunk whose state is the digest of a message of `length` bytes.
+    pub fn digest_chunk(length: usize) -> usize {
+        length.saturating_sub(1) / CHUNK_BYTES
+    }
+
+    /// Writes the bytes of `msg`, its length and the bits marking its bytes.
+    pub fn write<F: Field>(&self, msg: &[u8], writer: &mut impl AirWriter<Field = F>) {
+        let padded_msg = BLAKE2BUtil::pad(msg, self.num_chunks() as u64);
+        for (chunk, values) in self.chunks.iter().zip(padded_msg.chunks_exact(CHUNK_BYTES)) {
+            writer.write_array(
+                chunk,
+                values
+                    .chunks_exact(8)
+                    .map(|word| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(word[j]))),
+            );
+        }
+        writer.write(&self.length, &F::from_canonical_usize(msg.len()));
+        writer.write_array(
+            &self.in_message,
+            (0..self.in_message.len()).map(|i| F::from_canonical_u8((i < msg.len()) as u8)),
+        );
+    }
+}
+
+impl BLAKE2BPaddingGadget {
+    /// Writes the messages, their `t` values and their digests.
+    pub fn write<F: Field>(&self, msgs: &[&[u8]], writer: &mut impl AirWriter<Field = F>) {
+        assert_eq!(msgs.len(), self.messages.len());
+        let parameters = BLAKE2BParameters::new(self.digest_length, 0);
+        for (((message, msg), t_values), digest) in self
+            .messages
+            .iter()
+            .zip(msgs.iter())
+            .zip(self.t_values.iter())
+            .zip(self.digests.iter())
+        {
+            message.write(msg, writer);
+
+            let digest_chunk = BLAKE2BMessage::digest_chunk(msg.len());
+            let padded_msg = BLAKE2BUtil::pad(msg, message.num_chunks() as u64);
+            let mut state = parameters.initial_state();
+            for (k, chunk) in padded_msg.chunks_exact(CHUNK_BYTES).enumerate() {
+                let t = if k == digest_chunk {
+                    msg.len()
+                } else {
+                    CHUNK_BYTES * (k + 1)
+                };
+                writer.write(&t_values.get(k), &u64_to_le_field_bytes(t as u64));
+                if k <= digest_chunk {
+                    BLAKE2BPure::compress(chunk, &mut state, t as u64, k == digest_chunk);
+                }
+            }
+            writer.write_array(
+                digest,
+                state[..digest.len()]
+                    .iter()
+                    .map(|x| u64_to_le_field_bytes(*x)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE2BPaddingTest;
+
+    impl AirParameters for BLAKE2BPaddingTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1527;
+        const EXTENDED_COLUMNS: usize = 708;
+    }
+
+    /// Proves the digests of `msgs`, each in `max_chunks` chunks or in as few chunks as possible,
+    /// returning whether the proof verifies. If `tamper` is set, a byte past the length of the
+    /// last message is set.
+    fn prove_blake2b_padded(msgs: &[&[u8]], max_chunks: Option<usize>, tamper: bool) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let mut builder = BytesBuilder::<BLAKE2BPaddingTest>::new();
+        let messages = msgs
+            .iter()
+            .map(|msg| {
+                let num_chunks = BLAKE2BMessage::digest_chunk(msg.len()) + 1;
+                builder.alloc_blake2b_message(max_chunks.unwrap_or(num_chunks))
+            })
+            .collect::<Vec<_>>();
+        let gadget = builder.blake2b_padded(&messages, 32);
+
+        let num_chunks = messages.iter().map(|m| m.num_chunks()).sum::<usize>();
+        let num_rows = (96 * num_chunks).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(msgs, &mut writer);
+        if tamper {
+            let last = messages.last().unwrap();
+            let mut value = [F::ZERO; 8];
+            value[7] = F::ONE;
+            writer.write(&last.chunks.last().unwrap().get(MSG_ARRAY_SIZE - 1), &value);
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, msg) in gadget.digests.iter().zip(msgs.iter()) {
+            let digest_bytes = digest
+                .iter()
+                .flat_map(|word| writer.read(&word))
+                .map(|x| F::as_canonical_u64(&x) as u8)
+                .collect::<Vec<_>>();
+            let expected = BLAKE2BPure::hash(msg, &[], &BLAKE2BParameters::new(32, 0));
+            assert_eq!(digest_bytes, expected);
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_blake2b_padded() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let long_msg = vec![0xab; 200];
+        let msgs: [&[u8]; 3] = [b"", b"abc", &long_msg];
+        assert!(prove_blake2b_padded(&msgs, None, false));
+        assert!(!prove_blake2b_padded(&msgs, None, true));
+    }
+
+    #[test]
+    fn test_blake2b_padded_runtime_length() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let long_msg = vec![0xab; 200];
+        let full_msg = vec![0xcd; 256];
+        let msgs: [&[u8]; 4] = [b"", b"abc", &long_msg, &full_msg];
+        assert!(prove_blake2b_padded(&msgs, Some(3), false));
+        assert!(!prove_blake2b_padded(&msgs, Some(3), true));
+    }
+}
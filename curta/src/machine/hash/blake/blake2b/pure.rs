@@ -1,9 +1,43 @@
-use super::{COMPRESS_IV, STATE_SIZE, WORK_VECTOR_SIZE};
+use super::utils::BLAKE2BUtil;
+use super::{BLAKE2BParameters, COMPRESS_IV, STATE_SIZE, WORK_VECTOR_SIZE};
 use crate::machine::hash::blake::blake2b::SIGMA_PERMUTATIONS;
 
 pub struct BLAKE2BPure;
 
 impl BLAKE2BPure {
+    /// Computes the BLAKE2b hash of `msg` under `key` with the given parameter block.
+    ///
+    /// An empty `key` is the unkeyed hash, otherwise its length must match
+    /// `parameters.key_length`.
+    pub fn hash(msg: &[u8], key: &[u8], parameters: &BLAKE2BParameters) -> Vec<u8> {
+        assert_eq!(key.len(), parameters.key_length);
+
+        let mut data = Vec::new();
+        if !key.is_empty() {
+            data.extend_from_slice(&BLAKE2BUtil::pad(key, 1));
+        }
+        data.extend_from_slice(msg);
+
+        let num_chunks = data.len().div_ceil(128).max(1);
+        let padded_data = BLAKE2BUtil::pad(&data, num_chunks as u64);
+        let mut state = parameters.initial_state();
+        for (i, chunk) in padded_data.chunks_exact(128).enumerate() {
+            let last_chunk = i == num_chunks - 1;
+            let bytes_compressed = if last_chunk {
+                data.len()
+            } else {
+                128 * (i + 1)
+            };
+            Self::compress(chunk, &mut state, bytes_compressed as u64, last_chunk);
+        }
+
+        state
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(parameters.digest_length)
+            .collect()
+    }
+
     pub fn compress(
         msg_chunk: &[u8],
         state: &mut [u64; STATE_SIZE],
@@ -132,3 +166,41 @@ impl BLAKE2BPure {
         v[b] = (v[b] ^ v[c]).rotate_right(63);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blake2b_pure_parameters() {
+        let digest = BLAKE2BPure::hash(b"", &[], &BLAKE2BParameters::new(20, 0));
+        assert_eq!(
+            hex::encode(digest),
+            "3345524abf6bbe1809449224b5972c41790b6cf2"
+        );
+
+        let mut personal = [0u8; 16];
+        personal.copy_from_slice(b"substrate-ext!!!");
+        let parameters = BLAKE2BParameters {
+            salt: core::array::from_fn(|i| i as u8),
+            personal,
+            ..BLAKE2BParameters::new(32, 0)
+        };
+        assert_eq!(
+            hex::encode(BLAKE2BPure::hash(b"abc", &[], &parameters)),
+            "349bec523b15fddfd492677d5c4b729d437d84679f5b8522c711301eedf2be74"
+        );
+
+        let mut personal = [0u8; 16];
+        personal[..5].copy_from_slice(b"curta");
+        let parameters = BLAKE2BParameters {
+            salt: *b"0123456789abcdef",
+            personal,
+            ..BLAKE2BParameters::new(64, 6)
+        };
+        assert_eq!(
+            hex::encode(BLAKE2BPure::hash(&[b'a'; 200], b"secret", &parameters)),
+            "d23377807e8fbc5d24f8db973239b332fedc4766c0dcf3025ee21fd719454a3595bc8e111ad35cca67cfe76e7e641e8409eacbaa7968471127ce4a242d8ad9d5"
+        );
+    }
+}
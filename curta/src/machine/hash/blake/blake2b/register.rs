@@ -10,7 +10,7 @@ use crate::chip::uint::register::U64Register;
 pub struct BLAKE2BDigestRegister(ArrayRegister<U64Register>);
 
 impl RegisterSerializable for BLAKE2BDigestRegister {
-    const CELL: CellType = CellType::Element;
+    const CELL: CellType = CellType::Byte;
     fn register(&self) -> &MemorySlice {
         self.0.register()
     }
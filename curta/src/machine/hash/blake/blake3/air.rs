@@ -0,0 +1,376 @@
+use std::collections::BTreeMap;
+
+use plonky2::util::log2_ceil;
+
+use super::pure::BLAKE3Pure;
+use super::{
+    BLAKE3Air, BLOCK_LEN, BLOCK_WORDS, CV_WORDS, G_INDICES, IV, MSG_PERMUTATION, NUM_ROUNDS,
+};
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+const DUMMY_INDEX: u64 = i32::MAX as u64;
+
+impl<L: AirParameters> BLAKE3Air<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Stores one value per compression, readable on every row of the compression.
+    ///
+    /// The values of the real compressions are given by `values`, the dummy compressions at the
+    /// end of the trace get `dummy_value`.
+    fn compression_values<V: MemoryValue>(
+        builder: &mut BytesBuilder<L>,
+        values: &[V],
+        dummy_value: V,
+        num_compressions: usize,
+        length_last_compression: usize,
+    ) -> Slice<V> {
+        let reg_cycle_length = builder.constant(&L::Field::from_canonical_usize(NUM_ROUNDS));
+        let reg_last_length =
+            builder.constant(&L::Field::from_canonical_usize(length_last_compression));
+
+        let slice = builder.uninit_slice();
+        for i in 0..num_compressions {
+            let value = values.get(i).copied().unwrap_or(dummy_value);
+            let multiplicity = if i == num_compressions - 1 {
+                reg_last_length
+            } else {
+                reg_cycle_length
+            };
+            builder.store(
+                &slice.get(i),
+                value,
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+        slice
+    }
+
+    /// Hashes the messages of `message_lengths` bytes given by `padded_chunks` with BLAKE3.
+    ///
+    /// Every chunk is a block of sixteen little-endian words as given by `BLAKE3Pure::pad`, and
+    /// the blocks of all messages are concatenated. The chunk boundaries and the hash tree are
+    /// determined by the message lengths, which play the role of the end bits. Every compression
+    /// of `BLAKE3Pure::schedule` takes `NUM_ROUNDS` rows, so the trace must have
+    /// `(7 * num_compressions).next_power_of_two()` rows. Returns the digest of every message.
+    pub fn blake3(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        message_lengths: &[usize],
+    ) -> Vec<ArrayRegister<U32Register>> {
+        // The compressions of all messages, followed by each other.
+        let mut schedule = Vec::new();
+        let mut roots = Vec::with_capacity(message_lengths.len());
+        let mut num_message_blocks = 0;
+        for len in message_lengths.iter() {
+            let offset = schedule.len();
+            for mut compression in BLAKE3Pure::schedule(*len) {
+                compression.block = compression.block.map(|block| block + num_message_blocks);
+                compression.parent = compression
+                    .parent
+                    .map(|(parent, half)| (parent + offset, half));
+                schedule.push(compression);
+            }
+            num_message_blocks += len.div_ceil(BLOCK_LEN).max(1);
+            roots.push(schedule.len() - 1);
+        }
+        assert_eq!(
+            padded_chunks.len(),
+            num_message_blocks,
+            "The number of blocks does not match the message lengths"
+        );
+        for chunk in padded_chunks.iter() {
+            assert_eq!(chunk.len(), BLOCK_WORDS, "Blocks must have sixteen words");
+        }
+
+        let num_real_compressions = schedule.len();
+        let degree_log = log2_ceil(num_real_compressions * NUM_ROUNDS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        let num_rows = 1 << degree_log;
+        // The last compression is cut short by the end of the trace.
+        let num_dummy_compressions = num_rows / NUM_ROUNDS + 1 - num_real_compressions;
+        let length_last_compression = num_rows % NUM_ROUNDS;
+        let num_compressions = num_real_compressions + num_dummy_compressions;
+
+        // Store the words of the message blocks. The blocks of the parent nodes are stored by
+        // their children in the trace. On every row all sixteen words are read, either from the
+        // block at the start of a compression or from a dummy zero word otherwise.
+        let words = builder.uninit_slice();
+        for (i, compression) in schedule.iter().enumerate() {
+            if let Some(block) = compression.block {
+                for (j, word) in padded_chunks[block].iter().enumerate() {
+                    builder.store(
+                        &words.get(BLOCK_WORDS * i + j),
+                        word,
+                        &Time::zero(),
+                        None,
+                        None,
+                        None,
+                    );
+                }
+            }
+        }
+        assert!((BLOCK_WORDS * num_real_compressions) < DUMMY_INDEX as usize);
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let dummy_index = builder.constant(&L::Field::from_canonical_u64(DUMMY_INDEX));
+        let num_dummy_reads = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+            BLOCK_WORDS * (num_rows - num_real_compressions),
+        ));
+        builder.store(
+            &words.get(DUMMY_INDEX as usize),
+            zero_word,
+            &Time::zero(),
+            Some(num_dummy_reads),
+            None,
+            None,
+        );
+
+        // The parameters of every compression.
+        let one = builder.constant::<BitRegister>(&L::Field::ONE);
+        let zero = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let zero_element = builder.constant::<ElementRegister>(&L::Field::ZERO);
+        let mut word_constants = BTreeMap::new();
+        let mut word_constant = |builder: &mut BytesBuilder<L>, value: u32| {
+            *word_constants
+                .entry(value)
+                .or_insert_with(|| builder.constant::<U32Register>(&u32_to_le_field_bytes(value)))
+        };
+        let mut header_values = [vec![], vec![], vec![], vec![]];
+        let mut chain_values = Vec::with_capacity(num_real_compressions);
+        let mut store_values = Vec::with_capacity(num_real_compressions);
+        let mut cv_index_values = Vec::with_capacity(num_real_compressions);
+        let mut root_values = vec![zero; num_real_compressions];
+        for compression in schedule.iter() {
+            let header = [
+                compression.counter as u32,
+                (compression.counter >> 32) as u32,
+                compression.block_len,
+                compression.flags,
+            ];
+            for (values, value) in header_values.iter_mut().zip(header) {
+                values.push(word_constant(builder, value));
+            }
+            chain_values.push(if compression.chain { one } else { zero });
+            store_values.push(if compression.parent.is_some() {
+                one
+            } else {
+                zero
+            });
+            cv_index_values.push(match compression.parent {
+                Some((parent, half)) => builder.constant(&L::Field::from_canonical_usize(
+                    BLOCK_WORDS * parent + CV_WORDS * half,
+                )),
+                None => zero_element,
+            });
+        }
+        for root in roots.iter() {
+            root_values[*root] = one;
+        }
+        let is_dummy_values = vec![zero; num_real_compressions];
+
+        let header_slices = header_values.map(|values| {
+            Self::compression_values(
+                builder,
+                &values,
+                zero_word,
+                num_compressions,
+                length_last_compression,
+            )
+        });
+        let [chain_slice, store_slice, root_slice, is_dummy_slice] = [
+            (chain_values, zero),
+            (store_values, zero),
+            (root_values, zero),
+            (is_dummy_values, one),
+        ]
+        .map(|(values, dummy_value)| {
+            Self::compression_values(
+                builder,
+                &values,
+                dummy_value,
+                num_compressions,
+                length_last_compression,
+            )
+        });
+        let cv_index_slice = Self::compression_values(
+            builder,
+            &cv_index_values,
+            zero_element,
+            num_compressions,
+            length_last_compression,
+        );
+
+        let loop_rounds = builder.api.loop_instr(NUM_ROUNDS);
+        let is_first_round = loop_rounds.get_iteration_reg(0);
+        let cycle_end_bit = loop_rounds.get_iteration_reg(NUM_ROUNDS - 1);
+        let process_id = builder.process_id(NUM_ROUNDS, cycle_end_bit);
+
+        let header = header_slices
+            .map(|slice| builder.load(&slice.get_at(process_id), &Time::zero(), None, None));
+        let [chain, store_bit, root_bit, is_dummy] =
+            [chain_slice, store_slice, root_slice, is_dummy_slice]
+                .map(|slice| builder.load(&slice.get_at(process_id), &Time::zero(), None, None));
+        let cv_index = builder.load(
+            &cv_index_slice.get_at(process_id),
+            &Time::zero(),
+            None,
+            None,
+        );
+
+        // The block is read on the first row of every real compression.
+        let read_flag =
+            builder.expression::<BitRegister>(is_first_round.expr() * is_dummy.not_expr());
+        let block = (0..BLOCK_WORDS)
+            .map(|j| {
+                let word_index = builder.expression(
+                    read_flag.expr()
+                        * (process_id.expr() * L::Field::from_canonical_usize(BLOCK_WORDS)
+                            + L::Field::from_canonical_usize(j))
+                        + read_flag.not_expr() * dummy_index.expr(),
+                );
+                builder.load(&words.get_at(word_index), &Time::zero(), None, None)
+            })
+            .collect::<Vec<U32Register>>();
+
+        // The message words of the round, permuted after every round.
+        let msg = builder.alloc_array::<U32Register>(BLOCK_WORDS);
+        for (i, word) in msg.iter().enumerate() {
+            builder.set_to_expression_first_row(&word, block[i].expr());
+            builder.set_to_expression_transition(
+                &word.next(),
+                msg.get(MSG_PERMUTATION[i]).expr() * cycle_end_bit.not_expr()
+                    + block[i].next().expr(),
+            );
+        }
+
+        // The state at the start of the round.
+        let iv = builder.constant_array::<U32Register>(&IV.map(u32_to_le_field_bytes));
+        let state = builder.alloc_array::<U32Register>(BLOCK_WORDS);
+        let round_output = Self::blake3_round(builder, &state.iter().collect::<Vec<_>>(), &msg);
+        let output = (0..CV_WORDS)
+            .map(|j| builder.xor(&round_output[j], &round_output[j + CV_WORDS]))
+            .collect::<Vec<_>>();
+
+        // The first compression of the trace starts from the IV. The following ones start from
+        // the output of the previous compression if `chain` is set, and from the IV otherwise.
+        let next_chain = chain.next();
+        for (i, word) in state.iter().enumerate() {
+            let (initial_value, next_initial_value) = match i {
+                0..=7 => (
+                    iv.get(i).expr(),
+                    output[i].expr() * next_chain.expr() + iv.get(i).expr() * next_chain.not_expr(),
+                ),
+                8..=11 => (iv.get(i - 8).expr(), iv.get(i - 8).expr()),
+                _ => (header[i - 12].expr(), header[i - 12].next().expr()),
+            };
+            builder.set_to_expression_first_row(&word, initial_value);
+            builder.set_to_expression_transition(
+                &word.next(),
+                round_output[i].expr() * cycle_end_bit.not_expr()
+                    + next_initial_value * cycle_end_bit.expr(),
+            );
+        }
+
+        // Store the chaining values of the children in the blocks of their parents.
+        let store_flag = builder.expression(cycle_end_bit.expr() * store_bit.expr());
+        for (j, word) in output.iter().enumerate() {
+            let word_index =
+                builder.expression(cv_index.expr() + L::Field::from_canonical_usize(j));
+            builder.store(
+                &words.get_at(word_index),
+                *word,
+                &Time::zero(),
+                Some(store_flag),
+                None,
+                None,
+            );
+        }
+
+        // Store the outputs of the root nodes as the digests.
+        let state_ptr = builder.uninit_slice();
+        let hash_state_public = (0..roots.len())
+            .map(|_| builder.alloc_array_public::<U32Register>(CV_WORDS))
+            .collect::<Vec<_>>();
+        for (root, h) in roots.iter().zip(hash_state_public.iter()) {
+            let root_index = builder.constant(&L::Field::from_canonical_usize(*root));
+            for (j, word) in h.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(root_index));
+            }
+        }
+        let digest_flag = builder.expression(cycle_end_bit.expr() * root_bit.expr());
+        for (j, word) in output.iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                *word,
+                &Time::from_element(process_id),
+                Some(digest_flag),
+                None,
+                None,
+            );
+        }
+
+        hash_state_public
+    }
+
+    /// A round of the compression function applied to `state` with message words `m`.
+    pub fn blake3_round(
+        builder: &mut BytesBuilder<L>,
+        state: &[U32Register],
+        m: &ArrayRegister<U32Register>,
+    ) -> Vec<U32Register> {
+        assert_eq!(state.len(), BLOCK_WORDS);
+        assert_eq!(m.len(), BLOCK_WORDS);
+
+        let mut state = state.to_vec();
+        for (i, indices) in G_INDICES.iter().enumerate() {
+            Self::g(
+                builder,
+                &mut state,
+                *indices,
+                m.get(2 * i),
+                m.get(2 * i + 1),
+            );
+        }
+        state
+    }
+
+    fn g(
+        builder: &mut BytesBuilder<L>,
+        state: &mut [U32Register],
+        [a, b, c, d]: [usize; 4],
+        x: U32Register,
+        y: U32Register,
+    ) {
+        let sum = builder.add(state[a], state[b]);
+        state[a] = builder.add(sum, x);
+        let d_xor_a = builder.xor(&state[d], &state[a]);
+        state[d] = builder.rotate_right(d_xor_a, 16);
+        state[c] = builder.add(state[c], state[d]);
+        let b_xor_c = builder.xor(&state[b], &state[c]);
+        state[b] = builder.rotate_right(b_xor_c, 12);
+
+        let sum = builder.add(state[a], state[b]);
+        state[a] = builder.add(sum, y);
+        let d_xor_a = builder.xor(&state[d], &state[a]);
+        state[d] = builder.rotate_right(d_xor_a, 8);
+        state[c] = builder.add(state[c], state[d]);
+        let b_xor_c = builder.xor(&state[b], &state[c]);
+        state[b] = builder.rotate_right(b_xor_c, 7);
+    }
+}
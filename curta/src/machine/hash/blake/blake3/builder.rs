@@ -0,0 +1,140 @@
+use super::BLAKE3Air;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::AirParameters;
+use crate::machine::bytes::builder::BytesBuilder;
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the BLAKE3 hash of the messages given by `padded_chunks`.
+    ///
+    /// See `BLAKE3Air::blake3` for the layout of the inputs and the number of rows.
+    pub fn blake3(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        message_lengths: &[usize],
+    ) -> Vec<ArrayRegister<U32Register>> {
+        BLAKE3Air::blake3(self, padded_chunks, message_lengths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_to_le_field_bytes;
+    use crate::machine::builder::Builder;
+    use crate::machine::hash::blake::blake3::pure::BLAKE3Pure;
+    use crate::machine::hash::blake::blake3::{BLOCK_WORDS, NUM_ROUNDS};
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE3Test;
+
+    impl AirParameters for BLAKE3Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 3000;
+        const EXTENDED_COLUMNS: usize = 1500;
+    }
+
+    #[test]
+    fn test_blake3() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake3", log::Level::Info);
+
+        // The last message spans two chunks and is hashed with a parent node.
+        let msgs = [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            (0..1025).map(|i| (i % 251) as u8).collect::<Vec<_>>(),
+        ];
+        let message_lengths = msgs.iter().map(|msg| msg.len()).collect::<Vec<_>>();
+
+        let padded_chunks_values = msgs
+            .iter()
+            .flat_map(|msg| BLAKE3Pure::pad(msg))
+            .collect::<Vec<_>>();
+        let num_compressions = message_lengths
+            .iter()
+            .map(|len| BLAKE3Pure::schedule(*len).len())
+            .sum::<usize>();
+
+        let mut builder = BytesBuilder::<BLAKE3Test>::new();
+        let padded_chunks = (0..padded_chunks_values.len())
+            .map(|_| builder.alloc_array_public::<U32Register>(BLOCK_WORDS))
+            .collect::<Vec<_>>();
+        let digests = builder.blake3(&padded_chunks, &message_lengths);
+
+        let num_rows = (NUM_ROUNDS * num_compressions).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (chunk, values) in padded_chunks.iter().zip(padded_chunks_values.iter()) {
+            writer.write_array(chunk, values.map(u32_to_le_field_bytes));
+        }
+        for (digest, msg) in digests.iter().zip(msgs.iter()) {
+            writer.write_array(digest, BLAKE3Pure::digest(msg).map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
@@ -0,0 +1,63 @@
+pub mod air;
+pub mod builder;
+pub mod pure;
+
+pub struct BLAKE3Air<L> {
+    _marker: core::marker::PhantomData<L>,
+}
+
+/// The number of rounds of the compression function, each taking a row of the trace.
+pub const NUM_ROUNDS: usize = 7;
+/// The number of 32-bit words of a message block.
+pub const BLOCK_WORDS: usize = 16;
+/// The number of bytes of a message block.
+pub const BLOCK_LEN: usize = 64;
+/// The number of bytes of a chunk, the leaves of the hash tree.
+pub const CHUNK_LEN: usize = 1024;
+/// The number of 32-bit words of a chaining value and of a digest.
+pub const CV_WORDS: usize = 8;
+
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const PARENT: u32 = 1 << 2;
+pub const ROOT: u32 = 1 << 3;
+
+pub const IV: [u32; CV_WORDS] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// The permutation applied to the message words between rounds.
+pub const MSG_PERMUTATION: [usize; BLOCK_WORDS] =
+    [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// The state words mixed by each of the eight G functions of a round, the first four on the
+/// columns and the last four on the diagonals of the state.
+pub const G_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// A call to the compression function in the hash of a message.
+///
+/// The calls are ordered such that the blocks of a chunk are compressed consecutively and every
+/// parent node comes after its children, the root being the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BLAKE3Compression {
+    /// The index of the compressed message block, or `None` for a parent node whose block is
+    /// given by the chaining values of its children.
+    pub block: Option<usize>,
+    pub counter: u64,
+    pub block_len: u32,
+    pub flags: u32,
+    /// Whether the input chaining value is the output of the previous compression instead of
+    /// the IV.
+    pub chain: bool,
+    /// The parent node and the half of its block, `0` or `1`, given by the output.
+    pub parent: Option<(usize, usize)>,
+}
@@ -0,0 +1,230 @@
+use super::{
+    BLAKE3Compression, BLOCK_LEN, BLOCK_WORDS, CHUNK_END, CHUNK_LEN, CHUNK_START, CV_WORDS,
+    G_INDICES, IV, MSG_PERMUTATION, NUM_ROUNDS, PARENT, ROOT,
+};
+
+pub struct BLAKE3Pure;
+
+impl BLAKE3Pure {
+    fn g(state: &mut [u32; BLOCK_WORDS], [a, b, c, d]: [usize; 4], x: u32, y: u32) {
+        state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+        state[d] = (state[d] ^ state[a]).rotate_right(16);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_right(12);
+        state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+        state[d] = (state[d] ^ state[a]).rotate_right(8);
+        state[c] = state[c].wrapping_add(state[d]);
+        state[b] = (state[b] ^ state[c]).rotate_right(7);
+    }
+
+    pub fn round(state: &mut [u32; BLOCK_WORDS], m: &[u32; BLOCK_WORDS]) {
+        for (i, indices) in G_INDICES.iter().enumerate() {
+            Self::g(state, *indices, m[2 * i], m[2 * i + 1]);
+        }
+    }
+
+    pub fn permute(m: &[u32; BLOCK_WORDS]) -> [u32; BLOCK_WORDS] {
+        MSG_PERMUTATION.map(|i| m[i])
+    }
+
+    /// The compression function, truncated to the output chaining value.
+    pub fn compress(
+        cv: &[u32; CV_WORDS],
+        block: &[u32; BLOCK_WORDS],
+        counter: u64,
+        block_len: u32,
+        flags: u32,
+    ) -> [u32; CV_WORDS] {
+        let mut state = [0u32; BLOCK_WORDS];
+        state[..CV_WORDS].copy_from_slice(cv);
+        state[8..12].copy_from_slice(&IV[..4]);
+        state[12] = counter as u32;
+        state[13] = (counter >> 32) as u32;
+        state[14] = block_len;
+        state[15] = flags;
+
+        let mut m = *block;
+        for i in 0..NUM_ROUNDS {
+            Self::round(&mut state, &m);
+            if i < NUM_ROUNDS - 1 {
+                m = Self::permute(&m);
+            }
+        }
+        core::array::from_fn(|i| state[i] ^ state[i + CV_WORDS])
+    }
+
+    /// Pads the message with zeros to a non-zero number of blocks of little-endian words.
+    pub fn pad(msg: &[u8]) -> Vec<[u32; BLOCK_WORDS]> {
+        let num_blocks = msg.len().div_ceil(BLOCK_LEN).max(1);
+        let mut padded_msg = msg.to_vec();
+        padded_msg.resize(num_blocks * BLOCK_LEN, 0);
+        padded_msg
+            .chunks_exact(BLOCK_LEN)
+            .map(|block| {
+                core::array::from_fn(|i| {
+                    u32::from_le_bytes(block[4 * i..4 * i + 4].try_into().unwrap())
+                })
+            })
+            .collect()
+    }
+
+    /// The compressions computing the hash of a message of `msg_len` bytes.
+    ///
+    /// The blocks are the ones given by `pad`. The chunks are the leaves of a binary tree whose
+    /// left subtrees are complete with the largest possible number of chunks.
+    pub fn schedule(msg_len: usize) -> Vec<BLAKE3Compression> {
+        let num_chunks = msg_len.div_ceil(CHUNK_LEN).max(1);
+        let mut compressions = Vec::new();
+        let mut chunk_outputs = Vec::with_capacity(num_chunks);
+        let mut block = 0;
+        for chunk in 0..num_chunks {
+            let chunk_len = (msg_len - chunk * CHUNK_LEN).min(CHUNK_LEN);
+            let num_blocks = chunk_len.div_ceil(BLOCK_LEN).max(1);
+            for i in 0..num_blocks {
+                let mut flags = 0;
+                if i == 0 {
+                    flags |= CHUNK_START;
+                }
+                if i == num_blocks - 1 {
+                    flags |= CHUNK_END;
+                    if num_chunks == 1 {
+                        flags |= ROOT;
+                    }
+                }
+                compressions.push(BLAKE3Compression {
+                    block: Some(block),
+                    counter: chunk as u64,
+                    block_len: (chunk_len - i * BLOCK_LEN).min(BLOCK_LEN) as u32,
+                    flags,
+                    chain: i > 0,
+                    parent: None,
+                });
+                block += 1;
+            }
+            chunk_outputs.push(compressions.len() - 1);
+        }
+        Self::parent_nodes(&mut compressions, &chunk_outputs, true);
+        compressions
+    }
+
+    /// Adds the parent nodes of the subtree over the outputs of `children` and returns the
+    /// compression giving the chaining value of the subtree.
+    fn parent_nodes(
+        compressions: &mut Vec<BLAKE3Compression>,
+        children: &[usize],
+        is_root: bool,
+    ) -> usize {
+        if children.len() == 1 {
+            return children[0];
+        }
+        let left_len = 1 << (children.len() - 1).ilog2();
+        let left = Self::parent_nodes(compressions, &children[..left_len], false);
+        let right = Self::parent_nodes(compressions, &children[left_len..], false);
+
+        let index = compressions.len();
+        compressions.push(BLAKE3Compression {
+            block: None,
+            counter: 0,
+            block_len: BLOCK_LEN as u32,
+            flags: if is_root { PARENT | ROOT } else { PARENT },
+            chain: false,
+            parent: None,
+        });
+        compressions[left].parent = Some((index, 0));
+        compressions[right].parent = Some((index, 1));
+        index
+    }
+
+    /// The digest as little-endian words.
+    pub fn digest(msg: &[u8]) -> [u32; CV_WORDS] {
+        let blocks = Self::pad(msg);
+        let schedule = Self::schedule(msg.len());
+
+        let mut parent_blocks = vec![[0u32; BLOCK_WORDS]; schedule.len()];
+        let mut cv = IV;
+        for (i, compression) in schedule.iter().enumerate() {
+            let block = match compression.block {
+                Some(block) => blocks[block],
+                None => parent_blocks[i],
+            };
+            let input_cv = if compression.chain { cv } else { IV };
+            cv = Self::compress(
+                &input_cv,
+                &block,
+                compression.counter,
+                compression.block_len,
+                compression.flags,
+            );
+            if let Some((parent, half)) = compression.parent {
+                parent_blocks[parent][CV_WORDS * half..CV_WORDS * (half + 1)].copy_from_slice(&cv);
+            }
+        }
+        cv
+    }
+
+    pub fn hash(msg: &[u8]) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(Self::digest(msg)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blake3_pure() {
+        assert_eq!(
+            hex::encode(BLAKE3Pure::hash(b"")),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hex::encode(BLAKE3Pure::hash(b"abc")),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+
+        let expected = [
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            ),
+            (
+                2049,
+                "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+            ),
+            (
+                3073,
+                "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+            ),
+        ];
+        for (len, digest) in expected {
+            let msg = (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+            assert_eq!(hex::encode(BLAKE3Pure::hash(&msg)), digest);
+        }
+    }
+
+    #[test]
+    fn test_blake3_schedule() {
+        // Four chunks, the last one with a single block, hashed as a complete tree.
+        let schedule = BLAKE3Pure::schedule(3 * CHUNK_LEN + 1);
+        assert_eq!(schedule.len(), 3 * 16 + 1 + 3);
+        let parents = &schedule[49..];
+        assert!(parents.iter().all(|c| c.block.is_none()));
+        assert_eq!(parents[0].parent, Some((51, 0)));
+        assert_eq!(parents[1].parent, Some((51, 1)));
+        assert_eq!(parents[2].flags, PARENT | ROOT);
+        assert_eq!(schedule[15].parent, Some((49, 0)));
+        assert_eq!(schedule[31].parent, Some((49, 1)));
+        assert_eq!(schedule[47].parent, Some((50, 0)));
+        assert_eq!(schedule[48].parent, Some((50, 1)));
+        assert_eq!(schedule[48].flags, CHUNK_START | CHUNK_END);
+        assert_eq!(schedule[48].block_len, 1);
+    }
+}
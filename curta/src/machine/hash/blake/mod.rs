@@ -1 +1,2 @@
 pub mod blake2b;
+pub mod blake3;
@@ -0,0 +1,207 @@
+//! Hashing of byte registers of a length known at proving time.
+//!
+//! `BytesBuilder::hash_bytes` allocates the chunks of the message, constrains its padding from
+//! the length and proves the hash in a single call.
+
+use super::blake::blake2b::padding::BLAKE2BPaddingGadget;
+use super::blake::blake2b::pure::BLAKE2BPure;
+use super::blake::blake2b::BLAKE2BParameters;
+use super::sha::sha256::padding::SHA256PaddingGadget;
+use super::sha::sha256::SHA256;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::AirParameters;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// A hash function supported by `BytesBuilder::hash_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    SHA256,
+    /// Unkeyed BLAKE2b with a digest of `digest_length` bytes.
+    BLAKE2B {
+        digest_length: usize,
+    },
+}
+
+/// The registers of a message hashed by `BytesBuilder::hash_bytes`.
+#[derive(Debug, Clone)]
+pub struct HashBytesGadget {
+    /// The bytes of the digest.
+    pub digest: Vec<ByteRegister>,
+    padding: HashPadding,
+}
+
+#[derive(Debug, Clone)]
+enum HashPadding {
+    SHA256(SHA256PaddingGadget),
+    BLAKE2B(BLAKE2BPaddingGadget),
+}
+
+impl HashAlgorithm {
+    /// The number of rows of a trace hashing a message of at most `max_length` bytes.
+    pub fn num_rows(&self, max_length: usize) -> usize {
+        match self {
+            Self::SHA256 => (64 * (max_length + 9).div_ceil(64)).next_power_of_two(),
+            Self::BLAKE2B { .. } => (96 * max_length.div_ceil(128).max(1)).next_power_of_two(),
+        }
+    }
+
+    /// The digest of `msg`.
+    pub fn digest(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Self::SHA256 => SHA256::digest(msg)
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect(),
+            Self::BLAKE2B { digest_length } => {
+                BLAKE2BPure::hash(msg, &[], &BLAKE2BParameters::new(*digest_length, 0))
+            }
+        }
+    }
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the hash of the first `length` bytes of `message` with `algorithm`.
+    ///
+    /// The bytes of `message` and `length` must be public registers, and the bytes past the
+    /// length are ignored. The hash takes the whole trace, whose number of rows is given by
+    /// `HashAlgorithm::num_rows`, so a builder can hash a single message.
+    pub fn hash_bytes(
+        &mut self,
+        algorithm: HashAlgorithm,
+        message: &[ByteRegister],
+        length: ElementRegister,
+    ) -> HashBytesGadget {
+        match algorithm {
+            HashAlgorithm::SHA256 => {
+                let message = self.alloc_sha256_message(message, &length);
+                let padding = self.sha256_padded(&[message]);
+                // The words of the digest are big-endian.
+                let digest = padding.digests[0]
+                    .iter()
+                    .flat_map(|word| (0..4).rev().map(move |j| word.to_le_bytes().get(j)))
+                    .collect();
+                HashBytesGadget {
+                    digest,
+                    padding: HashPadding::SHA256(padding),
+                }
+            }
+            HashAlgorithm::BLAKE2B { digest_length } => {
+                let message = self.blake2b_message_from_bytes(message, &length);
+                let padding = self.blake2b_padded(&[message], digest_length);
+                let digest = padding.digests[0]
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes().iter())
+                    .take(digest_length)
+                    .collect();
+                HashBytesGadget {
+                    digest,
+                    padding: HashPadding::BLAKE2B(padding),
+                }
+            }
+        }
+    }
+}
+
+impl HashBytesGadget {
+    /// Writes the padding of `msg` and its digest.
+    ///
+    /// The bytes of the message and its length are written by the caller. This writes public
+    /// values, so it must be called before the global instructions are written.
+    pub fn write<F: Field>(&self, msg: &[u8], writer: &mut impl AirWriter<Field = F>) {
+        match &self.padding {
+            HashPadding::SHA256(padding) => padding.write(&[msg], writer),
+            HashPadding::BLAKE2B(padding) => padding.write(&[msg], writer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::builder::Builder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HashBytesTest;
+
+    impl AirParameters for HashBytesTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1527;
+        const EXTENDED_COLUMNS: usize = 708;
+    }
+
+    fn test_hash_bytes(algorithm: HashAlgorithm, msg: &[u8], capacity: usize) {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hash_bytes", log::Level::Debug);
+
+        let mut builder = BytesBuilder::<HashBytesTest>::new();
+        let message = builder.alloc_array_public::<ByteRegister>(capacity);
+        let length = builder.alloc_public::<ElementRegister>();
+        let gadget = builder.hash_bytes(algorithm, &message.iter().collect::<Vec<_>>(), length);
+
+        let num_rows = algorithm.num_rows(capacity);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        // The bytes past the length are arbitrary.
+        writer.write_array(
+            &message,
+            (0..capacity).map(|i| F::from_canonical_u8(msg.get(i).copied().unwrap_or(0x5a))),
+        );
+        writer.write(&length, &F::from_canonical_usize(msg.len()));
+        gadget.write(msg, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        let digest = gadget
+            .digest
+            .iter()
+            .map(|byte| writer.read(byte).as_canonical_u64() as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(digest, algorithm.digest(msg));
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_hash_bytes_sha256() {
+        test_hash_bytes(HashAlgorithm::SHA256, b"abc", 100);
+    }
+
+    #[test]
+    fn test_hash_bytes_blake2b() {
+        let msg = vec![0xab; 150];
+        test_hash_bytes(HashAlgorithm::BLAKE2B { digest_length: 32 }, &msg, 300);
+    }
+}
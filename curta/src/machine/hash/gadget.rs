@@ -0,0 +1,530 @@
+//! A common interface to the hash functions whose messages are proven as a batch of blocks.
+//!
+//! The blocks of all the messages of a builder are collected in a `HashInput` with the methods
+//! of `HashBuilder`, and the hash is then proven over the whole batch in a single call.
+
+use core::fmt::Debug;
+
+use super::blake::blake2b::pure::BLAKE2BPure;
+use super::blake::blake2b::utils::BLAKE2BUtil;
+use super::blake::blake2b::{BLAKE2BAir, BLAKE2BParameters};
+use super::keccak::pure::KeccakPure;
+use super::keccak::{KeccakAir, NUM_ROUNDS, RATE_LANES};
+use super::sha::algorithm::{SHAPure, SHAir};
+use super::sha::sha256::register::SHA256DigestRegister;
+use super::sha::sha256::SHA256;
+use super::sha::sha512::register::SHA512DigestRegister;
+use super::sha::sha512::SHA512;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::{U32Register, U64Register};
+use crate::chip::uint::util::{u32_to_le_field_bytes, u64_to_le_field_bytes};
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The length in bytes of the BLAKE2b digests of `HashGadget`.
+const BLAKE2B_DIGEST_LENGTH: usize = 32;
+
+/// A hash function proven over a batch of padded messages.
+pub trait HashGadget<B: Builder> {
+    /// The register of a word of a block.
+    type IntRegister: Register;
+
+    /// The register of a digest.
+    type DigestRegister: Debug + Copy + Into<ArrayRegister<Self::IntRegister>>;
+
+    /// The number of words of a padded block.
+    const BLOCK_WORDS: usize;
+
+    /// The number of rows of the trace taken by each block.
+    const ROWS_PER_BLOCK: usize;
+
+    /// Pads `msg` into words, a multiple of `BLOCK_WORDS` of them.
+    fn pad(msg: &[u8]) -> Vec<<Self::IntRegister as Register>::Value<B::Field>>;
+
+    /// The words of the digest of `msg`.
+    fn digest(msg: &[u8]) -> Vec<<Self::IntRegister as Register>::Value<B::Field>>;
+
+    /// Proves the digests of all the messages of `input`, in order.
+    ///
+    /// The digests are public registers whose values must be written by the prover, see
+    /// `HashInput::write`.
+    fn hash_blocks(
+        builder: &mut B,
+        input: &HashInput<Self::IntRegister>,
+    ) -> Vec<Self::DigestRegister>;
+
+    /// The number of padded blocks of a message of `msg_len` bytes.
+    fn num_blocks(msg_len: usize) -> usize {
+        Self::pad(&vec![0u8; msg_len]).len() / Self::BLOCK_WORDS
+    }
+
+    /// The number of rows of a trace proving the blocks of `input`.
+    fn num_rows(input: &HashInput<Self::IntRegister>) -> usize {
+        (Self::ROWS_PER_BLOCK * input.num_blocks()).next_power_of_two()
+    }
+}
+
+/// The padded blocks of a batch of messages.
+#[derive(Debug, Clone)]
+pub struct HashInput<T> {
+    blocks: Vec<ArrayRegister<T>>,
+    /// Whether every block is the last block of its message.
+    end_bits: Vec<bool>,
+    /// The length in bytes of every finalized message.
+    lengths: Vec<usize>,
+    /// The number of blocks of the messages that are finalized.
+    num_finalized_blocks: usize,
+}
+
+pub trait HashBuilder: Builder {
+    /// Allocates the padded blocks of a message of `msg_len` bytes and adds the message to
+    /// `input`.
+    ///
+    /// The values of the blocks are written by `HashInput::write`.
+    fn hash_pad<H: HashGadget<Self>>(
+        &mut self,
+        input: &mut HashInput<H::IntRegister>,
+        msg_len: usize,
+    ) -> Vec<ArrayRegister<H::IntRegister>> {
+        let blocks = (0..H::num_blocks(msg_len))
+            .map(|_| self.alloc_array_public::<H::IntRegister>(H::BLOCK_WORDS))
+            .collect::<Vec<_>>();
+        for block in blocks.iter() {
+            self.hash_update::<H>(input, block);
+        }
+        self.hash_finalize::<H>(input, msg_len);
+        blocks
+    }
+
+    /// Appends a padded block to the current message of `input`.
+    fn hash_update<H: HashGadget<Self>>(
+        &mut self,
+        input: &mut HashInput<H::IntRegister>,
+        block: &ArrayRegister<H::IntRegister>,
+    ) {
+        assert_eq!(block.len(), H::BLOCK_WORDS, "Invalid block length");
+        input.blocks.push(*block);
+        input.end_bits.push(false);
+    }
+
+    /// Ends the current message of `input`, whose length is `msg_len` bytes.
+    ///
+    /// The message must have exactly `H::num_blocks(msg_len)` blocks.
+    fn hash_finalize<H: HashGadget<Self>>(
+        &mut self,
+        input: &mut HashInput<H::IntRegister>,
+        msg_len: usize,
+    ) {
+        let num_blocks = input.num_blocks() - input.num_finalized_blocks;
+        assert_eq!(
+            num_blocks,
+            H::num_blocks(msg_len),
+            "A message of {} bytes must have {} blocks",
+            msg_len,
+            H::num_blocks(msg_len)
+        );
+        *input.end_bits.last_mut().unwrap() = true;
+        input.lengths.push(msg_len);
+        input.num_finalized_blocks = input.num_blocks();
+    }
+
+    /// Proves the digests of the messages of `input`, see `HashGadget::hash_blocks`.
+    fn hash<H: HashGadget<Self>>(
+        &mut self,
+        input: &HashInput<H::IntRegister>,
+    ) -> Vec<H::DigestRegister> {
+        assert_eq!(
+            input.num_finalized_blocks,
+            input.num_blocks(),
+            "The last message is not finalized"
+        );
+        assert!(input.num_messages() > 0, "Cannot hash an empty batch");
+        H::hash_blocks(self, input)
+    }
+}
+
+impl<B: Builder> HashBuilder for B {}
+
+impl<T: Register> HashInput<T> {
+    pub fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            end_bits: Vec::new(),
+            lengths: Vec::new(),
+            num_finalized_blocks: 0,
+        }
+    }
+
+    pub fn blocks(&self) -> &[ArrayRegister<T>] {
+        &self.blocks
+    }
+
+    pub fn end_bits(&self) -> &[bool] {
+        &self.end_bits
+    }
+
+    pub fn lengths(&self) -> &[usize] {
+        &self.lengths
+    }
+
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn num_messages(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// The values of the end bits.
+    pub fn end_bit_values<F: Field>(&self) -> Vec<F> {
+        self.end_bits
+            .iter()
+            .map(|bit| F::from_canonical_u8(*bit as u8))
+            .collect()
+    }
+
+    /// The index of the last block of every message.
+    pub fn digest_index_values<F: Field>(&self) -> Vec<F> {
+        self.end_bits
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| **bit)
+            .map(|(i, _)| F::from_canonical_usize(i))
+            .collect()
+    }
+
+    /// Writes the padded blocks of `messages` and their `digests`.
+    pub fn write<B: Builder, H: HashGadget<B, IntRegister = T>>(
+        &self,
+        messages: &[&[u8]],
+        digests: &[H::DigestRegister],
+        writer: &mut impl AirWriter<Field = B::Field>,
+    ) {
+        assert_eq!(messages.len(), self.num_messages());
+        assert_eq!(digests.len(), self.num_messages());
+        let mut blocks = self.blocks.iter();
+        for ((msg, length), digest) in messages.iter().zip(self.lengths.iter()).zip(digests) {
+            assert_eq!(msg.len(), *length, "Invalid message length");
+            let padded_msg = H::pad(msg);
+            for words in padded_msg.chunks_exact(H::BLOCK_WORDS) {
+                writer.write_array(blocks.next().unwrap(), words);
+            }
+            let digest: ArrayRegister<T> = (*digest).into();
+            writer.write_array(&digest, H::digest(msg));
+        }
+    }
+}
+
+impl<T: Register> Default for HashInput<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The end bits, digest bits and digest indices of `SHAir::sha` and `KeccakAir::keccak256`, with
+/// a digest at the end of every message.
+fn end_bits_and_indices<B: Builder, T: Register>(
+    builder: &mut B,
+    input: &HashInput<T>,
+) -> (ArrayRegister<BitRegister>, ArrayRegister<ElementRegister>) {
+    let end_bits = builder.constant_array::<BitRegister>(&input.end_bit_values());
+    let digest_indices = builder.constant_array::<ElementRegister>(&input.digest_index_values());
+    (end_bits, digest_indices)
+}
+
+impl<L: AirParameters> HashGadget<BytesBuilder<L>> for SHA256
+where
+    L::Instruction: UintInstructions,
+{
+    type IntRegister = U32Register;
+    type DigestRegister = SHA256DigestRegister;
+
+    const BLOCK_WORDS: usize = 16;
+    const ROWS_PER_BLOCK: usize = 64;
+
+    fn pad(msg: &[u8]) -> Vec<[L::Field; 4]> {
+        <SHA256 as SHAPure<64>>::pad(msg)
+            .into_iter()
+            .map(u32_to_le_field_bytes)
+            .collect()
+    }
+
+    fn digest(msg: &[u8]) -> Vec<[L::Field; 4]> {
+        SHA256::digest(msg).map(u32_to_le_field_bytes).to_vec()
+    }
+
+    fn hash_blocks(
+        builder: &mut BytesBuilder<L>,
+        input: &HashInput<U32Register>,
+    ) -> Vec<SHA256DigestRegister> {
+        let (end_bits, digest_indices) = end_bits_and_indices(builder, input);
+        SHA256::sha(
+            builder,
+            input.blocks(),
+            &end_bits,
+            &end_bits,
+            digest_indices,
+        )
+    }
+}
+
+impl<L: AirParameters> HashGadget<BytesBuilder<L>> for SHA512
+where
+    L::Instruction: UintInstructions,
+{
+    type IntRegister = U64Register;
+    type DigestRegister = SHA512DigestRegister;
+
+    const BLOCK_WORDS: usize = 16;
+    const ROWS_PER_BLOCK: usize = 80;
+
+    fn pad(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        <SHA512 as SHAPure<80>>::pad(msg)
+            .into_iter()
+            .map(u64_to_le_field_bytes)
+            .collect()
+    }
+
+    fn digest(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        <SHA512 as SHAPure<80>>::pad(msg)
+            .chunks_exact(16)
+            .fold(SHA512::INITIAL_HASH, |hash, chunk| {
+                SHA512::process(hash, &SHA512::pre_process(chunk))
+            })
+            .map(u64_to_le_field_bytes)
+            .to_vec()
+    }
+
+    fn hash_blocks(
+        builder: &mut BytesBuilder<L>,
+        input: &HashInput<U64Register>,
+    ) -> Vec<SHA512DigestRegister> {
+        let (end_bits, digest_indices) = end_bits_and_indices(builder, input);
+        SHA512::sha(
+            builder,
+            input.blocks(),
+            &end_bits,
+            &end_bits,
+            digest_indices,
+        )
+    }
+}
+
+/// Unkeyed BLAKE2b with a digest of `BLAKE2B_DIGEST_LENGTH` bytes.
+impl<L: AirParameters> HashGadget<BytesBuilder<L>> for BLAKE2BAir<L>
+where
+    L::Instruction: UintInstructions,
+{
+    type IntRegister = U64Register;
+    type DigestRegister = ArrayRegister<U64Register>;
+
+    const BLOCK_WORDS: usize = 16;
+    const ROWS_PER_BLOCK: usize = 96;
+
+    fn pad(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        let num_chunks = msg.len().div_ceil(128).max(1);
+        BLAKE2BUtil::pad(msg, num_chunks as u64)
+            .chunks_exact(8)
+            .map(|word| core::array::from_fn(|j| L::Field::from_canonical_u8(word[j])))
+            .collect()
+    }
+
+    fn digest(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        let parameters = BLAKE2BParameters::new(BLAKE2B_DIGEST_LENGTH, 0);
+        BLAKE2BPure::hash(msg, &[], &parameters)
+            .chunks_exact(8)
+            .map(|word| core::array::from_fn(|j| L::Field::from_canonical_u8(word[j])))
+            .collect()
+    }
+
+    /// The `t` value of every block counts the bytes of the message up to the end of the block.
+    fn hash_blocks(
+        builder: &mut BytesBuilder<L>,
+        input: &HashInput<U64Register>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        let mut t_values = Vec::new();
+        let mut lengths = input.lengths().iter();
+        let mut bytes_compressed = 0;
+        for end_bit in input.end_bits() {
+            bytes_compressed += 128;
+            if *end_bit {
+                t_values.push(*lengths.next().unwrap() as u64);
+                bytes_compressed = 0;
+            } else {
+                t_values.push(bytes_compressed as u64);
+            }
+        }
+        let t_values = builder.constant_array::<U64Register>(
+            &t_values
+                .into_iter()
+                .map(u64_to_le_field_bytes)
+                .collect::<Vec<_>>(),
+        );
+        let (end_bits, digest_indices) = end_bits_and_indices(builder, input);
+        let num_messages = builder
+            .constant::<ElementRegister>(&L::Field::from_canonical_usize(input.num_messages()));
+        BLAKE2BAir::blake2b(
+            builder,
+            input.blocks(),
+            &t_values,
+            &end_bits,
+            &end_bits,
+            &digest_indices,
+            &num_messages,
+            BLAKE2B_DIGEST_LENGTH,
+            None,
+            None,
+        )
+    }
+}
+
+/// Keccak-256, whose blocks are `RATE_LANES` lanes.
+impl<L: AirParameters> HashGadget<BytesBuilder<L>> for KeccakAir<L>
+where
+    L::Instruction: UintInstructions,
+{
+    type IntRegister = U64Register;
+    type DigestRegister = ArrayRegister<U64Register>;
+
+    const BLOCK_WORDS: usize = RATE_LANES;
+    const ROWS_PER_BLOCK: usize = NUM_ROUNDS;
+
+    fn pad(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        KeccakPure::pad(msg)
+            .into_iter()
+            .map(u64_to_le_field_bytes)
+            .collect()
+    }
+
+    fn digest(msg: &[u8]) -> Vec<[L::Field; 8]> {
+        KeccakPure::keccak256(msg)
+            .chunks_exact(8)
+            .map(|lane| u64_to_le_field_bytes(u64::from_le_bytes(lane.try_into().unwrap())))
+            .collect()
+    }
+
+    fn hash_blocks(
+        builder: &mut BytesBuilder<L>,
+        input: &HashInput<U64Register>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        let (end_bits, digest_indices) = end_bits_and_indices(builder, input);
+        KeccakAir::keccak256(
+            builder,
+            input.blocks(),
+            &end_bits,
+            &end_bits,
+            &digest_indices,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::Chip;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::plonky2::Plonky2Air;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HashGadgetTest;
+
+    impl AirParameters for HashGadgetTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1527;
+        const EXTENDED_COLUMNS: usize = 708;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct KeccakGadgetTest;
+
+    impl AirParameters for KeccakGadgetTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 7000;
+        const EXTENDED_COLUMNS: usize = 3500;
+    }
+
+    /// Proves the digests of a few messages with the hash `H`, written only through the generic
+    /// interface.
+    fn test_hash_gadget<L, H>()
+    where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        L::Instruction: UintInstructions,
+        H: HashGadget<BytesBuilder<L>>,
+        Chip<L>: Plonky2Air<GoldilocksField, 2>,
+    {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hash_gadget", log::Level::Debug);
+
+        let long_msg = vec![0xab; 200];
+        let msgs: [&[u8]; 3] = [b"", b"abc", &long_msg];
+
+        let mut builder = BytesBuilder::<L>::new();
+        let mut input = HashInput::new();
+        for msg in msgs.iter() {
+            builder.hash_pad::<H>(&mut input, msg.len());
+        }
+        let digests = builder.hash::<H>(&input);
+
+        let num_rows = H::num_rows(&input);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        input.write::<BytesBuilder<L>, H>(&msgs, &digests, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_hash_gadget_sha256() {
+        test_hash_gadget::<HashGadgetTest, SHA256>();
+    }
+
+    #[test]
+    fn test_hash_gadget_sha512() {
+        test_hash_gadget::<HashGadgetTest, SHA512>();
+    }
+
+    #[test]
+    fn test_hash_gadget_blake2b() {
+        test_hash_gadget::<HashGadgetTest, BLAKE2BAir<HashGadgetTest>>();
+    }
+
+    #[test]
+    fn test_hash_gadget_keccak256() {
+        test_hash_gadget::<KeccakGadgetTest, KeccakAir<KeccakGadgetTest>>();
+    }
+}
@@ -0,0 +1,273 @@
+use super::{HMACSHA256, IPAD, OPAD};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAir;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves `HMAC-SHA256(key, m)` for every message in `padded_messages`.
+    ///
+    /// `key` is the key block `K0` as sixteen words, see `HMACSHA256::key_block`, and the chunks
+    /// of each message must be padded with `HMACSHA256::pad`. The inner and outer key blocks as
+    /// well as the block holding the inner digest are computed in-circuit. The trace must have at
+    /// least `64 * (num_chunks + 3 * padded_messages.len())` rows, where `num_chunks` is the total
+    /// number of message chunks.
+    ///
+    /// Returns the inner and the outer digests of every message, both must be written by the
+    /// caller. The outer digests are the HMAC values.
+    pub fn hmac_sha256(
+        &mut self,
+        key: &ArrayRegister<U32Register>,
+        padded_messages: &[Vec<ArrayRegister<U32Register>>],
+    ) -> (Vec<SHA256DigestRegister>, Vec<SHA256DigestRegister>) {
+        assert_eq!(key.len(), 16);
+        let num_messages = padded_messages.len();
+
+        let inner_key_block = self.hmac_key_block(key, IPAD);
+        let outer_key_block = self.hmac_key_block(key, OPAD);
+
+        // The inner hashes of all messages come first, followed by the outer hashes.
+        let mut chunks = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for padded_msg in padded_messages.iter() {
+            chunks.push(inner_key_block);
+            chunks.extend_from_slice(padded_msg);
+            end_bits_values.extend((0..padded_msg.len()).map(|_| L::Field::ZERO));
+            end_bits_values.push(L::Field::ONE);
+            digest_indices_values.push(L::Field::from_canonical_usize(chunks.len() - 1));
+        }
+        let digest_blocks = (0..num_messages)
+            .map(|_| self.alloc_array_public_unchecked::<U32Register>(16))
+            .collect::<Vec<_>>();
+        for digest_block in digest_blocks.iter() {
+            chunks.push(outer_key_block);
+            chunks.push(*digest_block);
+            end_bits_values.extend([L::Field::ZERO, L::Field::ONE]);
+            digest_indices_values.push(L::Field::from_canonical_usize(chunks.len() - 1));
+        }
+
+        // The block of the outer hash following the key is the inner digest, padded as the end of
+        // a message of `64 + 32` bytes. The blocks must be set before they are stored, so the
+        // digests are allocated first.
+        let digests = (0..2 * num_messages)
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        let (inner_digests, outer_digests) = digests.split_at(num_messages);
+        let padding = HMACSHA256::pad(&[0u8; 32]);
+        let padding = self.constant_array::<U32Register>(
+            &padding[8..]
+                .iter()
+                .map(|word| u32_to_le_field_bytes(*word))
+                .collect::<Vec<_>>(),
+        );
+        for (block, digest) in digest_blocks.iter().zip(inner_digests.iter()) {
+            for (word, digest_word) in block.iter().zip(digest.as_array().iter()) {
+                self.set_to_expression(&word, digest_word.expr());
+            }
+            for (word, padding_word) in block.iter().skip(8).zip(padding.iter()) {
+                self.set_to_expression(&word, padding_word.expr());
+            }
+        }
+
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices = self.constant_array::<ElementRegister>(&digest_indices_values);
+        SHA256::sha_with_digests(
+            self,
+            &chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &digests,
+        );
+
+        (inner_digests.to_vec(), outer_digests.to_vec())
+    }
+
+    /// Computes the key block XORed with `pad` in every byte.
+    fn hmac_key_block(
+        &mut self,
+        key: &ArrayRegister<U32Register>,
+        pad: u8,
+    ) -> ArrayRegister<U32Register> {
+        let pad_byte = self.constant::<ByteRegister>(&L::Field::from_canonical_u8(pad));
+        let key_block = self.alloc_array_public_unchecked::<U32Register>(key.len());
+        for (word, key_word) in key.iter().zip(key_block.iter()) {
+            for (byte, key_byte) in word.to_le_bytes().iter().zip(key_word.to_le_bytes().iter()) {
+                let xor = ByteOperation::Xor(byte, pad_byte, key_byte);
+                self.api
+                    .set_public_inputs_byte_operation(&xor, &mut self.operations);
+            }
+        }
+        key_block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_from_le_field_bytes;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HMACSHA256Test;
+
+    impl AirParameters for HMACSHA256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1200;
+        const EXTENDED_COLUMNS: usize = 700;
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hmac_sha256", log::Level::Info);
+
+        let key = b"key";
+        let msgs = [
+            b"The quick brown fox jumps over the lazy dog".to_vec(),
+            b"".to_vec(),
+            vec![b'a'; 100],
+        ];
+        let expected_digests = [
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            "5d5d139563c95b5967b9bd9a8c9b233a9dedb45072794cd232dc1b74832607d0",
+            "d5f24a2f72b5b5287a7880151d68aa3f0548d1855d7df4da177a9b4007edb9db",
+        ];
+
+        let padded_messages_values = msgs
+            .iter()
+            .map(|msg| HMACSHA256::pad(msg))
+            .collect::<Vec<_>>();
+        let num_rounds = padded_messages_values
+            .iter()
+            .map(|padded_msg| padded_msg.len() / 16 + 3)
+            .sum::<usize>();
+
+        let mut builder = BytesBuilder::<HMACSHA256Test>::new();
+        let key_register = builder.alloc_array_public::<U32Register>(16);
+        let padded_messages = padded_messages_values
+            .iter()
+            .map(|padded_msg| {
+                (0..padded_msg.len() / 16)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let (inner_digests, outer_digests) = builder.hmac_sha256(&key_register, &padded_messages);
+
+        let num_rows = 1 << log2_ceil(64 * num_rounds);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        writer.write_array(
+            &key_register,
+            HMACSHA256::key_block(key).map(u32_to_le_field_bytes),
+        );
+        for (((chunks, values), msg), (inner, outer)) in padded_messages
+            .iter()
+            .zip(padded_messages_values.iter())
+            .zip(msgs.iter())
+            .zip(inner_digests.iter().zip(outer_digests.iter()))
+        {
+            for (chunk, value) in chunks.iter().zip(values.chunks_exact(16)) {
+                writer.write_array(chunk, value.iter().map(|w| u32_to_le_field_bytes(*w)));
+            }
+            let inner: ArrayRegister<U32Register> = (*inner).into();
+            writer.write_array(
+                &inner,
+                HMACSHA256::inner_digest(key, msg).map(u32_to_le_field_bytes),
+            );
+            let outer: ArrayRegister<U32Register> = (*outer).into();
+            writer.write_array(
+                &outer,
+                HMACSHA256::hmac(key, msg).map(u32_to_le_field_bytes),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, expected) in outer_digests.iter().zip(expected_digests) {
+            let digest: ArrayRegister<U32Register> = (*digest).into();
+            let value = writer
+                .read_array::<_, 8>(&digest)
+                .map(|word| u32_from_le_field_bytes(&word));
+            assert_eq!(value, SHA256::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
@@ -0,0 +1,12 @@
+pub mod builder;
+pub mod pure;
+
+/// HMAC instantiated with SHA-256.
+pub struct HMACSHA256;
+
+/// The number of bytes of a SHA-256 block, which is also the length of the padded key.
+pub const BLOCK_LEN: usize = 64;
+/// The byte XORed into the key of the inner hash.
+pub const IPAD: u8 = 0x36;
+/// The byte XORed into the key of the outer hash.
+pub const OPAD: u8 = 0x5c;
@@ -0,0 +1,80 @@
+use super::{BLOCK_LEN, HMACSHA256, IPAD, OPAD};
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha256::SHA256;
+
+impl HMACSHA256 {
+    /// The key block `K0` of a key: keys longer than a block are hashed first, then the key is
+    /// padded with zeros to a full block.
+    pub fn key_block(key: &[u8]) -> [u32; 16] {
+        let mut key_bytes = [0u8; BLOCK_LEN];
+        if key.len() > BLOCK_LEN {
+            let key_hash = SHA256::digest(key);
+            for (bytes, word) in key_bytes.chunks_exact_mut(4).zip(key_hash.iter()) {
+                bytes.copy_from_slice(&word.to_be_bytes());
+            }
+        } else {
+            key_bytes[..key.len()].copy_from_slice(key);
+        }
+        core::array::from_fn(|i| {
+            u32::from_be_bytes(key_bytes[4 * i..4 * i + 4].try_into().unwrap())
+        })
+    }
+
+    /// Pads a message as the continuation of the inner key block.
+    pub fn pad(msg: &[u8]) -> Vec<u32> {
+        SHA256::tagged_pad(msg)
+    }
+
+    /// The digest of the inner hash `SHA256((K0 ^ ipad) || msg)`.
+    pub fn inner_digest(key: &[u8], msg: &[u8]) -> [u32; 8] {
+        let inner_block = Self::key_block(key).map(|word| word ^ u32::from_be_bytes([IPAD; 4]));
+        Self::pad(msg).chunks_exact(16).fold(
+            Self::compress(SHA256::INITIAL_HASH, &inner_block),
+            Self::compress,
+        )
+    }
+
+    /// Computes `HMAC-SHA256(key, msg)` as eight big-endian words.
+    pub fn hmac(key: &[u8], msg: &[u8]) -> [u32; 8] {
+        let outer_block = Self::key_block(key).map(|word| word ^ u32::from_be_bytes([OPAD; 4]));
+        let mut digest_block = Self::pad(&[0u8; 32]);
+        digest_block[..8].copy_from_slice(&Self::inner_digest(key, msg));
+        Self::compress(
+            Self::compress(SHA256::INITIAL_HASH, &outer_block),
+            &digest_block,
+        )
+    }
+
+    fn compress(state: [u32; 8], chunk: &[u32]) -> [u32; 8] {
+        SHA256::process(state, &SHA256::pre_process(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_pure() {
+        let test_vectors: [(&[u8], &[u8], &str); 3] = [
+            (
+                b"key",
+                b"The quick brown fox jumps over the lazy dog",
+                "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            ),
+            (
+                b"",
+                b"",
+                "b613679a0814d9ec772f95d778c35fc5ff1697c493715653c6c712144292c5ad",
+            ),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+        for (key, msg, expected) in test_vectors {
+            assert_eq!(HMACSHA256::hmac(key, msg), SHA256::decode(expected));
+        }
+    }
+}
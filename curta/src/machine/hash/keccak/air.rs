@@ -0,0 +1,395 @@
+use plonky2::util::log2_ceil;
+
+use super::{
+    pi_index, KeccakAir, DIGEST_LANES, NUM_ROUNDS, RATE_LANES, RHO_OFFSETS, ROUND_CONSTANTS,
+    STATE_LANES,
+};
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+const DUMMY_INDEX: u64 = i32::MAX as u64;
+
+impl<L: AirParameters> KeccakAir<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// A bit that is `1` on the last row of every permutation and `0` otherwise.
+    fn cycle_end_bit(builder: &mut BytesBuilder<L>) -> BitRegister {
+        let cycle_8 = builder.cycle(3);
+        let loop_3 = builder.api.loop_instr(3);
+        builder.mul(loop_3.get_iteration_reg(2), cycle_8.end_bit)
+    }
+
+    /// Stores one bit per permutation, readable on every row of the permutation.
+    ///
+    /// The bits of the real permutations are given by `values`, the dummy permutations at the end
+    /// of the trace get `dummy_value`.
+    fn block_bits(
+        builder: &mut BytesBuilder<L>,
+        values: impl Iterator<Item = BitRegister>,
+        dummy_value: BitRegister,
+        num_blocks: usize,
+        length_last_block: usize,
+    ) -> Slice<BitRegister> {
+        let reg_cycle_length = builder.constant(&L::Field::from_canonical_usize(NUM_ROUNDS));
+        let reg_last_length = builder.constant(&L::Field::from_canonical_usize(length_last_block));
+
+        let slice = builder.uninit_slice();
+        let mut num_values = 0;
+        for (i, value) in values.enumerate() {
+            builder.store(
+                &slice.get(i),
+                value,
+                &Time::zero(),
+                Some(reg_cycle_length),
+                None,
+                None,
+            );
+            num_values += 1;
+        }
+        for i in num_values..num_blocks - 1 {
+            builder.store(
+                &slice.get(i),
+                dummy_value,
+                &Time::zero(),
+                Some(reg_cycle_length),
+                None,
+                None,
+            );
+        }
+        builder.store(
+            &slice.get(num_blocks - 1),
+            dummy_value,
+            &Time::zero(),
+            Some(reg_last_length),
+            None,
+            None,
+        );
+        slice
+    }
+
+    /// Hashes the messages given by `padded_chunks` with Keccak-256.
+    ///
+    /// Every chunk is a block of `RATE_LANES` little-endian lanes and each permutation takes
+    /// `NUM_ROUNDS` rows, so the trace must have `(24 * padded_chunks.len()).next_power_of_two()`
+    /// rows. `end_bits[i]` marks the last block of a message and `digest_bits[i]` the blocks whose
+    /// output is a digest, whose block index is given in `digest_indices`. Returns the four digest
+    /// lanes for every entry of `digest_indices`.
+    pub fn keccak256(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        Self::sponge(
+            builder,
+            RATE_LANES,
+            DIGEST_LANES,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+        )
+    }
+
+    /// Applies the Keccak-f[1600] permutation to each of `states`, without any padding.
+    ///
+    /// Every state is given by `STATE_LANES` little-endian lanes, which must be public registers.
+    /// Each permutation takes `NUM_ROUNDS` rows, so the trace must have
+    /// `(24 * states.len()).next_power_of_two()` rows. Returns the permuted states, whose values
+    /// must be written by the prover before the global instructions.
+    pub fn keccak_f(
+        builder: &mut BytesBuilder<L>,
+        states: &[ArrayRegister<U64Register>],
+    ) -> Vec<ArrayRegister<U64Register>> {
+        // A sponge whose rate is the whole state and which is reset after every block absorbs
+        // each state into the zero state and outputs its permutation.
+        let num_states = states.len();
+        let ones = builder.constant_array::<BitRegister>(&vec![L::Field::ONE; num_states]);
+        let indices = builder.constant_array::<ElementRegister>(
+            &(0..num_states)
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        Self::sponge(
+            builder,
+            STATE_LANES,
+            STATE_LANES,
+            states,
+            &ones,
+            &ones,
+            &indices,
+        )
+    }
+
+    /// A Keccak-f[1600] sponge absorbing blocks of `rate_lanes` lanes.
+    ///
+    /// The inputs are laid out as in `keccak256`, with blocks of `rate_lanes` lanes. The state is
+    /// reset after every block flagged by `end_bits`, and the first `output_lanes` lanes of the
+    /// state after every block flagged by `digest_bits` are returned. Squeezing more than one
+    /// block of output is done by absorbing zero blocks.
+    pub fn sponge(
+        builder: &mut BytesBuilder<L>,
+        rate_lanes: usize,
+        output_lanes: usize,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        assert!(
+            rate_lanes > 0 && rate_lanes <= STATE_LANES,
+            "The rate must be between 1 and 25 lanes"
+        );
+        assert!(
+            output_lanes <= rate_lanes,
+            "Cannot output more than the rate per block"
+        );
+        let num_real_blocks = padded_chunks.len();
+        assert_eq!(end_bits.len(), num_real_blocks);
+        assert_eq!(digest_bits.len(), num_real_blocks);
+        for chunk in padded_chunks.iter() {
+            assert_eq!(
+                chunk.len(),
+                rate_lanes,
+                "Blocks must have `rate_lanes` lanes"
+            );
+        }
+
+        let degree_log = log2_ceil(num_real_blocks * NUM_ROUNDS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        let num_rows = 1 << degree_log;
+        // The last permutation is cut short by the end of the trace.
+        let num_dummy_blocks = num_rows / NUM_ROUNDS + 1 - num_real_blocks;
+        let length_last_block = num_rows % NUM_ROUNDS;
+        let num_blocks = num_real_blocks + num_dummy_blocks;
+
+        // Store the round constants, each read once in every permutation.
+        let num_blocks_element = builder.constant(&L::Field::from_canonical_usize(num_blocks));
+        let num_blocks_minus_one =
+            builder.constant(&L::Field::from_canonical_usize(num_blocks - 1));
+        let round_constant_values =
+            builder.constant_array::<U64Register>(&ROUND_CONSTANTS.map(u64_to_le_field_bytes));
+        let round_constants = builder.uninit_slice();
+        for i in 0..NUM_ROUNDS {
+            let multiplicity = if i < length_last_block {
+                num_blocks_element
+            } else {
+                num_blocks_minus_one
+            };
+            builder.store(
+                &round_constants.get(i),
+                round_constant_values.get(i),
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+
+        // Store the lanes of the blocks. On every row each lane of the rate is read, either from
+        // the block at the start of a permutation or from a dummy zero lane otherwise.
+        let blocks = builder.uninit_slice();
+        for (i, chunk) in padded_chunks.iter().enumerate() {
+            for (j, lane) in chunk.iter().enumerate() {
+                builder.store(
+                    &blocks.get(rate_lanes * i + j),
+                    lane,
+                    &Time::zero(),
+                    None,
+                    None,
+                    None,
+                );
+            }
+        }
+        assert!((rate_lanes * num_real_blocks) < DUMMY_INDEX as usize);
+        let zero_lane = builder.constant::<U64Register>(&u64_to_le_field_bytes(0));
+        let dummy_index = builder.constant(&L::Field::from_canonical_u64(DUMMY_INDEX));
+        let num_dummy_reads = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+            rate_lanes * (num_rows - num_real_blocks),
+        ));
+        builder.store(
+            &blocks.get(DUMMY_INDEX as usize),
+            zero_lane,
+            &Time::zero(),
+            Some(num_dummy_reads),
+            None,
+            None,
+        );
+
+        let one = builder.constant::<BitRegister>(&L::Field::ONE);
+        let zero = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let end_bit_slice = Self::block_bits(
+            builder,
+            end_bits.iter(),
+            zero,
+            num_blocks,
+            length_last_block,
+        );
+        let digest_bit_slice = Self::block_bits(
+            builder,
+            digest_bits.iter(),
+            zero,
+            num_blocks,
+            length_last_block,
+        );
+        let is_dummy_slice = Self::block_bits(
+            builder,
+            (0..num_real_blocks).map(|_| zero),
+            one,
+            num_blocks,
+            length_last_block,
+        );
+
+        let cycle_end_bit = Self::cycle_end_bit(builder);
+        let process_id = builder.process_id(NUM_ROUNDS, cycle_end_bit);
+        let clk = builder.clk;
+        let index = builder.expression(
+            clk.expr() - process_id.expr() * L::Field::from_canonical_usize(NUM_ROUNDS),
+        );
+        let is_dummy = builder.load(
+            &is_dummy_slice.get_at(process_id),
+            &Time::zero(),
+            None,
+            None,
+        );
+        let end_bit = builder.load(&end_bit_slice.get_at(process_id), &Time::zero(), None, None);
+        let digest_bit = builder.load(
+            &digest_bit_slice.get_at(process_id),
+            &Time::zero(),
+            None,
+            None,
+        );
+
+        // The block is absorbed on the first row of every real permutation.
+        let is_absorb = builder.alloc::<BitRegister>();
+        builder.set_to_expression_first_row(&is_absorb, L::Field::ONE.into());
+        builder.set_to_expression_transition(&is_absorb.next(), cycle_end_bit.expr());
+        let absorb_flag = builder.expression::<BitRegister>(is_absorb.expr() * is_dummy.not_expr());
+        let block = (0..rate_lanes)
+            .map(|j| {
+                let lane_index = builder.expression(
+                    absorb_flag.expr()
+                        * (process_id.expr() * L::Field::from_canonical_usize(rate_lanes)
+                            + L::Field::from_canonical_usize(j))
+                        + absorb_flag.not_expr() * dummy_index.expr(),
+                );
+                builder.load(&blocks.get_at(lane_index), &Time::zero(), None, None)
+            })
+            .collect::<Vec<U64Register>>();
+
+        // The state at the start of the round, before absorbing the block. It is zero at the
+        // start of every message.
+        let state = builder.alloc_array::<U64Register>(STATE_LANES);
+        for lane in state.iter() {
+            builder.set_to_expression_first_row(&lane, zero_lane.expr());
+        }
+        let round_input = state
+            .iter()
+            .enumerate()
+            .map(|(i, lane)| {
+                if i < rate_lanes {
+                    builder.xor(&lane, &block[i])
+                } else {
+                    lane
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let round_constant =
+            builder.load(&round_constants.get_at(index), &Time::zero(), None, None);
+        let round_output = Self::keccak_round(builder, &round_input, round_constant);
+
+        let reset = builder.expression::<BitRegister>(cycle_end_bit.expr() * end_bit.expr());
+        for (lane, output) in state.iter().zip(round_output.iter()) {
+            builder.set_to_expression_transition(&lane.next(), output.expr() * reset.not_expr());
+        }
+
+        // Store the digests of the permutations flagged by the digest bits.
+        let state_ptr = builder.uninit_slice();
+        let hash_state_public = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<U64Register>(output_lanes))
+            .collect::<Vec<_>>();
+        for (i, h) in digest_indices.iter().zip(hash_state_public.iter()) {
+            for (j, lane) in h.iter().enumerate() {
+                builder.free(&state_ptr.get(j), lane, &Time::from_element(i));
+            }
+        }
+        let digest_flag =
+            builder.expression(cycle_end_bit.expr() * is_dummy.not_expr() * digest_bit.expr());
+        for (j, lane) in round_output[..output_lanes].iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                *lane,
+                &Time::from_element(process_id),
+                Some(digest_flag),
+                None,
+                None,
+            );
+        }
+
+        hash_state_public
+    }
+
+    /// A round of Keccak-f[1600] applied to the lanes `a` with round constant `rc`.
+    pub fn keccak_round(
+        builder: &mut BytesBuilder<L>,
+        a: &[U64Register],
+        rc: U64Register,
+    ) -> Vec<U64Register> {
+        assert_eq!(a.len(), STATE_LANES);
+
+        // Theta.
+        let c = (0..5)
+            .map(|x| {
+                let mut c = builder.xor(&a[x], &a[x + 5]);
+                for y in 2..5 {
+                    c = builder.xor(&c, &a[x + 5 * y]);
+                }
+                c
+            })
+            .collect::<Vec<_>>();
+        let d = (0..5)
+            .map(|x| {
+                let c_rotated = builder.rotate_right(c[(x + 1) % 5], 63);
+                builder.xor(&c[(x + 4) % 5], &c_rotated)
+            })
+            .collect::<Vec<_>>();
+
+        // Rho and pi.
+        let mut b = a.to_vec();
+        for (i, lane) in a.iter().enumerate() {
+            let theta = builder.xor(lane, &d[i % 5]);
+            b[pi_index(i)] = match RHO_OFFSETS[i] {
+                0 => theta,
+                offset => builder.rotate_right(theta, 64 - offset as usize),
+            };
+        }
+
+        // Chi.
+        let mut output = (0..STATE_LANES)
+            .map(|i| {
+                let (x, y) = (i % 5, i / 5);
+                let not_b = builder.not(b[(x + 1) % 5 + 5 * y]);
+                let and = builder.and(&not_b, &b[(x + 2) % 5 + 5 * y]);
+                builder.xor(&b[i], &and)
+            })
+            .collect::<Vec<_>>();
+
+        // Iota.
+        output[0] = builder.xor(&output[0], &rc);
+        output
+    }
+}
@@ -0,0 +1,54 @@
+pub mod air;
+pub mod builder;
+pub mod pure;
+
+pub struct KeccakAir<L> {
+    _marker: core::marker::PhantomData<L>,
+}
+
+/// The number of rounds of the Keccak-f[1600] permutation, each taking a row of the trace.
+pub const NUM_ROUNDS: usize = 24;
+/// The number of 64-bit lanes of the state.
+pub const STATE_LANES: usize = 25;
+/// The number of lanes absorbed per block by Keccak-256, i.e. a rate of 136 bytes.
+pub const RATE_LANES: usize = 17;
+/// The number of lanes of a Keccak-256 digest.
+pub const DIGEST_LANES: usize = 4;
+
+pub const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation offsets of the rho step, indexed by lane `x + 5 * y`.
+pub const RHO_OFFSETS: [u32; STATE_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The lane that the lane `x + 5 * y` is moved to by the pi step, which is `y + 5 * (2x + 3y)`.
+pub const fn pi_index(lane: usize) -> usize {
+    let (x, y) = (lane % 5, lane / 5);
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
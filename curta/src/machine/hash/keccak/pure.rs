@@ -0,0 +1,108 @@
+use super::{pi_index, DIGEST_LANES, RATE_LANES, RHO_OFFSETS, ROUND_CONSTANTS, STATE_LANES};
+
+pub struct KeccakPure;
+
+impl KeccakPure {
+    /// Applies a single round of Keccak-f[1600] with round constant `rc` to `state`.
+    pub fn round(state: &[u64; STATE_LANES], rc: u64) -> [u64; STATE_LANES] {
+        // Theta.
+        let c: [u64; 5] = core::array::from_fn(|x| {
+            state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20]
+        });
+        let d: [u64; 5] = core::array::from_fn(|x| c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1));
+        let theta: [u64; STATE_LANES] = core::array::from_fn(|i| state[i] ^ d[i % 5]);
+
+        // Rho and pi.
+        let mut b = [0u64; STATE_LANES];
+        for (i, lane) in theta.iter().enumerate() {
+            b[pi_index(i)] = lane.rotate_left(RHO_OFFSETS[i]);
+        }
+
+        // Chi.
+        let mut next: [u64; STATE_LANES] = core::array::from_fn(|i| {
+            let (x, y) = (i % 5, i / 5);
+            b[i] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y])
+        });
+
+        // Iota.
+        next[0] ^= rc;
+        next
+    }
+
+    /// Applies the Keccak-f[1600] permutation to `state`.
+    pub fn keccak_f(state: &mut [u64; STATE_LANES]) {
+        for rc in ROUND_CONSTANTS.iter() {
+            *state = Self::round(state, *rc);
+        }
+    }
+
+    /// Pads `msg` with the Keccak padding rule, returning the padded message as little-endian
+    /// lanes. The number of lanes is a multiple of `RATE_LANES`.
+    pub fn pad(msg: &[u8]) -> Vec<u64> {
+        Self::pad_sponge(msg, RATE_LANES, 0x01)
+    }
+
+    /// Pads `msg` for a sponge absorbing `rate_lanes` lanes per block, with the domain separation
+    /// bits given by `domain`. The padding is `domain || 0* || 1`, as in FIPS 202 where `domain`
+    /// includes the first bit of the `pad10*1` rule.
+    pub fn pad_sponge(msg: &[u8], rate_lanes: usize, domain: u8) -> Vec<u64> {
+        let rate = 8 * rate_lanes;
+        let mut padded_msg = msg.to_vec();
+        let num_blocks = msg.len() / rate + 1;
+        padded_msg.resize(num_blocks * rate, 0);
+        padded_msg[msg.len()] ^= domain;
+        padded_msg[num_blocks * rate - 1] ^= 0x80;
+
+        padded_msg
+            .chunks_exact(8)
+            .map(|lane| u64::from_le_bytes(lane.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Absorbs the padded `blocks` of a message and returns the resulting state.
+    pub fn absorb(blocks: &[u64]) -> [u64; STATE_LANES] {
+        Self::absorb_sponge(blocks, RATE_LANES)
+    }
+
+    /// Absorbs `blocks` of `rate_lanes` lanes each, applying the permutation after every block.
+    pub fn absorb_sponge(blocks: &[u64], rate_lanes: usize) -> [u64; STATE_LANES] {
+        assert_eq!(blocks.len() % rate_lanes, 0);
+        let mut state = [0u64; STATE_LANES];
+        for block in blocks.chunks_exact(rate_lanes) {
+            for (lane, word) in state.iter_mut().zip(block.iter()) {
+                *lane ^= word;
+            }
+            Self::keccak_f(&mut state);
+        }
+        state
+    }
+
+    /// The Keccak-256 digest of `msg`, as used by Ethereum.
+    pub fn keccak256(msg: &[u8]) -> [u8; 32] {
+        let state = Self::absorb(&Self::pad(msg));
+        let mut digest = [0u8; 32];
+        for (chunk, lane) in digest.chunks_exact_mut(8).zip(state[..DIGEST_LANES].iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak256_pure() {
+        assert_eq!(
+            hex::encode(KeccakPure::keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(KeccakPure::keccak256(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+        // A message of exactly one block is padded to two blocks.
+        assert_eq!(KeccakPure::pad(&[0u8; 136]).len(), 2 * RATE_LANES);
+    }
+}
@@ -0,0 +1,301 @@
+use plonky2::util::log2_ceil;
+
+use super::{message_index, shift, MD5Air, BLOCK_WORDS, INITIAL_HASH, K, NUM_STEPS, STATE_WORDS};
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+const DUMMY_INDEX: u64 = i32::MAX as u64;
+
+/// The number of bits of the rotation amounts, which are all smaller than 32.
+const SHIFT_BITS: usize = 5;
+
+impl<L: AirParameters> MD5Air<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Stores `values` in a slice, each value read `multiplicity` times.
+    fn values_slice<V: MemoryValue>(
+        builder: &mut BytesBuilder<L>,
+        values: impl Iterator<Item = V>,
+        multiplicity: ElementRegister,
+    ) -> Slice<V> {
+        let slice = builder.uninit_slice();
+        for (i, value) in values.enumerate() {
+            builder.store(
+                &slice.get(i),
+                value,
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+        slice
+    }
+
+    /// Hashes the messages given by `padded_chunks` with MD5.
+    ///
+    /// Every chunk is a block of sixteen little-endian words as given by `MD5Pure::pad`, and each
+    /// compression takes `NUM_STEPS` rows, so the trace must have
+    /// `64 * padded_chunks.len().next_power_of_two()` rows. `end_bits[i]` marks the last block of a
+    /// message and `digest_bits[i]` the blocks whose output is a digest, whose block index is given
+    /// in `digest_indices`. Returns the four digest words for every entry of `digest_indices`.
+    pub fn md5(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U32Register>> {
+        let num_real_blocks = padded_chunks.len();
+        assert_eq!(end_bits.len(), num_real_blocks);
+        assert_eq!(digest_bits.len(), num_real_blocks);
+        for chunk in padded_chunks.iter() {
+            assert_eq!(chunk.len(), BLOCK_WORDS, "Blocks must have sixteen words");
+        }
+
+        // The number of steps is a power of two, so every compression fits in the trace.
+        let degree_log = log2_ceil(num_real_blocks * NUM_STEPS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        let num_rows = 1 << degree_log;
+        let num_blocks = num_rows / NUM_STEPS;
+
+        let one = builder.constant::<BitRegister>(&L::Field::ONE);
+        let zero = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let bit = |value: bool| if value { one } else { zero };
+
+        // Store the parameters of every step, each read once in every compression.
+        let num_blocks_element =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_blocks));
+        let message_indices = builder.constant_array::<ElementRegister>(
+            &(0..BLOCK_WORDS)
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let k_values = builder.constant_array::<U32Register>(&K.map(u32_to_le_field_bytes));
+        let message_index_slice = Self::values_slice(
+            builder,
+            (0..NUM_STEPS).map(|i| message_indices.get(message_index(i))),
+            num_blocks_element,
+        );
+        let k_slice = Self::values_slice(builder, k_values.iter(), num_blocks_element);
+        let shift_bit_slices = core::array::from_fn::<_, SHIFT_BITS, _>(|b| {
+            Self::values_slice(
+                builder,
+                (0..NUM_STEPS).map(|i| bit(((shift(i) >> b) & 1) == 1)),
+                num_blocks_element,
+            )
+        });
+        let function_bit_slices = core::array::from_fn::<_, 4, _>(|f| {
+            Self::values_slice(
+                builder,
+                (0..NUM_STEPS).map(|i| bit(i / 16 == f)),
+                num_blocks_element,
+            )
+        });
+
+        // Store the parameters of every block, read on every row of its compression.
+        let reg_cycle_length =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(NUM_STEPS));
+        let padding = |dummy_value: BitRegister| {
+            core::iter::repeat(dummy_value).take(num_blocks - num_real_blocks)
+        };
+        let end_bit_slice = Self::values_slice(
+            builder,
+            end_bits.iter().chain(padding(zero)),
+            reg_cycle_length,
+        );
+        let digest_bit_slice = Self::values_slice(
+            builder,
+            digest_bits.iter().chain(padding(zero)),
+            reg_cycle_length,
+        );
+        let is_dummy_slice = Self::values_slice(
+            builder,
+            (0..num_real_blocks).map(|_| zero).chain(padding(one)),
+            reg_cycle_length,
+        );
+
+        // Store the words of the blocks, each read once in every round. The dummy compressions
+        // read a zero word instead.
+        let words = builder.uninit_slice();
+        let num_word_reads = builder.constant(&L::Field::from_canonical_usize(4));
+        for (i, chunk) in padded_chunks.iter().enumerate() {
+            for (j, word) in chunk.iter().enumerate() {
+                builder.store(
+                    &words.get(BLOCK_WORDS * i + j),
+                    word,
+                    &Time::zero(),
+                    Some(num_word_reads),
+                    None,
+                    None,
+                );
+            }
+        }
+        assert!((BLOCK_WORDS * num_real_blocks) < DUMMY_INDEX as usize);
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let dummy_index = builder.constant(&L::Field::from_canonical_u64(DUMMY_INDEX));
+        let num_dummy_reads = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+            num_rows - NUM_STEPS * num_real_blocks,
+        ));
+        builder.store(
+            &words.get(DUMMY_INDEX as usize),
+            zero_word,
+            &Time::zero(),
+            Some(num_dummy_reads),
+            None,
+            None,
+        );
+
+        let cycle_end_bit = builder.cycle(6).end_bit;
+        let process_id = builder.process_id(NUM_STEPS, cycle_end_bit);
+        let clk = builder.clk;
+        let step = builder
+            .expression(clk.expr() - process_id.expr() * L::Field::from_canonical_usize(NUM_STEPS));
+        let [end_bit, digest_bit, is_dummy] = [end_bit_slice, digest_bit_slice, is_dummy_slice]
+            .map(|slice| builder.load(&slice.get_at(process_id), &Time::zero(), None, None));
+        let word_offset =
+            builder.load(&message_index_slice.get_at(step), &Time::zero(), None, None);
+        let k = builder.load(&k_slice.get_at(step), &Time::zero(), None, None);
+        let shift_bits = shift_bit_slices
+            .map(|slice| builder.load(&slice.get_at(step), &Time::zero(), None, None));
+        let function_bits = function_bit_slices
+            .map(|slice| builder.load(&slice.get_at(step), &Time::zero(), None, None));
+
+        let word_index = builder.expression(
+            is_dummy.not_expr()
+                * (process_id.expr() * L::Field::from_canonical_usize(BLOCK_WORDS)
+                    + word_offset.expr())
+                + is_dummy.expr() * dummy_index.expr(),
+        );
+        let x = builder.load(&words.get_at(word_index), &Time::zero(), None, None);
+
+        // The state before the compression, and the working state `(a, b, c, d)` before the step.
+        let initial_hash =
+            builder.constant_array::<U32Register>(&INITIAL_HASH.map(u32_to_le_field_bytes));
+        let h = builder.alloc_array::<U32Register>(STATE_WORDS);
+        let state = builder.alloc_array::<U32Register>(STATE_WORDS);
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_first_row(&h.get(i), initial_hash.get(i).expr());
+            builder.set_to_expression_first_row(&state.get(i), initial_hash.get(i).expr());
+        }
+
+        let [a, b, c, d] = core::array::from_fn(|i| state.get(i));
+        let f = Self::boolean_function(builder, b, c, d, &function_bits);
+        let sum = builder.add(a, f);
+        let sum = builder.add(sum, k);
+        let sum = builder.add(sum, x);
+        let rotated = Self::rotate_left(builder, sum, &shift_bits);
+        let new_b = builder.add(b, rotated);
+        let state_output = [d, new_b, b, c];
+        let h_output = (0..STATE_WORDS)
+            .map(|i| builder.add(h.get(i), state_output[i]))
+            .collect::<Vec<_>>();
+
+        // At the end of a compression, the state is updated and reset after the last block of a
+        // message. The working state starts from the state.
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_transition(
+                &h.get(i).next(),
+                h.get(i).expr() * cycle_end_bit.not_expr()
+                    + (h_output[i].expr() * end_bit.not_expr()
+                        + initial_hash.get(i).expr() * end_bit.expr())
+                        * cycle_end_bit.expr(),
+            );
+            builder.set_to_expression_transition(
+                &state.get(i).next(),
+                state_output[i].expr() * cycle_end_bit.not_expr()
+                    + h.get(i).next().expr() * cycle_end_bit.expr(),
+            );
+        }
+
+        // Store the digests of the compressions flagged by the digest bits.
+        let state_ptr = builder.uninit_slice();
+        let hash_state_public = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<U32Register>(STATE_WORDS))
+            .collect::<Vec<_>>();
+        for (i, h) in digest_indices.iter().zip(hash_state_public.iter()) {
+            for (j, word) in h.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(i));
+            }
+        }
+        let digest_flag =
+            builder.expression(cycle_end_bit.expr() * is_dummy.not_expr() * digest_bit.expr());
+        for (j, word) in h_output.iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                *word,
+                &Time::from_element(process_id),
+                Some(digest_flag),
+                None,
+                None,
+            );
+        }
+
+        hash_state_public
+    }
+
+    /// The boolean function of the round selected by the one-hot `function_bits`.
+    fn boolean_function(
+        builder: &mut BytesBuilder<L>,
+        x: U32Register,
+        y: U32Register,
+        z: U32Register,
+        function_bits: &[BitRegister; 4],
+    ) -> U32Register {
+        let not_x = builder.not(x);
+        let not_z = builder.not(z);
+
+        // (x & y) | (!x & z), where the two terms have no bits in common.
+        let x_and_y = builder.and(&x, &y);
+        let not_x_and_z = builder.and(&not_x, &z);
+        let f_0 = builder.xor(&x_and_y, &not_x_and_z);
+
+        // (x & z) | (y & !z), where the two terms have no bits in common.
+        let x_and_z = builder.and(&x, &z);
+        let y_and_not_z = builder.and(&y, &not_z);
+        let f_1 = builder.xor(&x_and_z, &y_and_not_z);
+
+        // x ^ y ^ z
+        let x_xor_y = builder.xor(&x, &y);
+        let f_2 = builder.xor(&x_xor_y, &z);
+
+        // y ^ (x | !z)
+        let x_or_not_z = builder.not(not_x_and_z);
+        let f_3 = builder.xor(&y, &x_or_not_z);
+
+        let expression = [f_0, f_1, f_2, f_3]
+            .iter()
+            .zip(function_bits.iter())
+            .map(|(f, bit)| f.expr() * bit.expr())
+            .reduce(|acc, term| acc + term)
+            .unwrap();
+        builder.expression(expression)
+    }
+
+    /// Rotates `value` left by the amount whose binary decomposition is `shift_bits`.
+    fn rotate_left(
+        builder: &mut BytesBuilder<L>,
+        value: U32Register,
+        shift_bits: &[BitRegister; SHIFT_BITS],
+    ) -> U32Register {
+        let mut value = value;
+        for (i, bit) in shift_bits.iter().enumerate() {
+            let rotated = builder.rotate_right(value, 32 - (1 << i));
+            value = builder.expression(rotated.expr() * bit.expr() + value.expr() * bit.not_expr());
+        }
+        value
+    }
+}
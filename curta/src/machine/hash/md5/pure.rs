@@ -0,0 +1,88 @@
+use super::{message_index, shift, BLOCK_WORDS, INITIAL_HASH, K, NUM_STEPS, STATE_WORDS};
+
+pub struct MD5Pure;
+
+impl MD5Pure {
+    /// The boolean function of the round `round`.
+    pub fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+        match round {
+            0 => (x & y) | (!x & z),
+            1 => (x & z) | (y & !z),
+            2 => x ^ y ^ z,
+            3 => y ^ (x | !z),
+            _ => unreachable!("MD5 has four rounds"),
+        }
+    }
+
+    /// Pads the message to a whole number of blocks of little-endian words.
+    pub fn pad(msg: &[u8]) -> Vec<u32> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(0x80);
+        while padded_msg.len() % 64 != 56 {
+            padded_msg.push(0);
+        }
+        padded_msg.extend_from_slice(&((msg.len() as u64) * 8).to_le_bytes());
+        padded_msg
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    pub fn compress(h: [u32; STATE_WORDS], x: &[u32]) -> [u32; STATE_WORDS] {
+        assert_eq!(x.len(), BLOCK_WORDS);
+        let mut state = h;
+        for i in 0..NUM_STEPS {
+            let [a, b, c, d] = state;
+            let f = Self::f(i / 16, b, c, d);
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(K[i])
+                .wrapping_add(x[message_index(i)])
+                .rotate_left(shift(i));
+            state = [d, b.wrapping_add(rotated), b, c];
+        }
+        core::array::from_fn(|i| h[i].wrapping_add(state[i]))
+    }
+
+    /// The digest as little-endian words.
+    pub fn digest(msg: &[u8]) -> [u32; STATE_WORDS] {
+        Self::pad(msg)
+            .chunks_exact(BLOCK_WORDS)
+            .fold(INITIAL_HASH, |h, block| Self::compress(h, block))
+    }
+
+    pub fn hash(msg: &[u8]) -> [u8; 16] {
+        let mut hash = [0u8; 16];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(Self::digest(msg)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_md5_pure() {
+        assert_eq!(
+            hex::encode(MD5Pure::hash(b"")),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            hex::encode(MD5Pure::hash(b"abc")),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            hex::encode(MD5Pure::hash(
+                b"The quick brown fox jumps over the lazy dog"
+            )),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        assert_eq!(
+            hex::encode(MD5Pure::hash(&[b'a'; 100])),
+            "36a92cc94a9e0fa21f625f8bfb007adf"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use log::debug;
+use num::{BigUint, Zero};
+use plonky2::util::log2_ceil;
+
+use super::{MiMC, MiMCParameters};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The registers of a batch of MiMC hashes.
+#[derive(Debug, Clone)]
+pub struct MiMCHashGadget<M: MiMCParameters> {
+    pub digests: Vec<FieldRegister<M::Field>>,
+    ciphertexts: Vec<Vec<FieldRegister<M::Field>>>,
+}
+
+pub trait MiMCBuilder: Builder {
+    /// Computes the MiMC block cipher `E_k(x)` for each of `inputs` and the corresponding key of
+    /// `keys`.
+    ///
+    /// The inputs and keys must be public registers. The returned results are public registers
+    /// whose values must be written by the prover, before the global instructions.
+    ///
+    /// Each encryption takes a cycle of `M::NUM_ROUNDS.next_power_of_two()` rows, one round per
+    /// row, and the trace must have `(inputs.len() * cycle_length).next_power_of_two()` rows.
+    fn mimc_encrypt_batch<M: MiMCParameters>(
+        &mut self,
+        inputs: &[FieldRegister<M::Field>],
+        keys: &[FieldRegister<M::Field>],
+    ) -> Vec<FieldRegister<M::Field>>
+    where
+        Self::Instruction: FromFieldInstruction<M::Field>,
+    {
+        let results = (0..inputs.len())
+            .map(|_| self.alloc_public::<FieldRegister<M::Field>>())
+            .collect::<Vec<_>>();
+        self.mimc_encrypt_batch_with_results::<M>(inputs, keys, &results);
+        results
+    }
+
+    /// Same as `mimc_encrypt_batch`, with the results given as already allocated public registers.
+    ///
+    /// The inputs and keys are stored when the global instructions registered here are written,
+    /// so this allows them to be computed from the results by instructions registered before.
+    fn mimc_encrypt_batch_with_results<M: MiMCParameters>(
+        &mut self,
+        inputs: &[FieldRegister<M::Field>],
+        keys: &[FieldRegister<M::Field>],
+        results: &[FieldRegister<M::Field>],
+    ) where
+        Self::Instruction: FromFieldInstruction<M::Field>,
+    {
+        assert_eq!(inputs.len(), keys.len(), "Each input must have a key");
+        assert_eq!(inputs.len(), results.len(), "Each input must have a result");
+        let constants = M::round_constants();
+        assert_eq!(constants.len(), M::NUM_ROUNDS);
+
+        let cycle_length = M::NUM_ROUNDS.next_power_of_two();
+        let num_ops = inputs.len();
+        debug!("AIR degree before padding: {}", num_ops * cycle_length);
+        let degree_log = log2_ceil(num_ops * cycle_length);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        let num_cycles = (1 << degree_log) / cycle_length;
+
+        let zero = Time::zero();
+        let x_ptr = self.uninit_slice::<FieldRegister<M::Field>>();
+        let key_ptr = self.uninit_slice::<FieldRegister<M::Field>>();
+        let result_ptr = self.uninit_slice::<FieldRegister<M::Field>>();
+
+        // The input and the key of an encryption are read once in every row of its cycle.
+        let reads =
+            self.constant::<ElementRegister>(&Self::Field::from_canonical_usize(cycle_length));
+        for (k, ((x, key), result)) in inputs.iter().zip(keys.iter()).zip(results).enumerate() {
+            assert!(!x.is_trace(), "Inputs must be public");
+            assert!(!key.is_trace(), "Keys must be public");
+            assert!(!result.is_trace(), "Results must be public");
+            self.store(&x_ptr.get(k), *x, &zero, Some(reads), None, None);
+            self.store(&key_ptr.get(k), *key, &zero, Some(reads), None, None);
+            self.free(&result_ptr.get(k), *result, &zero);
+        }
+
+        // Dummy operations encrypt zero with the zero key.
+        let zero_element = self.api().fp_zero::<M::Field>();
+        let dummy_result = self
+            .api()
+            .fp_constant(&MiMC::<M>::encrypt(&BigUint::zero(), &BigUint::zero()));
+        for k in num_ops..num_cycles {
+            self.store(&x_ptr.get(k), zero_element, &zero, Some(reads), None, None);
+            self.store(
+                &key_ptr.get(k),
+                zero_element,
+                &zero,
+                Some(reads),
+                None,
+                None,
+            );
+            self.free(&result_ptr.get(k), dummy_result, &zero);
+        }
+
+        // The round constants and a flag of the rows that compute a round, indexed by the
+        // position in the cycle. The rows after the last round keep the state unchanged.
+        let c_table = self.uninit_slice::<FieldRegister<M::Field>>();
+        let active_table = self.uninit_slice::<ElementRegister>();
+        let table_reads =
+            self.constant::<ElementRegister>(&Self::Field::from_canonical_usize(num_cycles));
+        for round in 0..cycle_length {
+            let (constant, active) = match constants.get(round) {
+                Some(constant) => (constant.clone(), Self::Field::ONE),
+                None => (BigUint::zero(), Self::Field::ZERO),
+            };
+            let constant = self.api().fp_constant(&constant);
+            let active = self.constant::<ElementRegister>(&active);
+            self.store(
+                &c_table.get(round),
+                constant,
+                &zero,
+                Some(table_reads),
+                None,
+                None,
+            );
+            self.store(
+                &active_table.get(round),
+                active,
+                &zero,
+                Some(table_reads),
+                None,
+                None,
+            );
+        }
+
+        let cycle = self.cycle(cycle_length.ilog2() as usize);
+        let process_id = self.process_id(cycle_length, cycle.end_bit);
+        let clk = self.clk();
+        let round = self.expression::<ElementRegister>(
+            clk.expr() - process_id.expr() * Self::Field::from_canonical_usize(cycle_length),
+        );
+        let x = self.load(&x_ptr.get_at(process_id), &zero, None, None);
+        let key = self.load(&key_ptr.get_at(process_id), &zero, None, None);
+        let constant = self.load(&c_table.get_at(round), &zero, None, None);
+        let active = self.load(&active_table.get_at(round), &zero, None, None);
+
+        // The state is the input at the beginning of each cycle, and the state computed in the
+        // previous row otherwise.
+        let carry = self.alloc::<FieldRegister<M::Field>>();
+        let state = self.expression::<FieldRegister<M::Field>>(
+            cycle.start_bit.expr() * x.expr()
+                + (ArithmeticExpression::one() - cycle.start_bit.expr()) * carry.expr(),
+        );
+        let shifted = self.add(state, key);
+        let shifted = self.add(shifted, constant);
+        let power = mimc_power(self, shifted, M::EXPONENT);
+        let next_state = self.expression::<FieldRegister<M::Field>>(
+            active.expr() * power.expr()
+                + (ArithmeticExpression::one() - active.expr()) * state.expr(),
+        );
+        self.set_next(&carry, &next_state);
+
+        let result = self.add(next_state, key);
+        self.store(
+            &result_ptr.get_at(process_id),
+            result,
+            &zero,
+            Some(cycle.end_bit.as_element()),
+            None,
+            None,
+        );
+    }
+
+    /// Hashes each of `messages`, starting from the corresponding key of `keys`, see
+    /// `MiMC::hash`.
+    ///
+    /// The message elements and the keys must be public registers. All the encryptions of all
+    /// the messages are proven in a single batch, see `mimc_encrypt_batch` for the number of rows.
+    /// The outputs of the encryptions are written by `MiMCHashGadget::write`.
+    fn mimc_hash_batch<M: MiMCParameters>(
+        &mut self,
+        messages: &[Vec<FieldRegister<M::Field>>],
+        keys: &[FieldRegister<M::Field>],
+    ) -> MiMCHashGadget<M>
+    where
+        Self::Instruction: FromFieldInstruction<M::Field>,
+    {
+        assert_eq!(messages.len(), keys.len(), "Each message must have a key");
+
+        // The key of each encryption is the running value `r` of its message, which is computed
+        // from the ciphertexts before the keys are stored.
+        let ciphertexts = messages
+            .iter()
+            .map(|message| {
+                (0..message.len())
+                    .map(|_| self.alloc_public::<FieldRegister<M::Field>>())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let chains = messages
+            .iter()
+            .zip(keys.iter())
+            .zip(ciphertexts.iter())
+            .map(|((message, key), ciphertexts)| {
+                let mut chain = vec![*key];
+                for (x, ciphertext) in message.iter().zip(ciphertexts.iter()) {
+                    let sum = self.api().fp_add(chain.last().unwrap(), x);
+                    chain.push(self.api().fp_add(&sum, ciphertext));
+                }
+                chain
+            })
+            .collect::<Vec<_>>();
+
+        let inputs = messages.iter().flatten().copied().collect::<Vec<_>>();
+        let encryption_keys = chains
+            .iter()
+            .flat_map(|chain| chain[..chain.len() - 1].iter().copied())
+            .collect::<Vec<_>>();
+        let results = ciphertexts.iter().flatten().copied().collect::<Vec<_>>();
+        self.mimc_encrypt_batch_with_results::<M>(&inputs, &encryption_keys, &results);
+
+        MiMCHashGadget {
+            digests: chains.iter().map(|chain| *chain.last().unwrap()).collect(),
+            ciphertexts,
+        }
+    }
+}
+
+impl<B: Builder> MiMCBuilder for B {}
+
+/// Computes `base^exponent` by square-and-multiply.
+fn mimc_power<B: Builder, P: FieldParameters>(
+    builder: &mut B,
+    base: FieldRegister<P>,
+    exponent: u64,
+) -> FieldRegister<P>
+where
+    B::Instruction: FromFieldInstruction<P>,
+{
+    assert!(exponent > 1, "Exponent must be larger than one");
+    let mut result = base;
+    for i in (0..exponent.ilog2()).rev() {
+        result = builder.mul(result, result);
+        if (exponent >> i) & 1 == 1 {
+            result = builder.mul(result, base);
+        }
+    }
+    result
+}
+
+impl<M: MiMCParameters> MiMCHashGadget<M> {
+    /// Writes the outputs of the encryptions of hashing `messages[i]` with `keys[i]`.
+    ///
+    /// The messages and keys themselves are written by the caller.
+    pub fn write<F: PrimeField64>(
+        &self,
+        messages: &[Vec<BigUint>],
+        keys: &[BigUint],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        assert_eq!(messages.len(), self.ciphertexts.len());
+        assert_eq!(keys.len(), self.ciphertexts.len());
+        let modulus = M::Field::modulus();
+        for ((message, key), registers) in messages.iter().zip(keys).zip(&self.ciphertexts) {
+            assert_eq!(message.len(), registers.len());
+            let mut r = key % &modulus;
+            for (x, register) in message.iter().zip(registers.iter()) {
+                let ciphertext = MiMC::<M>::encrypt(x, &r);
+                writer.write(
+                    register,
+                    &to_u16_le_limbs_polynomial::<F, M::Field>(&ciphertext),
+                );
+                r = (r + x + ciphertext) % &modulus;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::weierstrass::bn254::Bn254ScalarField;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::utils::digits_to_biguint;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::hash::mimc::MiMC7Bn254;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct MiMCTest;
+
+    impl AirParameters for MiMCTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = FpInstruction<Bn254ScalarField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 740;
+        const NUM_FREE_COLUMNS: usize = 16;
+        const EXTENDED_COLUMNS: usize = 1500;
+    }
+
+    /// Proves the hashes of `messages` with `keys`, and returns whether the proof verifies.
+    ///
+    /// If `tamper` is set, the output of the first encryption is replaced by a wrong value.
+    fn prove_mimc_hash(messages: &[Vec<BigUint>], keys: &[BigUint], tamper: bool) -> bool {
+        type F = GoldilocksField;
+        type L = MiMCTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type M = MiMC7Bn254;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let message_registers = messages
+            .iter()
+            .map(|message| {
+                message
+                    .iter()
+                    .map(|_| builder.alloc_public::<FieldRegister<Bn254ScalarField>>())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let key_registers = keys
+            .iter()
+            .map(|_| builder.alloc_public::<FieldRegister<Bn254ScalarField>>())
+            .collect::<Vec<_>>();
+        let gadget = builder.mimc_hash_batch::<M>(&message_registers, &key_registers);
+
+        let num_encryptions = messages.iter().map(|message| message.len()).sum::<usize>();
+        let num_rows = (num_encryptions * M::NUM_ROUNDS.next_power_of_two()).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for ((registers, message), (key_register, key)) in message_registers
+            .iter()
+            .zip(messages)
+            .zip(key_registers.iter().zip(keys))
+        {
+            for (register, x) in registers.iter().zip(message) {
+                writer.write(
+                    register,
+                    &to_u16_le_limbs_polynomial::<F, Bn254ScalarField>(x),
+                );
+            }
+            writer.write(
+                key_register,
+                &to_u16_le_limbs_polynomial::<F, Bn254ScalarField>(key),
+            );
+        }
+        gadget.write(messages, keys, &mut writer);
+        if tamper {
+            writer.write(
+                &gadget.ciphertexts[0][0],
+                &to_u16_le_limbs_polynomial::<F, Bn254ScalarField>(&BigUint::from(1u32)),
+            );
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+
+        for ((digest, message), key) in gadget.digests.iter().zip(messages).zip(keys) {
+            let digits = writer
+                .read(digest)
+                .coefficients
+                .iter()
+                .map(|x| x.as_canonical_u64() as u16)
+                .collect::<Vec<_>>();
+            if !tamper {
+                assert_eq!(digits_to_biguint(&digits), MiMC::<M>::hash(message, key));
+            }
+        }
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_mimc7_hash() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let messages = vec![
+            [1u32, 2, 3].map(BigUint::from).to_vec(),
+            [12u32, 45, 78, 41].map(BigUint::from).to_vec(),
+        ];
+        let keys = [BigUint::from(5u32), BigUint::zero()];
+        assert!(prove_mimc_hash(&messages, &keys, false));
+
+        // A wrong encryption output is rejected.
+        assert!(!prove_mimc_hash(&messages, &keys, true));
+    }
+}
@@ -0,0 +1,59 @@
+//! MiMC over a prime field given by `FieldParameters`.
+//!
+//! The permutation is the MiMC block cipher `E_k(x)`: every round maps the state `x` to
+//! `(x + k + c_i)^e` and the output is `x + k` after the last round. The hash is the
+//! Miyaguchi-Preneel construction `r <- r + x_i + E_r(x_i)` of circomlib's `multiHash`.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use num::{BigUint, Zero};
+
+use crate::chip::ec::weierstrass::bn254::Bn254ScalarField;
+use crate::chip::field::parameters::FieldParameters;
+use crate::machine::hash::keccak::pure::KeccakPure;
+
+pub mod builder;
+pub mod pure;
+
+/// The parameters of a MiMC instance.
+pub trait MiMCParameters: 'static + Send + Sync + Clone + Debug {
+    type Field: FieldParameters;
+
+    const NUM_ROUNDS: usize;
+
+    /// The exponent `e` of the rounds, which must be coprime to `p - 1`.
+    const EXPONENT: u64;
+
+    /// The round constants `c_0, ..., c_{NUM_ROUNDS - 1}`.
+    fn round_constants() -> Vec<BigUint>;
+}
+
+/// The MiMC permutation and hash of the instance `M`.
+#[derive(Debug, Clone, Copy)]
+pub struct MiMC<M>(PhantomData<M>);
+
+/// MiMC-7 with 91 rounds over the scalar field of BN254, as implemented by circomlib's `mimc7`.
+#[derive(Debug, Clone, Copy)]
+pub struct MiMC7Bn254;
+
+impl MiMCParameters for MiMC7Bn254 {
+    type Field = Bn254ScalarField;
+
+    const NUM_ROUNDS: usize = 91;
+
+    const EXPONENT: u64 = 7;
+
+    /// The first constant is zero, the following ones are the iterated Keccak-256 hashes of the
+    /// seed `"mimc"`, starting from the second one.
+    fn round_constants() -> Vec<BigUint> {
+        let modulus = Self::Field::modulus();
+        let mut constants = vec![BigUint::zero()];
+        let mut hash = KeccakPure::keccak256(b"mimc");
+        for _ in 1..Self::NUM_ROUNDS {
+            hash = KeccakPure::keccak256(&hash);
+            constants.push(BigUint::from_bytes_be(&hash) % &modulus);
+        }
+        constants
+    }
+}
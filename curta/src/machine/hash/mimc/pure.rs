@@ -0,0 +1,79 @@
+use num::BigUint;
+
+use super::{MiMC, MiMCParameters};
+use crate::chip::field::parameters::FieldParameters;
+
+impl<M: MiMCParameters> MiMC<M> {
+    /// Computes the MiMC block cipher `E_key(x)`.
+    pub fn encrypt(x: &BigUint, key: &BigUint) -> BigUint {
+        let modulus = M::Field::modulus();
+        let exponent = BigUint::from(M::EXPONENT);
+        let state = M::round_constants()
+            .iter()
+            .fold(x % &modulus, |state, constant| {
+                (state + key + constant).modpow(&exponent, &modulus)
+            });
+        (state + key) % &modulus
+    }
+
+    /// Hashes `inputs` with the Miyaguchi-Preneel construction, starting from `key`.
+    pub fn hash(inputs: &[BigUint], key: &BigUint) -> BigUint {
+        let modulus = M::Field::modulus();
+        inputs.iter().fold(key % &modulus, |r, x| {
+            let ciphertext = Self::encrypt(x, &r);
+            (r + x + ciphertext) % &modulus
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Num;
+
+    use super::*;
+    use crate::machine::hash::mimc::MiMC7Bn254;
+
+    #[test]
+    fn test_mimc7_pure() {
+        let constants = MiMC7Bn254::round_constants();
+        assert_eq!(constants.len(), MiMC7Bn254::NUM_ROUNDS);
+        assert_eq!(
+            constants[1],
+            BigUint::from_str_radix(
+                "2e2ebbb178296b63d88ec198f0976ad98bc1d4eb0d921ddd2eb86cb7e70a98e5",
+                16
+            )
+            .unwrap()
+        );
+
+        // The vector of circomlib's MiMC7 test.
+        let ciphertext = MiMC::<MiMC7Bn254>::encrypt(&BigUint::from(1u32), &BigUint::from(2u32));
+        assert_eq!(
+            ciphertext,
+            BigUint::from_str_radix(
+                "176c6eefc3fdf8d6136002d8e6f7a885bbd1c4e3957b93ddc1ec3ae7859f1a08",
+                16
+            )
+            .unwrap()
+        );
+
+        // The vectors of go-iden3-crypto's `TestMIMC7`.
+        assert_eq!(
+            MiMC::<MiMC7Bn254>::hash(&[BigUint::from(12u32)], &BigUint::from(0u32)),
+            BigUint::from_str_radix(
+                "237c92644dbddb86d8a259e0e923aaab65a93f1ec5758b8799988894ac0958fd",
+                16
+            )
+            .unwrap()
+        );
+        let inputs = [12u32, 45, 78, 41].map(BigUint::from);
+        assert_eq!(
+            MiMC::<MiMC7Bn254>::hash(&inputs, &BigUint::from(0u32)),
+            BigUint::from_str_radix(
+                "284bc1f34f335933a23a433b6ff3ee179d682cd5e5e2fcdd2d964afa85104beb",
+                16
+            )
+            .unwrap()
+        );
+    }
+}
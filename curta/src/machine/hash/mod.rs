@@ -8,7 +8,19 @@ use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::Register;
 
 pub mod blake;
+pub mod bytes;
+pub mod gadget;
+pub mod hmac;
+pub mod keccak;
+pub mod md5;
+pub mod mimc;
+pub mod poseidon;
+pub mod rescue;
+pub mod ripemd160;
 pub mod sha;
+pub mod sha3;
+pub mod sinsemilla;
+pub mod smt;
 
 pub trait HashPureInteger {
     type Integer: Num + Copy + Debug;
@@ -0,0 +1,364 @@
+use super::{
+    ALL_ROUND_CONSTANTS, HALF_N_FULL_ROUNDS, MDS_MATRIX_CIRC, MDS_MATRIX_DIAG, NUM_HASH_OUT_ELTS,
+    N_PARTIAL_ROUNDS, RATE, WIDTH,
+};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+pub trait PoseidonBuilder: Builder {
+    /// Applies the Poseidon permutation to a state of `WIDTH` elements.
+    ///
+    /// The permutation is computed within a single row, the returned state is a trace register.
+    fn poseidon_permutation(
+        &mut self,
+        state: &ArrayRegister<ElementRegister>,
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(state.len(), WIDTH);
+        let state = state.iter().map(|x| x.expr()).collect();
+        self.poseidon_rounds(state)
+    }
+
+    /// Computes the Poseidon compression of two digests, as plonky2's `PoseidonHash::two_to_one`.
+    fn poseidon_two_to_one(
+        &mut self,
+        left: &ArrayRegister<ElementRegister>,
+        right: &ArrayRegister<ElementRegister>,
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(left.len(), NUM_HASH_OUT_ELTS);
+        assert_eq!(right.len(), NUM_HASH_OUT_ELTS);
+        let state = left
+            .iter()
+            .chain(right.iter())
+            .map(|x| x.expr())
+            .chain((2 * NUM_HASH_OUT_ELTS..WIDTH).map(|_| ArithmeticExpression::zero()))
+            .collect();
+        let output = self.poseidon_rounds(state);
+        output.get_subarray(0..NUM_HASH_OUT_ELTS)
+    }
+
+    /// Hashes `inputs` without padding, as plonky2's `PoseidonHash::hash_no_pad`.
+    ///
+    /// The inputs are absorbed in overwrite mode, `RATE` elements per permutation.
+    fn poseidon_hash_no_pad(
+        &mut self,
+        inputs: &[ElementRegister],
+    ) -> ArrayRegister<ElementRegister> {
+        assert!(!inputs.is_empty(), "Cannot hash an empty input");
+        let mut state = (0..WIDTH)
+            .map(|_| ArithmeticExpression::zero())
+            .collect::<Vec<_>>();
+        let mut output = None;
+        for chunk in inputs.chunks(RATE) {
+            for (element, input) in state.iter_mut().zip(chunk.iter()) {
+                *element = input.expr();
+            }
+            let permuted = self.poseidon_rounds(state);
+            state = permuted.iter().map(|x| x.expr()).collect();
+            output = Some(permuted);
+        }
+        output.unwrap().get_subarray(0..NUM_HASH_OUT_ELTS)
+    }
+
+    /// Computes the rounds of the permutation and returns the final state.
+    ///
+    /// The state after each partial round is stored in the trace, so that the expressions of the
+    /// MDS layers do not depend on each other.
+    fn poseidon_rounds(
+        &mut self,
+        state: Vec<ArithmeticExpression<Self::Field>>,
+    ) -> ArrayRegister<ElementRegister> {
+        let mut state = state;
+        let mut round = 0;
+        for _ in 0..HALF_N_FULL_ROUNDS {
+            state = self.poseidon_full_round(state, round);
+            round += 1;
+        }
+        for _ in 0..N_PARTIAL_ROUNDS {
+            state = self.poseidon_partial_round(state, round);
+            let registers = self.alloc_array::<ElementRegister>(WIDTH);
+            for (register, element) in registers.iter().zip(state) {
+                self.set_to_expression(&register, element);
+            }
+            state = registers.iter().map(|x| x.expr()).collect();
+            round += 1;
+        }
+        for _ in 0..HALF_N_FULL_ROUNDS {
+            state = self.poseidon_full_round(state, round);
+            round += 1;
+        }
+
+        let output = self.alloc_array::<ElementRegister>(WIDTH);
+        for (register, element) in output.iter().zip(state) {
+            self.set_to_expression(&register, element);
+        }
+        output
+    }
+
+    fn poseidon_full_round(
+        &mut self,
+        state: Vec<ArithmeticExpression<Self::Field>>,
+        round: usize,
+    ) -> Vec<ArithmeticExpression<Self::Field>> {
+        let state = state
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let x = x + round_constant::<Self::Field>(round, i);
+                self.poseidon_sbox(x)
+            })
+            .collect::<Vec<_>>();
+        mds_layer(&state)
+    }
+
+    fn poseidon_partial_round(
+        &mut self,
+        state: Vec<ArithmeticExpression<Self::Field>>,
+        round: usize,
+    ) -> Vec<ArithmeticExpression<Self::Field>> {
+        let mut state = state
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| x + round_constant::<Self::Field>(round, i))
+            .collect::<Vec<_>>();
+        state[0] = self.poseidon_sbox(state[0].clone());
+        mds_layer(&state)
+    }
+
+    /// Computes `x^7` using the intermediate value `x^3`, both stored in the trace.
+    fn poseidon_sbox(
+        &mut self,
+        x: ArithmeticExpression<Self::Field>,
+    ) -> ArithmeticExpression<Self::Field> {
+        let x3 = self.expression::<ElementRegister>(x.clone() * x.clone() * x.clone());
+        let x7 = self.expression::<ElementRegister>(x3.expr() * x3.expr() * x);
+        x7.expr()
+    }
+}
+
+impl<B: Builder> PoseidonBuilder for B {}
+
+fn round_constant<F: Field>(round: usize, i: usize) -> F {
+    F::from_canonical_u64(ALL_ROUND_CONSTANTS[i + WIDTH * round])
+}
+
+fn mds_layer<F: Field>(state: &[ArithmeticExpression<F>]) -> Vec<ArithmeticExpression<F>> {
+    (0..WIDTH)
+        .map(|r| {
+            let mut row = state[r].clone() * F::from_canonical_u64(MDS_MATRIX_DIAG[r]);
+            for (i, coefficient) in MDS_MATRIX_CIRC.iter().enumerate() {
+                row = row + state[(i + r) % WIDTH].clone() * F::from_canonical_u64(*coefficient);
+            }
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::hash::poseidon::{Poseidon, PoseidonHash};
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::Hasher;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::instruction::empty::EmptyInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::AirParameters;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PoseidonTest;
+
+    impl AirParameters for PoseidonTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 2079;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PoseidonKnownAnswerTest;
+
+    impl AirParameters for PoseidonKnownAnswerTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 2591;
+    }
+
+    #[test]
+    fn test_poseidon_constants() {
+        assert_eq!(
+            MDS_MATRIX_CIRC,
+            <GoldilocksField as Poseidon>::MDS_MATRIX_CIRC
+        );
+        assert_eq!(
+            MDS_MATRIX_DIAG,
+            <GoldilocksField as Poseidon>::MDS_MATRIX_DIAG
+        );
+    }
+
+    #[test]
+    fn test_poseidon() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_poseidon", log::Level::Debug);
+
+        let mut builder = StarkBuilder::<PoseidonTest>::new();
+        let state = builder.alloc_array::<ElementRegister>(WIDTH);
+        let permuted = builder.poseidon_permutation(&state);
+        let left = builder.alloc_array::<ElementRegister>(NUM_HASH_OUT_ELTS);
+        let right = builder.alloc_array::<ElementRegister>(NUM_HASH_OUT_ELTS);
+        let compressed = builder.poseidon_two_to_one(&left, &right);
+        let inputs = builder.alloc_array::<ElementRegister>(10);
+        let hash = builder.poseidon_hash_no_pad(&inputs.iter().collect::<Vec<_>>());
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let air_data = &stark.air_data;
+        air_data.write_global_instructions(&mut writer_data.public_writer());
+
+        writer_data.chunks(num_rows).for_each(|mut chunk| {
+            for i in 0..num_rows {
+                let mut writer = chunk.row_writer(i);
+
+                let state_value = F::rand_array::<WIDTH>();
+                let left_value = HashOut {
+                    elements: F::rand_array::<NUM_HASH_OUT_ELTS>(),
+                };
+                let right_value = HashOut {
+                    elements: F::rand_array::<NUM_HASH_OUT_ELTS>(),
+                };
+                let inputs_value = F::rand_vec(10);
+                writer.write_array(&state, state_value);
+                writer.write_array(&left, left_value.elements);
+                writer.write_array(&right, right_value.elements);
+                writer.write_array(&inputs, &inputs_value);
+                air_data.write_trace_instructions(&mut writer);
+
+                assert_eq!(
+                    writer.read_vec(&permuted),
+                    F::poseidon(state_value).to_vec()
+                );
+                assert_eq!(
+                    writer.read_vec(&compressed),
+                    PoseidonHash::two_to_one(left_value, right_value)
+                        .elements
+                        .to_vec()
+                );
+                assert_eq!(
+                    writer.read_vec(&hash),
+                    PoseidonHash::hash_no_pad(&inputs_value).elements.to_vec()
+                );
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+        let rec_proof = rec_data.prove(pw).unwrap();
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_poseidon_known_answers() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_poseidon_known_answers", log::Level::Debug);
+
+        // The inputs of the test vectors of plonky2's Poseidon permutation, and hashes of one,
+        // `RATE` and `RATE + 1` elements, which absorb a partial, a full and two chunks.
+        let states: [[F; WIDTH]; 3] = [
+            [F::ZERO; WIDTH],
+            core::array::from_fn(|i| F::from_canonical_usize(i)),
+            [F::ZERO - F::ONE; WIDTH],
+        ];
+        let input_lengths = [1, RATE, RATE + 1];
+
+        let mut builder = StarkBuilder::<PoseidonKnownAnswerTest>::new();
+        let state = builder.alloc_array::<ElementRegister>(WIDTH);
+        let permuted = builder.poseidon_permutation(&state);
+        let inputs = input_lengths.map(|length| builder.alloc_array::<ElementRegister>(length));
+        let hashes =
+            inputs.map(|inputs| builder.poseidon_hash_no_pad(&inputs.iter().collect::<Vec<_>>()));
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let air_data = &stark.air_data;
+        air_data.write_global_instructions(&mut writer_data.public_writer());
+
+        writer_data.chunks(num_rows).for_each(|mut chunk| {
+            for i in 0..num_rows {
+                let mut writer = chunk.row_writer(i);
+
+                let state_value = states[i % states.len()];
+                writer.write_array(&state, state_value);
+                let inputs_values = input_lengths.map(|length| {
+                    (0..length)
+                        .map(|j| F::from_canonical_usize(i * length + j))
+                        .collect::<Vec<_>>()
+                });
+                for (register, value) in inputs.iter().zip(inputs_values.iter()) {
+                    writer.write_array(register, value);
+                }
+                air_data.write_trace_instructions(&mut writer);
+
+                assert_eq!(
+                    writer.read_vec(&permuted),
+                    F::poseidon(state_value).to_vec()
+                );
+                for (hash, value) in hashes.iter().zip(inputs_values.iter()) {
+                    assert_eq!(
+                        writer.read_vec(hash),
+                        PoseidonHash::hash_no_pad(value).elements.to_vec()
+                    );
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
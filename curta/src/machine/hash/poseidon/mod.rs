@@ -0,0 +1,25 @@
+//! The Poseidon permutation over Goldilocks, with the parameters used by plonky2.
+//!
+//! The round constants are taken from plonky2 so that the digests computed in the AIR match the
+//! ones of `PoseidonHash`.
+
+pub mod builder;
+
+pub use plonky2::hash::poseidon::ALL_ROUND_CONSTANTS;
+
+/// The width of the permutation state.
+pub const WIDTH: usize = 12;
+/// The number of state elements absorbed by each permutation of the sponge.
+pub const RATE: usize = 8;
+/// The number of elements of a digest.
+pub const NUM_HASH_OUT_ELTS: usize = 4;
+
+/// The number of full rounds before and after the partial rounds.
+pub const HALF_N_FULL_ROUNDS: usize = 4;
+pub const N_PARTIAL_ROUNDS: usize = 22;
+pub const N_ROUNDS: usize = 2 * HALF_N_FULL_ROUNDS + N_PARTIAL_ROUNDS;
+
+/// The first row of the circulant part of the MDS matrix.
+pub const MDS_MATRIX_CIRC: [u64; WIDTH] = [17, 15, 41, 16, 2, 28, 13, 13, 39, 18, 34, 20];
+/// The diagonal part of the MDS matrix.
+pub const MDS_MATRIX_DIAG: [u64; WIDTH] = [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
@@ -0,0 +1,206 @@
+use super::constants::{MDS, ROUND_CONSTANTS};
+use super::{ALPHA_INV, NUM_HASH_OUT_ELTS, N_ROUNDS, RATE, WIDTH};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+pub trait RescueBuilder: Builder {
+    /// Applies the Rescue-Prime permutation to a state of `WIDTH` elements.
+    ///
+    /// The permutation is computed within a single row, the returned state is a trace register.
+    fn rescue_permutation(
+        &mut self,
+        state: &ArrayRegister<ElementRegister>,
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(state.len(), WIDTH);
+        let state = state.iter().map(|x| x.expr()).collect();
+        self.rescue_rounds(state)
+    }
+
+    /// Hashes `inputs` with the Rescue-Prime sponge, as `RescuePrime::hash`.
+    fn rescue_hash(&mut self, inputs: &[ElementRegister]) -> ArrayRegister<ElementRegister> {
+        let mut padded_inputs = inputs.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        padded_inputs.push(ArithmeticExpression::one());
+        padded_inputs.resize(
+            padded_inputs.len().div_ceil(RATE) * RATE,
+            ArithmeticExpression::zero(),
+        );
+        let mut state = (0..WIDTH)
+            .map(|_| ArithmeticExpression::zero())
+            .collect::<Vec<_>>();
+        let mut output = None;
+        for chunk in padded_inputs.chunks_exact(RATE) {
+            for (element, input) in state.iter_mut().zip(chunk.iter()) {
+                *element = element.clone() + input.clone();
+            }
+            let permuted = self.rescue_rounds(state);
+            state = permuted.iter().map(|x| x.expr()).collect();
+            output = Some(permuted);
+        }
+        output.unwrap().get_subarray(0..NUM_HASH_OUT_ELTS)
+    }
+
+    /// Computes the rounds of the permutation and returns the final state.
+    fn rescue_rounds(
+        &mut self,
+        state: Vec<ArithmeticExpression<Self::Field>>,
+    ) -> ArrayRegister<ElementRegister> {
+        let mut state = state;
+        for round in 0..N_ROUNDS {
+            state = state.into_iter().map(|x| self.rescue_sbox(x)).collect();
+            state = rescue_linear_layer(&state, 2 * round);
+            state = state
+                .into_iter()
+                .map(|x| self.rescue_inverse_sbox(x))
+                .collect();
+            state = rescue_linear_layer(&state, 2 * round + 1);
+        }
+
+        let output = self.alloc_array::<ElementRegister>(WIDTH);
+        for (register, element) in output.iter().zip(state) {
+            self.set_to_expression(&register, element);
+        }
+        output
+    }
+
+    /// Computes `x^7` using the intermediate value `x^3`, both stored in the trace.
+    fn rescue_sbox(
+        &mut self,
+        x: ArithmeticExpression<Self::Field>,
+    ) -> ArithmeticExpression<Self::Field> {
+        let x3 = self.expression::<ElementRegister>(x.clone() * x.clone() * x.clone());
+        let x7 = self.expression::<ElementRegister>(x3.expr() * x3.expr() * x);
+        x7.expr()
+    }
+
+    /// Computes `x^(1/7)` as a witness `y` constrained by `y^7 = x`.
+    fn rescue_inverse_sbox(
+        &mut self,
+        x: ArithmeticExpression<Self::Field>,
+    ) -> ArithmeticExpression<Self::Field> {
+        let y = self.api().unconstrained_power(x.clone(), ALPHA_INV);
+        let y3 = self.expression::<ElementRegister>(y.expr() * y.expr() * y.expr());
+        self.assert_expressions_equal(y3.expr() * y3.expr() * y.expr(), x);
+        y.expr()
+    }
+}
+
+impl<B: Builder> RescueBuilder for B {}
+
+/// Applies the MDS matrix and adds the round constants of `step`.
+fn rescue_linear_layer<F: Field>(
+    state: &[ArithmeticExpression<F>],
+    step: usize,
+) -> Vec<ArithmeticExpression<F>> {
+    MDS.iter()
+        .enumerate()
+        .map(|(r, row)| {
+            let mut element = ArithmeticExpression::from_constant(F::from_canonical_u64(
+                ROUND_CONSTANTS[WIDTH * step + r],
+            ));
+            for (x, coefficient) in state.iter().zip(row.iter()) {
+                element = element + x.clone() * F::from_canonical_u64(*coefficient);
+            }
+            element
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::instruction::empty::EmptyInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::AirParameters;
+    use crate::machine::hash::rescue::RescuePrime;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct RescueTest;
+
+    impl AirParameters for RescueTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 1067;
+    }
+
+    #[test]
+    fn test_rescue_prime() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_rescue_prime", log::Level::Debug);
+
+        let mut builder = StarkBuilder::<RescueTest>::new();
+        let state = builder.alloc_array::<ElementRegister>(WIDTH);
+        let permuted = builder.rescue_permutation(&state);
+        let inputs = builder.alloc_array::<ElementRegister>(10);
+        let hash = builder.rescue_hash(&inputs.iter().collect::<Vec<_>>());
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let air_data = &stark.air_data;
+        air_data.write_global_instructions(&mut writer_data.public_writer());
+
+        writer_data.chunks(num_rows).for_each(|mut chunk| {
+            for i in 0..num_rows {
+                let mut writer = chunk.row_writer(i);
+
+                let state_value = F::rand_array::<WIDTH>();
+                let inputs_value = F::rand_vec(10);
+                writer.write_array(&state, state_value);
+                writer.write_array(&inputs, &inputs_value);
+                air_data.write_trace_instructions(&mut writer);
+
+                assert_eq!(
+                    writer.read_vec(&permuted),
+                    RescuePrime::permutation(state_value).to_vec()
+                );
+                assert_eq!(
+                    writer.read_vec(&hash),
+                    RescuePrime::hash(&inputs_value).to_vec()
+                );
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+        let rec_proof = rec_data.prove(pw).unwrap();
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
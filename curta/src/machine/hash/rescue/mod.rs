@@ -0,0 +1,28 @@
+//! The Rescue-Prime permutation and sponge hash over Goldilocks.
+//!
+//! The instance has a state of 12 elements with a capacity of 4, 7 rounds and the S-box `x^7`,
+//! the shape used by Miden's `Rp64_256`. The MDS matrix and the round constants are the output of
+//! the parameter generation of the Rescue-Prime reference implementation, see `constants`.
+//!
+//! The inverse S-box `x^(1/7)` has a very large degree, so the AIR takes its output as a witness
+//! `y` and constrains `y^7 = x` instead.
+
+pub mod builder;
+pub mod constants;
+pub mod pure;
+
+/// The width of the permutation state.
+pub const WIDTH: usize = 12;
+/// The number of state elements absorbed by each permutation of the sponge.
+pub const RATE: usize = 8;
+/// The number of elements of a digest.
+pub const NUM_HASH_OUT_ELTS: usize = 4;
+pub const N_ROUNDS: usize = 7;
+
+/// The exponent of the S-box.
+pub const ALPHA: u64 = 7;
+/// The exponent of the inverse S-box, the inverse of `ALPHA` modulo `p - 1`.
+pub const ALPHA_INV: u64 = 10540996611094048183;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RescuePrime;
@@ -0,0 +1,108 @@
+use super::constants::{MDS, ROUND_CONSTANTS};
+use super::{RescuePrime, ALPHA, ALPHA_INV, NUM_HASH_OUT_ELTS, N_ROUNDS, RATE, WIDTH};
+use crate::math::prelude::*;
+
+impl RescuePrime {
+    /// Applies the Rescue-Prime permutation to `state`.
+    pub fn permutation<F: Field>(state: [F; WIDTH]) -> [F; WIDTH] {
+        let mut state = state;
+        for round in 0..N_ROUNDS {
+            state = state.map(|x| x.pow(ALPHA));
+            state = Self::mds_layer(&state);
+            Self::add_round_constants(&mut state, 2 * round);
+            state = state.map(|x| x.pow(ALPHA_INV));
+            state = Self::mds_layer(&state);
+            Self::add_round_constants(&mut state, 2 * round + 1);
+        }
+        state
+    }
+
+    /// Hashes `inputs` with the sponge construction.
+    ///
+    /// The inputs are padded with a one followed by zeros to a multiple of `RATE` elements, and
+    /// each chunk is added to the first `RATE` elements of the state before a permutation.
+    pub fn hash<F: Field>(inputs: &[F]) -> [F; NUM_HASH_OUT_ELTS] {
+        let mut state = [F::ZERO; WIDTH];
+        for chunk in Self::pad(inputs).chunks_exact(RATE) {
+            for (element, input) in state.iter_mut().zip(chunk.iter()) {
+                *element += *input;
+            }
+            state = Self::permutation(state);
+        }
+        core::array::from_fn(|i| state[i])
+    }
+
+    /// Pads `inputs` with a one followed by zeros to a multiple of `RATE` elements.
+    pub fn pad<F: Field>(inputs: &[F]) -> Vec<F> {
+        let mut padded = inputs.to_vec();
+        padded.push(F::ONE);
+        padded.resize(padded.len().div_ceil(RATE) * RATE, F::ZERO);
+        padded
+    }
+
+    fn mds_layer<F: Field>(state: &[F; WIDTH]) -> [F; WIDTH] {
+        core::array::from_fn(|r| {
+            state
+                .iter()
+                .zip(MDS[r].iter())
+                .map(|(x, coefficient)| *x * F::from_canonical_u64(*coefficient))
+                .sum()
+        })
+    }
+
+    fn add_round_constants<F: Field>(state: &mut [F; WIDTH], step: usize) {
+        for (i, x) in state.iter_mut().enumerate() {
+            *x += F::from_canonical_u64(ROUND_CONSTANTS[WIDTH * step + i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn to_field<const N: usize>(values: [u64; N]) -> [F; N] {
+        values.map(F::from_canonical_u64)
+    }
+
+    #[test]
+    fn test_rescue_prime_pure() {
+        let state = core::array::from_fn(|i| F::from_canonical_usize(i));
+        let expected = to_field([
+            8830151182902853374,
+            13187004865337989000,
+            15101162352278848053,
+            14435613191222483279,
+            1292156311573281399,
+            8764222276562584657,
+            3431628250940046033,
+            3850389327580572944,
+            14991535190515157846,
+            5109801939400497066,
+            15005662634163591475,
+            2058051616562672581,
+        ]);
+        assert_eq!(RescuePrime::permutation(state), expected);
+
+        let inputs = (1..=10).map(F::from_canonical_u64).collect::<Vec<_>>();
+        let expected = to_field([
+            3910019218920136091,
+            15707486223850450542,
+            1415233355864658853,
+            15532888640724442240,
+        ]);
+        assert_eq!(RescuePrime::hash(&inputs), expected);
+
+        let expected = to_field([
+            13561453048864196546,
+            12477995195093864516,
+            16691327925800721206,
+            16260423760751589714,
+        ]);
+        assert_eq!(RescuePrime::hash::<F>(&[]), expected);
+    }
+}
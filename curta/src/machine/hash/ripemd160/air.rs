@@ -0,0 +1,390 @@
+use plonky2::util::log2_ceil;
+
+use super::{
+    RIPEMD160Air, BLOCK_WORDS, IV, K_LEFT, K_RIGHT, NUM_STEPS, R_LEFT, R_RIGHT, STATE_WORDS,
+    S_LEFT, S_RIGHT,
+};
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+const DUMMY_INDEX: u64 = i32::MAX as u64;
+
+/// The number of bits of the rotation amounts, which are all smaller than 16.
+const SHIFT_BITS: usize = 4;
+
+/// The constant inputs of a step of one line.
+struct StepParameters {
+    message_index: ElementRegister,
+    k: U32Register,
+    shift_bits: [BitRegister; SHIFT_BITS],
+    /// A one-hot encoding of the boolean function of the step.
+    function_bits: [BitRegister; 5],
+}
+
+impl<L: AirParameters> RIPEMD160Air<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// A bit that is `1` on the last row of every compression and `0` otherwise.
+    fn cycle_end_bit(builder: &mut BytesBuilder<L>) -> BitRegister {
+        let cycle_16 = builder.cycle(4);
+        let loop_5 = builder.api.loop_instr(5);
+        builder.mul(loop_5.get_iteration_reg(4), cycle_16.end_bit)
+    }
+
+    /// Stores `values` in a slice, where value `i` is read `multiplicity(i)` times.
+    fn values_slice<V: MemoryValue>(
+        builder: &mut BytesBuilder<L>,
+        values: impl Iterator<Item = V>,
+        multiplicity: impl Fn(usize) -> ElementRegister,
+    ) -> Slice<V> {
+        let slice = builder.uninit_slice();
+        for (i, value) in values.enumerate() {
+            builder.store(
+                &slice.get(i),
+                value,
+                &Time::zero(),
+                Some(multiplicity(i)),
+                None,
+                None,
+            );
+        }
+        slice
+    }
+
+    /// Hashes the messages given by `padded_chunks` with RIPEMD-160.
+    ///
+    /// Every chunk is a block of sixteen little-endian words as given by `RIPEMD160Pure::pad`,
+    /// and each compression takes `NUM_STEPS` rows, so the trace must have
+    /// `(80 * padded_chunks.len()).next_power_of_two()` rows. `end_bits[i]` marks the last block
+    /// of a message and `digest_bits[i]` the blocks whose output is a digest, whose block index is
+    /// given in `digest_indices`. Returns the five digest words for every entry of
+    /// `digest_indices`.
+    pub fn ripemd160(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U32Register>> {
+        let num_real_blocks = padded_chunks.len();
+        assert_eq!(end_bits.len(), num_real_blocks);
+        assert_eq!(digest_bits.len(), num_real_blocks);
+        for chunk in padded_chunks.iter() {
+            assert_eq!(chunk.len(), BLOCK_WORDS, "Blocks must have sixteen words");
+        }
+
+        let degree_log = log2_ceil(num_real_blocks * NUM_STEPS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        let num_rows = 1 << degree_log;
+        // The last compression is cut short by the end of the trace.
+        let num_dummy_blocks = num_rows / NUM_STEPS + 1 - num_real_blocks;
+        let length_last_block = num_rows % NUM_STEPS;
+        let num_blocks = num_real_blocks + num_dummy_blocks;
+
+        let one = builder.constant::<BitRegister>(&L::Field::ONE);
+        let zero = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let bit = |value: bool| if value { one } else { zero };
+
+        // Store the parameters of every step, each read once in every compression.
+        let num_blocks_element =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_blocks));
+        let num_blocks_minus_one =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_blocks - 1));
+        let step_multiplicity = |i: usize| {
+            if i < length_last_block {
+                num_blocks_element
+            } else {
+                num_blocks_minus_one
+            }
+        };
+        let message_indices = builder.constant_array::<ElementRegister>(
+            &(0..BLOCK_WORDS)
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let k_left = builder.constant_array::<U32Register>(&K_LEFT.map(u32_to_le_field_bytes));
+        let k_right = builder.constant_array::<U32Register>(&K_RIGHT.map(u32_to_le_field_bytes));
+        let mut step_slices = |r: &[usize; NUM_STEPS],
+                               k: &ArrayRegister<U32Register>,
+                               s: &[u32; NUM_STEPS],
+                               function: fn(usize) -> usize| {
+            let message_index = Self::values_slice(
+                builder,
+                r.iter().map(|i| message_indices.get(*i)),
+                step_multiplicity,
+            );
+            let k = Self::values_slice(
+                builder,
+                (0..NUM_STEPS).map(|j| k.get(j / 16)),
+                step_multiplicity,
+            );
+            let shift_bits = core::array::from_fn::<_, SHIFT_BITS, _>(|b| {
+                Self::values_slice(
+                    builder,
+                    s.iter().map(|shift| bit(((shift >> b) & 1) == 1)),
+                    step_multiplicity,
+                )
+            });
+            let function_bits = core::array::from_fn::<_, 5, _>(|f| {
+                Self::values_slice(
+                    builder,
+                    (0..NUM_STEPS).map(|j| bit(function(j / 16) == f)),
+                    step_multiplicity,
+                )
+            });
+            (message_index, k, shift_bits, function_bits)
+        };
+        let left_slices = step_slices(&R_LEFT, &k_left, &S_LEFT, |round| round);
+        let right_slices = step_slices(&R_RIGHT, &k_right, &S_RIGHT, |round| 4 - round);
+
+        // Store the parameters of every block, read on every row of its compression.
+        let reg_cycle_length =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(NUM_STEPS));
+        let reg_last_length =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(length_last_block));
+        let block_multiplicity = |i: usize| {
+            if i == num_blocks - 1 {
+                reg_last_length
+            } else {
+                reg_cycle_length
+            }
+        };
+        let block_bits = |builder: &mut BytesBuilder<L>,
+                          values: &mut dyn Iterator<Item = BitRegister>,
+                          dummy_value: BitRegister| {
+            Self::values_slice(
+                builder,
+                values
+                    .chain(core::iter::repeat(dummy_value))
+                    .take(num_blocks),
+                block_multiplicity,
+            )
+        };
+        let end_bit_slice = block_bits(builder, &mut end_bits.iter(), zero);
+        let digest_bit_slice = block_bits(builder, &mut digest_bits.iter(), zero);
+        let is_dummy_slice = block_bits(builder, &mut (0..num_real_blocks).map(|_| zero), one);
+
+        // Store the words of the blocks, each read once in every round of both lines. The dummy
+        // compressions read a zero word instead.
+        let words = builder.uninit_slice();
+        let num_word_reads = builder.constant(&L::Field::from_canonical_usize(10));
+        for (i, chunk) in padded_chunks.iter().enumerate() {
+            for (j, word) in chunk.iter().enumerate() {
+                builder.store(
+                    &words.get(BLOCK_WORDS * i + j),
+                    word,
+                    &Time::zero(),
+                    Some(num_word_reads),
+                    None,
+                    None,
+                );
+            }
+        }
+        assert!((BLOCK_WORDS * num_real_blocks) < DUMMY_INDEX as usize);
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let dummy_index = builder.constant(&L::Field::from_canonical_u64(DUMMY_INDEX));
+        let num_dummy_reads = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+            2 * (num_rows - NUM_STEPS * num_real_blocks),
+        ));
+        builder.store(
+            &words.get(DUMMY_INDEX as usize),
+            zero_word,
+            &Time::zero(),
+            Some(num_dummy_reads),
+            None,
+            None,
+        );
+
+        let cycle_end_bit = Self::cycle_end_bit(builder);
+        let process_id = builder.process_id(NUM_STEPS, cycle_end_bit);
+        let clk = builder.clk;
+        let step = builder
+            .expression(clk.expr() - process_id.expr() * L::Field::from_canonical_usize(NUM_STEPS));
+        let [end_bit, digest_bit, is_dummy] = [end_bit_slice, digest_bit_slice, is_dummy_slice]
+            .map(|slice| builder.load(&slice.get_at(process_id), &Time::zero(), None, None));
+
+        let [left_parameters, right_parameters] =
+            [left_slices, right_slices].map(|(message_index, k, shift_bits, function_bits)| {
+                StepParameters {
+                    message_index: builder.load(
+                        &message_index.get_at(step),
+                        &Time::zero(),
+                        None,
+                        None,
+                    ),
+                    k: builder.load(&k.get_at(step), &Time::zero(), None, None),
+                    shift_bits: shift_bits
+                        .map(|slice| builder.load(&slice.get_at(step), &Time::zero(), None, None)),
+                    function_bits: function_bits
+                        .map(|slice| builder.load(&slice.get_at(step), &Time::zero(), None, None)),
+                }
+            });
+        let [x_left, x_right] = [&left_parameters, &right_parameters].map(|parameters| {
+            let word_index = builder.expression(
+                is_dummy.not_expr()
+                    * (process_id.expr() * L::Field::from_canonical_usize(BLOCK_WORDS)
+                        + parameters.message_index.expr())
+                    + is_dummy.expr() * dummy_index.expr(),
+            );
+            builder.load(&words.get_at(word_index), &Time::zero(), None, None)
+        });
+
+        // The state before the compression, and the states of the two lines before the step.
+        let iv = builder.constant_array::<U32Register>(&IV.map(u32_to_le_field_bytes));
+        let h = builder.alloc_array::<U32Register>(STATE_WORDS);
+        let left = builder.alloc_array::<U32Register>(STATE_WORDS);
+        let right = builder.alloc_array::<U32Register>(STATE_WORDS);
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_first_row(&h.get(i), iv.get(i).expr());
+            builder.set_to_expression_first_row(&left.get(i), iv.get(i).expr());
+            builder.set_to_expression_first_row(&right.get(i), iv.get(i).expr());
+        }
+
+        let left_output = Self::step(builder, &left, x_left, &left_parameters);
+        let right_output = Self::step(builder, &right, x_right, &right_parameters);
+        let h_output = (0..STATE_WORDS)
+            .map(|i| {
+                let sum = builder.add(h.get((i + 1) % 5), left_output[(i + 2) % 5]);
+                builder.add(sum, right_output[(i + 3) % 5])
+            })
+            .collect::<Vec<_>>();
+
+        // At the end of a compression, the state is updated and reset after the last block of a
+        // message. Both lines start from the state.
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_transition(
+                &h.get(i).next(),
+                h.get(i).expr() * cycle_end_bit.not_expr()
+                    + (h_output[i].expr() * end_bit.not_expr() + iv.get(i).expr() * end_bit.expr())
+                        * cycle_end_bit.expr(),
+            );
+            builder.set_to_expression_transition(
+                &left.get(i).next(),
+                left_output[i].expr() * cycle_end_bit.not_expr()
+                    + h.get(i).next().expr() * cycle_end_bit.expr(),
+            );
+            builder.set_to_expression_transition(
+                &right.get(i).next(),
+                right_output[i].expr() * cycle_end_bit.not_expr()
+                    + h.get(i).next().expr() * cycle_end_bit.expr(),
+            );
+        }
+
+        // Store the digests of the compressions flagged by the digest bits.
+        let state_ptr = builder.uninit_slice();
+        let hash_state_public = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<U32Register>(STATE_WORDS))
+            .collect::<Vec<_>>();
+        for (i, h) in digest_indices.iter().zip(hash_state_public.iter()) {
+            for (j, word) in h.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(i));
+            }
+        }
+        let digest_flag =
+            builder.expression(cycle_end_bit.expr() * is_dummy.not_expr() * digest_bit.expr());
+        for (j, word) in h_output.iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                *word,
+                &Time::from_element(process_id),
+                Some(digest_flag),
+                None,
+                None,
+            );
+        }
+
+        hash_state_public
+    }
+
+    /// A step of one line applied to the state `(a, b, c, d, e)` with message word `x`.
+    fn step(
+        builder: &mut BytesBuilder<L>,
+        state: &ArrayRegister<U32Register>,
+        x: U32Register,
+        parameters: &StepParameters,
+    ) -> [U32Register; STATE_WORDS] {
+        let [a, b, c, d, e] = core::array::from_fn(|i| state.get(i));
+
+        let f = Self::boolean_function(builder, b, c, d, &parameters.function_bits);
+        let sum = builder.add(a, f);
+        let sum = builder.add(sum, x);
+        let sum = builder.add(sum, parameters.k);
+        let rotated = Self::rotate_left(builder, sum, &parameters.shift_bits);
+        let t = builder.add(rotated, e);
+        let c_rotated = builder.rotate_right(c, 22);
+
+        [e, t, b, c_rotated, d]
+    }
+
+    /// The boolean function of the round selected by the one-hot `function_bits`.
+    fn boolean_function(
+        builder: &mut BytesBuilder<L>,
+        x: U32Register,
+        y: U32Register,
+        z: U32Register,
+        function_bits: &[BitRegister; 5],
+    ) -> U32Register {
+        let not_x = builder.not(x);
+        let not_y = builder.not(y);
+        let not_z = builder.not(z);
+
+        // x ^ y ^ z
+        let x_xor_y = builder.xor(&x, &y);
+        let f_0 = builder.xor(&x_xor_y, &z);
+
+        // (x & y) | (!x & z), where the two terms have no bits in common.
+        let x_and_y = builder.and(&x, &y);
+        let not_x_and_z = builder.and(&not_x, &z);
+        let f_1 = builder.xor(&x_and_y, &not_x_and_z);
+
+        // (x | !y) ^ z
+        let not_x_and_y = builder.and(&not_x, &y);
+        let x_or_not_y = builder.not(not_x_and_y);
+        let f_2 = builder.xor(&x_or_not_y, &z);
+
+        // (x & z) | (y & !z), where the two terms have no bits in common.
+        let x_and_z = builder.and(&x, &z);
+        let y_and_not_z = builder.and(&y, &not_z);
+        let f_3 = builder.xor(&x_and_z, &y_and_not_z);
+
+        // x ^ (y | !z)
+        let not_y_and_z = builder.and(&not_y, &z);
+        let y_or_not_z = builder.not(not_y_and_z);
+        let f_4 = builder.xor(&x, &y_or_not_z);
+
+        let expression = [f_0, f_1, f_2, f_3, f_4]
+            .iter()
+            .zip(function_bits.iter())
+            .map(|(f, bit)| f.expr() * bit.expr())
+            .reduce(|acc, term| acc + term)
+            .unwrap();
+        builder.expression(expression)
+    }
+
+    /// Rotates `value` left by the amount whose binary decomposition is `shift_bits`.
+    fn rotate_left(
+        builder: &mut BytesBuilder<L>,
+        value: U32Register,
+        shift_bits: &[BitRegister; SHIFT_BITS],
+    ) -> U32Register {
+        let mut value = value;
+        for (i, bit) in shift_bits.iter().enumerate() {
+            let rotated = builder.rotate_right(value, 32 - (1 << i));
+            value = builder.expression(rotated.expr() * bit.expr() + value.expr() * bit.not_expr());
+        }
+        value
+    }
+}
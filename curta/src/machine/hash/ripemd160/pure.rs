@@ -0,0 +1,109 @@
+use super::{
+    BLOCK_WORDS, IV, K_LEFT, K_RIGHT, NUM_STEPS, R_LEFT, R_RIGHT, STATE_WORDS, S_LEFT, S_RIGHT,
+};
+
+pub struct RIPEMD160Pure;
+
+impl RIPEMD160Pure {
+    /// The boolean function of the round `round`.
+    pub fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+        match round {
+            0 => x ^ y ^ z,
+            1 => (x & y) | (!x & z),
+            2 => (x | !y) ^ z,
+            3 => (x & z) | (y & !z),
+            4 => x ^ (y | !z),
+            _ => unreachable!("RIPEMD-160 has five rounds"),
+        }
+    }
+
+    /// Pads the message to a whole number of blocks of little-endian words.
+    pub fn pad(msg: &[u8]) -> Vec<u32> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(0x80);
+        while padded_msg.len() % 64 != 56 {
+            padded_msg.push(0);
+        }
+        padded_msg.extend_from_slice(&((msg.len() as u64) * 8).to_le_bytes());
+        padded_msg
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    fn step(state: &mut [u32; STATE_WORDS], f: u32, x: u32, k: u32, s: u32) {
+        let [a, b, c, d, e] = *state;
+        let t = a
+            .wrapping_add(f)
+            .wrapping_add(x)
+            .wrapping_add(k)
+            .rotate_left(s)
+            .wrapping_add(e);
+        *state = [e, t, b, c.rotate_left(10), d];
+    }
+
+    pub fn compress(h: [u32; STATE_WORDS], x: &[u32]) -> [u32; STATE_WORDS] {
+        assert_eq!(x.len(), BLOCK_WORDS);
+        let mut left = h;
+        let mut right = h;
+        for j in 0..NUM_STEPS {
+            let round = j / 16;
+            let f = Self::f(round, left[1], left[2], left[3]);
+            Self::step(&mut left, f, x[R_LEFT[j]], K_LEFT[round], S_LEFT[j]);
+            let f = Self::f(4 - round, right[1], right[2], right[3]);
+            Self::step(&mut right, f, x[R_RIGHT[j]], K_RIGHT[round], S_RIGHT[j]);
+        }
+        Self::combine(h, left, right)
+    }
+
+    /// Combines the state before a compression with the outputs of the two lines.
+    pub fn combine(
+        h: [u32; STATE_WORDS],
+        left: [u32; STATE_WORDS],
+        right: [u32; STATE_WORDS],
+    ) -> [u32; STATE_WORDS] {
+        core::array::from_fn(|i| {
+            h[(i + 1) % 5]
+                .wrapping_add(left[(i + 2) % 5])
+                .wrapping_add(right[(i + 3) % 5])
+        })
+    }
+
+    /// The digest as little-endian words.
+    pub fn digest(msg: &[u8]) -> [u32; STATE_WORDS] {
+        Self::pad(msg)
+            .chunks_exact(BLOCK_WORDS)
+            .fold(IV, |h, block| Self::compress(h, block))
+    }
+
+    pub fn hash(msg: &[u8]) -> [u8; 20] {
+        let mut hash = [0u8; 20];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(Self::digest(msg)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ripemd160_pure() {
+        assert_eq!(
+            hex::encode(RIPEMD160Pure::hash(b"")),
+            "9c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+        assert_eq!(
+            hex::encode(RIPEMD160Pure::hash(b"abc")),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+        assert_eq!(
+            hex::encode(RIPEMD160Pure::hash(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "12a053384a9c0c88e405a06c27dcf49ada62eb2b"
+        );
+    }
+}
@@ -103,15 +103,134 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: ArrayRegister<ElementRegister>,
     ) -> Vec<Self::StateVariable> {
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_public::<Self::StateVariable>())
+            .collect::<Vec<_>>();
+        Self::sha_with_digests(
+            builder,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            &digests,
+        );
+        digests
+    }
+
+    /// Same as `sha`, with the digests given as already allocated public registers.
+    ///
+    /// The chunks are stored when the global instructions registered here are written, so this
+    /// allows chunks to be computed from the digests by instructions registered before.
+    fn sha_with_digests(
+        builder: &mut B,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        digests: &[Self::StateVariable],
+    ) {
+        let num_rows = 1 << log2_ceil(padded_chunks.len() * CYCLE_LENGTH);
+        Self::sha_with_digests_in_rows(
+            builder,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            digests,
+            num_rows,
+        );
+    }
+
+    /// Same as `sha_with_digests`, in a trace of `num_rows` rows.
+    ///
+    /// The hash uses every row of the trace, which can be longer than the chunks need when the
+    /// hash is proven together with other gadgets. `num_rows` must be a power of two of at least
+    /// `CYCLE_LENGTH * padded_chunks.len()`.
+    fn sha_with_digests_in_rows(
+        builder: &mut B,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        digests: &[Self::StateVariable],
+        num_rows: usize,
+    ) {
+        assert_eq!(digests.len(), digest_indices.len());
         let data = Self::data(
             builder,
             padded_chunks,
             end_bits,
             digest_bits,
             digest_indices,
+            num_rows,
         );
         let w_i = Self::preprocessing(builder, &data);
-        Self::processing(builder, w_i, &data)
+        Self::processing(builder, w_i, &data, digests);
+    }
+
+    /// Applies the compression function to each of `states` and the corresponding chunk of
+    /// `chunks`, without any padding or chaining.
+    ///
+    /// The states and chunks must be public registers. Each compression takes `CYCLE_LENGTH`
+    /// rows, so the trace must have `(CYCLE_LENGTH * chunks.len()).next_power_of_two()` rows.
+    /// Returns the output states, whose values must be written by the prover before the global
+    /// instructions.
+    fn compress(
+        builder: &mut B,
+        states: &[ArrayRegister<Self::IntRegister>],
+        chunks: &[ArrayRegister<Self::IntRegister>],
+    ) -> Vec<Self::StateVariable> {
+        assert_eq!(states.len(), chunks.len(), "Each chunk must have a state");
+        for state in states.iter() {
+            assert_eq!(state.len(), 8, "States must have 8 words");
+        }
+        let num_chunks = chunks.len();
+
+        // Every chunk is a message of its own, whose digest is the output state.
+        let ones = builder.constant_array::<BitRegister>(&vec![B::Field::ONE; num_chunks]);
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &(0..num_chunks)
+                .map(B::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let num_rows = 1 << log2_ceil(num_chunks * CYCLE_LENGTH);
+        let mut data = Self::data(builder, chunks, &ones, &ones, digest_indices, num_rows);
+
+        // The state is reset at the end of the cycle of chunk `k` to the initial state of chunk
+        // `k + 1`, which is read on every row of that cycle. Dummy chunks start from the initial
+        // hash.
+        let num_rounds = data.degree / CYCLE_LENGTH + 1;
+        let length_last_round = data.degree % CYCLE_LENGTH;
+        let reg_cycle_length = builder.constant(&B::Field::from_canonical_usize(CYCLE_LENGTH));
+        let reg_last_length = builder.constant(&B::Field::from_canonical_usize(length_last_round));
+        let initial_hash = data.public.initial_hash;
+        let initial_states = (0..8)
+            .map(|j| {
+                let slice = builder.uninit_slice();
+                for k in 1..=num_rounds {
+                    let value = match states.get(k) {
+                        Some(state) => state.get(j),
+                        None => initial_hash.get(j),
+                    };
+                    let reads = if k < num_rounds {
+                        reg_cycle_length
+                    } else {
+                        reg_last_length
+                    };
+                    builder.store(&slice.get(k), value, &Time::zero(), Some(reads), None, None);
+                }
+                slice
+            })
+            .collect::<Vec<_>>();
+        data.public.initial_states = Some(states.to_vec());
+        data.memory.initial_states = Some(initial_states);
+
+        let digests = (0..num_chunks)
+            .map(|_| builder.alloc_public::<Self::StateVariable>())
+            .collect::<Vec<_>>();
+        let w_i = Self::preprocessing(builder, &data);
+        Self::processing(builder, w_i, &data, &digests);
+        digests
     }
 
     fn data(
@@ -120,6 +239,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         end_bits: &ArrayRegister<BitRegister>,
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: ArrayRegister<ElementRegister>,
+        num_rows: usize,
     ) -> SHAData<Self::IntRegister, CYCLE_LENGTH> {
         assert_eq!(padded_chunks.len(), end_bits.len());
         let num_real_rounds = padded_chunks.len();
@@ -127,7 +247,17 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             "AIR degree before padding: {}",
             num_real_rounds * CYCLE_LENGTH
         );
-        let degree_log = log2_ceil(num_real_rounds * CYCLE_LENGTH);
+        assert!(
+            num_rows.is_power_of_two(),
+            "AIR degree must be a power of two"
+        );
+        assert!(
+            num_rows >= num_real_rounds * CYCLE_LENGTH,
+            "AIR degree {} is too small for {} chunks",
+            num_rows,
+            num_real_rounds
+        );
+        let degree_log = log2_ceil(num_rows);
         assert!(degree_log < 31, "AIR degree is too large");
         debug!("AIR degree after padding: {}", 1 << degree_log);
         let num_dummy_rounds = (1 << degree_log) / CYCLE_LENGTH + 1 - num_real_rounds;
@@ -385,6 +515,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             initial_hash,
             padded_chunks: padded_chunks.to_vec(),
             digest_indices,
+            initial_states: None,
         };
 
         let trace = SHATraceData {
@@ -402,6 +533,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             end_bit,
             digest_bit,
             dummy_index,
+            initial_states: None,
         };
         SHAData {
             public,
@@ -474,12 +606,9 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         builder: &mut B,
         w_i: Self::IntRegister,
         data: &SHAData<Self::IntRegister, CYCLE_LENGTH>,
-    ) -> Vec<Self::StateVariable> {
-        let num_digests = data.public.digest_indices.len();
-        let hash_state_public = (0..num_digests)
-            .map(|_| builder.alloc_public::<Self::StateVariable>())
-            .collect::<Vec<_>>();
-        let state_ptr = Self::load_state(builder, &hash_state_public, data.public.digest_indices);
+        hash_state_public: &[Self::StateVariable],
+    ) {
+        let state_ptr = Self::load_state(builder, hash_state_public, data.public.digest_indices);
 
         let index = data.trace.index;
         let initial_hash = data.public.initial_hash;
@@ -492,14 +621,35 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             None,
         );
 
+        // The state of the first row, and the state that the working variables are reset to at
+        // the end of a message.
+        let (first_hash, reset_hash) =
+            match (&data.public.initial_states, &data.memory.initial_states) {
+                (Some(states), Some(slices)) => {
+                    let reset_hash = slices
+                        .iter()
+                        .map(|slice| {
+                            builder.load(
+                                &slice.get_at_shifted(data.trace.process_id, 1),
+                                &Time::zero(),
+                                None,
+                                None,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (states[0], reset_hash)
+                }
+                _ => (initial_hash, initial_hash.iter().collect()),
+            };
+
         // Initialize working variables
         let state = builder.alloc_array::<Self::IntRegister>(8);
-        for (h, h_init) in state.iter().zip(initial_hash.iter()) {
+        for (h, h_init) in state.iter().zip(first_hash.iter()) {
             builder.set_to_expression_first_row(&h, h_init.expr());
         }
         // Initialize working variables and set them to the inital hash in the first row.
         let vars = builder.alloc_array::<Self::IntRegister>(8);
-        for (v, h_init) in vars.iter().zip(initial_hash.iter()) {
+        for (v, h_init) in vars.iter().zip(first_hash.iter()) {
             builder.set_to_expression_first_row(&v, h_init.expr());
         }
 
@@ -539,7 +689,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         for ((((var, h), init), var_next), h_next) in vars
             .iter()
             .zip(state.iter())
-            .zip(initial_hash.iter())
+            .zip(reset_hash.iter())
             .zip(vars_next.iter())
             .zip(state_next_arr.iter())
         {
@@ -556,7 +706,5 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
                         * bit.expr(),
             );
         }
-
-        hash_state_public
     }
 }
@@ -157,7 +157,9 @@ pub mod test_utils {
                 .read_array::<_, 8>(&array)
                 .map(|x| S::field_value_to_int(&x));
             let expected_digest = S::decode(expected);
-            assert_eq!(digest, expected_digest);
+            // Truncated digests only cover the first words of the state.
+            let num_words = expected.len() / (2 * core::mem::size_of::<S::Integer>());
+            assert_eq!(digest[..num_words], expected_digest[..num_words]);
         }
 
         let (trace, public) = (writer_data.trace, writer_data.public);
@@ -14,6 +14,8 @@ pub struct SHAPublicData<T> {
     pub initial_hash: ArrayRegister<T>,
     pub padded_chunks: Vec<ArrayRegister<T>>,
     pub digest_indices: ArrayRegister<ElementRegister>,
+    /// The initial state of every chunk, if the chunks are compressed independently.
+    pub initial_states: Option<Vec<ArrayRegister<T>>>,
 }
 
 pub struct SHATraceData<const LENGTH: usize> {
@@ -31,4 +33,6 @@ pub struct SHAMemory<T> {
     pub end_bit: Slice<BitRegister>,
     pub digest_bit: Slice<BitRegister>,
     pub dummy_index: ElementRegister,
+    /// For every word of the state, the initial values of the chunks indexed by chunk.
+    pub(crate) initial_states: Option<Vec<Slice<T>>>,
 }
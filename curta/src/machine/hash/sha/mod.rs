@@ -1,5 +1,7 @@
 pub mod algorithm;
 pub mod builder;
 pub mod data;
+pub mod sha1;
 pub mod sha256;
+pub mod sha384;
 pub mod sha512;
@@ -0,0 +1,328 @@
+use plonky2::util::log2_ceil;
+
+use super::{INITIAL_HASH, NUM_STEPS, ROUND_CONSTANTS, SHA1, STATE_WORDS};
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+const DUMMY_INDEX: u64 = i32::MAX as u64;
+
+impl SHA1 {
+    /// A bit that is `1` on the last row of every compression and `0` otherwise.
+    fn cycle_end_bit<L: AirParameters>(builder: &mut BytesBuilder<L>) -> BitRegister
+    where
+        L::Instruction: UintInstructions,
+    {
+        let cycle_16 = builder.cycle(4);
+        let loop_5 = builder.api.loop_instr(5);
+        builder.mul(loop_5.get_iteration_reg(4), cycle_16.end_bit)
+    }
+
+    /// Stores `values` in a slice, where value `i` is read `multiplicity(i)` times.
+    fn values_slice<L: AirParameters, V: MemoryValue>(
+        builder: &mut BytesBuilder<L>,
+        values: impl Iterator<Item = V>,
+        multiplicity: impl Fn(usize) -> ElementRegister,
+    ) -> Slice<V>
+    where
+        L::Instruction: UintInstructions,
+    {
+        let slice = builder.uninit_slice();
+        for (i, value) in values.enumerate() {
+            builder.store(
+                &slice.get(i),
+                value,
+                &Time::zero(),
+                Some(multiplicity(i)),
+                None,
+                None,
+            );
+        }
+        slice
+    }
+
+    /// Hashes the messages given by `padded_chunks` with SHA-1.
+    ///
+    /// Every chunk consists of sixteen padded message words as given by `SHA1::pad`, and each
+    /// compression takes `NUM_STEPS` rows, so the trace must have
+    /// `(80 * padded_chunks.len()).next_power_of_two()` rows. `end_bits[i]` marks the last chunk
+    /// of a message and `digest_bits[i]` the chunks whose state is a digest, whose chunk index is
+    /// given in `digest_indices`. Returns the five digest words for every entry of
+    /// `digest_indices`.
+    pub fn sha1<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U32Register>>
+    where
+        L::Instruction: UintInstructions,
+    {
+        let num_real_rounds = padded_chunks.len();
+        assert_eq!(end_bits.len(), num_real_rounds);
+        assert_eq!(digest_bits.len(), num_real_rounds);
+        for chunk in padded_chunks.iter() {
+            assert_eq!(chunk.len(), 16, "Chunks must have sixteen words");
+        }
+
+        let degree_log = log2_ceil(num_real_rounds * NUM_STEPS);
+        assert!(degree_log < 31, "AIR degree is too large");
+        let num_rows = 1 << degree_log;
+        // The last round is cut short by the end of the trace.
+        let num_dummy_rounds = num_rows / NUM_STEPS + 1 - num_real_rounds;
+        let length_last_round = num_rows % NUM_STEPS;
+        let num_rounds = num_real_rounds + num_dummy_rounds;
+
+        let one = builder.constant::<BitRegister>(&L::Field::ONE);
+        let zero = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let bit = |value: bool| if value { one } else { zero };
+
+        // Store the parameters of every step, each read once in every round.
+        let num_round_element =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_rounds));
+        let num_round_minus_one =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_rounds - 1));
+        let step_multiplicity = |i: usize| {
+            if i < length_last_round {
+                num_round_element
+            } else {
+                num_round_minus_one
+            }
+        };
+        let round_constant_values =
+            builder.constant_array::<U32Register>(&ROUND_CONSTANTS.map(u32_to_le_field_bytes));
+        let round_constants = Self::values_slice(
+            builder,
+            (0..NUM_STEPS).map(|i| round_constant_values.get(i / 20)),
+            step_multiplicity,
+        );
+        let is_message_slice = Self::values_slice(
+            builder,
+            (0..NUM_STEPS).map(|i| bit(i < 16)),
+            step_multiplicity,
+        );
+        // The functions of the four rounds are the choice, parity, majority and parity functions.
+        let function_bit_slices = [
+            [true, false, false, false],
+            [false, true, false, true],
+            [false, false, true, false],
+        ]
+        .map(|rounds| {
+            Self::values_slice(
+                builder,
+                (0..NUM_STEPS).map(|i| bit(rounds[i / 20])),
+                step_multiplicity,
+            )
+        });
+
+        // Store the parameters of every round, read on every row of the round.
+        let reg_cycle_length =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(NUM_STEPS));
+        let reg_last_length =
+            builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(length_last_round));
+        let round_multiplicity = |i: usize| {
+            if i == num_rounds - 1 {
+                reg_last_length
+            } else {
+                reg_cycle_length
+            }
+        };
+        let padding =
+            |dummy_value: BitRegister| core::iter::repeat(dummy_value).take(num_dummy_rounds);
+        let end_bit_slice = Self::values_slice(
+            builder,
+            end_bits.iter().chain(padding(zero)),
+            round_multiplicity,
+        );
+        let digest_bit_slice = Self::values_slice(
+            builder,
+            digest_bits.iter().chain(padding(zero)),
+            round_multiplicity,
+        );
+        let is_dummy_slice = Self::values_slice(
+            builder,
+            (0..num_real_rounds).map(|_| zero).chain(padding(one)),
+            round_multiplicity,
+        );
+
+        // Store the words of the chunks, each read in one of the first sixteen steps of its round.
+        // All the other steps read a zero word instead.
+        let words = builder.uninit_slice();
+        for (i, chunk) in padded_chunks.iter().enumerate() {
+            for (j, word) in chunk.iter().enumerate() {
+                builder.store(
+                    &words.get(16 * i + j),
+                    word,
+                    &Time::zero(),
+                    None,
+                    None,
+                    None,
+                );
+            }
+        }
+        assert!((16 * num_real_rounds) < DUMMY_INDEX as usize);
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let dummy_index = builder.constant(&L::Field::from_canonical_u64(DUMMY_INDEX));
+        let num_dummy_reads = builder.constant::<ElementRegister>(&L::Field::from_canonical_usize(
+            num_rows - 16 * num_real_rounds,
+        ));
+        builder.store(
+            &words.get(DUMMY_INDEX as usize),
+            zero_word,
+            &Time::zero(),
+            Some(num_dummy_reads),
+            None,
+            None,
+        );
+
+        let cycle_end_bit = Self::cycle_end_bit(builder);
+        let process_id = builder.process_id(NUM_STEPS, cycle_end_bit);
+        let clk = builder.clk;
+        let step = builder
+            .expression(clk.expr() - process_id.expr() * L::Field::from_canonical_usize(NUM_STEPS));
+        let [end_bit, digest_bit, is_dummy] = [end_bit_slice, digest_bit_slice, is_dummy_slice]
+            .map(|slice| builder.load(&slice.get_at(process_id), &Time::zero(), None, None));
+        let round_constant = builder.load(&round_constants.get_at(step), &Time::zero(), None, None);
+        let is_message = builder.load(&is_message_slice.get_at(step), &Time::zero(), None, None);
+        let function_bits = function_bit_slices
+            .map(|slice| builder.load(&slice.get_at(step), &Time::zero(), None, None));
+
+        // The message schedule. The window holds the words `w[i-16], ..., w[i-1]`, and `w[i]` is
+        // read from the chunk in the first sixteen steps and computed from the window otherwise.
+        let read_flag = builder.expression::<BitRegister>(is_message.expr() * is_dummy.not_expr());
+        let word_index = builder.expression(
+            read_flag.expr()
+                * (process_id.expr() * L::Field::from_canonical_usize(16) + step.expr())
+                + read_flag.not_expr() * dummy_index.expr(),
+        );
+        let message_word = builder.load(&words.get_at(word_index), &Time::zero(), None, None);
+
+        let window = builder.alloc_array::<U32Register>(16);
+        for word in window.iter() {
+            builder.set_to_expression_first_row(&word, zero_word.expr());
+        }
+        let xor_1 = builder.xor(&window.get(13), &window.get(8));
+        let xor_2 = builder.xor(&xor_1, &window.get(2));
+        let xor_3 = builder.xor(&xor_2, &window.get(0));
+        let schedule_word = builder.rotate_right(xor_3, 31);
+        let w_i = builder.expression::<U32Register>(
+            message_word.expr() * is_message.expr() + schedule_word.expr() * is_message.not_expr(),
+        );
+        for j in 0..15 {
+            builder.set_to_expression_transition(&window.get(j).next(), window.get(j + 1).expr());
+        }
+        builder.set_to_expression_transition(&window.get(15).next(), w_i.expr());
+
+        // The state before the round, and the working state `(a, b, c, d, e)` before the step.
+        let initial_hash =
+            builder.constant_array::<U32Register>(&INITIAL_HASH.map(u32_to_le_field_bytes));
+        let h = builder.alloc_array::<U32Register>(STATE_WORDS);
+        let state = builder.alloc_array::<U32Register>(STATE_WORDS);
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_first_row(&h.get(i), initial_hash.get(i).expr());
+            builder.set_to_expression_first_row(&state.get(i), initial_hash.get(i).expr());
+        }
+
+        let [a, b, c, d, e] = core::array::from_fn(|i| state.get(i));
+        let f = Self::boolean_function(builder, b, c, d, &function_bits);
+        let a_rotated = builder.rotate_right(a, 27);
+        let temp = builder.add(a_rotated, f);
+        let temp = builder.add(temp, e);
+        let temp = builder.add(temp, round_constant);
+        let temp = builder.add(temp, w_i);
+        let b_rotated = builder.rotate_right(b, 2);
+        let state_output = [temp, a, b_rotated, c, d];
+        let h_output = (0..STATE_WORDS)
+            .map(|i| builder.add(h.get(i), state_output[i]))
+            .collect::<Vec<_>>();
+
+        // At the end of a round, the state is updated and reset after the last chunk of a
+        // message. The working state starts from the state.
+        for i in 0..STATE_WORDS {
+            builder.set_to_expression_transition(
+                &h.get(i).next(),
+                h.get(i).expr() * cycle_end_bit.not_expr()
+                    + (h_output[i].expr() * end_bit.not_expr()
+                        + initial_hash.get(i).expr() * end_bit.expr())
+                        * cycle_end_bit.expr(),
+            );
+            builder.set_to_expression_transition(
+                &state.get(i).next(),
+                state_output[i].expr() * cycle_end_bit.not_expr()
+                    + h.get(i).next().expr() * cycle_end_bit.expr(),
+            );
+        }
+
+        // Store the digests of the rounds flagged by the digest bits.
+        let state_ptr = builder.uninit_slice();
+        let hash_state_public = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<U32Register>(STATE_WORDS))
+            .collect::<Vec<_>>();
+        for (i, h) in digest_indices.iter().zip(hash_state_public.iter()) {
+            for (j, word) in h.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(i));
+            }
+        }
+        let digest_flag =
+            builder.expression(cycle_end_bit.expr() * is_dummy.not_expr() * digest_bit.expr());
+        for (j, word) in h_output.iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                *word,
+                &Time::from_element(process_id),
+                Some(digest_flag),
+                None,
+                None,
+            );
+        }
+
+        hash_state_public
+    }
+
+    /// The choice, parity or majority function of `(x, y, z)`, selected by the one-hot
+    /// `function_bits`.
+    fn boolean_function<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+        x: U32Register,
+        y: U32Register,
+        z: U32Register,
+        function_bits: &[BitRegister; 3],
+    ) -> U32Register
+    where
+        L::Instruction: UintInstructions,
+    {
+        // (x & y) | (!x & z), where the two terms have no bits in common.
+        let not_x = builder.not(x);
+        let x_and_y = builder.and(&x, &y);
+        let not_x_and_z = builder.and(&not_x, &z);
+        let choice = builder.xor(&x_and_y, &not_x_and_z);
+
+        // x ^ y ^ z
+        let x_xor_y = builder.xor(&x, &y);
+        let parity = builder.xor(&x_xor_y, &z);
+
+        // (x & y) ^ (x & z) ^ (y & z)
+        let x_and_z = builder.and(&x, &z);
+        let y_and_z = builder.and(&y, &z);
+        let maj_xor = builder.xor(&x_and_y, &x_and_z);
+        let majority = builder.xor(&maj_xor, &y_and_z);
+
+        builder.expression(
+            choice.expr() * function_bits[0].expr()
+                + parity.expr() * function_bits[1].expr()
+                + majority.expr() * function_bits[2].expr(),
+        )
+    }
+}
@@ -0,0 +1,156 @@
+use super::SHA1;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::AirParameters;
+use crate::machine::bytes::builder::BytesBuilder;
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the SHA-1 hash of the messages given by `padded_chunks`.
+    ///
+    /// See `SHA1::sha1` for the layout of the inputs and the number of rows.
+    pub fn sha1(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U32Register>> {
+        SHA1::sha1(self, padded_chunks, end_bits, digest_bits, digest_indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_to_le_field_bytes;
+    use crate::machine::builder::Builder;
+    use crate::machine::hash::sha::sha1::NUM_STEPS;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA1Test;
+
+    impl AirParameters for SHA1Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 2000;
+        const EXTENDED_COLUMNS: usize = 1000;
+    }
+
+    #[test]
+    fn test_sha1() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha1", log::Level::Info);
+
+        let msgs = [
+            b"".to_vec(),
+            b"abc".to_vec(),
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_vec(),
+        ];
+
+        let mut padded_chunks_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            let padded_msg = SHA1::pad(msg);
+            let num_chunks = padded_msg.len() / 16;
+            padded_chunks_values.extend(padded_msg.chunks_exact(16).map(|c| c.to_vec()));
+            end_bits_values.extend((0..num_chunks).map(|i| i == num_chunks - 1));
+            digest_indices_values.push(end_bits_values.len() - 1);
+        }
+        let num_blocks = padded_chunks_values.len();
+
+        let mut builder = BytesBuilder::<SHA1Test>::new();
+        let padded_chunks = (0..num_blocks)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_blocks);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(msgs.len());
+        let digests = builder.sha1(&padded_chunks, &end_bits, &end_bits, &digest_indices);
+
+        let num_rows = (NUM_STEPS * num_blocks).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, chunk) in padded_chunks_values.iter().enumerate() {
+            writer.write_array(
+                &padded_chunks[i],
+                chunk.iter().map(|word| u32_to_le_field_bytes(*word)),
+            );
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+        }
+        for (i, (msg, index)) in msgs.iter().zip(digest_indices_values.iter()).enumerate() {
+            writer.write(&digest_indices.get(i), &F::from_canonical_usize(*index));
+            writer.write_array(&digests[i], SHA1::hash(msg).map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
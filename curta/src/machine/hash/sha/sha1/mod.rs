@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod builder;
+pub mod pure;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SHA1;
+
+/// The number of steps of the compression function, each taking a row of the trace.
+pub const NUM_STEPS: usize = 80;
+/// The number of 32-bit words of the state and of a digest.
+pub const STATE_WORDS: usize = 5;
+
+pub(crate) const INITIAL_HASH: [u32; STATE_WORDS] =
+    [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// The constants of the four rounds of twenty steps.
+pub(crate) const ROUND_CONSTANTS: [u32; 4] = [0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xCA62C1D6];
@@ -0,0 +1,86 @@
+use super::{INITIAL_HASH, NUM_STEPS, ROUND_CONSTANTS, SHA1, STATE_WORDS};
+
+impl SHA1 {
+    pub fn pad(msg: &[u8]) -> Vec<u32> {
+        let mut padded_msg = Vec::new();
+        padded_msg.extend_from_slice(msg);
+        padded_msg.push(1 << 7);
+        while padded_msg.len() % 64 != 56 {
+            padded_msg.push(0);
+        }
+        padded_msg.extend_from_slice(&((msg.len() * 8) as u64).to_be_bytes());
+
+        padded_msg
+            .chunks_exact(4)
+            .map(|slice| u32::from_be_bytes(slice.try_into().unwrap()))
+            .collect()
+    }
+
+    /// The boolean function of step `i`.
+    pub fn f(i: usize, b: u32, c: u32, d: u32) -> u32 {
+        match i / 20 {
+            0 => (b & c) | (!b & d),
+            2 => (b & c) | (b & d) | (c & d),
+            _ => b ^ c ^ d,
+        }
+    }
+
+    pub fn pre_process(chunk: &[u32]) -> [u32; NUM_STEPS] {
+        let mut w = [0u32; NUM_STEPS];
+        w[..16].copy_from_slice(&chunk[..16]);
+        for i in 16..NUM_STEPS {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        w
+    }
+
+    pub fn process(hash: [u32; STATE_WORDS], w: &[u32; NUM_STEPS]) -> [u32; STATE_WORDS] {
+        let mut state = hash;
+        for (i, w_i) in w.iter().enumerate() {
+            let [a, b, c, d, e] = state;
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(Self::f(i, b, c, d))
+                .wrapping_add(e)
+                .wrapping_add(ROUND_CONSTANTS[i / 20])
+                .wrapping_add(*w_i);
+            state = [temp, a, b.rotate_left(30), c, d];
+        }
+        core::array::from_fn(|i| hash[i].wrapping_add(state[i]))
+    }
+
+    pub fn hash(msg: &[u8]) -> [u32; STATE_WORDS] {
+        Self::pad(msg)
+            .chunks_exact(16)
+            .fold(INITIAL_HASH, |hash, chunk| {
+                Self::process(hash, &Self::pre_process(chunk))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(digest: [u32; STATE_WORDS]) -> String {
+        digest.iter().map(|word| format!("{:08x}", word)).collect()
+    }
+
+    #[test]
+    fn test_sha1_pure() {
+        assert_eq!(
+            encode(SHA1::hash(b"")),
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+        );
+        assert_eq!(
+            encode(SHA1::hash(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            encode(SHA1::hash(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+}
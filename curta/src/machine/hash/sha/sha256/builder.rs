@@ -0,0 +1,696 @@
+use super::register::SHA256DigestRegister;
+use super::SHA256;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::math::prelude::*;
+
+/// The padding words of a second block whose message is a single 32-byte digest.
+const DIGEST_BLOCK_PADDING: [u32; 8] = [0x80000000, 0, 0, 0, 0, 0, 0, 256];
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the double SHA-256 hash `SHA256(SHA256(m))` of the messages given by
+    /// `padded_chunks`.
+    ///
+    /// The inputs follow the layout of `SHAir::sha`: `end_bits[i]` marks the last chunk of a
+    /// message and `digest_indices` holds the index of that chunk for every message. The second
+    /// block of each message is built in-circuit from the first digest, so the caller only pads
+    /// the original messages. The trace must have at least `64 * (padded_chunks.len() +
+    /// digest_indices.len())` rows.
+    ///
+    /// Returns the inner and the outer digests of every message. Both must be written by the
+    /// caller, just like the digests of `SHAir::sha`.
+    pub fn sha256d(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> (Vec<SHA256DigestRegister>, Vec<SHA256DigestRegister>) {
+        assert_eq!(padded_chunks.len(), end_bits.len());
+        let num_chunks = padded_chunks.len();
+        let num_messages = digest_indices.len();
+
+        // The second blocks are appended after all the chunks of the first hash.
+        let second_blocks = (0..num_messages)
+            .map(|_| self.alloc_array_public_unchecked::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let chunks = padded_chunks
+            .iter()
+            .chain(second_blocks.iter())
+            .copied()
+            .collect::<Vec<_>>();
+
+        // Every message ends twice, once in its own chunks and once in its second block.
+        let all_end_bits = self.alloc_array_public_unchecked::<BitRegister>(chunks.len());
+        for (bit, end_bit) in all_end_bits.iter().zip(end_bits.iter()) {
+            self.api().set_to_expression_public(&bit, end_bit.expr());
+        }
+        let one = self.constant::<BitRegister>(&L::Field::ONE);
+        for bit in all_end_bits.iter().skip(num_chunks) {
+            self.api().set_to_expression_public(&bit, one.expr());
+        }
+
+        // The inner digests come first, followed by the outer digests.
+        let all_digest_indices =
+            self.alloc_array_public_unchecked::<ElementRegister>(2 * num_messages);
+        for (index, digest_index) in all_digest_indices.iter().zip(digest_indices.iter()) {
+            self.api()
+                .set_to_expression_public(&index, digest_index.expr());
+        }
+        for (i, index) in all_digest_indices.iter().skip(num_messages).enumerate() {
+            let value =
+                self.constant::<ElementRegister>(&L::Field::from_canonical_usize(num_chunks + i));
+            self.api().set_to_expression_public(&index, value.expr());
+        }
+
+        // Wire each inner digest into the first half of its second block, followed by the
+        // padding of a 256-bit message. The blocks must be set before they are stored, so the
+        // digests are allocated first.
+        let digests = (0..2 * num_messages)
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        let (inner_digests, outer_digests) = digests.split_at(num_messages);
+        let padding = self.constant_array::<U32Register>(
+            &DIGEST_BLOCK_PADDING.map(u32_to_le_field_bytes::<L::Field>),
+        );
+        for (block, digest) in second_blocks.iter().zip(inner_digests.iter()) {
+            for (word, digest_word) in block.iter().zip(digest.as_array().iter()) {
+                self.api()
+                    .set_to_expression_public(&word, digest_word.expr());
+            }
+            for (word, padding_word) in block.iter().skip(8).zip(padding.iter()) {
+                self.api()
+                    .set_to_expression_public(&word, padding_word.expr());
+            }
+        }
+
+        SHA256::sha_with_digests(
+            self,
+            &chunks,
+            &all_end_bits,
+            &all_end_bits,
+            all_digest_indices,
+            &digests,
+        );
+
+        (inner_digests.to_vec(), outer_digests.to_vec())
+    }
+
+    /// Proves the SHA-256 digest of every message in `padded_messages`, in a trace of `num_rows`
+    /// rows.
+    ///
+    /// The chunks of each message must be padded with `SHA256::pad`. The hash takes the whole
+    /// trace alongside the other gadgets of the AIR, so `num_rows` must be the number of rows the
+    /// AIR is built with, a power of two of at least `64` times the total number of chunks.
+    /// Returns the digest of every message, which must be written by the caller.
+    pub fn sha256(
+        &mut self,
+        padded_messages: &[Vec<ArrayRegister<U32Register>>],
+        num_rows: usize,
+    ) -> Vec<SHA256DigestRegister> {
+        let digests = (0..padded_messages.len())
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        self.sha256_with_digests(padded_messages, &digests, num_rows);
+        digests
+    }
+
+    /// Proves that `digests` are the SHA-256 digests of `padded_messages`, as in `sha256`.
+    fn sha256_with_digests(
+        &mut self,
+        padded_messages: &[Vec<ArrayRegister<U32Register>>],
+        digests: &[SHA256DigestRegister],
+        num_rows: usize,
+    ) {
+        let mut chunks = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for padded_msg in padded_messages.iter() {
+            chunks.extend_from_slice(padded_msg);
+            end_bits_values.extend((1..padded_msg.len()).map(|_| L::Field::ZERO));
+            end_bits_values.push(L::Field::ONE);
+            digest_indices_values.push(L::Field::from_canonical_usize(chunks.len() - 1));
+        }
+
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices = self.constant_array::<ElementRegister>(&digest_indices_values);
+
+        SHA256::sha_with_digests_in_rows(
+            self,
+            &chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            digests,
+            num_rows,
+        );
+    }
+
+    /// Proves the expansion `SHA256::expand_message_xmd(msg, dst, len_in_bytes)` of the message
+    /// given by the public bytes `msg`, in a trace of `num_rows` rows.
+    ///
+    /// The message `msg_prime` and the blocks `(b_0 xor b_(i-1)) || i || DST'` of RFC 9380 are
+    /// built in-circuit from `msg` and the digests, and hashed as in `sha256`. `num_rows` must be
+    /// the number of rows the AIR is built with, a power of two of at least `64` times
+    /// `SHA256::expand_message_xmd_num_chunks`. Returns the digests `b_0, ..., b_ell`, whose
+    /// values are given by `SHA256::expand_message_xmd_digests` and must be written by the
+    /// caller. The uniform bytes are the bytes of `b_1, ..., b_ell`.
+    pub fn expand_message_xmd(
+        &mut self,
+        msg: &ArrayRegister<ByteRegister>,
+        dst: &[u8],
+        len_in_bytes: usize,
+        num_rows: usize,
+    ) -> Vec<SHA256DigestRegister> {
+        let ell = len_in_bytes.div_ceil(32);
+        assert!(ell <= 255 && len_in_bytes <= u16::MAX as usize && dst.len() <= 255);
+        let dst_prime = [dst, &[dst.len() as u8]].concat();
+        let constants = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| ArithmeticExpression::from_constant(L::Field::from_canonical_u8(*byte)))
+                .collect::<Vec<_>>()
+        };
+
+        // The blocks are built from the digests, so the digests are allocated first.
+        let digests = (0..=ell)
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        // Byte `k` of a digest is byte `3 - k % 4` of the big-endian word `k / 4`.
+        let digest_byte = |digest: &SHA256DigestRegister, k: usize| {
+            digest.get(k / 4).to_le_bytes().get(3 - k % 4)
+        };
+
+        let msg_prime = [
+            constants(&[0; 64]),
+            msg.iter().map(|byte| byte.expr()).collect(),
+            constants(&(len_in_bytes as u16).to_be_bytes()),
+            constants(&[0]),
+            constants(&dst_prime),
+        ]
+        .concat();
+        let mut messages = vec![msg_prime];
+        for i in 1..=ell {
+            let xor_bytes = (0..32)
+                .map(|k| {
+                    let b_0 = digest_byte(&digests[0], k);
+                    if i == 1 {
+                        b_0.expr()
+                    } else {
+                        let result = self.alloc_public_unchecked::<ByteRegister>();
+                        let op = ByteOperation::Xor(b_0, digest_byte(&digests[i - 1], k), result);
+                        self.api
+                            .set_public_inputs_byte_operation(&op, &mut self.operations);
+                        result.expr()
+                    }
+                })
+                .collect::<Vec<_>>();
+            messages.push([xor_bytes, constants(&[i as u8]), constants(&dst_prime)].concat());
+        }
+
+        let padded_messages = messages
+            .iter()
+            .map(|msg| self.sha256_chunks(msg))
+            .collect::<Vec<_>>();
+        self.sha256_with_digests(&padded_messages, &digests, num_rows);
+        digests
+    }
+
+    /// Allocates the chunks of the message whose bytes are `msg`, padded with `SHA256::pad`.
+    fn sha256_chunks(
+        &mut self,
+        msg: &[ArithmeticExpression<L::Field>],
+    ) -> Vec<ArrayRegister<U32Register>> {
+        let padding = SHA256::pad(&vec![0; msg.len()]);
+        let chunks = (0..padding.len() / 16)
+            .map(|_| self.alloc_array_public_unchecked::<U32Register>(16))
+            .collect::<Vec<_>>();
+        // Byte `k` of the message is byte `3 - k % 4` of the big-endian word `k / 4`.
+        for k in 0..4 * padding.len() {
+            let byte = chunks[k / 64].get(k % 64 / 4).to_le_bytes().get(3 - k % 4);
+            let value = msg.get(k).cloned().unwrap_or_else(|| {
+                let padding_byte = padding[k / 4].to_be_bytes()[k % 4];
+                ArithmeticExpression::from_constant(L::Field::from_canonical_u8(padding_byte))
+            });
+            self.set_to_expression(&byte, value);
+        }
+        chunks
+    }
+
+    /// Proves the BIP-340 tagged hash `SHA256(SHA256(tag) || SHA256(tag) || m)` of every message
+    /// in `padded_messages`, in a trace of `num_rows` rows.
+    ///
+    /// `SHA256(tag)` is computed when building the AIR and the tag block is added as a constant
+    /// chunk in front of each message. The chunks of each message must be padded with
+    /// `SHA256::tagged_pad`, which accounts for the length of the tag block. As for `sha256`,
+    /// `num_rows` must be the number of rows the AIR is built with, a power of two of at least
+    /// `64 * (num_chunks + padded_messages.len())`, where `num_chunks` is the total number of
+    /// message chunks. Returns the digest of every message, which must be written by the caller.
+    pub fn tagged_hash(
+        &mut self,
+        tag: &[u8],
+        padded_messages: &[Vec<ArrayRegister<U32Register>>],
+        num_rows: usize,
+    ) -> Vec<SHA256DigestRegister> {
+        let tag_block = self.constant_array::<U32Register>(
+            &SHA256::tag_block(tag).map(u32_to_le_field_bytes::<L::Field>),
+        );
+
+        let mut chunks = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for padded_msg in padded_messages.iter() {
+            chunks.push(tag_block);
+            chunks.extend_from_slice(padded_msg);
+            end_bits_values.extend((0..padded_msg.len()).map(|_| L::Field::ZERO));
+            end_bits_values.push(L::Field::ONE);
+            digest_indices_values.push(L::Field::from_canonical_usize(chunks.len() - 1));
+        }
+
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices = self.constant_array::<ElementRegister>(&digest_indices_values);
+        let digests = (0..padded_messages.len())
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+
+        SHA256::sha_with_digests_in_rows(
+            self,
+            &chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &digests,
+            num_rows,
+        );
+        digests
+    }
+
+    /// Proves a single SHA-256 compression of each of `chunks`, starting from the corresponding
+    /// state of `states`.
+    ///
+    /// No padding is applied and the compressions are independent, so they can be chained by the
+    /// caller in any way. See `SHAir::compress` for the number of rows. Returns the output states,
+    /// whose values must be written by the caller.
+    pub fn sha256_compress(
+        &mut self,
+        states: &[ArrayRegister<U32Register>],
+        chunks: &[ArrayRegister<U32Register>],
+    ) -> Vec<SHA256DigestRegister> {
+        SHA256::compress(self, states, chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_from_le_field_bytes;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA256dTest;
+
+    impl AirParameters for SHA256dTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1200;
+        const EXTENDED_COLUMNS: usize = 700;
+    }
+
+    #[test]
+    fn test_sha256d() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256d", log::Level::Info);
+
+        let msgs = [b"abc".to_vec(), b"".to_vec(), b"hello".repeat(30)];
+        let expected_digests = [
+            "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358",
+            "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456",
+            "dbfb4cc8bb146924fe984c2ef579158e46f43387bcfa17e09363d899a5aad6c4",
+        ];
+
+        let mut padded_chunks_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            let padded_msg = SHA256::pad(msg);
+            let num_chunks = padded_msg.len() / 16;
+            padded_chunks_values.extend(padded_msg.chunks_exact(16).map(|c| c.to_vec()));
+            end_bits_values.extend((0..num_chunks).map(|i| i == num_chunks - 1));
+            digest_indices_values.push(end_bits_values.len() - 1);
+        }
+        let num_rounds = padded_chunks_values.len();
+
+        let mut builder = BytesBuilder::<SHA256dTest>::new();
+        let padded_chunks = (0..num_rounds)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(msgs.len());
+        let (inner_digests, outer_digests) =
+            builder.sha256d(&padded_chunks, &end_bits, &digest_indices);
+
+        let num_rows = 1 << log2_ceil(64 * (num_rounds + msgs.len()));
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut state = SHA256::INITIAL_HASH;
+        let mut inner_iter = inner_digests.iter();
+        let mut outer_iter = outer_digests.iter();
+        for (i, chunk) in padded_chunks_values.iter().enumerate() {
+            writer.write_array(
+                &padded_chunks[i],
+                chunk.iter().map(|w| u32_to_le_field_bytes(*w)),
+            );
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+
+            state = SHA256::process(state, &SHA256::pre_process(chunk));
+            if end_bits_values[i] {
+                let inner: ArrayRegister<U32Register> = (*inner_iter.next().unwrap()).into();
+                writer.write_array(&inner, state.map(u32_to_le_field_bytes));
+
+                let mut second_block = state.to_vec();
+                second_block.extend_from_slice(&DIGEST_BLOCK_PADDING);
+                let outer_state =
+                    SHA256::process(SHA256::INITIAL_HASH, &SHA256::pre_process(&second_block));
+                let outer: ArrayRegister<U32Register> = (*outer_iter.next().unwrap()).into();
+                writer.write_array(&outer, outer_state.map(u32_to_le_field_bytes));
+                state = SHA256::INITIAL_HASH;
+            }
+        }
+        for (i, index) in digest_indices_values.iter().enumerate() {
+            writer.write(&digest_indices.get(i), &F::from_canonical_usize(*index));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, expected) in outer_digests.iter().zip(expected_digests) {
+            let digest: ArrayRegister<U32Register> = (*digest).into();
+            let value = writer
+                .read_array::<_, 8>(&digest)
+                .map(|word| u32_from_le_field_bytes(&word));
+            assert_eq!(value, SHA256::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_tagged_hash() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_tagged_hash", log::Level::Info);
+
+        let tag = b"BIP0340/challenge";
+        let msgs = [(0..96).collect::<Vec<u8>>(), b"".to_vec()];
+        let expected_digests = [
+            "4345168763d5810509f4d0bf61eb704ccfa6a551a28fb4d07f7aca1fa78522f0",
+            "c216d352f5818b7b4beacd4ae0a26fe888080823d2a598856661bcd54f1b3713",
+        ];
+
+        let padded_messages_values = msgs
+            .iter()
+            .map(|msg| SHA256::tagged_pad(msg))
+            .collect::<Vec<_>>();
+        let num_rounds = padded_messages_values
+            .iter()
+            .map(|padded_msg| padded_msg.len() / 16 + 1)
+            .sum::<usize>();
+
+        let mut builder = BytesBuilder::<SHA256dTest>::new();
+        let padded_messages = padded_messages_values
+            .iter()
+            .map(|padded_msg| {
+                (0..padded_msg.len() / 16)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let num_rows = 1 << log2_ceil(64 * num_rounds);
+        let digests = builder.tagged_hash(tag, &padded_messages, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let tag_block = SHA256::tag_block(tag);
+        for ((chunks, values), digest) in padded_messages
+            .iter()
+            .zip(padded_messages_values.iter())
+            .zip(digests.iter())
+        {
+            let mut state = SHA256::process(SHA256::INITIAL_HASH, &SHA256::pre_process(&tag_block));
+            for (chunk, value) in chunks.iter().zip(values.chunks_exact(16)) {
+                writer.write_array(chunk, value.iter().map(|w| u32_to_le_field_bytes(*w)));
+                state = SHA256::process(state, &SHA256::pre_process(value));
+            }
+            let digest: ArrayRegister<U32Register> = (*digest).into();
+            writer.write_array(&digest, state.map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, expected) in digests.iter().zip(expected_digests) {
+            let digest: ArrayRegister<U32Register> = (*digest).into();
+            let value = writer
+                .read_array::<_, 8>(&digest)
+                .map(|word| u32_from_le_field_bytes(&word));
+            assert_eq!(value, SHA256::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_expand_message_xmd", log::Level::Info);
+
+        // The test vector of RFC 9380, Appendix K.1, with `len_in_bytes = 0x80`.
+        let (msg, dst, len_in_bytes) = (b"abc", b"QUUX-V01-CS02-with-expander-SHA256-128", 0x80);
+        let expected = "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40";
+        assert_eq!(
+            hex::encode(SHA256::expand_message_xmd(msg, dst, len_in_bytes)),
+            expected
+        );
+
+        let num_chunks = SHA256::expand_message_xmd_num_chunks(msg.len(), dst.len(), len_in_bytes);
+        let num_rows = 1 << log2_ceil(64 * num_chunks);
+        let mut builder = BytesBuilder::<SHA256dTest>::new();
+        let msg_bytes = builder.alloc_array_public::<ByteRegister>(msg.len());
+        let digests = builder.expand_message_xmd(&msg_bytes, dst, len_in_bytes, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        writer.write_array(&msg_bytes, msg.map(F::from_canonical_u8));
+        let values = SHA256::expand_message_xmd_digests(msg, dst, len_in_bytes);
+        for (digest, value) in digests.iter().zip(values) {
+            writer.write_array(&digest.as_array(), value.map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_sha256_compress() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_compress", log::Level::Info);
+
+        // The first compression is the hash of "abc", the others start from random states.
+        let mut rng = thread_rng();
+        let mut states_values = vec![SHA256::INITIAL_HASH];
+        let mut chunks_values = vec![SHA256::pad(b"abc")];
+        for _ in 0..4 {
+            states_values.push(rng.gen::<[u32; 8]>());
+            chunks_values.push(rng.gen::<[u32; 16]>().to_vec());
+        }
+        let num_compressions = states_values.len();
+
+        let mut builder = BytesBuilder::<SHA256dTest>::new();
+        let states = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(8))
+            .collect::<Vec<_>>();
+        let chunks = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let outputs = builder.sha256_compress(&states, &chunks);
+
+        let num_rows = (64 * num_compressions).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (((state, chunk), output), (state_value, chunk_value)) in states
+            .iter()
+            .zip(chunks.iter())
+            .zip(outputs.iter())
+            .zip(states_values.iter().zip(chunks_values.iter()))
+        {
+            writer.write_array(state, state_value.map(u32_to_le_field_bytes));
+            writer.write_array(chunk, chunk_value.iter().map(|w| u32_to_le_field_bytes(*w)));
+            let output_value = SHA256::process(*state_value, &SHA256::pre_process(chunk_value));
+            let output: ArrayRegister<U32Register> = (*output).into();
+            writer.write_array(&output, output_value.map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        let output: ArrayRegister<U32Register> = outputs[0].into();
+        let value = writer
+            .read_array::<_, 8>(&output)
+            .map(|word| u32_from_le_field_bytes(&word));
+        assert_eq!(
+            value,
+            SHA256::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 pub mod air;
+pub mod builder;
+pub mod padding;
 pub mod pure;
 pub mod register;
 
@@ -0,0 +1,341 @@
+//! SHA-256 messages whose padding is constrained by the AIR from their lengths.
+//!
+//! A message is the first `length` bytes of an array of byte registers, whose length is only
+//! known at proving time. The prover gives a bit for every byte of the padded chunks marking
+//! whether the byte belongs to the message. The bits are constrained to be ones followed by zeros,
+//! as many ones as the length, which determines the position of the `0x80` byte and the block
+//! holding the bit length of the message.
+//!
+//! The digest is taken at the block holding the bit length, and the blocks after it are hashed
+//! without affecting the digest.
+
+use super::register::SHA256DigestRegister;
+use super::SHA256;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::math::prelude::*;
+
+/// The number of bytes of a block.
+const BLOCK_BYTES: usize = 64;
+
+/// The number of bytes of the bit length at the end of the last block of a message.
+const LENGTH_BYTES: usize = 8;
+
+/// The number of low bytes of the bit length that may be non-zero, so that its bytes determine it
+/// without wrapping around the field.
+const BIT_LENGTH_BYTES: usize = 5;
+
+/// The registers of a message whose padding is constrained from its length.
+#[derive(Debug, Clone)]
+pub struct SHA256Message {
+    /// The bytes of the message, of which the first `length` are hashed.
+    pub bytes: Vec<ByteRegister>,
+    pub length: ElementRegister,
+    pub chunks: Vec<ArrayRegister<U32Register>>,
+    /// A bit for every byte of the chunks, set if the byte belongs to the message.
+    in_message: ArrayRegister<BitRegister>,
+    /// The little-endian bytes of the bit length of the message.
+    bit_length: ArrayRegister<ByteRegister>,
+}
+
+/// The registers of a batch of messages hashed by `BytesBuilder::sha256_padded`.
+#[derive(Debug, Clone)]
+pub struct SHA256PaddingGadget {
+    pub messages: Vec<SHA256Message>,
+    pub digests: Vec<SHA256DigestRegister>,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Allocates the chunks of a message made of the first `length` bytes of `bytes`.
+    ///
+    /// The message takes enough blocks for `bytes.len()` bytes and its padding, whatever its
+    /// length.
+    pub fn alloc_sha256_message(
+        &mut self,
+        bytes: &[ByteRegister],
+        length: &ElementRegister,
+    ) -> SHA256Message {
+        let num_chunks = (bytes.len() + LENGTH_BYTES + 1).div_ceil(BLOCK_BYTES);
+        SHA256Message {
+            bytes: bytes.to_vec(),
+            length: *length,
+            chunks: (0..num_chunks)
+                .map(|_| self.alloc_array_public::<U32Register>(16))
+                .collect(),
+            in_message: self.alloc_array_public::<BitRegister>(num_chunks * BLOCK_BYTES),
+            bit_length: self.alloc_array_public::<ByteRegister>(LENGTH_BYTES),
+        }
+    }
+
+    /// Proves the SHA-256 hash of `messages`, whose padding is constrained by the AIR.
+    ///
+    /// The bytes and the lengths of the messages must be public registers. The digest bits and
+    /// the digest indices are derived from the lengths. Each message takes all of its chunks
+    /// whatever its length, so the trace must have `(64 * num_chunks).next_power_of_two()` rows.
+    pub fn sha256_padded(&mut self, messages: &[SHA256Message]) -> SHA256PaddingGadget {
+        let num_chunks = messages.iter().map(|m| m.num_chunks()).sum::<usize>();
+        let digest_bits = self.alloc_array_public_unchecked::<BitRegister>(num_chunks);
+        let digest_indices = self.alloc_array_public_unchecked::<ElementRegister>(messages.len());
+        let mut end_bits_values = Vec::new();
+        for (message, digest_index) in messages.iter().zip(digest_indices.iter()) {
+            let offset = end_bits_values.len();
+            let range = offset..offset + message.num_chunks();
+            let selectors = self.sha256_padding_constraints(message);
+
+            let mut index = ArithmeticExpression::zero();
+            for ((k, digest_bit), selector) in range
+                .clone()
+                .zip(digest_bits.get_subarray(range).iter())
+                .zip(selectors)
+            {
+                index = index + selector.clone() * L::Field::from_canonical_usize(k);
+                self.api().set_to_expression_public(&digest_bit, selector);
+            }
+            self.api().set_to_expression_public(&digest_index, index);
+
+            end_bits_values
+                .extend((0..message.num_chunks()).map(|k| k == message.num_chunks() - 1));
+        }
+        let chunks = messages
+            .iter()
+            .flat_map(|message| message.chunks.iter().copied())
+            .collect::<Vec<_>>();
+
+        let end_bits = self.constant_array::<BitRegister>(
+            &end_bits_values
+                .iter()
+                .map(|bit| L::Field::from_canonical_u8(*bit as u8))
+                .collect::<Vec<_>>(),
+        );
+
+        let digests = SHA256::sha(self, &chunks, &end_bits, &digest_bits, digest_indices);
+
+        SHA256PaddingGadget {
+            messages: messages.to_vec(),
+            digests,
+        }
+    }
+
+    /// Constrains the chunks of `message` to be the padding of its first `length` bytes.
+    ///
+    /// Returns the selectors of the blocks, which are `1` at the block holding the bit length of
+    /// the message and `0` elsewhere.
+    fn sha256_padding_constraints(
+        &mut self,
+        message: &SHA256Message,
+    ) -> Vec<ArithmeticExpression<L::Field>> {
+        let num_chunks = message.num_chunks();
+        let bits = message.in_message;
+
+        // The bits are ones followed by zeros, as many ones as the length, which is at most the
+        // number of bytes.
+        for i in 1..bits.len() {
+            self.assert_expression_zero(bits.get(i).expr() * bits.get(i - 1).not_expr());
+        }
+        let count = bits
+            .iter()
+            .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr());
+        self.assert_expressions_equal(count, message.length.expr());
+        self.assert_expression_zero(bits.get(message.bytes.len()).expr());
+
+        // The bytes of the bit length.
+        let bit_length = message.bit_length.iter().collect::<Vec<_>>();
+        for byte in bit_length.iter().skip(BIT_LENGTH_BYTES) {
+            self.assert_expression_zero(byte.expr());
+        }
+        let value = bit_length
+            .iter()
+            .take(BIT_LENGTH_BYTES)
+            .enumerate()
+            .fold(ArithmeticExpression::zero(), |acc, (j, byte)| {
+                acc + byte.expr() * L::Field::from_canonical_u64(1 << (8 * j))
+            });
+        self.assert_expressions_equal(
+            value,
+            message.length.expr() * L::Field::from_canonical_u8(8),
+        );
+
+        // A block is selected if the message with its `0x80` byte and its bit length ends in it,
+        // that is if `64 * k <= length + 8 < 64 * (k + 1)`.
+        let selectors = (0..num_chunks)
+            .map(|k| {
+                let start = match (BLOCK_BYTES * k).checked_sub(LENGTH_BYTES + 1) {
+                    Some(i) => bits.get(i).expr(),
+                    None => ArithmeticExpression::one(),
+                };
+                start - bits.get(BLOCK_BYTES * (k + 1) - LENGTH_BYTES - 1).expr()
+            })
+            .collect::<Vec<_>>();
+
+        // A padded byte is the message byte if it belongs to the message, `0x80` right after the
+        // message, the bit length in the last bytes of the selected block and zero otherwise.
+        for (k, chunk) in message.chunks.iter().enumerate() {
+            for (w, word) in chunk.iter().enumerate() {
+                let word_bytes = word.to_le_bytes();
+                for j in 0..4 {
+                    let i = BLOCK_BYTES * k + 4 * w + j;
+                    let bit = bits.get(i);
+                    let previous_bit = match i {
+                        0 => ArithmeticExpression::one(),
+                        _ => bits.get(i - 1).expr(),
+                    };
+                    let mut padded_byte =
+                        (previous_bit - bit.expr()) * L::Field::from_canonical_u8(0x80);
+                    if let Some(byte) = message.bytes.get(i) {
+                        padded_byte = padded_byte + byte.expr() * bit.expr();
+                    }
+                    let position = 4 * w + j;
+                    if position >= BLOCK_BYTES - LENGTH_BYTES {
+                        let length_byte = bit_length[BLOCK_BYTES - 1 - position];
+                        padded_byte = padded_byte + selectors[k].clone() * length_byte.expr();
+                    }
+                    // The words are big-endian.
+                    self.assert_expressions_equal(word_bytes.get(3 - j).expr(), padded_byte);
+                }
+            }
+        }
+        selectors
+    }
+}
+
+impl SHA256Message {
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Writes the chunks of `msg` and the bits marking its bytes.
+    ///
+    /// The bytes and the length of the message are written by the caller.
+    pub fn write<F: Field>(&self, msg: &[u8], writer: &mut impl AirWriter<Field = F>) {
+        assert!(msg.len() <= self.bytes.len(), "Message too long");
+        let mut padded_msg = <SHA256 as SHAPure<64>>::pad(msg);
+        padded_msg.resize(16 * self.num_chunks(), 0);
+        for (chunk, words) in self.chunks.iter().zip(padded_msg.chunks_exact(16)) {
+            writer.write_array(chunk, words.iter().map(|word| u32_to_le_field_bytes(*word)));
+        }
+        writer.write_array(
+            &self.in_message,
+            (0..self.in_message.len()).map(|i| F::from_canonical_u8((i < msg.len()) as u8)),
+        );
+        writer.write_array(
+            &self.bit_length,
+            (8 * msg.len() as u64)
+                .to_le_bytes()
+                .map(F::from_canonical_u8),
+        );
+    }
+}
+
+impl SHA256PaddingGadget {
+    /// Writes the chunks of the messages and their digests.
+    pub fn write<F: Field>(&self, msgs: &[&[u8]], writer: &mut impl AirWriter<Field = F>) {
+        assert_eq!(msgs.len(), self.messages.len());
+        for ((message, msg), digest) in self.messages.iter().zip(msgs).zip(self.digests.iter()) {
+            message.write(msg, writer);
+            writer.write_array(
+                &digest.as_array(),
+                SHA256::digest(msg).map(u32_to_le_field_bytes),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA256PaddingTest;
+
+    impl AirParameters for SHA256PaddingTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1527;
+        const EXTENDED_COLUMNS: usize = 708;
+    }
+
+    /// Proves the digests of `msgs` in messages of `capacity` bytes, returning whether the proof
+    /// verifies. If `tamper` is set, the first byte past the end of the first message is set to
+    /// a non-zero value in its chunk.
+    fn prove_sha256_padded(msgs: &[&[u8]], capacity: usize, tamper: bool) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_padded", log::Level::Debug);
+
+        let mut builder = BytesBuilder::<SHA256PaddingTest>::new();
+        let messages = msgs
+            .iter()
+            .map(|_| {
+                let bytes = builder.alloc_array_public::<ByteRegister>(capacity);
+                let length = builder.alloc_public::<ElementRegister>();
+                builder.alloc_sha256_message(&bytes.iter().collect::<Vec<_>>(), &length)
+            })
+            .collect::<Vec<_>>();
+        let gadget = builder.sha256_padded(&messages);
+
+        let num_chunks = messages.iter().map(|m| m.num_chunks()).sum::<usize>();
+        let num_rows = (64 * num_chunks).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (message, msg) in messages.iter().zip(msgs) {
+            for (i, byte) in message.bytes.iter().enumerate() {
+                let value = msg.get(i).copied().unwrap_or(0x11);
+                writer.write(byte, &F::from_canonical_u8(value));
+            }
+            writer.write(&message.length, &F::from_canonical_usize(msg.len()));
+        }
+        gadget.write(msgs, &mut writer);
+        if tamper {
+            let word = messages[0].chunks[0].get(msgs[0].len() / 4 + 1);
+            writer.write(&word, &u32_to_le_field_bytes(0x01010101));
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).is_ok()
+    }
+
+    #[test]
+    fn test_sha256_padded() {
+        let long_msg = vec![0xab; 100];
+        let msgs: [&[u8]; 4] = [b"", b"abc", &long_msg[..55], &long_msg];
+        assert!(prove_sha256_padded(&msgs, 100, false));
+        assert!(!prove_sha256_padded(&msgs, 100, true));
+    }
+}
@@ -109,3 +109,97 @@ pub fn step(msg: [u32; 8], w_i: u32, round_constant: u32) -> [u32; 8] {
 
     [a, b, c, d, e, f, g, h]
 }
+
+impl SHA256 {
+    /// Computes the SHA-256 digest of `msg` as eight big-endian words.
+    pub fn digest(msg: &[u8]) -> [u32; 8] {
+        Self::pad(msg)
+            .chunks_exact(16)
+            .fold(INITIAL_HASH, |hash, chunk| {
+                Self::process(hash, &Self::pre_process(chunk))
+            })
+    }
+
+    /// The block `SHA256(tag) || SHA256(tag)` that starts every BIP-340 tagged hash.
+    pub fn tag_block(tag: &[u8]) -> [u32; 16] {
+        let tag_hash = Self::digest(tag);
+        core::array::from_fn(|i| tag_hash[i % 8])
+    }
+
+    /// Pads `msg` as the continuation of a tagged hash.
+    ///
+    /// Returns the padded chunks of `tag_block(tag) || msg` without the leading tag block, which
+    /// do not depend on the tag.
+    pub fn tagged_pad(msg: &[u8]) -> Vec<u32> {
+        let mut prefixed_msg = vec![0u8; 64];
+        prefixed_msg.extend_from_slice(msg);
+        Self::pad(&prefixed_msg).split_off(16)
+    }
+
+    /// The digest of `msg` as bytes, in the order of the standard SHA-256 output.
+    pub fn digest_bytes(msg: &[u8]) -> [u8; 32] {
+        let digest = Self::digest(msg);
+        core::array::from_fn(|i| digest[i / 4].to_be_bytes()[i % 4])
+    }
+
+    /// The expansion `expand_message_xmd` of RFC 9380 of `msg` into `len_in_bytes` uniform bytes,
+    /// with the domain separation tag `dst`.
+    ///
+    /// Reference: https://www.rfc-editor.org/rfc/rfc9380#section-5.3.1
+    pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+        let mut uniform_bytes = Self::expand_message_xmd_digests(msg, dst, len_in_bytes)[1..]
+            .iter()
+            .flat_map(|digest| digest.iter().flat_map(|word| word.to_be_bytes()))
+            .collect::<Vec<_>>();
+        uniform_bytes.truncate(len_in_bytes);
+        uniform_bytes
+    }
+
+    /// The digests `b_0, b_1, ..., b_ell` of `expand_message_xmd`, as big-endian words.
+    pub fn expand_message_xmd_digests(
+        msg: &[u8],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<[u32; 8]> {
+        let ell = len_in_bytes.div_ceil(32);
+        assert!(ell <= 255 && len_in_bytes <= u16::MAX as usize && dst.len() <= 255);
+        let mut dst_prime = dst.to_vec();
+        dst_prime.push(dst.len() as u8);
+
+        let mut msg_prime = vec![0u8; 64];
+        msg_prime.extend_from_slice(msg);
+        msg_prime.extend_from_slice(&(len_in_bytes as u16).to_be_bytes());
+        msg_prime.push(0);
+        msg_prime.extend_from_slice(&dst_prime);
+        let b_0 = Self::digest(&msg_prime);
+
+        let mut digests = vec![b_0];
+        for i in 1..=ell {
+            // b_1 = H(b_0 || 1 || DST') and b_i = H((b_0 xor b_(i-1)) || i || DST').
+            let previous = if i == 1 { [0; 8] } else { digests[i - 1] };
+            let mut block = b_0
+                .iter()
+                .zip(previous.iter())
+                .flat_map(|(a, b)| (a ^ b).to_be_bytes())
+                .collect::<Vec<_>>();
+            block.push(i as u8);
+            block.extend_from_slice(&dst_prime);
+            digests.push(Self::digest(&block));
+        }
+        digests
+    }
+
+    /// The number of chunks hashed by `expand_message_xmd` for a message of `msg_len` bytes and a
+    /// domain separation tag of `dst_len` bytes.
+    pub fn expand_message_xmd_num_chunks(
+        msg_len: usize,
+        dst_len: usize,
+        len_in_bytes: usize,
+    ) -> usize {
+        // A message of `len` bytes is padded with at least 9 bytes into chunks of 64 bytes.
+        let num_chunks = |len: usize| (len + 9).div_ceil(64);
+        let msg_prime_len = 64 + msg_len + 3 + dst_len + 1;
+        let block_len = 32 + 1 + dst_len + 1;
+        num_chunks(msg_prime_len) + len_in_bytes.div_ceil(32) * num_chunks(block_len)
+    }
+}
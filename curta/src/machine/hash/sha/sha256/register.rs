@@ -10,7 +10,7 @@ use crate::chip::uint::register::{U32Register, U64Register};
 pub struct SHA256DigestRegister(ArrayRegister<U32Register>);
 
 impl RegisterSerializable for SHA256DigestRegister {
-    const CELL: CellType = CellType::Element;
+    const CELL: CellType = CellType::Byte;
     fn register(&self) -> &MemorySlice {
         self.0.register()
     }
@@ -0,0 +1,184 @@
+use super::register::SHA384DigestRegister;
+use super::SHA384;
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::{u64_from_le_field_bytes, u64_to_le_field_bytes};
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAir;
+use crate::machine::hash::sha::sha512::register::SHA512DigestRegister;
+use crate::machine::hash::sha::sha512::SHA512;
+use crate::machine::hash::{HashDigest, HashIntConversion, HashInteger};
+
+impl<B: Builder> HashInteger<B> for SHA384 {
+    type Value = <U64Register as Register>::Value<B::Field>;
+    type IntRegister = U64Register;
+}
+
+impl<B: Builder> HashIntConversion<B> for SHA384 {
+    fn int_to_field_value(int: Self::Integer) -> Self::Value {
+        u64_to_le_field_bytes(int)
+    }
+
+    fn field_value_to_int(value: &Self::Value) -> Self::Integer {
+        u64_from_le_field_bytes(value)
+    }
+}
+
+impl<B: Builder> HashDigest<B> for SHA384 {
+    type DigestRegister = SHA384DigestRegister;
+}
+
+fn to_sha512_state(state: &SHA384DigestRegister) -> SHA512DigestRegister {
+    SHA512DigestRegister::from_array(state.as_array())
+}
+
+// All the steps of the compression are the ones of SHA-512, only the constant initial hash
+// differs.
+impl<L: AirParameters> SHAir<BytesBuilder<L>, 80> for SHA384
+where
+    L::Instruction: UintInstructions,
+{
+    type StateVariable = SHA384DigestRegister;
+    type StatePointer = Slice<U64Register>;
+
+    fn clk(builder: &mut BytesBuilder<L>) -> ElementRegister {
+        <SHA512 as SHAir<BytesBuilder<L>, 80>>::clk(builder)
+    }
+
+    fn cycles_end_bits(builder: &mut BytesBuilder<L>) -> (BitRegister, BitRegister) {
+        <SHA512 as SHAir<BytesBuilder<L>, 80>>::cycles_end_bits(builder)
+    }
+
+    fn load_state(
+        builder: &mut BytesBuilder<L>,
+        hash_state_public: &[Self::StateVariable],
+        digest_indices: ArrayRegister<ElementRegister>,
+    ) -> Self::StatePointer {
+        let hash_state_public = hash_state_public
+            .iter()
+            .map(to_sha512_state)
+            .collect::<Vec<_>>();
+        SHA512::load_state(builder, &hash_state_public, digest_indices)
+    }
+
+    fn store_state(
+        builder: &mut BytesBuilder<L>,
+        state_ptr: &Self::StatePointer,
+        state_next: Self::StateVariable,
+        time: &Time<L::Field>,
+        flag: Option<ElementRegister>,
+    ) {
+        SHA512::store_state(builder, state_ptr, to_sha512_state(&state_next), time, flag)
+    }
+
+    fn preprocessing_step(
+        builder: &mut BytesBuilder<L>,
+        w_i_minus_15: Self::IntRegister,
+        w_i_minus_2: Self::IntRegister,
+        w_i_mimus_16: Self::IntRegister,
+        w_i_mimus_7: Self::IntRegister,
+    ) -> Self::IntRegister {
+        <SHA512 as SHAir<BytesBuilder<L>, 80>>::preprocessing_step(
+            builder,
+            w_i_minus_15,
+            w_i_minus_2,
+            w_i_mimus_16,
+            w_i_mimus_7,
+        )
+    }
+
+    fn processing_step(
+        builder: &mut BytesBuilder<L>,
+        vars: ArrayRegister<Self::IntRegister>,
+        w_i: Self::IntRegister,
+        round_constant: Self::IntRegister,
+    ) -> Vec<Self::IntRegister> {
+        <SHA512 as SHAir<BytesBuilder<L>, 80>>::processing_step(builder, vars, w_i, round_constant)
+    }
+
+    fn absorb(
+        builder: &mut BytesBuilder<L>,
+        state: ArrayRegister<Self::IntRegister>,
+        vars_next: &[Self::IntRegister],
+    ) -> Self::StateVariable {
+        let state_next = SHA512::absorb(builder, state, vars_next);
+        SHA384DigestRegister::from_array(state_next.as_array())
+    }
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the SHA-384 hash of the messages given by `padded_chunks`.
+    ///
+    /// The inputs are the same as for `sha512`. The returned registers hold the full chaining
+    /// state of each digest, which must be written as a whole, and the SHA-384 digest is given by
+    /// `SHA384DigestRegister::digest`.
+    pub fn sha384(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<SHA384DigestRegister> {
+        SHA384::sha(self, padded_chunks, end_bits, digest_bits, *digest_indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Debug;
+
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use serde::{Deserialize, Serialize};
+
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::AirParameters;
+    use crate::machine::hash::sha::builder::test_utils::test_sha;
+    use crate::machine::hash::sha::sha384::SHA384;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA384Test;
+
+    impl AirParameters for SHA384Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1191;
+        const EXTENDED_COLUMNS: usize = 654;
+    }
+
+    #[test]
+    fn test_sha384_pure() {
+        let digest = SHA384::hash(b"abc");
+        let expected = "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7";
+        let expected_digest = hex::decode(expected).unwrap();
+        let digest_bytes = digest
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(digest_bytes, expected_digest);
+    }
+
+    #[test]
+    fn test_sha384() {
+        let abc_digest = "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7";
+        let empty_digest = "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b";
+        test_sha::<SHA384Test, SHA384, _, _, 80>(
+            [b"abc".as_slice(), b"".as_slice(), b"abc".as_slice()],
+            [abc_digest, empty_digest, abc_digest],
+        )
+    }
+}
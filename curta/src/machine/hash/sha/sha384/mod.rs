@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod pure;
+pub mod register;
+
+/// SHA-384, which is SHA-512 with a different initial hash and a digest truncated to the first
+/// six words of the state.
+///
+/// The AIR is the SHA-512 AIR, so proving SHA-384 costs the same as proving SHA-512.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SHA384;
+
+/// The number of state words in a SHA-384 digest.
+pub const DIGEST_LEN: usize = 6;
+
+pub(crate) const INITIAL_HASH: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
@@ -0,0 +1,49 @@
+use super::{DIGEST_LEN, INITIAL_HASH, SHA384};
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha512::{ROUND_CONSTANTS, SHA512};
+use crate::machine::hash::HashPureInteger;
+
+impl HashPureInteger for SHA384 {
+    type Integer = u64;
+}
+
+impl SHAPure<80> for SHA384 {
+    const INITIAL_HASH: [Self::Integer; 8] = INITIAL_HASH;
+    const ROUND_CONSTANTS: [Self::Integer; 80] = ROUND_CONSTANTS;
+
+    fn pad(msg: &[u8]) -> Vec<Self::Integer> {
+        SHA512::pad(msg)
+    }
+
+    fn pre_process(chunk: &[Self::Integer]) -> [Self::Integer; 80] {
+        SHA512::pre_process(chunk)
+    }
+
+    fn process(hash: [Self::Integer; 8], w: &[Self::Integer; 80]) -> [Self::Integer; 8] {
+        SHA512::process(hash, w)
+    }
+
+    /// Decodes a 48-byte hex digest into the first six words of the state. The last two words
+    /// are not part of the digest and are set to zero.
+    fn decode(digest: &str) -> [Self::Integer; 8] {
+        let bytes = hex::decode(digest).unwrap();
+        assert_eq!(bytes.len(), 8 * DIGEST_LEN, "SHA-384 digests are 48 bytes");
+        let mut words = [0u64; 8];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        words
+    }
+}
+
+impl SHA384 {
+    /// Computes the SHA-384 digest of `msg` as six big-endian words.
+    pub fn hash(msg: &[u8]) -> [u64; DIGEST_LEN] {
+        let state = Self::pad(msg)
+            .chunks_exact(16)
+            .fold(INITIAL_HASH, |state, chunk| {
+                Self::process(state, &Self::pre_process(chunk))
+            });
+        core::array::from_fn(|i| state[i])
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use super::DIGEST_LEN;
+use crate::chip::register::array::{ArrayIterator, ArrayRegister};
+use crate::chip::register::cell::CellType;
+use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::uint::register::U64Register;
+
+/// The eight word SHA-384 chaining state of a message.
+///
+/// The digest is given by the first six words of the state, see `digest`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SHA384DigestRegister(ArrayRegister<U64Register>);
+
+impl RegisterSerializable for SHA384DigestRegister {
+    const CELL: CellType = CellType::Byte;
+    fn register(&self) -> &MemorySlice {
+        self.0.register()
+    }
+
+    fn from_register_unsafe(register: MemorySlice) -> Self {
+        Self(ArrayRegister::from_register_unsafe(register))
+    }
+}
+
+impl RegisterSized for SHA384DigestRegister {
+    fn size_of() -> usize {
+        U64Register::size_of() * 8
+    }
+}
+
+impl Register for SHA384DigestRegister {
+    type Value<T> = [T; 64];
+
+    fn align<T>(value: &Self::Value<T>) -> &[T] {
+        value
+    }
+
+    fn value_from_slice<T: Copy>(slice: &[T]) -> Self::Value<T> {
+        let elem_fn = |i| slice[i];
+        core::array::from_fn(elem_fn)
+    }
+}
+
+impl SHA384DigestRegister {
+    pub fn as_array(&self) -> ArrayRegister<U64Register> {
+        self.0
+    }
+
+    /// The truncated SHA-384 digest.
+    pub fn digest(&self) -> ArrayRegister<U64Register> {
+        self.0.get_subarray(0..DIGEST_LEN)
+    }
+
+    pub fn get(&self, index: usize) -> U64Register {
+        self.0.get(index)
+    }
+
+    pub fn iter(&self) -> ArrayIterator<U64Register> {
+        self.0.iter()
+    }
+
+    pub fn from_array(array: ArrayRegister<U64Register>) -> Self {
+        assert_eq!(array.len(), 8);
+        Self(array)
+    }
+}
+
+impl From<SHA384DigestRegister> for ArrayRegister<U64Register> {
+    fn from(register: SHA384DigestRegister) -> Self {
+        register.0
+    }
+}
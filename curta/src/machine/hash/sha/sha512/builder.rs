@@ -0,0 +1,181 @@
+use super::register::SHA512DigestRegister;
+use super::SHA512;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::AirParameters;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAir;
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the SHA-512 hash of the messages given by `padded_chunks`.
+    ///
+    /// Each chunk consists of sixteen padded message words. `end_bits[i]` marks the last chunk of
+    /// a message and `digest_bits[i]` marks the chunks whose state is a digest, whose chunk index
+    /// is recorded in `digest_indices`. The trace must have at least `80 * padded_chunks.len()`
+    /// rows. Returns one digest register for every entry of `digest_indices`.
+    pub fn sha512(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<SHA512DigestRegister> {
+        SHA512::sha(self, padded_chunks, end_bits, digest_bits, *digest_indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::{u64_from_le_field_bytes, u64_to_le_field_bytes};
+    use crate::machine::builder::Builder;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA512BuilderTest;
+
+    impl AirParameters for SHA512BuilderTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1191;
+        const EXTENDED_COLUMNS: usize = 654;
+    }
+
+    #[test]
+    fn test_sha512_builder() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha512_builder", log::Level::Info);
+
+        let short_msg = b"plonky2".to_vec();
+        let long_msg = hex::decode("35c323757c20640a294345c89c0bfcebe3d554fdb0c7b7a0bdb72222c531b1ecf7ec1c43f4de9d49556de87b86b26a98942cb078486fdb44de38b80864c3973153756363696e6374204c616273").unwrap();
+        let msgs = [short_msg, long_msg];
+        let expected_digests = [
+            "7c6159dd615db8c15bc76e23d36106e77464759979a0fcd1366e531f552cfa0852dbf5c832f00bb279cbc945b44a132bff3ed0028259813b6a07b57326e88c87",
+            "4388243c4452274402673de881b2f942ff5730fd2c7d8ddb94c3e3d789fb3754380cba8faa40554d9506a0730a681e88ab348a04bc5c41d18926f140b59aed39",
+        ];
+
+        let mut padded_chunks_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            let padded_msg = SHA512::pad(msg);
+            let num_chunks = padded_msg.len() / 16;
+            padded_chunks_values.extend(padded_msg.chunks_exact(16).map(|c| c.to_vec()));
+            end_bits_values.extend((0..num_chunks).map(|i| i == num_chunks - 1));
+            digest_indices_values.push(end_bits_values.len() - 1);
+        }
+        let num_rounds = padded_chunks_values.len();
+
+        let mut builder = BytesBuilder::<SHA512BuilderTest>::new();
+        let padded_chunks = (0..num_rounds)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(msgs.len());
+        let digests = builder.sha512(&padded_chunks, &end_bits, &end_bits, &digest_indices);
+
+        let num_rows = 1 << log2_ceil(80 * num_rounds);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+        let rec_data = recursive_builder.build::<Config>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut state = SHA512::INITIAL_HASH;
+        let mut digests_iter = digests.iter();
+        for (i, chunk) in padded_chunks_values.iter().enumerate() {
+            writer.write_array(
+                &padded_chunks[i],
+                chunk.iter().map(|w| u64_to_le_field_bytes(*w)),
+            );
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+
+            state = SHA512::process(state, &SHA512::pre_process(chunk));
+            if end_bits_values[i] {
+                let digest: ArrayRegister<U64Register> = (*digests_iter.next().unwrap()).into();
+                writer.write_array(&digest, state.map(u64_to_le_field_bytes));
+                state = SHA512::INITIAL_HASH;
+            }
+        }
+        for (i, index) in digest_indices_values.iter().enumerate() {
+            writer.write(&digest_indices.get(i), &F::from_canonical_usize(*index));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let writer = writer_data.public_writer();
+        for (digest, expected) in digests.iter().zip(expected_digests) {
+            let digest: ArrayRegister<U64Register> = (*digest).into();
+            let value = writer
+                .read_array::<_, 8>(&digest)
+                .map(|word| u64_from_le_field_bytes(&word));
+            assert_eq!(value, SHA512::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&public_input, &public);
+        stark.set_proof_target(&mut pw, &proof_target, proof);
+
+        let rec_proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
@@ -109,3 +109,14 @@ pub fn step(msg: [u64; 8], w_i: u64, round_constant: u64) -> [u64; 8] {
 
     [a, b, c, d, e, f, g, h]
 }
+
+impl SHA512 {
+    /// Computes the SHA-512 digest of `msg` as eight big-endian words.
+    pub fn hash(msg: &[u8]) -> [u64; 8] {
+        Self::pad(msg)
+            .chunks_exact(16)
+            .fold(INITIAL_HASH, |state, chunk| {
+                Self::process(state, &Self::pre_process(chunk))
+            })
+    }
+}
@@ -0,0 +1,199 @@
+use super::SpongeParameters;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::AirParameters;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::keccak::KeccakAir;
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Proves the sponge given by `parameters` over the messages given by `padded_chunks`.
+    ///
+    /// The blocks, end bits and digest bits of a message are given by `SpongeParameters::pad` and
+    /// `SpongeParameters::block_bits`. Every output block has an entry in `digest_indices` and
+    /// the output of a message is the concatenation of its output blocks, truncated to
+    /// `parameters.output_bytes()`.
+    pub fn sha3(
+        &mut self,
+        parameters: &SpongeParameters,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<U64Register>> {
+        KeccakAir::sponge(
+            self,
+            parameters.rate_lanes(),
+            parameters.output_lanes(),
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u64_to_le_field_bytes;
+    use crate::machine::builder::Builder;
+    use crate::machine::hash::keccak::NUM_ROUNDS;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SpongeTest;
+
+    impl AirParameters for SpongeTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 7000;
+        const EXTENDED_COLUMNS: usize = 3500;
+    }
+
+    fn test_sponge(parameters: SpongeParameters, msgs: &[&[u8]]) {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type F = GoldilocksField;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sponge", log::Level::Info);
+
+        let rate_lanes = parameters.rate_lanes();
+        let mut padded_chunks_values = Vec::new();
+        let mut end_bits_values = Vec::new();
+        let mut digest_bits_values = Vec::new();
+        let mut digest_indices_values = Vec::new();
+        for msg in msgs.iter() {
+            padded_chunks_values.extend(
+                parameters
+                    .pad(msg)
+                    .chunks_exact(rate_lanes)
+                    .map(|c| c.to_vec()),
+            );
+            for (end_bit, digest_bit) in parameters.block_bits(msg.len()) {
+                if digest_bit {
+                    digest_indices_values.push(end_bits_values.len());
+                }
+                end_bits_values.push(end_bit);
+                digest_bits_values.push(digest_bit);
+            }
+        }
+        let num_blocks = padded_chunks_values.len();
+        let num_digests = digest_indices_values.len();
+
+        let mut builder = BytesBuilder::<SpongeTest>::new();
+        let padded_chunks = (0..num_blocks)
+            .map(|_| builder.alloc_array_public::<U64Register>(rate_lanes))
+            .collect::<Vec<_>>();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_blocks);
+        let digest_bits = builder.alloc_array_public::<BitRegister>(num_blocks);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(num_digests);
+        let outputs = builder.sha3(
+            &parameters,
+            &padded_chunks,
+            &end_bits,
+            &digest_bits,
+            &digest_indices,
+        );
+
+        let num_rows = (NUM_ROUNDS * num_blocks).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, chunk) in padded_chunks_values.iter().enumerate() {
+            writer.write_array(
+                &padded_chunks[i],
+                chunk.iter().map(|lane| u64_to_le_field_bytes(*lane)),
+            );
+            writer.write(
+                &end_bits.get(i),
+                &F::from_canonical_u8(end_bits_values[i] as u8),
+            );
+            writer.write(
+                &digest_bits.get(i),
+                &F::from_canonical_u8(digest_bits_values[i] as u8),
+            );
+        }
+
+        let mut outputs_iter = outputs.iter().zip(digest_indices_values.iter()).enumerate();
+        for msg in msgs.iter() {
+            for block_output in parameters.output_blocks(msg) {
+                let (i, (register, index)) = outputs_iter.next().unwrap();
+                writer.write(&digest_indices.get(i), &F::from_canonical_usize(*index));
+                writer.write_array(
+                    register,
+                    block_output.into_iter().map(u64_to_le_field_bytes),
+                );
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = timed!(
+            timing,
+            log::Level::Info,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_sponge_pure() {
+        assert_eq!(
+            hex::encode(SpongeParameters::sha3_256().hash(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex::encode(SpongeParameters::sha3_512().hash(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        assert_eq!(
+            hex::encode(SpongeParameters::shake128(200).hash(b"")),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef263cb1eea988004b93103cfb0aeefd2a686e01fa4a58e8a3639ca8a1e3f9ae57e235b8cc873c23dc62b8d260169afa2f75ab916a58d974918835d25e6a435085b2badfd6dfaac359a5efbb7bcc4b59d538df9a04302e10c8bc1cbf1a0b3a5120ea17cda7cfad765f5623474d368ccca8af0007cd9f5e4c849f167a580b14aabdefaee7eef47cb0fca9767be1fda69419dfb927e9df07348b196691abaeb580b32def58538b8d23f877"
+        );
+        assert_eq!(
+            hex::encode(SpongeParameters::shake256(20).hash(b"abc")),
+            "483366601360a8771c6863080cc4114d8db44530"
+        );
+    }
+
+    #[test]
+    fn test_sha3_256() {
+        test_sponge(SpongeParameters::sha3_256(), &[b"abc", b"", &[0x5a; 150]]);
+    }
+
+    #[test]
+    fn test_shake128_multiple_output_blocks() {
+        test_sponge(SpongeParameters::shake128(200), &[b"", b"abc"]);
+    }
+}
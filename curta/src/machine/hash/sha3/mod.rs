@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use super::keccak::pure::KeccakPure;
+use super::keccak::STATE_LANES;
+
+pub mod builder;
+
+/// The parameters of a Keccak-f[1600] sponge.
+///
+/// The rate and the capacity add up to the 200 bytes of the state. The domain separation byte is
+/// the first byte of the padding, and the output length is the number of bytes squeezed out of the
+/// sponge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpongeParameters {
+    capacity: usize,
+    domain: u8,
+    output_bytes: usize,
+}
+
+impl SpongeParameters {
+    /// A sponge with a capacity of `capacity` bytes, which must be a non-zero multiple of eight.
+    pub fn new(capacity: usize, domain: u8, output_bytes: usize) -> Self {
+        assert!(
+            capacity > 0 && capacity % 8 == 0 && capacity < 8 * STATE_LANES,
+            "Invalid sponge capacity {}",
+            capacity
+        );
+        assert!(output_bytes > 0, "The output must be non-empty");
+        Self {
+            capacity,
+            domain,
+            output_bytes,
+        }
+    }
+
+    pub fn sha3_256() -> Self {
+        Self::new(64, 0x06, 32)
+    }
+
+    pub fn sha3_512() -> Self {
+        Self::new(128, 0x06, 64)
+    }
+
+    pub fn shake128(output_bytes: usize) -> Self {
+        Self::new(32, 0x1f, output_bytes)
+    }
+
+    pub fn shake256(output_bytes: usize) -> Self {
+        Self::new(64, 0x1f, output_bytes)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn rate(&self) -> usize {
+        8 * STATE_LANES - self.capacity
+    }
+
+    pub fn rate_lanes(&self) -> usize {
+        self.rate() / 8
+    }
+
+    pub fn output_bytes(&self) -> usize {
+        self.output_bytes
+    }
+
+    /// The number of blocks whose output is squeezed, one more than the number of extra
+    /// permutations applied after the last block of the message.
+    pub fn num_output_blocks(&self) -> usize {
+        (self.output_bytes + self.rate() - 1) / self.rate()
+    }
+
+    /// The number of lanes squeezed out of every output block.
+    pub fn output_lanes(&self) -> usize {
+        self.rate_lanes().min((self.output_bytes + 7) / 8)
+    }
+
+    /// Pads `msg` and appends a zero block for every extra squeeze, returning the blocks as
+    /// little-endian lanes.
+    pub fn pad(&self, msg: &[u8]) -> Vec<u64> {
+        let mut blocks = KeccakPure::pad_sponge(msg, self.rate_lanes(), self.domain);
+        blocks.resize(
+            blocks.len() + (self.num_output_blocks() - 1) * self.rate_lanes(),
+            0,
+        );
+        blocks
+    }
+
+    /// The end bits and digest bits of the blocks returned by `pad` for a message of `msg_len`
+    /// bytes.
+    pub fn block_bits(&self, msg_len: usize) -> Vec<(bool, bool)> {
+        let num_absorb_blocks = msg_len / self.rate() + 1;
+        let num_blocks = num_absorb_blocks + self.num_output_blocks() - 1;
+        (0..num_blocks)
+            .map(|i| (i == num_blocks - 1, i + 1 >= num_absorb_blocks))
+            .collect()
+    }
+
+    /// The lanes squeezed out of every output block when hashing `msg`.
+    pub fn output_blocks(&self, msg: &[u8]) -> Vec<Vec<u64>> {
+        let blocks = self.pad(msg);
+        let mut outputs = Vec::new();
+        let mut state = [0u64; STATE_LANES];
+        for (block, (_, digest_bit)) in blocks
+            .chunks_exact(self.rate_lanes())
+            .zip(self.block_bits(msg.len()))
+        {
+            for (lane, word) in state.iter_mut().zip(block.iter()) {
+                *lane ^= word;
+            }
+            KeccakPure::keccak_f(&mut state);
+            if digest_bit {
+                outputs.push(state[..self.output_lanes()].to_vec());
+            }
+        }
+        outputs
+    }
+
+    /// Hashes `msg`, returning `output_bytes` bytes.
+    pub fn hash(&self, msg: &[u8]) -> Vec<u8> {
+        let mut output = self
+            .output_blocks(msg)
+            .iter()
+            .flatten()
+            .flat_map(|lane| lane.to_le_bytes())
+            .collect::<Vec<_>>();
+        output.truncate(self.output_bytes);
+        output
+    }
+}
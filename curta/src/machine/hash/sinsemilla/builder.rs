@@ -0,0 +1,318 @@
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::{Sinsemilla, SinsemillaParameters};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::ec::gadget::EllipticCurveAirWriter;
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::ec::weierstrass::SWCurve;
+use crate::chip::ec::{EllipticCurveAir, EllipticCurveParameters};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::machine::ec::builder::EllipticCurveBuilder;
+use crate::math::prelude::*;
+
+type BaseField<S> = <<S as SinsemillaParameters>::Curve as EllipticCurveParameters>::BaseField;
+
+/// The registers of a batch of Sinsemilla hashes.
+#[derive(Debug, Clone)]
+pub struct SinsemillaGadget<S: SinsemillaParameters> {
+    /// The points `SinsemillaHashToPoint(M)`, whose `x` coordinates are the hashes.
+    pub digests: Vec<AffinePointRegister<SWCurve<S::Curve>>>,
+    /// The number of reads of each entry of the table of `S`.
+    multiplicities: ArrayRegister<ElementRegister>,
+    cycle_length: usize,
+    num_cycles: usize,
+}
+
+pub trait SinsemillaBuilder: Builder {
+    /// Computes `SinsemillaHashToPoint(M)` for each of `messages`.
+    ///
+    /// The messages must be public registers of bits, their lengths are fixed at build time. The
+    /// points `S(j)` are stored in a read-only memory table and each step of a hash looks up the
+    /// entry of its chunk value. The results and the number of reads of each table entry must be
+    /// written with `SinsemillaGadget::write`, before the global instructions.
+    ///
+    /// Each hash takes a cycle of `L` rows, one step per row, where `L` is the largest number of
+    /// chunks of a message rounded up to a power of two, and at least two. The trace must have
+    /// `(messages.len() * L).next_power_of_two()` rows.
+    fn sinsemilla_hash_batch<S: SinsemillaParameters>(
+        &mut self,
+        messages: &[ArrayRegister<BitRegister>],
+    ) -> SinsemillaGadget<S>
+    where
+        SWCurve<S::Curve>: EllipticCurveAir<Self::Parameters>,
+    {
+        let num_chunks = messages
+            .iter()
+            .map(|message| message.len().div_ceil(S::K))
+            .collect::<Vec<_>>();
+        let cycle_length = num_chunks
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .next_power_of_two()
+            .max(2);
+        let num_ops = messages.len();
+        debug!("AIR degree before padding: {}", num_ops * cycle_length);
+        let degree_log = log2_ceil(num_ops * cycle_length);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        let num_cycles = (1 << degree_log) / cycle_length;
+
+        let zero = Time::zero();
+        let digit_ptr = self.uninit_slice::<ElementRegister>();
+        let active_ptr = self.uninit_slice::<ElementRegister>();
+        let x_ptr = self.uninit_slice::<FieldRegister<BaseField<S>>>();
+        let y_ptr = self.uninit_slice::<FieldRegister<BaseField<S>>>();
+
+        // The chunk values and a flag of the rows that compute a step, indexed by row. The rows
+        // after the last chunk of a message, and all the rows of dummy operations, keep the
+        // accumulator unchanged and read the first table entry.
+        let zero_element = self.constant::<ElementRegister>(&Self::Field::ZERO);
+        let one_element = self.constant::<ElementRegister>(&Self::Field::ONE);
+        let q = S::q();
+        let q = AffinePointRegister::<SWCurve<S::Curve>>::new(
+            self.api().fp_constant(&q.x),
+            self.api().fp_constant(&q.y),
+        );
+        let mut digests = Vec::with_capacity(num_ops);
+        for k in 0..num_cycles {
+            let (message, chunks) = match messages.get(k) {
+                Some(message) => (Some(message), num_chunks[k]),
+                None => (None, 0),
+            };
+            for i in 0..cycle_length {
+                let index = k * cycle_length + i;
+                let (digit, active) = match message {
+                    Some(message) if i < chunks => {
+                        let digit = (S::K * i..(S::K * (i + 1)).min(message.len()))
+                            .map(|j| message.get(j).expr())
+                            .enumerate()
+                            .fold(ArithmeticExpression::zero(), |acc, (j, bit)| {
+                                acc + bit * Self::Field::from_canonical_usize(1 << j)
+                            });
+                        (
+                            self.public_expression::<ElementRegister>(digit),
+                            one_element,
+                        )
+                    }
+                    _ => (zero_element, zero_element),
+                };
+                self.store(&digit_ptr.get(index), digit, &zero, None, None, None);
+                self.store(&active_ptr.get(index), active, &zero, None, None, None);
+            }
+
+            match message {
+                Some(message) => {
+                    assert!(!message.is_trace(), "Message bits must be public");
+                    let digest: AffinePointRegister<SWCurve<S::Curve>> =
+                        self.alloc_public_ec_point();
+                    self.free(&x_ptr.get(k), digest.x, &zero);
+                    self.free(&y_ptr.get(k), digest.y, &zero);
+                    digests.push(digest);
+                }
+                None => {
+                    self.free(&x_ptr.get(k), q.x, &zero);
+                    self.free(&y_ptr.get(k), q.y, &zero);
+                }
+            }
+        }
+
+        // The table of the points `S(j)`, each stored with the number of its reads.
+        let table_size = 1 << S::K;
+        let multiplicities = self.alloc_array_public::<ElementRegister>(table_size);
+        let table_x = self.uninit_slice::<FieldRegister<BaseField<S>>>();
+        let table_y = self.uninit_slice::<FieldRegister<BaseField<S>>>();
+        for (j, multiplicity) in multiplicities.iter().enumerate() {
+            let point = S::s(j);
+            let x = self.api().fp_constant(&point.x);
+            let y = self.api().fp_constant(&point.y);
+            self.store(&table_x.get(j), x, &zero, Some(multiplicity), None, None);
+            self.store(&table_y.get(j), y, &zero, Some(multiplicity), None, None);
+        }
+
+        let cycle = self.cycle(cycle_length.ilog2() as usize);
+        let process_id = self.process_id(cycle_length, cycle.end_bit);
+        let clk = self.clk();
+        let digit = self.load(&digit_ptr.get_at(clk), &zero, None, None);
+        let active = self.load(&active_ptr.get_at(clk), &zero, None, None);
+        let entry_x = self.load(&table_x.get_at(digit), &zero, None, None);
+        let entry_y = self.load(&table_y.get_at(digit), &zero, None, None);
+        let entry = AffinePointRegister::new(entry_x, entry_y);
+
+        // The accumulator is reset to `Q` at the beginning of each cycle.
+        let acc: AffinePointRegister<SWCurve<S::Curve>> = self.alloc_ec_point();
+        let sum = self.add(&acc, &entry);
+        let step = self.add(&sum, &acc);
+        let acc_next_x = self.expression::<FieldRegister<BaseField<S>>>(
+            active.expr() * step.x.expr()
+                + (ArithmeticExpression::one() - active.expr()) * acc.x.expr(),
+        );
+        let acc_next_y = self.expression::<FieldRegister<BaseField<S>>>(
+            active.expr() * step.y.expr()
+                + (ArithmeticExpression::one() - active.expr()) * acc.y.expr(),
+        );
+        let acc_next = AffinePointRegister::new(acc_next_x, acc_next_y);
+        self.set_to_expression_first_row(&acc.x, q.x.expr());
+        self.set_to_expression_first_row(&acc.y, q.y.expr());
+        self.select_next_ec_point(cycle.end_bit, &q, &acc_next, &acc);
+
+        let end_flag = Some(cycle.end_bit.as_element());
+        self.store(
+            &x_ptr.get_at(process_id),
+            acc_next.x,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+        self.store(
+            &y_ptr.get_at(process_id),
+            acc_next.y,
+            &zero,
+            end_flag,
+            None,
+            None,
+        );
+
+        SinsemillaGadget {
+            digests,
+            multiplicities,
+            cycle_length,
+            num_cycles,
+        }
+    }
+}
+
+impl<B: Builder> SinsemillaBuilder for B {}
+
+impl<S: SinsemillaParameters> SinsemillaGadget<S> {
+    /// Writes the hashes of `messages` and the number of reads of each table entry.
+    ///
+    /// The message bits themselves are written by the caller.
+    pub fn write<F: PrimeField64>(
+        &self,
+        messages: &[Vec<bool>],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        assert_eq!(messages.len(), self.digests.len());
+        let total_rows = self.num_cycles * self.cycle_length;
+        let mut counts = vec![0usize; 1 << S::K];
+        for (message, digest) in messages.iter().zip(self.digests.iter()) {
+            for m in Sinsemilla::<S>::chunks(message) {
+                counts[m] += 1;
+            }
+            writer.write_ec_point(digest, &Sinsemilla::<S>::hash_to_point(message));
+        }
+        let num_steps = messages
+            .iter()
+            .map(|message| message.len().div_ceil(S::K))
+            .sum::<usize>();
+        counts[0] += total_rows - num_steps;
+        writer.write_array(
+            &self.multiplicities,
+            counts.into_iter().map(F::from_canonical_usize),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::ec::weierstrass::pallas::PallasBaseField;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::hash::sinsemilla::tests::PallasTestDomain;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct SinsemillaTest;
+
+    impl AirParameters for SinsemillaTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = FpInstruction<PallasBaseField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2000;
+        const NUM_FREE_COLUMNS: usize = 100;
+        const EXTENDED_COLUMNS: usize = 4000;
+    }
+
+    /// Proves the hashes of `messages` and returns whether the proof verifies.
+    ///
+    /// If `tamper` is set, the first digest is replaced by the hash of a different message.
+    fn prove_sinsemilla_hash(messages: &[Vec<bool>], tamper: bool) -> bool {
+        type F = GoldilocksField;
+        type L = SinsemillaTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type S = PallasTestDomain;
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let message_registers = messages
+            .iter()
+            .map(|message| builder.alloc_array_public::<BitRegister>(message.len()))
+            .collect::<Vec<_>>();
+        let gadget = builder.sinsemilla_hash_batch::<S>(&message_registers);
+
+        let num_rows = (messages.len() * gadget.cycle_length).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (register, message) in message_registers.iter().zip(messages) {
+            writer.write_array(register, message.iter().map(|bit| F::from_bool(*bit)));
+        }
+        gadget.write(messages, &mut writer);
+        if tamper {
+            let mut wrong_message = messages[0].clone();
+            wrong_message.push(true);
+            writer.write_ec_point(
+                &gadget.digests[0],
+                &Sinsemilla::<S>::hash_to_point(&wrong_message),
+            );
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_sinsemilla_hash() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut rng = thread_rng();
+        let messages = [17, 4, 30, 0]
+            .into_iter()
+            .map(|len| (0..len).map(|_| rng.gen::<bool>()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert!(prove_sinsemilla_hash(&messages, false));
+
+        // A wrong digest is rejected.
+        assert!(!prove_sinsemilla_hash(&messages, true));
+    }
+}
@@ -0,0 +1,75 @@
+//! The Sinsemilla hash of Zcash Orchard.
+//!
+//! A message of bits is padded with zeros to a multiple of `K` bits and split into chunks of `K`
+//! bits, each read as a little-endian integer `m_i`. Starting from the point `Q`, every chunk
+//! updates the accumulator as `Acc <- (Acc + S(m_i)) + Acc`, where `S` is a table of `2^K` fixed
+//! points and the additions are incomplete. The hash is the `x` coordinate of the final
+//! accumulator.
+//!
+//! The points `Q` and `S(j)` of a domain are given by `SinsemillaParameters`. In Orchard, these
+//! are hashes to the Pallas curve of the domain separator and of the chunk values, and `K = 10`.
+
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+
+pub mod builder;
+pub mod pure;
+
+/// The parameters of a Sinsemilla domain.
+pub trait SinsemillaParameters: 'static + Send + Sync + Clone + Debug {
+    type Curve: WeierstrassParameters;
+
+    /// The number of message bits absorbed by each step.
+    const K: usize;
+
+    /// The initial value `Q` of the accumulator.
+    fn q() -> AffinePoint<SWCurve<Self::Curve>>;
+
+    /// The point `S(j)` for a chunk value `j < 2^K`.
+    fn s(j: usize) -> AffinePoint<SWCurve<Self::Curve>>;
+}
+
+/// The Sinsemilla hash of the domain `S`.
+#[derive(Debug, Clone, Copy)]
+pub struct Sinsemilla<S>(PhantomData<S>);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num::{BigUint, Num};
+
+    use super::*;
+    use crate::chip::ec::weierstrass::pallas::{Pallas, PallasParameters};
+
+    /// A test domain over Pallas with `Q = a * G` and `S(j) = (b + j) * G`, so that the hash of
+    /// a message is the multiple of `G` by a known scalar.
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct PallasTestDomain;
+
+    impl PallasTestDomain {
+        pub(crate) fn q_scalar() -> BigUint {
+            BigUint::from_str_radix("1234567890123456789012345678901234567890", 10).unwrap()
+        }
+
+        pub(crate) fn s_scalar(j: usize) -> BigUint {
+            BigUint::from_str_radix("9876543210987654321098765432109876543210", 10).unwrap() + j
+        }
+    }
+
+    impl SinsemillaParameters for PallasTestDomain {
+        type Curve = PallasParameters;
+
+        const K: usize = 4;
+
+        fn q() -> AffinePoint<Pallas> {
+            Pallas::generator().sw_scalar_mul(&Self::q_scalar())
+        }
+
+        fn s(j: usize) -> AffinePoint<Pallas> {
+            assert!(j < 1 << Self::K);
+            Pallas::generator().sw_scalar_mul(&Self::s_scalar(j))
+        }
+    }
+}
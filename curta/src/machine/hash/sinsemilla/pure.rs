@@ -0,0 +1,70 @@
+use num::BigUint;
+
+use super::{Sinsemilla, SinsemillaParameters};
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::weierstrass::SWCurve;
+
+impl<S: SinsemillaParameters> Sinsemilla<S> {
+    /// Splits `bits` into the chunk values `m_i`, padding the last chunk with zeros.
+    pub fn chunks(bits: &[bool]) -> Vec<usize> {
+        bits.chunks(S::K)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |value, (i, bit)| value | ((*bit as usize) << i))
+            })
+            .collect()
+    }
+
+    /// Computes the point `SinsemillaHashToPoint(bits)`.
+    pub fn hash_to_point(bits: &[bool]) -> AffinePoint<SWCurve<S::Curve>> {
+        Self::chunks(bits)
+            .into_iter()
+            .fold(S::q(), |acc, m| acc.sw_add(&S::s(m)).sw_add(&acc))
+    }
+
+    /// Computes `SinsemillaHash(bits)`, the `x` coordinate of `hash_to_point(bits)`.
+    pub fn hash(bits: &[bool]) -> BigUint {
+        Self::hash_to_point(bits).x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::ec::weierstrass::pallas::{Pallas, PallasParameters};
+    use crate::chip::ec::weierstrass::WeierstrassParameters;
+    use crate::machine::hash::sinsemilla::tests::PallasTestDomain;
+
+    #[test]
+    fn test_sinsemilla_pure() {
+        type H = Sinsemilla<PallasTestDomain>;
+
+        let bits = [true, false, true, true, false, true];
+        assert_eq!(H::chunks(&bits), vec![13, 2]);
+        assert_eq!(H::chunks(&[]), Vec::<usize>::new());
+
+        // Padding with zeros up to a multiple of `K` does not change the hash.
+        let mut padded = bits.to_vec();
+        padded.resize(8, false);
+        assert_eq!(H::hash(&bits), H::hash(&padded));
+
+        // Every step doubles the scalar of the accumulator and adds the scalar of `S(m_i)`.
+        let mut rng = thread_rng();
+        let order = PallasParameters::prime_group_order();
+        for len in [0, 1, 4, 9, 30] {
+            let bits = (0..len).map(|_| rng.gen::<bool>()).collect::<Vec<_>>();
+            let scalar = H::chunks(&bits)
+                .into_iter()
+                .fold(PallasTestDomain::q_scalar(), |acc, m| {
+                    (acc * 2u32 + PallasTestDomain::s_scalar(m)) % &order
+                });
+            let expected = Pallas::generator().sw_scalar_mul(&scalar);
+            assert_eq!(H::hash_to_point(&bits), expected);
+            assert_eq!(H::hash(&bits), expected.x);
+        }
+    }
+}
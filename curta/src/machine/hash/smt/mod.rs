@@ -0,0 +1,357 @@
+//! Sparse Merkle tree membership and non-membership proofs over BLAKE2b.
+//!
+//! Every internal node is a single BLAKE2b compression of `left || right`, so all the levels of
+//! all the verified paths are proven together in one batch of compressions.
+
+pub mod pure;
+
+use self::pure::{SMTDigest, SMTProof, SparseMerkleTree};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::blake::blake2b::IV;
+use crate::math::prelude::*;
+
+/// The number of `U64Register` words in a node digest.
+const DIGEST_WORDS: usize = 4;
+
+/// The hashes of the empty subtrees of every height, allocated once as constants.
+#[derive(Debug, Clone)]
+pub struct SMTDefaults {
+    pub hashes: Vec<ArrayRegister<U64Register>>,
+}
+
+/// The registers of a single path from a leaf slot to the root.
+#[derive(Debug, Clone)]
+pub struct SMTPath {
+    /// The key bits, read from the root. A bit of `1` selects the right child.
+    pub key: ArrayRegister<BitRegister>,
+    pub leaf: ArrayRegister<U64Register>,
+    /// The siblings along the path, ordered from the leaf to the root.
+    pub siblings: Vec<ArrayRegister<U64Register>>,
+    pub root: ArrayRegister<U64Register>,
+}
+
+/// The registers of a batch of verified paths.
+#[derive(Debug, Clone)]
+pub struct SMTGadget {
+    pub paths: Vec<SMTPath>,
+    messages: Vec<Vec<ArrayRegister<U64Register>>>,
+    states: Vec<Vec<ArrayRegister<U64Register>>>,
+}
+
+impl SMTDefaults {
+    pub fn depth(&self) -> usize {
+        self.hashes.len() - 1
+    }
+
+    /// The value of an empty leaf slot.
+    pub fn empty_leaf(&self) -> ArrayRegister<U64Register> {
+        self.hashes[0]
+    }
+
+    /// The hash of an empty subtree of height `height`.
+    pub fn get(&self, height: usize) -> ArrayRegister<U64Register> {
+        self.hashes[height]
+    }
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Allocates the default hashes of a sparse Merkle tree of depth `depth` as constants.
+    pub fn smt_defaults(&mut self, depth: usize) -> SMTDefaults {
+        let hashes = SparseMerkleTree::default_hashes(depth)
+            .iter()
+            .map(|digest| self.constant_array::<U64Register>(&digest_to_words(digest)))
+            .collect();
+        SMTDefaults { hashes }
+    }
+
+    /// Allocates a path whose leaf is given by the prover.
+    pub fn alloc_smt_path(&mut self, depth: usize) -> SMTPath {
+        let leaf = self.alloc_array_public::<U64Register>(DIGEST_WORDS);
+        self.alloc_smt_path_with_leaf(depth, leaf)
+    }
+
+    /// Allocates a path whose leaf is the empty marker, proving that its key is not in the tree.
+    pub fn alloc_smt_non_membership_path(&mut self, defaults: &SMTDefaults) -> SMTPath {
+        self.alloc_smt_path_with_leaf(defaults.depth(), defaults.empty_leaf())
+    }
+
+    fn alloc_smt_path_with_leaf(
+        &mut self,
+        depth: usize,
+        leaf: ArrayRegister<U64Register>,
+    ) -> SMTPath {
+        let key = self.alloc_array_public::<BitRegister>(depth);
+        let siblings = (0..depth)
+            .map(|_| self.alloc_array_public::<U64Register>(DIGEST_WORDS))
+            .collect();
+        let root = self.alloc_array_public::<U64Register>(DIGEST_WORDS);
+        SMTPath {
+            key,
+            leaf,
+            siblings,
+            root,
+        }
+    }
+
+    /// Verifies that each path hashes its leaf to its root.
+    ///
+    /// All the paths are proven in a single batch of BLAKE2b compressions, so the trace must have
+    /// `(96 * num_nodes).next_power_of_two()` rows, where `num_nodes` is the total number of
+    /// siblings of all the paths.
+    pub fn smt_verify(&mut self, paths: &[SMTPath]) -> SMTGadget {
+        let num_compresses = paths.iter().map(|path| path.siblings.len()).sum::<usize>();
+        assert!(num_compresses > 0, "no nodes to verify");
+
+        let h_in = self.constant_array::<U64Register>(&IV.map(u64_to_le_field_bytes));
+        let t_value = u64_to_le_field_bytes(2 * 32);
+        let t = self.constant_array::<U64Register>(&vec![t_value; num_compresses]);
+        let last = self.constant_array::<BitRegister>(&vec![L::Field::ONE; num_compresses]);
+
+        let messages = paths
+            .iter()
+            .map(|path| {
+                path.siblings
+                    .iter()
+                    .map(|_| {
+                        let m = self.alloc_array_public_unchecked::<U64Register>(16);
+                        self.assert_expression_zero(m.get_subarray(2 * DIGEST_WORDS..16).expr());
+                        m
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let h_out = self.blake2b_compress(
+            &vec![h_in; num_compresses],
+            &messages.iter().flatten().copied().collect::<Vec<_>>(),
+            &t,
+            &last,
+        );
+        let mut h_out = h_out.into_iter();
+        let states = messages
+            .iter()
+            .map(|m| h_out.by_ref().take(m.len()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for ((path, messages), states) in paths.iter().zip(messages.iter()).zip(states.iter()) {
+            let depth = path.siblings.len();
+            assert_eq!(
+                path.key.len(),
+                depth,
+                "key length must match the path length"
+            );
+            let mut node = path.leaf;
+            for (level, ((sibling, m), state)) in path
+                .siblings
+                .iter()
+                .zip(messages.iter())
+                .zip(states.iter())
+                .enumerate()
+            {
+                let bit = path.key.get(depth - 1 - level);
+                for j in 0..DIGEST_WORDS {
+                    let (sibling, node) = (sibling.get(j), node.get(j));
+                    self.api.set_select(&bit, &sibling, &node, &m.get(j));
+                    self.api
+                        .set_select(&bit, &node, &sibling, &m.get(DIGEST_WORDS + j));
+                }
+                node = state.get_subarray(0..DIGEST_WORDS);
+            }
+            for j in 0..DIGEST_WORDS {
+                self.assert_equal(&node.get(j), &path.root.get(j));
+            }
+        }
+
+        SMTGadget {
+            paths: paths.to_vec(),
+            messages,
+            states,
+        }
+    }
+}
+
+impl SMTGadget {
+    /// Writes the path values of `proofs[i]` and the claimed `roots[i]` for each path.
+    pub fn write<F: Field>(
+        &self,
+        proofs: &[SMTProof],
+        roots: &[SMTDigest],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        assert_eq!(proofs.len(), self.paths.len());
+        assert_eq!(roots.len(), self.paths.len());
+        for (i, (proof, root)) in proofs.iter().zip(roots.iter()).enumerate() {
+            let path = &self.paths[i];
+            let depth = proof.key.len();
+            assert_eq!(proof.siblings.len(), path.siblings.len());
+
+            writer.write_array(
+                &path.key,
+                proof.key.iter().map(|b| F::from_canonical_u8(*b as u8)),
+            );
+            writer.write_array(&path.leaf, digest_to_words(&proof.leaf));
+            writer.write_array(&path.root, digest_to_words(root));
+
+            let mut node = proof.leaf;
+            for (level, sibling) in proof.siblings.iter().enumerate() {
+                writer.write_array(&path.siblings[level], digest_to_words(sibling));
+                let (msg, state) = if proof.key[depth - 1 - level] {
+                    SparseMerkleTree::compress_node(sibling, &node)
+                } else {
+                    SparseMerkleTree::compress_node(&node, sibling)
+                };
+                writer.write_array(
+                    &self.messages[i][level],
+                    msg.chunks_exact(8)
+                        .map(|w| core::array::from_fn::<_, 8, _>(|j| F::from_canonical_u8(w[j]))),
+                );
+                writer.write_array(&self.states[i][level], state.map(u64_to_le_field_bytes));
+                node = SparseMerkleTree::state_digest(&state);
+            }
+        }
+    }
+}
+
+fn digest_to_words<F: Field>(digest: &SMTDigest) -> [[F; 8]; DIGEST_WORDS] {
+    core::array::from_fn(|i| core::array::from_fn(|j| F::from_canonical_u8(digest[8 * i + j])))
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::AirWriterData;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SMTTest;
+
+    impl AirParameters for SMTTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1800;
+        const EXTENDED_COLUMNS: usize = 900;
+    }
+
+    const DEPTH: usize = 8;
+
+    fn random_key(rng: &mut impl Rng) -> Vec<bool> {
+        (0..DEPTH).map(|_| rng.gen()).collect()
+    }
+
+    /// Proves the given membership and non-membership paths against one tree, returning whether
+    /// the proof verifies.
+    fn prove_smt_paths(proofs: &[SMTProof], members: &[bool], root: SMTDigest) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = BytesBuilder::<SMTTest>::new();
+        let defaults = builder.smt_defaults(DEPTH);
+        let paths = members
+            .iter()
+            .map(|&member| {
+                if member {
+                    builder.alloc_smt_path(DEPTH)
+                } else {
+                    builder.alloc_smt_non_membership_path(&defaults)
+                }
+            })
+            .collect::<Vec<_>>();
+        let gadget = builder.smt_verify(&paths);
+
+        let num_rows = (96 * DEPTH * proofs.len()).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(proofs, &vec![root; proofs.len()], &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_smt_pure() {
+        let mut rng = thread_rng();
+        let mut tree = SparseMerkleTree::new(DEPTH);
+        let defaults = SparseMerkleTree::default_hashes(DEPTH);
+        assert_eq!(tree.root(), defaults[DEPTH]);
+
+        let keys = (0..10).map(|_| random_key(&mut rng)).collect::<Vec<_>>();
+        for key in keys.iter() {
+            tree.insert(key, rng.gen());
+        }
+        for key in keys.iter() {
+            let proof = tree.prove(key);
+            assert_eq!(proof.leaf, tree.get(key));
+            assert_eq!(proof.compute_root(), tree.root());
+        }
+
+        for key in keys.iter() {
+            tree.insert(key, pure::EMPTY_LEAF);
+        }
+        assert_eq!(tree.root(), defaults[DEPTH]);
+    }
+
+    #[test]
+    fn test_smt_paths() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut rng = thread_rng();
+        let mut tree = SparseMerkleTree::new(DEPTH);
+        let mut member_keys = Vec::new();
+        while member_keys.len() < 4 {
+            let key = random_key(&mut rng);
+            if !member_keys.contains(&key) {
+                tree.insert(&key, rng.gen());
+                member_keys.push(key);
+            }
+        }
+        let absent_key = loop {
+            let key = random_key(&mut rng);
+            if !member_keys.contains(&key) {
+                break key;
+            }
+        };
+        let root = tree.root();
+
+        let mut proofs = vec![tree.prove(&member_keys[0]), tree.prove(&member_keys[1])];
+        proofs.push(tree.prove(&absent_key));
+        let members = [true, true, false];
+        assert!(prove_smt_paths(&proofs, &members, root));
+
+        // A wrong sibling yields a different root.
+        let mut bad_proofs = proofs.clone();
+        bad_proofs[1].siblings[3][0] ^= 1;
+        assert!(!prove_smt_paths(&bad_proofs, &members, root));
+
+        // A member key cannot be proven absent.
+        let member_as_absent = vec![tree.prove(&member_keys[2])];
+        assert!(!prove_smt_paths(&member_as_absent, &[false], root));
+    }
+}
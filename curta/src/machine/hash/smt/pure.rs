@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use crate::machine::hash::blake::blake2b::pure::BLAKE2BPure;
+use crate::machine::hash::blake::blake2b::IV;
+
+/// A 32-byte BLAKE2b digest, which is the value stored in every node of the tree.
+pub type SMTDigest = [u8; 32];
+
+/// The value of an empty leaf slot.
+pub const EMPTY_LEAF: SMTDigest = [0u8; 32];
+
+/// A sparse Merkle tree of fixed depth, keyed by bit strings read from the root.
+///
+/// A key bit of `1` selects the right child. Internal nodes are `BLAKE2b-256(left || right)` and
+/// every empty subtree of height `h` hashes to `default_hashes(depth)[h]`.
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree {
+    depth: usize,
+    leaves: BTreeMap<Vec<bool>, SMTDigest>,
+    defaults: Vec<SMTDigest>,
+}
+
+/// An authentication path for a single key, with siblings ordered from the leaf to the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SMTProof {
+    pub key: Vec<bool>,
+    pub leaf: SMTDigest,
+    pub siblings: Vec<SMTDigest>,
+}
+
+impl SparseMerkleTree {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            leaves: BTreeMap::new(),
+            defaults: Self::default_hashes(depth),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Hashes two child nodes into their parent with a single BLAKE2b compression.
+    pub fn hash_node(left: &SMTDigest, right: &SMTDigest) -> SMTDigest {
+        let (_, state) = Self::compress_node(left, right);
+        Self::state_digest(&state)
+    }
+
+    /// The message block and output state of the compression hashing `left || right`.
+    pub fn compress_node(left: &SMTDigest, right: &SMTDigest) -> ([u8; 128], [u64; 8]) {
+        let mut msg = [0u8; 128];
+        msg[..32].copy_from_slice(left);
+        msg[32..64].copy_from_slice(right);
+        let mut state = IV;
+        let state = BLAKE2BPure::compress(&msg, &mut state, 64, true);
+        (msg, state)
+    }
+
+    /// The digest given by the first four words of a BLAKE2b state.
+    pub fn state_digest(state: &[u64; 8]) -> SMTDigest {
+        let mut digest = [0u8; 32];
+        for (chunk, word) in digest.chunks_exact_mut(8).zip(state.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    /// The hashes of empty subtrees of height `0..=depth`, starting with `EMPTY_LEAF`.
+    pub fn default_hashes(depth: usize) -> Vec<SMTDigest> {
+        let mut defaults = vec![EMPTY_LEAF];
+        for i in 0..depth {
+            defaults.push(Self::hash_node(&defaults[i], &defaults[i]));
+        }
+        defaults
+    }
+
+    /// Sets the leaf at `key` to `value`. Setting a leaf to `EMPTY_LEAF` removes it.
+    pub fn insert(&mut self, key: &[bool], value: SMTDigest) {
+        assert_eq!(
+            key.len(),
+            self.depth,
+            "key length must match the tree depth"
+        );
+        if value == EMPTY_LEAF {
+            self.leaves.remove(key);
+        } else {
+            self.leaves.insert(key.to_vec(), value);
+        }
+    }
+
+    pub fn get(&self, key: &[bool]) -> SMTDigest {
+        self.leaves.get(key).copied().unwrap_or(EMPTY_LEAF)
+    }
+
+    pub fn root(&self) -> SMTDigest {
+        self.subtree(&[])
+    }
+
+    /// The authentication path of `key`, valid both for members and for empty slots.
+    pub fn prove(&self, key: &[bool]) -> SMTProof {
+        assert_eq!(
+            key.len(),
+            self.depth,
+            "key length must match the tree depth"
+        );
+        let siblings = (0..self.depth)
+            .map(|level| {
+                let position = self.depth - 1 - level;
+                let mut prefix = key[..position].to_vec();
+                prefix.push(!key[position]);
+                self.subtree(&prefix)
+            })
+            .collect();
+        SMTProof {
+            key: key.to_vec(),
+            leaf: self.get(key),
+            siblings,
+        }
+    }
+
+    fn subtree(&self, prefix: &[bool]) -> SMTDigest {
+        let height = self.depth - prefix.len();
+        let mut leaves = self
+            .leaves
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix));
+        match leaves.next() {
+            None => self.defaults[height],
+            Some((_, value)) if height == 0 => *value,
+            Some(_) => {
+                let mut left = prefix.to_vec();
+                left.push(false);
+                let mut right = prefix.to_vec();
+                right.push(true);
+                Self::hash_node(&self.subtree(&left), &self.subtree(&right))
+            }
+        }
+    }
+}
+
+impl SMTProof {
+    /// The root obtained by hashing the leaf up the path.
+    pub fn compute_root(&self) -> SMTDigest {
+        let depth = self.key.len();
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(self.leaf, |node, (level, sibling)| {
+                if self.key[depth - 1 - level] {
+                    SparseMerkleTree::hash_node(sibling, &node)
+                } else {
+                    SparseMerkleTree::hash_node(&node, sibling)
+                }
+            })
+    }
+}
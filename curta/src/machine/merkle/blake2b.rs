@@ -0,0 +1,116 @@
+use super::{MerkleHash, MerkleHasher};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::blake::blake2b::IV;
+use crate::machine::hash::smt::pure::{SMTDigest, SparseMerkleTree};
+use crate::math::prelude::*;
+
+/// The number of `U64Register` words of a digest.
+const DIGEST_WORDS: usize = 4;
+
+/// Merkle nodes hashed as `BLAKE2b-256(left || right)`, a single compression per node.
+#[derive(Debug, Clone, Copy)]
+pub struct BLAKE2BHasher;
+
+impl MerkleHash for BLAKE2BHasher {
+    type Digest = SMTDigest;
+
+    fn hash(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        SparseMerkleTree::hash_node(left, right)
+    }
+}
+
+impl<L: AirParameters> MerkleHasher<BytesBuilder<L>> for BLAKE2BHasher
+where
+    L::Instruction: UintInstructions,
+{
+    type Word = U64Register;
+    /// The output state of every compression.
+    type Witness = Vec<ArrayRegister<U64Register>>;
+
+    const DIGEST_WORDS: usize = DIGEST_WORDS;
+
+    fn digest_words(digest: &Self::Digest) -> Vec<[L::Field; 8]> {
+        digest
+            .chunks_exact(8)
+            .map(|word| core::array::from_fn(|j| L::Field::from_canonical_u8(word[j])))
+            .collect()
+    }
+
+    /// The message blocks are set from the children before they are stored, and the first words
+    /// of every output state are constrained to be equal to the parent. The trace must have
+    /// `(96 * children.len()).next_power_of_two()` rows.
+    fn hash_nodes(
+        builder: &mut BytesBuilder<L>,
+        children: &[(ArrayRegister<U64Register>, ArrayRegister<U64Register>)],
+        parents: &[ArrayRegister<U64Register>],
+    ) -> Self::Witness {
+        assert_eq!(children.len(), parents.len());
+        let num_compresses = children.len();
+
+        let zero = builder.constant::<U64Register>(&u64_to_le_field_bytes(0));
+        let messages = children
+            .iter()
+            .map(|(left, right)| {
+                let m = builder.alloc_array_public_unchecked::<U64Register>(16);
+                for (j, word) in m.iter().enumerate() {
+                    let value = match j / DIGEST_WORDS {
+                        0 => left.get(j),
+                        1 => right.get(j - DIGEST_WORDS),
+                        _ => zero,
+                    };
+                    builder.set_to_expression(&word, value.expr());
+                }
+                m
+            })
+            .collect::<Vec<_>>();
+
+        let h_in = builder.constant_array::<U64Register>(&IV.map(u64_to_le_field_bytes));
+        let t_value = u64_to_le_field_bytes(2 * 32);
+        let t = builder.constant_array::<U64Register>(&vec![t_value; num_compresses]);
+        let last = builder.constant_array::<BitRegister>(&vec![L::Field::ONE; num_compresses]);
+        let states = builder.blake2b_compress(&vec![h_in; num_compresses], &messages, &t, &last);
+
+        for (state, parent) in states.iter().zip(parents.iter()) {
+            for j in 0..DIGEST_WORDS {
+                builder.assert_equal(&state.get(j), &parent.get(j));
+            }
+        }
+        states
+    }
+
+    fn write_witness(
+        witness: &Self::Witness,
+        children: &[(Self::Digest, Self::Digest)],
+        writer: &mut impl AirWriter<Field = L::Field>,
+    ) {
+        assert_eq!(witness.len(), children.len());
+        for (state, (left, right)) in witness.iter().zip(children.iter()) {
+            let (_, value) = SparseMerkleTree::compress_node(left, right);
+            writer.write_array(state, value.map(u64_to_le_field_bytes));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blake2b_hasher() {
+        let left = core::array::from_fn(|i| i as u8);
+        let right = core::array::from_fn(|i| 32 + i as u8);
+        assert_eq!(
+            hex::encode(BLAKE2BHasher::hash(&left, &right)),
+            "10d8e6d534b00939843fe9dcc4dae48cdf008f6b8b2b82b156f5404d874887f5"
+        );
+    }
+}
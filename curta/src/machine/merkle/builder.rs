@@ -0,0 +1,460 @@
+use super::{tree_layout, MerkleHasher, MerkleProof};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// The registers of a Merkle tree.
+#[derive(Debug, Clone)]
+pub struct MerkleTreeGadget<B: Builder, H: MerkleHasher<B>> {
+    pub leaves: Vec<ArrayRegister<H::Word>>,
+    pub root: ArrayRegister<H::Word>,
+    /// The parent of every hash of the tree, in the order of `tree_layout`.
+    parents: Vec<ArrayRegister<H::Word>>,
+    witness: H::Witness,
+}
+
+/// The registers of a Merkle authentication path.
+#[derive(Debug, Clone)]
+pub struct MerklePath<T: Register> {
+    pub leaf: ArrayRegister<T>,
+    /// The bits of the leaf index, from the leaf to the root. A bit of `1` makes the node the
+    /// right child.
+    pub index: ArrayRegister<BitRegister>,
+    /// The siblings, ordered from the leaf to the root.
+    pub siblings: Vec<ArrayRegister<T>>,
+    pub root: ArrayRegister<T>,
+}
+
+/// The registers of a batch of verified paths.
+#[derive(Debug, Clone)]
+pub struct MerklePathGadget<B: Builder, H: MerkleHasher<B>> {
+    pub paths: Vec<MerklePath<H::Word>>,
+    /// The nodes of every path strictly between the leaf and the root.
+    nodes: Vec<Vec<ArrayRegister<H::Word>>>,
+    witness: H::Witness,
+}
+
+pub trait MerkleBuilder: Builder {
+    /// Computes the Merkle root of `leaves` with the two-to-one hash `H`.
+    ///
+    /// The leaves must be public registers of `H::DIGEST_WORDS` words. An odd node at the end of
+    /// a level is promoted to the next level unchanged. All the hashes of the tree are proven in
+    /// a single call to `H::hash_nodes`, the inner nodes and the root are public registers whose
+    /// values are written by `MerkleTreeGadget::write`.
+    fn merkle_root<H: MerkleHasher<Self>>(
+        &mut self,
+        leaves: &[ArrayRegister<H::Word>],
+    ) -> MerkleTreeGadget<Self, H> {
+        for leaf in leaves.iter() {
+            assert_eq!(leaf.len(), H::DIGEST_WORDS, "Leaves must be digests");
+        }
+        let layout = tree_layout(leaves.len());
+        let parents = layout
+            .iter()
+            .map(|_| self.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+            .collect::<Vec<_>>();
+        let nodes = leaves.iter().chain(parents.iter()).collect::<Vec<_>>();
+        let children = layout
+            .iter()
+            .map(|&(left, right)| (*nodes[left], *nodes[right]))
+            .collect::<Vec<_>>();
+        let witness = H::hash_nodes(self, &children, &parents);
+
+        MerkleTreeGadget {
+            leaves: leaves.to_vec(),
+            root: *parents.last().unwrap(),
+            parents,
+            witness,
+        }
+    }
+
+    /// Allocates a path of depth `depth` whose values are given by the prover.
+    fn alloc_merkle_path<H: MerkleHasher<Self>>(&mut self, depth: usize) -> MerklePath<H::Word> {
+        MerklePath {
+            leaf: self.alloc_array_public::<H::Word>(H::DIGEST_WORDS),
+            index: self.alloc_array_public::<BitRegister>(depth),
+            siblings: (0..depth)
+                .map(|_| self.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+                .collect(),
+            root: self.alloc_array_public::<H::Word>(H::DIGEST_WORDS),
+        }
+    }
+
+    /// Verifies that each of `paths` hashes its leaf up to its root.
+    ///
+    /// At every level, the node and its sibling are swapped when the index bit is set. The hashes
+    /// of all the paths are proven in a single call to `H::hash_nodes`, and the last hash of each
+    /// path is constrained to be equal to its root. The inner nodes of the paths are public
+    /// registers whose values are written by `MerklePathGadget::write`.
+    fn merkle_verify<H: MerkleHasher<Self>>(
+        &mut self,
+        paths: &[MerklePath<H::Word>],
+    ) -> MerklePathGadget<Self, H> {
+        let mut children = Vec::new();
+        let mut parents = Vec::new();
+        let nodes = paths
+            .iter()
+            .map(|path| {
+                assert!(
+                    !path.siblings.is_empty(),
+                    "A path must have at least one sibling"
+                );
+                path_nodes::<Self, H>(self, path, &mut children, &mut parents)
+            })
+            .collect();
+        let witness = H::hash_nodes(self, &children, &parents);
+
+        MerklePathGadget {
+            paths: paths.to_vec(),
+            nodes,
+            witness,
+        }
+    }
+}
+
+impl<B: Builder> MerkleBuilder for B {}
+
+/// Adds the hashes of `path` to `children` and `parents`, the last parent being the root.
+///
+/// Returns the nodes of the path strictly between the leaf and the root.
+pub(crate) fn path_nodes<B: Builder, H: MerkleHasher<B>>(
+    builder: &mut B,
+    path: &MerklePath<H::Word>,
+    children: &mut Vec<(ArrayRegister<H::Word>, ArrayRegister<H::Word>)>,
+    parents: &mut Vec<ArrayRegister<H::Word>>,
+) -> Vec<ArrayRegister<H::Word>> {
+    let depth = path.siblings.len();
+    assert_eq!(
+        path.index.len(),
+        depth,
+        "The index must have a bit per sibling"
+    );
+
+    let inner_nodes = (1..depth)
+        .map(|_| builder.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+        .collect::<Vec<_>>();
+    let mut node = path.leaf;
+    for (k, sibling) in path.siblings.iter().enumerate() {
+        let bit = path.index.get(k);
+        let left = builder.alloc_array_public_unchecked::<H::Word>(H::DIGEST_WORDS);
+        let right = builder.alloc_array_public_unchecked::<H::Word>(H::DIGEST_WORDS);
+        for j in 0..H::DIGEST_WORDS {
+            let (sibling, node) = (sibling.get(j), node.get(j));
+            builder
+                .api()
+                .set_select(&bit, &sibling, &node, &left.get(j));
+            builder
+                .api()
+                .set_select(&bit, &node, &sibling, &right.get(j));
+        }
+        let parent = inner_nodes.get(k).copied().unwrap_or(path.root);
+        children.push((left, right));
+        parents.push(parent);
+        node = parent;
+    }
+    inner_nodes
+}
+
+impl<B: Builder, H: MerkleHasher<B>> MerkleTreeGadget<B, H> {
+    /// Writes the inner nodes of the tree of `leaves` and returns the root.
+    ///
+    /// The leaves themselves are written by the caller.
+    pub fn write(
+        &self,
+        leaves: &[H::Digest],
+        writer: &mut impl AirWriter<Field = B::Field>,
+    ) -> H::Digest {
+        assert_eq!(leaves.len(), self.leaves.len());
+        let mut nodes = leaves.to_vec();
+        let mut children = Vec::new();
+        for ((left, right), parent) in tree_layout(leaves.len()).into_iter().zip(&self.parents) {
+            let value = H::hash(&nodes[left], &nodes[right]);
+            writer.write_array(parent, H::digest_words(&value));
+            children.push((nodes[left].clone(), nodes[right].clone()));
+            nodes.push(value);
+        }
+        H::write_witness(&self.witness, &children, writer);
+        nodes.pop().unwrap()
+    }
+}
+
+impl<T: Register> MerklePath<T> {
+    /// Writes the index, the leaf and the siblings of `proof`.
+    pub fn write_proof<B: Builder, H: MerkleHasher<B, Word = T>>(
+        &self,
+        proof: &MerkleProof<H::Digest>,
+        writer: &mut impl AirWriter<Field = B::Field>,
+    ) {
+        assert_eq!(proof.depth(), self.siblings.len());
+        writer.write_array(
+            &self.index,
+            proof
+                .index_bits()
+                .into_iter()
+                .map(|bit| B::Field::from_canonical_u8(bit as u8)),
+        );
+        writer.write_array(&self.leaf, H::digest_words(&proof.leaf));
+        for (register, sibling) in self.siblings.iter().zip(proof.siblings.iter()) {
+            writer.write_array(register, H::digest_words(sibling));
+        }
+    }
+}
+
+impl<B: Builder, H: MerkleHasher<B>> MerklePathGadget<B, H> {
+    /// Writes the values of `proofs[i]` and the claimed `roots[i]` for each path.
+    pub fn write(
+        &self,
+        proofs: &[MerkleProof<H::Digest>],
+        roots: &[H::Digest],
+        writer: &mut impl AirWriter<Field = B::Field>,
+    ) {
+        assert_eq!(proofs.len(), self.paths.len());
+        assert_eq!(roots.len(), self.paths.len());
+        let mut all_children = Vec::new();
+        for (((path, nodes), proof), root) in self
+            .paths
+            .iter()
+            .zip(self.nodes.iter())
+            .zip(proofs.iter())
+            .zip(roots.iter())
+        {
+            path.write_proof::<B, H>(proof, writer);
+            writer.write_array(&path.root, H::digest_words(root));
+
+            let children = proof.children::<H>();
+            for (register, (left, right)) in nodes.iter().zip(children.iter()) {
+                writer.write_array(register, H::digest_words(&H::hash(left, right)));
+            }
+            all_children.extend(children);
+        }
+        H::write_witness(&self.witness, &all_children, writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::util::timing::TimingTree;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::instruction::empty::EmptyInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::poseidon::NUM_HASH_OUT_ELTS;
+    use crate::machine::merkle::blake2b::BLAKE2BHasher;
+    use crate::machine::merkle::poseidon::PoseidonHasher;
+    use crate::machine::merkle::sha256::SHA256Hasher;
+    use crate::machine::merkle::MerkleHash;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MerkleTest;
+
+    impl AirParameters for MerkleTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1800;
+        const EXTENDED_COLUMNS: usize = 900;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PoseidonMerkleTest;
+
+    impl AirParameters for PoseidonMerkleTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 1100;
+    }
+
+    /// Proves the root of `leaves` with a hash of the bytes machine, returning whether the proof
+    /// verifies. If `tamper` is set, the root is replaced by one of the leaves.
+    fn prove_merkle_root<H: MerkleHasher<BytesBuilder<MerkleTest>>>(
+        leaves: &[H::Digest],
+        rows_per_hash: usize,
+        tamper: bool,
+    ) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = BytesBuilder::<MerkleTest>::new();
+        let leaf_registers = leaves
+            .iter()
+            .map(|_| builder.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+            .collect::<Vec<_>>();
+        let gadget = builder.merkle_root::<H>(&leaf_registers);
+
+        let num_rows = (rows_per_hash * (leaves.len() - 1)).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (register, leaf) in leaf_registers.iter().zip(leaves.iter()) {
+            writer.write_array(register, H::digest_words(leaf));
+        }
+        let root = gadget.write(leaves, &mut writer);
+        assert_eq!(root, H::root(leaves));
+        if tamper {
+            writer.write_array(&gadget.root, H::digest_words(&leaves[0]));
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    /// Proves the given paths against `root`, returning whether the proof verifies.
+    fn prove_merkle_paths<H: MerkleHasher<BytesBuilder<MerkleTest>>>(
+        proofs: &[MerkleProof<H::Digest>],
+        root: &H::Digest,
+        rows_per_hash: usize,
+    ) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = BytesBuilder::<MerkleTest>::new();
+        let paths = proofs
+            .iter()
+            .map(|proof| builder.alloc_merkle_path::<H>(proof.depth()))
+            .collect::<Vec<_>>();
+        let gadget = builder.merkle_verify::<H>(&paths);
+
+        let num_hashes = proofs.iter().map(|proof| proof.depth()).sum::<usize>();
+        let num_rows = (rows_per_hash * num_hashes).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(proofs, &vec![root.clone(); proofs.len()], &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_merkle_root_sha256() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut rng = thread_rng();
+        let leaves = (0..5).map(|_| rng.gen()).collect::<Vec<[u32; 8]>>();
+        assert!(prove_merkle_root::<SHA256Hasher>(&leaves, 128, false));
+        assert!(!prove_merkle_root::<SHA256Hasher>(&leaves, 128, true));
+    }
+
+    #[test]
+    fn test_merkle_root_blake2b() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut rng = thread_rng();
+        let leaves = (0..4).map(|_| rng.gen()).collect::<Vec<[u8; 32]>>();
+        assert!(prove_merkle_root::<BLAKE2BHasher>(&leaves, 96, false));
+        assert!(!prove_merkle_root::<BLAKE2BHasher>(&leaves, 96, true));
+    }
+
+    #[test]
+    fn test_merkle_root_poseidon() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_merkle_root_poseidon", log::Level::Debug);
+
+        let leaves = (0..3)
+            .map(|_| HashOut {
+                elements: F::rand_array::<NUM_HASH_OUT_ELTS>(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = StarkBuilder::<PoseidonMerkleTest>::new();
+        let leaf_registers = leaves
+            .iter()
+            .map(|_| builder.alloc_array_public(NUM_HASH_OUT_ELTS))
+            .collect::<Vec<_>>();
+        let gadget = builder.merkle_root::<PoseidonHasher>(&leaf_registers);
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for (register, leaf) in leaf_registers.iter().zip(leaves.iter()) {
+            writer.write_array(register, leaf.elements);
+        }
+        let root = gadget.write(&leaves, &mut writer);
+        assert_eq!(root, PoseidonHasher::root(&leaves));
+        stark.air_data.write_global_instructions(&mut writer);
+        writer_data.chunks(num_rows).for_each(|mut chunk| {
+            for i in 0..num_rows {
+                let mut writer = chunk.row_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_merkle_path_sha256() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut rng = thread_rng();
+        let leaves = (0..8).map(|_| rng.gen()).collect::<Vec<[u32; 8]>>();
+        let root = SHA256Hasher::root(&leaves);
+        let proofs = [
+            SHA256Hasher::prove(&leaves, 0),
+            SHA256Hasher::prove(&leaves, 5),
+        ];
+        for proof in proofs.iter() {
+            assert_eq!(proof.compute_root::<SHA256Hasher>(), root);
+        }
+        assert!(prove_merkle_paths::<SHA256Hasher>(&proofs, &root, 128));
+
+        // A wrong sibling or a wrong index yields a different root.
+        let mut bad_proofs = proofs.clone();
+        bad_proofs[1].siblings[1][0] ^= 1;
+        assert!(!prove_merkle_paths::<SHA256Hasher>(&bad_proofs, &root, 128));
+        let mut bad_proofs = proofs.clone();
+        bad_proofs[1].index ^= 2;
+        assert!(!prove_merkle_paths::<SHA256Hasher>(&bad_proofs, &root, 128));
+    }
+
+    #[test]
+    fn test_merkle_path_blake2b() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut rng = thread_rng();
+        let leaves = (0..4).map(|_| rng.gen()).collect::<Vec<[u8; 32]>>();
+        let root = BLAKE2BHasher::root(&leaves);
+        let proofs = [BLAKE2BHasher::prove(&leaves, 3)];
+        assert!(prove_merkle_paths::<BLAKE2BHasher>(&proofs, &root, 96));
+        assert!(!prove_merkle_paths::<BLAKE2BHasher>(
+            &proofs, &leaves[0], 96
+        ));
+    }
+}
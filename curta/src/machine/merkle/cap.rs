@@ -0,0 +1,275 @@
+//! Verification of plonky2 Merkle proofs against a `MerkleCap`.
+//!
+//! A plonky2 `MerkleTree` hashes every leaf with `PoseidonHash::hash_or_noop` and keeps the
+//! `2^cap_height` nodes at height `depth` as its cap. A proof of the leaf at `index` is a path of
+//! `depth` siblings up to the cap entry `cap[index >> depth]`.
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::merkle_proofs::MerkleProof as Plonky2MerkleProof;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::plonk::config::Hasher;
+
+use super::builder::{MerkleBuilder, MerklePath, MerklePathGadget};
+use super::poseidon::PoseidonHasher;
+use super::MerkleProof;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::machine::hash::poseidon::builder::PoseidonBuilder;
+use crate::machine::hash::poseidon::NUM_HASH_OUT_ELTS;
+use crate::math::prelude::*;
+
+/// The registers of a path from the data of a leaf to an entry of a cap.
+#[derive(Debug, Clone)]
+pub struct MerkleCapPath {
+    pub leaf_data: ArrayRegister<ElementRegister>,
+    /// The bits of the leaf index, from the leaf to the cap. The last `cap_height` bits select
+    /// the cap entry.
+    pub index: ArrayRegister<BitRegister>,
+    /// The path from the leaf digest to the selected cap entry.
+    pub path: MerklePath<ElementRegister>,
+}
+
+/// The registers of a batch of paths verified against a cap.
+#[derive(Debug, Clone)]
+pub struct MerkleCapGadget<B: Builder> {
+    pub cap: Vec<ArrayRegister<ElementRegister>>,
+    pub paths: Vec<MerkleCapPath>,
+    gadget: MerklePathGadget<B, PoseidonHasher>,
+}
+
+pub trait MerkleCapBuilder: Builder<Field = GoldilocksField> {
+    /// Allocates the `2^cap_height` entries of a cap, whose values are given by the prover.
+    fn alloc_merkle_cap(&mut self, cap_height: usize) -> Vec<ArrayRegister<ElementRegister>> {
+        (0..1 << cap_height)
+            .map(|_| self.alloc_array_public::<ElementRegister>(NUM_HASH_OUT_ELTS))
+            .collect()
+    }
+
+    /// Allocates a path of `depth` siblings from a leaf of `leaf_len` elements to an entry of
+    /// `cap`.
+    ///
+    /// The leaf digest is computed as `PoseidonHash::hash_or_noop`: a leaf of at most
+    /// `NUM_HASH_OUT_ELTS` elements is padded with zeros, a longer one is hashed within a row.
+    /// The cap entry is selected by the last bits of the index.
+    fn alloc_merkle_cap_path(
+        &mut self,
+        cap: &[ArrayRegister<ElementRegister>],
+        leaf_len: usize,
+        depth: usize,
+    ) -> MerkleCapPath {
+        assert!(
+            cap.len().is_power_of_two(),
+            "A cap must have 2^cap_height entries"
+        );
+        assert!(leaf_len > 0, "Cannot hash an empty leaf");
+        let cap_height = cap.len().trailing_zeros() as usize;
+
+        let leaf_data = self.alloc_array_public::<ElementRegister>(leaf_len);
+        let index = self.alloc_array_public::<BitRegister>(depth + cap_height);
+
+        let leaf = if leaf_len <= NUM_HASH_OUT_ELTS {
+            let leaf = self.alloc_array_public_unchecked::<ElementRegister>(NUM_HASH_OUT_ELTS);
+            for (j, element) in leaf.iter().enumerate() {
+                let value = if j < leaf_len {
+                    leaf_data.get(j).expr()
+                } else {
+                    ArithmeticExpression::zero()
+                };
+                self.set_to_expression(&element, value);
+            }
+            leaf
+        } else {
+            let leaf = self.alloc_array_public::<ElementRegister>(NUM_HASH_OUT_ELTS);
+            let output = self.poseidon_hash_no_pad(&leaf_data.iter().collect::<Vec<_>>());
+            self.assert_expressions_equal(output.expr(), leaf.expr());
+            leaf
+        };
+
+        let mut level = cap.to_vec();
+        for k in depth..depth + cap_height {
+            let bit = index.get(k);
+            level = level
+                .chunks_exact(2)
+                .map(|pair| {
+                    let node =
+                        self.alloc_array_public_unchecked::<ElementRegister>(NUM_HASH_OUT_ELTS);
+                    for j in 0..NUM_HASH_OUT_ELTS {
+                        self.api()
+                            .set_select(&bit, &pair[1].get(j), &pair[0].get(j), &node.get(j));
+                    }
+                    node
+                })
+                .collect();
+        }
+
+        MerkleCapPath {
+            leaf_data,
+            index,
+            path: MerklePath {
+                leaf,
+                index: index.get_subarray(0..depth),
+                siblings: (0..depth)
+                    .map(|_| self.alloc_array_public::<ElementRegister>(NUM_HASH_OUT_ELTS))
+                    .collect(),
+                root: level[0],
+            },
+        }
+    }
+
+    /// Verifies each of `paths` against `cap`, as plonky2's `verify_merkle_proof_to_cap`.
+    ///
+    /// The hashes of all the paths are proven in a single call to `merkle_verify`.
+    fn merkle_verify_to_cap(
+        &mut self,
+        cap: &[ArrayRegister<ElementRegister>],
+        paths: &[MerkleCapPath],
+    ) -> MerkleCapGadget<Self> {
+        let merkle_paths = paths.iter().map(|p| p.path.clone()).collect::<Vec<_>>();
+        let gadget = self.merkle_verify::<PoseidonHasher>(&merkle_paths);
+        MerkleCapGadget {
+            cap: cap.to_vec(),
+            paths: paths.to_vec(),
+            gadget,
+        }
+    }
+}
+
+impl<B: Builder<Field = GoldilocksField>> MerkleCapBuilder for B {}
+
+impl<B: Builder<Field = GoldilocksField>> MerkleCapGadget<B> {
+    /// Writes `cap` and, for each path, the leaf data at `indices[i]` with its plonky2 proof.
+    pub fn write(
+        &self,
+        cap: &MerkleCap<GoldilocksField, PoseidonHash>,
+        leaves: &[Vec<GoldilocksField>],
+        indices: &[usize],
+        proofs: &[Plonky2MerkleProof<GoldilocksField, PoseidonHash>],
+        writer: &mut impl AirWriter<Field = GoldilocksField>,
+    ) {
+        assert_eq!(cap.0.len(), self.cap.len());
+        assert_eq!(leaves.len(), self.paths.len());
+        assert_eq!(indices.len(), self.paths.len());
+        assert_eq!(proofs.len(), self.paths.len());
+        for (register, entry) in self.cap.iter().zip(cap.0.iter()) {
+            writer.write_array(register, entry.elements);
+        }
+
+        let mut path_proofs = Vec::new();
+        let mut roots = Vec::new();
+        for (((path, leaf), &index), proof) in self
+            .paths
+            .iter()
+            .zip(leaves.iter())
+            .zip(indices.iter())
+            .zip(proofs.iter())
+        {
+            let depth = path.path.siblings.len();
+            assert_eq!(proof.siblings.len(), depth);
+            writer.write_array(&path.leaf_data, leaf.iter().copied());
+            writer.write_array(
+                &path.index,
+                (0..path.index.len())
+                    .map(|k| GoldilocksField::from_canonical_usize((index >> k) & 1)),
+            );
+            path_proofs.push(MerkleProof {
+                index: index & ((1 << depth) - 1),
+                leaf: PoseidonHash::hash_or_noop(leaf),
+                siblings: proof.siblings.clone(),
+            });
+            roots.push(cap.0[index >> depth]);
+        }
+        self.gadget.write(&path_proofs, &roots, writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+    use plonky2::hash::merkle_proofs::verify_merkle_proof_to_cap;
+    use plonky2::hash::merkle_tree::MerkleTree;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::instruction::empty::EmptyInstruction;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::AirParameters;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MerkleCapTest;
+
+    impl AirParameters for MerkleCapTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 1536;
+    }
+
+    fn test_merkle_cap(leaf_len: usize, index: usize) {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_merkle_cap", log::Level::Debug);
+
+        let (log_num_leaves, cap_height) = (3, 1);
+        let depth = log_num_leaves - cap_height;
+        let leaves = (0..1 << log_num_leaves)
+            .map(|_| F::rand_vec(leaf_len))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<F, PoseidonHash>::new(leaves.clone(), cap_height);
+        let proof = tree.prove(index);
+        verify_merkle_proof_to_cap(leaves[index].clone(), index, &tree.cap, &proof).unwrap();
+
+        let mut builder = StarkBuilder::<MerkleCapTest>::new();
+        let cap = builder.alloc_merkle_cap(cap_height);
+        let path = builder.alloc_merkle_cap_path(&cap, leaf_len, depth);
+        let gadget = builder.merkle_verify_to_cap(&cap, &[path]);
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(
+            &tree.cap,
+            &[leaves[index].clone()],
+            &[index],
+            &[proof],
+            &mut writer,
+        );
+        stark.air_data.write_global_instructions(&mut writer);
+        writer_data.chunks(num_rows).for_each(|mut chunk| {
+            for i in 0..num_rows {
+                let mut writer = chunk.row_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_merkle_cap_hashed_leaf() {
+        test_merkle_cap(7, 5);
+    }
+
+    #[test]
+    fn test_merkle_cap_short_leaf() {
+        test_merkle_cap(3, 2);
+    }
+}
@@ -0,0 +1,328 @@
+//! Merkle mountain ranges, with inclusion proofs and appends proven over a `MerkleHasher`.
+//!
+//! The number of leaves of a range is fixed when building the AIR, and so are its mountains.
+//! See `pure::MerkleMountainRange` for the merging and bagging rules.
+
+pub mod pure;
+
+use self::pure::{MMRProof, MerkleMountainRange};
+use super::builder::{path_nodes, MerklePath};
+use super::MerkleHasher;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+
+/// The registers of the peaks and the root of a Merkle mountain range.
+#[derive(Debug, Clone)]
+pub struct MMRPeaks<T: Register> {
+    pub num_leaves: usize,
+    /// The peaks, largest mountain first.
+    pub peaks: Vec<ArrayRegister<T>>,
+    pub root: ArrayRegister<T>,
+}
+
+/// The registers of an inclusion proof of a leaf of the mountain `mountain`.
+#[derive(Debug, Clone)]
+pub struct MMRPath<T: Register> {
+    pub mountain: usize,
+    /// The path of the leaf within its mountain, whose root is the peak.
+    pub path: MerklePath<T>,
+}
+
+/// The registers of a batch of inclusion proofs and appends on a Merkle mountain range.
+#[derive(Debug, Clone)]
+pub struct MMRGadget<B: Builder, H: MerkleHasher<B>> {
+    pub mmr: MMRPeaks<H::Word>,
+    pub paths: Vec<MMRPath<H::Word>>,
+    /// The appended leaves.
+    pub leaves: Vec<ArrayRegister<H::Word>>,
+    /// The range after appending `leaves`.
+    pub next: MMRPeaks<H::Word>,
+    /// The parent of every hash, in the order of the hashes.
+    parents: Vec<ArrayRegister<H::Word>>,
+    witness: H::Witness,
+}
+
+pub trait MMRBuilder: Builder {
+    /// Allocates the peaks of a range of `num_leaves` leaves, whose values are given by the
+    /// prover.
+    ///
+    /// The root is a public register computed by `mmr_verify`.
+    fn alloc_mmr<H: MerkleHasher<Self>>(&mut self, num_leaves: usize) -> MMRPeaks<H::Word> {
+        assert!(num_leaves > 0, "A range must have at least one leaf");
+        let peaks = MerkleMountainRange::<H>::heights(num_leaves)
+            .iter()
+            .map(|_| self.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+            .collect::<Vec<_>>();
+        let root = match peaks.as_slice() {
+            [peak] => *peak,
+            _ => self.alloc_array_public::<H::Word>(H::DIGEST_WORDS),
+        };
+        MMRPeaks {
+            num_leaves,
+            peaks,
+            root,
+        }
+    }
+
+    /// Allocates an inclusion proof of a leaf of the mountain `mountain` of `mmr`.
+    ///
+    /// The mountain fixes the range of the leaf index, and the position of the leaf within the
+    /// mountain is given by the index bits of the path.
+    fn alloc_mmr_path<H: MerkleHasher<Self>>(
+        &mut self,
+        mmr: &MMRPeaks<H::Word>,
+        mountain: usize,
+    ) -> MMRPath<H::Word> {
+        let height = MerkleMountainRange::<H>::heights(mmr.num_leaves)[mountain];
+        let peak = mmr.peaks[mountain];
+        let leaf = match height {
+            0 => peak,
+            _ => self.alloc_array_public::<H::Word>(H::DIGEST_WORDS),
+        };
+        let path = MerklePath {
+            leaf,
+            index: self.alloc_array_public::<BitRegister>(height),
+            siblings: (0..height)
+                .map(|_| self.alloc_array_public::<H::Word>(H::DIGEST_WORDS))
+                .collect(),
+            root: peak,
+        };
+        MMRPath { mountain, path }
+    }
+
+    /// Proves each of `paths` against `mmr` and appends `leaves` to it.
+    ///
+    /// The root of `mmr` is computed by bagging its peaks, and the peaks and the root of the
+    /// range after the appends are returned in `MMRGadget::next`. All the hashes are proven in a
+    /// single call to `H::hash_nodes`, and the computed nodes are public registers whose values
+    /// are written by `MMRGadget::write`.
+    fn mmr_verify<H: MerkleHasher<Self>>(
+        &mut self,
+        mmr: &MMRPeaks<H::Word>,
+        paths: &[MMRPath<H::Word>],
+        leaves: &[ArrayRegister<H::Word>],
+    ) -> MMRGadget<Self, H> {
+        let mut children = Vec::new();
+        let mut parents = Vec::new();
+        for path in paths.iter() {
+            path_nodes::<Self, H>(self, &path.path, &mut children, &mut parents);
+        }
+        bag_peaks::<Self, H>(self, &mmr.peaks, mmr.root, &mut children, &mut parents);
+
+        // Every appended leaf is merged with the peaks of the same height.
+        let mut peaks = mmr
+            .peaks
+            .iter()
+            .copied()
+            .zip(MerkleMountainRange::<H>::heights(mmr.num_leaves))
+            .collect::<Vec<_>>();
+        for leaf in leaves.iter() {
+            assert_eq!(leaf.len(), H::DIGEST_WORDS, "Leaves must be digests");
+            let (mut node, mut height) = (*leaf, 0);
+            while peaks.last().map(|(_, h)| *h) == Some(height) {
+                let (peak, _) = peaks.pop().unwrap();
+                let parent = self.alloc_array_public::<H::Word>(H::DIGEST_WORDS);
+                children.push((peak, node));
+                parents.push(parent);
+                node = parent;
+                height += 1;
+            }
+            peaks.push((node, height));
+        }
+        let next = if leaves.is_empty() {
+            mmr.clone()
+        } else {
+            let peaks = peaks.into_iter().map(|(peak, _)| peak).collect::<Vec<_>>();
+            let root = match peaks.as_slice() {
+                [peak] => *peak,
+                _ => self.alloc_array_public::<H::Word>(H::DIGEST_WORDS),
+            };
+            bag_peaks::<Self, H>(self, &peaks, root, &mut children, &mut parents);
+            MMRPeaks {
+                num_leaves: mmr.num_leaves + leaves.len(),
+                peaks,
+                root,
+            }
+        };
+
+        assert!(!children.is_empty(), "No hashes to prove");
+        let witness = H::hash_nodes(self, &children, &parents);
+
+        MMRGadget {
+            mmr: mmr.clone(),
+            paths: paths.to_vec(),
+            leaves: leaves.to_vec(),
+            next,
+            parents,
+            witness,
+        }
+    }
+}
+
+impl<B: Builder> MMRBuilder for B {}
+
+/// Adds the hashes bagging `peaks` into `root` to `children` and `parents`.
+fn bag_peaks<B: Builder, H: MerkleHasher<B>>(
+    builder: &mut B,
+    peaks: &[ArrayRegister<H::Word>],
+    root: ArrayRegister<H::Word>,
+    children: &mut Vec<(ArrayRegister<H::Word>, ArrayRegister<H::Word>)>,
+    parents: &mut Vec<ArrayRegister<H::Word>>,
+) {
+    let (last, rest) = peaks.split_last().expect("A range must have a peak");
+    let mut bag = *last;
+    for (i, peak) in rest.iter().rev().enumerate() {
+        let parent = if i + 1 == rest.len() {
+            root
+        } else {
+            builder.alloc_array_public::<H::Word>(H::DIGEST_WORDS)
+        };
+        children.push((bag, *peak));
+        parents.push(parent);
+        bag = parent;
+    }
+}
+
+impl<B: Builder, H: MerkleHasher<B>> MMRGadget<B, H> {
+    /// Writes the values of proving `proofs` against `mmr` and appending `leaves` to it, and
+    /// returns the range after the appends.
+    pub fn write(
+        &self,
+        mmr: &MerkleMountainRange<H>,
+        proofs: &[MMRProof<H::Digest>],
+        leaves: &[H::Digest],
+        writer: &mut impl AirWriter<Field = B::Field>,
+    ) -> MerkleMountainRange<H> {
+        assert_eq!(mmr.len(), self.mmr.num_leaves);
+        assert_eq!(proofs.len(), self.paths.len());
+        assert_eq!(leaves.len(), self.leaves.len());
+
+        let peaks = mmr.peaks();
+        for (register, peak) in self.mmr.peaks.iter().zip(peaks.iter()) {
+            writer.write_array(register, H::digest_words(peak));
+        }
+
+        // The children of every hash, in the order of `MMRBuilder::mmr_verify`.
+        let mut children = Vec::new();
+        for (path, proof) in self.paths.iter().zip(proofs.iter()) {
+            assert_eq!(path.mountain, proof.mountain);
+            path.path.write_proof::<B, H>(&proof.proof, writer);
+            children.extend(proof.proof.children::<H>());
+        }
+        children.extend(MerkleMountainRange::<H>::bag_children(&peaks));
+        let mut next = mmr.clone();
+        for (register, leaf) in self.leaves.iter().zip(leaves.iter()) {
+            writer.write_array(register, H::digest_words(leaf));
+            children.extend(next.push(leaf.clone()));
+        }
+        if !leaves.is_empty() {
+            children.extend(MerkleMountainRange::<H>::bag_children(&next.peaks()));
+        }
+
+        assert_eq!(children.len(), self.parents.len());
+        for (parent, (left, right)) in self.parents.iter().zip(children.iter()) {
+            writer.write_array(parent, H::digest_words(&H::hash(left, right)));
+        }
+        H::write_witness(&self.witness, &children, writer);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::{thread_rng, Rng};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::merkle::sha256::SHA256Hasher;
+    use crate::machine::tests::verifies;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MMRTest;
+
+    impl AirParameters for MMRTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1200;
+        const EXTENDED_COLUMNS: usize = 700;
+    }
+
+    /// Proves the leaves at `indices` of the range of `leaves` and appends `new_leaves`,
+    /// returning whether the proof verifies. If `tamper` is set, the new root is replaced by one
+    /// of the leaves.
+    fn prove_mmr(
+        leaves: &[[u32; 8]],
+        indices: &[usize],
+        new_leaves: &[[u32; 8]],
+        tamper: bool,
+    ) -> bool {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type H = SHA256Hasher;
+
+        let mmr = MerkleMountainRange::<H>::from_leaves(leaves);
+        let proofs = indices
+            .iter()
+            .map(|index| mmr.prove(*index))
+            .collect::<Vec<_>>();
+
+        let mut builder = BytesBuilder::<MMRTest>::new();
+        let mmr_register = builder.alloc_mmr::<H>(leaves.len());
+        let paths = proofs
+            .iter()
+            .map(|proof| builder.alloc_mmr_path::<H>(&mmr_register, proof.mountain))
+            .collect::<Vec<_>>();
+        let leaf_registers = new_leaves
+            .iter()
+            .map(|_| builder.alloc_array_public(8))
+            .collect::<Vec<_>>();
+        let gadget = builder.mmr_verify::<H>(&mmr_register, &paths, &leaf_registers);
+
+        let num_rows = (128 * gadget.parents.len()).next_power_of_two();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        let next = gadget.write(&mmr, &proofs, new_leaves, &mut writer);
+        let all_leaves = [leaves, new_leaves].concat();
+        assert_eq!(
+            next.root(),
+            MerkleMountainRange::<H>::from_leaves(&all_leaves).root()
+        );
+        if tamper {
+            let root = <H as MerkleHasher<BytesBuilder<MMRTest>>>::digest_words(&leaves[0]);
+            writer.write_array(&gadget.next.root, root);
+        }
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        verifies(&stark, &trace, &public)
+    }
+
+    #[test]
+    fn test_mmr() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut rng = thread_rng();
+        let leaves = (0..6).map(|_| rng.gen()).collect::<Vec<[u32; 8]>>();
+        let new_leaves = (0..3).map(|_| rng.gen()).collect::<Vec<[u32; 8]>>();
+        assert!(prove_mmr(&leaves, &[1, 5], &new_leaves, false));
+        assert!(!prove_mmr(&leaves, &[1, 5], &new_leaves, true));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::machine::merkle::{MerkleHash, MerkleProof};
+
+/// A Merkle mountain range over the hash `H`.
+///
+/// The leaves are split into perfect trees of decreasing sizes, the mountains, as given by the
+/// binary decomposition of the number of leaves. A new leaf is merged with the peaks of equal
+/// height as `H(peak, node)`. The root bags the peaks from right to left, `bag = H(bag, peak)`
+/// starting from the last peak.
+#[derive(Debug)]
+pub struct MerkleMountainRange<H: MerkleHash> {
+    leaves: Vec<H::Digest>,
+    /// The peaks with the heights of their mountains, largest first.
+    peaks: Vec<(H::Digest, usize)>,
+}
+
+/// An inclusion proof of a leaf in a Merkle mountain range.
+///
+/// `proof` is the path of the leaf within its mountain, whose root is `peaks[mountain]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MMRProof<D> {
+    pub mountain: usize,
+    pub proof: MerkleProof<D>,
+    pub peaks: Vec<D>,
+}
+
+impl<H: MerkleHash> Clone for MerkleMountainRange<H> {
+    fn clone(&self) -> Self {
+        Self {
+            leaves: self.leaves.clone(),
+            peaks: self.peaks.clone(),
+        }
+    }
+}
+
+impl<H: MerkleHash> Default for MerkleMountainRange<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: MerkleHash> MerkleMountainRange<H> {
+    pub fn new() -> Self {
+        Self {
+            leaves: Vec::new(),
+            peaks: Vec::new(),
+        }
+    }
+
+    pub fn from_leaves(leaves: &[H::Digest]) -> Self {
+        let mut mmr = Self::new();
+        for leaf in leaves.iter() {
+            mmr.push(leaf.clone());
+        }
+        mmr
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// The heights of the mountains of a range of `num_leaves` leaves, largest first.
+    pub fn heights(num_leaves: usize) -> Vec<usize> {
+        (0..usize::BITS as usize)
+            .rev()
+            .filter(|height| (num_leaves >> height) & 1 == 1)
+            .collect()
+    }
+
+    /// Appends `leaf` and returns the children of the hashes merging it with the peaks.
+    pub fn push(&mut self, leaf: H::Digest) -> Vec<(H::Digest, H::Digest)> {
+        self.leaves.push(leaf.clone());
+        let (mut node, mut height) = (leaf, 0);
+        let mut children = Vec::new();
+        while self.peaks.last().map(|(_, h)| *h) == Some(height) {
+            let (peak, _) = self.peaks.pop().unwrap();
+            let parent = H::hash(&peak, &node);
+            children.push((peak, node));
+            node = parent;
+            height += 1;
+        }
+        self.peaks.push((node, height));
+        children
+    }
+
+    pub fn peaks(&self) -> Vec<H::Digest> {
+        self.peaks.iter().map(|(peak, _)| peak.clone()).collect()
+    }
+
+    /// The children of the hashes bagging `peaks` into the root.
+    pub fn bag_children(peaks: &[H::Digest]) -> Vec<(H::Digest, H::Digest)> {
+        let (last, rest) = peaks.split_last().expect("A range must have a peak");
+        let mut bag = last.clone();
+        let mut children = Vec::new();
+        for peak in rest.iter().rev() {
+            let parent = H::hash(&bag, peak);
+            children.push((bag, peak.clone()));
+            bag = parent;
+        }
+        children
+    }
+
+    pub fn bag_peaks(peaks: &[H::Digest]) -> H::Digest {
+        match Self::bag_children(peaks).last() {
+            Some((bag, peak)) => H::hash(bag, peak),
+            None => peaks[0].clone(),
+        }
+    }
+
+    pub fn root(&self) -> H::Digest {
+        Self::bag_peaks(&self.peaks())
+    }
+
+    /// The inclusion proof of the leaf at `index`.
+    pub fn prove(&self, index: usize) -> MMRProof<H::Digest> {
+        assert!(index < self.len(), "Index out of range");
+        let mut offset = 0;
+        for (mountain, height) in Self::heights(self.len()).into_iter().enumerate() {
+            let size = 1 << height;
+            if index < offset + size {
+                return MMRProof {
+                    mountain,
+                    proof: H::prove(&self.leaves[offset..offset + size], index - offset),
+                    peaks: self.peaks(),
+                };
+            }
+            offset += size;
+        }
+        unreachable!()
+    }
+}
+
+impl<D: Clone + PartialEq> MMRProof<D> {
+    /// Checks the path against its peak and the peaks against `root`.
+    pub fn verify<H: MerkleHash<Digest = D>>(&self, root: &D) -> bool {
+        self.proof.compute_root::<H>() == self.peaks[self.mountain]
+            && MerkleMountainRange::<H>::bag_peaks(&self.peaks) == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::machine::merkle::sha256::SHA256Hasher;
+
+    #[test]
+    fn test_mmr_pure() {
+        type H = SHA256Hasher;
+
+        let mut rng = thread_rng();
+        let leaves = (0..11).map(|_| rng.gen()).collect::<Vec<[u32; 8]>>();
+        let mmr = MerkleMountainRange::<H>::from_leaves(&leaves);
+        assert_eq!(MerkleMountainRange::<H>::heights(11), [3, 1, 0]);
+
+        let peaks = [H::root(&leaves[..8]), H::root(&leaves[8..10]), leaves[10]];
+        assert_eq!(mmr.peaks(), peaks);
+        let bag = H::hash(&peaks[2], &peaks[1]);
+        let root = H::hash(&bag, &peaks[0]);
+        assert_eq!(mmr.root(), root);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = mmr.prove(index);
+            assert_eq!(proof.proof.leaf, *leaf);
+            assert!(proof.verify::<H>(&root));
+        }
+        let mut bad_proof = mmr.prove(9);
+        bad_proof.proof.index ^= 1;
+        assert!(!bad_proof.verify::<H>(&root));
+    }
+}
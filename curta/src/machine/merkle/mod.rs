@@ -0,0 +1,167 @@
+//! Binary Merkle trees over a pluggable two-to-one hash.
+//!
+//! A `MerkleHasher` proves a batch of node compressions in one call, so every gadget of this
+//! module collects all of its nodes first and hashes them together.
+
+pub mod blake2b;
+pub mod builder;
+pub mod cap;
+pub mod mmr;
+pub mod poseidon;
+pub mod sha256;
+
+use core::fmt::Debug;
+
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+
+/// A two-to-one hash of Merkle nodes.
+pub trait MerkleHash {
+    /// The value of a node.
+    type Digest: Clone + Debug + PartialEq;
+
+    /// Hashes two children into their parent.
+    fn hash(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Computes the root of the tree of `leaves`, see `MerkleBuilder::merkle_root`.
+    fn root(leaves: &[Self::Digest]) -> Self::Digest {
+        let mut nodes = leaves.to_vec();
+        for (left, right) in tree_layout(leaves.len()) {
+            let parent = Self::hash(&nodes[left], &nodes[right]);
+            nodes.push(parent);
+        }
+        nodes.pop().unwrap()
+    }
+
+    /// The authentication path of the leaf at `index` in the tree of `leaves`, whose number must
+    /// be a power of two.
+    fn prove(leaves: &[Self::Digest], index: usize) -> MerkleProof<Self::Digest> {
+        assert!(leaves.len().is_power_of_two(), "The tree must be complete");
+        let mut level = leaves.to_vec();
+        let mut position = index;
+        let mut siblings = Vec::new();
+        while level.len() > 1 {
+            siblings.push(level[position ^ 1].clone());
+            level = level
+                .chunks_exact(2)
+                .map(|pair| Self::hash(&pair[0], &pair[1]))
+                .collect();
+            position /= 2;
+        }
+        MerkleProof {
+            index,
+            leaf: leaves[index].clone(),
+            siblings,
+        }
+    }
+}
+
+/// An authentication path of a leaf, with the siblings ordered from the leaf to the root.
+///
+/// Bit `k` of `index` is `1` if the node at height `k` of the path is a right child.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof<D> {
+    pub index: usize,
+    pub leaf: D,
+    pub siblings: Vec<D>,
+}
+
+impl<D: Clone> MerkleProof<D> {
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// The bits of `index`, from the leaf to the root.
+    pub fn index_bits(&self) -> Vec<bool> {
+        (0..self.depth())
+            .map(|k| (self.index >> k) & 1 == 1)
+            .collect()
+    }
+
+    /// The children of every hash of the path, from the leaf to the root.
+    pub fn children<H: MerkleHash<Digest = D>>(&self) -> Vec<(D, D)> {
+        let mut node = self.leaf.clone();
+        let mut children = Vec::new();
+        for (sibling, bit) in self.siblings.iter().zip(self.index_bits()) {
+            let pair = if bit {
+                (sibling.clone(), node)
+            } else {
+                (node, sibling.clone())
+            };
+            node = H::hash(&pair.0, &pair.1);
+            children.push(pair);
+        }
+        children
+    }
+
+    /// Hashes the leaf up the path.
+    pub fn compute_root<H: MerkleHash<Digest = D>>(&self) -> D {
+        match self.children::<H>().last() {
+            Some((left, right)) => H::hash(left, right),
+            None => self.leaf.clone(),
+        }
+    }
+}
+
+/// A `MerkleHash` that can be proven by a builder of type `B`.
+///
+/// A digest is an array of `DIGEST_WORDS` registers of type `Word`.
+pub trait MerkleHasher<B: Builder>: MerkleHash {
+    /// The register type of a word of a digest.
+    type Word: Register;
+
+    /// The registers of a batch of compressions whose values are written by the prover.
+    type Witness: Clone + Debug;
+
+    /// The number of words of a digest.
+    const DIGEST_WORDS: usize;
+
+    /// The values of the words of `digest`.
+    fn digest_words(digest: &Self::Digest) -> Vec<<Self::Word as Register>::Value<B::Field>>;
+
+    /// Constrains each of `parents` to be the hash of the corresponding pair of `children`.
+    ///
+    /// All the registers must be public. The hashes may take the whole trace, so all the nodes
+    /// of a builder must be hashed in a single call.
+    fn hash_nodes(
+        builder: &mut B,
+        children: &[(ArrayRegister<Self::Word>, ArrayRegister<Self::Word>)],
+        parents: &[ArrayRegister<Self::Word>],
+    ) -> Self::Witness;
+
+    /// Writes the values of `witness`, given the values of the children of every hash.
+    fn write_witness(
+        witness: &Self::Witness,
+        children: &[(Self::Digest, Self::Digest)],
+        writer: &mut impl AirWriter<Field = B::Field>,
+    );
+}
+
+/// The hashes of a tree of `num_leaves` leaves, level by level from the leaves.
+///
+/// The nodes are indexed with the leaves first, followed by the parent of every hash in order.
+/// An odd node at the end of a level is promoted to the next level unchanged.
+pub(crate) fn tree_layout(num_leaves: usize) -> Vec<(usize, usize)> {
+    assert!(num_leaves > 1, "A tree must have at least two leaves");
+    let mut layout = Vec::new();
+    let mut level = (0..num_leaves).collect::<Vec<_>>();
+    let mut num_nodes = num_leaves;
+    while level.len() > 1 {
+        let mut next_level = Vec::new();
+        for pair in level.chunks(2) {
+            match pair {
+                [left, right] => {
+                    layout.push((*left, *right));
+                    next_level.push(num_nodes);
+                    num_nodes += 1;
+                }
+                [node] => next_level.push(*node),
+                _ => unreachable!(),
+            }
+        }
+        level = next_level;
+    }
+    layout
+}
@@ -0,0 +1,59 @@
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::hash_types::HashOut;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::plonk::config::Hasher;
+
+use super::{MerkleHash, MerkleHasher};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::machine::hash::poseidon::builder::PoseidonBuilder;
+use crate::machine::hash::poseidon::NUM_HASH_OUT_ELTS;
+
+/// Merkle nodes hashed with plonky2's `PoseidonHash::two_to_one`.
+#[derive(Debug, Clone, Copy)]
+pub struct PoseidonHasher;
+
+impl MerkleHash for PoseidonHasher {
+    type Digest = HashOut<GoldilocksField>;
+
+    fn hash(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        PoseidonHash::two_to_one(*left, *right)
+    }
+}
+
+impl<B: Builder<Field = GoldilocksField>> MerkleHasher<B> for PoseidonHasher {
+    type Word = ElementRegister;
+    type Witness = ();
+
+    const DIGEST_WORDS: usize = NUM_HASH_OUT_ELTS;
+
+    fn digest_words(digest: &Self::Digest) -> Vec<GoldilocksField> {
+        digest.elements.to_vec()
+    }
+
+    /// Every compression is computed within a single row and constrained to be equal to its
+    /// parent, so the number of columns grows with the number of nodes.
+    fn hash_nodes(
+        builder: &mut B,
+        children: &[(
+            ArrayRegister<ElementRegister>,
+            ArrayRegister<ElementRegister>,
+        )],
+        parents: &[ArrayRegister<ElementRegister>],
+    ) -> Self::Witness {
+        assert_eq!(children.len(), parents.len());
+        for ((left, right), parent) in children.iter().zip(parents.iter()) {
+            let output = builder.poseidon_two_to_one(left, right);
+            builder.assert_expressions_equal(output.expr(), parent.expr());
+        }
+    }
+
+    fn write_witness(
+        _witness: &Self::Witness,
+        _children: &[(Self::Digest, Self::Digest)],
+        _writer: &mut impl AirWriter<Field = GoldilocksField>,
+    ) {
+    }
+}
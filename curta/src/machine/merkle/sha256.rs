@@ -0,0 +1,129 @@
+use super::{MerkleHash, MerkleHasher};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+
+/// The padding block of a 64-byte message.
+const PADDING_BLOCK: [u32; 16] = [0x80000000, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 512];
+
+/// Merkle nodes hashed as `SHA256(left || right)`.
+#[derive(Debug, Clone, Copy)]
+pub struct SHA256Hasher;
+
+impl MerkleHash for SHA256Hasher {
+    type Digest = [u32; 8];
+
+    fn hash(left: &Self::Digest, right: &Self::Digest) -> Self::Digest {
+        let mut block = [0u32; 16];
+        block[..8].copy_from_slice(left);
+        block[8..].copy_from_slice(right);
+        [block, PADDING_BLOCK]
+            .iter()
+            .fold(SHA256::INITIAL_HASH, |state, chunk| {
+                SHA256::process(state, &SHA256::pre_process(chunk))
+            })
+    }
+}
+
+impl<L: AirParameters> MerkleHasher<BytesBuilder<L>> for SHA256Hasher
+where
+    L::Instruction: UintInstructions,
+{
+    type Word = U32Register;
+    type Witness = ();
+
+    const DIGEST_WORDS: usize = 8;
+
+    fn digest_words(digest: &Self::Digest) -> Vec<[L::Field; 4]> {
+        digest
+            .iter()
+            .map(|word| u32_to_le_field_bytes(*word))
+            .collect()
+    }
+
+    /// Hashes every node as a message of two chunks, `left || right` and a constant padding
+    /// block. The first chunks are set from the children before they are stored, and the
+    /// parents are used as the digests of the batch. The trace must have at least
+    /// `128 * children.len()` rows.
+    fn hash_nodes(
+        builder: &mut BytesBuilder<L>,
+        children: &[(ArrayRegister<U32Register>, ArrayRegister<U32Register>)],
+        parents: &[ArrayRegister<U32Register>],
+    ) -> Self::Witness {
+        assert_eq!(children.len(), parents.len());
+        let padding = builder
+            .constant_array::<U32Register>(&PADDING_BLOCK.map(u32_to_le_field_bytes::<L::Field>));
+
+        let mut chunks = Vec::new();
+        for (left, right) in children.iter() {
+            let block = builder.alloc_array_public_unchecked::<U32Register>(16);
+            for (word, child_word) in block.iter().zip(left.iter().chain(right.iter())) {
+                builder.set_to_expression(&word, child_word.expr());
+            }
+            chunks.push(block);
+            chunks.push(padding);
+        }
+
+        let end_bits = builder.constant_array::<BitRegister>(
+            &(0..chunks.len())
+                .map(|i| L::Field::from_canonical_usize(i % 2))
+                .collect::<Vec<_>>(),
+        );
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &(0..children.len())
+                .map(|i| L::Field::from_canonical_usize(2 * i + 1))
+                .collect::<Vec<_>>(),
+        );
+        let digests = parents
+            .iter()
+            .map(|parent| SHA256DigestRegister::from_array(*parent))
+            .collect::<Vec<_>>();
+        SHA256::sha_with_digests(
+            builder,
+            &chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &digests,
+        );
+    }
+
+    fn write_witness(
+        _witness: &Self::Witness,
+        _children: &[(Self::Digest, Self::Digest)],
+        _writer: &mut impl AirWriter<Field = L::Field>,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hasher() {
+        let words = (0..16u8)
+            .map(|i| u32::from_be_bytes(core::array::from_fn(|j| 4 * i + j as u8)))
+            .collect::<Vec<_>>();
+        let (left, right) = (
+            words[..8].try_into().unwrap(),
+            words[8..].try_into().unwrap(),
+        );
+        assert_eq!(
+            SHA256Hasher::hash(&left, &right),
+            SHA256::decode("fdeab9acf3710362bd2658cdc9a29e8f9c757fcf9811603a8c447cd1d9151108")
+        );
+    }
+}
@@ -3,4 +3,130 @@ pub mod bytes;
 pub mod ec;
 pub mod emulated;
 pub mod hash;
+pub mod merkle;
+pub mod rsa;
 pub mod stark;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use anyhow::Result;
+    use plonky2::field::extension::Extendable;
+    use plonky2::hash::hash_types::RichField;
+    use plonky2::util::timing::TimingTree;
+
+    use super::bytes::proof::ByteStarkProof;
+    use super::bytes::stark::ByteStark;
+    use super::emulated::proof::EmulatedStarkProof;
+    use super::emulated::stark::EmulatedStark;
+    use crate::chip::{AirParameters, Chip};
+    use crate::plonky2::stark::config::CurtaConfig;
+    use crate::plonky2::Plonky2Air;
+    use crate::trace::AirTrace;
+
+    /// A stark of a machine, which proves a trace and verifies the proof.
+    pub(crate) trait MachineStark<F> {
+        type Proof;
+
+        fn prove(
+            &self,
+            trace: &AirTrace<F>,
+            public: &[F],
+            timing: &mut TimingTree,
+        ) -> Result<Self::Proof>;
+
+        fn verify(&self, proof: Self::Proof, public: &[F]) -> Result<()>;
+    }
+
+    impl<L: AirParameters, C, const D: usize> MachineStark<L::Field> for ByteStark<L, C, D>
+    where
+        L::Field: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = L::Field, FE = <L::Field as Extendable<D>>::Extension>,
+        Chip<L>: Plonky2Air<L::Field, D>,
+    {
+        type Proof = ByteStarkProof<L::Field, C, D>;
+
+        fn prove(
+            &self,
+            trace: &AirTrace<L::Field>,
+            public: &[L::Field],
+            timing: &mut TimingTree,
+        ) -> Result<Self::Proof> {
+            ByteStark::prove(self, trace, public, timing)
+        }
+
+        fn verify(&self, proof: Self::Proof, public: &[L::Field]) -> Result<()> {
+            ByteStark::verify(self, proof, public)
+        }
+    }
+
+    impl<L: AirParameters, C, const D: usize> MachineStark<L::Field> for EmulatedStark<L, C, D>
+    where
+        L::Field: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = L::Field, FE = <L::Field as Extendable<D>>::Extension>,
+        Chip<L>: Plonky2Air<L::Field, D>,
+    {
+        type Proof = EmulatedStarkProof<L::Field, C, D>;
+
+        fn prove(
+            &self,
+            trace: &AirTrace<L::Field>,
+            public: &[L::Field],
+            timing: &mut TimingTree,
+        ) -> Result<Self::Proof> {
+            EmulatedStark::prove(self, trace, public, timing)
+        }
+
+        fn verify(&self, proof: Self::Proof, public: &[L::Field]) -> Result<()> {
+            EmulatedStark::verify(self, proof, public)
+        }
+    }
+
+    fn prove_and_verify<F, S: MachineStark<F>>(
+        stark: &S,
+        trace: &AirTrace<F>,
+        public: &[F],
+    ) -> Result<()> {
+        let mut timing = TimingTree::default();
+        let proof = stark.prove(trace, public, &mut timing)?;
+        stark.verify(proof, public)
+    }
+
+    /// Whether the proof of `trace` and `public` verifies.
+    ///
+    /// The prover panics on a lookup of a value outside of its table and the verifier on a failing
+    /// global constraint, so a panic counts as a rejection.
+    pub(crate) fn verifies<F, S: MachineStark<F>>(
+        stark: &S,
+        trace: &AirTrace<F>,
+        public: &[F],
+    ) -> bool {
+        let result = catch_unwind(AssertUnwindSafe(|| prove_and_verify(stark, trace, public)));
+        matches!(result, Ok(Ok(())))
+    }
+
+    /// Whether the proof of the trace and public values returned by `write` verifies.
+    ///
+    /// As in `verifies`, a panic counts as a rejection, including one of the witness generation on
+    /// inputs with no valid trace.
+    pub(crate) fn verifies_with<F, S: MachineStark<F>>(
+        stark: &S,
+        write: impl FnOnce() -> (AirTrace<F>, Vec<F>),
+    ) -> bool {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let (trace, public) = write();
+            prove_and_verify(stark, &trace, &public)
+        }));
+        matches!(result, Ok(Ok(())))
+    }
+
+    /// Asserts that the proof of `trace` and `public` does not verify.
+    pub(crate) fn assert_rejected<F, S: MachineStark<F>>(
+        stark: &S,
+        trace: &AirTrace<F>,
+        public: &[F],
+    ) {
+        assert!(!verifies(stark, trace, public), "the proof verifies");
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::air::AirConstraint;
+use crate::chip::field::modexp::ModMulInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::uint::bytes::count::ByteCountInstruction;
+use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
+use crate::chip::uint::bytes::lookup_table::{ByteInstructionSet, ByteInstructions};
+use crate::chip::uint::bytes::operations::instruction::ByteOperationInstruction;
+use crate::chip::uint::bytes::operations::value::ByteOperationDigestConstraint;
+use crate::chip::uint::operations::add::ByteArrayAdd;
+use crate::chip::uint::operations::instruction::{UintInstruction, UintInstructions};
+use crate::chip::uint::operations::mul::ByteArrayMul;
+use crate::chip::uint::operations::native::U32NativeInstruction;
+use crate::chip::uint::operations::sub::ByteArraySub;
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions of an AIR verifying RSA signatures, with the modular multiplications of the
+/// exponentiation and the `u32` operations of SHA-256.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum RSAInstruction<P: FieldParameters> {
+    ModMul(ModMulInstruction<P>),
+    Uint(UintInstruction),
+}
+
+impl<P: FieldParameters> ByteInstructions for RSAInstruction<P> {}
+
+impl<P: FieldParameters> UintInstructions for RSAInstruction<P> {}
+
+impl<P: FieldParameters> From<ModMulInstruction<P>> for RSAInstruction<P> {
+    fn from(i: ModMulInstruction<P>) -> Self {
+        Self::ModMul(i)
+    }
+}
+
+impl<P: FieldParameters> From<UintInstruction> for RSAInstruction<P> {
+    fn from(i: UintInstruction) -> Self {
+        Self::Uint(i)
+    }
+}
+
+impl<P: FieldParameters> From<ByteInstructionSet> for RSAInstruction<P> {
+    fn from(i: ByteInstructionSet) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteOperationInstruction> for RSAInstruction<P> {
+    fn from(i: ByteOperationInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteDecodeInstruction> for RSAInstruction<P> {
+    fn from(i: ByteDecodeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteOperationDigestConstraint> for RSAInstruction<P> {
+    fn from(i: ByteOperationDigestConstraint) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteArrayAdd<4>> for RSAInstruction<P> {
+    fn from(i: ByteArrayAdd<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteArraySub<4>> for RSAInstruction<P> {
+    fn from(i: ByteArraySub<4>) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteArrayMul> for RSAInstruction<P> {
+    fn from(i: ByteArrayMul) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<U32NativeInstruction> for RSAInstruction<P> {
+    fn from(i: U32NativeInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<P: FieldParameters> From<ByteCountInstruction> for RSAInstruction<P> {
+    fn from(i: ByteCountInstruction) -> Self {
+        Self::Uint(i.into())
+    }
+}
+
+impl<AP: PolynomialParser, P: FieldParameters> AirConstraint<AP> for RSAInstruction<P> {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            RSAInstruction::ModMul(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+            RSAInstruction::Uint(instruction) => AirConstraint::<AP>::eval(instruction, parser),
+        }
+    }
+}
+
+impl<F: PrimeField64, P: FieldParameters> Instruction<F> for RSAInstruction<P> {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            RSAInstruction::ModMul(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            RSAInstruction::Uint(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            RSAInstruction::ModMul(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            RSAInstruction::Uint(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! RSA signature verification with the PKCS#1 v1.5 encoding and SHA-256, as used by DKIM and by
+//! TLS certificates.
+//!
+//! A signature `s` on a message `m` under the public key `(n, e)` is valid if `s < n` and
+//!
+//! s^e mod n = EM
+//!
+//! where `EM` is the encoding `0x00 || 0x01 || 0xff..ff || 0x00 || DigestInfo || SHA256(m)` of the
+//! message on as many bytes as `n`. The gadget checks `s < n` with a multiplication by one,
+//! computes `s^e mod n` with the fixed exponent `e = 65537` and compares the result with the
+//! encoding, whose bytes above the digest are constant.
+//!
+//! The message is a public input given by its padded SHA-256 chunks, and its digest is computed
+//! by the SHA-256 AIR in the same trace as the exponentiation. The low limbs of `s^e mod n` are
+//! then constrained to the bytes of the digest.
+//!
+//! RSA-PSS signatures are verified by `BytesBuilder::rsa_pss_verify` together with the encoding
+//! checks of `pss`.
+//!
+//! Reference: https://www.rfc-editor.org/rfc/rfc8017#section-8.2.2
+
+pub mod instruction;
+pub mod pss;
+
+use itertools::Itertools;
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use self::pss::EMSAPSSGadget;
+use crate::chip::field::modexp::ModMulInstruction;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The public exponent of the keys, used by nearly all RSA keys in practice.
+pub const PUBLIC_EXPONENT: u32 = 65537;
+
+/// The DER encoding of the `DigestInfo` of SHA-256 without the digest, see RFC 8017, section 9.2.
+pub const SHA256_DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The number of 16-bit limbs of a SHA-256 digest.
+const DIGEST_LIMBS: usize = 16;
+
+/// The limb layout of 2048-bit keys for the instructions of `modexp`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RSA2048;
+
+impl FieldParameters for RSA2048 {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 128;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+    const WITNESS_OFFSET: usize = 1usize << 24;
+}
+
+/// The limb layout of 4096-bit keys for the instructions of `modexp`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RSA4096;
+
+impl FieldParameters for RSA4096 {
+    const NB_BITS_PER_LIMB: usize = 16;
+    const NB_LIMBS: usize = 256;
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+    const MODULUS: [u16; MAX_NB_LIMBS] = [0; MAX_NB_LIMBS];
+    const WITNESS_OFFSET: usize = 1usize << 25;
+}
+
+/// The public registers of an RSA signature verification.
+#[derive(Debug, Clone)]
+pub struct RSAGadget<P: FieldParameters> {
+    pub modulus: FieldRegister<P>,
+    pub signature: FieldRegister<P>,
+    /// The chunks of the message, padded with `SHA256::pad`.
+    pub padded_chunks: Vec<ArrayRegister<U32Register>>,
+    /// The SHA-256 digest of the message, computed by the SHA-256 AIR.
+    pub msg_hash: SHA256DigestRegister,
+}
+
+/// The values of an RSA signature verification.
+#[derive(Debug, Clone)]
+pub struct RSAWitness<P: FieldParameters> {
+    pub modulus: BigUint,
+    pub signature: BigUint,
+    pub msg: Vec<u8>,
+    pub msg_hash: [u8; 32],
+    _marker: core::marker::PhantomData<P>,
+}
+
+/// The public registers of an RSA-PSS signature verification.
+#[derive(Debug, Clone)]
+pub struct RSAPSSGadget<P: FieldParameters> {
+    pub modulus: FieldRegister<P>,
+    pub signature: FieldRegister<P>,
+    /// The encoding check of the encoded message `signature^65537 mod n`.
+    pub encoding: EMSAPSSGadget,
+}
+
+pub type RSA2048Witness = RSAWitness<RSA2048>;
+
+pub type RSA4096Witness = RSAWitness<RSA4096>;
+
+pub trait RSABuilder: Builder {
+    /// The verification primitive `RSAVP1`, which computes `signature^65537 mod n` and checks
+    /// that `signature < n`.
+    fn rsavp1<P: FieldParameters>(
+        &mut self,
+        modulus: &FieldRegister<P>,
+        signature: &FieldRegister<P>,
+    ) -> FieldRegister<P>
+    where
+        Self::Instruction: From<ModMulInstruction<P>>,
+    {
+        // The reduced product `signature * 1` is equal to `signature` only if `signature < n`.
+        let one = self.api().fp_one();
+        let reduced_signature = self.api().mod_mul(signature, &one, modulus);
+        self.assert_equal(&reduced_signature, signature);
+
+        let exponent = BigUint::from(PUBLIC_EXPONENT);
+        self.api().mod_pow(signature, &exponent, modulus)
+    }
+}
+
+impl<B: Builder> RSABuilder for B {}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Verifies the PKCS#1 v1.5 signature `signature` of a message under the public key
+    /// `(modulus, 65537)`, for keys of `2 * P::NB_LIMBS` bytes.
+    ///
+    /// The message is given by `padded_chunks`, its chunks padded with `SHA256::pad`, and is
+    /// hashed by the SHA-256 AIR in a trace of `num_rows` rows, see `BytesBuilder::sha256`. All
+    /// the registers must be public, and their values are written by `RSAGadget::write` before
+    /// the global instructions.
+    pub fn rsa_pkcs1_verify<P: FieldParameters>(
+        &mut self,
+        modulus: &FieldRegister<P>,
+        signature: &FieldRegister<P>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        num_rows: usize,
+    ) -> RSAGadget<P>
+    where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        assert!(
+            !modulus.is_trace()
+                && !signature.is_trace()
+                && padded_chunks.iter().all(|chunk| !chunk.is_trace()),
+            "Inputs must be public"
+        );
+
+        let msg_hash = self.sha256(&[padded_chunks.to_vec()], num_rows)[0];
+        let encoded_msg = self.rsavp1(modulus, signature);
+
+        // The low limbs hold the digest, and the other limbs the constant padding. The digest
+        // words are big-endian, so byte `k` of the digest is byte `3 - k % 4` of word `k / 4`.
+        let digest_byte = |k: usize| msg_hash.get(k / 4).to_le_bytes().get(3 - k % 4);
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*encoded_msg.register());
+        let padding = to_u16_le_limbs_polynomial::<L::Field, P>(&pkcs1_sha256_encoding(
+            &[0u8; 32],
+            2 * P::NB_LIMBS,
+        ));
+        for (i, limb) in limbs.iter().enumerate() {
+            if i < DIGEST_LIMBS {
+                let low = digest_byte(31 - 2 * i);
+                let high = digest_byte(30 - 2 * i);
+                self.assert_expression_zero(
+                    limb.expr() - low.expr() - high.expr() * L::Field::from_canonical_u32(1 << 8),
+                );
+            } else {
+                self.assert_expression_zero(limb.expr() - padding.coefficients()[i]);
+            }
+        }
+
+        RSAGadget {
+            modulus: *modulus,
+            signature: *signature,
+            padded_chunks: padded_chunks.to_vec(),
+            msg_hash,
+        }
+    }
+
+    /// Verifies a PKCS#1 v1.5 signature under a 2048-bit key.
+    pub fn rsa2048_pkcs1_verify(
+        &mut self,
+        modulus: &FieldRegister<RSA2048>,
+        signature: &FieldRegister<RSA2048>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        num_rows: usize,
+    ) -> RSAGadget<RSA2048>
+    where
+        L::Instruction: From<ModMulInstruction<RSA2048>>,
+    {
+        self.rsa_pkcs1_verify(modulus, signature, padded_chunks, num_rows)
+    }
+
+    /// Verifies a PKCS#1 v1.5 signature under a 4096-bit key.
+    pub fn rsa4096_pkcs1_verify(
+        &mut self,
+        modulus: &FieldRegister<RSA4096>,
+        signature: &FieldRegister<RSA4096>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        num_rows: usize,
+    ) -> RSAGadget<RSA4096>
+    where
+        L::Instruction: From<ModMulInstruction<RSA4096>>,
+    {
+        self.rsa_pkcs1_verify(modulus, signature, padded_chunks, num_rows)
+    }
+}
+
+/// The encoding `EMSA-PKCS1-v1_5` of a SHA-256 digest on `nb_bytes` bytes, as an integer.
+pub fn pkcs1_sha256_encoding(digest: &[u8; 32], nb_bytes: usize) -> BigUint {
+    let padding_len = nb_bytes - 3 - SHA256_DIGEST_INFO_PREFIX.len() - digest.len();
+    let bytes = [0x00, 0x01]
+        .into_iter()
+        .chain(core::iter::repeat(0xff).take(padding_len))
+        .chain([0x00])
+        .chain(SHA256_DIGEST_INFO_PREFIX)
+        .chain(digest.iter().copied())
+        .collect::<Vec<_>>();
+    BigUint::from_bytes_be(&bytes)
+}
+
+impl<P: FieldParameters> RSAGadget<P> {
+    /// Writes the public key, the signature, the chunks of the message and its digest.
+    pub fn write<F: Field>(&self, witness: &RSAWitness<P>, writer: &mut impl AirWriter<Field = F>) {
+        for (register, value) in [self.modulus, self.signature]
+            .iter()
+            .zip_eq([&witness.modulus, &witness.signature])
+        {
+            writer.write(register, &to_u16_le_limbs_polynomial::<F, P>(value));
+        }
+        let padded_msg = SHA256::pad(&witness.msg);
+        for (chunk, values) in self
+            .padded_chunks
+            .iter()
+            .zip_eq(padded_msg.chunks_exact(16))
+        {
+            writer.write_array(
+                chunk,
+                values.iter().map(|word| u32_to_le_field_bytes(*word)),
+            );
+        }
+        let msg_hash: ArrayRegister<U32Register> = self.msg_hash.into();
+        writer.write_array(
+            &msg_hash,
+            SHA256::digest(&witness.msg).map(u32_to_le_field_bytes),
+        );
+    }
+}
+
+impl<P: FieldParameters> RSAWitness<P> {
+    /// Computes the witness of the signature `signature` of `msg` under the public key
+    /// `(modulus, 65537)`, where the message is hashed with SHA-256.
+    pub fn new(modulus: BigUint, msg: &[u8], signature: BigUint) -> Self {
+        Self {
+            modulus,
+            signature,
+            msg: msg.to_vec(),
+            msg_hash: SHA256::digest_bytes(msg),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Checks the signature outside of the AIR.
+    pub fn verify(&self) -> bool {
+        if self.signature >= self.modulus {
+            return false;
+        }
+        let encoded_msg = pkcs1_sha256_encoding(&self.msg_hash, 2 * P::NB_LIMBS);
+        let exponent = BigUint::from(PUBLIC_EXPONENT);
+        self.signature.modpow(&exponent, &self.modulus) == encoded_msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+
+    use num::One;
+
+    use super::instruction::RSAInstruction;
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub(super) struct RSA2048Test;
+
+    impl AirParameters for RSA2048Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = RSAInstruction<RSA2048>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 0;
+        const NUM_FREE_COLUMNS: usize = 700;
+        const EXTENDED_COLUMNS: usize = 500;
+    }
+
+    /// The modulus of a 2048-bit test key, whose public exponent is 65537.
+    pub(super) const RSA2048_MODULUS: &str = "d0c4d768ec530307d04ecc154fdb71c2904d714f79a5eeaedf47941929c6c67b899305784d757117f30c509266be42e381f3e492de1466d1d4c991904a6bbb3f77d36100c15e98b31af9887271e72e9d516551420408bb8749a2303780c205c854ba7b2a5048fde2410c4c53f37feff5f13595e31acf1ee60101aef73285ca0e8d96099bc379ff312a9ae70aa139d96f9530d5dc54504f1c862b64732ebd40aa842df723c26c5d956f9092c457aad73030632865727855dbf5b588dd9fbc5e61bbc0f6355373176eca9968d52be581f4c3901be15197641b52a90367af6d395aa02e9b0ecb6a709930eb4ae649abdffe7cc33c9479d174899c82bae14fe79865";
+
+    /// The message of the test signatures.
+    pub(super) const MSG: &str = "The quick brown fox jumps over the lazy dog";
+
+    /// The signature of `MSG` under the test key with `openssl dgst -sha256 -sign`.
+    const PKCS1_SIGNATURE: &str = "8a140c4a09a107873776cf1481b78259da9c726143eb3aa161a276f109cf850647e2ac0bb156d1320bdbd5bea93f5052188550f274ab778d4308afdd2f96c3b1828cbef34e7f7dbc2a4b5f690aa05d27286cb781d00cc8490b4033c81af22a333503b44d92c0c3231c68405833c7b24c4206656a68aa91ee544418b44d1095f0a00fb6fd5946f62ab8bfbf1ad3e197815e648faa903a41d5e5a82a19e26852fa864cfef08a64d3cb0e6540e2fcc070417d46cbc0efb5bcb652ff61115688c92facc7fc17d9f202c93c04ea55ae50592aaa57a03e9a6e8358f2f605f7bc9a1b34bf32083d6defd5b11b7804074c07c40b33bc2cb34c62dd113a028156a6f8658f";
+
+    pub(super) fn hex_int(value: &str) -> BigUint {
+        BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+    }
+
+    fn witness(msg: Option<&[u8]>) -> RSA2048Witness {
+        let msg = msg.unwrap_or(MSG.as_bytes());
+        RSAWitness::new(hex_int(RSA2048_MODULUS), msg, hex_int(PKCS1_SIGNATURE))
+    }
+
+    #[test]
+    fn test_rsa_pkcs1_verify_pure() {
+        assert!(witness(None).verify());
+        assert!(!witness(Some(b"tampered".as_slice())).verify());
+
+        let mut unreduced = witness(None);
+        unreduced.signature += &unreduced.modulus;
+        assert!(!unreduced.verify());
+
+        let mut tampered = witness(None);
+        tampered.signature += BigUint::one();
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn test_rsa2048_pkcs1_verify() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = RSA2048Test;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_rsa2048_pkcs1_verify", log::Level::Debug);
+
+        let witness = witness(None);
+        let num_rows = 256;
+
+        let mut builder = BytesBuilder::<L>::new();
+        let modulus = builder.alloc_public::<FieldRegister<RSA2048>>();
+        let signature = builder.alloc_public::<FieldRegister<RSA2048>>();
+        let padded_chunks = (0..SHA256::pad(&witness.msg).len() / 16)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let gadget = builder.rsa2048_pkcs1_verify(&modulus, &signature, &padded_chunks, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(&witness, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
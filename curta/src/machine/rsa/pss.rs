@@ -0,0 +1,576 @@
+//! The encoding EMSA-PSS of RSA-PSS signatures with SHA-256 and MGF1-SHA256.
+//!
+//! The encoded message of a signature on a message with the digest `mHash` is
+//!
+//! EM = maskedDB || H || 0xbc
+//!
+//! where `H = SHA256(0x00 * 8 || mHash || salt)`, `DB = 0x00..00 || 0x01 || salt` and `maskedDB`
+//! is `DB` XORed with the mask `MGF1(H)`, whose blocks are the digests `SHA256(H || counter)` of
+//! the seed `H` followed by a 32-bit big-endian counter. The top bit of `EM` is always zero, as
+//! the moduli have a whole number of bytes.
+//!
+//! `BytesBuilder::rsa_pss_verify` recovers `EM` from the signature with the modular
+//! instructions and decomposes it into bytes, on which `BytesBuilder::emsa_pss_sha256_verify`
+//! checks the encoding in the same AIR. The digest `mHash` of the message, the blocks of the mask
+//! and the hash `H` are all computed by the SHA-256 AIR.
+//!
+//! The salt has the length of the digest, 32 bytes, which is the default of most implementations.
+//!
+//! Reference: https://www.rfc-editor.org/rfc/rfc8017#section-9.1
+
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use num::BigUint;
+
+use super::{RSABuilder, RSAPSSGadget, PUBLIC_EXPONENT};
+use crate::chip::field::modexp::ModMulInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::{SHAPure, SHAir};
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+use crate::polynomial::to_u16_le_limbs_polynomial;
+
+/// The number of bytes of a SHA-256 digest, which is also the length of the salt.
+const HASH_LEN: usize = 32;
+
+/// The last byte of an encoded message.
+const TRAILER: u8 = 0xbc;
+
+/// The public registers of an EMSA-PSS encoding check.
+#[derive(Debug, Clone)]
+pub struct EMSAPSSGadget {
+    /// The bytes of the encoded message `EM`, from the most significant.
+    pub encoded_msg: ArrayRegister<ByteRegister>,
+    /// The chunks of the message, padded with `SHA256::pad`.
+    pub padded_chunks: Vec<ArrayRegister<U32Register>>,
+    /// The SHA-256 digest `mHash` of the message.
+    pub msg_hash: SHA256DigestRegister,
+    /// The blocks `SHA256(H || counter)` of the mask.
+    mask_digests: Vec<SHA256DigestRegister>,
+    /// The digest `SHA256(M')`, which must be equal to `H`.
+    hash_digest: SHA256DigestRegister,
+}
+
+/// The values of an RSA-PSS signature verification.
+#[derive(Debug, Clone)]
+pub struct RSAPSSWitness<P: FieldParameters> {
+    pub modulus: BigUint,
+    pub signature: BigUint,
+    pub msg: Vec<u8>,
+    pub msg_hash: [u8; 32],
+    _marker: PhantomData<P>,
+}
+
+impl<L: AirParameters> BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    /// Verifies the RSA-PSS signature `signature` of a message under the public key
+    /// `(modulus, 65537)`, for keys of `2 * P::NB_LIMBS` bytes.
+    ///
+    /// The encoded message `signature^65537 mod n` is decomposed into bytes, whose encoding is
+    /// checked by `emsa_pss_sha256_verify` for the message given by `padded_chunks`. All the
+    /// registers must be public, and their values are written by `RSAPSSGadget::write` before the
+    /// global instructions.
+    pub fn rsa_pss_verify<P: FieldParameters>(
+        &mut self,
+        modulus: &FieldRegister<P>,
+        signature: &FieldRegister<P>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        num_rows: usize,
+    ) -> RSAPSSGadget<P>
+    where
+        L::Instruction: From<ModMulInstruction<P>>,
+    {
+        assert!(
+            !modulus.is_trace() && !signature.is_trace(),
+            "Inputs must be public"
+        );
+        let value = self.rsavp1(modulus, signature);
+
+        // Limb `i` of the value is made of the bytes `2 * i` and `2 * i + 1` from the least
+        // significant one.
+        let em_len = 2 * P::NB_LIMBS;
+        let encoded_msg = self.alloc_array_public::<ByteRegister>(em_len);
+        let limbs = ArrayRegister::<U16Register>::from_register_unsafe(*value.register());
+        for (i, limb) in limbs.iter().enumerate() {
+            let low = encoded_msg.get(em_len - 1 - 2 * i);
+            let high = encoded_msg.get(em_len - 2 - 2 * i);
+            self.assert_expression_zero(
+                limb.expr() - low.expr() - high.expr() * L::Field::from_canonical_u32(1 << 8),
+            );
+        }
+        let encoding = self.emsa_pss_sha256_verify(&encoded_msg, padded_chunks, num_rows);
+
+        RSAPSSGadget {
+            modulus: *modulus,
+            signature: *signature,
+            encoding,
+        }
+    }
+
+    /// Checks that `encoded_msg` is an EMSA-PSS encoding with a salt of 32 bytes of the message
+    /// given by `padded_chunks`, its chunks padded with `SHA256::pad`.
+    ///
+    /// All the registers must be public, and their values are written by `EMSAPSSGadget::write`
+    /// before the global instructions. The message, the `(encoded_msg.len() - 33) / 32` blocks of
+    /// the mask, rounded up, and the two blocks of `M'` are hashed by the SHA-256 AIR in a trace of
+    /// `num_rows` rows, see `BytesBuilder::sha256`. This is 1024 rows for a message of one chunk
+    /// and a 2048-bit key.
+    pub fn emsa_pss_sha256_verify(
+        &mut self,
+        encoded_msg: &ArrayRegister<ByteRegister>,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        num_rows: usize,
+    ) -> EMSAPSSGadget {
+        let em_len = encoded_msg.len();
+        assert!(em_len >= 2 * HASH_LEN + 2, "Encoded message is too short");
+        assert!(
+            padded_chunks.iter().all(|chunk| !chunk.is_trace()),
+            "Inputs must be public"
+        );
+        let db_len = em_len - HASH_LEN - 1;
+        let ps_len = db_len - HASH_LEN - 1;
+        let num_mask_blocks = db_len.div_ceil(HASH_LEN);
+        let h = encoded_msg.get_subarray(db_len..em_len - 1);
+
+        let trailer = encoded_msg.get(em_len - 1);
+        self.assert_expression_zero(trailer.expr() - L::Field::from_canonical_u8(TRAILER));
+
+        // The digests are public values written by the prover, so the digest of the message and
+        // the mask can be used by the instructions computing the chunks of `M'`.
+        let msg_hash = self.alloc_public::<SHA256DigestRegister>();
+        let mask_digests = (0..num_mask_blocks)
+            .map(|_| self.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        let hash_digest = self.alloc_public::<SHA256DigestRegister>();
+
+        // The chunks of the message come first, followed by the chunks of MGF1, which are
+        // `H || counter` padded as a message of 36 bytes.
+        let mgf1_padding = SHA256::pad(&[0u8; HASH_LEN + 4]);
+        let mut chunks = padded_chunks.to_vec();
+        for counter in 0..num_mask_blocks {
+            let chunk = self.alloc_array_public_unchecked::<U32Register>(16);
+            for j in 0..8 {
+                self.pss_set_be_bytes(&chunk.get(j), &h.get_subarray(4 * j..4 * j + 4));
+            }
+            let mut tail = mgf1_padding[8..].to_vec();
+            tail[0] = counter as u32;
+            self.pss_set_constant_words(&chunk.get_subarray(8..16), &tail);
+            chunks.push(chunk);
+        }
+
+        // Unmask the data block. All its bytes are known except for the salt, and the top bit of
+        // `EM` must be zero.
+        let mask_byte = |i: usize| {
+            mask_digests[i / HASH_LEN]
+                .get(i % HASH_LEN / 4)
+                .to_le_bytes()
+                .get(3 - i % 4)
+        };
+        let db_first =
+            self.pss_byte_operation(ByteOperation::Xor, &encoded_msg.get(0), &mask_byte(0));
+        let low_bits = self.constant::<ByteRegister>(&L::Field::from_canonical_u8(0x7f));
+        let top_bit = self.constant::<ByteRegister>(&L::Field::from_canonical_u8(0x80));
+        let db_first_low = self.pss_byte_operation(ByteOperation::And, &db_first, &low_bits);
+        self.assert_expression_zero(db_first_low.expr());
+        let em_first_top =
+            self.pss_byte_operation(ByteOperation::And, &encoded_msg.get(0), &top_bit);
+        self.assert_expression_zero(em_first_top.expr());
+        for i in 1..ps_len {
+            self.assert_expression_zero(encoded_msg.get(i).expr() - mask_byte(i).expr());
+        }
+        let separator = self.pss_byte_operation(
+            ByteOperation::Xor,
+            &encoded_msg.get(ps_len),
+            &mask_byte(ps_len),
+        );
+        self.assert_expression_zero(separator.expr() - L::Field::ONE);
+        let salt = (ps_len + 1..db_len)
+            .map(|i| {
+                self.pss_byte_operation(ByteOperation::Xor, &encoded_msg.get(i), &mask_byte(i))
+            })
+            .collect::<Vec<_>>();
+
+        // The chunks of `M' = 0x00 * 8 || mHash || salt`, padded as a message of 72 bytes.
+        let m_prime_padding = SHA256::pad(&[0u8; 8 + 2 * HASH_LEN]);
+        let m_prime = self.alloc_array_public_unchecked::<U32Register>(32);
+        self.pss_set_constant_words(&m_prime.get_subarray(0..2), &m_prime_padding[0..2]);
+        for (word, hash_word) in m_prime.get_subarray(2..10).iter().zip(msg_hash.iter()) {
+            self.set_to_expression(&word, hash_word.expr());
+        }
+        for (word, salt_bytes) in m_prime
+            .get_subarray(10..18)
+            .iter()
+            .zip(salt.chunks_exact(4))
+        {
+            for (byte, salt_byte) in word.to_le_bytes().iter().zip(salt_bytes.iter().rev()) {
+                self.set_to_expression(&byte, salt_byte.expr());
+            }
+        }
+        self.pss_set_constant_words(&m_prime.get_subarray(18..32), &m_prime_padding[18..]);
+        chunks.push(m_prime.get_subarray(0..16));
+        chunks.push(m_prime.get_subarray(16..32));
+
+        // The message is followed by the blocks of the mask, each a message of its own, and by
+        // the two blocks of `M'`.
+        let num_msg_chunks = padded_chunks.len();
+        let mut end_bits_values = vec![L::Field::ZERO; num_msg_chunks - 1];
+        end_bits_values.extend(vec![L::Field::ONE; num_mask_blocks + 1]);
+        end_bits_values.extend([L::Field::ZERO, L::Field::ONE]);
+        let end_bits = self.constant_array::<BitRegister>(&end_bits_values);
+        let digest_indices = self.constant_array::<ElementRegister>(
+            &(num_msg_chunks - 1..num_msg_chunks + num_mask_blocks)
+                .chain([num_msg_chunks + num_mask_blocks + 1])
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let digests = [msg_hash]
+            .into_iter()
+            .chain(mask_digests.iter().copied())
+            .chain([hash_digest])
+            .collect::<Vec<_>>();
+        SHA256::sha_with_digests_in_rows(
+            self,
+            &chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            &digests,
+            num_rows,
+        );
+
+        // Check that `SHA256(M') = H`.
+        for (j, word) in hash_digest.iter().enumerate() {
+            for (k, byte) in word.to_le_bytes().iter().enumerate() {
+                self.assert_expression_zero(byte.expr() - h.get(4 * j + 3 - k).expr());
+            }
+        }
+
+        EMSAPSSGadget {
+            encoded_msg: *encoded_msg,
+            padded_chunks: padded_chunks.to_vec(),
+            msg_hash,
+            mask_digests,
+            hash_digest,
+        }
+    }
+
+    /// Sets `word` to the big-endian word of the four bytes of `bytes`.
+    fn pss_set_be_bytes(&mut self, word: &U32Register, bytes: &ArrayRegister<ByteRegister>) {
+        for (byte, value) in word.to_le_bytes().iter().zip((0..4).rev()) {
+            self.set_to_expression(&byte, bytes.get(value).expr());
+        }
+    }
+
+    /// Sets `words` to the constant words `values`.
+    fn pss_set_constant_words(&mut self, words: &ArrayRegister<U32Register>, values: &[u32]) {
+        let constants = self.constant_array::<U32Register>(
+            &values
+                .iter()
+                .map(|value| u32_to_le_field_bytes(*value))
+                .collect::<Vec<_>>(),
+        );
+        for (word, constant) in words.iter().zip_eq(constants.iter()) {
+            self.set_to_expression(&word, constant.expr());
+        }
+    }
+
+    /// Computes `operation(a, b)` on public bytes.
+    fn pss_byte_operation(
+        &mut self,
+        operation: fn(ByteRegister, ByteRegister, ByteRegister) -> ByteOperation<ByteRegister>,
+        a: &ByteRegister,
+        b: &ByteRegister,
+    ) -> ByteRegister {
+        let result = self.alloc_public_unchecked::<ByteRegister>();
+        let op = operation(*a, *b, result);
+        self.api
+            .set_public_inputs_byte_operation(&op, &mut self.operations);
+        result
+    }
+}
+
+/// The mask generation function MGF1 with SHA-256, which expands `seed` into `len` bytes.
+pub fn mgf1_sha256(seed: &[u8], len: usize) -> Vec<u8> {
+    (0..len.div_ceil(HASH_LEN) as u32)
+        .flat_map(|counter| SHA256::digest_bytes(&[seed, &counter.to_be_bytes()].concat()))
+        .take(len)
+        .collect()
+}
+
+/// Checks that `encoded_msg` is an EMSA-PSS encoding of `msg_hash` with a salt of 32 bytes,
+/// outside of the AIR.
+pub fn emsa_pss_sha256_verify(msg_hash: &[u8; 32], encoded_msg: &[u8]) -> bool {
+    let em_len = encoded_msg.len();
+    if em_len < 2 * HASH_LEN + 2 || encoded_msg[em_len - 1] != TRAILER || encoded_msg[0] & 0x80 != 0
+    {
+        return false;
+    }
+    let db = data_block(encoded_msg);
+    let ps_len = em_len - 2 * HASH_LEN - 2;
+    if db[..ps_len].iter().any(|byte| *byte != 0) || db[ps_len] != 0x01 {
+        return false;
+    }
+    let h = &encoded_msg[em_len - HASH_LEN - 1..em_len - 1];
+    SHA256::digest_bytes(&m_prime(msg_hash, &db[ps_len + 1..])) == h
+}
+
+/// The data block `DB` of `encoded_msg`, unmasked and with the top bit cleared.
+fn data_block(encoded_msg: &[u8]) -> Vec<u8> {
+    let db_len = encoded_msg.len() - HASH_LEN - 1;
+    let (masked_db, h) = encoded_msg.split_at(db_len);
+    let mut db = masked_db
+        .iter()
+        .zip(mgf1_sha256(&h[..HASH_LEN], db_len))
+        .map(|(byte, mask)| byte ^ mask)
+        .collect::<Vec<_>>();
+    db[0] &= 0x7f;
+    db
+}
+
+/// The message `M' = 0x00 * 8 || mHash || salt`, whose digest is `H`.
+fn m_prime(msg_hash: &[u8; 32], salt: &[u8]) -> Vec<u8> {
+    [&[0u8; 8], msg_hash.as_slice(), salt].concat()
+}
+
+impl EMSAPSSGadget {
+    /// Writes the encoded message, the chunks of the message and the digests of the SHA-256
+    /// blocks.
+    pub fn write<F: Field>(
+        &self,
+        encoded_msg: &[u8],
+        msg: &[u8],
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        writer.write_array(
+            &self.encoded_msg,
+            encoded_msg.iter().map(|byte| F::from_canonical_u8(*byte)),
+        );
+        let padded_msg = SHA256::pad(msg);
+        for (chunk, values) in self
+            .padded_chunks
+            .iter()
+            .zip_eq(padded_msg.chunks_exact(16))
+        {
+            writer.write_array(
+                chunk,
+                values.iter().map(|word| u32_to_le_field_bytes(*word)),
+            );
+        }
+        let msg_hash: ArrayRegister<U32Register> = self.msg_hash.into();
+        writer.write_array(&msg_hash, SHA256::digest(msg).map(u32_to_le_field_bytes));
+
+        let em_len = encoded_msg.len();
+        let h = &encoded_msg[em_len - HASH_LEN - 1..em_len - 1];
+        for (counter, digest) in self.mask_digests.iter().enumerate() {
+            let block = [h, &(counter as u32).to_be_bytes()].concat();
+            let digest: ArrayRegister<U32Register> = (*digest).into();
+            writer.write_array(&digest, SHA256::digest(&block).map(u32_to_le_field_bytes));
+        }
+        let db = data_block(encoded_msg);
+        let salt = &db[db.len() - HASH_LEN..];
+        let hash_digest: ArrayRegister<U32Register> = self.hash_digest.into();
+        writer.write_array(
+            &hash_digest,
+            SHA256::digest(&m_prime(&SHA256::digest_bytes(msg), salt)).map(u32_to_le_field_bytes),
+        );
+    }
+}
+
+impl<P: FieldParameters> RSAPSSGadget<P> {
+    /// Writes the public key, the signature, the encoded message and the values of the encoding
+    /// check.
+    pub fn write<F: Field>(
+        &self,
+        witness: &RSAPSSWitness<P>,
+        writer: &mut impl AirWriter<Field = F>,
+    ) {
+        for (register, value) in [self.modulus, self.signature]
+            .iter()
+            .zip_eq([&witness.modulus, &witness.signature])
+        {
+            writer.write(register, &to_u16_le_limbs_polynomial::<F, P>(value));
+        }
+        self.encoding
+            .write(&witness.encoded_msg(), &witness.msg, writer);
+    }
+}
+
+impl<P: FieldParameters> RSAPSSWitness<P> {
+    /// Computes the witness of the signature `signature` of `msg` under the public key
+    /// `(modulus, 65537)`, where the message is hashed with SHA-256.
+    pub fn new(modulus: BigUint, msg: &[u8], signature: BigUint) -> Self {
+        Self {
+            modulus,
+            signature,
+            msg: msg.to_vec(),
+            msg_hash: SHA256::digest_bytes(msg),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The encoded message `signature^65537 mod n` on `2 * P::NB_LIMBS` bytes.
+    pub fn encoded_msg(&self) -> Vec<u8> {
+        let value = self
+            .signature
+            .modpow(&BigUint::from(PUBLIC_EXPONENT), &self.modulus)
+            .to_bytes_be();
+        let mut encoded_msg = vec![0u8; 2 * P::NB_LIMBS - value.len()];
+        encoded_msg.extend(value);
+        encoded_msg
+    }
+
+    /// Checks the signature outside of the AIR.
+    pub fn verify(&self) -> bool {
+        self.signature < self.modulus && emsa_pss_sha256_verify(&self.msg_hash, &self.encoded_msg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::One;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::super::tests::{hex_int, RSA2048Test, MSG, RSA2048_MODULUS};
+    use super::super::RSA2048;
+    use super::*;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct EMSAPSSTest;
+
+    impl AirParameters for EMSAPSSTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1200;
+        const EXTENDED_COLUMNS: usize = 700;
+    }
+
+    /// The signature of `MSG` under the test key with `openssl dgst -sha256 -sign`, with the
+    /// options `rsa_padding_mode:pss` and `rsa_pss_saltlen:32`.
+    const PSS_SIGNATURE: &str = "a049f35c89ff44d5ad75769f5630df5a13cba4ee55efd9425eb9a542b642daaadef8444256bff1871a69df807eb1322e3cc5286ba74816336e0b3a04e5b71b3117c82281143fa9c0bd8448984e0c9461d42b2ccbab442c88584e8c07f3a577a088ccb5f238f4d58415f47651949cb95acfa7d1e53cae0c5ea918fba32c2d4acfae679d7757a5508793b5c59afdeeff92df63f75d38fe2d056b6d64d600262b59c5156c998fb5721d02a509bd9296da72eceb24700bcd602ab7bc8120b5474fb1297575dddefdeb39d193c14f6c00a7ed04e0d99703e1d31be9e8fb1ae1dc7f71824a122fc34149b7c9e80872a6eb02b7984749e12483541a962bd823a41edaae";
+
+    fn witness(msg: Option<&[u8]>) -> RSAPSSWitness<RSA2048> {
+        let msg = msg.unwrap_or(MSG.as_bytes());
+        RSAPSSWitness::new(hex_int(RSA2048_MODULUS), msg, hex_int(PSS_SIGNATURE))
+    }
+
+    #[test]
+    fn test_rsa_pss_verify_pure() {
+        assert!(witness(None).verify());
+        assert!(!witness(Some(b"tampered".as_slice())).verify());
+
+        let mut tampered = witness(None);
+        tampered.signature += BigUint::one();
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn test_rsa2048_pss_verify() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type L = RSA2048Test;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_rsa2048_pss_verify", log::Level::Debug);
+
+        let witness = witness(None);
+        let num_chunks = SHA256::pad(&witness.msg).len() / 16;
+        let num_blocks = num_chunks + (2 * RSA2048::NB_LIMBS - HASH_LEN - 1).div_ceil(HASH_LEN) + 2;
+        let num_rows = 1 << log2_ceil(64 * num_blocks);
+
+        let mut builder = BytesBuilder::<L>::new();
+        let modulus = builder.alloc_public::<FieldRegister<RSA2048>>();
+        let signature = builder.alloc_public::<FieldRegister<RSA2048>>();
+        let padded_chunks = (0..num_chunks)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let gadget = builder.rsa_pss_verify(&modulus, &signature, &padded_chunks, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(&witness, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_emsa_pss_sha256_verify() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_emsa_pss_sha256_verify", log::Level::Debug);
+
+        let witness = witness(None);
+        let encoded_msg_value = witness.encoded_msg();
+        let num_chunks = SHA256::pad(&witness.msg).len() / 16;
+        let num_blocks =
+            num_chunks + (encoded_msg_value.len() - HASH_LEN - 1).div_ceil(HASH_LEN) + 2;
+        let num_rows = 1 << log2_ceil(64 * num_blocks);
+
+        let mut builder = BytesBuilder::<EMSAPSSTest>::new();
+        let encoded_msg = builder.alloc_array_public::<ByteRegister>(encoded_msg_value.len());
+        let padded_chunks = (0..num_chunks)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let gadget = builder.emsa_pss_sha256_verify(&encoded_msg, &padded_chunks, num_rows);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        gadget.write(&encoded_msg_value, &witness.msg, &mut writer);
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
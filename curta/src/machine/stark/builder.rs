@@ -1,4 +1,5 @@
 use super::Stark;
+use crate::air::RAirData;
 use crate::chip::builder::AirBuilder;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::AirParameters;
@@ -40,8 +41,20 @@ impl<L: AirParameters> StarkBuilder<L> {
     ) -> Stark<L, C, D> {
         let api = self.api;
 
-        let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
         let (air, air_data) = api.build();
+        let config = StarkyConfig::<C, D>::standard_fast_config_with_degree(
+            num_rows,
+            air.constraint_degree(),
+        );
+        if let Some(segment) = air_data.segments.last() {
+            assert!(
+                segment.rows.end <= num_rows,
+                "Segment {} ends at row {}, but the trace has {} rows",
+                segment.name,
+                segment.rows.end,
+                num_rows
+            );
+        }
         let stark = Starky::new(air);
 
         Stark {
@@ -120,9 +120,9 @@ where
         public_values: &[L::Field],
         challenger: &mut Challenger<L::Field, C::Hasher>,
         timing: &mut TimingTree,
-    ) -> AirCommitment<L::Field, C, D> {
+    ) -> Result<AirCommitment<L::Field, C, D>> {
         // Absorve public values into the challenger.
-        challenger.observe_elements(public_values);
+        let public_challenges = self.stark.observe_public_inputs(challenger, public_values);
 
         // Generate execution trace.
         let writer = self.generate_execution_trace(execution_trace, public_values);
@@ -149,7 +149,10 @@ where
         challenger.observe_cap(&execution_commitment.merkle_tree.cap);
 
         // Get random AIR challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
         // Save challenges to the writer.
         writer
             .challenges
@@ -160,6 +163,10 @@ where
         // Generate extended trace.
         self.generate_extended_trace(&writer);
 
+        // Check the constraints on the full trace to report the failing instruction, if any.
+        #[cfg(feature = "debug-constraints")]
+        self.stark.air.check_constraints(&writer)?;
+
         let InnerWriterData {
             trace,
             public,
@@ -189,12 +196,12 @@ where
         challenger.observe_cap(&extended_commitment.merkle_tree.cap);
 
         // Return the air commitment.
-        AirCommitment {
+        Ok(AirCommitment {
             trace_commitments: vec![execution_commitment, extended_commitment],
             public_inputs: public,
             global_values: global,
             challenges,
-        }
+        })
     }
 
     pub fn prove(
@@ -210,7 +217,7 @@ where
         let air_commitment = timed!(
             timing,
             "Generate stark trace",
-            self.generate_trace(execution_trace, public_values, &mut challenger, timing)
+            self.generate_trace(execution_trace, public_values, &mut challenger, timing)?
         );
 
         // Generate individual stark proofs.
@@ -239,13 +246,18 @@ where
         let mut challenger = Challenger::<L::Field, C::Hasher>::new();
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges = self
+            .stark
+            .observe_public_inputs(&mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.air_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges =
+            challenger.get_n_challenges(self.stark.air.num_challenges - data.num_challenges());
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
@@ -312,13 +324,20 @@ where
         let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
 
         // Observe public values.
-        challenger.observe_elements(public_values);
+        let public_challenges =
+            self.stark
+                .observe_public_inputs_target(builder, &mut challenger, public_values);
 
         // Observe execution trace commitments.
         challenger.observe_cap(&proof.air_proof.trace_caps[0]);
 
         // Get challenges.
-        let challenges = challenger.get_n_challenges(builder, self.stark.air.num_challenges);
+        let data = self.stark.air.public_challenge_data;
+        let round_challenges = challenger.get_n_challenges(
+            builder,
+            self.stark.air.num_challenges - data.num_challenges(),
+        );
+        let challenges = data.insert(&public_challenges, &round_challenges);
 
         // Observe global values.
         challenger.observe_elements(&proof.global_values);
@@ -9,6 +9,7 @@ use serde::Serialize;
 use self::parser::global::{GlobalRecursiveStarkParser, GlobalStarkParser};
 use self::parser::{RecursiveStarkParser, StarkParser};
 use crate::air::RAir;
+use crate::chip::debug::parser::ConstraintCheckParser;
 
 pub mod cubic;
 pub mod field;
@@ -17,10 +18,14 @@ pub mod stark;
 pub mod trace;
 
 /// an air that can generate constraints for the Starky proving system.
+///
+/// The air can also be evaluated on a concrete trace with `ConstraintCheckParser`, which is used
+/// to attribute constraint failures when debugging.
 pub trait StarkyAir<F: RichField + Extendable<D>, const D: usize>:
     for<'a> RAir<StarkParser<'a, F, F, <F as Packable>::Packing, D, 1>>
     + for<'a> RAir<StarkParser<'a, F, F::Extension, F::Extension, D, D>>
     + for<'a> RAir<GlobalStarkParser<'a, F, F, F, D, 1>>
+    + for<'a> RAir<ConstraintCheckParser<'a, F>>
     + 'static
     + Debug
     + Send
@@ -42,6 +47,7 @@ impl<F: RichField + Extendable<D>, const D: usize, T> StarkyAir<F, D> for T wher
     T: for<'a> RAir<StarkParser<'a, F, F, <F as Packable>::Packing, D, 1>>
         + for<'a> RAir<StarkParser<'a, F, F::Extension, F::Extension, D, D>>
         + for<'a> RAir<GlobalStarkParser<'a, F, F, F, D, 1>>
+        + for<'a> RAir<ConstraintCheckParser<'a, F>>
         + 'static
         + Debug
         + Send
@@ -7,8 +7,8 @@ use plonky2::fri::reduction_strategies::FriReductionStrategy;
 use plonky2::fri::{FriConfig, FriParams};
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, PoseidonGoldilocksConfig};
-use plonky2::util::log2_strict;
 use plonky2::util::timing::TimingTree;
+use plonky2::util::{log2_ceil, log2_strict};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
@@ -72,6 +72,15 @@ impl<C: CurtaConfig<D>, const D: usize> StarkyConfig<C, D> {
         }
     }
 
+    /// The standard fast configuration, with a rate low enough to support constraints of degree
+    /// `constraint_degree`.
+    pub fn standard_fast_config_with_degree(num_rows: usize, constraint_degree: usize) -> Self {
+        let mut config = Self::standard_fast_config(num_rows);
+        let quotient_degree_bits = log2_ceil(1.max(constraint_degree - 1));
+        config.fri_config.rate_bits = config.fri_config.rate_bits.max(quotient_degree_bits);
+        config
+    }
+
     pub fn fri_params(&self) -> FriParams {
         self.fri_config.fri_params(self.degree_bits, false)
     }
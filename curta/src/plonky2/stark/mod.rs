@@ -8,8 +8,11 @@ use plonky2::fri::structure::{
     FriPolynomialInfo,
 };
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
 use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{AlgebraicHasher, Hasher};
 use serde::{Deserialize, Serialize};
 
 use self::config::{CurtaConfig, StarkyConfig};
@@ -38,6 +41,59 @@ impl<A> Starky<A> {
         &self.air
     }
 
+    /// Observes the public inputs, drawing the public challenges of the air right after the
+    /// public inputs before them.
+    pub fn observe_public_inputs<F: RichField, H: Hasher<F>>(
+        &self,
+        challenger: &mut Challenger<F, H>,
+        public_inputs: &[F],
+    ) -> Vec<F>
+    where
+        A: RAirData,
+    {
+        let data = self.air().public_challenge_data();
+        let (before, after) = public_inputs.split_at(data.num_public_inputs);
+        challenger.observe_elements(before);
+        let public_challenges = challenger.get_n_challenges(data.num_challenges());
+        challenger.observe_elements(after);
+        public_challenges
+    }
+
+    /// Observes the public input targets in a recursive challenger, as `observe_public_inputs`.
+    pub fn observe_public_inputs_target<
+        F: RichField + Extendable<D>,
+        H: AlgebraicHasher<F>,
+        const D: usize,
+    >(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        challenger: &mut RecursiveChallenger<F, H, D>,
+        public_inputs: &[Target],
+    ) -> Vec<Target>
+    where
+        A: RAirData,
+    {
+        let data = self.air().public_challenge_data();
+        let (before, after) = public_inputs.split_at(data.num_public_inputs);
+        challenger.observe_elements(before);
+        let public_challenges = challenger.get_n_challenges(builder, data.num_challenges());
+        challenger.observe_elements(after);
+        public_challenges
+    }
+
+    /// The public challenges drawn by the verifier, observing the public inputs first.
+    ///
+    /// Only the public inputs before the challenges are read, so the prover can get the
+    /// challenges once these are written.
+    pub fn public_challenges<F: RichField, H: Hasher<F>>(&self, public_inputs: &[F]) -> Vec<F>
+    where
+        A: RAirData,
+    {
+        let num_public_inputs = self.air().public_challenge_data().num_public_inputs;
+        let mut challenger = Challenger::<F, H>::new();
+        self.observe_public_inputs(&mut challenger, &public_inputs[..num_public_inputs])
+    }
+
     pub fn num_quotient_polys<
         F: RichField + Extendable<D>,
         C: CurtaConfig<D, F = F>,
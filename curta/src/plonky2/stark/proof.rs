@@ -127,7 +127,7 @@ impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> Sta
 
         let mut challenger = Challenger::<F, C::Hasher>::new();
         // Observe public inputs
-        challenger.observe_elements(public_inputs);
+        let public_challenges = stark.observe_public_inputs(&mut challenger, public_inputs);
 
         let mut challenges = vec![];
         for (round, cap) in stark.air().round_data().iter().zip_eq(trace_caps.iter()) {
@@ -137,6 +137,10 @@ impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> Sta
             let round_challenges = challenger.get_n_challenges(round.num_challenges);
             challenges.extend(round_challenges);
         }
+        let challenges = stark
+            .air()
+            .public_challenge_data()
+            .insert(&public_challenges, &challenges);
 
         self.get_iop_challenges(config, degree_bits, challenges, &mut challenger)
     }
@@ -252,7 +256,8 @@ impl<const D: usize> StarkProofTarget<D> {
         let mut challenger = RecursiveChallenger::<F, C::InnerHasher, D>::new(builder);
 
         // Observe public inputs
-        challenger.observe_elements(public_inputs);
+        let public_challenges =
+            stark.observe_public_inputs_target(builder, &mut challenger, public_inputs);
 
         let mut challenges = vec![];
         for (round, cap) in stark.air().round_data().iter().zip(trace_caps.iter()) {
@@ -262,6 +267,10 @@ impl<const D: usize> StarkProofTarget<D> {
             let round_challenges = challenger.get_n_challenges(builder, round.num_challenges);
             challenges.extend(round_challenges);
         }
+        let challenges = stark
+            .air()
+            .public_challenge_data()
+            .insert(&public_challenges, &challenges);
 
         self.get_iop_challenges_target(builder, config, challenges, &mut challenger)
     }
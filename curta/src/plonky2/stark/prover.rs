@@ -64,7 +64,7 @@ where
         let mut global_values = vec![F::ZERO; stark.air().num_global_values()];
 
         // Oberve public inputs
-        challenger.observe_elements(public_inputs);
+        let public_challenges = stark.observe_public_inputs(challenger, public_inputs);
 
         let rate_bits = config.fri_config.rate_bits;
         let cap_height = config.fri_config.cap_height;
@@ -99,6 +99,12 @@ where
             // Get the challenges for next round
             let round_challenges = challenger.get_n_challenges(round.num_challenges);
             challenges.extend(round_challenges);
+            if r == 0 {
+                challenges = stark
+                    .air()
+                    .public_challenge_data()
+                    .insert(&public_challenges, &challenges);
+            }
         }
 
         Ok(AirCommitment {
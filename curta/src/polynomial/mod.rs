@@ -181,22 +181,26 @@ impl<T: Sub<Output = T> + Neg<Output = T> + Copy + Default> Sub for &Polynomial<
     }
 }
 
-impl<T: Mul<Output = T> + Add<Output = T> + Copy + Default> Mul for Polynomial<T> {
+impl<T: Mul<Output = T> + Add<Output = T> + Sub<Output = T> + Copy + Default> Mul
+    for Polynomial<T>
+{
     type Output = Self;
 
     fn mul(self, other: Self) -> Self {
-        Self::from_coefficients(PolynomialOps::mul(
+        Self::from_coefficients(PolynomialOps::karatsuba_mul(
             self.coefficients(),
             other.coefficients(),
         ))
     }
 }
 
-impl<T: Mul<Output = T> + Add<Output = T> + Copy + Default> Mul for &Polynomial<T> {
+impl<T: Mul<Output = T> + Add<Output = T> + Sub<Output = T> + Copy + Default> Mul
+    for &Polynomial<T>
+{
     type Output = Polynomial<T>;
 
     fn mul(self, other: Self) -> Polynomial<T> {
-        Polynomial::from_coefficients(PolynomialOps::mul(
+        Polynomial::from_coefficients(PolynomialOps::karatsuba_mul(
             self.coefficients(),
             other.coefficients(),
         ))
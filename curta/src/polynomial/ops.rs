@@ -6,6 +6,9 @@ use itertools::Itertools;
 
 use super::{get_powers, One};
 
+/// The number of coefficients below which `karatsuba_mul` falls back to the schoolbook `mul`.
+pub const KARATSUBA_THRESHOLD: usize = 8;
+
 /// A struct which implements helper methods for polynomial operations, such as addition and
 /// multiplication.
 #[derive(Debug, Clone, Copy)]
@@ -82,6 +85,48 @@ impl PolynomialOps {
         result
     }
 
+    /// Polynomial multiplication by the Karatsuba method, which computes the product of two
+    /// polynomials of `n` coefficients with `O(n^1.58)` multiplications.
+    ///
+    /// The halves of `a = a0 + x^m * a1` and `b = b0 + x^m * b1` are multiplied recursively, where
+    /// the middle term is `(a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1`. Operands of different
+    /// lengths or below `KARATSUBA_THRESHOLD` coefficients use the schoolbook `mul`.
+    pub fn karatsuba_mul<T>(a: &[T], b: &[T]) -> Vec<T>
+    where
+        T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy + Default,
+    {
+        let n = a.len();
+        if n != b.len() || n < KARATSUBA_THRESHOLD {
+            return Self::mul(a, b);
+        }
+
+        let m = n / 2;
+        let (a0, a1) = a.split_at(m);
+        let (b0, b1) = b.split_at(m);
+        let z0 = Self::karatsuba_mul(a0, b0);
+        let z2 = Self::karatsuba_mul(a1, b1);
+        let mut z1 = Self::karatsuba_mul(&Self::add(a0, a1), &Self::add(b0, b1));
+        for (z1, (z0, z2)) in z1.iter_mut().zip(z0.iter().zip_longest(z2.iter())) {
+            match (z0, z2) {
+                itertools::EitherOrBoth::Both(z0, z2) => *z1 = *z1 - *z0 - *z2,
+                itertools::EitherOrBoth::Left(z0) => *z1 = *z1 - *z0,
+                itertools::EitherOrBoth::Right(z2) => *z1 = *z1 - *z2,
+            }
+        }
+
+        let mut result = vec![T::default(); 2 * n - 1];
+        for (i, z) in z0.into_iter().enumerate() {
+            result[i] = result[i] + z;
+        }
+        for (i, z) in z1.into_iter().enumerate() {
+            result[i + m] = result[i + m] + z;
+        }
+        for (i, z) in z2.into_iter().enumerate() {
+            result[i + 2 * m] = result[i + 2 * m] + z;
+        }
+        result
+    }
+
     /// Scalar polynomial addition.
     pub fn scalar_poly_add<T, S>(a: &[T], b: &[S]) -> Vec<T>
     where
@@ -174,3 +219,27 @@ impl PolynomialOps {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    #[test]
+    fn test_karatsuba_mul() {
+        let mut rng = thread_rng();
+        for (len_a, len_b) in (1..40).map(|n| (n, n)).chain([(16, 1), (7, 24), (24, 25)]) {
+            let a = (0..len_a)
+                .map(|_| rng.gen_range(-1 << 16..1 << 16))
+                .collect::<Vec<i64>>();
+            let b = (0..len_b)
+                .map(|_| rng.gen_range(-1 << 16..1 << 16))
+                .collect::<Vec<i64>>();
+            assert_eq!(
+                PolynomialOps::karatsuba_mul(&a, &b),
+                PolynomialOps::mul(&a, &b)
+            );
+        }
+    }
+}